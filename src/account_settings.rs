@@ -0,0 +1,201 @@
+use crate::checks::{Check, ItemOptions};
+use crate::quota_events;
+use crate::schedules;
+use crate::validation_history;
+use chrono::Utc;
+use mongodb::{Client, Collection, bson::doc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// An account's default validation profile, applied to requests that leave the
+/// corresponding option out
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AccountSettings {
+    /// Account the settings belong to, lowercased
+    pub tenant_id: String,
+    /// Role-based detection when a request doesn't pass `check_role_based`
+    pub check_role_based: Option<bool>,
+    /// Stages to run when a request doesn't choose its own; all of them when unset
+    pub checks: Option<Vec<Check>>,
+    /// Webhook of schedules created without one
+    pub webhook_url: Option<String>,
+    /// Days the account's validation history is kept, at most the server's retention
+    pub result_retention_days: Option<u64>,
+    /// Whether bulk validation treats a sub-addressed address (`user+news@`) as a
+    /// repeat of its base address; off when unset
+    #[serde(default)]
+    pub dedupe_tagged_addresses: Option<bool>,
+    /// Share of the monthly quota, in percent, at which `quota.warning` is posted to
+    /// `webhook_url`; 80 when unset
+    #[serde(default)]
+    pub quota_warning_percent: Option<u64>,
+    /// SNS topics whose signed notifications to `/api/v1/feedback/sns` are recorded as
+    /// the account's bounce feedback
+    #[serde(default)]
+    pub sns_topic_arns: Vec<String>,
+    /// Unix timestamp of the last change
+    pub updated_at: i64,
+}
+
+impl AccountSettings {
+    /// Checks the webhook URL and retention period; unset fields keep the server defaults
+    pub fn new(
+        tenant_id: &str,
+        check_role_based: Option<bool>,
+        checks: Option<Vec<Check>>,
+        webhook_url: Option<&str>,
+        result_retention_days: Option<u64>,
+    ) -> Result<Self, String> {
+        let webhook_url = webhook_url
+            .filter(|url| !url.trim().is_empty())
+            .map(schedules::validate_webhook_url)
+            .transpose()?;
+        let max_days = validation_history::retention().as_secs() / (24 * 3600);
+        if result_retention_days.is_some_and(|days| days == 0 || days > max_days) {
+            return Err(format!(
+                "result_retention_days must be between 1 and {}",
+                max_days
+            ));
+        }
+
+        Ok(Self {
+            tenant_id: tenant_id.to_lowercase(),
+            check_role_based,
+            checks,
+            webhook_url,
+            result_retention_days,
+            dedupe_tagged_addresses: None,
+            quota_warning_percent: None,
+            sns_topic_arns: Vec::new(),
+            updated_at: Utc::now().timestamp(),
+        })
+    }
+
+    /// Settings of an account that saved none
+    pub fn unset(tenant_id: &str) -> Self {
+        Self {
+            tenant_id: tenant_id.to_lowercase(),
+            check_role_based: None,
+            checks: None,
+            webhook_url: None,
+            result_retention_days: None,
+            dedupe_tagged_addresses: None,
+            quota_warning_percent: None,
+            sns_topic_arns: Vec::new(),
+            updated_at: 0,
+        }
+    }
+
+    /// Whether the account's bulk requests merge sub-addresses with their base address
+    pub fn dedupes_tagged(&self) -> bool {
+        self.dedupe_tagged_addresses.unwrap_or(false)
+    }
+
+    /// Share of the quota at which the account is warned, in percent
+    pub fn warning_percent(&self) -> u64 {
+        self.quota_warning_percent
+            .unwrap_or(quota_events::DEFAULT_WARNING_PERCENT)
+    }
+
+    /// The validation options requests of the account fall back to
+    pub fn options(&self) -> ItemOptions {
+        ItemOptions {
+            check_role_based: self.check_role_based,
+            checks: self.checks.clone(),
+        }
+    }
+}
+
+/// Reads `DB_NAME_PRODUCTION` (default `email_sanitizer`) and `DB_ACCOUNT_SETTINGS_COLLECTION`
+/// (default `account_settings`)
+pub fn settings_collection(mongo_client: &Client) -> Collection<AccountSettings> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let collection_name = std::env::var("DB_ACCOUNT_SETTINGS_COLLECTION")
+        .unwrap_or_else(|_| "account_settings".to_string());
+    mongo_client.database(&db_name).collection(&collection_name)
+}
+
+pub async fn load_settings(
+    mongo_client: &Client,
+    tenant_id: &str,
+) -> Result<Option<AccountSettings>, String> {
+    settings_collection(mongo_client)
+        .find_one(doc! { "tenant_id": tenant_id.to_lowercase() })
+        .await
+        .map_err(|_| "Database error".to_string())
+}
+
+/// Account that registered an SNS topic for its bounce feedback
+pub async fn tenant_for_topic(
+    mongo_client: &Client,
+    topic_arn: &str,
+) -> Result<Option<String>, String> {
+    Ok(settings_collection(mongo_client)
+        .find_one(doc! { "sns_topic_arns": topic_arn })
+        .await
+        .map_err(|_| "Database error".to_string())?
+        .map(|settings| settings.tenant_id))
+}
+
+/// Options the account's requests fall back to; empty when it saved no settings
+pub async fn default_options(
+    mongo_client: &Client,
+    tenant_id: &str,
+) -> Result<ItemOptions, String> {
+    Ok(load_settings(mongo_client, tenant_id)
+        .await?
+        .map(|settings| settings.options())
+        .unwrap_or_default())
+}
+
+/// Replaces the account's settings
+pub async fn save_settings(
+    mongo_client: &Client,
+    settings: &AccountSettings,
+) -> Result<(), String> {
+    settings_collection(mongo_client)
+        .replace_one(doc! { "tenant_id": &settings.tenant_id }, settings)
+        .upsert(true)
+        .await
+        .map_err(|_| "Database error".to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_settings() {
+        let settings = AccountSettings::new(
+            "Owner@Example.com",
+            Some(true),
+            Some(vec![Check::Dns]),
+            Some(" https://hooks.example.com/done "),
+            Some(30),
+        )
+        .unwrap();
+        assert_eq!(settings.tenant_id, "owner@example.com");
+        assert_eq!(
+            settings.webhook_url.as_deref(),
+            Some("https://hooks.example.com/done")
+        );
+        assert_eq!(
+            settings.options(),
+            ItemOptions {
+                check_role_based: Some(true),
+                checks: Some(vec![Check::Dns]),
+            }
+        );
+
+        assert!(AccountSettings::new("a@example.com", None, None, Some("ftp://x"), None).is_err());
+        assert!(AccountSettings::new("a@example.com", None, None, None, Some(0)).is_err());
+        assert!(AccountSettings::new("a@example.com", None, None, None, Some(100_000)).is_err());
+        assert!(
+            AccountSettings::unset("a@example.com")
+                .options()
+                .is_default()
+        );
+    }
+}
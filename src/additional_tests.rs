@@ -1,9 +1,13 @@
 #[cfg(test)]
 mod additional_coverage_tests {
     use crate::auth::*;
+    use crate::handlers::validation::dnsmx::DnsOutcome;
     use crate::job_queue::JobQueue;
     use crate::routes::email::RedisCache;
+    use crate::stores::MemoryStore;
+    use crate::validation_service::ValidationBackends;
     use crate::worker::*;
+    use std::sync::Arc;
 
     #[test]
     fn test_user_struct_creation() {
@@ -11,6 +15,8 @@ mod additional_coverage_tests {
             email: "test@example.com".to_string(),
             password_hash: "hashed_password".to_string(),
             active: true,
+            key_salt: String::new(),
+            ..User::default()
         };
 
         assert_eq!(user.email, "test@example.com");
@@ -23,6 +29,9 @@ mod additional_coverage_tests {
         let api_key = ApiKey {
             key: "test-key".to_string(),
             active: true,
+            owner: None,
+            plan: PlanTier::Free,
+            ..ApiKey::default()
         };
 
         assert_eq!(api_key.key, "test-key");
@@ -47,16 +56,16 @@ mod additional_coverage_tests {
         // Either succeeds or fails gracefully
         assert!(cache_result.is_ok() || cache_result.is_err());
 
-        // Test dummy cache creation
-        let dummy_cache = RedisCache::test_dummy();
-        assert_eq!(dummy_cache.ttl, 3600);
+        // Test in-memory cache creation
+        let memory_cache = RedisCache::from_store(Arc::new(MemoryStore::default()), 3600);
+        assert_eq!(memory_cache.ttl, 3600);
     }
 
     #[tokio::test]
     async fn test_validation_worker_creation() {
-        let redis_cache = RedisCache::test_dummy();
+        let backends = ValidationBackends::from_store(Arc::new(MemoryStore::default()));
         if let Ok(job_queue) = JobQueue::new("redis://127.0.0.1:6379") {
-            let _worker = ValidationWorker::new(job_queue, redis_cache);
+            let _worker = ValidationWorker::new(job_queue, backends);
             // Test that worker is created successfully
             // We can't access private fields, but creation should succeed
             assert!(true);
@@ -94,6 +103,8 @@ mod additional_coverage_tests {
             email: "test@example.com".to_string(),
             password_hash: "hash".to_string(),
             active: true,
+            key_salt: String::new(),
+            ..User::default()
         };
 
         // Test that structs can be serialized
@@ -103,6 +114,9 @@ mod additional_coverage_tests {
         let api_key = ApiKey {
             key: "test-key".to_string(),
             active: true,
+            owner: None,
+            plan: PlanTier::Free,
+            ..ApiKey::default()
         };
 
         let json_result = serde_json::to_string(&api_key);
@@ -127,6 +141,8 @@ mod additional_coverage_tests {
             email: "".to_string(),
             password_hash: "".to_string(),
             active: false,
+            key_salt: String::new(),
+            ..User::default()
         };
 
         assert_eq!(user.email, "");
@@ -138,6 +154,8 @@ mod additional_coverage_tests {
             email: "tëst@exämple.com".to_string(),
             password_hash: "üñíçødé".to_string(),
             active: true,
+            key_salt: String::new(),
+            ..User::default()
         };
 
         assert_eq!(user_unicode.email, "tëst@exämple.com");
@@ -146,14 +164,16 @@ mod additional_coverage_tests {
 
     #[tokio::test]
     async fn test_redis_cache_methods() {
-        let cache = RedisCache::test_dummy();
+        let cache = RedisCache::from_store(Arc::new(MemoryStore::default()), 3600);
 
         // Test get method (should not panic)
         let result = cache.get_dns_validation("example.com").await;
         assert!(result.is_ok());
 
         // Test set method (should not panic)
-        let result = cache.set_dns_validation("example.com", true).await;
+        let result = cache
+            .set_dns_validation("example.com", DnsOutcome::Valid)
+            .await;
         assert!(result.is_ok());
     }
 
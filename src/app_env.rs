@@ -0,0 +1,56 @@
+/// Deployment the service runs in (`APP_ENV`, default `development`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppEnv {
+    Development,
+    Staging,
+    Production,
+}
+
+impl AppEnv {
+    /// Parses `development`, `staging` or `production` (also `dev`, `stage`, `prod`)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "development" | "dev" => Some(AppEnv::Development),
+            "staging" | "stage" => Some(AppEnv::Staging),
+            "production" | "prod" => Some(AppEnv::Production),
+            _ => None,
+        }
+    }
+
+    /// Read from `APP_ENV`; unset or unrecognized values mean development
+    pub fn from_env() -> Self {
+        std::env::var("APP_ENV")
+            .ok()
+            .and_then(|v| Self::parse(&v))
+            .unwrap_or(AppEnv::Development)
+    }
+}
+
+/// Whether the GraphQL playground and schema introspection are served
+///
+/// Both are off in production. `GRAPHQL_DEV_TOOLS=true` or `false` overrides the
+/// environment, e.g. to keep them on in a staging deployment that runs as production.
+pub fn graphql_dev_tools_enabled() -> bool {
+    let override_flag = std::env::var("GRAPHQL_DEV_TOOLS").ok().and_then(|v| {
+        match v.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" => Some(true),
+            "false" | "0" | "no" => Some(false),
+            _ => None,
+        }
+    });
+    override_flag.unwrap_or(AppEnv::from_env() != AppEnv::Production)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(AppEnv::parse("Production"), Some(AppEnv::Production));
+        assert_eq!(AppEnv::parse(" prod "), Some(AppEnv::Production));
+        assert_eq!(AppEnv::parse("staging"), Some(AppEnv::Staging));
+        assert_eq!(AppEnv::parse("dev"), Some(AppEnv::Development));
+        assert_eq!(AppEnv::parse("qa"), None);
+    }
+}
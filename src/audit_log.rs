@@ -0,0 +1,472 @@
+use crate::auth::AuthenticatedAccount;
+use crate::client_ip::ClientIp;
+use crate::pii::{self, Store};
+use actix_web::dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::error::PayloadError;
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use actix_web::{Error, HttpMessage, web};
+use futures::{Stream, StreamExt, TryStreamExt};
+use mongodb::bson::{DateTime, Document, doc};
+use mongodb::error::ErrorKind;
+use mongodb::{Client, Collection, IndexModel};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::future::{Ready, ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::time::Instant;
+use utoipa::ToSchema;
+
+/// Size of the capped collection when `AUDIT_LOG_MAX_BYTES` is unset (1 GiB)
+pub const DEFAULT_MAX_BYTES: u64 = 1 << 30;
+
+/// Entries per page of an audit log query
+pub const AUDIT_PAGE_SIZE: usize = 100;
+
+/// How a request ended, by response status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultClass {
+    /// 1xx, 2xx and 3xx
+    Success,
+    /// 4xx, including rejected credentials and exhausted quotas
+    ClientError,
+    /// 5xx
+    ServerError,
+}
+
+impl ResultClass {
+    pub fn of_status(status: StatusCode) -> Self {
+        if status.is_server_error() {
+            ResultClass::ServerError
+        } else if status.is_client_error() {
+            ResultClass::ClientError
+        } else {
+            ResultClass::Success
+        }
+    }
+
+    pub fn parse(class: &str) -> Option<Self> {
+        match class.trim().to_ascii_lowercase().as_str() {
+            "success" => Some(ResultClass::Success),
+            "client_error" => Some(ResultClass::ClientError),
+            "server_error" => Some(ResultClass::ServerError),
+            _ => None,
+        }
+    }
+}
+
+/// One authenticated request as stored in the audit collection
+///
+/// The request itself is kept only as a hash, and the endpoint as its route pattern,
+/// so addresses in paths and bodies never reach the collection.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Account the request authenticated as, lowercased and protected as `PII_AUDIT_LOG`
+    /// says (see [`stored_account`])
+    pub account: String,
+    /// Id of the API key used; `None` for session tokens and legacy keys
+    pub key_id: Option<String>,
+    pub method: String,
+    /// Route pattern, e.g. `/api/v1/validation/{email}`
+    pub endpoint: String,
+    /// SHA-256 of the method, path, query string and body
+    pub input_hash: String,
+    pub status: u16,
+    pub result: ResultClass,
+    pub latency_ms: u64,
+    /// Client address, resolved through the trusted proxies
+    pub ip: Option<String>,
+    pub at: DateTime,
+}
+
+/// An audit record as returned by the query API
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct AuditEntry {
+    pub account: String,
+    pub key_id: Option<String>,
+    pub method: String,
+    pub endpoint: String,
+    pub input_hash: String,
+    pub status: u16,
+    pub result: ResultClass,
+    pub latency_ms: u64,
+    pub ip: Option<String>,
+    /// Unix timestamp the request was received at
+    pub at: i64,
+}
+
+impl From<AuditRecord> for AuditEntry {
+    fn from(record: AuditRecord) -> Self {
+        AuditEntry {
+            account: pii::settings()
+                .reveal(&record.account)
+                .unwrap_or(record.account),
+            key_id: record.key_id,
+            method: record.method,
+            endpoint: record.endpoint,
+            input_hash: record.input_hash,
+            status: record.status,
+            result: record.result,
+            latency_ms: record.latency_ms,
+            ip: record.ip,
+            at: record.at.timestamp_millis() / 1000,
+        }
+    }
+}
+
+/// Narrows an audit log query; every field is optional
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditFilter {
+    pub account: Option<String>,
+    /// Route pattern as recorded, e.g. `/api/v1/validate-email`
+    pub endpoint: Option<String>,
+    pub result: Option<ResultClass>,
+    pub ip: Option<String>,
+    /// Unix timestamps bounding `at`, both inclusive
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl AuditFilter {
+    /// Rejects ranges that end before they start
+    pub fn check(&self) -> Result<(), String> {
+        match (self.from, self.to) {
+            (Some(from), Some(to)) if from > to => Err("from must not be after to".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    fn document(&self) -> Document {
+        let mut filter = Document::new();
+        if let Some(account) = &self.account {
+            filter.insert("account", stored_account(account));
+        }
+        if let Some(endpoint) = &self.endpoint {
+            filter.insert("endpoint", endpoint);
+        }
+        if let Some(result) = self.result {
+            filter.insert(
+                "result",
+                mongodb::bson::to_bson(&result).unwrap_or_default(),
+            );
+        }
+        if let Some(ip) = &self.ip {
+            filter.insert("ip", ip.trim());
+        }
+        let mut range = Document::new();
+        if let Some(from) = self.from {
+            range.insert("$gte", DateTime::from_millis(from.saturating_mul(1000)));
+        }
+        if let Some(to) = self.to {
+            range.insert("$lte", DateTime::from_millis(to.saturating_mul(1000)));
+        }
+        if !range.is_empty() {
+            filter.insert("at", range);
+        }
+        filter
+    }
+}
+
+/// An account as the audit log stores it: plain, hashed or encrypted (`PII_AUDIT_LOG`)
+pub fn stored_account(account: &str) -> String {
+    let account = account.trim().to_lowercase();
+    pii::settings().protect(Store::AuditLog, &account)
+}
+
+/// Whether requests are recorded (`AUDIT_LOG_ENABLED`, default true)
+pub fn is_enabled() -> bool {
+    std::env::var("AUDIT_LOG_ENABLED")
+        .map(|v| !matches!(v.trim().to_ascii_lowercase().as_str(), "false" | "0" | "no"))
+        .unwrap_or(true)
+}
+
+/// Size of the capped collection (`AUDIT_LOG_MAX_BYTES`)
+pub fn max_bytes() -> u64 {
+    std::env::var("AUDIT_LOG_MAX_BYTES")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|bytes| *bytes > 0)
+        .unwrap_or(DEFAULT_MAX_BYTES)
+}
+
+/// Reads `DB_NAME_PRODUCTION` (default `email_sanitizer`) and
+/// `DB_AUDIT_LOG_COLLECTION` (default `audit_log`)
+pub fn audit_collection(mongo_client: &Client) -> Collection<AuditRecord> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let collection_name =
+        std::env::var("DB_AUDIT_LOG_COLLECTION").unwrap_or_else(|_| "audit_log".to_string());
+    mongo_client.database(&db_name).collection(&collection_name)
+}
+
+/// Creates the capped collection and its query indexes
+///
+/// Once full, the oldest records make room for new ones; nothing else removes or
+/// changes a record. An existing collection keeps the size it was created with.
+pub async fn ensure_collection(mongo_client: &Client) -> Result<(), String> {
+    let collection = audit_collection(mongo_client);
+    let created = mongo_client
+        .database(&collection.namespace().db)
+        .create_collection(collection.name())
+        .capped(true)
+        .size(max_bytes())
+        .await;
+    match created {
+        // NamespaceExists
+        Err(e) if !matches!(e.kind.as_ref(), ErrorKind::Command(error) if error.code == 48) => {
+            return Err(e.to_string());
+        }
+        _ => {}
+    }
+
+    for keys in [doc! { "account": 1, "at": -1 }, doc! { "at": -1 }] {
+        collection
+            .create_index(IndexModel::builder().keys(keys).build())
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Stores a record without holding up the response; a failed write is logged
+pub fn record(mongo_client: &Client, record: AuditRecord) {
+    let mongo_client = mongo_client.clone();
+    tokio::spawn(async move {
+        if let Err(e) = audit_collection(&mongo_client).insert_one(record).await {
+            eprintln!("audit log write failed: {}", e);
+        }
+    });
+}
+
+/// One page of audit records, newest first
+pub async fn query_audit(
+    mongo_client: &Client,
+    filter: &AuditFilter,
+    page: usize,
+) -> Result<Vec<AuditEntry>, String> {
+    let skip = page.saturating_sub(1).saturating_mul(AUDIT_PAGE_SIZE);
+    audit_collection(mongo_client)
+        .find(filter.document())
+        .sort(doc! { "at": -1 })
+        .skip(skip as u64)
+        .limit(AUDIT_PAGE_SIZE as i64)
+        .await
+        .map_err(|_| "Database error".to_string())?
+        .map_ok(AuditEntry::from)
+        .try_collect()
+        .await
+        .map_err(|_| "Database error".to_string())
+}
+
+/// Who a request authenticated as: the account resolved by [`crate::auth::Auth`]
+fn caller(req: &ServiceRequest) -> Option<(String, Option<String>)> {
+    req.extensions()
+        .get::<AuthenticatedAccount>()
+        .map(|account| (stored_account(&account.email), account.key_id.clone()))
+}
+
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub struct AuditMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for AuditMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let mongo_client = req
+            .app_data::<web::Data<Client>>()
+            .cloned()
+            .filter(|_| is_enabled());
+        let (Some(mongo_client), Some((account, key_id))) = (mongo_client, caller(&req)) else {
+            return Box::pin(service.call(req));
+        };
+
+        let started = Instant::now();
+        let at = DateTime::now();
+        let method = req.method().to_string();
+        let ip = ClientIp::from_http(req.request()).map(|ip| ip.0.to_string());
+        let hasher = Rc::new(RefCell::new(Sha256::new()));
+        {
+            let mut hasher = hasher.borrow_mut();
+            hasher.update(method.as_bytes());
+            hasher.update(b"\n");
+            hasher.update(req.path().as_bytes());
+            hasher.update(b"?");
+            hasher.update(req.query_string().as_bytes());
+            hasher.update(b"\n");
+        }
+        // The body is hashed as the handler reads it, so it is never buffered twice
+        let body_hasher = Rc::clone(&hasher);
+        let payload = req.take_payload().inspect(move |chunk| {
+            if let Ok(chunk) = chunk {
+                body_hasher.borrow_mut().update(chunk);
+            }
+        });
+        let payload: Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>> = Box::pin(payload);
+        req.set_payload(Payload::from(payload));
+
+        Box::pin(async move {
+            let res = service.call(req).await?;
+            let endpoint = res
+                .request()
+                .match_pattern()
+                .unwrap_or_else(|| res.request().path().to_string());
+            let input_hash = hex(&hasher.borrow().clone().finalize());
+            record(
+                &mongo_client,
+                AuditRecord {
+                    account,
+                    key_id,
+                    method,
+                    endpoint,
+                    input_hash,
+                    status: res.status().as_u16(),
+                    result: ResultClass::of_status(res.status()),
+                    latency_ms: started.elapsed().as_millis() as u64,
+                    ip,
+                    at,
+                },
+            );
+            Ok(res)
+        })
+    }
+}
+
+/// Records every authenticated request in the audit log
+///
+/// Must run inside [`crate::auth::Auth`], which resolves the account; requests no
+/// account was resolved for pass through unrecorded, as they do when no MongoDB client
+/// is registered as app data or `AUDIT_LOG_ENABLED` is false.
+pub struct Audit;
+
+impl Audit {
+    pub fn from_app_data() -> Self {
+        Audit
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Audit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AuditMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuditMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, HttpResponse, test as actix_test};
+    use mongodb::options::ClientOptions;
+
+    #[test]
+    fn test_result_classes() {
+        assert_eq!(ResultClass::of_status(StatusCode::OK), ResultClass::Success);
+        assert_eq!(
+            ResultClass::of_status(StatusCode::TOO_MANY_REQUESTS),
+            ResultClass::ClientError
+        );
+        assert_eq!(
+            ResultClass::of_status(StatusCode::SERVICE_UNAVAILABLE),
+            ResultClass::ServerError
+        );
+        assert_eq!(
+            ResultClass::parse("Client_Error"),
+            Some(ResultClass::ClientError)
+        );
+        assert_eq!(ResultClass::parse("4xx"), None);
+    }
+
+    #[test]
+    fn test_filter_document() {
+        let filter = AuditFilter {
+            account: Some(" User@Example.com".to_string()),
+            result: Some(ResultClass::ServerError),
+            from: Some(100),
+            ..Default::default()
+        };
+        assert!(filter.check().is_ok());
+
+        let document = filter.document();
+        assert_eq!(document.get_str("account").unwrap(), "user@example.com");
+        assert_eq!(document.get_str("result").unwrap(), "server_error");
+        assert!(!document.contains_key("endpoint"));
+        let range = document.get_document("at").unwrap();
+        assert_eq!(
+            range.get_datetime("$gte").unwrap(),
+            &DateTime::from_millis(100_000)
+        );
+        assert!(!range.contains_key("$lte"));
+
+        assert!(AuditFilter::default().document().is_empty());
+        let reversed = AuditFilter {
+            from: Some(2),
+            to: Some(1),
+            ..Default::default()
+        };
+        assert!(reversed.check().is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_body_reaches_handler_intact() {
+        let mongo_client = Client::with_options(ClientOptions::default()).unwrap();
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .wrap(Audit::from_app_data())
+                .wrap_fn(|req, srv| {
+                    if req.headers().contains_key("x-account") {
+                        req.extensions_mut().insert(AuthenticatedAccount {
+                            email: "user@example.com".to_string(),
+                            ..AuthenticatedAccount::default()
+                        });
+                    }
+                    srv.call(req)
+                })
+                .route(
+                    "/echo",
+                    web::post().to(|body: web::Bytes| async move { HttpResponse::Ok().body(body) }),
+                ),
+        )
+        .await;
+
+        for authenticated in [true, false] {
+            let mut req = actix_test::TestRequest::post()
+                .uri("/echo")
+                .set_payload("user@example.com");
+            if authenticated {
+                req = req.insert_header(("x-account", "1"));
+            }
+            let body = actix_test::call_and_read_body(&app, req.to_request()).await;
+            assert_eq!(body, "user@example.com");
+        }
+    }
+}
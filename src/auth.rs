@@ -1,19 +1,91 @@
-use actix_web::dev::{Service, ServiceResponse, Transform, forward_ready};
-use actix_web::error::ErrorUnauthorized;
-use actix_web::{Error, Result, dev::ServiceRequest};
-use chrono::{Duration, Utc};
+use crate::email_verification;
+use crate::error::ApiError;
+use crate::handlers::validation::syntax;
+use crate::normalize::normalize_email;
+use crate::password::{self, PasswordScheme};
+use actix_web::body::EitherBody;
+use actix_web::dev::{Payload, Service, ServiceResponse, Transform, forward_ready};
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest, Result, dev::ServiceRequest, web};
+use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
-use mongodb::{Client, Collection, bson::doc};
+use mongodb::error::{ErrorKind, WriteFailure};
+use mongodb::options::IndexOptions;
+use mongodb::{
+    Client, Collection, IndexModel,
+    bson::{Document, doc},
+};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::future::{Ready, ready};
+use std::ops::Deref;
 use std::pin::Pin;
+use std::rc::Rc;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct User {
     pub email: String,
     pub password_hash: String,
     pub active: bool,
+    /// Mixed into the API key prefix; replacing it invalidates every key issued so far
+    #[serde(default)]
+    pub key_salt: String,
+    /// Algorithm of `password_hash`; records from before Argon2id have none and use bcrypt
+    #[serde(default)]
+    pub password_scheme: PasswordScheme,
+    /// Prefix of the API keys issued before the password hash was upgraded on login,
+    /// so the upgrade doesn't revoke them; dropped whenever keys are reissued
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legacy_key_prefix: Option<String>,
+    /// New email awaiting confirmation; the account keeps `email` until the verification
+    /// link sent to it is followed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_email: Option<String>,
+}
+
+impl User {
+    /// Secret the account's API key prefix is derived from
+    pub fn key_secret(&self) -> String {
+        format!("{}{}", self.password_hash, self.key_salt)
+    }
+}
+
+/// Users collection shared by REST registration, GraphQL account mutations and key checks
+///
+/// Reads `DB_NAME_PRODUCTION` (default `email_sanitizer`) and `DB_USERS_COLLECTION`
+/// (default `users`).
+pub fn users_collection(mongo_client: &Client) -> Collection<User> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let collection_name =
+        std::env::var("DB_USERS_COLLECTION").unwrap_or_else(|_| "users".to_string());
+    mongo_client.database(&db_name).collection(&collection_name)
+}
+
+/// Creates the unique index that keeps two accounts from sharing an email, and the one
+/// API keys are looked up by
+pub async fn ensure_indexes(mongo_client: &Client) -> Result<(), String> {
+    users_collection(mongo_client)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "email": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    api_keys_collection(mongo_client)
+        .create_index(IndexModel::builder().keys(doc! { "key_id": 1 }).build())
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Whether a write failed on a unique index
+fn is_duplicate_key(e: &mongodb::error::Error) -> bool {
+    matches!(
+        e.kind.as_ref(),
+        ErrorKind::Write(WriteFailure::WriteError(write_error)) if write_error.code == 11000
+    )
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,14 +94,407 @@ pub struct Claims {
     pub exp: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A record in the `api_keys` collection
+///
+/// Keys are random, in the `esk_<key_id>_<secret>` format, and stored as the Argon2id
+/// `secret_hash` of their secret. Records from before carry a SHA-256 `key_hash` or
+/// the plaintext `key`; see [`legacy_keys_accepted`].
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ApiKey {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub key: String,
     pub active: bool,
+    /// Email of the account that owns the key
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub plan: PlanTier,
+    /// Public identifier used to manage the key
+    #[serde(default)]
+    pub key_id: Option<String>,
+    #[serde(default)]
+    pub key_hash: Option<String>,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Unix timestamp of creation
+    #[serde(default)]
+    pub created_at: Option<i64>,
+    /// Unix timestamp of the last authenticated request
+    #[serde(default)]
+    pub last_used: Option<i64>,
+    #[serde(default)]
+    pub revoked: bool,
+    /// Argon2id hash of the key's secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_hash: Option<String>,
+    /// Issued at registration, rotation or an account change, and replaced by the next
+    /// one; keys from `/api/v1/keys` are managed separately
+    #[serde(default)]
+    pub primary: bool,
+    /// [`KEY_SCOPES`] the key is limited to; empty for full access
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+}
+
+/// Scope opening the admin routes; unlike the others, full-access keys don't hold it
+pub const ADMIN_SCOPE: &str = "admin";
+
+/// Scopes a key can be limited to, with the paths (relative to the API version scope)
+/// each one opens; a scoped key is refused everywhere else, GraphQL and key
+/// management included
+pub const KEY_SCOPES: &[(&str, &[&str])] = &[
+    (
+        "validate",
+        &[
+            "/validate-email",
+            "/validate-emails-bulk",
+            "/validation/",
+            "/lookup/",
+        ],
+    ),
+    (
+        "jobs",
+        &[
+            "/job-status/",
+            "/job-results/",
+            "/job-report/",
+            "/jobs",
+            "/lists",
+            "/schedules",
+            "/history",
+        ],
+    ),
+    ("sync", &["/sync/"]),
+    (ADMIN_SCOPE, &["/admin/"]),
+];
+
+/// Whether `email` may issue keys with [`ADMIN_SCOPE`]: one of the comma-separated
+/// accounts of `ADMIN_ACCOUNTS`, set by the operator (none when unset)
+pub fn may_grant_admin(email: &str) -> bool {
+    std::env::var("ADMIN_ACCOUNTS").is_ok_and(|accounts| {
+        accounts
+            .split(',')
+            .any(|account| account.trim().eq_ignore_ascii_case(email.trim()))
+    })
+}
+
+pub fn is_key_scope(scope: &str) -> bool {
+    KEY_SCOPES.iter().any(|(name, _)| *name == scope)
+}
+
+/// Scope of [`KEY_SCOPES`] opening a path under `/api/v1` or `/api/v2`, if any
+pub fn scope_for_path(path: &str) -> Option<&'static str> {
+    let path = path
+        .strip_prefix("/api/v1")
+        .or_else(|| path.strip_prefix("/api/v2"))
+        .unwrap_or(path);
+    KEY_SCOPES
+        .iter()
+        .find(|(_, prefixes)| prefixes.iter().any(|prefix| path.starts_with(prefix)))
+        .map(|(scope, _)| *scope)
+}
+
+/// Subscription plan of an account, ordered from lowest to highest
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlanTier {
+    #[default]
+    Free,
+    Pro,
+    Enterprise,
+}
+
+impl PlanTier {
+    /// Number of consecutive bulk jobs a tenant on this plan is served per
+    /// round of the job queue's weighted round-robin
+    pub fn queue_weight(&self) -> usize {
+        match self {
+            PlanTier::Free => 1,
+            PlanTier::Pro => 3,
+            PlanTier::Enterprise => 5,
+        }
+    }
+}
+
+impl ApiKey {
+    /// Identifier used to partition per-tenant resources such as job sub-queues.
+    ///
+    /// Falls back to a hash of the key for legacy records without an owner so the
+    /// raw key is never used as a Redis key.
+    pub fn tenant_id(&self) -> String {
+        match &self.owner {
+            Some(owner) => owner.to_lowercase(),
+            None => {
+                let mut hasher = Sha256::new();
+                hasher.update(&self.key);
+                format!("key-{}", &format!("{:x}", hasher.finalize())[..16])
+            }
+        }
+    }
 }
 
 pub struct AuthGuard;
 
+/// The `api_keys` collection consulted by every authenticated endpoint
+pub fn api_keys_collection(mongo_client: &Client) -> Collection<ApiKey> {
+    mongo_client
+        .database("email_sanitizer")
+        .collection("api_keys")
+}
+
+/// SHA-256 of an API key, hex encoded; the form keys are stored in
+pub fn hash_api_key(api_key: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(api_key);
+    format!("{:x}", hasher.finalize())
+}
+
+const API_KEY_PREFIX: &str = "esk_";
+
+/// Key id and secret of a key in the `esk_<key_id>_<secret>` format
+pub fn parse_api_key(api_key: &str) -> Option<(&str, &str)> {
+    let (key_id, secret) = api_key.strip_prefix(API_KEY_PREFIX)?.split_once('_')?;
+    let is_hex =
+        |part: &str, len: usize| part.len() == len && part.bytes().all(|b| b.is_ascii_hexdigit());
+    (is_hex(key_id, 32) && is_hex(secret, 64)).then_some((key_id, secret))
+}
+
+/// End of the legacy grace period when `LEGACY_API_KEY_SUNSET` is unset, 90 days
+/// after random keys replaced the older formats
+pub const DEFAULT_LEGACY_API_KEY_SUNSET: &str = "2027-01-15T00:00:00Z";
+
+/// Whether keys in the formats issued before random keys are still accepted at `now`
+///
+/// Account keys derived from the password (`<prefix>.<jwt>`) and SHA-256 hashed or
+/// plaintext key records work until `LEGACY_API_KEY_SUNSET` (RFC 3339, e.g.
+/// `2026-12-01T00:00:00Z`); unset or unparseable, until
+/// [`DEFAULT_LEGACY_API_KEY_SUNSET`].
+pub fn legacy_keys_accepted(now: DateTime<Utc>) -> bool {
+    legacy_grace(std::env::var("LEGACY_API_KEY_SUNSET").ok().as_deref(), now)
+}
+
+fn legacy_grace(sunset: Option<&str>, now: DateTime<Utc>) -> bool {
+    let parse = |sunset: &str| DateTime::parse_from_rfc3339(sunset.trim()).ok();
+    let sunset = sunset
+        .and_then(parse)
+        .or_else(|| parse(DEFAULT_LEGACY_API_KEY_SUNSET))
+        .expect("the default sunset is RFC 3339");
+    now < sunset
+}
+
+/// Looks up an active, unrevoked key record by key id and secret, or during the
+/// legacy grace period by its SHA-256 hash or plaintext key
+///
+/// Records the time of use on current and hashed records; a failure to do so is ignored.
+pub async fn find_api_key(
+    mongo_client: &Client,
+    api_key: &str,
+) -> Result<Option<ApiKey>, mongodb::error::Error> {
+    if api_key.is_empty() {
+        return Ok(None);
+    }
+    let collection = api_keys_collection(mongo_client);
+
+    if let Some((key_id, secret)) = parse_api_key(api_key) {
+        let record = collection
+            .find_one(doc! {
+                "key_id": key_id,
+                "active": true,
+                "revoked": { "$ne": true },
+            })
+            .await?
+            .filter(|record| {
+                record
+                    .secret_hash
+                    .as_deref()
+                    .is_some_and(|hash| password::verify_key_secret(secret, hash))
+            });
+        if record.is_some() {
+            let _ = collection
+                .update_one(
+                    doc! { "key_id": key_id },
+                    doc! { "$set": { "last_used": Utc::now().timestamp() } },
+                )
+                .await;
+        }
+        return Ok(record);
+    }
+    if !legacy_keys_accepted(Utc::now()) {
+        return Ok(None);
+    }
+    let key_hash = hash_api_key(api_key);
+
+    let record = collection
+        .find_one(doc! {
+            "$or": [{ "key_hash": &key_hash }, { "key": api_key }],
+            "active": true,
+            "revoked": { "$ne": true },
+        })
+        .await?;
+
+    if record.as_ref().is_some_and(|r| r.key_hash.is_some()) {
+        let _ = collection
+            .update_one(
+                doc! { "key_hash": &key_hash },
+                doc! { "$set": { "last_used": Utc::now().timestamp() } },
+            )
+            .await;
+    }
+    Ok(record)
+}
+
+/// Creates an additional API key for `owner`, limited to `scopes` unless empty; returns
+/// the stored record and the plaintext key, which can't be retrieved afterwards
+pub async fn create_api_key(
+    mongo_client: &Client,
+    owner: &str,
+    plan: PlanTier,
+    label: Option<String>,
+    scopes: Vec<String>,
+) -> Result<(ApiKey, String), String> {
+    insert_api_key(mongo_client, owner, plan, label, scopes, false, true).await
+}
+
+/// Stores a new random key: 128-bit key id, 256-bit secret
+///
+/// An inactive key is refused by [`find_api_key`] until [`activate_account`] enables it.
+async fn insert_api_key(
+    mongo_client: &Client,
+    owner: &str,
+    plan: PlanTier,
+    label: Option<String>,
+    scopes: Vec<String>,
+    primary: bool,
+    active: bool,
+) -> Result<(ApiKey, String), String> {
+    let key_id = password::random_hex(16);
+    let secret = password::random_hex(32);
+    let record = ApiKey {
+        active,
+        owner: Some(owner.to_string()),
+        plan,
+        key_id: Some(key_id.clone()),
+        label,
+        created_at: Some(Utc::now().timestamp()),
+        secret_hash: Some(password::hash_key_secret(&secret)?),
+        primary,
+        scopes,
+        ..ApiKey::default()
+    };
+
+    api_keys_collection(mongo_client)
+        .insert_one(&record)
+        .await
+        .map_err(|_| "Database error")?;
+
+    Ok((record, format!("{}{}_{}", API_KEY_PREFIX, key_id, secret)))
+}
+
+/// Revokes `owner`'s primary key(s)
+async fn revoke_primary_keys(mongo_client: &Client, owner: &str) -> Result<(), String> {
+    api_keys_collection(mongo_client)
+        .update_many(
+            doc! { "owner": owner, "primary": true, "revoked": { "$ne": true } },
+            doc! { "$set": { "revoked": true, "active": false } },
+        )
+        .await
+        .map(|_| ())
+        .map_err(|_| "Database error".to_string())
+}
+
+/// Replaces `owner`'s primary key with a new one; returns the plaintext key
+///
+/// Keys of accounts still awaiting verification are issued inactive.
+async fn issue_primary_key(
+    mongo_client: &Client,
+    owner: &str,
+    active: bool,
+) -> Result<String, String> {
+    revoke_primary_keys(mongo_client, owner).await?;
+    insert_api_key(
+        mongo_client,
+        owner,
+        PlanTier::default(),
+        None,
+        Vec::new(),
+        true,
+        active,
+    )
+    .await
+    .map(|(_, api_key)| api_key)
+}
+
+/// Lists the key records owned by `owner`, including revoked ones
+pub async fn list_api_keys(mongo_client: &Client, owner: &str) -> Result<Vec<ApiKey>, String> {
+    use futures::TryStreamExt;
+
+    api_keys_collection(mongo_client)
+        .find(doc! { "owner": owner, "key_id": { "$exists": true } })
+        .sort(doc! { "created_at": -1 })
+        .await
+        .map_err(|_| "Database error")?
+        .try_collect()
+        .await
+        .map_err(|_| "Database error".to_string())
+}
+
+/// Highest plan among `owner`'s active keys; accounts without managed keys are on the free plan
+pub async fn account_plan(mongo_client: &Client, owner: &str) -> Result<PlanTier, String> {
+    highest_plan(mongo_client, &[owner.to_string()]).await
+}
+
+/// Highest plan among the active keys of any of `owners`, e.g. an organization's members
+pub async fn highest_plan(mongo_client: &Client, owners: &[String]) -> Result<PlanTier, String> {
+    use futures::TryStreamExt;
+
+    let keys: Vec<ApiKey> = api_keys_collection(mongo_client)
+        .find(doc! {
+            "owner": { "$in": owners },
+            "key_id": { "$exists": true },
+            "active": true,
+            "revoked": { "$ne": true },
+        })
+        .await
+        .map_err(|_| "Database error")?
+        .try_collect()
+        .await
+        .map_err(|_| "Database error")?;
+
+    Ok(keys
+        .into_iter()
+        .map(|key| key.plan)
+        .max()
+        .unwrap_or_default())
+}
+
+/// Whether an account is registered under `email`
+pub async fn account_exists(mongo_client: &Client, email: &str) -> Result<bool, String> {
+    users_collection(mongo_client)
+        .find_one(doc! { "email": email })
+        .await
+        .map(|user| user.is_some())
+        .map_err(|_| "Database error".to_string())
+}
+
+/// Revokes one of `owner`'s keys by id; returns whether a key was revoked
+pub async fn revoke_api_key_by_id(
+    mongo_client: &Client,
+    owner: &str,
+    key_id: &str,
+) -> Result<bool, String> {
+    let result = api_keys_collection(mongo_client)
+        .update_one(
+            doc! { "key_id": key_id, "owner": owner, "revoked": { "$ne": true } },
+            doc! { "$set": { "revoked": true, "active": false } },
+        )
+        .await
+        .map_err(|_| "Database error")?;
+
+    Ok(result.modified_count > 0)
+}
+
+/// Derives an account key in the legacy `<prefix>.<jwt>` format; no longer issued, but
+/// accepted during the grace period of [`legacy_keys_accepted`]
 pub fn generate_api_key(email: &str, password: &str) -> Result<String, Box<dyn std::error::Error>> {
     let jwt_secret = std::env::var("JWT_SECRET")?;
     let claims = Claims {
@@ -37,22 +502,44 @@ pub fn generate_api_key(email: &str, password: &str) -> Result<String, Box<dyn s
         exp: (Utc::now() + Duration::days(30)).timestamp() as usize,
     };
 
-    let mut hasher = Sha256::new();
-    hasher.update(format!("{}{}", email, password));
-    let input_hash = format!("{:x}", hasher.finalize());
-
     let token = encode(
         &Header::default(),
         &claims,
         &EncodingKey::from_secret(jwt_secret.as_ref()),
     )?;
-    Ok(format!("{}.{}", &input_hash[..16], token))
+    Ok(format!("{}.{}", key_prefix(email, password), token))
+}
+
+/// First part of an account's API keys, derived from its email and key secret
+fn key_prefix(email: &str, key_secret: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}{}", email, key_secret));
+    format!("{:x}", hasher.finalize())[..16].to_string()
 }
 
 pub async fn verify_api_key(
     api_key: &str,
     mongo_client: &Client,
 ) -> Result<String, Box<dyn std::error::Error>> {
+    // Keys issued through /api/v1/keys are checked against their stored records
+    if let Some(owner) = find_api_key(mongo_client, api_key)
+        .await?
+        .and_then(|record| record.owner)
+    {
+        return Ok(owner);
+    }
+    verify_legacy_api_key(api_key, mongo_client).await
+}
+
+/// Checks a password-derived `<prefix>.<jwt>` key during the legacy grace period
+async fn verify_legacy_api_key(
+    api_key: &str,
+    mongo_client: &Client,
+) -> Result<String, Box<dyn std::error::Error>> {
+    if !legacy_keys_accepted(Utc::now()) {
+        return Err("Invalid API key".into());
+    }
+
     let parts: Vec<&str> = api_key.splitn(2, '.').collect();
     if parts.len() != 2 {
         return Err("Invalid key format".into());
@@ -65,86 +552,765 @@ pub async fn verify_api_key(
         &Validation::new(Algorithm::HS256),
     )?;
 
-    let db = mongo_client.database("email_sanitizer");
-    let collection: Collection<User> = db.collection("users");
+    let collection = users_collection(mongo_client);
 
     if let Some(user) = collection
         .find_one(doc! { "email": &token_data.claims.email, "active": true })
         .await?
     {
-        let mut hasher = Sha256::new();
-        hasher.update(format!("{}{}", user.email, user.password_hash));
-        let expected_prefix = format!("{:x}", hasher.finalize())[..16].to_string();
-
-        if parts[0] == expected_prefix {
+        // Both prefixes are compared, in constant time, whichever one matches
+        let current =
+            password::constant_time_eq(parts[0], &key_prefix(&user.email, &user.key_secret()));
+        let legacy = user
+            .legacy_key_prefix
+            .as_deref()
+            .is_some_and(|prefix| password::constant_time_eq(parts[0], prefix));
+        if current | legacy {
             return Ok(user.email);
         }
     }
     Err("Invalid API key".into())
 }
 
+/// Claims of the short-lived session tokens issued by `/api/v1/auth/login`
+///
+/// `typ` keeps access and refresh tokens from standing in for one another, and
+/// `pwd` ties a session to the password it was opened with.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionClaims {
+    pub sub: String,
+    pub typ: String,
+    pub pwd: String,
+    pub iat: usize,
+    pub exp: usize,
+}
+
+const ACCESS_TOKEN_TYPE: &str = "access";
+const REFRESH_TOKEN_TYPE: &str = "refresh";
+
+/// Token pair returned by the login and refresh endpoints
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct SessionTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: String,
+    /// Lifetime of the access token in seconds
+    pub expires_in: i64,
+}
+
+/// Lifetimes of session tokens in seconds
+///
+/// Reads `SESSION_ACCESS_TTL` (default 900) and `SESSION_REFRESH_TTL` (default 30 days).
+fn session_ttls() -> (i64, i64) {
+    let read = |name: &str, default: i64| {
+        std::env::var(name)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|v: &i64| *v > 0)
+            .unwrap_or(default)
+    };
+    (
+        read("SESSION_ACCESS_TTL", 900),
+        read("SESSION_REFRESH_TTL", 30 * 24 * 3600),
+    )
+}
+
+/// Short fingerprint of a password hash, so changing the password ends existing sessions
+fn password_fingerprint(password_hash: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(password_hash);
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
+
+fn issue_session(user: &User) -> Result<SessionTokens, String> {
+    let jwt_secret = std::env::var("JWT_SECRET").map_err(|_| "JWT_SECRET is not configured")?;
+    let key = EncodingKey::from_secret(jwt_secret.as_ref());
+    let (access_ttl, refresh_ttl) = session_ttls();
+    let now = Utc::now();
+
+    let sign = |typ: &str, ttl: i64| {
+        let claims = SessionClaims {
+            sub: user.email.clone(),
+            typ: typ.to_string(),
+            pwd: password_fingerprint(&user.password_hash),
+            iat: now.timestamp() as usize,
+            exp: (now + Duration::seconds(ttl)).timestamp() as usize,
+        };
+        encode(&Header::default(), &claims, &key).map_err(|_| "Token signing failed".to_string())
+    };
+
+    Ok(SessionTokens {
+        access_token: sign(ACCESS_TOKEN_TYPE, access_ttl)?,
+        refresh_token: sign(REFRESH_TOKEN_TYPE, refresh_ttl)?,
+        token_type: "Bearer".to_string(),
+        expires_in: access_ttl,
+    })
+}
+
+fn decode_session(token: &str, expected_type: &str) -> Result<SessionClaims, String> {
+    let jwt_secret = std::env::var("JWT_SECRET").map_err(|_| "JWT_SECRET is not configured")?;
+    let claims = decode::<SessionClaims>(
+        token,
+        &DecodingKey::from_secret(jwt_secret.as_ref()),
+        &Validation::new(Algorithm::HS256),
+    )
+    .map_err(|_| "Invalid or expired token")?
+    .claims;
+
+    if claims.typ != expected_type {
+        return Err("Invalid or expired token".to_string());
+    }
+    Ok(claims)
+}
+
+/// Runs password hashing or verification on the blocking pool; at the configured
+/// Argon2id cost it would otherwise stall every request sharing the async worker
+async fn off_worker<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|_| "Password hashing failed".to_string())
+}
+
+/// [`password::hash_password`] off the async workers
+async fn hash_password(password: &str) -> Result<(String, PasswordScheme), String> {
+    let password = password.to_string();
+    off_worker(move || password::hash_password(&password)).await?
+}
+
+/// Checks email and password and opens a session
+///
+/// A password stored with an outdated scheme or cost (bcrypt, typically) is rehashed
+/// with the current one. The account's API keys keep working, but its other sessions
+/// end as they would after a password change.
+pub async fn login(
+    mongo_client: &Client,
+    email: &str,
+    password: &str,
+) -> Result<SessionTokens, String> {
+    let user = users_collection(mongo_client)
+        .find_one(doc! { "email": email, "active": true })
+        .await
+        .map_err(|_| "Database error")?;
+
+    // Unknown accounts are rejected only after a verification of the same cost
+    let candidate = password.to_string();
+    let stored = user
+        .as_ref()
+        .map(|user| (user.password_hash.clone(), user.password_scheme));
+    let verified = off_worker(move || match stored {
+        Some((hash, scheme)) => password::verify_password(&candidate, &hash, scheme),
+        None => {
+            password::verify_dummy(&candidate);
+            false
+        }
+    })
+    .await?;
+    let user = user
+        .filter(|_| verified)
+        .ok_or_else(|| "Invalid email or password".to_string())?;
+
+    let user = upgrade_password_hash(mongo_client, user, password).await;
+    issue_session(&user)
+}
+
+/// Rehashes a just-verified password if [`password::needs_rehash`] says so
+///
+/// Best effort: on failure the account keeps its current hash and the next login retries.
+async fn upgrade_password_hash(mongo_client: &Client, mut user: User, password: &str) -> User {
+    if !password::needs_rehash(&user.password_hash, user.password_scheme) {
+        return user;
+    }
+    let Ok((new_hash, scheme)) = hash_password(password).await else {
+        return user;
+    };
+    let legacy_key_prefix = user
+        .legacy_key_prefix
+        .clone()
+        .unwrap_or_else(|| key_prefix(&user.email, &user.key_secret()));
+
+    // Matching on the old hash keeps a concurrent password change from being overwritten
+    let updated = users_collection(mongo_client)
+        .update_one(
+            doc! { "email": &user.email, "password_hash": &user.password_hash },
+            doc! { "$set": {
+                "password_hash": &new_hash,
+                "password_scheme": scheme.as_str(),
+                "legacy_key_prefix": &legacy_key_prefix,
+            } },
+        )
+        .await;
+    if matches!(updated, Ok(result) if result.modified_count > 0) {
+        user.password_hash = new_hash;
+        user.password_scheme = scheme;
+        user.legacy_key_prefix = Some(legacy_key_prefix);
+    }
+    user
+}
+
+/// Exchanges a refresh token for a new token pair
+///
+/// Fails once the account is deactivated or its password has changed.
+pub async fn refresh_session(
+    mongo_client: &Client,
+    refresh_token: &str,
+) -> Result<SessionTokens, String> {
+    let claims = decode_session(refresh_token, REFRESH_TOKEN_TYPE)?;
+
+    let user = users_collection(mongo_client)
+        .find_one(doc! { "email": &claims.sub, "active": true })
+        .await
+        .map_err(|_| "Database error")?
+        .filter(|user| {
+            password::constant_time_eq(&password_fingerprint(&user.password_hash), &claims.pwd)
+        })
+        .ok_or_else(|| "Invalid or expired token".to_string())?;
+
+    issue_session(&user)
+}
+
+/// Returns the email of a valid session access token
+pub fn verify_session_token(token: &str) -> Result<String, String> {
+    decode_session(token, ACCESS_TOKEN_TYPE).map(|claims| claims.sub)
+}
+
+/// Resolves a bearer credential, either a session access token or an API key, to its account
+///
+/// Key records lend the account their id, plan and scopes; sessions and legacy
+/// password-derived keys have no id, the free plan and full access.
+pub async fn authenticate(
+    credential: &str,
+    mongo_client: &Client,
+) -> Result<AuthenticatedAccount, Box<dyn std::error::Error>> {
+    if let Ok(email) = verify_session_token(credential) {
+        return Ok(AuthenticatedAccount {
+            email,
+            ..AuthenticatedAccount::default()
+        });
+    }
+    if let Some(record) = find_api_key(mongo_client, credential).await?
+        && let Some(email) = record.owner
+    {
+        return Ok(AuthenticatedAccount {
+            email,
+            key_id: record.key_id,
+            plan: record.plan,
+            scopes: record.scopes,
+        });
+    }
+    let email = verify_legacy_api_key(credential, mongo_client).await?;
+    Ok(AuthenticatedAccount {
+        email,
+        ..AuthenticatedAccount::default()
+    })
+}
+
+/// Checks the email of an account and returns it in its canonical form (see
+/// [`normalize_email`])
+pub fn account_email(email: &str) -> Result<String, String> {
+    let email = normalize_email(email);
+    if !syntax::is_valid_email(&email) {
+        return Err("Invalid email address".to_string());
+    }
+    Ok(email)
+}
+
+/// Creates an inactive account, sends it a verification link and returns its email, as
+/// normalized by [`account_email`], and its first API key
+///
+/// The key, sessions and everything else that needs an active account work once the
+/// link has been followed (see [`email_verification::confirm`]).
+pub async fn register_account(
+    mongo_client: &Client,
+    email: &str,
+    password: &str,
+) -> Result<(String, String), String> {
+    let email = &account_email(email)?;
+    password::check_policy(password)?;
+    if account_exists(mongo_client, email).await? {
+        return Err("Email already registered".to_string());
+    }
+    let (password_hash, password_scheme) = hash_password(password).await?;
+
+    let user = User {
+        email: email.to_string(),
+        password_hash,
+        active: false,
+        key_salt: String::new(),
+        password_scheme,
+        legacy_key_prefix: None,
+        pending_email: None,
+    };
+
+    // The unique index catches registrations racing past the check above
+    users_collection(mongo_client)
+        .insert_one(&user)
+        .await
+        .map_err(|e| {
+            if is_duplicate_key(&e) {
+                "Email already registered"
+            } else {
+                "Database error"
+            }
+        })?;
+
+    let api_key = issue_primary_key(mongo_client, &user.email, false).await?;
+    email_verification::send_verification_link(&user.email).await;
+    Ok((user.email, api_key))
+}
+
+/// Marks a verified account and the primary key it was registered with active; returns
+/// whether the account exists
+pub async fn activate_account(mongo_client: &Client, email: &str) -> Result<bool, String> {
+    let matched = users_collection(mongo_client)
+        .update_one(doc! { "email": email }, doc! { "$set": { "active": true } })
+        .await
+        .map(|result| result.matched_count > 0)
+        .map_err(|_| "Database error")?;
+    if matched {
+        api_keys_collection(mongo_client)
+            .update_many(
+                doc! { "owner": email, "primary": true, "active": false, "revoked": { "$ne": true } },
+                doc! { "$set": { "active": true } },
+            )
+            .await
+            .map_err(|_| "Database error")?;
+    }
+    Ok(matched)
+}
+
+/// Issues a new API key for the account owning `api_key`; the old key stops working
+///
+/// Returns the account email and the new key.
+pub async fn rotate_api_key(
+    mongo_client: &Client,
+    api_key: &str,
+) -> Result<(String, String), String> {
+    let email = verify_api_key(api_key, mongo_client)
+        .await
+        .map_err(|_| "Invalid API key")?;
+    // Legacy keys derived from the old salt stop working too
+    replace_key_salt(mongo_client, &email).await?;
+
+    let key = issue_primary_key(mongo_client, &email, true).await?;
+    Ok((email, key))
+}
+
+/// Invalidates `api_key` and the account's primary and legacy keys without issuing a new one
+pub async fn revoke_api_key(mongo_client: &Client, api_key: &str) -> Result<(), String> {
+    let email = verify_api_key(api_key, mongo_client)
+        .await
+        .map_err(|_| "Invalid API key")?;
+    replace_key_salt(mongo_client, &email).await?;
+    revoke_primary_keys(mongo_client, &email).await?;
+    if let Some((key_id, _)) = parse_api_key(api_key) {
+        revoke_api_key_by_id(mongo_client, &email, key_id).await?;
+    }
+    Ok(())
+}
+
+/// Changes the password of the account owning `api_key` and/or starts an email change
+///
+/// A new email is checked and normalized like on registration and only recorded as
+/// pending: a verification link goes to it, and the account moves over once it has been
+/// followed (see [`confirm_email_change`]). The account's primary and legacy keys are
+/// invalidated; returns the account's current email and a fresh primary key.
+pub async fn update_account(
+    mongo_client: &Client,
+    api_key: &str,
+    new_email: Option<&str>,
+    new_password: Option<&str>,
+) -> Result<(String, String), String> {
+    let email = verify_api_key(api_key, mongo_client)
+        .await
+        .map_err(|_| "Invalid API key")?;
+    let collection = users_collection(mongo_client);
+
+    let mut user = collection
+        .find_one(doc! { "email": &email, "active": true })
+        .await
+        .map_err(|_| "Database error")?
+        .ok_or("Account not found")?;
+
+    let pending_email = new_email
+        .map(account_email)
+        .transpose()?
+        .filter(|new_email| *new_email != email);
+    if let Some(pending_email) = &pending_email
+        && account_exists(mongo_client, pending_email).await?
+    {
+        return Err("Email already registered".to_string());
+    }
+    if let Some(new_password) = new_password {
+        password::check_policy(new_password)?;
+        (user.password_hash, user.password_scheme) = hash_password(new_password).await?;
+    }
+    user.key_salt = uuid::Uuid::new_v4().simple().to_string();
+
+    let mut set = doc! {
+        "password_hash": &user.password_hash,
+        "password_scheme": user.password_scheme.as_str(),
+        "key_salt": &user.key_salt,
+    };
+    if let Some(pending_email) = &pending_email {
+        set.insert("pending_email", pending_email);
+    }
+    collection
+        .update_one(
+            doc! { "email": &email },
+            doc! { "$set": set, "$unset": { "legacy_key_prefix": "" } },
+        )
+        .await
+        .map_err(|_| "Database error")?;
+
+    if let Some(pending_email) = &pending_email {
+        email_verification::send_verification_link(pending_email).await;
+    }
+    let key = issue_primary_key(mongo_client, &email, true).await?;
+    Ok((email, key))
+}
+
+/// Moves the account whose pending email is `new_email` over to it, with everything it
+/// owns; returns whether there was such an account
+///
+/// Fails with "Email already registered" when another account took the address since
+/// the change was requested.
+pub async fn confirm_email_change(mongo_client: &Client, new_email: &str) -> Result<bool, String> {
+    let previous = users_collection(mongo_client)
+        .find_one_and_update(
+            doc! { "pending_email": new_email },
+            doc! {
+                "$set": { "email": new_email },
+                "$unset": { "pending_email": "" },
+            },
+        )
+        .await
+        .map_err(|e| {
+            if is_duplicate_key(&e) {
+                "Email already registered"
+            } else {
+                "Database error"
+            }
+        })?;
+    let Some(previous) = previous else {
+        return Ok(false);
+    };
+    transfer_account_records(mongo_client, &previous.email, new_email).await?;
+    Ok(true)
+}
+
+/// Moves everything an account owns from its old email to its new one: API keys,
+/// saved lists, schedules, custom and suppression lists, bounce feedback, history,
+/// settings, branding, webhook secrets and organization memberships
+///
+/// Otherwise the records would drop off the account, and whoever registers the old
+/// address next would inherit them, keys included.
+async fn transfer_account_records(
+    mongo_client: &Client,
+    from: &str,
+    to: &str,
+) -> Result<(), String> {
+    use crate::handlers::validation::{bounce_feedback, custom_lists, suppression_list};
+    use crate::{
+        account_settings, branding, organizations, saved_lists, schedules, validation_history,
+        webhooks,
+    };
+
+    let owned: [(Collection<Document>, &str); 11] = [
+        (api_keys_collection(mongo_client).clone_with_type(), "owner"),
+        (
+            saved_lists::saved_lists_collection(mongo_client).clone_with_type(),
+            "owner",
+        ),
+        (
+            schedules::schedules_collection(mongo_client).clone_with_type(),
+            "owner",
+        ),
+        (
+            custom_lists::collection(mongo_client).clone_with_type(),
+            "owner",
+        ),
+        (
+            suppression_list::collection(mongo_client).clone_with_type(),
+            "owner",
+        ),
+        (
+            bounce_feedback::collection(mongo_client).clone_with_type(),
+            "owner",
+        ),
+        (
+            validation_history::history_collection(mongo_client).clone_with_type(),
+            "owner",
+        ),
+        (
+            account_settings::settings_collection(mongo_client).clone_with_type(),
+            "tenant_id",
+        ),
+        (
+            branding::branding_collection(mongo_client).clone_with_type(),
+            "tenant_id",
+        ),
+        (
+            webhooks::secrets_collection(mongo_client).clone_with_type(),
+            "tenant_id",
+        ),
+        (
+            organizations::organizations_collection(mongo_client).clone_with_type(),
+            "members.$.email",
+        ),
+    ];
+    for (collection, field) in &owned {
+        rename_owner(collection, field, from, to).await?;
+    }
+    Ok(())
+}
+
+/// Rewrites `field` from `from` to `to` across a collection
+///
+/// Owner fields hold the email either as given or lowercased, like
+/// [`AuthenticatedAccount::tenant_id`]; each record keeps its form. A positional
+/// `field` such as `members.$.email` is matched on its array path.
+async fn rename_owner(
+    collection: &Collection<Document>,
+    field: &str,
+    from: &str,
+    to: &str,
+) -> Result<(), String> {
+    let mut renames = vec![(from.to_string(), to.to_string())];
+    if from.to_lowercase() != from {
+        renames.push((from.to_lowercase(), to.to_lowercase()));
+    }
+    for (old, new) in renames {
+        let mut filter = Document::new();
+        filter.insert(field.replace(".$", ""), old);
+        let mut set = Document::new();
+        set.insert(field, new);
+        collection
+            .update_many(filter, doc! { "$set": set })
+            .await
+            .map_err(|_| "Database error")?;
+    }
+    Ok(())
+}
+
+async fn replace_key_salt(mongo_client: &Client, email: &str) -> Result<User, String> {
+    let collection = users_collection(mongo_client);
+    let key_salt = uuid::Uuid::new_v4().simple().to_string();
+
+    collection
+        .find_one_and_update(
+            doc! { "email": email, "active": true },
+            doc! {
+                "$set": { "key_salt": &key_salt },
+                "$unset": { "legacy_key_prefix": "" },
+            },
+        )
+        .return_document(mongodb::options::ReturnDocument::After)
+        .await
+        .map_err(|_| "Database error")?
+        .ok_or_else(|| "Account not found".to_string())
+}
+
+/// Account resolved by [`Auth`], stored in the request extensions for handlers and GraphQL
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuthenticatedAccount {
+    pub email: String,
+    /// Id of the key record the request authenticated with; `None` for session tokens
+    /// and legacy password-derived keys
+    pub key_id: Option<String>,
+    /// Plan of that key
+    pub plan: PlanTier,
+    /// Scopes of that key; empty for full access
+    pub scopes: Vec<String>,
+}
+
+impl AuthenticatedAccount {
+    /// Identifier partitioning the account's jobs, history and schedules, as
+    /// [`ApiKey::tenant_id`] of its keys
+    pub fn tenant_id(&self) -> String {
+        self.email.to_lowercase()
+    }
+
+    /// Whether the key holds `scope`; full-access keys hold every scope but
+    /// [`ADMIN_SCOPE`], which must be granted explicitly
+    pub fn has_scope(&self, scope: &str) -> bool {
+        (self.scopes.is_empty() && scope != ADMIN_SCOPE) || self.scopes.iter().any(|s| s == scope)
+    }
+
+    /// Whether the key's scopes open `path` (see [`scope_for_path`])
+    pub fn may_access(&self, path: &str) -> bool {
+        match scope_for_path(path) {
+            Some(scope) => self.has_scope(scope),
+            None => self.scopes.is_empty(),
+        }
+    }
+
+    /// 403 for requests outside the key's scopes
+    pub fn insufficient_scope(&self) -> ApiError {
+        let message = if self.scopes.is_empty() {
+            format!("This route needs an API key with the {} scope", ADMIN_SCOPE)
+        } else {
+            format!("This API key is limited to: {}", self.scopes.join(", "))
+        };
+        ApiError::validation("INSUFFICIENT_SCOPE", message)
+            .with_status(actix_web::http::StatusCode::FORBIDDEN)
+    }
+}
+
+/// Extractor of the [`AuthenticatedAccount`] of a request, so handlers don't verify the
+/// credential again; 401 where [`Auth`] resolved none
+pub struct AuthedAccount(pub AuthenticatedAccount);
+
+impl AuthedAccount {
+    /// For handlers that check their input before the caller
+    pub fn from_http(req: &HttpRequest) -> Result<Self, ApiError> {
+        req.extensions()
+            .get::<AuthenticatedAccount>()
+            .cloned()
+            .map(AuthedAccount)
+            .ok_or_else(|| ApiError::unauthorized("Missing Authorization header"))
+    }
+}
+
+impl FromRequest for AuthedAccount {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, ApiError>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Self::from_http(req))
+    }
+}
+
+impl Deref for AuthedAccount {
+    type Target = AuthenticatedAccount;
+
+    fn deref(&self) -> &AuthenticatedAccount {
+        &self.0
+    }
+}
+
+/// Paths under `/api/v1` reachable without credentials
+const PUBLIC_PATHS: &[&str] = &[
+    "/health",
+    "/health/history",
+    "/ready",
+    "/register",
+    "/verify",
+    "/playground",
+    "/auth/login",
+    "/auth/refresh",
+    "/meta/error-codes",
+    "/error-codes",
+    "/feedback/sns",
+];
+
+/// Whether a path (relative to the `/api/v1` scope) skips authentication
+pub fn is_public_path(path: &str) -> bool {
+    let path = path.strip_prefix("/api/v1").unwrap_or(path);
+    PUBLIC_PATHS.contains(&path.trim_end_matches('/'))
+}
+
 pub struct AuthMiddleware<S> {
-    service: S,
-    mongo_client: Client,
+    service: Rc<S>,
+    mongo_client: Option<Client>,
 }
 
 impl<S, B> Service<ServiceRequest> for AuthMiddleware<S>
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
     type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
 
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let mongo_client = self.mongo_client.clone();
-        let fut = self.service.call(req);
+        let service = Rc::clone(&self.service);
+        let mongo_client = self.mongo_client.clone().or_else(|| {
+            req.app_data::<web::Data<Client>>()
+                .map(|client| client.get_ref().clone())
+        });
 
         Box::pin(async move {
-            let (req, payload) = fut.await?.into_parts();
+            // Unknown routes fall through so they still answer 404
+            let known_route = req.request().resource_map().has_resource(req.path());
+            if is_public_path(req.path()) || !known_route {
+                return service
+                    .call(req)
+                    .await
+                    .map(ServiceResponse::map_into_left_body);
+            }
 
-            let auth_header = req
+            let credential = req
                 .headers()
                 .get("Authorization")
                 .and_then(|h| h.to_str().ok())
-                .and_then(|s| s.strip_prefix("Bearer "));
-
-            if let Some(api_key) = auth_header {
-                match verify_api_key(api_key, &mongo_client).await {
-                    Ok(_) => {
-                        let res = ServiceResponse::new(req, payload);
-                        Ok(res)
-                    }
-                    _ => Err(ErrorUnauthorized("Invalid API key")),
+                .and_then(|s| s.strip_prefix("Bearer "))
+                .map(str::to_string);
+            let Some(credential) = credential else {
+                let err = ApiError::unauthorized("Missing Authorization header");
+                return Ok(req.error_response(err).map_into_right_body());
+            };
+            let Some(mongo_client) = mongo_client else {
+                let err = ApiError::upstream("database", "no client in app data");
+                return Ok(req.error_response(err).map_into_right_body());
+            };
+
+            match authenticate(&credential, &mongo_client).await {
+                Ok(account) if !account.may_access(req.path()) => {
+                    let err = account.insufficient_scope();
+                    Ok(req.error_response(err).map_into_right_body())
+                }
+                Ok(account) => {
+                    req.extensions_mut().insert(account);
+                    service
+                        .call(req)
+                        .await
+                        .map(ServiceResponse::map_into_left_body)
+                }
+                Err(_) => {
+                    let err = ApiError::unauthorized("Invalid API key");
+                    Ok(req.error_response(err).map_into_right_body())
                 }
-            } else {
-                Err(ErrorUnauthorized("Missing Authorization header"))
             }
         })
     }
 }
 
+/// Requires a session token or API key on every route except [`is_public_path`] ones,
+/// and refuses scoped keys outside their [`KEY_SCOPES`]
+///
+/// Built with [`Auth::new`] it uses the given client; [`Auth::from_app_data`] looks up
+/// the `web::Data<Client>` registered on the app at request time.
 pub struct Auth {
-    mongo_client: Client,
+    mongo_client: Option<Client>,
 }
 
 impl Auth {
     pub fn new(mongo_client: Client) -> Self {
-        Self { mongo_client }
+        Self {
+            mongo_client: Some(mongo_client),
+        }
+    }
+
+    pub fn from_app_data() -> Self {
+        Self { mongo_client: None }
     }
 }
 
 impl<S, B> Transform<S, ServiceRequest> for Auth
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
     type InitError = ();
     type Transform = AuthMiddleware<S>;
@@ -152,12 +1318,24 @@ where
 
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(AuthMiddleware {
-            service,
+            service: Rc::new(service),
             mongo_client: self.mongo_client.clone(),
         }))
     }
 }
 
+/// Test stand-in for [`Auth`]: any bearer token authenticates `user@example.com` with
+/// a full-access key
+#[cfg(test)]
+pub(crate) fn accept_any_bearer(req: &ServiceRequest) {
+    if req.headers().contains_key("Authorization") {
+        req.extensions_mut().insert(AuthenticatedAccount {
+            email: "user@example.com".to_string(),
+            ..AuthenticatedAccount::default()
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -200,7 +1378,8 @@ mod tests {
         let auth = Auth::new(mongo_client.clone());
 
         // Test that Auth struct is created successfully
-        assert_eq!(std::ptr::eq(&auth.mongo_client, &mongo_client), false); // Different Arc instances
+        assert!(auth.mongo_client.is_some());
+        assert!(Auth::from_app_data().mongo_client.is_none());
     }
 
     async fn create_test_mongo_client() -> MongoClient {
@@ -218,18 +1397,146 @@ mod tests {
         let api_key = ApiKey {
             key: "test-key".to_string(),
             active: true,
+            owner: None,
+            plan: PlanTier::Free,
+            ..ApiKey::default()
         };
 
         assert_eq!(api_key.key, "test-key");
         assert_eq!(api_key.active, true);
     }
 
+    #[test]
+    fn test_hash_api_key() {
+        let hash = hash_api_key("esk_example");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_api_key("esk_example"));
+        assert_ne!(hash, hash_api_key("esk_other"));
+    }
+
+    #[test]
+    fn test_hashed_key_record_omits_plaintext() {
+        let record = ApiKey {
+            active: true,
+            key_id: Some("id-1".to_string()),
+            key_hash: Some(hash_api_key("esk_example")),
+            ..ApiKey::default()
+        };
+
+        let json = serde_json::to_value(&record).unwrap();
+        assert!(json.get("key").is_none());
+        assert_eq!(json["revoked"], false);
+    }
+
+    #[actix_web::test]
+    async fn test_authed_account_extractor() {
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        assert!(AuthedAccount::extract(&req).await.is_err());
+
+        req.extensions_mut().insert(AuthenticatedAccount {
+            email: "User@Example.com".to_string(),
+            key_id: Some("key-1".to_string()),
+            scopes: vec!["validate".to_string()],
+            ..AuthenticatedAccount::default()
+        });
+        let account = AuthedAccount::extract(&req).await.unwrap();
+        assert_eq!(account.tenant_id(), "user@example.com");
+        assert_eq!(account.key_id.as_deref(), Some("key-1"));
+        assert!(account.has_scope("validate"));
+        assert!(!account.has_scope("admin"));
+        assert!(account.may_access("/api/v1/validate-emails-bulk"));
+        assert!(account.may_access("/api/v2/validate-email"));
+        assert!(!account.may_access("/api/v1/job-status/job-1"));
+        assert!(!account.may_access("/api/v1/keys"));
+        assert!(!account.may_access("/api/v1/graphql"));
+        assert_eq!(account.insufficient_scope().code(), "INSUFFICIENT_SCOPE");
+    }
+
+    #[test]
+    fn test_admin_scope_is_never_implied() {
+        let full_access = AuthenticatedAccount::default();
+        assert!(full_access.has_scope("jobs"));
+        assert!(!full_access.has_scope(ADMIN_SCOPE));
+        assert!(full_access.may_access("/api/v1/keys"));
+        assert!(!full_access.may_access("/api/v1/admin/queue"));
+
+        let admin = AuthenticatedAccount {
+            scopes: vec![ADMIN_SCOPE.to_string()],
+            ..AuthenticatedAccount::default()
+        };
+        assert!(admin.may_access("/api/v1/admin/queue"));
+        assert!(!admin.may_access("/api/v1/validate-email"));
+    }
+
+    #[test]
+    fn test_parse_api_key() {
+        let key_id = "0123456789abcdef0123456789abcdef";
+        let secret = "ab".repeat(32);
+        let api_key = format!("esk_{}_{}", key_id, secret);
+        assert_eq!(parse_api_key(&api_key), Some((key_id, secret.as_str())));
+
+        // Keys in the older formats
+        assert_eq!(parse_api_key(&format!("esk_{}{}", key_id, key_id)), None);
+        assert_eq!(parse_api_key("0123456789abcdef.header.claims.sig"), None);
+        assert_eq!(
+            parse_api_key(&format!("esk_{}_{}", key_id, "zz".repeat(32))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_legacy_grace() {
+        let now = Utc::now();
+        assert!(legacy_grace(Some("2999-01-01T00:00:00Z"), now));
+        assert!(!legacy_grace(Some("2020-01-01T00:00:00Z"), now));
+
+        let before = "2027-01-14T23:59:59Z".parse::<DateTime<Utc>>().unwrap();
+        let after = "2027-01-15T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert!(legacy_grace(None, before));
+        assert!(legacy_grace(Some("not a date"), before));
+        assert!(!legacy_grace(None, after));
+        assert!(!legacy_grace(Some("not a date"), after));
+        assert!(legacy_grace(Some("2027-06-01T00:00:00Z"), after));
+    }
+
+    #[tokio::test]
+    async fn test_find_api_key_rejects_empty_key() {
+        let mongo_client = create_test_mongo_client().await;
+        assert!(find_api_key(&mongo_client, "").await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_plan_tier_queue_weights() {
+        assert!(PlanTier::Free.queue_weight() < PlanTier::Pro.queue_weight());
+        assert!(PlanTier::Pro.queue_weight() < PlanTier::Enterprise.queue_weight());
+        assert_eq!(PlanTier::default(), PlanTier::Free);
+    }
+
+    #[test]
+    fn test_api_key_tenant_id() {
+        let owned = ApiKey {
+            key: "secret".to_string(),
+            active: true,
+            owner: Some("Owner@Example.com".to_string()),
+            plan: PlanTier::Pro,
+            ..ApiKey::default()
+        };
+        assert_eq!(owned.tenant_id(), "owner@example.com");
+
+        let legacy: ApiKey = serde_json::from_str(r#"{"key":"secret","active":true}"#).unwrap();
+        assert_eq!(legacy.plan, PlanTier::Free);
+        assert!(legacy.tenant_id().starts_with("key-"));
+        assert!(!legacy.tenant_id().contains("secret"));
+    }
+
     #[test]
     fn test_user_struct() {
         let user = User {
             email: "test@example.com".to_string(),
             password_hash: "hashed-password".to_string(),
             active: true,
+            key_salt: String::new(),
+            ..User::default()
         };
 
         assert_eq!(user.email, "test@example.com");
@@ -237,6 +1544,108 @@ mod tests {
         assert_eq!(user.active, true);
     }
 
+    #[test]
+    fn test_key_salt_changes_key_secret() {
+        let mut user = User {
+            email: "test@example.com".to_string(),
+            password_hash: "hashed-password".to_string(),
+            active: true,
+            key_salt: String::new(),
+            ..User::default()
+        };
+        // Accounts created before key rotation derive their keys from the hash alone
+        assert_eq!(user.key_secret(), "hashed-password");
+
+        let legacy: User = serde_json::from_str(
+            r#"{"email":"test@example.com","password_hash":"hashed-password","active":true}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.key_secret(), user.key_secret());
+        // Records from before Argon2id carry bcrypt hashes
+        assert_eq!(legacy.password_scheme, PasswordScheme::Bcrypt);
+
+        user.key_salt = "rotated".to_string();
+        assert_ne!(legacy.key_secret(), user.key_secret());
+    }
+
+    #[test]
+    fn test_account_email_is_checked_and_normalized() {
+        assert_eq!(
+            account_email("  New.User@Example.COM ").unwrap(),
+            "New.User@example.com"
+        );
+        assert_eq!(
+            account_email("Someone@Gmail.com").unwrap(),
+            "someone@gmail.com"
+        );
+        for invalid in ["", "not-an-email", "user@", "user@@example.com"] {
+            assert_eq!(
+                account_email(invalid),
+                Err("Invalid email address".to_string())
+            );
+        }
+        assert_eq!(
+            ApiError::from_auth("Invalid email address").code(),
+            "INVALID_EMAIL"
+        );
+    }
+
+    #[test]
+    fn test_session_tokens_are_typed() {
+        unsafe {
+            std::env::set_var("JWT_SECRET", "test-secret-key-for-testing");
+        }
+        let user = User {
+            email: "session@example.com".to_string(),
+            password_hash: "hash".to_string(),
+            active: true,
+            key_salt: String::new(),
+            ..User::default()
+        };
+
+        let tokens = issue_session(&user).unwrap();
+        assert_eq!(tokens.token_type, "Bearer");
+        assert_eq!(
+            verify_session_token(&tokens.access_token).unwrap(),
+            "session@example.com"
+        );
+        // A refresh token must not be usable as an access token, nor the reverse
+        assert!(verify_session_token(&tokens.refresh_token).is_err());
+        assert!(decode_session(&tokens.access_token, REFRESH_TOKEN_TYPE).is_err());
+        assert!(verify_session_token("not-a-token").is_err());
+    }
+
+    #[test]
+    fn test_password_fingerprint_changes_with_password() {
+        assert_eq!(
+            password_fingerprint("hash-a"),
+            password_fingerprint("hash-a")
+        );
+        assert_ne!(
+            password_fingerprint("hash-a"),
+            password_fingerprint("hash-b")
+        );
+    }
+
+    #[test]
+    fn test_public_paths() {
+        assert!(is_public_path("/api/v1/health"));
+        assert!(is_public_path("/api/v1/health/history"));
+        assert!(is_public_path("/api/v1/ready"));
+        assert!(is_public_path("/api/v1/register"));
+        assert!(is_public_path("/api/v1/verify"));
+        assert!(is_public_path("/api/v1/playground"));
+        assert!(is_public_path("/api/v1/auth/login"));
+        assert!(is_public_path("/api/v1/meta/error-codes"));
+        assert!(is_public_path("/api/v1/error-codes"));
+        assert!(is_public_path("/api/v1/feedback/sns"));
+        assert!(!is_public_path("/api/v1/feedback/bounces"));
+        assert!(!is_public_path("/api/v1/admin/cache/stats"));
+        assert!(!is_public_path("/api/v1/graphql"));
+        assert!(!is_public_path("/api/v1/validate-emails-bulk"));
+        assert!(!is_public_path("/api/v1/registerx"));
+    }
+
     #[test]
     fn test_claims_struct() {
         let claims = Claims {
@@ -0,0 +1,152 @@
+use crate::routes::status::escape_html;
+use chrono::Utc;
+use mongodb::{Client, Collection, bson::doc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Display names longer than this are rejected
+pub const MAX_NAME_LENGTH: usize = 80;
+
+pub const MAX_LOGO_URL_LENGTH: usize = 2048;
+
+/// A tenant's name and logo, shown on artifacts generated for it such as shared
+/// result pages
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Branding {
+    /// Tenant the branding applies to, the same ID bulk jobs are filed under
+    pub tenant_id: String,
+    pub name: Option<String>,
+    /// HTTPS URL of the logo image
+    pub logo_url: Option<String>,
+    /// Unix timestamp of the last change
+    pub updated_at: i64,
+}
+
+impl Branding {
+    /// Checks and trims a name and logo URL; at least one is required
+    pub fn new(
+        tenant_id: &str,
+        name: Option<&str>,
+        logo_url: Option<&str>,
+    ) -> Result<Self, String> {
+        let name = name.map(str::trim).filter(|name| !name.is_empty());
+        let logo_url = logo_url.map(str::trim).filter(|url| !url.is_empty());
+
+        if name.is_none() && logo_url.is_none() {
+            return Err("Provide a name, a logo_url or both".to_string());
+        }
+        if name.is_some_and(|name| name.chars().count() > MAX_NAME_LENGTH) {
+            return Err(format!("Names are at most {} characters", MAX_NAME_LENGTH));
+        }
+        if let Some(url) = logo_url
+            && (!url.starts_with("https://")
+                || url.len() > MAX_LOGO_URL_LENGTH
+                || url.chars().any(|c| c.is_whitespace() || c.is_control()))
+        {
+            return Err(format!(
+                "logo_url must be an https:// URL of at most {} characters",
+                MAX_LOGO_URL_LENGTH
+            ));
+        }
+
+        Ok(Self {
+            tenant_id: tenant_id.to_string(),
+            name: name.map(str::to_string),
+            logo_url: logo_url.map(str::to_string),
+            updated_at: Utc::now().timestamp(),
+        })
+    }
+
+    /// Page header with the logo and name, escaped for HTML
+    pub fn header_html(&self) -> String {
+        let name = self.name.as_deref().map(escape_html).unwrap_or_default();
+        let mut html = String::from("<header class=\"brand\">");
+        if let Some(logo_url) = &self.logo_url {
+            html.push_str(&format!(
+                "<img src=\"{}\" alt=\"{}\" height=\"40\">",
+                escape_html(logo_url),
+                name
+            ));
+        }
+        if !name.is_empty() {
+            html.push_str(&format!("<strong>{}</strong>", name));
+        }
+        html.push_str("</header>\n");
+        html
+    }
+}
+
+/// Reads `DB_NAME_PRODUCTION` (default `email_sanitizer`) and `DB_BRANDING_COLLECTION`
+/// (default `branding`)
+pub fn branding_collection(mongo_client: &Client) -> Collection<Branding> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let collection_name =
+        std::env::var("DB_BRANDING_COLLECTION").unwrap_or_else(|_| "branding".to_string());
+    mongo_client.database(&db_name).collection(&collection_name)
+}
+
+pub async fn load_branding(
+    mongo_client: &Client,
+    tenant_id: &str,
+) -> Result<Option<Branding>, String> {
+    branding_collection(mongo_client)
+        .find_one(doc! { "tenant_id": tenant_id })
+        .await
+        .map_err(|_| "Database error".to_string())
+}
+
+/// Replaces the tenant's branding
+pub async fn save_branding(mongo_client: &Client, branding: &Branding) -> Result<(), String> {
+    branding_collection(mongo_client)
+        .replace_one(doc! { "tenant_id": &branding.tenant_id }, branding)
+        .upsert(true)
+        .await
+        .map_err(|_| "Database error".to_string())?;
+    Ok(())
+}
+
+/// Removes the tenant's branding; `false` if none was set
+pub async fn remove_branding(mongo_client: &Client, tenant_id: &str) -> Result<bool, String> {
+    let result = branding_collection(mongo_client)
+        .delete_one(doc! { "tenant_id": tenant_id })
+        .await
+        .map_err(|_| "Database error".to_string())?;
+    Ok(result.deleted_count > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branding_validation() {
+        let branding = Branding::new(
+            "owner@example.com",
+            Some("  Acme Agency "),
+            Some("https://cdn.example.com/logo.png"),
+        )
+        .unwrap();
+        assert_eq!(branding.name.as_deref(), Some("Acme Agency"));
+
+        assert!(Branding::new("t", None, Some("  ")).is_err());
+        assert!(Branding::new("t", None, Some("http://example.com/logo.png")).is_err());
+        assert!(Branding::new("t", None, Some("https://example.com/a b.png")).is_err());
+        assert!(Branding::new("t", Some(&"x".repeat(MAX_NAME_LENGTH + 1)), None).is_err());
+    }
+
+    #[test]
+    fn test_header_is_escaped() {
+        let branding = Branding::new(
+            "t",
+            Some("<Acme & Co>"),
+            Some("https://example.com/logo.png?a=1&b=\"2\""),
+        )
+        .unwrap();
+        let html = branding.header_html();
+
+        assert!(html.contains("<strong>&lt;Acme &amp; Co&gt;</strong>"));
+        assert!(html.contains("src=\"https://example.com/logo.png?a=1&amp;b=&quot;2&quot;\""));
+        assert!(!html.contains("<Acme"));
+    }
+}
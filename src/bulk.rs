@@ -0,0 +1,359 @@
+use crate::handlers::validation::syntax;
+use crate::normalize::base_address;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+pub use crate::normalize::normalize_email;
+
+/// Largest batch accepted by `max_batch_size` when `BULK_MAX_BATCH_SIZE` is unset
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 10_000;
+
+/// Maximum number of addresses accepted in one bulk request (`BULK_MAX_BATCH_SIZE`)
+pub fn max_batch_size() -> usize {
+    std::env::var("BULK_MAX_BATCH_SIZE")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|size| *size > 0)
+        .unwrap_or(DEFAULT_MAX_BATCH_SIZE)
+}
+
+/// A bulk input reduced to its unique addresses
+///
+/// Each unique address is validated once; [`DedupedBatch::fan_out`] then maps the
+/// results back so every input position, duplicates included, gets its verdict.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DedupedBatch {
+    /// Normalized addresses in order of first appearance
+    pub unique: Vec<String>,
+    /// For every input position, the index of its address in `unique`
+    positions: Vec<usize>,
+}
+
+impl DedupedBatch {
+    pub fn new(emails: &[String]) -> Self {
+        Self::with_keys(emails, &vec![(); emails.len()]).0
+    }
+
+    /// Like [`DedupedBatch::new`], but repeats of an address only share a result when
+    /// their keys (e.g. the checks to run) are equal too; also returns the key of each
+    /// unique entry
+    pub fn with_keys<K: Clone + Eq + Hash>(emails: &[String], keys: &[K]) -> (Self, Vec<K>) {
+        Self::keyed(emails, keys, normalize_email)
+    }
+
+    /// Like [`DedupedBatch::with_keys`], but a well-formed sub-addressed address
+    /// (`user+news@gmail.com`) is a repeat of its base address, which is validated in
+    /// its place
+    pub fn merging_tags<K: Clone + Eq + Hash>(emails: &[String], keys: &[K]) -> (Self, Vec<K>) {
+        Self::keyed(emails, keys, |email| {
+            base_address(email)
+                .filter(|_| syntax::is_valid_email(email))
+                .unwrap_or_else(|| normalize_email(email))
+        })
+    }
+
+    fn keyed<K: Clone + Eq + Hash>(
+        emails: &[String],
+        keys: &[K],
+        canonical: impl Fn(&str) -> String,
+    ) -> (Self, Vec<K>) {
+        let mut index: HashMap<(String, K), usize> = HashMap::with_capacity(emails.len());
+        let mut unique = Vec::new();
+        let mut unique_keys = Vec::new();
+        let positions = emails
+            .iter()
+            .zip(keys)
+            .map(|(email, key)| {
+                let entry = (canonical(email), key.clone());
+                *index.entry(entry).or_insert_with_key(|(normalized, key)| {
+                    unique.push(normalized.clone());
+                    unique_keys.push(key.clone());
+                    unique.len() - 1
+                })
+            })
+            .collect();
+
+        (Self { unique, positions }, unique_keys)
+    }
+
+    /// Number of input positions
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Quota units the batch is charged: one per validation it runs, so repeats of an
+    /// address are counted once
+    pub fn units(&self) -> u64 {
+        self.unique.len() as u64
+    }
+
+    /// Expands one result per unique address into one result per input position
+    pub fn fan_out<T: Clone>(&self, results: &[T]) -> Vec<T> {
+        self.positions.iter().map(|&i| results[i].clone()).collect()
+    }
+}
+
+/// Results of the validations run during one request, keyed by normalized address
+///
+/// Web forms often submit the same address twice (e.g. email and confirm-email fields
+/// joined upstream). Concurrent lookups of the same address wait for the first one,
+/// so each distinct address is validated, and counted, once per request.
+pub struct ValidationMemo<T> {
+    entries: Mutex<HashMap<MemoKey, Arc<OnceCell<T>>>>,
+}
+
+/// Normalized address and whether role-based checking was requested
+type MemoKey = (String, bool);
+
+impl<T> Default for ValidationMemo<T> {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: Clone> ValidationMemo<T> {
+    /// Returns the memoized result for the address, running `validate` on first use
+    ///
+    /// Failed validations are not memoized, so a later lookup retries them.
+    pub async fn get_or_validate<F, Fut, E>(
+        &self,
+        email: &str,
+        check_role_based: bool,
+        validate: F,
+    ) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<T, E>>,
+    {
+        let cell = self
+            .entries
+            .lock()
+            .unwrap()
+            .entry((normalize_email(email), check_role_based))
+            .or_default()
+            .clone();
+        cell.get_or_try_init(validate).await.cloned()
+    }
+
+    /// Number of distinct validations completed in this request
+    pub fn validations(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|cell| cell.initialized())
+            .count()
+    }
+}
+
+/// Domain-level check whose verdict [`DomainMemo`] shares
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DomainCheck {
+    Dns,
+    Disposable,
+}
+
+/// Domain-level verdicts shared by every address of one bulk request or job
+///
+/// Uploaded lists hold thousands of addresses across a few hundred domains, and the DNS
+/// and disposable checks depend only on the domain. Each runs once per domain; concurrent
+/// validations of addresses at the same domain wait for the first lookup.
+#[derive(Default)]
+pub struct DomainMemo {
+    entries: Mutex<HashMap<DomainKey, Arc<OnceCell<bool>>>>,
+}
+
+/// Check and lowercased domain
+type DomainKey = (DomainCheck, String);
+
+impl DomainMemo {
+    /// Returns the memoized verdict of `check` for `domain`, running `lookup` on first use
+    ///
+    /// Failed lookups are not memoized, so the next address at the domain retries them.
+    pub async fn get_or_check<F, Fut>(
+        &self,
+        check: DomainCheck,
+        domain: &str,
+        lookup: F,
+    ) -> Result<bool, String>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<bool, String>>,
+    {
+        let cell = self
+            .entries
+            .lock()
+            .unwrap()
+            .entry((check, domain.to_ascii_lowercase()))
+            .or_default()
+            .clone();
+        cell.get_or_try_init(lookup).await.copied()
+    }
+
+    /// Number of lookups of `check` completed so far
+    pub fn lookups(&self, check: DomainCheck) -> usize {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((kind, _), cell)| *kind == check && cell.initialized())
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_email() {
+        assert_eq!(normalize_email("  User@Example.COM "), "User@example.com");
+        assert_eq!(normalize_email("not-an-email"), "not-an-email");
+    }
+
+    #[test]
+    fn test_dedup_and_fan_out() {
+        let emails: Vec<String> = [
+            "a@example.com",
+            "b@example.com",
+            " a@EXAMPLE.com",
+            "A@example.com",
+        ]
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+        let batch = DedupedBatch::new(&emails);
+
+        assert_eq!(
+            batch.unique,
+            vec!["a@example.com", "b@example.com", "A@example.com"]
+        );
+        assert_eq!(batch.len(), 4);
+        assert_eq!(batch.fan_out(&[1, 2, 3]), vec![1, 2, 1, 3]);
+    }
+
+    #[test]
+    fn test_repeated_address_is_charged_once() {
+        // Email and confirm-email fields joined upstream
+        let emails: Vec<String> = ["user@example.com", " user@EXAMPLE.com", "other@example.com"]
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        let (batch, _) = DedupedBatch::with_keys(&emails, &[false; 3]);
+        assert_eq!(batch.units(), 2);
+
+        // Repeats validated with other checks are charged for those checks
+        let (batch, _) = DedupedBatch::with_keys(&emails, &[false, true, false]);
+        assert_eq!(batch.units(), 3);
+    }
+
+    #[test]
+    fn test_dedup_with_keys() {
+        let emails: Vec<String> = ["a@example.com", "a@EXAMPLE.com", "a@example.com"]
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        let (batch, keys) = DedupedBatch::with_keys(&emails, &[false, true, false]);
+
+        assert_eq!(batch.unique, vec!["a@example.com", "a@example.com"]);
+        assert_eq!(keys, vec![false, true]);
+        assert_eq!(batch.fan_out(&[1, 2]), vec![1, 2, 1]);
+    }
+
+    #[test]
+    fn test_dedup_merging_tags() {
+        let emails: Vec<String> = [
+            "jane+news@gmail.com",
+            "jane@gmail.com",
+            "jane-shop@yahoo.com",
+            "jane@yahoo.com",
+            "jane+x y@gmail.com",
+        ]
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+        let (batch, _) = DedupedBatch::merging_tags(&emails, &[(); 5]);
+
+        // A malformed address keeps its own result
+        assert_eq!(
+            batch.unique,
+            vec!["jane@gmail.com", "jane@yahoo.com", "jane+x y@gmail.com"]
+        );
+        assert_eq!(batch.fan_out(&[1, 2, 3]), vec![1, 1, 2, 2, 3]);
+
+        let (batch, _) = DedupedBatch::with_keys(&emails, &[(); 5]);
+        assert_eq!(batch.unique.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_memo_validates_each_address_once() {
+        let memo = ValidationMemo::default();
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let validate = || async {
+            calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok::<_, String>(true)
+        };
+
+        let (a, b) = tokio::join!(
+            memo.get_or_validate("user@example.com", false, validate),
+            memo.get_or_validate(" user@EXAMPLE.com", false, validate)
+        );
+        assert_eq!((a, b), (Ok(true), Ok(true)));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // Role-based checking changes the verdict, so it is memoized separately
+        let _ = memo
+            .get_or_validate("user@example.com", true, validate)
+            .await;
+        assert_eq!(memo.validations(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_memo_retries_failures() {
+        let memo = ValidationMemo::<bool>::default();
+        let failed = memo
+            .get_or_validate("user@example.com", false, || async { Err("down") })
+            .await;
+        assert_eq!(failed, Err("down"));
+        assert_eq!(memo.validations(), 0);
+
+        let retried = memo
+            .get_or_validate("user@example.com", false, || async { Ok::<_, &str>(true) })
+            .await;
+        assert_eq!(retried, Ok(true));
+    }
+
+    #[tokio::test]
+    async fn test_domain_memo_checks_each_domain_once() {
+        let memo = DomainMemo::default();
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let lookup = || async {
+            calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(true)
+        };
+
+        let (a, b) = tokio::join!(
+            memo.get_or_check(DomainCheck::Dns, "example.com", lookup),
+            memo.get_or_check(DomainCheck::Dns, "EXAMPLE.com", lookup)
+        );
+        assert_eq!((a, b), (Ok(true), Ok(true)));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let failed = memo
+            .get_or_check(DomainCheck::Disposable, "example.com", || async {
+                Err("database down".to_string())
+            })
+            .await;
+        assert!(failed.is_err());
+        assert_eq!(memo.lookups(DomainCheck::Dns), 1);
+        assert_eq!(memo.lookups(DomainCheck::Disposable), 0);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::handlers::validation::dnsmx::{self, DnsOutcome};
+use crate::stores::{CacheStore, WriteBatch};
+use redis::RedisError;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Pipeline stage migrating from the blocking resolver to async DNS lookups
+pub const DNS_ASYNC_STAGE: &str = "dns_async";
+
+/// Whether a key falls into the canary share of traffic
+///
+/// Routing is sticky: the same key (a domain) always takes the same path for a given
+/// percentage, so verdicts for one domain don't flip between requests.
+pub fn routes_to_canary(key: &str, percent: u8) -> bool {
+    if percent == 0 {
+        return false;
+    }
+    let digest = Sha256::digest(key.to_lowercase().as_bytes());
+    let bucket = u16::from_be_bytes([digest[0], digest[1]]) % 100;
+    bucket < u16::from(percent.min(100))
+}
+
+/// Comparison counters of one canary stage, reported by the admin API
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct CanaryStats {
+    pub stage: String,
+    pub percent: u8,
+    pub requests: u64,
+    pub mismatches: u64,
+    pub mismatch_rate: f64,
+    pub avg_legacy_ms: f64,
+    pub avg_canary_ms: f64,
+}
+
+impl CanaryStats {
+    fn from_counters(stage: &str, percent: u8, counters: &HashMap<String, u64>) -> Self {
+        let get = |field: &str| counters.get(field).copied().unwrap_or(0);
+        let requests = get("requests");
+        let ratio = |total: u64| {
+            if requests == 0 {
+                0.0
+            } else {
+                total as f64 / requests as f64
+            }
+        };
+
+        CanaryStats {
+            stage: stage.to_string(),
+            percent,
+            requests,
+            mismatches: get("mismatches"),
+            mismatch_rate: ratio(get("mismatches")),
+            avg_legacy_ms: ratio(get("legacy_ms")),
+            avg_canary_ms: ratio(get("canary_ms")),
+        }
+    }
+}
+
+/// Routes a configurable share of validations through new pipeline implementations
+///
+/// Canary requests run both implementations side by side and serve the new one's
+/// verdict; disagreements and latencies are counted in the store (`canary:stats:{stage}`)
+/// so a refactor can be compared against the old path before it takes all traffic.
+#[derive(Clone, Default)]
+pub struct CanaryRouter {
+    store: Option<Arc<dyn CacheStore>>,
+    /// Share of domains (0-100) checked by the async DNS resolver
+    pub dns_async_percent: u8,
+}
+
+impl CanaryRouter {
+    pub fn from_store(store: Arc<dyn CacheStore>, dns_async_percent: u8) -> Self {
+        Self {
+            store: Some(store),
+            dns_async_percent: dns_async_percent.min(100),
+        }
+    }
+
+    /// Reads `CANARY_DNS_ASYNC_PERCENT` (default 0, capped at 100)
+    pub fn dns_async_percent_from_env() -> u8 {
+        std::env::var("CANARY_DNS_ASYNC_PERCENT")
+            .ok()
+            .and_then(|v| v.trim().parse::<u8>().ok())
+            .unwrap_or(0)
+            .min(100)
+    }
+
+    fn stats_key(stage: &str) -> String {
+        format!("canary:stats:{}", stage)
+    }
+
+    /// DNS/MX check, through the async resolver for the canary share of domains
+    pub async fn check_dns(&self, email: &str) -> DnsOutcome {
+        let domain = email.rsplit_once('@').map(|(_, d)| d).unwrap_or(email);
+
+        let legacy = async {
+            let started = Instant::now();
+            let email = email.to_owned();
+            let outcome = actix_web::web::block(move || dnsmx::resolve_email_dns(&email))
+                .await
+                .unwrap_or(DnsOutcome::Unavailable);
+            (outcome, started.elapsed().as_millis() as u64)
+        };
+        if !routes_to_canary(domain, self.dns_async_percent) {
+            return legacy.await.0;
+        }
+
+        let canary = async {
+            let started = Instant::now();
+            let outcome = dnsmx::resolve_email_dns_async(email).await;
+            (outcome, started.elapsed().as_millis() as u64)
+        };
+        let ((legacy_outcome, legacy_ms), (canary_outcome, canary_ms)) =
+            tokio::join!(legacy, canary);
+
+        self.record(
+            DNS_ASYNC_STAGE,
+            legacy_outcome == canary_outcome,
+            legacy_ms,
+            canary_ms,
+        )
+        .await;
+        canary_outcome
+    }
+
+    /// Counts one side-by-side run; metrics are best effort and never fail a validation
+    async fn record(&self, stage: &str, matched: bool, legacy_ms: u64, canary_ms: u64) {
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        let key = Self::stats_key(stage);
+        let mut counters = WriteBatch::default();
+        counters
+            .hincr_by(&key, "requests", 1)
+            .hincr_by(&key, "mismatches", u64::from(!matched))
+            .hincr_by(&key, "legacy_ms", legacy_ms)
+            .hincr_by(&key, "canary_ms", canary_ms);
+        let _ = store.write(&counters).await;
+    }
+
+    /// Counters of the async DNS canary
+    pub async fn dns_async_stats(&self) -> Result<CanaryStats, RedisError> {
+        let store = match &self.store {
+            Some(store) => store,
+            None => {
+                return Ok(CanaryStats::from_counters(
+                    DNS_ASYNC_STAGE,
+                    self.dns_async_percent,
+                    &HashMap::new(),
+                ));
+            }
+        };
+        let counters: HashMap<String, u64> = store
+            .hgetall(&Self::stats_key(DNS_ASYNC_STAGE))
+            .await?
+            .into_iter()
+            .filter_map(|(field, count)| Some((field, count.parse().ok()?)))
+            .collect();
+        Ok(CanaryStats::from_counters(
+            DNS_ASYNC_STAGE,
+            self.dns_async_percent,
+            &counters,
+        ))
+    }
+
+    /// Clears a stage's counters, e.g. after changing its percentage
+    pub async fn reset_dns_async_stats(&self) -> Result<(), RedisError> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        store
+            .del(&[Self::stats_key(DNS_ASYNC_STAGE)])
+            .await
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canary_routing_bounds() {
+        assert!(!routes_to_canary("example.com", 0));
+        assert!(routes_to_canary("example.com", 100));
+        assert!(routes_to_canary("example.com", 255));
+    }
+
+    #[test]
+    fn test_canary_routing_is_sticky_and_proportional() {
+        assert_eq!(
+            routes_to_canary("Example.COM", 30),
+            routes_to_canary("example.com", 30)
+        );
+
+        let routed = (0..1000)
+            .filter(|i| routes_to_canary(&format!("domain{}.test", i), 25))
+            .count();
+        assert!((150..350).contains(&routed), "routed {} of 1000", routed);
+    }
+
+    #[test]
+    fn test_stats_from_counters() {
+        let counters = HashMap::from([
+            ("requests".to_string(), 4),
+            ("mismatches".to_string(), 1),
+            ("legacy_ms".to_string(), 400),
+            ("canary_ms".to_string(), 100),
+        ]);
+        let stats = CanaryStats::from_counters(DNS_ASYNC_STAGE, 10, &counters);
+
+        assert_eq!(stats.mismatch_rate, 0.25);
+        assert_eq!(stats.avg_legacy_ms, 100.0);
+        assert_eq!(stats.avg_canary_ms, 25.0);
+        assert_eq!(
+            CanaryStats::from_counters(DNS_ASYNC_STAGE, 10, &HashMap::new()).mismatch_rate,
+            0.0
+        );
+    }
+}
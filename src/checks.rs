@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A validation stage that can be chosen per address; syntax is always checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    /// Known spamtrap, complainer and bouncer hashes
+    Suppression,
+    /// Registered top-level domain
+    Tld,
+    /// MX, A or AAAA records for the domain
+    Dns,
+    /// Role-based local parts such as `admin@`
+    RoleBased,
+    /// Disposable email providers
+    Disposable,
+}
+
+/// The stages run after the syntax check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Checks {
+    pub suppression: bool,
+    pub tld: bool,
+    pub dns: bool,
+    pub role_based: bool,
+    pub disposable: bool,
+}
+
+impl Checks {
+    /// Every stage, with role-based detection only when requested
+    pub fn standard(check_role_based: bool) -> Self {
+        Checks {
+            suppression: true,
+            tld: true,
+            dns: true,
+            role_based: check_role_based,
+            disposable: true,
+        }
+    }
+
+    /// Exactly the listed stages
+    pub fn only(checks: &[Check]) -> Self {
+        Checks {
+            suppression: checks.contains(&Check::Suppression),
+            tld: checks.contains(&Check::Tld),
+            dns: checks.contains(&Check::Dns),
+            role_based: checks.contains(&Check::RoleBased),
+            disposable: checks.contains(&Check::Disposable),
+        }
+    }
+
+    /// Whether every stage except the optional role-based one runs
+    ///
+    /// Only such results are read from and written to the validation cache, whose keys
+    /// tell apart nothing but the role-based flag.
+    pub fn is_standard(&self) -> bool {
+        self.suppression && self.tld && self.dns && self.disposable
+    }
+}
+
+/// Validation options of one bulk entry, overriding those of the request
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ItemOptions {
+    /// Turns role-based detection on or off for this entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check_role_based: Option<bool>,
+    /// Stages to run instead of all of them; syntax is always checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checks: Option<Vec<Check>>,
+}
+
+impl ItemOptions {
+    /// Whether the entry just follows the request's options
+    pub fn is_default(&self) -> bool {
+        self.check_role_based.is_none() && self.checks.is_none()
+    }
+
+    /// These options, with each one left unset taken from `defaults`
+    pub fn with_defaults(&self, defaults: &ItemOptions) -> ItemOptions {
+        ItemOptions {
+            check_role_based: self.check_role_based.or(defaults.check_role_based),
+            checks: self.checks.clone().or_else(|| defaults.checks.clone()),
+        }
+    }
+
+    /// The stages to run for this entry of a request with the given `check_role_based`
+    ///
+    /// `checks` replaces the standard stages, role-based included; `check_role_based`
+    /// then switches role-based detection on or off.
+    pub fn checks(&self, check_role_based: bool) -> Checks {
+        let mut checks = match &self.checks {
+            Some(checks) => Checks::only(checks),
+            None => Checks::standard(check_role_based),
+        };
+        if let Some(check_role_based) = self.check_role_based {
+            checks.role_based = check_role_based;
+        }
+        checks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_item_checks() {
+        assert_eq!(ItemOptions::default().checks(true), Checks::standard(true));
+
+        let role_off = ItemOptions {
+            check_role_based: Some(false),
+            checks: None,
+        };
+        assert_eq!(role_off.checks(true), Checks::standard(false));
+        assert!(role_off.checks(true).is_standard());
+
+        let syntax_and_dns = ItemOptions {
+            check_role_based: None,
+            checks: Some(vec![Check::Dns]),
+        };
+        let checks = syntax_and_dns.checks(true);
+        assert!(checks.dns && !checks.role_based && !checks.disposable);
+        assert!(!checks.is_standard());
+    }
+
+    #[test]
+    fn test_with_defaults() {
+        let defaults = ItemOptions {
+            check_role_based: Some(true),
+            checks: Some(vec![Check::Dns, Check::RoleBased]),
+        };
+        assert_eq!(ItemOptions::default().with_defaults(&defaults), defaults);
+
+        let role_off = ItemOptions {
+            check_role_based: Some(false),
+            checks: None,
+        };
+        let merged = role_off.with_defaults(&defaults);
+        assert_eq!(merged.check_role_based, Some(false));
+        assert_eq!(merged.checks, defaults.checks);
+        assert!(!merged.checks(false).role_based);
+    }
+
+    #[test]
+    fn test_item_options_serde() {
+        let options: ItemOptions =
+            serde_json::from_str(r#"{ "checks": ["dns", "role_based"] }"#).unwrap();
+        assert_eq!(options.checks, Some(vec![Check::Dns, Check::RoleBased]));
+        assert_eq!(
+            serde_json::to_string(&ItemOptions::default()).unwrap(),
+            "{}"
+        );
+    }
+}
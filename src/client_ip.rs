@@ -0,0 +1,303 @@
+use actix_web::dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+use std::future::{Ready, ready};
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::rc::Rc;
+
+/// An address range in CIDR notation; a bare address covers only itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    pub fn parse(cidr: &str) -> Result<Self, String> {
+        let cidr = cidr.trim();
+        let (addr, prefix) = match cidr.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (cidr, None),
+        };
+        let network: IpAddr = addr
+            .parse()
+            .map_err(|_| format!("{} is not an IP address or CIDR range", cidr))?;
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix| *prefix <= max_prefix)
+                .ok_or_else(|| format!("{} has an invalid prefix length", cidr))?,
+            None => max_prefix,
+        };
+        Ok(Self { network, prefix })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        // IPv4 clients reaching a dual-stack listener show up as mapped IPv6 addresses
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            IpAddr::V4(_) => ip,
+        };
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Load balancers and proxies allowed to report the client address in
+/// `X-Forwarded-For`
+///
+/// Requests from any other peer are attributed to the peer itself, whatever
+/// forwarding headers they carry, so clients can't spoof their address.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustedProxies {
+    ranges: Vec<Cidr>,
+}
+
+impl TrustedProxies {
+    pub fn new(ranges: Vec<Cidr>) -> Self {
+        Self { ranges }
+    }
+
+    /// Comma-separated addresses and CIDR ranges; fails on the first malformed entry
+    pub fn parse(list: &str) -> Result<Self, String> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(Cidr::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self::new)
+    }
+
+    /// Reads `TRUSTED_PROXIES`; unset trusts no proxy
+    ///
+    /// A malformed list is a deployment error that would silently misattribute every
+    /// request, so it stops the server from starting.
+    pub fn from_env() -> Self {
+        match std::env::var("TRUSTED_PROXIES") {
+            Ok(list) => {
+                Self::parse(&list).unwrap_or_else(|e| panic!("Invalid TRUSTED_PROXIES: {}", e))
+            }
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.ranges.iter().any(|range| range.contains(ip))
+    }
+
+    /// Address of the client behind `peer`
+    ///
+    /// `X-Forwarded-For` is read right to left, each trusted proxy having appended
+    /// the address it received the request from; the first untrusted hop is the
+    /// client. A malformed hop stops the walk at the last proxy that vouched for it.
+    pub fn client_ip(&self, peer: IpAddr, forwarded_for: Option<&str>) -> IpAddr {
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+        let Some(forwarded_for) = forwarded_for else {
+            return peer;
+        };
+
+        let mut client = peer;
+        for hop in forwarded_for.rsplit(',') {
+            let Ok(hop) = hop.trim().parse::<IpAddr>() else {
+                break;
+            };
+            client = hop;
+            if !self.is_trusted(hop) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+/// The client address of a request, resolved through the trusted proxies
+///
+/// Inserted by [`ClientIpResolver`]; as an extractor it falls back to the peer
+/// address when the middleware isn't installed, and fails only when there is no
+/// peer address at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl ClientIp {
+    pub fn from_http(req: &HttpRequest) -> Option<Self> {
+        req.extensions()
+            .get::<ClientIp>()
+            .copied()
+            .or_else(|| req.peer_addr().map(|addr| ClientIp(addr.ip())))
+    }
+}
+
+impl FromRequest for ClientIp {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            Self::from_http(req)
+                .ok_or_else(|| actix_web::error::ErrorBadRequest("The client address is unknown")),
+        )
+    }
+}
+
+pub struct ClientIpMiddleware<S> {
+    service: S,
+    proxies: Rc<TrustedProxies>,
+}
+
+impl<S, B> Service<ServiceRequest> for ClientIpMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if let Some(peer) = req.peer_addr() {
+            let forwarded_for = req
+                .headers()
+                .get("X-Forwarded-For")
+                .and_then(|value| value.to_str().ok());
+            let client = self.proxies.client_ip(peer.ip(), forwarded_for);
+            req.extensions_mut().insert(ClientIp(client));
+        }
+        Box::pin(self.service.call(req))
+    }
+}
+
+/// Resolves each request's [`ClientIp`] for handlers and inner middleware
+pub struct ClientIpResolver {
+    proxies: Rc<TrustedProxies>,
+}
+
+impl ClientIpResolver {
+    pub fn new(proxies: TrustedProxies) -> Self {
+        Self {
+            proxies: Rc::new(proxies),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(TrustedProxies::from_env())
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ClientIpResolver
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ClientIpMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ClientIpMiddleware {
+            service,
+            proxies: self.proxies.clone(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, HttpResponse, test as actix_test, web};
+
+    fn ip(addr: &str) -> IpAddr {
+        addr.parse().unwrap()
+    }
+
+    #[test]
+    fn test_cidr() {
+        let private = Cidr::parse("10.0.0.0/8").unwrap();
+        assert!(private.contains(ip("10.1.2.3")));
+        assert!(private.contains(ip("::ffff:10.1.2.3")));
+        assert!(!private.contains(ip("11.0.0.1")));
+
+        assert!(Cidr::parse("fd00::/8").unwrap().contains(ip("fd12::1")));
+        assert!(
+            Cidr::parse("0.0.0.0/0")
+                .unwrap()
+                .contains(ip("203.0.113.9"))
+        );
+        assert!(Cidr::parse("192.0.2.1").unwrap().contains(ip("192.0.2.1")));
+        assert!(!Cidr::parse("192.0.2.1").unwrap().contains(ip("192.0.2.2")));
+
+        assert!(Cidr::parse("10.0.0.0/33").is_err());
+        assert!(Cidr::parse("lb.internal").is_err());
+        assert!(TrustedProxies::parse("10.0.0.0/8, nope").is_err());
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let proxies = TrustedProxies::parse("10.0.0.0/8, 192.0.2.10").unwrap();
+        let chain = Some("198.51.100.7, 203.0.113.5, 10.0.0.3");
+
+        // Only listed proxies may set the header
+        assert_eq!(
+            proxies.client_ip(ip("203.0.113.99"), chain),
+            ip("203.0.113.99")
+        );
+        // The rightmost untrusted hop is the client, not a spoofed leftmost entry
+        assert_eq!(proxies.client_ip(ip("10.0.0.1"), chain), ip("203.0.113.5"));
+        assert_eq!(
+            proxies.client_ip(ip("192.0.2.10"), Some("10.0.0.4, 10.0.0.5")),
+            ip("10.0.0.4")
+        );
+        assert_eq!(proxies.client_ip(ip("10.0.0.1"), None), ip("10.0.0.1"));
+        assert_eq!(
+            proxies.client_ip(ip("10.0.0.1"), Some("junk, 10.0.0.9")),
+            ip("10.0.0.9")
+        );
+        assert_eq!(
+            TrustedProxies::default().client_ip(ip("10.0.0.1"), chain),
+            ip("10.0.0.1")
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_middleware_resolves_client_ip() {
+        let proxies = TrustedProxies::parse("10.0.0.0/8").unwrap();
+        let app = actix_test::init_service(App::new().wrap(ClientIpResolver::new(proxies)).route(
+            "/ip",
+            web::get().to(|client: ClientIp| async move {
+                HttpResponse::Ok().body(client.0.to_string())
+            }),
+        ))
+        .await;
+
+        for (peer, expected) in [
+            ("10.0.0.2:4000", "198.51.100.7"),
+            ("203.0.113.1:4000", "203.0.113.1"),
+        ] {
+            let req = actix_test::TestRequest::get()
+                .uri("/ip")
+                .peer_addr(peer.parse().unwrap())
+                .insert_header(("X-Forwarded-For", "198.51.100.7"))
+                .to_request();
+            let body = actix_test::call_and_read_body(&app, req).await;
+            assert_eq!(body, expected.as_bytes());
+        }
+    }
+}
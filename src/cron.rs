@@ -0,0 +1,207 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+
+/// Days [`CronSchedule::next_after`] searches before giving up; long enough to reach
+/// the next February 29 across a skipped leap year (e.g. 2096 to 2104)
+const SEARCH_LIMIT_DAYS: u32 = 8 * 366;
+
+/// A five-field cron expression: `minute hour day-of-month month day-of-week`, in UTC
+///
+/// Fields take `*`, numbers, ranges (`1-5`), lists (`1,15`) and steps (`*/6`,
+/// `0-30/10`). Day-of-week runs from 0 (Sunday) to 6, with 7 also meaning Sunday.
+/// `@hourly`, `@daily`, `@weekly` and `@monthly` are accepted as shorthands. As in
+/// cron, when both day fields are restricted a day matching either one qualifies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(
+                "Cron expressions have five fields: minute hour day-of-month month day-of-week"
+                    .to_string(),
+            );
+        };
+
+        let days_of_week = parse_field(day_of_week, 0, 7, "day-of-week")?;
+        Ok(CronSchedule {
+            minutes: parse_field(minute, 0, 59, "minute")?,
+            hours: parse_field(hour, 0, 23, "hour")?,
+            days_of_month: parse_field(day_of_month, 1, 31, "day-of-month")?,
+            months: parse_field(month, 1, 12, "month")?,
+            // Sunday is both 0 and 7
+            days_of_week: (days_of_week | (days_of_week >> 7)) & 0x7f,
+            any_day_of_month: day_of_month.starts_with('*'),
+            any_day_of_week: day_of_week.starts_with('*'),
+        })
+    }
+
+    /// Whether the schedule fires more than once within some hour
+    pub fn runs_more_than_hourly(&self) -> bool {
+        self.minutes.count_ones() > 1
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day_of_month = bit(self.days_of_month, date.day());
+        let day_of_week = bit(self.days_of_week, date.weekday().num_days_from_sunday());
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (false, false) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        }
+    }
+
+    /// First time strictly after `after` that the schedule fires, at minute precision;
+    /// `None` for expressions that never fire, such as `0 0 31 2 *`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let mut date = start.date_naive();
+        for _ in 0..SEARCH_LIMIT_DAYS {
+            if bit(self.months, date.month()) && self.matches_day(date) {
+                let today = date == start.date_naive();
+                let first_hour = if today { start.hour() } else { 0 };
+                for hour in (first_hour..24).filter(|hour| bit(self.hours, *hour)) {
+                    let first_minute = if today && hour == start.hour() {
+                        start.minute()
+                    } else {
+                        0
+                    };
+                    if let Some(minute) = (first_minute..60).find(|m| bit(self.minutes, *m)) {
+                        return Some(Utc.from_utc_datetime(&date.and_hms_opt(hour, minute, 0)?));
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+}
+
+fn bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+/// Bitmask of the values a field selects, each within `min..=max`
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid {} field '{}'", name, field);
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<usize>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(invalid)?,
+            ),
+            None => (part, 1),
+        };
+        let (low, high) = if range == "*" {
+            (min, max)
+        } else if let Some((low, high)) = range.split_once('-') {
+            (
+                low.parse::<u32>().map_err(|_| invalid())?,
+                high.parse::<u32>().map_err(|_| invalid())?,
+            )
+        } else {
+            let value = range.parse::<u32>().map_err(|_| invalid())?;
+            // `5/15` steps from 5 to the end of the field
+            (value, if part.contains('/') { max } else { value })
+        };
+        if low < min || high > max || low > high {
+            return Err(invalid());
+        }
+        for value in (low..=high).step_by(step) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn next(expression: &str, after: &str) -> Option<DateTime<Utc>> {
+        CronSchedule::parse(expression)
+            .unwrap()
+            .next_after(at(after))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            CronSchedule::parse("@daily"),
+            CronSchedule::parse("0 0 * * *")
+        );
+        assert!(CronSchedule::parse("0 6 * * 1-5").is_ok());
+        assert!(CronSchedule::parse("*/15 0-12/3 1,15 * 7").is_ok());
+        assert!(CronSchedule::parse("0 6 * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("0 0 0 * *").is_err());
+        assert!(CronSchedule::parse("0 5-1 * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("@yearly").is_err());
+
+        assert!(
+            !CronSchedule::parse("@hourly")
+                .unwrap()
+                .runs_more_than_hourly()
+        );
+        assert!(
+            CronSchedule::parse("0,30 * * * *")
+                .unwrap()
+                .runs_more_than_hourly()
+        );
+    }
+
+    #[test]
+    fn test_next_after() {
+        let expected = Some(at("2026-03-02T06:00:00Z"));
+        // Friday evening to Monday morning
+        assert_eq!(next("0 6 * * 1-5", "2026-02-27T18:00:00Z"), expected);
+        assert_eq!(next("0 6 * * 1-5", "2026-03-02T05:59:30Z"), expected);
+        assert_eq!(
+            next("0 6 * * 1-5", "2026-03-02T06:00:00Z"),
+            Some(at("2026-03-03T06:00:00Z"))
+        );
+        assert_eq!(
+            next("*/20 * * * *", "2026-03-02T06:41:00Z"),
+            Some(at("2026-03-02T07:00:00Z"))
+        );
+        // Either day field matches: the 15th, or any Sunday
+        assert_eq!(
+            next("0 0 15 * 0", "2026-03-02T00:00:00Z"),
+            Some(at("2026-03-08T00:00:00Z"))
+        );
+        assert_eq!(
+            next("@monthly", "2026-12-31T23:59:00Z"),
+            Some(at("2027-01-01T00:00:00Z"))
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2026-03-01T00:00:00Z"),
+            Some(at("2028-02-29T00:00:00Z"))
+        );
+        assert_eq!(next("0 0 31 2 *", "2026-03-01T00:00:00Z"), None);
+    }
+}
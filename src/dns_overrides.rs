@@ -0,0 +1,151 @@
+use crate::handlers::validation::dnsmx::{self, DomainOverrides, parse_nameservers};
+use crate::stores::CacheStore;
+use redis::RedisError;
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Redis hash of the domain overrides set through the admin API: ASCII domain to
+/// comma-separated nameservers
+const OVERRIDES_KEY: &str = "dns_domain_overrides";
+
+/// How often each instance re-reads the overrides from Redis
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Where a domain override comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OverrideSource {
+    /// `DNS_DOMAIN_OVERRIDES`, which the admin API can't change
+    Env,
+    Redis,
+}
+
+/// Nameservers answering the lookups under a domain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct DomainOverride {
+    /// ASCII (punycode) domain; its subdomains are overridden too
+    pub domain: String,
+    /// `ip:port` of each nameserver, queried over classic DNS
+    pub nameservers: Vec<String>,
+    pub source: OverrideSource,
+}
+
+impl DomainOverride {
+    fn new(domain: &str, nameservers: &[SocketAddr], source: OverrideSource) -> Self {
+        Self {
+            domain: domain.to_string(),
+            nameservers: nameservers.iter().map(SocketAddr::to_string).collect(),
+            source,
+        }
+    }
+}
+
+/// Split-horizon DNS overrides stored in Redis, next to those of
+/// `DNS_DOMAIN_OVERRIDES` (see `ResolverSettings`)
+///
+/// Lookups read an in-process copy that [`Self::refresh_periodically`] keeps current,
+/// so an override set through the admin API takes effect on every instance within
+/// seconds. Without Redis the last overrides read stay in force.
+#[derive(Clone, Default)]
+pub struct DnsOverrides {
+    store: Option<Arc<dyn CacheStore>>,
+}
+
+impl DnsOverrides {
+    pub fn from_store(store: Arc<dyn CacheStore>) -> Self {
+        Self { store: Some(store) }
+    }
+
+    /// The overrides stored in Redis; entries that no longer parse are left out
+    pub async fn read(&self) -> Result<DomainOverrides, RedisError> {
+        let Some(store) = &self.store else {
+            return Ok(DomainOverrides::new());
+        };
+        let raw = store.hgetall(OVERRIDES_KEY).await?;
+        Ok(raw
+            .into_iter()
+            .filter_map(|(domain, nameservers)| {
+                Some((domain, parse_nameservers(&nameservers).ok()?))
+            })
+            .collect())
+    }
+
+    /// Installs the stored overrides for this instance's lookups
+    pub async fn refresh(&self) -> Result<(), RedisError> {
+        dnsmx::replace_stored_overrides(self.read().await?);
+        Ok(())
+    }
+
+    /// Every override in force, by domain; at the same domain the environment wins
+    pub async fn list(&self) -> Result<Vec<DomainOverride>, RedisError> {
+        let env = &dnsmx::settings().domain_overrides;
+        let stored = self.read().await?;
+        let mut overrides: Vec<DomainOverride> = env
+            .iter()
+            .map(|(domain, nameservers)| {
+                DomainOverride::new(domain, nameservers, OverrideSource::Env)
+            })
+            .chain(
+                stored
+                    .iter()
+                    .filter(|(domain, _)| !env.contains_key(*domain))
+                    .map(|(domain, nameservers)| {
+                        DomainOverride::new(domain, nameservers, OverrideSource::Redis)
+                    }),
+            )
+            .collect();
+        overrides.sort_by(|a, b| a.domain.cmp(&b.domain));
+        Ok(overrides)
+    }
+
+    /// Stores the nameservers of `domain`, an [`dnsmx::override_domain`] key, or removes
+    /// its override with `None`; returns whether an override was stored before
+    pub async fn set(
+        &self,
+        domain: &str,
+        nameservers: Option<&[SocketAddr]>,
+    ) -> Result<bool, RedisError> {
+        let Some(store) = &self.store else {
+            return Ok(false);
+        };
+        let existed = match nameservers {
+            Some(nameservers) => {
+                let value = nameservers
+                    .iter()
+                    .map(SocketAddr::to_string)
+                    .collect::<Vec<_>>()
+                    .join(",");
+                !store.hset(OVERRIDES_KEY, domain, &value).await?
+            }
+            None => store.hdel(OVERRIDES_KEY, domain).await?,
+        };
+        // This instance sees its own change right away
+        self.refresh().await?;
+        Ok(existed)
+    }
+
+    /// Re-reads the stored overrides every few seconds; a failed read keeps the
+    /// current ones
+    pub async fn refresh_periodically(self) {
+        let mut ticker = tokio::time::interval(REFRESH_INTERVAL);
+        let mut failing = false;
+        loop {
+            ticker.tick().await;
+            match self.refresh().await {
+                Ok(()) => failing = false,
+                // Logged once per outage rather than every few seconds
+                Err(e) if !failing => {
+                    failing = true;
+                    eprintln!(
+                        "DNS override refresh failed, keeping the current ones: {}",
+                        e
+                    );
+                }
+                Err(_) => {}
+            }
+        }
+    }
+}
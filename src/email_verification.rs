@@ -0,0 +1,161 @@
+use crate::app_env::AppEnv;
+use crate::auth;
+use chrono::{Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use mongodb::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+const VERIFY_TOKEN_TYPE: &str = "verify";
+const WEBHOOK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Claims of a signed email verification link
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerificationClaims {
+    pub sub: String,
+    pub typ: String,
+    pub exp: usize,
+}
+
+/// Lifetime of verification links in seconds (`EMAIL_VERIFICATION_TTL`, default 24 hours)
+pub fn verification_ttl() -> i64 {
+    std::env::var("EMAIL_VERIFICATION_TTL")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|v: &i64| *v > 0)
+        .unwrap_or(24 * 3600)
+}
+
+/// Signs a verification token for an account; returns the token and its expiry timestamp
+pub fn sign_verification_token(email: &str) -> Result<(String, i64), String> {
+    let jwt_secret = std::env::var("JWT_SECRET").map_err(|_| "JWT_SECRET is not configured")?;
+    let expires_at = (Utc::now() + Duration::seconds(verification_ttl())).timestamp();
+    let claims = VerificationClaims {
+        sub: email.to_string(),
+        typ: VERIFY_TOKEN_TYPE.to_string(),
+        exp: expires_at as usize,
+    };
+
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(jwt_secret.as_ref()),
+    )
+    .map_err(|_| "Token signing failed".to_string())?;
+    Ok((token, expires_at))
+}
+
+/// Account email of a valid, unexpired verification token
+pub fn verify_token(token: &str) -> Result<String, String> {
+    let jwt_secret = std::env::var("JWT_SECRET").map_err(|_| "JWT_SECRET is not configured")?;
+    let claims = decode::<VerificationClaims>(
+        token,
+        &DecodingKey::from_secret(jwt_secret.as_ref()),
+        &Validation::new(Algorithm::HS256),
+    )
+    .map_err(|_| "Invalid or expired token")?
+    .claims;
+
+    if claims.typ != VERIFY_TOKEN_TYPE {
+        return Err("Invalid or expired token".to_string());
+    }
+    Ok(claims.sub)
+}
+
+/// Link that confirms the account, absolute when `PUBLIC_BASE_URL` is set
+pub fn verification_link(token: &str) -> String {
+    let base = std::env::var("PUBLIC_BASE_URL").unwrap_or_default();
+    format!(
+        "{}/api/v1/verify?token={}",
+        base.trim_end_matches('/'),
+        token
+    )
+}
+
+/// Whether undeliverable verification links are written to the log
+///
+/// Only with `EMAIL_VERIFICATION_LOG_LINKS=true` and outside production: the link
+/// activates the account, so it must not end up in shared logs by default.
+fn log_links_enabled() -> bool {
+    let enabled = std::env::var("EMAIL_VERIFICATION_LOG_LINKS")
+        .is_ok_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "true" | "1" | "yes"));
+    enabled && AppEnv::from_env() != AppEnv::Production
+}
+
+/// Sends a new account, or the new email of an account, its verification link
+///
+/// The link is posted as `{ "email", "verify_url", "expires_at" }` to
+/// `EMAIL_VERIFICATION_WEBHOOK_URL`, typically a transactional mail service. Without
+/// one the link is not sent; for local development it can be logged instead (see
+/// [`log_links_enabled`]). Best effort: a failed delivery is logged and registration
+/// still succeeds.
+pub async fn send_verification_link(email: &str) {
+    let (token, expires_at) = match sign_verification_token(email) {
+        Ok(signed) => signed,
+        Err(e) => {
+            eprintln!("Failed to sign verification link for {}: {}", email, e);
+            return;
+        }
+    };
+    let link = verification_link(&token);
+
+    let Some(url) = std::env::var("EMAIL_VERIFICATION_WEBHOOK_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+    else {
+        if log_links_enabled() {
+            eprintln!("Verification link for {}: {}", email, link);
+        } else {
+            eprintln!(
+                "Verification link for {} not sent: EMAIL_VERIFICATION_WEBHOOK_URL is not set",
+                email
+            );
+        }
+        return;
+    };
+    let delivered = reqwest::Client::new()
+        .post(&url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .json(&json!({ "email": email, "verify_url": link, "expires_at": expires_at }))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+    if let Err(e) = delivered {
+        eprintln!("Failed to deliver verification link for {}: {}", email, e);
+    }
+}
+
+/// Activates the account a verification token was issued for, or completes the email
+/// change it was issued for; returns its email
+///
+/// Confirming an already active account again succeeds.
+pub async fn confirm(mongo_client: &Client, token: &str) -> Result<String, String> {
+    let email = verify_token(token)?;
+    if !auth::activate_account(mongo_client, &email).await?
+        && !auth::confirm_email_change(mongo_client, &email).await?
+    {
+        return Err("Invalid or expired token".to_string());
+    }
+    Ok(email)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verification_token_round_trip() {
+        unsafe {
+            std::env::set_var("JWT_SECRET", "test-secret-key-for-testing");
+        }
+
+        let (token, expires_at) = sign_verification_token("user@example.com").unwrap();
+        assert!(expires_at > Utc::now().timestamp());
+        assert_eq!(verify_token(&token).unwrap(), "user@example.com");
+        assert!(verify_token("not-a-token").is_err());
+        assert!(verification_link(&token).ends_with(&format!("/api/v1/verify?token={}", token)));
+
+        let (share_token, _) = crate::routes::share::sign_share_token("job-1").unwrap();
+        assert!(verify_token(&share_token).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use crate::handlers::validation::bounce_feedback;
+use crate::handlers::validation::suppression::{self, address_hash};
+use crate::handlers::validation::suppression_list;
+use crate::job_queue::{ErasedJobData, JobQueue};
+use crate::validation_cache::ValidationCache;
+use crate::validation_history;
+use mongodb::Client;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Whose data an erasure covers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErasureScope {
+    /// The data kept for one account
+    Account {
+        /// Account email, owner of its history and jobs
+        account: String,
+        /// Owner of the suppression list and bounce feedback the account may change;
+        /// `None` for members of an organization, whose shared list only its owner
+        /// can change
+        suppression_owner: Option<String>,
+    },
+    /// Every account's data, and the global suppression list
+    Global,
+}
+
+/// What an erasure removed, per store
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ErasureReport {
+    pub email: String,
+    /// Validation history records deleted
+    pub history: u64,
+    /// Suppression list entries deleted
+    pub suppression_lists: u64,
+    /// Whether the address was on the global suppression list; global erasures only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub global_suppression: Option<bool>,
+    /// Bounce and complaint feedback entries deleted
+    pub bounce_feedback: u64,
+    /// Cached validation results deleted, with the address's hash lookup verdict
+    pub cached_results: u64,
+    pub jobs: ErasedJobData,
+}
+
+/// Purges an address from the validation history, suppression lists, bounce feedback,
+/// result cache and stored job results
+///
+/// Each store is purged in turn and the first failure is returned, so a failed erasure
+/// can be retried; stores already purged just report nothing left to delete. Other
+/// instances drop their in-process copies of cached results within a second.
+pub async fn erase(
+    mongo_client: &Client,
+    cache: &ValidationCache,
+    job_queue: &JobQueue,
+    email: &str,
+    scope: &ErasureScope,
+) -> Result<ErasureReport, String> {
+    let email = email.trim();
+    let mut report = ErasureReport {
+        email: email.to_string(),
+        ..ErasureReport::default()
+    };
+
+    let account = match scope {
+        ErasureScope::Account { account, .. } => Some(account.to_lowercase()),
+        ErasureScope::Global => None,
+    };
+    report.history = validation_history::erase(mongo_client, account.as_deref(), email).await?;
+
+    // Feedback is kept per list owner, like the suppression list it feeds
+    (report.suppression_lists, report.bounce_feedback) = match scope {
+        ErasureScope::Account {
+            suppression_owner: Some(owner),
+            ..
+        } => (
+            suppression_list::remove(mongo_client, owner, email).await? as u64,
+            bounce_feedback::remove(mongo_client, owner, email).await?,
+        ),
+        ErasureScope::Account { .. } => (0, 0),
+        ErasureScope::Global => {
+            report.global_suppression =
+                Some(suppression::remove(mongo_client, &address_hash(email)).await?);
+            (
+                suppression_list::remove_everywhere(mongo_client, email).await?,
+                bounce_feedback::remove_everywhere(mongo_client, email).await?,
+            )
+        }
+    };
+
+    report.cached_results = cache
+        .invalidate_email(email)
+        .await
+        .map_err(|e| format!("Redis error: {}", e))?;
+    report.jobs = job_queue
+        .erase_email(account.as_deref(), email)
+        .await
+        .map_err(|e| format!("Redis error: {}", e))?;
+    Ok(report)
+}
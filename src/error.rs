@@ -0,0 +1,290 @@
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use utoipa::ToSchema;
+
+/// Body of every error response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorEnvelope {
+    pub error: ErrorBody,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// Machine-readable code, e.g. `INVALID_SYNTAX` or `UPSTREAM_UNAVAILABLE`
+    pub code: String,
+    pub message: String,
+    /// Quote this when reporting a problem; it is logged with the underlying cause
+    pub request_id: String,
+    /// Extra context for some codes, e.g. `max_batch_size` for `BATCH_TOO_LARGE`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<Value>,
+}
+
+/// Error returned by REST handlers and GraphQL resolvers
+///
+/// Every variant renders as the same envelope:
+///
+/// ```json
+/// { "error": { "code": "INVALID_SYNTAX", "message": "...", "request_id": "..." } }
+/// ```
+///
+/// Upstream and internal failures never show their underlying cause to clients; it is
+/// logged together with the request ID instead, so a reported ID can be traced.
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request can't be processed as sent; 400 unless another 4xx status is set
+    Validation {
+        status: StatusCode,
+        code: String,
+        message: String,
+        /// Extra machine-readable context, e.g. the batch size limit
+        details: Option<Value>,
+    },
+    /// Missing or invalid credentials (401)
+    Auth { code: String, message: String },
+    /// The caller exceeded a rate limit or quota (429)
+    RateLimited {
+        message: String,
+        retry_after_secs: Option<u64>,
+    },
+    /// A dependency (MongoDB, Redis, DNS) failed or is unreachable (503)
+    Upstream {
+        service: &'static str,
+        detail: String,
+    },
+    /// A bug or misconfiguration on our side (500)
+    Internal { detail: String },
+}
+
+impl ApiError {
+    /// 400 with the given code; see [`ApiError::with_status`] for other client errors
+    pub fn validation(code: impl Into<String>, message: impl Into<String>) -> Self {
+        ApiError::Validation {
+            status: StatusCode::BAD_REQUEST,
+            code: code.into(),
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn not_found(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::validation(code, message).with_status(StatusCode::NOT_FOUND)
+    }
+
+    /// 401 with the generic `UNAUTHORIZED` code
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::auth("UNAUTHORIZED", message)
+    }
+
+    pub fn auth(code: impl Into<String>, message: impl Into<String>) -> Self {
+        ApiError::Auth {
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn upstream(service: &'static str, detail: impl fmt::Display) -> Self {
+        ApiError::Upstream {
+            service,
+            detail: detail.to_string(),
+        }
+    }
+
+    pub fn internal(detail: impl fmt::Display) -> Self {
+        ApiError::Internal {
+            detail: detail.to_string(),
+        }
+    }
+
+    /// Classifies the `String` errors of [`crate::auth`]
+    pub fn from_auth(message: impl Into<String>) -> Self {
+        let message = message.into();
+        match message.as_str() {
+            "Database error" => Self::upstream("database", message),
+            "Password hashing failed"
+            | "Key generation failed"
+            | "Token signing failed"
+            | "JWT_SECRET is not configured" => Self::internal(message),
+            "Invalid API key"
+            | "Invalid key format"
+            | "Invalid or expired token"
+            | "Invalid email or password" => Self::unauthorized(message),
+            "Email already registered" => Self::validation("EMAIL_ALREADY_REGISTERED", message)
+                .with_status(StatusCode::CONFLICT),
+            "Invalid email address" => Self::validation("INVALID_EMAIL", message),
+            m if m.starts_with("Password must") => Self::validation("WEAK_PASSWORD", message),
+            _ => Self::validation("INVALID_REQUEST", message),
+        }
+    }
+
+    /// Overrides the status of a validation error (e.g. 404, 409, 413, 422)
+    pub fn with_status(mut self, new_status: StatusCode) -> Self {
+        if let ApiError::Validation { status, .. } = &mut self {
+            *status = new_status;
+        }
+        self
+    }
+
+    pub fn with_details(mut self, new_details: Value) -> Self {
+        if let ApiError::Validation { details, .. } = &mut self {
+            *details = Some(new_details);
+        }
+        self
+    }
+
+    pub fn code(&self) -> &str {
+        match self {
+            ApiError::Validation { code, .. } | ApiError::Auth { code, .. } => code,
+            ApiError::RateLimited { .. } => "RATE_LIMITED",
+            ApiError::Upstream { .. } => "UPSTREAM_UNAVAILABLE",
+            ApiError::Internal { .. } => "INTERNAL_ERROR",
+        }
+    }
+
+    /// Body of the envelope for the given request ID
+    pub fn envelope(&self, request_id: &str) -> ErrorEnvelope {
+        let details = match self {
+            ApiError::Validation { details, .. } => details.clone(),
+            _ => None,
+        };
+        ErrorEnvelope {
+            error: ErrorBody {
+                code: self.code().to_string(),
+                message: self.to_string(),
+                request_id: request_id.to_string(),
+                details,
+            },
+        }
+    }
+
+    /// Logs what clients don't get to see
+    fn log(&self, request_id: &str) {
+        match self {
+            ApiError::Upstream { service, detail } => {
+                eprintln!("[{}] {} error: {}", request_id, service, detail)
+            }
+            ApiError::Internal { detail } => {
+                eprintln!("[{}] internal error: {}", request_id, detail)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn new_request_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Validation { message, .. }
+            | ApiError::Auth { message, .. }
+            | ApiError::RateLimited { message, .. } => f.write_str(message),
+            ApiError::Upstream { service, .. } => {
+                write!(f, "The {} is temporarily unavailable", service)
+            }
+            ApiError::Internal { .. } => f.write_str("An internal error occurred"),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::Validation { status, .. } => *status,
+            ApiError::Auth { .. } => StatusCode::UNAUTHORIZED,
+            ApiError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Upstream { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let request_id = new_request_id();
+        self.log(&request_id);
+
+        let mut response = HttpResponse::build(self.status_code());
+        if let ApiError::RateLimited {
+            retry_after_secs: Some(secs),
+            ..
+        } = self
+        {
+            response.insert_header(("Retry-After", secs.to_string()));
+        }
+        response
+            .insert_header(("X-Request-Id", request_id.clone()))
+            .json(self.envelope(&request_id))
+    }
+}
+
+impl async_graphql::ErrorExtensions for ApiError {
+    /// GraphQL errors carry the code and request ID as extensions
+    fn extend(&self) -> async_graphql::Error {
+        let request_id = new_request_id();
+        self.log(&request_id);
+
+        async_graphql::Error::new(self.to_string()).extend_with(|_, extensions| {
+            extensions.set("code", self.code());
+            extensions.set("request_id", request_id.as_str());
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::ErrorExtensions;
+    use serde_json::json;
+
+    #[test]
+    fn test_envelope_shape() {
+        let error = ApiError::validation("BATCH_TOO_LARGE", "Too many")
+            .with_status(StatusCode::PAYLOAD_TOO_LARGE)
+            .with_details(json!({ "max_batch_size": 10 }));
+        let body = serde_json::to_value(error.envelope("req-1")).unwrap();
+
+        assert_eq!(error.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["error"]["code"], "BATCH_TOO_LARGE");
+        assert_eq!(body["error"]["message"], "Too many");
+        assert_eq!(body["error"]["request_id"], "req-1");
+        assert_eq!(body["error"]["details"]["max_batch_size"], 10);
+    }
+
+    #[test]
+    fn test_internal_details_are_not_exposed() {
+        let error = ApiError::upstream("database", "connection refused at 10.0.0.5:27017");
+        let body = serde_json::to_value(error.envelope("req-2")).unwrap();
+
+        assert_eq!(error.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["error"]["code"], "UPSTREAM_UNAVAILABLE");
+        assert!(!body.to_string().contains("10.0.0.5"));
+
+        let graphql_error = ApiError::internal("panic in worker").extend();
+        assert_eq!(graphql_error.message, "An internal error occurred");
+    }
+
+    #[test]
+    fn test_auth_errors_are_classified() {
+        assert_eq!(
+            ApiError::from_auth("Invalid API key").status_code(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            ApiError::from_auth("Database error").status_code(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            ApiError::from_auth("Email already registered").status_code(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError::from_auth("Password must be 10 to 128 characters long").code(),
+            "WEAK_PASSWORD"
+        );
+    }
+}
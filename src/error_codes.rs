@@ -0,0 +1,941 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Where a code appears
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeKind {
+    /// `error.code` of an error response: the request itself was refused or failed
+    Request,
+    /// `error.code` of one address's validation result; `POST /validate-email` also
+    /// answers 400 with it
+    Verdict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema, async_graphql::Enum)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The address is deliverable but risky, or the request can be served later
+    Warning,
+    /// The address or request is rejected as sent
+    Error,
+    /// A failure on our side or in a dependency
+    Critical,
+}
+
+/// Code of an address's validation result (`error.code` on v1, `reason.code` on v2)
+///
+/// The single list of verdict codes: the registry, `GET /error-codes` and the GraphQL
+/// `errorCodes` query are all built from it. Variants render as their code, e.g.
+/// `INVALID_SYNTAX`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema, async_graphql::Enum)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VerdictCode {
+    CustomBlocked,
+    DatabaseError,
+    DisposableEmail,
+    DnsUnavailable,
+    InvalidDomain,
+    InvalidInput,
+    InvalidSyntax,
+    InvalidTld,
+    KnownTrap,
+    PreviousBounce,
+    PreviousBouncer,
+    PreviousComplaint,
+    ProcessingError,
+    RoleBasedEmail,
+    Suppressed,
+    Undeliverable,
+}
+
+impl VerdictCode {
+    pub const ALL: [VerdictCode; 16] = [
+        VerdictCode::CustomBlocked,
+        VerdictCode::DatabaseError,
+        VerdictCode::DisposableEmail,
+        VerdictCode::DnsUnavailable,
+        VerdictCode::InvalidDomain,
+        VerdictCode::InvalidInput,
+        VerdictCode::InvalidSyntax,
+        VerdictCode::InvalidTld,
+        VerdictCode::KnownTrap,
+        VerdictCode::PreviousBounce,
+        VerdictCode::PreviousBouncer,
+        VerdictCode::PreviousComplaint,
+        VerdictCode::ProcessingError,
+        VerdictCode::RoleBasedEmail,
+        VerdictCode::Suppressed,
+        VerdictCode::Undeliverable,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            VerdictCode::CustomBlocked => "CUSTOM_BLOCKED",
+            VerdictCode::DatabaseError => "DATABASE_ERROR",
+            VerdictCode::DisposableEmail => "DISPOSABLE_EMAIL",
+            VerdictCode::DnsUnavailable => "DNS_UNAVAILABLE",
+            VerdictCode::InvalidDomain => "INVALID_DOMAIN",
+            VerdictCode::InvalidInput => "INVALID_INPUT",
+            VerdictCode::InvalidSyntax => "INVALID_SYNTAX",
+            VerdictCode::InvalidTld => "INVALID_TLD",
+            VerdictCode::KnownTrap => "KNOWN_TRAP",
+            VerdictCode::PreviousBounce => "PREVIOUS_BOUNCE",
+            VerdictCode::PreviousBouncer => "PREVIOUS_BOUNCER",
+            VerdictCode::PreviousComplaint => "PREVIOUS_COMPLAINT",
+            VerdictCode::ProcessingError => "PROCESSING_ERROR",
+            VerdictCode::RoleBasedEmail => "ROLE_BASED_EMAIL",
+            VerdictCode::Suppressed => "SUPPRESSED",
+            VerdictCode::Undeliverable => "UNDELIVERABLE",
+        }
+    }
+
+    pub fn parse(code: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|verdict| verdict.as_str() == code)
+    }
+
+    /// The code's registry entry
+    pub fn info(self) -> &'static ErrorCodeInfo {
+        lookup(self.as_str())
+            .find(|info| info.kind == CodeKind::Verdict)
+            .expect("every verdict code is registered")
+    }
+}
+
+/// One entry of the error code registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ErrorCodeInfo {
+    pub code: &'static str,
+    pub kind: CodeKind,
+    /// HTTP statuses of responses carrying the code; empty for verdicts, which arrive
+    /// inside successful responses
+    #[schema(value_type = Vec<u16>)]
+    pub statuses: &'static [u16],
+    pub severity: Severity,
+    /// Whether the same request may succeed when sent again later
+    pub retryable: bool,
+    pub description: &'static str,
+}
+
+const fn verdict(
+    code: VerdictCode,
+    severity: Severity,
+    retryable: bool,
+    description: &'static str,
+) -> ErrorCodeInfo {
+    ErrorCodeInfo {
+        code: code.as_str(),
+        kind: CodeKind::Verdict,
+        statuses: &[],
+        severity,
+        retryable,
+        description,
+    }
+}
+
+const fn request(
+    code: &'static str,
+    statuses: &'static [u16],
+    severity: Severity,
+    retryable: bool,
+    description: &'static str,
+) -> ErrorCodeInfo {
+    ErrorCodeInfo {
+        code,
+        kind: CodeKind::Request,
+        statuses,
+        severity,
+        retryable,
+        description,
+    }
+}
+
+/// Every code the REST, GraphQL and gRPC interfaces return, sorted by kind and code
+///
+/// Codes are stable identifiers; clients should branch and localize on them rather
+/// than on messages. A test fails when a code is returned that isn't listed here.
+pub const ERROR_CODES: &[ErrorCodeInfo] = &[
+    verdict(
+        VerdictCode::CustomBlocked,
+        Severity::Error,
+        false,
+        "The address or its domain is on the account's blocklist",
+    ),
+    verdict(
+        VerdictCode::DatabaseError,
+        Severity::Critical,
+        true,
+        "A reference list lookup failed, so no verdict could be reached",
+    ),
+    verdict(
+        VerdictCode::DisposableEmail,
+        Severity::Warning,
+        false,
+        "The domain provides disposable email addresses",
+    ),
+    verdict(
+        VerdictCode::DnsUnavailable,
+        Severity::Critical,
+        true,
+        "The resolver failed (SERVFAIL or timeout), so the domain could not be checked; never cached for long",
+    ),
+    verdict(
+        VerdictCode::InvalidDomain,
+        Severity::Error,
+        false,
+        "The domain has no MX, A or AAAA records",
+    ),
+    verdict(
+        VerdictCode::InvalidInput,
+        Severity::Error,
+        false,
+        "A lenient bulk request contained an entry that is not a string",
+    ),
+    verdict(
+        VerdictCode::InvalidSyntax,
+        Severity::Error,
+        false,
+        "The address is not valid according to RFC 5322 and RFC 6531",
+    ),
+    verdict(
+        VerdictCode::InvalidTld,
+        Severity::Error,
+        false,
+        "The domain's top-level domain is not in the IANA list of delegated TLDs",
+    ),
+    verdict(
+        VerdictCode::KnownTrap,
+        Severity::Error,
+        false,
+        "The address is a known spamtrap or spam complainer; only checked when suppression lookups are enabled",
+    ),
+    verdict(
+        VerdictCode::PreviousBounce,
+        Severity::Error,
+        false,
+        "Mail from the account to the address bounced, as reported to its bounce feedback",
+    ),
+    verdict(
+        VerdictCode::PreviousBouncer,
+        Severity::Error,
+        false,
+        "The address has hard-bounced before; only checked when suppression lookups are enabled",
+    ),
+    verdict(
+        VerdictCode::PreviousComplaint,
+        Severity::Error,
+        false,
+        "The recipient marked the account's mail as spam, as reported to its bounce feedback",
+    ),
+    verdict(
+        VerdictCode::ProcessingError,
+        Severity::Critical,
+        true,
+        "Validating the address failed unexpectedly",
+    ),
+    verdict(
+        VerdictCode::RoleBasedEmail,
+        Severity::Warning,
+        false,
+        "The local part names a role (admin@, support@, ...) rather than a person; only checked on request",
+    ),
+    verdict(
+        VerdictCode::Suppressed,
+        Severity::Error,
+        false,
+        "The address is on the account's suppression list; the message names the reason",
+    ),
+    verdict(
+        VerdictCode::Undeliverable,
+        Severity::Error,
+        false,
+        "A fallback verification provider reported the address as undeliverable",
+    ),
+    request(
+        "ALREADY_IN_ORGANIZATION",
+        &[409],
+        Severity::Error,
+        false,
+        "The account already belongs to an organization",
+    ),
+    request(
+        "BATCH_TOO_LARGE",
+        &[413],
+        Severity::Error,
+        false,
+        "The bulk request or suppression import holds more addresses than allowed; details.max_batch_size gives the limit",
+    ),
+    request(
+        "BRANDING_NOT_SET",
+        &[404],
+        Severity::Error,
+        false,
+        "The account has no branding configured",
+    ),
+    request(
+        "CURSOR_INVALID",
+        &[409],
+        Severity::Error,
+        false,
+        "The sync cursor is unknown or expired; resync from a snapshot",
+    ),
+    request(
+        "DUPLICATE_JOB",
+        &[409],
+        Severity::Error,
+        false,
+        "The submission overlaps a recently queued job; details.duplicate_of names it",
+    ),
+    request(
+        "EMAIL_ALREADY_REGISTERED",
+        &[409],
+        Severity::Error,
+        false,
+        "An account with this email address exists",
+    ),
+    request(
+        "EMPTY_BATCH",
+        &[422],
+        Severity::Error,
+        false,
+        "The bulk request, saved list or suppression import contains no addresses",
+    ),
+    request(
+        "ENTRY_NOT_FOUND",
+        &[404],
+        Severity::Error,
+        false,
+        "The custom list or suppression list does not contain this entry",
+    ),
+    request(
+        "EXPORT_TOO_LARGE",
+        &[422],
+        Severity::Error,
+        false,
+        "The job has more results than fit in an xlsx worksheet; export csv or jsonl",
+    ),
+    request(
+        "FEATURE_DISABLED",
+        &[503],
+        Severity::Warning,
+        true,
+        "The feature was switched off by an operator; details.flag names the feature flag",
+    ),
+    request(
+        "FORBIDDEN",
+        &[403],
+        Severity::Error,
+        false,
+        "The account's organization role, or its lack of admin rights, does not allow this action",
+    ),
+    request(
+        "INSUFFICIENT_SCOPE",
+        &[403],
+        Severity::Error,
+        false,
+        "The API key is limited to scopes that don't include this route",
+    ),
+    request(
+        "INTERNAL_ERROR",
+        &[500],
+        Severity::Critical,
+        false,
+        "An unexpected failure on our side; quote the request_id when reporting it",
+    ),
+    request(
+        "INVALID_BATCH",
+        &[422],
+        Severity::Error,
+        false,
+        "A sync verdict batch is malformed",
+    ),
+    request(
+        "INVALID_BODY",
+        &[400, 422],
+        Severity::Error,
+        false,
+        "The request body is not valid JSON (400) or does not match the expected shape (422)",
+    ),
+    request(
+        "INVALID_BRANDING",
+        &[400],
+        Severity::Error,
+        false,
+        "The branding name or logo URL is not acceptable",
+    ),
+    request(
+        "INVALID_CREDENTIALS",
+        &[401],
+        Severity::Error,
+        false,
+        "The email and password do not match an account",
+    ),
+    request(
+        "INVALID_DNS_OVERRIDE",
+        &[400],
+        Severity::Error,
+        false,
+        "The overridden domain is not a valid host name, or a nameserver is not an IP address with an optional port",
+    ),
+    request(
+        "INVALID_EMAIL",
+        &[400],
+        Severity::Error,
+        false,
+        "The email address of an account is not a valid address",
+    ),
+    request(
+        "INVALID_ENTRY",
+        &[400],
+        Severity::Error,
+        false,
+        "A custom list entry is neither a domain nor an address, or a suppression is not a valid address or has an overlong note",
+    ),
+    request(
+        "INVALID_FEEDBACK",
+        &[400],
+        Severity::Error,
+        false,
+        "The bounce feedback is not an SNS message, an SES notification or an events array",
+    ),
+    request(
+        "INVALID_FIELD",
+        &[422],
+        Severity::Error,
+        false,
+        "An address is too long or contains control characters; details.field names it",
+    ),
+    request(
+        "INVALID_FIELDS",
+        &[400],
+        Severity::Error,
+        false,
+        "The fields query parameter names unknown result fields; details.unknown_fields lists them",
+    ),
+    request(
+        "INVALID_FILTER",
+        &[400],
+        Severity::Error,
+        false,
+        "The only result filter is not valid or invalid, or error_code is combined with only=valid",
+    ),
+    request(
+        "INVALID_FLAG",
+        &[400],
+        Severity::Error,
+        false,
+        "The feature flag is not one of maintenance_mode, bulk_enabled or registration_open",
+    ),
+    request(
+        "INVALID_FORMAT",
+        &[400],
+        Severity::Error,
+        false,
+        "The export format is not one of csv, jsonl or xlsx (csv or jsonl for suppression lists)",
+    ),
+    request(
+        "INVALID_HASH_PREFIX",
+        &[400],
+        Severity::Error,
+        false,
+        "The hash prefix is not exactly 5 hexadecimal characters",
+    ),
+    request(
+        "INVALID_INPUT",
+        &[400],
+        Severity::Error,
+        false,
+        "The request's arguments are inconsistent or of the wrong type",
+    ),
+    request(
+        "INVALID_LANG",
+        &[400],
+        Severity::Error,
+        false,
+        "The lang parameter names a language without a message catalog; en, es, de and fr are available",
+    ),
+    request(
+        "INVALID_RESULT",
+        &[400],
+        Severity::Error,
+        false,
+        "The audit log result filter is not one of success, client_error or server_error",
+    ),
+    request(
+        "INVALID_NAME",
+        &[400],
+        Severity::Error,
+        false,
+        "The organization or list name is empty or too long",
+    ),
+    request(
+        "INVALID_PAGE",
+        &[400],
+        Severity::Error,
+        false,
+        "Page numbers start at 1; per_page, where accepted, is between 1 and 1000",
+    ),
+    request(
+        "INVALID_RANGE",
+        &[400],
+        Severity::Error,
+        false,
+        "The time range ends before it starts",
+    ),
+    request(
+        "INVALID_REFRESH_TOKEN",
+        &[401],
+        Severity::Error,
+        false,
+        "The refresh token is invalid, expired or already used",
+    ),
+    request(
+        "INVALID_REQUEST",
+        &[400],
+        Severity::Error,
+        false,
+        "The request cannot be processed as sent",
+    ),
+    request(
+        "INVALID_SCHEDULE",
+        &[400],
+        Severity::Error,
+        false,
+        "The cron expression is malformed, never fires, or fires more than once an hour",
+    ),
+    request(
+        "INVALID_SCOPE",
+        &[422],
+        Severity::Error,
+        false,
+        "A requested key scope is not one of validate, jobs, sync or admin",
+    ),
+    request(
+        "INVALID_SETTINGS",
+        &[400],
+        Severity::Error,
+        false,
+        "The webhook URL, retention period or SNS topics of the account settings are invalid",
+    ),
+    request(
+        "INVALID_SNS_SIGNATURE",
+        &[403],
+        Severity::Error,
+        false,
+        "An SNS message is not signed by SNS with SignatureVersion 2",
+    ),
+    request(
+        "INVALID_STATUS",
+        &[400],
+        Severity::Error,
+        false,
+        "The job status filter is not one of pending, processing, completed or failed",
+    ),
+    request(
+        "INVALID_SUPPRESSION_ENTRY",
+        &[400],
+        Severity::Error,
+        false,
+        "A suppression import entry is not a SHA-256 hash or an email address, or the import is empty",
+    ),
+    request(
+        "INVALID_VERIFICATION_TOKEN",
+        &[400],
+        Severity::Error,
+        false,
+        "The email verification link is invalid or has expired",
+    ),
+    request(
+        "INVALID_VERBOSITY",
+        &[400],
+        Severity::Error,
+        false,
+        "The verbosity is not one of minimal, standard or full",
+    ),
+    request(
+        "INVALID_WEBHOOK_URL",
+        &[400],
+        Severity::Error,
+        false,
+        "The webhook URL is not an absolute http or https URL",
+    ),
+    request(
+        "INVALID_PERIOD",
+        &[400],
+        Severity::Error,
+        false,
+        "The billing period is not a month written as YYYY-MM",
+    ),
+    request(
+        "INVALID_WINDOW",
+        &[400],
+        Severity::Error,
+        false,
+        "The stats window is not one of hour, day or month",
+    ),
+    request(
+        "JOB_NOT_COMPLETE",
+        &[409],
+        Severity::Warning,
+        true,
+        "The job's results are not available yet",
+    ),
+    request(
+        "JOB_NOT_FOUND",
+        &[404],
+        Severity::Error,
+        false,
+        "The job does not exist, has expired or belongs to another account",
+    ),
+    request(
+        "JOB_RUNNING",
+        &[409],
+        Severity::Warning,
+        true,
+        "The job is being processed and cannot be removed until it finishes",
+    ),
+    request(
+        "JOB_TOO_LARGE",
+        &[413],
+        Severity::Error,
+        false,
+        "The bulk job exceeds the queue's payload limit; split the list",
+    ),
+    request(
+        "KEY_NOT_FOUND",
+        &[404],
+        Severity::Error,
+        false,
+        "No active API key with this id",
+    ),
+    request(
+        "LAST_OWNER",
+        &[409],
+        Severity::Error,
+        false,
+        "An organization needs at least one owner",
+    ),
+    request(
+        "LIST_FULL",
+        &[400],
+        Severity::Error,
+        false,
+        "The custom list or suppression list has reached its entry limit",
+    ),
+    request(
+        "LIST_NOT_FOUND",
+        &[404],
+        Severity::Error,
+        false,
+        "The saved list does not exist or belongs to another account",
+    ),
+    request(
+        "LIST_TOO_LARGE",
+        &[413],
+        Severity::Error,
+        false,
+        "The list has more addresses than a saved list holds or a schedule re-validates",
+    ),
+    request(
+        "MAINTENANCE",
+        &[503],
+        Severity::Warning,
+        true,
+        "The service is down for maintenance; retry after Retry-After seconds",
+    ),
+    request(
+        "MEMBER_NOT_FOUND",
+        &[404],
+        Severity::Error,
+        false,
+        "No such account or organization member",
+    ),
+    request(
+        "METHOD_NOT_ALLOWED",
+        &[200],
+        Severity::Error,
+        false,
+        "A GraphQL mutation was sent with GET; send it with POST",
+    ),
+    request(
+        "NOT_IN_ORGANIZATION",
+        &[404],
+        Severity::Error,
+        false,
+        "The account does not belong to an organization",
+    ),
+    request(
+        "NO_PREVIOUS_VERSION",
+        &[409],
+        Severity::Error,
+        false,
+        "The reference list has never been switched, so there is nothing to roll back to",
+    ),
+    request(
+        "PAYLOAD_TOO_LARGE",
+        &[413],
+        Severity::Error,
+        false,
+        "The request body exceeds the size limit; details.max_bytes gives it",
+    ),
+    request(
+        "PERSISTED_QUERY_HASH_MISMATCH",
+        &[200],
+        Severity::Error,
+        false,
+        "The GraphQL query does not match its persistedQuery.sha256Hash",
+    ),
+    request(
+        "PERSISTED_QUERY_NOT_FOUND",
+        &[200],
+        Severity::Warning,
+        true,
+        "The persisted GraphQL query hash is unknown; resend it with the full query",
+    ),
+    request(
+        "PERSISTED_QUERY_NOT_SUPPORTED",
+        &[200],
+        Severity::Error,
+        false,
+        "The persistedQuery extension is not version 1 or has no sha256Hash",
+    ),
+    request(
+        "RATE_LIMITED",
+        &[429],
+        Severity::Warning,
+        true,
+        "A rate limit or quota was exceeded; wait for Retry-After when present",
+    ),
+    request(
+        "READ_ONLY",
+        &[503],
+        Severity::Warning,
+        true,
+        "The instance only serves validations during maintenance or on a replica",
+    ),
+    request(
+        "SCHEDULE_NOT_FOUND",
+        &[404],
+        Severity::Error,
+        false,
+        "The schedule does not exist or belongs to another account",
+    ),
+    request(
+        "SEQUENCE_CONFLICT",
+        &[409],
+        Severity::Error,
+        false,
+        "Sync batches are missing; resend from details.expected_sequence",
+    ),
+    request(
+        "SLOT_EMPTY",
+        &[409],
+        Severity::Error,
+        false,
+        "The reference list slot has no entries to activate",
+    ),
+    request(
+        "SWITCH_CONFLICT",
+        &[409],
+        Severity::Warning,
+        true,
+        "The reference list was switched concurrently",
+    ),
+    request(
+        "UNAUTHORIZED",
+        &[401],
+        Severity::Error,
+        false,
+        "Credentials are missing or invalid",
+    ),
+    request(
+        "UNKNOWN_SNS_TOPIC",
+        &[403],
+        Severity::Error,
+        false,
+        "No account listed the SNS topic in its sns_topic_arns setting",
+    ),
+    request(
+        "UNSUPPORTED_MEDIA_TYPE",
+        &[415],
+        Severity::Error,
+        false,
+        "Request bodies must be sent as application/json",
+    ),
+    request(
+        "UNSUPPORTED_PROTOCOL_VERSION",
+        &[426],
+        Severity::Error,
+        false,
+        "The on-prem sync agent must be upgraded",
+    ),
+    request(
+        "UPSTREAM_UNAVAILABLE",
+        &[503],
+        Severity::Critical,
+        true,
+        "A dependency such as the database or job queue is unavailable",
+    ),
+    request(
+        "WEAK_PASSWORD",
+        &[400],
+        Severity::Error,
+        false,
+        "The password is too short, too long or made of letters only",
+    ),
+    request(
+        "WEBHOOK_NOT_CONFIGURED",
+        &[422],
+        Severity::Error,
+        false,
+        "Async validation needs a webhook URL in the account settings",
+    ),
+];
+
+/// Registry entries for a code, one per kind it appears as
+pub fn lookup(code: &str) -> impl Iterator<Item = &'static ErrorCodeInfo> + '_ {
+    ERROR_CODES.iter().filter(move |info| info.code == code)
+}
+
+/// One validation result code, as listed by `GET /error-codes` and GraphQL `errorCodes`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema, async_graphql::SimpleObject)]
+pub struct ValidationErrorCode {
+    pub code: VerdictCode,
+    pub severity: Severity,
+    /// Whether validating the address again later may reach another verdict
+    pub retryable: bool,
+    pub description: String,
+}
+
+/// Every validation result code, in [`VerdictCode::ALL`] order
+pub fn validation_error_codes() -> Vec<ValidationErrorCode> {
+    VerdictCode::ALL
+        .into_iter()
+        .map(|code| {
+            let info = code.info();
+            ValidationErrorCode {
+                code,
+                severity: info.severity,
+                retryable: info.retryable,
+                description: info.description.to_string(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ApiError;
+    use std::path::Path;
+
+    /// Quoted codes following `marker` in `source`
+    fn codes_after<'a>(source: &'a str, marker: &str) -> Vec<&'a str> {
+        source
+            .match_indices(marker)
+            .filter_map(|(at, _)| {
+                let rest = source[at + marker.len()..].trim_start().strip_prefix('"')?;
+                let code = &rest[..rest.find('"')?];
+                let is_code = code.len() > 2
+                    && code
+                        .chars()
+                        .all(|c| c.is_ascii_uppercase() || c == '_' || c.is_ascii_digit());
+                is_code.then_some(code)
+            })
+            .collect()
+    }
+
+    fn collect_codes(dir: &Path, codes: &mut Vec<(String, String)>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                collect_codes(&path, codes);
+                continue;
+            }
+            let name = path.to_string_lossy().to_string();
+            if !name.ends_with(".rs") || name.ends_with("_test.rs") || name.ends_with("tests.rs") {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            let source = source.split("#[cfg(test)]").next().unwrap();
+            for marker in [
+                "validation(",
+                "not_found(",
+                "auth(",
+                "code: ",
+                "\"error\": ",
+            ] {
+                for code in codes_after(source, marker) {
+                    codes.push((code.to_string(), name.clone()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_returned_code_is_registered() {
+        let mut codes = Vec::new();
+        collect_codes(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut codes,
+        );
+        assert!(codes.iter().any(|(code, _)| code == "INVALID_SYNTAX"));
+        for (code, file) in &codes {
+            assert!(
+                lookup(code).next().is_some(),
+                "{} returned in {} is missing from ERROR_CODES",
+                code,
+                file
+            );
+        }
+
+        let fixed = [
+            ApiError::unauthorized("x"),
+            ApiError::RateLimited {
+                message: "x".to_string(),
+                retry_after_secs: None,
+            },
+            ApiError::upstream("database", "x"),
+            ApiError::internal("x"),
+        ];
+        for err in fixed {
+            assert!(lookup(err.code()).next().is_some(), "{}", err.code());
+        }
+    }
+
+    #[test]
+    fn test_registry_is_consistent() {
+        for (i, info) in ERROR_CODES.iter().enumerate() {
+            assert!(!info.description.is_empty());
+            assert_eq!(info.kind == CodeKind::Verdict, info.statuses.is_empty());
+            assert!(
+                ERROR_CODES[..i]
+                    .iter()
+                    .all(|other| (other.code, other.kind) != (info.code, info.kind)),
+                "{} listed twice",
+                info.code
+            );
+        }
+        assert_eq!(lookup("INVALID_INPUT").count(), 2);
+    }
+
+    #[test]
+    fn test_verdict_codes_match_registry() {
+        let registered = ERROR_CODES
+            .iter()
+            .filter(|info| info.kind == CodeKind::Verdict)
+            .count();
+        assert_eq!(registered, VerdictCode::ALL.len());
+        for code in VerdictCode::ALL {
+            assert_eq!(VerdictCode::parse(code.as_str()), Some(code));
+            assert_eq!(
+                serde_json::to_value(code).unwrap(),
+                serde_json::json!(code.as_str())
+            );
+            assert_eq!(code.info().code, code.as_str());
+        }
+        assert_eq!(VerdictCode::parse("UNAUTHORIZED"), None);
+    }
+}
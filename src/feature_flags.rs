@@ -0,0 +1,410 @@
+use crate::error::ApiError;
+use crate::stores::CacheStore;
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::StatusCode;
+use actix_web::{Error, web};
+use redis::RedisError;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::future::{Ready, ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// Redis hash holding the flags set through the admin API
+const FLAGS_KEY: &str = "feature_flags";
+
+/// How long an instance reuses the flags it read from Redis
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Seconds clients are told to wait during maintenance when
+/// `MAINTENANCE_RETRY_AFTER_SECS` is unset
+pub const DEFAULT_MAINTENANCE_RETRY_AFTER_SECS: u64 = 300;
+
+/// A switch ops can flip at runtime, e.g. during incident response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Flag {
+    /// Answers every route except health, status and admin with 503
+    MaintenanceMode,
+    /// Bulk validation, queued jobs and saved list validation
+    BulkEnabled,
+    /// Account registration
+    RegistrationOpen,
+}
+
+impl Flag {
+    pub const ALL: [Flag; 3] = [
+        Flag::MaintenanceMode,
+        Flag::BulkEnabled,
+        Flag::RegistrationOpen,
+    ];
+
+    pub fn parse(flag: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|candidate| candidate.as_str() == flag.trim())
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Flag::MaintenanceMode => "maintenance_mode",
+            Flag::BulkEnabled => "bulk_enabled",
+            Flag::RegistrationOpen => "registration_open",
+        }
+    }
+
+    /// Value when neither the environment nor Redis sets the flag
+    pub fn default_value(&self) -> bool {
+        !matches!(self, Flag::MaintenanceMode)
+    }
+
+    /// `FEATURE_<FLAG>`, e.g. `FEATURE_MAINTENANCE_MODE=true`
+    pub fn env_var(&self) -> String {
+        format!("FEATURE_{}", self.as_str().to_ascii_uppercase())
+    }
+
+    fn env_override(&self) -> Option<bool> {
+        let value = std::env::var(self.env_var()).ok()?;
+        match value.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" => Some(true),
+            "false" | "0" | "no" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// Where a flag's value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagSource {
+    /// A `FEATURE_*` variable, which the admin API can't override
+    Env,
+    Redis,
+    Default,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct FlagState {
+    pub flag: Flag,
+    pub enabled: bool,
+    pub source: FlagSource,
+}
+
+impl FlagState {
+    /// The environment wins over Redis, which wins over the flag's default
+    pub fn resolve(flag: Flag, env: Option<bool>, stored: Option<bool>) -> Self {
+        let (enabled, source) = match (env, stored) {
+            (Some(enabled), _) => (enabled, FlagSource::Env),
+            (None, Some(enabled)) => (enabled, FlagSource::Redis),
+            (None, None) => (flag.default_value(), FlagSource::Default),
+        };
+        FlagState {
+            flag,
+            enabled,
+            source,
+        }
+    }
+}
+
+/// Stored flags with the time they were read from Redis
+type CachedFlags = Option<(Instant, HashMap<String, bool>)>;
+
+/// Feature flags stored in Redis, with `FEATURE_*` environment overrides
+///
+/// Each instance re-reads Redis at most every few seconds, so a flag flipped
+/// through the admin API takes effect everywhere shortly after. Without Redis the
+/// last values read, or the defaults, stay in force.
+#[derive(Clone, Default)]
+pub struct FeatureFlags {
+    store: Option<Arc<dyn CacheStore>>,
+    cached: Arc<Mutex<CachedFlags>>,
+}
+
+impl FeatureFlags {
+    pub fn from_store(store: Arc<dyn CacheStore>) -> Self {
+        Self {
+            store: Some(store),
+            cached: Arc::default(),
+        }
+    }
+
+    async fn stored(&self) -> HashMap<String, bool> {
+        let cached = self.cached.lock().unwrap().clone();
+        if let Some((read_at, flags)) = &cached
+            && read_at.elapsed() < REFRESH_INTERVAL
+        {
+            return flags.clone();
+        }
+
+        // An unreachable Redis is retried after the same interval, not on every request
+        let flags = match self.read().await {
+            Ok(flags) => flags,
+            Err(_) => cached.map(|(_, flags)| flags).unwrap_or_default(),
+        };
+        *self.cached.lock().unwrap() = Some((Instant::now(), flags.clone()));
+        flags
+    }
+
+    async fn read(&self) -> Result<HashMap<String, bool>, RedisError> {
+        let Some(store) = &self.store else {
+            return Ok(HashMap::new());
+        };
+        let raw = store.hgetall(FLAGS_KEY).await?;
+        Ok(raw
+            .into_iter()
+            .map(|(flag, value)| (flag, value == "true"))
+            .collect())
+    }
+
+    pub async fn state(&self, flag: Flag) -> FlagState {
+        let stored = self.stored().await.get(flag.as_str()).copied();
+        FlagState::resolve(flag, flag.env_override(), stored)
+    }
+
+    pub async fn is_enabled(&self, flag: Flag) -> bool {
+        self.state(flag).await.enabled
+    }
+
+    pub async fn states(&self) -> Vec<FlagState> {
+        let stored = self.stored().await;
+        Flag::ALL
+            .into_iter()
+            .map(|flag| {
+                FlagState::resolve(
+                    flag,
+                    flag.env_override(),
+                    stored.get(flag.as_str()).copied(),
+                )
+            })
+            .collect()
+    }
+
+    /// Stores a flag, or clears it back to its default with `None`
+    pub async fn set(&self, flag: Flag, enabled: Option<bool>) -> Result<(), RedisError> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        match enabled {
+            Some(enabled) => {
+                store
+                    .hset(FLAGS_KEY, flag.as_str(), &enabled.to_string())
+                    .await?;
+            }
+            None => {
+                store.hdel(FLAGS_KEY, flag.as_str()).await?;
+            }
+        }
+        // This instance sees its own change right away
+        *self.cached.lock().unwrap() = None;
+        Ok(())
+    }
+
+    /// `FEATURE_DISABLED` (503) while `flag` is off
+    pub async fn require(&self, flag: Flag) -> Result<(), ApiError> {
+        if self.is_enabled(flag).await {
+            return Ok(());
+        }
+        Err(disabled(flag))
+    }
+}
+
+/// Error of a request to a feature that is switched off
+pub fn disabled(flag: Flag) -> ApiError {
+    ApiError::validation(
+        "FEATURE_DISABLED",
+        "This feature is temporarily disabled; try again later",
+    )
+    .with_status(StatusCode::SERVICE_UNAVAILABLE)
+    .with_details(json!({ "flag": flag }))
+}
+
+/// Seconds clients are told to wait during maintenance (`MAINTENANCE_RETRY_AFTER_SECS`)
+pub fn maintenance_retry_after_secs() -> u64 {
+    std::env::var("MAINTENANCE_RETRY_AFTER_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_MAINTENANCE_RETRY_AFTER_SECS)
+}
+
+/// Routes served during maintenance: health and status checks, and the admin API
+/// that turns maintenance off again
+pub fn is_exempt_path(path: &str) -> bool {
+    let path = path.trim_end_matches('/');
+    let path = path.strip_prefix("/api/v1").unwrap_or(path);
+    matches!(path, "/health" | "/ready" | "/status")
+        || path.starts_with("/health/")
+        || path.starts_with("/status/")
+        || path.starts_with("/admin/")
+}
+
+pub struct MaintenanceMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for MaintenanceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let flags = req
+            .app_data::<web::Data<FeatureFlags>>()
+            .map(|flags| flags.get_ref().clone())
+            .filter(|_| !is_exempt_path(req.path()));
+
+        Box::pin(async move {
+            let under_maintenance = match &flags {
+                Some(flags) => flags.is_enabled(Flag::MaintenanceMode).await,
+                None => false,
+            };
+            if !under_maintenance {
+                return service
+                    .call(req)
+                    .await
+                    .map(ServiceResponse::map_into_left_body);
+            }
+
+            let err = ApiError::validation(
+                "MAINTENANCE",
+                "The service is down for maintenance; try again later",
+            )
+            .with_status(StatusCode::SERVICE_UNAVAILABLE);
+            let mut res = req.error_response(err);
+            if let Ok(value) = maintenance_retry_after_secs().to_string().parse() {
+                res.headers_mut()
+                    .insert(actix_web::http::header::RETRY_AFTER, value);
+            }
+            Ok(res.map_into_right_body())
+        })
+    }
+}
+
+/// Answers 503 with `Retry-After` while `maintenance_mode` is on
+///
+/// Reads the [`FeatureFlags`] app data; requests pass through when none is registered.
+pub struct Maintenance;
+
+impl Maintenance {
+    pub fn from_app_data() -> Self {
+        Maintenance
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Maintenance
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = MaintenanceMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MaintenanceMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stores::MemoryStore;
+
+    #[test]
+    fn test_flag_resolution() {
+        let state = FlagState::resolve(Flag::BulkEnabled, None, None);
+        assert!(state.enabled);
+        assert_eq!(state.source, FlagSource::Default);
+        assert!(!FlagState::resolve(Flag::MaintenanceMode, None, None).enabled);
+
+        let state = FlagState::resolve(Flag::BulkEnabled, None, Some(false));
+        assert!(!state.enabled);
+        assert_eq!(state.source, FlagSource::Redis);
+
+        let state = FlagState::resolve(Flag::BulkEnabled, Some(true), Some(false));
+        assert!(state.enabled);
+        assert_eq!(state.source, FlagSource::Env);
+
+        assert_eq!(
+            Flag::parse("registration_open"),
+            Some(Flag::RegistrationOpen)
+        );
+        assert_eq!(Flag::parse("smtp"), None);
+        assert_eq!(Flag::MaintenanceMode.env_var(), "FEATURE_MAINTENANCE_MODE");
+    }
+
+    #[test]
+    fn test_exempt_paths() {
+        assert!(is_exempt_path("/api/v1/health"));
+        assert!(is_exempt_path("/api/v1/health/history"));
+        assert!(is_exempt_path("/api/v1/ready"));
+        assert!(is_exempt_path("/status"));
+        assert!(is_exempt_path("/api/v1/admin/flags"));
+        assert!(!is_exempt_path("/api/v1/validate-email"));
+        assert!(!is_exempt_path("/api/v1/healthy"));
+        assert!(!is_exempt_path("/api/v1/graphql"));
+    }
+
+    #[actix_web::test]
+    async fn test_maintenance_middleware() {
+        use actix_web::{App, HttpResponse, test as actix_test};
+
+        unsafe {
+            std::env::set_var("FEATURE_MAINTENANCE_MODE", "true");
+        }
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(FeatureFlags::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .wrap(Maintenance::from_app_data())
+                .route("/api/v1/health", web::get().to(HttpResponse::Ok))
+                .route("/api/v1/validate-email", web::post().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::post()
+            .uri("/api/v1/validate-email")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(resp.headers().contains_key("Retry-After"));
+
+        let req = actix_test::TestRequest::get()
+            .uri("/api/v1/health")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        unsafe {
+            std::env::remove_var("FEATURE_MAINTENANCE_MODE");
+        }
+    }
+
+    #[test]
+    fn test_disabled_error() {
+        let err = disabled(Flag::BulkEnabled);
+        assert_eq!(err.code(), "FEATURE_DISABLED");
+        assert_eq!(
+            actix_web::ResponseError::status_code(&err),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+}
@@ -0,0 +1,635 @@
+use crate::auth::{self, AuthenticatedAccount, PlanTier};
+use crate::bulk;
+use crate::error::ApiError;
+use crate::feature_flags::{FeatureFlags, Flag};
+use crate::handlers::validation::custom_lists::{self, AddEntryError, CustomList};
+use crate::metering::{self, Meter, OveragePolicy, PlanQuota};
+use crate::organizations::{self, AccountScope};
+use crate::validation_history::{self, HistoryEntry, HistoryFilter, ValidationSource};
+use actix_web::http::StatusCode;
+use async_graphql::{Context, Enum, ErrorExtensions, Object, Result, SimpleObject};
+use chrono::Utc;
+use mongodb::Client as MongoClient;
+use tokio::sync::OnceCell;
+
+/// Bearer token of the current HTTP request, attached by the GraphQL handler
+#[derive(Clone, Debug)]
+pub struct BearerToken(pub String);
+
+/// Result of mutations that issue a new API key
+#[derive(SimpleObject)]
+pub struct ApiKeyPayload {
+    /// Email address of the account the key belongs to
+    pub email: String,
+    /// The newly issued API key; previously issued keys no longer work
+    pub api_key: String,
+}
+
+/// The authenticated account, for resolvers that act on behalf of the caller
+pub fn current_account<'a>(ctx: &'a Context<'_>) -> Result<&'a AuthenticatedAccount> {
+    ctx.data_opt::<AuthenticatedAccount>()
+        .ok_or_else(|| ApiError::unauthorized("Authentication required").extend())
+}
+
+/// Which of the caller's custom lists an operation targets
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum CustomListKind {
+    /// Domains and addresses that always fail with `CUSTOM_BLOCKED`
+    Blocklist,
+    /// Domains and addresses that are always accepted
+    Allowlist,
+}
+
+impl From<CustomListKind> for CustomList {
+    fn from(kind: CustomListKind) -> Self {
+        match kind {
+            CustomListKind::Blocklist => CustomList::Blocklist,
+            CustomListKind::Allowlist => CustomList::Allowlist,
+        }
+    }
+}
+
+/// An entry of a custom list
+#[derive(SimpleObject)]
+pub struct CustomListEntryObject {
+    /// Domain (covering its subdomains) or full address
+    pub entry: String,
+    /// Unix timestamp the entry was added
+    pub created_at: i64,
+}
+
+impl From<custom_lists::CustomListEntry> for CustomListEntryObject {
+    fn from(entry: custom_lists::CustomListEntry) -> Self {
+        CustomListEntryObject {
+            entry: entry.entry,
+            created_at: entry.created_at,
+        }
+    }
+}
+
+/// A validation from the caller's history
+#[derive(SimpleObject)]
+pub struct ValidationHistoryEntry {
+    /// SHA-256 of the trimmed, lowercased address, keyed with `PII_HASH_SALT` when set
+    pub email_hash: String,
+    /// The address, for records stored with `PII_HISTORY=encrypt`
+    pub email: Option<String>,
+    /// `VALID` or `INVALID`
+    pub verdict: String,
+    pub error_code: Option<String>,
+    /// `rest`, `graphql` or `bulk`
+    pub source: String,
+    /// Unix timestamp of the validation
+    pub validated_at: i64,
+}
+
+impl From<HistoryEntry> for ValidationHistoryEntry {
+    fn from(entry: HistoryEntry) -> Self {
+        ValidationHistoryEntry {
+            email_hash: entry.email_hash,
+            email: entry.email,
+            verdict: entry.verdict,
+            error_code: entry.error_code,
+            source: match entry.source {
+                ValidationSource::Rest => "rest",
+                ValidationSource::Graphql => "graphql",
+                ValidationSource::Bulk => "bulk",
+            }
+            .to_string(),
+            validated_at: entry.validated_at,
+        }
+    }
+}
+
+/// Subscription plan, as used for quotas and queue priority
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum Plan {
+    Free,
+    Pro,
+    Enterprise,
+}
+
+impl From<PlanTier> for Plan {
+    fn from(plan: PlanTier) -> Self {
+        match plan {
+            PlanTier::Free => Plan::Free,
+            PlanTier::Pro => Plan::Pro,
+            PlanTier::Enterprise => Plan::Enterprise,
+        }
+    }
+}
+
+/// What happens to validations once the quota and grace are used up
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum OveragePolicyKind {
+    HardStop,
+    SoftOverage,
+    Throttle,
+}
+
+impl From<OveragePolicy> for OveragePolicyKind {
+    fn from(policy: OveragePolicy) -> Self {
+        match policy {
+            OveragePolicy::HardStop => OveragePolicyKind::HardStop,
+            OveragePolicy::SoftOverage => OveragePolicyKind::SoftOverage,
+            OveragePolicy::Throttle => OveragePolicyKind::Throttle,
+        }
+    }
+}
+
+/// Metered usage of the current billing period
+#[derive(SimpleObject)]
+pub struct AccountUsage {
+    /// Billing period, e.g. `2024-05`
+    pub period: String,
+    /// Validated addresses so far, shared by all members of an organization
+    pub consumed: u64,
+    /// Requests left within the monthly quota (grace not included); null when unlimited
+    pub remaining: Option<u64>,
+    /// Unix timestamp the next period starts
+    pub resets_at: i64,
+}
+
+/// Quota and limits of the account's plan
+#[derive(SimpleObject)]
+pub struct AccountLimits {
+    pub plan: Plan,
+    /// Validated addresses per calendar month; null when unlimited
+    pub monthly_quota: Option<u64>,
+    /// Share of the quota served on top before the overage policy applies
+    pub grace_percent: u64,
+    pub overage_policy: OveragePolicyKind,
+    /// Requests per minute a throttled account keeps
+    pub trickle_per_minute: u64,
+    /// Most addresses accepted in one bulk request
+    pub max_batch_size: u64,
+}
+
+/// The authenticated account, with its usage and limits
+pub struct Account {
+    email: String,
+    scope: OnceCell<AccountScope>,
+}
+
+impl Account {
+    /// Billing scope of the account, resolved once per query
+    async fn scope(&self, ctx: &Context<'_>) -> Result<&AccountScope> {
+        self.scope
+            .get_or_try_init(|| async {
+                organizations::account_scope(mongo_client(ctx)?, &self.email)
+                    .await
+                    .map_err(|e| ApiError::upstream("database", e).extend())
+            })
+            .await
+    }
+}
+
+#[Object]
+impl Account {
+    /// Email of the account the request is authenticated as
+    async fn email(&self) -> &str {
+        &self.email
+    }
+
+    /// Usage of the current billing period, in validated addresses
+    async fn usage(&self, ctx: &Context<'_>) -> Result<AccountUsage> {
+        let meter = ctx
+            .data_opt::<Meter>()
+            .ok_or_else(|| ApiError::upstream("usage store", "no meter in schema data").extend())?;
+        let scope = self.scope(ctx).await?;
+
+        let now = Utc::now();
+        let period = metering::billing_period(now);
+        let consumed = meter
+            .usage(&scope.owner, &period)
+            .await
+            .map_err(|e| ApiError::upstream("usage store", e).extend())?;
+        Ok(AccountUsage {
+            remaining: PlanQuota::for_plan(scope.plan)
+                .monthly_limit
+                .map(|limit| limit.saturating_sub(consumed)),
+            resets_at: now.timestamp() + metering::secs_until_next_period(now) as i64,
+            period,
+            consumed,
+        })
+    }
+
+    /// Quota and limits of the plan, the organization's when the account belongs to one
+    async fn limits(&self, ctx: &Context<'_>) -> Result<AccountLimits> {
+        let plan = self.scope(ctx).await?.plan;
+        let quota = PlanQuota::for_plan(plan);
+        Ok(AccountLimits {
+            plan: plan.into(),
+            monthly_quota: quota.monthly_limit,
+            grace_percent: quota.grace_percent,
+            overage_policy: quota.policy.into(),
+            trickle_per_minute: quota.trickle_per_minute,
+            max_batch_size: bulk::max_batch_size() as u64,
+        })
+    }
+}
+
+/// Account queries for the authenticated caller
+#[derive(Default)]
+pub struct AccountQuery;
+
+#[Object]
+impl AccountQuery {
+    /// The account the request is authenticated as
+    async fn me(&self, ctx: &Context<'_>) -> Result<Account> {
+        Ok(Account {
+            email: current_account(ctx)?.email.clone(),
+            scope: OnceCell::new(),
+        })
+    }
+
+    /// Entries of the caller's blocklist or allowlist
+    async fn custom_list(
+        &self,
+        ctx: &Context<'_>,
+        list: CustomListKind,
+    ) -> Result<Vec<CustomListEntryObject>> {
+        let owner = list_owner(ctx, false).await?;
+        let entries = custom_lists::list_entries(mongo_client(ctx)?, &owner, list.into())
+            .await
+            .map_err(|e| ApiError::upstream("database", e).extend())?;
+        Ok(entries.into_iter().map(Into::into).collect())
+    }
+
+    /// The caller's validations, newest first, like `GET /api/v1/history`
+    async fn validation_history(
+        &self,
+        ctx: &Context<'_>,
+        email: Option<String>,
+        from: Option<i64>,
+        to: Option<i64>,
+        page: Option<usize>,
+    ) -> Result<Vec<ValidationHistoryEntry>> {
+        let owner = &current_account(ctx)?.email;
+        let page = page.unwrap_or(1);
+        if page == 0 {
+            return Err(ApiError::validation("INVALID_PAGE", "page starts at 1").extend());
+        }
+        let filter = HistoryFilter { email, from, to };
+        filter
+            .check()
+            .map_err(|message| ApiError::validation("INVALID_RANGE", message).extend())?;
+
+        let entries = validation_history::query_history(mongo_client(ctx)?, owner, &filter, page)
+            .await
+            .map_err(|e| ApiError::upstream("database", e).extend())?;
+        Ok(entries.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Account and API key management mutations, mirroring the REST `/register` flow
+#[derive(Default)]
+pub struct AccountMutation;
+
+pub(crate) fn mongo_client<'a>(ctx: &'a Context<'_>) -> Result<&'a MongoClient> {
+    ctx.data_opt::<MongoClient>()
+        .ok_or_else(|| ApiError::upstream("database", "no client in schema data").extend())
+}
+
+/// Refuses account changes on read-only replicas
+pub(crate) fn ensure_writable() -> Result<()> {
+    if crate::read_only::is_enabled() {
+        return Err(crate::read_only::read_only_error().extend());
+    }
+    Ok(())
+}
+
+/// Refuses features switched off through the feature flags attached by the handler
+pub(crate) async fn ensure_enabled(ctx: &Context<'_>, flag: Flag) -> Result<()> {
+    match ctx.data_opt::<FeatureFlags>() {
+        Some(flags) => flags.require(flag).await.map_err(|e| e.extend()),
+        None => Ok(()),
+    }
+}
+
+/// Charges `units` validated addresses to the caller's quota through the meter
+/// attached by the handler
+pub(crate) async fn charge_usage(ctx: &Context<'_>, units: u64) -> Result<()> {
+    let (Some(meter), Some(account)) = (
+        ctx.data_opt::<Meter>(),
+        ctx.data_opt::<AuthenticatedAccount>(),
+    ) else {
+        return Ok(());
+    };
+    metering::charge(meter, mongo_client(ctx)?, &account.email, units)
+        .await
+        .map_err(|e| ApiError::from(e).extend())
+}
+
+/// Owner of the caller's custom lists; only organization owners may change shared ones
+pub(crate) async fn list_owner(ctx: &Context<'_>, write: bool) -> Result<String> {
+    let email = &current_account(ctx)?.email;
+    let scope = organizations::account_scope(mongo_client(ctx)?, email)
+        .await
+        .map_err(|e| ApiError::upstream("database", e).extend())?;
+    if write && !scope.can_manage_lists() {
+        return Err(ApiError::validation(
+            "FORBIDDEN",
+            "Only organization owners can change shared lists",
+        )
+        .with_status(StatusCode::FORBIDDEN)
+        .extend());
+    }
+    Ok(scope.owner)
+}
+
+async fn add_list_entry(
+    ctx: &Context<'_>,
+    list: CustomList,
+    entry: &str,
+) -> Result<CustomListEntryObject> {
+    ensure_writable()?;
+    let owner = list_owner(ctx, true).await?;
+    custom_lists::add_entry(mongo_client(ctx)?, &owner, list, entry)
+        .await
+        .map(Into::into)
+        .map_err(|e| {
+            match e {
+                AddEntryError::Invalid(message) => ApiError::validation("INVALID_ENTRY", message),
+                AddEntryError::Database(message) => ApiError::upstream("database", message),
+                AddEntryError::ListFull => ApiError::validation(
+                    "LIST_FULL",
+                    format!(
+                        "A list holds at most {} entries",
+                        custom_lists::MAX_ENTRIES_PER_LIST
+                    ),
+                ),
+            }
+            .extend()
+        })
+}
+
+async fn remove_list_entry(ctx: &Context<'_>, list: CustomList, entry: &str) -> Result<bool> {
+    ensure_writable()?;
+    let owner = list_owner(ctx, true).await?;
+    custom_lists::remove_entry(mongo_client(ctx)?, &owner, list, entry)
+        .await
+        .map_err(|e| ApiError::upstream("database", e).extend())
+}
+
+fn bearer_token<'a>(ctx: &'a Context<'_>) -> Result<&'a str> {
+    ctx.data_opt::<BearerToken>()
+        .map(|token| token.0.as_str())
+        .ok_or_else(|| ApiError::unauthorized("Missing Authorization header").extend())
+}
+
+#[Object]
+impl AccountMutation {
+    /// Creates an account and returns its first API key, usable once the verification
+    /// link sent to the address has been followed
+    async fn register(
+        &self,
+        ctx: &Context<'_>,
+        email: String,
+        password: String,
+    ) -> Result<ApiKeyPayload> {
+        ensure_writable()?;
+        ensure_enabled(ctx, Flag::RegistrationOpen).await?;
+        let (email, api_key) = auth::register_account(mongo_client(ctx)?, &email, &password)
+            .await
+            .map_err(|e| ApiError::from_auth(e).extend())?;
+
+        Ok(ApiKeyPayload { email, api_key })
+    }
+
+    /// Replaces the API key sent in the Authorization header with a new one
+    async fn rotate_api_key(&self, ctx: &Context<'_>) -> Result<ApiKeyPayload> {
+        ensure_writable()?;
+        let (email, api_key) = auth::rotate_api_key(mongo_client(ctx)?, bearer_token(ctx)?)
+            .await
+            .map_err(|e| ApiError::from_auth(e).extend())?;
+
+        Ok(ApiKeyPayload { email, api_key })
+    }
+
+    /// Revokes the API key sent in the Authorization header without issuing a new one
+    async fn revoke_api_key(&self, ctx: &Context<'_>) -> Result<bool> {
+        ensure_writable()?;
+        auth::revoke_api_key(mongo_client(ctx)?, bearer_token(ctx)?)
+            .await
+            .map_err(|e| ApiError::from_auth(e).extend())?;
+
+        Ok(true)
+    }
+
+    /// Adds a domain or address to the caller's blocklist or allowlist
+    async fn add_custom_list_entry(
+        &self,
+        ctx: &Context<'_>,
+        list: CustomListKind,
+        entry: String,
+    ) -> Result<CustomListEntryObject> {
+        add_list_entry(ctx, list.into(), &entry).await
+    }
+
+    /// Removes an entry from the caller's list; false when it wasn't listed
+    async fn remove_custom_list_entry(
+        &self,
+        ctx: &Context<'_>,
+        list: CustomListKind,
+        entry: String,
+    ) -> Result<bool> {
+        remove_list_entry(ctx, list.into(), &entry).await
+    }
+
+    /// Blocks a domain or address, like `POST /api/v1/account/blocklist`
+    async fn add_denylist_entry(
+        &self,
+        ctx: &Context<'_>,
+        entry: String,
+    ) -> Result<CustomListEntryObject> {
+        add_list_entry(ctx, CustomList::Blocklist, &entry).await
+    }
+
+    /// Like `DELETE /api/v1/account/blocklist/{entry}`; false when it wasn't listed
+    async fn remove_denylist_entry(&self, ctx: &Context<'_>, entry: String) -> Result<bool> {
+        remove_list_entry(ctx, CustomList::Blocklist, &entry).await
+    }
+
+    /// Always accepts a domain or address, like `POST /api/v1/account/allowlist`
+    async fn add_allowlist_entry(
+        &self,
+        ctx: &Context<'_>,
+        entry: String,
+    ) -> Result<CustomListEntryObject> {
+        add_list_entry(ctx, CustomList::Allowlist, &entry).await
+    }
+
+    /// Like `DELETE /api/v1/account/allowlist/{entry}`; false when it wasn't listed
+    async fn remove_allowlist_entry(&self, ctx: &Context<'_>, entry: String) -> Result<bool> {
+        remove_list_entry(ctx, CustomList::Allowlist, &entry).await
+    }
+
+    /// Changes the account's password and/or starts an email change, confirmed by the
+    /// verification link sent to the new address; returns a fresh API key
+    async fn update_account(
+        &self,
+        ctx: &Context<'_>,
+        email: Option<String>,
+        password: Option<String>,
+    ) -> Result<ApiKeyPayload> {
+        ensure_writable()?;
+        if email.is_none() && password.is_none() {
+            return Err(ApiError::validation(
+                "INVALID_REQUEST",
+                "Provide an email or password to update",
+            )
+            .extend());
+        }
+
+        let (email, api_key) = auth::update_account(
+            mongo_client(ctx)?,
+            bearer_token(ctx)?,
+            email.as_deref(),
+            password.as_deref(),
+        )
+        .await
+        .map_err(|e| ApiError::from_auth(e).extend())?;
+
+        Ok(ApiKeyPayload { email, api_key })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{EmptySubscription, Schema};
+
+    fn schema() -> Schema<crate::graphql::health::HealthQuery, AccountMutation, EmptySubscription> {
+        Schema::build(
+            crate::graphql::health::HealthQuery,
+            AccountMutation,
+            EmptySubscription,
+        )
+        .finish()
+    }
+
+    #[tokio::test]
+    async fn test_mutations_exposed_in_sdl() {
+        let sdl = schema().sdl();
+
+        assert!(sdl.contains("register(email: String!, password: String!): ApiKeyPayload!"));
+        assert!(sdl.contains("rotateApiKey: ApiKeyPayload!"));
+        assert!(sdl.contains("revokeApiKey: Boolean!"));
+        assert!(sdl.contains("updateAccount(email: String, password: String): ApiKeyPayload!"));
+        assert!(sdl.contains(
+            "addCustomListEntry(list: CustomListKind!, entry: String!): CustomListEntryObject!"
+        ));
+        assert!(
+            sdl.contains("removeCustomListEntry(list: CustomListKind!, entry: String!): Boolean!")
+        );
+        assert!(sdl.contains("addDenylistEntry(entry: String!): CustomListEntryObject!"));
+        assert!(sdl.contains("removeAllowlistEntry(entry: String!): Boolean!"));
+    }
+
+    #[tokio::test]
+    async fn test_rotate_requires_authorization() {
+        let request = async_graphql::Request::new("mutation { rotateApiKey { apiKey } }").data(
+            MongoClient::with_uri_str("mongodb://localhost:27017")
+                .await
+                .unwrap(),
+        );
+
+        let res = schema().execute(request).await;
+        assert_eq!(res.errors[0].message, "Missing Authorization header");
+        let extensions = res.errors[0].extensions.as_ref().unwrap();
+        assert_eq!(
+            extensions.get("code"),
+            Some(&async_graphql::Value::from("UNAUTHORIZED"))
+        );
+        assert!(extensions.get("request_id").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_me_requires_authenticated_account() {
+        let schema = Schema::build(AccountQuery, AccountMutation, EmptySubscription).finish();
+
+        let res = schema.execute("{ me { email } }").await;
+        assert_eq!(res.errors[0].message, "Authentication required");
+
+        let request = async_graphql::Request::new("{ me { email } }").data(AuthenticatedAccount {
+            email: "user@example.com".to_string(),
+            ..AuthenticatedAccount::default()
+        });
+        let res = schema.execute(request).await;
+        assert_eq!(
+            res.data.into_json().unwrap()["me"]["email"],
+            serde_json::json!("user@example.com")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validation_history_rejects_reversed_range() {
+        let schema = Schema::build(AccountQuery, AccountMutation, EmptySubscription).finish();
+
+        let res = schema.execute("{ validationHistory { verdict } }").await;
+        assert_eq!(res.errors[0].message, "Authentication required");
+
+        let request =
+            async_graphql::Request::new("{ validationHistory(from: 200, to: 100) { verdict } }")
+                .data(AuthenticatedAccount {
+                    email: "user@example.com".to_string(),
+                    ..AuthenticatedAccount::default()
+                });
+        let res = schema.execute(request).await;
+        assert_eq!(
+            res.errors[0].extensions.as_ref().unwrap().get("code"),
+            Some(&async_graphql::Value::from("INVALID_RANGE"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_usage_requires_meter() {
+        let schema = Schema::build(AccountQuery, AccountMutation, EmptySubscription).finish();
+        let sdl = schema.sdl();
+        assert!(sdl.contains("usage: AccountUsage!"));
+        assert!(sdl.contains("limits: AccountLimits!"));
+
+        let request = async_graphql::Request::new("{ me { usage { consumed } } }").data(
+            AuthenticatedAccount {
+                email: "user@example.com".to_string(),
+                ..AuthenticatedAccount::default()
+            },
+        );
+        let res = schema.execute(request).await;
+        assert_eq!(
+            res.errors[0].message,
+            "The usage store is temporarily unavailable"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_mutations_require_account() {
+        let res = schema()
+            .execute(r#"mutation { addDenylistEntry(entry: "example.com") { entry } }"#)
+            .await;
+        assert_eq!(res.errors[0].message, "Authentication required");
+    }
+
+    #[tokio::test]
+    async fn test_update_account_requires_changes() {
+        let res = schema()
+            .execute("mutation { updateAccount { apiKey } }")
+            .await;
+        assert_eq!(
+            res.errors[0].message,
+            "Provide an email or password to update"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_register_without_database() {
+        let res = schema()
+            .execute(r#"mutation { register(email: "a@example.com", password: "pw") { apiKey } }"#)
+            .await;
+        assert_eq!(
+            res.errors[0].message,
+            "The database is temporarily unavailable"
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use crate::auth::AuthenticatedAccount;
+use crate::checks::Checks;
+use crate::graphql::email::{EmailQuery, EmailValidationError, EmailValidationResponse};
+use crate::validation_history::{self, HistoryRecord, ValidationSource};
+use async_graphql::{Context, Result};
+
+pub(crate) fn rejection(
+    code: &str,
+    message: impl Into<String>,
+    violations: Vec<String>,
+) -> EmailValidationResponse {
+    EmailValidationResponse {
+        is_valid: false,
+        status: None,
+        error: Some(EmailValidationError {
+            code: code.to_string(),
+            message: message.into(),
+            violations,
+        }),
+    }
+}
+
+impl EmailQuery {
+    /// Validates the distinct addresses of a bulk request, each with its own `checks`,
+    /// through the shared validation flow and records them in the caller's history
+    ///
+    /// Custom lists are loaded once; see
+    /// [`ValidationService::validate_batch`](crate::validation_service::ValidationService::validate_batch)
+    /// for the round trips behind the rest. Results are in the order of `emails`, which should
+    /// already be deduplicated.
+    pub(crate) async fn validate_batch(
+        &self,
+        ctx: &Context<'_>,
+        emails: &[String],
+        checks: &[Checks],
+    ) -> Result<Vec<EmailValidationResponse>> {
+        let lists = self.caller_lists(ctx, emails).await?.unwrap_or_default();
+        let tenant_id = ctx
+            .data_opt::<AuthenticatedAccount>()
+            .map(AuthenticatedAccount::tenant_id);
+        let results: Vec<EmailValidationResponse> = self
+            .service_for(tenant_id.as_deref())
+            .validate_batch(emails, checks, &lists)
+            .await
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        if let (Some(account), Some(mongo_client)) = (
+            ctx.data_opt::<AuthenticatedAccount>(),
+            ctx.data_opt::<mongodb::Client>(),
+        ) {
+            let records = emails
+                .iter()
+                .zip(&results)
+                .map(|(email, result)| {
+                    HistoryRecord::new(
+                        &account.email,
+                        email,
+                        result.is_valid,
+                        result.error.as_ref().map(|e| e.code.as_str()),
+                        ValidationSource::Graphql,
+                    )
+                })
+                .collect();
+            validation_history::record(mongo_client, records);
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_validate_batch_without_backends() {
+        let schema = async_graphql::Schema::build(
+            EmailQuery::default(),
+            async_graphql::EmptyMutation,
+            async_graphql::EmptySubscription,
+        )
+        .finish();
+        let res = schema
+            .execute(
+                r#"{ validateEmailsBulk(emails: ["bad", "x@example.fake", "BAD", "bad"]) {
+                    results { email validation { error { code } } }
+                    invalidCount uniqueCount
+                } }"#,
+            )
+            .await;
+        assert!(res.errors.is_empty(), "{:?}", res.errors);
+        let data = res.data.into_json().unwrap();
+        let bulk = &data["validateEmailsBulk"];
+        assert_eq!(bulk["results"].as_array().unwrap().len(), 4);
+        assert_eq!(bulk["results"][1]["email"], "x@example.fake");
+        assert_eq!(
+            bulk["results"][1]["validation"]["error"]["code"],
+            "INVALID_TLD"
+        );
+        assert_eq!(bulk["invalidCount"], 4);
+        assert_eq!(bulk["uniqueCount"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_validate_emails_bulk_items() {
+        let schema = async_graphql::Schema::build(
+            EmailQuery::default(),
+            async_graphql::EmptyMutation,
+            async_graphql::EmptySubscription,
+        )
+        .finish();
+        let res = schema
+            .execute(
+                r#"{ validateEmailsBulk(items: [
+                    { email: "x@example.fake" },
+                    { email: "x@example.fake", checks: [] }
+                ]) {
+                    results { validation { isValid error { code } } }
+                    uniqueCount
+                } }"#,
+            )
+            .await;
+        assert!(res.errors.is_empty(), "{:?}", res.errors);
+        let data = res.data.into_json().unwrap();
+        let bulk = &data["validateEmailsBulk"];
+        assert_eq!(
+            bulk["results"][0]["validation"]["error"]["code"],
+            "INVALID_TLD"
+        );
+        assert_eq!(bulk["results"][1]["validation"]["isValid"], true);
+        assert_eq!(bulk["uniqueCount"], 2);
+
+        let res = schema
+            .execute(r#"{ validateEmailsBulk(emails: ["a@b.c"], items: [{ email: "a@b.c" }]) { uniqueCount } }"#)
+            .await;
+        assert_eq!(res.errors.len(), 1);
+    }
+}
@@ -1,8 +1,27 @@
-use crate::handlers::validation::{disposable, dnsmx, role_based, syntax};
-use crate::job_queue::JobQueue;
-use async_graphql::{Context, Object, Result, SimpleObject};
-use futures::future::join_all;
-use redis::{Client, Commands, RedisError};
+use crate::account_settings;
+use crate::auth::{AuthenticatedAccount, PlanTier};
+use crate::bulk::{self, DedupedBatch, ValidationMemo};
+use crate::checks::{Check, Checks, ItemOptions};
+use crate::error::ApiError;
+use crate::feature_flags::Flag;
+use crate::graphql::account::{charge_usage, current_account, ensure_enabled};
+use crate::graphql::batch;
+use crate::handlers::validation::custom_lists::AccountLists;
+use crate::handlers::validation::first_seen;
+use crate::job_queue::{DEFAULT_TENANT, JobQueue};
+use crate::list_report::{self, ListGrade, ListReport, ReportBuilder};
+use crate::local_cache::LocalTierConfig;
+use crate::organizations;
+use crate::routes;
+use crate::routes::email::{RedisCache, load_owned_job, require_completed};
+use crate::stores::{DomainListStore, MongoDomainLists};
+use crate::validation_cache::{CacheTtlConfig, ValidationCache};
+use crate::validation_history::{self, HistoryRecord, ValidationSource};
+use crate::validation_service::ValidationService;
+use crate::validation_stats::ValidationStats;
+use crate::verification::VerifierChain;
+use async_graphql::{Context, Enum, ErrorExtensions, InputObject, Object, Result, SimpleObject};
+use redis::RedisError;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -10,16 +29,24 @@ use std::sync::Arc;
 ///
 /// Each error corresponds to a specific validation failure:
 /// - `INVALID_SYNTAX`: The email format is not RFC-compliant
+/// - `KNOWN_TRAP`: The address is a known spamtrap or spam complainer
+/// - `PREVIOUS_BOUNCER`: The address has hard-bounced before
+/// - `INVALID_TLD`: The domain's top-level domain is not delegated by IANA
 /// - `INVALID_DOMAIN`: The domain does not have valid DNS/MX records
 /// - `ROLE_BASED_EMAIL`: The email uses a role-based local part (when enabled)
 /// - `DISPOSABLE_EMAIL`: The email comes from a disposable email provider
+/// - `CUSTOM_BLOCKED`: The address or its domain is on the caller's blocklist
+/// - `UNDELIVERABLE`: A fallback verification provider rejected the address
 /// - `DATABASE_ERROR`: Could not check disposable email database
 #[derive(SimpleObject, Clone, Serialize, Deserialize, Debug)]
 pub struct EmailValidationError {
-    /// Error code: INVALID_SYNTAX, INVALID_DOMAIN, ROLE_BASED_EMAIL, DISPOSABLE_EMAIL, or DATABASE_ERROR
+    /// Error code: INVALID_SYNTAX, KNOWN_TRAP, PREVIOUS_BOUNCER, INVALID_TLD, INVALID_DOMAIN, ROLE_BASED_EMAIL, DISPOSABLE_EMAIL, CUSTOM_BLOCKED, UNDELIVERABLE, or DATABASE_ERROR
     pub code: String,
     /// Human-readable error message
     pub message: String,
+    /// For INVALID_SYNTAX, each rule the address breaks, e.g. LOCAL_TOO_LONG or CONSECUTIVE_DOTS
+    #[serde(default)]
+    pub violations: Vec<String>,
 }
 
 /// Response object for email validation containing either valid status or error details
@@ -51,6 +78,169 @@ pub struct BulkEmailValidationResponse {
     pub valid_count: i32,
     /// Count of invalid emails in the batch
     pub invalid_count: i32,
+    /// Distinct addresses validated; repeats within the request share one result and count once
+    pub unique_count: i32,
+}
+
+/// A validation stage that can be chosen per address; syntax is always checked
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationCheck {
+    Suppression,
+    Tld,
+    Dns,
+    RoleBased,
+    Disposable,
+}
+
+impl From<ValidationCheck> for Check {
+    fn from(check: ValidationCheck) -> Self {
+        match check {
+            ValidationCheck::Suppression => Check::Suppression,
+            ValidationCheck::Tld => Check::Tld,
+            ValidationCheck::Dns => Check::Dns,
+            ValidationCheck::RoleBased => Check::RoleBased,
+            ValidationCheck::Disposable => Check::Disposable,
+        }
+    }
+}
+
+/// One address of a bulk validation, with options overriding the request's
+#[derive(InputObject)]
+pub struct BulkEmailInput {
+    pub email: String,
+    /// Turns role-based detection on or off for this address
+    pub check_role_based: Option<bool>,
+    /// Stages to run instead of all of them
+    pub checks: Option<Vec<ValidationCheck>>,
+}
+
+impl BulkEmailInput {
+    fn options(&self) -> ItemOptions {
+        ItemOptions {
+            check_role_based: self.check_role_based,
+            checks: self
+                .checks
+                .as_ref()
+                .map(|checks| checks.iter().map(|&check| check.into()).collect()),
+        }
+    }
+}
+
+/// Overall list quality, A (best) to F
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum ListQualityGrade {
+    A,
+    B,
+    C,
+    D,
+    F,
+}
+
+impl From<ListGrade> for ListQualityGrade {
+    fn from(grade: ListGrade) -> Self {
+        match grade {
+            ListGrade::A => ListQualityGrade::A,
+            ListGrade::B => ListQualityGrade::B,
+            ListGrade::C => ListQualityGrade::C,
+            ListGrade::D => ListQualityGrade::D,
+            ListGrade::F => ListQualityGrade::F,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct RiskyDomainCount {
+    pub domain: String,
+    /// Unique addresses of the domain that should be suppressed
+    pub count: u64,
+}
+
+/// List hygiene report, mirroring `GET /api/v1/job-report/{id}`
+///
+/// Category percentages are shares of the unique addresses.
+#[derive(SimpleObject)]
+pub struct ListHygieneReport {
+    /// The bulk job the report was computed from; null for ad-hoc lists
+    pub job_id: Option<String>,
+    pub total: u64,
+    pub unique: u64,
+    pub invalid_percent: f64,
+    pub disposable_percent: f64,
+    pub role_based_percent: f64,
+    pub invalid_domain_percent: f64,
+    /// Share of all rows repeating an earlier address
+    pub duplicate_percent: f64,
+    pub top_risky_domains: Vec<RiskyDomainCount>,
+    /// 0 to 100
+    pub quality_score: u8,
+    pub grade: ListQualityGrade,
+}
+
+impl From<ListReport> for ListHygieneReport {
+    fn from(report: ListReport) -> Self {
+        Self {
+            job_id: report.job_id,
+            total: report.total,
+            unique: report.unique,
+            invalid_percent: report.invalid_percent,
+            disposable_percent: report.disposable_percent,
+            role_based_percent: report.role_based_percent,
+            invalid_domain_percent: report.invalid_domain_percent,
+            duplicate_percent: report.duplicate_percent,
+            top_risky_domains: report
+                .top_risky_domains
+                .into_iter()
+                .map(|d| RiskyDomainCount {
+                    domain: d.domain,
+                    count: d.count,
+                })
+                .collect(),
+            quality_score: report.quality_score,
+            grade: report.grade.into(),
+        }
+    }
+}
+
+/// How long an address has existed according to the first-seen dataset
+#[derive(SimpleObject)]
+pub struct EmailAgeReport {
+    /// Whether the address appears in the dataset
+    pub seen: bool,
+    /// RFC 3339 timestamp of the first sighting, if seen
+    pub first_seen: Option<String>,
+    /// Days since the first sighting, if seen
+    pub age_days: Option<i64>,
+    /// Risk contribution from address age, 0 (established) to 40 (never seen)
+    pub risk_score: i32,
+}
+
+impl From<first_seen::EmailAge> for EmailAgeReport {
+    fn from(age: first_seen::EmailAge) -> Self {
+        EmailAgeReport {
+            seen: age.seen,
+            first_seen: age.first_seen,
+            age_days: age.age_days,
+            risk_score: age.risk_score as i32,
+        }
+    }
+}
+
+impl From<routes::email::EmailValidationResponse> for EmailValidationResponse {
+    fn from(resp: routes::email::EmailValidationResponse) -> Self {
+        EmailValidationResponse {
+            is_valid: resp.is_valid,
+            status: resp.status,
+            error: resp.error.map(|error| EmailValidationError {
+                code: error.code,
+                message: error.message,
+                violations: error
+                    .violations
+                    .iter()
+                    .map(|violation| violation.as_str().to_string())
+                    .collect(),
+            }),
+        }
+    }
 }
 
 /// Serializable version of the validation response
@@ -82,99 +272,218 @@ impl From<EmailValidationResponse> for CachedValidationResponse {
 }
 
 /// Email validation query operations
-#[derive(Default)]
 pub struct EmailQuery {
-    pub redis_client: Option<Arc<Client>>,
-    pub cache_ttl: u64,
+    pub cache: ValidationCache,
+    pub stats: ValidationStats,
+    pub verifiers: VerifierChain,
+    /// Disposable and role-based reference lists
+    pub domain_lists: Arc<dyn DomainListStore>,
+    /// DNS verdict cache; without one, every lookup goes to the resolver
+    pub dns: Option<RedisCache>,
+}
+
+impl Default for EmailQuery {
+    fn default() -> Self {
+        Self {
+            cache: ValidationCache::default(),
+            stats: ValidationStats::default(),
+            verifiers: VerifierChain::default(),
+            domain_lists: Arc::new(MongoDomainLists::from_env()),
+            dns: None,
+        }
+    }
 }
 
 impl EmailQuery {
+    /// Creates a caching query object; `cache_ttl` is the lifetime of valid results and
+    /// DNS verdicts, other outcome classes use the `VALIDATION_CACHE_TTL_*` settings
+    ///
+    /// An in-process tier (`VALIDATION_CACHE_LOCAL_*`) sits in front of Redis and keeps
+    /// answering while Redis is unreachable.
     pub fn new(redis_url: &str, cache_ttl: u64) -> Result<Self, RedisError> {
-        let client = Client::open(redis_url)?;
         Ok(Self {
-            redis_client: Some(Arc::new(client)),
-            cache_ttl,
+            cache: ValidationCache::new(redis_url, Self::ttls(cache_ttl))?
+                .with_local_tier(LocalTierConfig::validation_from_env()),
+            stats: ValidationStats::new(redis_url)?,
+            verifiers: VerifierChain::from_env(),
+            domain_lists: Arc::new(MongoDomainLists::from_env()),
+            dns: Some(RedisCache::new(redis_url, cache_ttl)?),
         })
     }
 
-    pub async fn get_cached_result(&self, email: &str) -> Option<EmailValidationResponse> {
-        if let Some(client) = &self.redis_client {
-            let mut conn = client.get_connection().ok()?;
-            let cache_key = format!("email:validation:{}", email);
+    /// Caches in process only, for when no Redis client could be created
+    pub fn local_only(cache_ttl: u64) -> Self {
+        Self {
+            cache: ValidationCache::local_only(
+                LocalTierConfig::validation_from_env(),
+                Self::ttls(cache_ttl),
+            ),
+            stats: ValidationStats::default(),
+            verifiers: VerifierChain::from_env(),
+            domain_lists: Arc::new(MongoDomainLists::from_env()),
+            dns: None,
+        }
+    }
 
-            let cached: Option<String> = conn.get(&cache_key).ok();
+    /// The shared validation flow, through this query's stores
+    pub(crate) fn service(&self) -> ValidationService<'_> {
+        ValidationService::from_parts(
+            &self.cache,
+            &self.stats,
+            &self.verifiers,
+            self.domain_lists.as_ref(),
+            self.dns.as_ref(),
+        )
+    }
 
-            if let Some(cached_str) = cached
-                && let Ok(cached_response) =
-                    serde_json::from_str::<CachedValidationResponse>(&cached_str)
-            {
-                return Some(cached_response.into());
-            }
+    /// The shared validation flow for a caller, indexing its verdicts for the hash
+    /// lookups of `tenant_id`
+    pub(crate) fn service_for<'b>(&'b self, tenant_id: Option<&'b str>) -> ValidationService<'b> {
+        match tenant_id {
+            Some(tenant_id) => self.service().for_tenant(tenant_id),
+            None => self.service(),
         }
-        None
     }
 
-    pub async fn cache_result(&self, email: &str, result: &EmailValidationResponse) {
-        if let Some(client) = &self.redis_client
-            && let Ok(mut conn) = client.get_connection()
-        {
-            let cache_key = format!("email:validation:{}", email);
-            let cached_response: CachedValidationResponse = (*result).clone().into();
-
-            if let Ok(json) = serde_json::to_string(&cached_response) {
-                let _: Result<(), RedisError> = conn.set_ex(&cache_key, json, self.cache_ttl);
-            }
+    fn ttls(cache_ttl: u64) -> CacheTtlConfig {
+        CacheTtlConfig {
+            valid: cache_ttl,
+            ..CacheTtlConfig::from_env()
         }
     }
+
+    pub async fn get_cached_result(
+        &self,
+        email: &str,
+        check_role_based: bool,
+    ) -> Option<EmailValidationResponse> {
+        self.cache
+            .get::<CachedValidationResponse>(email, check_role_based)
+            .await
+            .map(Into::into)
+    }
+
+    pub async fn cache_result(
+        &self,
+        email: &str,
+        check_role_based: bool,
+        result: &EmailValidationResponse,
+    ) {
+        let cached_response: CachedValidationResponse = result.clone().into();
+        let error_code = result.error.as_ref().map(|e| e.code.as_str());
+
+        self.cache
+            .set(email, check_role_based, &cached_response, error_code)
+            .await;
+    }
 }
 
 #[Object]
 impl EmailQuery {
     async fn validate_email(
         &self,
-        _ctx: &Context<'_>,
+        ctx: &Context<'_>,
         email: String,
         check_role_based: Option<bool>,
     ) -> Result<EmailValidationResponse> {
         let email = email.trim();
+        charge_usage(ctx, 1).await?;
 
-        // Try to get cached result first
-        if let Some(cached) = self.get_cached_result(email).await {
-            return Ok(cached);
+        // Options left out come from the account settings
+        let checks = ItemOptions {
+            check_role_based,
+            checks: None,
         }
-
-        // If not in cache, perform validation
-        let validation_result = self
-            .perform_validation(email.to_string(), check_role_based.unwrap_or(false))
-            .await?;
-
-        // Cache the result if it's valid or has a permanent error (like invalid syntax)
-        if validation_result.is_valid
-            || validation_result
-                .error
-                .as_ref()
-                .map(|e| e.code != "DATABASE_ERROR")
-                .unwrap_or(false)
-        {
-            self.cache_result(email, &validation_result).await;
+        .with_defaults(&self.caller_defaults(ctx).await?)
+        .checks(false);
+
+        // Repeats of an address within one request share the first validation
+        let validation = match ctx.data_opt::<ValidationMemo<EmailValidationResponse>>() {
+            Some(memo) => {
+                memo.get_or_validate(email, checks.role_based, || {
+                    self.validate_uncached(ctx, email, checks)
+                })
+                .await
+            }
+            None => self.validate_uncached(ctx, email, checks).await,
+        }?;
+
+        if let (Some(account), Some(mongo_client)) = (
+            ctx.data_opt::<AuthenticatedAccount>(),
+            ctx.data_opt::<mongodb::Client>(),
+        ) {
+            validation_history::record(
+                mongo_client,
+                vec![HistoryRecord::new(
+                    &account.email,
+                    email,
+                    validation.is_valid,
+                    validation.error.as_ref().map(|e| e.code.as_str()),
+                    ValidationSource::Graphql,
+                )],
+            );
         }
-
-        Ok(validation_result)
+        Ok(validation)
     }
 
+    /// Validates plain `emails` or `items` carrying per-address options, not both
     async fn validate_emails_bulk(
         &self,
         ctx: &Context<'_>,
-        emails: Vec<String>,
+        #[graphql(default)] emails: Vec<String>,
+        items: Option<Vec<BulkEmailInput>>,
         use_queue: Option<bool>,
     ) -> Result<BulkEmailValidationResponse> {
+        ensure_enabled(ctx, Flag::BulkEnabled).await?;
+        let defaults = self.caller_defaults(ctx).await?;
+        let (emails, options): (Vec<String>, Vec<ItemOptions>) = match items {
+            Some(_) if !emails.is_empty() => {
+                return Err(ApiError::validation(
+                    "INVALID_INPUT",
+                    "Pass either emails or items, not both",
+                )
+                .extend());
+            }
+            Some(items) => items
+                .into_iter()
+                .map(|item| {
+                    let options = item.options().with_defaults(&defaults);
+                    (item.email, options)
+                })
+                .unzip(),
+            None => {
+                let options = vec![defaults; emails.len()];
+                (emails, options)
+            }
+        };
+        // Each distinct address and set of checks is validated, and charged, once
+        let checks: Vec<Checks> = options
+            .iter()
+            .map(|options| options.checks(false))
+            .collect();
+        let (batch, unique_checks) = DedupedBatch::with_keys(&emails, &checks);
+        charge_usage(ctx, batch.units()).await?;
+
         // Use job queue for large batches if available and requested
         if use_queue.unwrap_or(false)
             && emails.len() > 10
             && let Some(job_queue) = ctx.data_opt::<JobQueue>()
         {
+            let item_options = options
+                .iter()
+                .enumerate()
+                .filter(|(_, options)| !options.is_default())
+                .map(|(i, options)| (i, options.clone()))
+                .collect();
             match job_queue
-                .enqueue_bulk_validation(emails.clone(), false)
+                .enqueue_bulk_validation_with_options(
+                    DEFAULT_TENANT,
+                    PlanTier::default(),
+                    emails.clone(),
+                    false,
+                    item_options,
+                    false,
+                )
                 .await
             {
                 Ok(job_id) => {
@@ -189,6 +498,7 @@ impl EmailQuery {
                         }],
                         valid_count: 0,
                         invalid_count: 0,
+                        unique_count: 0,
                     });
                 }
                 Err(_) => {
@@ -197,159 +507,179 @@ impl EmailQuery {
             }
         }
 
-        let validation_futures = emails
-            .iter()
-            .map(|email| {
-                let email_clone = email.clone();
-                let ctx = ctx.clone();
-                async move {
-                    let validation = self.validate_email(&ctx, email_clone.clone(), None).await?;
-                    Ok::<_, async_graphql::Error>((email_clone, validation))
-                }
-            })
-            .collect::<Vec<_>>();
+        let validations = match self
+            .validate_batch(ctx, &batch.unique, &unique_checks)
+            .await
+        {
+            Ok(results) => results,
+            Err(e) => {
+                let failed = batch::rejection("PROCESSING_ERROR", e.message, Vec::new());
+                vec![failed; batch.unique.len()]
+            }
+        };
 
-        let results = join_all(validation_futures).await;
-        let mut validation_results = Vec::new();
+        let mut validation_results = Vec::with_capacity(emails.len());
         let mut valid_count = 0;
         let mut invalid_count = 0;
-
-        for result in results {
-            match result {
-                Ok((email, validation)) => {
-                    if validation.is_valid {
-                        valid_count += 1;
-                    } else {
-                        invalid_count += 1;
-                    }
-                    validation_results.push(BulkEmailValidationResult { email, validation });
-                }
-                Err(e) => {
-                    invalid_count += 1;
-                    validation_results.push(BulkEmailValidationResult {
-                        email: "unknown".to_string(),
-                        validation: EmailValidationResponse {
-                            is_valid: false,
-                            status: None,
-                            error: Some(EmailValidationError {
-                                code: "PROCESSING_ERROR".to_string(),
-                                message: format!("{:?}", e),
-                            }),
-                        },
-                    });
-                }
+        for (email, validation) in emails.into_iter().zip(batch.fan_out(&validations)) {
+            if validation.is_valid {
+                valid_count += 1;
+            } else {
+                invalid_count += 1;
             }
+            validation_results.push(BulkEmailValidationResult { email, validation });
         }
 
         Ok(BulkEmailValidationResponse {
             results: validation_results,
             valid_count,
             invalid_count,
+            unique_count: batch.unique.len() as i32,
         })
     }
 
+    /// Age of an address from the first-seen dataset; null when lookups are disabled
+    async fn email_age(&self, ctx: &Context<'_>, email: String) -> Result<Option<EmailAgeReport>> {
+        current_account(ctx)?;
+        first_seen::lookup_email_age(email.trim())
+            .await
+            .map(|age| age.map(Into::into))
+            .map_err(|e| ApiError::upstream("first-seen dataset", e).extend())
+    }
+
+    /// Hygiene report of a completed bulk job or of an ad-hoc list
+    ///
+    /// Pass exactly one of `jobId` or `emails`. Lists are validated inline, up to
+    /// `BULK_MAX_BATCH_SIZE` addresses; job reports are cached like the REST endpoint's.
+    async fn list_report(
+        &self,
+        ctx: &Context<'_>,
+        job_id: Option<String>,
+        emails: Option<Vec<String>>,
+    ) -> Result<ListHygieneReport> {
+        let account = current_account(ctx)?;
+        match (job_id, emails) {
+            (Some(job_id), None) => {
+                let job_queue = ctx.data_opt::<JobQueue>().ok_or_else(|| {
+                    ApiError::upstream("job queue", "no queue in schema data").extend()
+                })?;
+                load_owned_job(job_queue, &job_id, &account.tenant_id())
+                    .await
+                    .and_then(require_completed)
+                    .map_err(|e| e.extend())?;
+                list_report::job_report(job_queue, &job_id)
+                    .await
+                    .map(Into::into)
+                    .map_err(|e| ApiError::upstream("job queue", e).extend())
+            }
+            (None, Some(emails)) => {
+                if let Some(rejection) =
+                    crate::routes::email::batch_size_rejection(emails.len(), bulk::max_batch_size())
+                {
+                    return Err(rejection.extend());
+                }
+                let response = self.validate_emails_bulk(ctx, emails, None, None).await?;
+                let mut builder = ReportBuilder::default();
+                for row in &response.results {
+                    let verdict = match &row.validation.error {
+                        None if row.validation.is_valid => "VALID",
+                        None => "UNKNOWN",
+                        Some(error) => error.code.as_str(),
+                    };
+                    builder.add_result(&row.email, row.validation.is_valid, verdict);
+                }
+                Ok(builder.finish(None).into())
+            }
+            _ => Err(
+                ApiError::validation("INVALID_INPUT", "Pass exactly one of jobId or emails")
+                    .extend(),
+            ),
+        }
+    }
+
     async fn get_job_status(&self, ctx: &Context<'_>, job_id: String) -> Result<String> {
+        let account = current_account(ctx)?;
         if let Some(job_queue) = ctx.data_opt::<JobQueue>() {
-            match job_queue.get_job_status(&job_id).await {
-                Ok(Some(job)) => Ok(format!("{:?}", job.status)),
-                Ok(None) => Err(async_graphql::Error::new("Job not found")),
-                Err(e) => Err(async_graphql::Error::new(format!("Redis error: {:?}", e))),
-            }
+            load_owned_job(job_queue, &job_id, &account.tenant_id())
+                .await
+                .map(|job| format!("{:?}", job.status))
+                .map_err(|e| e.extend())
         } else {
-            Err(async_graphql::Error::new("Job queue not available"))
+            Err(ApiError::upstream("job queue", "no queue in schema data").extend())
         }
     }
 }
 
 // Move the validation logic to a separate method outside the Object impl
 impl EmailQuery {
-    pub async fn perform_validation(
+    /// Validates one address for the caller through the shared validation flow
+    async fn validate_uncached(
         &self,
-        email: String,
-        check_role_based: bool,
+        ctx: &Context<'_>,
+        email: &str,
+        checks: Checks,
     ) -> Result<EmailValidationResponse> {
-        // 1. Syntax validation
-        if !syntax::is_valid_email(&email) {
-            return Ok(EmailValidationResponse {
-                is_valid: false,
-                status: None,
-                error: Some(EmailValidationError {
-                    code: "INVALID_SYNTAX".to_string(),
-                    message: "Email address has invalid syntax".to_string(),
-                }),
-            });
-        }
+        let lists = self
+            .caller_lists(ctx, &[email.to_string()])
+            .await?
+            .unwrap_or_default();
+        let tenant_id = ctx
+            .data_opt::<AuthenticatedAccount>()
+            .map(AuthenticatedAccount::tenant_id);
+        Ok(self
+            .service_for(tenant_id.as_deref())
+            .validate(email, checks, &lists)
+            .await
+            .into())
+    }
 
-        // 2. DNS/MX validation (blocking task)
-        let email_clone = email.clone();
-        let dns_valid =
-            tokio::task::spawn_blocking(move || dnsmx::validate_email_dns(&email_clone))
-                .await
-                .map_err(|e| async_graphql::Error::new(format!("Task join error: {}", e)))?;
-
-        if !dns_valid {
-            return Ok(EmailValidationResponse {
-                is_valid: false,
-                status: None,
-                error: Some(EmailValidationError {
-                    code: "INVALID_DOMAIN".to_string(),
-                    message: "Email domain has no valid DNS records".to_string(),
-                }),
-            });
-        }
+    /// Validation options the authenticated account's requests fall back to
+    async fn caller_defaults(&self, ctx: &Context<'_>) -> Result<ItemOptions> {
+        let (Some(account), Some(mongo_client)) = (
+            ctx.data_opt::<AuthenticatedAccount>(),
+            ctx.data_opt::<mongodb::Client>(),
+        ) else {
+            return Ok(ItemOptions::default());
+        };
+        account_settings::default_options(mongo_client, &account.tenant_id())
+            .await
+            .map_err(|e| ApiError::upstream("database", e).extend())
+    }
 
-        // 3. Role-based email check (optional)
-        if check_role_based {
-            match role_based::is_role_based_email(&email).await {
-                Ok(true) => {
-                    return Ok(EmailValidationResponse {
-                        is_valid: false,
-                        status: None,
-                        error: Some(EmailValidationError {
-                            code: "ROLE_BASED_EMAIL".to_string(),
-                            message: "Email address uses a role-based local part".to_string(),
-                        }),
-                    });
-                }
-                Ok(false) => {} // Continue validation
-                Err(e) => {
-                    return Ok(EmailValidationResponse {
-                        is_valid: false,
-                        status: None,
-                        error: Some(EmailValidationError {
-                            code: "DATABASE_ERROR".to_string(),
-                            message: e,
-                        }),
-                    });
-                }
-            }
-        }
+    /// Custom lists of the authenticated account, or its organization's, with its
+    /// feedback about `emails`
+    pub(crate) async fn caller_lists(
+        &self,
+        ctx: &Context<'_>,
+        emails: &[String],
+    ) -> Result<Option<AccountLists>> {
+        let (Some(account), Some(mongo_client)) = (
+            ctx.data_opt::<AuthenticatedAccount>(),
+            ctx.data_opt::<mongodb::Client>(),
+        ) else {
+            return Ok(None);
+        };
 
-        // 4. Disposable email check
-        match disposable::is_disposable_email(&email).await {
-            Ok(true) => Ok(EmailValidationResponse {
-                is_valid: false,
-                status: None,
-                error: Some(EmailValidationError {
-                    code: "DISPOSABLE_EMAIL".to_string(),
-                    message: "The email address domain is a provider of disposable email addresses"
-                        .to_string(),
-                }),
-            }),
-            Ok(false) => Ok(EmailValidationResponse {
-                is_valid: true,
-                status: Some("VALID".to_string()),
-                error: None,
-            }),
-            Err(e) => Ok(EmailValidationResponse {
-                is_valid: false,
-                status: None,
-                error: Some(EmailValidationError {
-                    code: "DATABASE_ERROR".to_string(),
-                    message: format!("{:?}", e),
-                }),
-            }),
-        }
+        let owner = organizations::list_owner(mongo_client, &account.email)
+            .await
+            .map_err(|e| ApiError::upstream("database", e).extend())?;
+        let lists = AccountLists::load(mongo_client, &owner, emails)
+            .await
+            .map_err(|e| ApiError::upstream("database", e).extend())?;
+        Ok(Some(lists))
+    }
+
+    /// Runs the full validation pipeline for one address, bypassing lists and cache
+    pub async fn perform_validation(
+        &self,
+        email: String,
+        check_role_based: bool,
+    ) -> Result<EmailValidationResponse> {
+        let mut results = self
+            .service()
+            .run_pipeline(&[email], Checks::standard(check_role_based))
+            .await;
+        Ok(results.remove(0).into())
     }
 }
 
@@ -357,6 +687,7 @@ impl EmailQuery {
 mod tests {
     use super::*;
     use async_graphql::Schema;
+    use futures::future::join_all;
     use mockall::mock;
     use mockall::predicate::*;
 
@@ -426,6 +757,7 @@ mod tests {
                     error {
                         code
                         message
+                        violations
                     }
                 }
             }
@@ -454,7 +786,11 @@ mod tests {
         assert_eq!(validation_result["error"]["code"], "INVALID_SYNTAX");
         assert_eq!(
             validation_result["error"]["message"],
-            "Email address has invalid syntax"
+            "Email address has invalid syntax: the address has no @ separator"
+        );
+        assert_eq!(
+            validation_result["error"]["violations"],
+            serde_json::json!(["MISSING_AT"])
         );
     }
 
@@ -487,6 +823,7 @@ mod tests {
                         error: Some(EmailValidationError {
                             code: "INVALID_DOMAIN".to_string(),
                             message: "Email domain has no valid DNS records".to_string(),
+                            violations: Vec::new(),
                         }),
                     });
                 } else {
@@ -497,6 +834,7 @@ mod tests {
                         error: Some(EmailValidationError {
                             code: "INVALID_SYNTAX".to_string(),
                             message: "Email address has invalid syntax".to_string(),
+                            violations: Vec::new(),
                         }),
                     });
                 }
@@ -583,6 +921,7 @@ mod tests {
                         error: Some(EmailValidationError {
                             code: "DATABASE_ERROR".to_string(),
                             message: error_message,
+                            violations: Vec::new(),
                         }),
                     });
                 } else {
@@ -668,6 +1007,7 @@ mod tests {
                         error: Some(EmailValidationError {
                             code: "ROLE_BASED_EMAIL".to_string(),
                             message: "Email address uses a role-based local part".to_string(),
+                            violations: Vec::new(),
                         }),
                     });
                 }
@@ -814,6 +1154,7 @@ mod tests {
                         error: Some(EmailValidationError {
                             code: "INVALID_SYNTAX".to_string(),
                             message: "Email address has invalid syntax".to_string(),
+                            violations: Vec::new(),
                         }),
                     });
                 }
@@ -871,6 +1212,7 @@ mod tests {
                     results: validation_results,
                     valid_count: valid_count,
                     invalid_count: invalid_count,
+                    unique_count: emails.len() as i32,
                 })
             }
         }
@@ -1030,6 +1372,7 @@ mod tests {
             error: Some(EmailValidationError {
                 code: "INVALID_SYNTAX".to_string(),
                 message: "Test error".to_string(),
+                violations: Vec::new(),
             }),
         };
 
@@ -1063,6 +1406,7 @@ mod tests {
                         error: Some(EmailValidationError {
                             code: "DISPOSABLE_EMAIL".to_string(),
                             message: "The email address domain is a provider of disposable email addresses".to_string(),
+                            violations: Vec::new(),
                         }),
                     });
                 }
@@ -8,6 +8,7 @@ mod graphql_email_tests {
         let error = EmailValidationError {
             code: "TEST_CODE".to_string(),
             message: "Test message".to_string(),
+            violations: Vec::new(),
         };
         assert_eq!(error.code, "TEST_CODE");
         assert_eq!(error.message, "Test message");
@@ -33,6 +34,7 @@ mod graphql_email_tests {
             error: Some(EmailValidationError {
                 code: "INVALID_SYNTAX".to_string(),
                 message: "Invalid format".to_string(),
+                violations: Vec::new(),
             }),
         };
         assert!(!response.is_valid);
@@ -60,6 +62,7 @@ mod graphql_email_tests {
             results: vec![],
             valid_count: 10,
             invalid_count: 5,
+            unique_count: 15,
         };
         assert_eq!(response.valid_count, 10);
         assert_eq!(response.invalid_count, 5);
@@ -93,6 +96,7 @@ mod graphql_email_tests {
             error: Some(EmailValidationError {
                 code: "TEST_ERROR".to_string(),
                 message: "Test error message".to_string(),
+                violations: Vec::new(),
             }),
         };
 
@@ -124,14 +128,13 @@ mod graphql_email_tests {
     #[test]
     fn test_email_query_default() {
         let query = EmailQuery::default();
-        assert!(query.redis_client.is_none());
-        assert_eq!(query.cache_ttl, 0);
+        assert!(!query.cache.is_enabled());
     }
 
     #[tokio::test]
     async fn test_email_query_get_cached_result_no_client() {
         let query = EmailQuery::default();
-        let result = query.get_cached_result("test@example.com").await;
+        let result = query.get_cached_result("test@example.com", false).await;
         assert!(result.is_none());
     }
 
@@ -144,7 +147,9 @@ mod graphql_email_tests {
             error: None,
         };
         // Should not panic when no Redis client is available
-        query.cache_result("test@example.com", &response).await;
+        query
+            .cache_result("test@example.com", false, &response)
+            .await;
     }
 
     #[tokio::test]
@@ -210,6 +215,7 @@ mod graphql_email_tests {
             error: Some(EmailValidationError {
                 code: "TEST_ERROR".to_string(),
                 message: "Test message".to_string(),
+                violations: Vec::new(),
             }),
         };
 
@@ -241,6 +247,7 @@ mod graphql_email_tests {
             let error = EmailValidationError {
                 code: code.to_string(),
                 message: format!("Message for {}", code),
+                violations: Vec::new(),
             };
             assert_eq!(error.code, code);
             assert!(error.message.contains(code));
@@ -267,6 +274,7 @@ mod graphql_email_tests {
                     error: Some(EmailValidationError {
                         code: "INVALID_SYNTAX".to_string(),
                         message: "Invalid syntax".to_string(),
+                        violations: Vec::new(),
                     }),
                 },
             },
@@ -276,6 +284,7 @@ mod graphql_email_tests {
             results,
             valid_count: 1,
             invalid_count: 1,
+            unique_count: 2,
         };
 
         assert_eq!(response.results.len(), 2);
@@ -310,6 +319,7 @@ mod graphql_email_tests {
             error: Some(EmailValidationError {
                 code: "TEST".to_string(),
                 message: "Test".to_string(),
+                violations: Vec::new(),
             }),
         };
         assert!(!response2.is_valid);
@@ -332,6 +342,7 @@ mod graphql_email_tests {
             let error = EmailValidationError {
                 code: "TEST_CODE".to_string(),
                 message: message.to_string(),
+                violations: Vec::new(),
             };
             assert_eq!(error.message, message);
         }
@@ -343,6 +354,7 @@ mod graphql_email_tests {
         let original = EmailValidationError {
             code: "TEST".to_string(),
             message: "Test message".to_string(),
+            violations: Vec::new(),
         };
         let cloned = original.clone();
         assert_eq!(original.code, cloned.code);
@@ -367,6 +379,7 @@ mod graphql_email_tests {
         let error = EmailValidationError {
             code: "TEST".to_string(),
             message: "Test".to_string(),
+            violations: Vec::new(),
         };
         let debug_str = format!("{:?}", error);
         assert!(debug_str.contains("TEST"));
@@ -375,11 +388,9 @@ mod graphql_email_tests {
     // Test default implementations where applicable
     #[test]
     fn test_email_query_default_values() {
-        let query = EmailQuery {
-            redis_client: None,
-            cache_ttl: 7200,
-        };
-        assert!(query.redis_client.is_none());
-        assert_eq!(query.cache_ttl, 7200);
+        let mut query = EmailQuery::default();
+        query.cache.ttls.valid = 7200;
+        assert!(!query.cache.is_enabled());
+        assert_eq!(query.cache.ttls.valid, 7200);
     }
 }
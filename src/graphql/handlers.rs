@@ -1,8 +1,29 @@
-use actix_web::{HttpResponse, Responder, web};
+use actix_web::http::Method;
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, web};
 use async_graphql::http::{GraphQLPlaygroundConfig, playground_source};
 use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse};
 
+use crate::auth::AuthenticatedAccount;
+use crate::bulk::ValidationMemo;
+use crate::feature_flags::FeatureFlags;
+use crate::graphql::account::BearerToken;
+use crate::graphql::email::EmailValidationResponse;
+use crate::graphql::persisted_queries::{self, PersistedQueryStore};
 use crate::graphql::schema::AppSchema;
+use crate::job_queue::JobQueue;
+use crate::metering::Meter;
+use mongodb::Client as MongoClient;
+use serde::Deserialize;
+
+/// GraphQL request body as documented in the OpenAPI spec; parsing is done by async-graphql
+#[derive(Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQLRequestBody {
+    pub query: String,
+    #[schema(value_type = Option<Object>)]
+    pub variables: Option<serde_json::Value>,
+    pub operation_name: Option<String>,
+}
 
 /// Handles incoming GraphQL requests.
 ///
@@ -10,12 +31,103 @@ use crate::graphql::schema::AppSchema;
 ///
 /// # Arguments
 /// - `schema`: The application's GraphQL schema, provided as shared data through Actix-web's state management.
+/// - `http_req`: The underlying HTTP request; its bearer token, the account resolved by the auth
+///   middleware and the MongoDB client are attached to the GraphQL context.
 /// - `req`: The incoming GraphQL request containing the query, variables, and operation name.
 ///
+/// Requests may carry an Automatic Persisted Query hash instead of the query (see
+/// [`PersistedQueryStore`]). Queries can also be sent with GET, which only allows
+/// queries, not mutations.
+///
 /// # Returns
 /// A [`GraphQLResponse`] containing the execution result of the GraphQL operation.
-pub async fn graphql_handler(schema: web::Data<AppSchema>, req: GraphQLRequest) -> GraphQLResponse {
-    schema.execute(req.into_inner()).await.into()
+#[utoipa::path(
+    post,
+    path = "/api/v1/graphql",
+    request_body = GraphQLRequestBody,
+    responses(
+        (status = 200, description = "GraphQL response; resolver errors carry `code` and `request_id` extensions")
+    ),
+    tag = "GraphQL"
+)]
+pub async fn graphql_handler(
+    schema: web::Data<AppSchema>,
+    http_req: HttpRequest,
+    req: GraphQLRequest,
+) -> GraphQLResponse {
+    let mut request = req.into_inner();
+    let persisted_queries = http_req
+        .app_data::<web::Data<PersistedQueryStore>>()
+        .map(|store| store.get_ref().clone())
+        .unwrap_or_default();
+    let prepared = match persisted_queries.resolve(&mut request).await {
+        Ok(()) if http_req.method() == Method::GET => {
+            persisted_queries::reject_mutation_over_get(&request)
+        }
+        prepared => prepared,
+    };
+    if let Err(error) = prepared {
+        return async_graphql::Response::from_errors(vec![error]).into();
+    }
+
+    // Identical addresses within one request are validated once
+    let mut request = request.data(ValidationMemo::<EmailValidationResponse>::default());
+
+    // Account mutations need the database and the caller's API key
+    if let Some(mongo_client) = http_req.app_data::<web::Data<MongoClient>>() {
+        request = request.data(mongo_client.get_ref().clone());
+    }
+    if let Some(token) = http_req
+        .headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "))
+    {
+        request = request.data(BearerToken(token.to_string()));
+    }
+    if let Some(account) = http_req.extensions().get::<AuthenticatedAccount>() {
+        request = request.data(account.clone());
+    }
+    // Usage counters for `me { usage }`
+    if let Some(meter) = http_req.app_data::<web::Data<Meter>>() {
+        request = request.data(meter.get_ref().clone());
+    }
+    // Feature flags switched off during incidents
+    if let Some(flags) = http_req.app_data::<web::Data<FeatureFlags>>() {
+        request = request.data(flags.get_ref().clone());
+    }
+    // Job reports and saved list validation
+    if let Some(job_queue) = http_req.app_data::<web::Data<JobQueue>>() {
+        request = request.data(job_queue.get_ref().clone());
+    }
+
+    schema.execute(request).await.into()
+}
+
+/// Handles GraphQL queries sent with GET, e.g. persisted queries cached by a CDN
+///
+/// Takes `query`, `operation_name`, and JSON-encoded `variables` and `extensions`
+/// query parameters; mutations are rejected with `METHOD_NOT_ALLOWED`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/graphql",
+    params(
+        ("query" = Option<String>, Query, description = "Query document; omitted for a known persisted query"),
+        ("operation_name" = Option<String>, Query, description = "Operation to run"),
+        ("variables" = Option<String>, Query, description = "JSON-encoded variables"),
+        ("extensions" = Option<String>, Query, description = "JSON-encoded extensions, e.g. `{\"persistedQuery\":{\"version\":1,\"sha256Hash\":\"...\"}}`")
+    ),
+    responses(
+        (status = 200, description = "GraphQL response; resolver errors carry `code` and `request_id` extensions")
+    ),
+    tag = "GraphQL"
+)]
+pub async fn graphql_get_handler(
+    schema: web::Data<AppSchema>,
+    http_req: HttpRequest,
+    req: GraphQLRequest,
+) -> GraphQLResponse {
+    graphql_handler(schema, http_req, req).await
 }
 
 /// Serves the GraphQL Playground interface for interactive query testing.
@@ -25,11 +137,19 @@ pub async fn graphql_handler(schema: web::Data<AppSchema>, req: GraphQLRequest)
 /// to send requests to the `/api/v1/graphql` endpoint.
 ///
 /// # Note
-/// This endpoint is typically used during development and should be disabled in production
-/// environments for security reasons.
+/// The route is not registered when `APP_ENV=production`, unless `GRAPHQL_DEV_TOOLS=true`.
 ///
 /// # Returns
 /// An [`HttpResponse`] with HTML content rendering the GraphQL Playground interface.
+#[utoipa::path(
+    get,
+    path = "/api/v1/playground",
+    responses(
+        (status = 200, description = "GraphQL Playground", content_type = "text/html")
+    ),
+    security(()),
+    tag = "GraphQL"
+)]
 pub async fn graphql_playground() -> impl Responder {
     HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
@@ -107,6 +227,31 @@ mod tests {
         assert!(resp_body["errors"].as_array().unwrap().len() > 0);
     }
 
+    #[actix_web::test]
+    async fn test_graphql_get_handler() {
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(create_schema()))
+                .route("/graphql", web::get().to(graphql_get_handler)),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .uri("/graphql?query=%7B%20health%20%7B%20status%20%7D%20%7D")
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"]["health"]["status"], "UP");
+
+        let req = TestRequest::get()
+            .uri("/graphql?query=mutation%20%7B%20revokeApiKey%20%7D")
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"],
+            "METHOD_NOT_ALLOWED"
+        );
+    }
+
     // Test for graphql_playground
     #[actix_web::test]
     async fn test_graphql_playground() {
@@ -89,6 +89,7 @@ mod tests {
         let health_response = HealthResponse {
             status: status.clone(),
             timestamp: timestamp.clone(),
+            dependencies: Vec::new(),
         };
 
         // Convert to Health
@@ -0,0 +1,296 @@
+use crate::account_settings::{self, AccountSettings};
+use crate::bulk::{self, DedupedBatch};
+use crate::checks::ItemOptions;
+use crate::error::ApiError;
+use crate::feature_flags::Flag;
+use crate::graphql::account::{charge_usage, current_account, ensure_enabled, mongo_client};
+use crate::graphql::email::{BulkEmailValidationResult, ValidationCheck};
+use crate::input_limits;
+use crate::job_payload;
+use crate::job_queue::{BulkValidationJob, JobQueue, JobStatus, RESULT_CHUNK_SIZE};
+use crate::job_results::{self, Outcome, ResultFilter};
+use crate::routes::email::{batch_size_rejection, load_owned_job};
+use actix_web::http::StatusCode;
+use async_graphql::{
+    Context, Enum, ErrorExtensions, ID, InputObject, Object, Result, SimpleObject,
+};
+use std::collections::BTreeMap;
+
+/// Where a queued bulk job stands
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(name = "JobStatus")]
+pub enum JobStatusKind {
+    Pending,
+    Processing,
+    Completed,
+    Failed,
+}
+
+impl From<&JobStatus> for JobStatusKind {
+    fn from(status: &JobStatus) -> Self {
+        match status {
+            JobStatus::Pending => JobStatusKind::Pending,
+            JobStatus::Processing => JobStatusKind::Processing,
+            JobStatus::Completed => JobStatusKind::Completed,
+            JobStatus::Failed => JobStatusKind::Failed,
+        }
+    }
+}
+
+/// Verdict of the results to return
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum ResultOutcome {
+    Valid,
+    Invalid,
+}
+
+impl From<ResultOutcome> for Outcome {
+    fn from(outcome: ResultOutcome) -> Self {
+        match outcome {
+            ResultOutcome::Valid => Outcome::Valid,
+            ResultOutcome::Invalid => Outcome::Invalid,
+        }
+    }
+}
+
+/// Options of every address of a queued job; unset ones follow the account settings
+#[derive(InputObject, Default)]
+pub struct BulkJobOptions {
+    /// Turns role-based detection on or off
+    pub check_role_based: Option<bool>,
+    /// Stages to run instead of all of them
+    pub checks: Option<Vec<ValidationCheck>>,
+}
+
+impl From<BulkJobOptions> for ItemOptions {
+    fn from(options: BulkJobOptions) -> Self {
+        ItemOptions {
+            check_role_based: options.check_role_based,
+            checks: options
+                .checks
+                .map(|checks| checks.into_iter().map(Into::into).collect()),
+        }
+    }
+}
+
+/// One page of a completed job's results, in input order
+#[derive(SimpleObject)]
+pub struct JobResultPage {
+    pub results: Vec<BulkEmailValidationResult>,
+    pub page: usize,
+    pub per_page: usize,
+    /// Results matching `only` and `errorCode`, across all pages
+    pub total: usize,
+}
+
+/// A bulk validation job of the caller, as `GET /api/v1/job-status/{job_id}` and
+/// `GET /api/v1/job-results/{job_id}` report it
+pub struct Job(BulkValidationJob);
+
+fn job_queue<'a>(ctx: &'a Context<'_>) -> Result<&'a JobQueue> {
+    ctx.data_opt::<JobQueue>()
+        .ok_or_else(|| ApiError::upstream("job queue", "no queue in schema data").extend())
+}
+
+#[Object]
+impl Job {
+    async fn id(&self) -> ID {
+        ID(self.0.id.clone())
+    }
+
+    async fn status(&self) -> JobStatusKind {
+        (&self.0.status).into()
+    }
+
+    /// Unix timestamp of the submission
+    async fn created_at(&self) -> i64 {
+        self.0.created_at
+    }
+
+    /// Unix timestamp of the last status change; null while the job is queued
+    async fn updated_at(&self) -> Option<i64> {
+        self.0.updated_at
+    }
+
+    /// Number of addresses submitted
+    async fn email_count(&self) -> usize {
+        self.0.email_count()
+    }
+
+    /// Share of the addresses with stored results, from 0 to 1
+    async fn progress(&self, ctx: &Context<'_>) -> Result<f64> {
+        let total = self.0.email_count();
+        match self.0.status {
+            JobStatus::Pending => Ok(0.0),
+            JobStatus::Completed | JobStatus::Failed => Ok(1.0),
+            JobStatus::Processing if total == 0 => Ok(0.0),
+            JobStatus::Processing => {
+                let chunks = job_queue(ctx)?
+                    .result_chunk_count(&self.0.id)
+                    .await
+                    .map_err(|e| ApiError::upstream("job queue", e).extend())?;
+                Ok(((chunks * RESULT_CHUNK_SIZE) as f64 / total as f64).min(1.0))
+            }
+        }
+    }
+
+    /// One page of the results, once the job has completed; at most 1000 per page.
+    /// `only` and `errorCode` (e.g. `DISPOSABLE_EMAIL`) keep just the matching results
+    async fn results(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 1)] page: usize,
+        #[graphql(default = 100)] per_page: usize,
+        only: Option<ResultOutcome>,
+        error_code: Option<String>,
+    ) -> Result<JobResultPage> {
+        if let Some(message) = job_results::page_error(page, per_page) {
+            return Err(ApiError::validation("INVALID_PAGE", message).extend());
+        }
+        let filter = ResultFilter::new(only.map(Into::into), error_code.as_deref())
+            .map_err(|message| ApiError::validation("INVALID_FILTER", message).extend())?;
+        if self.0.status != JobStatus::Completed {
+            return Err(ApiError::validation(
+                "JOB_NOT_COMPLETE",
+                "Results are available once the job has completed",
+            )
+            .with_status(StatusCode::CONFLICT)
+            .extend());
+        }
+        let result_page = job_results::read_page(job_queue(ctx)?, &self.0, &filter, page, per_page)
+            .await
+            .map_err(|e| ApiError::upstream("job queue", e).extend())?;
+        Ok(JobResultPage {
+            results: result_page
+                .rows
+                .into_iter()
+                .map(|row| BulkEmailValidationResult {
+                    email: row.email,
+                    validation: row.validation.into(),
+                })
+                .collect(),
+            page,
+            per_page,
+            total: result_page.total,
+        })
+    }
+}
+
+/// Queued bulk jobs of the authenticated caller
+#[derive(Default)]
+pub struct JobsQuery;
+
+#[Object]
+impl JobsQuery {
+    /// One of the caller's jobs that is still on record
+    async fn job(&self, ctx: &Context<'_>, id: ID) -> Result<Job> {
+        let account = current_account(ctx)?;
+        load_owned_job(job_queue(ctx)?, &id, &account.tenant_id())
+            .await
+            .map(Job)
+            .map_err(|e| e.extend())
+    }
+}
+
+/// Bulk job submission, mirroring the queued path of `POST /api/v1/validate-emails-bulk`
+#[derive(Default)]
+pub struct JobsMutation;
+
+#[Object]
+impl JobsMutation {
+    /// Queues a bulk validation of up to `BULK_MAX_BATCH_SIZE` addresses, whatever
+    /// their number; poll `job(id:)` for its status and results
+    async fn queue_bulk_validation(
+        &self,
+        ctx: &Context<'_>,
+        emails: Vec<String>,
+        options: Option<BulkJobOptions>,
+    ) -> Result<Job> {
+        let account = current_account(ctx)?;
+        ensure_enabled(ctx, Flag::BulkEnabled).await?;
+        if let Some(rejection) = batch_size_rejection(emails.len(), bulk::max_batch_size()) {
+            return Err(rejection.extend());
+        }
+        for (i, email) in emails.iter().enumerate() {
+            input_limits::check_email_field(&format!("emails[{}]", i), email)
+                .map_err(|e| e.extend())?;
+        }
+
+        let tenant_id = account.tenant_id();
+        let settings = account_settings::load_settings(mongo_client(ctx)?, &tenant_id)
+            .await
+            .map_err(|e| ApiError::upstream("database", e).extend())?
+            .unwrap_or_else(|| AccountSettings::unset(&tenant_id));
+        let options =
+            ItemOptions::from(options.unwrap_or_default()).with_defaults(&settings.options());
+        // The job carries the role-based flag; chosen checks travel with each entry
+        let item_options: BTreeMap<usize, ItemOptions> = match &options.checks {
+            Some(checks) => (0..emails.len())
+                .map(|i| {
+                    let entry = ItemOptions {
+                        check_role_based: None,
+                        checks: Some(checks.clone()),
+                    };
+                    (i, entry)
+                })
+                .collect(),
+            None => BTreeMap::new(),
+        };
+
+        let job_queue = job_queue(ctx)?;
+        // The worker validates, and so charges, every distinct address once
+        let keys = vec![(); emails.len()];
+        let (batch, _) = if settings.dedupes_tagged() {
+            DedupedBatch::merging_tags(&emails, &keys)
+        } else {
+            DedupedBatch::with_keys(&emails, &keys)
+        };
+        charge_usage(ctx, batch.units()).await?;
+        let job_id = job_queue
+            .enqueue_bulk_validation_with_options(
+                &tenant_id,
+                account.plan,
+                emails,
+                options.check_role_based.unwrap_or(false),
+                item_options,
+                settings.dedupes_tagged(),
+            )
+            .await
+            .map_err(|e| {
+                if job_payload::is_too_large(&e) {
+                    ApiError::validation(
+                        "JOB_TOO_LARGE",
+                        "The batch is too large to queue; split it into smaller jobs",
+                    )
+                    .with_status(StatusCode::PAYLOAD_TOO_LARGE)
+                    .extend()
+                } else {
+                    ApiError::upstream("job queue", e).extend()
+                }
+            })?;
+        match job_queue.get_job_status(&job_id).await {
+            Ok(Some(job)) => Ok(Job(job)),
+            Ok(None) => Err(ApiError::not_found("JOB_NOT_FOUND", "Job not found").extend()),
+            Err(e) => Err(ApiError::upstream("job queue", e).extend()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphql::schema::create_schema;
+
+    #[tokio::test]
+    async fn test_jobs_require_account() {
+        let schema = create_schema();
+        let response = schema
+            .execute(r#"{ job(id: "1") { id status progress } }"#)
+            .await;
+        assert_eq!(response.errors[0].message, "Authentication required");
+
+        let response = schema
+            .execute(r#"mutation { queueBulkValidation(emails: ["a@example.com"]) { id } }"#)
+            .await;
+        assert_eq!(response.errors[0].message, "Authentication required");
+    }
+}
@@ -0,0 +1,198 @@
+use crate::error::ApiError;
+use crate::feature_flags::Flag;
+use crate::graphql::account::{current_account, ensure_enabled, ensure_writable, mongo_client};
+use crate::input_limits;
+use crate::job_queue::JobQueue;
+use crate::metering::Meter;
+use crate::routes::lists::list_not_found;
+use crate::saved_lists::{self, SavedList, SavedListSummary};
+use async_graphql::{Context, ErrorExtensions, Object, Result, SimpleObject};
+
+/// A saved list without its addresses
+#[derive(SimpleObject)]
+pub struct SavedListInfo {
+    pub list_id: String,
+    pub name: String,
+    pub email_count: u64,
+    /// Unix timestamps
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Latest bulk job validating the list
+    pub last_job_id: Option<String>,
+}
+
+impl From<SavedListSummary> for SavedListInfo {
+    fn from(summary: SavedListSummary) -> Self {
+        SavedListInfo {
+            list_id: summary.list_id,
+            name: summary.name,
+            email_count: summary.email_count,
+            created_at: summary.created_at,
+            updated_at: summary.updated_at,
+            last_job_id: summary.last_job_id,
+        }
+    }
+}
+
+/// A saved list with its addresses, like `GET /api/v1/lists/{list_id}`
+#[derive(SimpleObject)]
+pub struct SavedListObject {
+    pub list_id: String,
+    pub name: String,
+    pub emails: Vec<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub last_job_id: Option<String>,
+}
+
+impl From<SavedList> for SavedListObject {
+    fn from(list: SavedList) -> Self {
+        SavedListObject {
+            list_id: list.list_id,
+            name: list.name,
+            emails: list.emails,
+            created_at: list.created_at,
+            updated_at: list.updated_at,
+            last_job_id: list.last_job_id,
+        }
+    }
+}
+
+/// Owner of the caller's saved lists, as in the REST routes
+fn owner(ctx: &Context<'_>) -> Result<String> {
+    Ok(current_account(ctx)?.email.to_lowercase())
+}
+
+fn check_emails(emails: &[String]) -> Result<()> {
+    for (i, email) in emails.iter().enumerate() {
+        input_limits::check_email_field(&format!("emails[{}]", i), email)
+            .map_err(|e| e.extend())?;
+    }
+    Ok(())
+}
+
+/// Saved list queries for the authenticated caller
+#[derive(Default)]
+pub struct ListsQuery;
+
+#[Object]
+impl ListsQuery {
+    /// The caller's saved lists, oldest first
+    async fn saved_lists(&self, ctx: &Context<'_>) -> Result<Vec<SavedListInfo>> {
+        let owner = owner(ctx)?;
+        let lists = saved_lists::list_lists(mongo_client(ctx)?, &owner)
+            .await
+            .map_err(|e| ApiError::upstream("database", e).extend())?;
+        Ok(lists.into_iter().map(Into::into).collect())
+    }
+
+    /// One of the caller's saved lists with its addresses
+    async fn saved_list(&self, ctx: &Context<'_>, list_id: String) -> Result<SavedListObject> {
+        let owner = owner(ctx)?;
+        saved_lists::get_list(mongo_client(ctx)?, &owner, &list_id)
+            .await
+            .map_err(|e| ApiError::upstream("database", e).extend())?
+            .map(Into::into)
+            .ok_or_else(|| list_not_found().extend())
+    }
+}
+
+/// Saved list mutations, mirroring `/api/v1/lists`
+#[derive(Default)]
+pub struct ListsMutation;
+
+#[Object]
+impl ListsMutation {
+    /// Creates a named list; addresses are trimmed and exact duplicates dropped
+    async fn create_saved_list(
+        &self,
+        ctx: &Context<'_>,
+        name: String,
+        #[graphql(default)] emails: Vec<String>,
+    ) -> Result<SavedListObject> {
+        ensure_writable()?;
+        check_emails(&emails)?;
+        let owner = owner(ctx)?;
+        saved_lists::create_list(mongo_client(ctx)?, &owner, &name, &emails)
+            .await
+            .map(Into::into)
+            .map_err(|e| ApiError::from(e).extend())
+    }
+
+    /// Appends addresses the list doesn't hold yet
+    async fn add_saved_list_emails(
+        &self,
+        ctx: &Context<'_>,
+        list_id: String,
+        emails: Vec<String>,
+    ) -> Result<SavedListInfo> {
+        ensure_writable()?;
+        check_emails(&emails)?;
+        let owner = owner(ctx)?;
+        saved_lists::add_emails(mongo_client(ctx)?, &owner, &list_id, &emails)
+            .await
+            .map(|list| SavedListSummary::from(&list).into())
+            .map_err(|e| ApiError::from(e).extend())
+    }
+
+    /// Removes addresses from the list; ones it doesn't hold are ignored
+    async fn remove_saved_list_emails(
+        &self,
+        ctx: &Context<'_>,
+        list_id: String,
+        emails: Vec<String>,
+    ) -> Result<SavedListInfo> {
+        ensure_writable()?;
+        let owner = owner(ctx)?;
+        saved_lists::remove_emails(mongo_client(ctx)?, &owner, &list_id, &emails)
+            .await
+            .map(|list| SavedListSummary::from(&list).into())
+            .map_err(|e| ApiError::from(e).extend())
+    }
+
+    /// Deletes the list; false when the caller has no such list
+    async fn delete_saved_list(&self, ctx: &Context<'_>, list_id: String) -> Result<bool> {
+        ensure_writable()?;
+        let owner = owner(ctx)?;
+        saved_lists::delete_list(mongo_client(ctx)?, &owner, &list_id)
+            .await
+            .map_err(|e| ApiError::upstream("database", e).extend())
+    }
+
+    /// Queues a bulk job validating every address of the list; returns the job id
+    async fn validate_saved_list(
+        &self,
+        ctx: &Context<'_>,
+        list_id: String,
+        #[graphql(default)] check_role_based: bool,
+    ) -> Result<String> {
+        let owner = owner(ctx)?;
+        ensure_enabled(ctx, Flag::BulkEnabled).await?;
+        let job_queue = ctx
+            .data_opt::<JobQueue>()
+            .ok_or_else(|| ApiError::upstream("job queue", "no queue in schema data").extend())?;
+        saved_lists::queue_validation(
+            mongo_client(ctx)?,
+            job_queue,
+            &owner,
+            &list_id,
+            check_role_based,
+            ctx.data_opt::<Meter>(),
+        )
+        .await
+        .map_err(|e| ApiError::from(e).extend())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphql::schema::create_schema;
+
+    #[tokio::test]
+    async fn test_saved_lists_require_account() {
+        let response = create_schema()
+            .execute("{ savedLists { listId name emailCount } }")
+            .await;
+        assert_eq!(response.errors[0].message, "Authentication required");
+    }
+}
@@ -0,0 +1,40 @@
+use crate::error_codes::{ValidationErrorCode, validation_error_codes};
+use async_graphql::Object;
+
+/// Root query type for descriptions of the API itself
+#[derive(Default)]
+pub struct MetaQuery;
+
+#[Object]
+impl MetaQuery {
+    /// Every code a validation result can carry in `error.code`, as listed by
+    /// `GET /api/v1/error-codes`
+    async fn error_codes(&self) -> Vec<ValidationErrorCode> {
+        validation_error_codes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+
+    #[tokio::test]
+    async fn test_error_codes_query() {
+        let schema = Schema::build(MetaQuery, EmptyMutation, EmptySubscription).finish();
+        let result = schema
+            .execute("{ errorCodes { code severity retryable description } }")
+            .await;
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+        let data = result.data.into_json().unwrap();
+        let codes = data["errorCodes"].as_array().unwrap();
+        assert_eq!(codes.len(), validation_error_codes().len());
+        let trap = codes
+            .iter()
+            .find(|code| code["code"] == "KNOWN_TRAP")
+            .unwrap();
+        assert_eq!(trap["severity"], "ERROR");
+        assert_eq!(trap["retryable"], false);
+    }
+}
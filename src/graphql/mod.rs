@@ -1,7 +1,15 @@
+pub mod account;
+pub mod batch;
 pub mod email;
 pub mod handlers;
 pub mod health;
+pub mod jobs;
+pub mod lists;
+pub mod meta;
+pub mod persisted_queries;
 pub mod schema;
+pub mod stats;
+pub mod suppressions;
 
 #[cfg(test)]
 mod email_test;
@@ -0,0 +1,199 @@
+use crate::error::ApiError;
+use crate::redis_pool::RedisPool;
+use actix_web::http::StatusCode;
+use async_graphql::parser::types::OperationType;
+use async_graphql::{ErrorExtensions, Pos, Request, ServerError};
+use redis::{AsyncCommands, RedisError};
+use sha2::{Digest, Sha256};
+
+const KEY_PREFIX: &str = "graphql_apq::";
+
+/// Default lifetime of a stored query document: 7 days
+pub const DEFAULT_TTL_SECS: u64 = 7 * 86400;
+
+/// Query documents registered through Automatic Persisted Queries (APQ)
+///
+/// Clients send `extensions.persistedQuery.sha256Hash` instead of the query; when the
+/// hash is unknown they get `PersistedQueryNotFound` and resend the full document with
+/// its hash, which is stored here for later calls. Like the validation cache, Redis
+/// errors never fail a request: an unreadable document is reported as not found.
+#[derive(Clone, Default)]
+pub struct PersistedQueryStore {
+    client: Option<RedisPool>,
+    ttl: u64,
+}
+
+impl PersistedQueryStore {
+    pub fn new(redis_url: &str, ttl: u64) -> Result<Self, RedisError> {
+        Ok(Self::from_pool(RedisPool::new(redis_url)?, ttl))
+    }
+
+    pub fn from_pool(pool: RedisPool, ttl: u64) -> Self {
+        Self {
+            client: Some(pool),
+            ttl,
+        }
+    }
+
+    /// Document lifetime from `GRAPHQL_APQ_TTL_SECS` (default 7 days)
+    pub fn ttl_from_env() -> u64 {
+        std::env::var("GRAPHQL_APQ_TTL_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(DEFAULT_TTL_SECS)
+    }
+
+    async fn get(&self, hash: &str) -> Option<String> {
+        let client = self.client.as_ref()?;
+        let mut conn = client.get().await.ok()?;
+        let key = format!("{}{}", KEY_PREFIX, hash);
+        let query: Option<String> = conn.get_ex(&key, redis::Expiry::EX(self.ttl)).await.ok()?;
+        query
+    }
+
+    async fn set(&self, hash: &str, query: &str) {
+        let Some(client) = &self.client else {
+            return;
+        };
+        let Ok(mut conn) = client.get().await else {
+            return;
+        };
+        let key = format!("{}{}", KEY_PREFIX, hash);
+        let _: Result<(), RedisError> = conn.set_ex(&key, query, self.ttl).await;
+    }
+
+    /// Fills in the query of a request that only carries a persisted query hash, or
+    /// stores the document of one that carries both
+    pub async fn resolve(&self, request: &mut Request) -> Result<(), ServerError> {
+        let Some(extension) = request.extensions.remove("persistedQuery") else {
+            return Ok(());
+        };
+        let extension = extension.into_json().unwrap_or_default();
+        if extension.get("version").and_then(|v| v.as_i64()) != Some(1) {
+            return Err(error(
+                "PERSISTED_QUERY_NOT_SUPPORTED",
+                "Only version 1 of the persistedQuery extension is supported",
+            ));
+        }
+        let Some(hash) = extension
+            .get("sha256Hash")
+            .and_then(|v| v.as_str())
+            .map(str::to_ascii_lowercase)
+        else {
+            return Err(error(
+                "PERSISTED_QUERY_NOT_SUPPORTED",
+                "persistedQuery.sha256Hash is missing",
+            ));
+        };
+
+        if request.query.is_empty() {
+            // Apollo clients match on this exact message to resend the full query
+            request.query = self
+                .get(&hash)
+                .await
+                .ok_or_else(|| error("PERSISTED_QUERY_NOT_FOUND", "PersistedQueryNotFound"))?;
+            return Ok(());
+        }
+
+        let digest = Sha256::digest(request.query.as_bytes());
+        let actual: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        if actual != hash {
+            return Err(error(
+                "PERSISTED_QUERY_HASH_MISMATCH",
+                "provided sha does not match query",
+            ));
+        }
+        self.set(&hash, &request.query).await;
+        Ok(())
+    }
+}
+
+fn error(code: &str, message: &str) -> ServerError {
+    ApiError::validation(code, message)
+        .extend()
+        .into_server_error(Pos::default())
+}
+
+/// Rejects mutations sent with GET, which CDNs and browsers treat as safe to repeat
+pub fn reject_mutation_over_get(request: &Request) -> Result<(), ServerError> {
+    let Ok(document) = async_graphql::parser::parse_query(&request.query) else {
+        // Execution reports the syntax error
+        return Ok(());
+    };
+    let is_mutation = document
+        .operations
+        .iter()
+        .filter(|(name, _)| match (&request.operation_name, name) {
+            (Some(wanted), Some(name)) => wanted == name.as_str(),
+            _ => true,
+        })
+        .any(|(_, operation)| operation.node.ty == OperationType::Mutation);
+    if is_mutation {
+        return Err(
+            ApiError::validation("METHOD_NOT_ALLOWED", "Mutations must be sent with POST")
+                .with_status(StatusCode::METHOD_NOT_ALLOWED)
+                .extend()
+                .into_server_error(Pos::default()),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::Value;
+
+    const QUERY: &str = "{ health { status } }";
+
+    fn with_hash(query: &str, hash: &str) -> Request {
+        let mut request = Request::new(query);
+        request.extensions.insert(
+            "persistedQuery".to_string(),
+            Value::from_json(serde_json::json!({ "version": 1, "sha256Hash": hash })).unwrap(),
+        );
+        request
+    }
+
+    fn code(error: &ServerError) -> Option<String> {
+        let extensions = serde_json::to_value(&error.extensions).unwrap();
+        extensions["code"].as_str().map(str::to_string)
+    }
+
+    #[tokio::test]
+    async fn test_resolve() {
+        let store = PersistedQueryStore::default();
+        let hash = format!("{:x}", Sha256::digest(QUERY.as_bytes()));
+
+        let mut request = Request::new(QUERY);
+        assert!(store.resolve(&mut request).await.is_ok());
+
+        let mut request = with_hash(QUERY, &hash);
+        assert!(store.resolve(&mut request).await.is_ok());
+        assert_eq!(request.query, QUERY);
+        assert!(!request.extensions.contains_key("persistedQuery"));
+
+        let error = store.resolve(&mut with_hash("", &hash)).await.unwrap_err();
+        assert_eq!(error.message, "PersistedQueryNotFound");
+        assert_eq!(code(&error).as_deref(), Some("PERSISTED_QUERY_NOT_FOUND"));
+
+        let error = store
+            .resolve(&mut with_hash("{ __typename }", &hash))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            code(&error).as_deref(),
+            Some("PERSISTED_QUERY_HASH_MISMATCH")
+        );
+    }
+
+    #[test]
+    fn test_reject_mutation_over_get() {
+        assert!(reject_mutation_over_get(&Request::new(QUERY)).is_ok());
+        assert!(reject_mutation_over_get(&Request::new("mutation { revokeApiKey }")).is_err());
+
+        let document = "query Q { health { status } } mutation M { revokeApiKey }";
+        assert!(reject_mutation_over_get(&Request::new(document).operation_name("Q")).is_ok());
+        assert!(reject_mutation_over_get(&Request::new(document).operation_name("M")).is_err());
+    }
+}
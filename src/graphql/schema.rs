@@ -1,26 +1,52 @@
+use super::account::{AccountMutation, AccountQuery};
 use super::email::EmailQuery;
 use super::health::HealthQuery;
-use async_graphql::{EmptyMutation, EmptySubscription, MergedObject, Schema};
+use super::jobs::{JobsMutation, JobsQuery};
+use super::lists::{ListsMutation, ListsQuery};
+use super::meta::MetaQuery;
+use super::stats::StatsQuery;
+use super::suppressions::{SuppressionsMutation, SuppressionsQuery};
+use crate::app_env::graphql_dev_tools_enabled;
+use async_graphql::{EmptySubscription, MergedObject, Schema};
 
 /// Combined root query object that merges all query operations
 #[derive(MergedObject, Default)]
-pub struct RootQuery(HealthQuery, EmailQuery);
+pub struct RootQuery(
+    HealthQuery,
+    EmailQuery,
+    AccountQuery,
+    StatsQuery,
+    ListsQuery,
+    SuppressionsQuery,
+    JobsQuery,
+    MetaQuery,
+);
+
+/// Combined root mutation object that merges all mutation operations
+#[derive(MergedObject, Default)]
+pub struct RootMutation(
+    AccountMutation,
+    ListsMutation,
+    SuppressionsMutation,
+    JobsMutation,
+);
 
 /// Main GraphQL Schema Definition
 ///
-/// Combines the root query type with empty mutation and subscription types
+/// Combines the root query and mutation types with an empty subscription type
 /// to form the complete GraphQL schema for the application.
 ///
 /// # Type Parameters
 /// - `RootQuery`: Root query type containing all available query operations
-/// - `EmptyMutation`: Placeholder for mutation operations (currently unused)
+/// - `RootMutation`: Account, API key, saved list, suppression list and bulk job mutations
 /// - `EmptySubscription`: Placeholder for subscription operations (currently unused)
-pub type AppSchema = Schema<RootQuery, EmptyMutation, EmptySubscription>;
+pub type AppSchema = Schema<RootQuery, RootMutation, EmptySubscription>;
 
 /// Creates a new GraphQL schema with configured queries and mutations.
 ///
 /// This function combines the health check query and email validation query
-/// into a unified schema that can be used with the GraphQL handler.
+/// into a unified schema that can be used with the GraphQL handler. Introspection
+/// is disabled in production (see [`graphql_dev_tools_enabled`]).
 ///
 /// # Example
 ///
@@ -30,6 +56,11 @@ pub type AppSchema = Schema<RootQuery, EmptyMutation, EmptySubscription>;
 /// let schema = create_schema();
 /// ```
 pub fn create_schema() -> AppSchema {
+    build_schema(graphql_dev_tools_enabled())
+}
+
+/// Builds the schema, answering introspection queries only when `introspection` is set
+pub fn build_schema(introspection: bool) -> AppSchema {
     let redis_url =
         std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
 
@@ -38,14 +69,30 @@ pub fn create_schema() -> AppSchema {
         .and_then(|v| v.parse::<u64>().ok())
         .unwrap_or(86400); // 24 hours default
 
-    let email_query = EmailQuery::new(&redis_url, cache_ttl).unwrap_or_default(); // Fallback to non-caching if Redis connection fails
-
-    Schema::build(
-        RootQuery(HealthQuery, email_query),
-        EmptyMutation,
+    // Fall back to an in-process cache if no Redis client can be created
+    let email_query = EmailQuery::new(&redis_url, cache_ttl)
+        .unwrap_or_else(|_| EmailQuery::local_only(cache_ttl));
+    let stats_query = StatsQuery::new(&redis_url).unwrap_or_default();
+
+    let builder = Schema::build(
+        RootQuery(
+            HealthQuery,
+            email_query,
+            AccountQuery,
+            stats_query,
+            ListsQuery,
+            SuppressionsQuery,
+            JobsQuery,
+            MetaQuery,
+        ),
+        RootMutation::default(),
         EmptySubscription,
-    )
-    .finish()
+    );
+    if introspection {
+        builder.finish()
+    } else {
+        builder.disable_introspection().finish()
+    }
 }
 
 #[cfg(test)]
@@ -129,13 +176,37 @@ mod tests {
         let root_query = RootQuery::default();
         // Just ensure we can create a default instance
         // This tests the Default trait implementation
-        let schema = Schema::build(root_query, EmptyMutation, EmptySubscription).finish();
+        let schema =
+            Schema::build(root_query, async_graphql::EmptyMutation, EmptySubscription).finish();
 
         let query = "{ health { status } }";
         let result = tokio_test::block_on(schema.execute(query));
         assert!(result.errors.is_empty());
     }
 
+    #[test]
+    fn test_introspection_can_be_disabled() {
+        let query = "{ __schema { queryType { name } } }";
+
+        let result = tokio_test::block_on(build_schema(true).execute(query));
+        let data = result.data.into_json().unwrap();
+        assert_eq!(data["__schema"]["queryType"]["name"], "RootQuery");
+
+        let result = tokio_test::block_on(build_schema(false).execute(query));
+        let data = result.data.into_json().unwrap();
+        assert!(data["__schema"].is_null());
+    }
+
+    #[test]
+    fn test_schema_exposes_account_mutations() {
+        let schema: AppSchema = create_schema();
+        let sdl = schema.sdl();
+
+        assert!(sdl.contains("type RootMutation"));
+        assert!(sdl.contains("rotateApiKey"));
+        assert!(sdl.contains("revokeApiKey"));
+    }
+
     #[test]
     fn test_bulk_email_validation_in_schema() {
         let schema: AppSchema = create_schema();
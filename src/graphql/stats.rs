@@ -0,0 +1,137 @@
+use crate::error::ApiError;
+use crate::graphql::account::current_account;
+use crate::validation_stats::{StatsReport, StatsWindow, ValidationStats};
+use async_graphql::{Context, Enum, ErrorExtensions, Object, Result, SimpleObject};
+
+/// Period a statistics report covers, ending now
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum StatsPeriod {
+    Hour,
+    Day,
+    Month,
+}
+
+impl From<StatsPeriod> for StatsWindow {
+    fn from(period: StatsPeriod) -> Self {
+        match period {
+            StatsPeriod::Hour => StatsWindow::Hour,
+            StatsPeriod::Day => StatsWindow::Day,
+            StatsPeriod::Month => StatsWindow::Month,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ErrorCodeCount {
+    pub code: String,
+    pub count: u64,
+}
+
+#[derive(SimpleObject)]
+pub struct InvalidDomainCount {
+    pub domain: String,
+    pub count: u64,
+}
+
+/// Validation totals over a period, mirroring `GET /api/v1/stats`
+#[derive(SimpleObject)]
+pub struct ValidationStatsReport {
+    /// Unix timestamp of the first bucket included
+    pub since: i64,
+    pub total: u64,
+    pub valid: u64,
+    pub invalid: u64,
+    pub error_codes: Vec<ErrorCodeCount>,
+    pub top_invalid_domains: Vec<InvalidDomainCount>,
+    pub cache_hit_rate: Option<f64>,
+    pub average_latency_ms: Option<f64>,
+}
+
+impl From<StatsReport> for ValidationStatsReport {
+    fn from(report: StatsReport) -> Self {
+        Self {
+            since: report.since,
+            total: report.total,
+            valid: report.valid,
+            invalid: report.invalid,
+            error_codes: report
+                .error_codes
+                .into_iter()
+                .map(|c| ErrorCodeCount {
+                    code: c.code,
+                    count: c.count,
+                })
+                .collect(),
+            top_invalid_domains: report
+                .top_invalid_domains
+                .into_iter()
+                .map(|d| InvalidDomainCount {
+                    domain: d.domain,
+                    count: d.count,
+                })
+                .collect(),
+            cache_hit_rate: report.cache_hit_rate,
+            average_latency_ms: report.average_latency_ms,
+        }
+    }
+}
+
+/// Root query for service-wide validation statistics
+#[derive(Default)]
+pub struct StatsQuery {
+    pub stats: ValidationStats,
+}
+
+impl StatsQuery {
+    pub fn new(redis_url: &str) -> Result<Self, redis::RedisError> {
+        Ok(Self {
+            stats: ValidationStats::new(redis_url)?,
+        })
+    }
+}
+
+#[Object]
+impl StatsQuery {
+    /// Verdict, error code, cache and latency totals; defaults to the last day
+    async fn validation_stats(
+        &self,
+        ctx: &Context<'_>,
+        period: Option<StatsPeriod>,
+    ) -> Result<ValidationStatsReport> {
+        current_account(ctx)?;
+        let window = period.map(StatsWindow::from).unwrap_or_default();
+        self.stats
+            .report(window, chrono::Utc::now().timestamp())
+            .await
+            .map(Into::into)
+            .map_err(|e| ApiError::upstream("stats store", e).extend())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthenticatedAccount;
+    use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+
+    #[tokio::test]
+    async fn test_validation_stats_requires_account() {
+        let schema =
+            Schema::build(StatsQuery::default(), EmptyMutation, EmptySubscription).finish();
+        let query =
+            "{ validationStats(period: HOUR) { total cacheHitRate topInvalidDomains { domain } } }";
+
+        let anonymous = schema.execute(query).await;
+        assert_eq!(anonymous.errors.len(), 1);
+
+        let request = async_graphql::Request::new(query).data(AuthenticatedAccount {
+            email: "owner@example.com".to_string(),
+            ..AuthenticatedAccount::default()
+        });
+        let response = schema.execute(request).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["validationStats"]["total"], 0);
+        assert!(data["validationStats"]["cacheHitRate"].is_null());
+    }
+}
@@ -0,0 +1,245 @@
+use crate::error::ApiError;
+use crate::graphql::account::{ensure_writable, list_owner, mongo_client};
+use crate::handlers::validation::suppression_list::{
+    self, ImportSummary, MAX_IMPORT_ENTRIES, SUPPRESSIONS_PAGE_SIZE, SuppressedAddress,
+    SuppressionInput, SuppressionReason,
+};
+use actix_web::http::StatusCode;
+use async_graphql::{Context, Enum, ErrorExtensions, InputObject, Object, Result, SimpleObject};
+use serde_json::json;
+
+/// Why an address was suppressed
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(name = "SuppressionReason")]
+pub enum SuppressionReasonKind {
+    Bounce,
+    Complaint,
+    Unsubscribe,
+    Manual,
+}
+
+impl From<SuppressionReasonKind> for SuppressionReason {
+    fn from(kind: SuppressionReasonKind) -> Self {
+        match kind {
+            SuppressionReasonKind::Bounce => SuppressionReason::Bounce,
+            SuppressionReasonKind::Complaint => SuppressionReason::Complaint,
+            SuppressionReasonKind::Unsubscribe => SuppressionReason::Unsubscribe,
+            SuppressionReasonKind::Manual => SuppressionReason::Manual,
+        }
+    }
+}
+
+impl From<SuppressionReason> for SuppressionReasonKind {
+    fn from(reason: SuppressionReason) -> Self {
+        match reason {
+            SuppressionReason::Bounce => SuppressionReasonKind::Bounce,
+            SuppressionReason::Complaint => SuppressionReasonKind::Complaint,
+            SuppressionReason::Unsubscribe => SuppressionReasonKind::Unsubscribe,
+            SuppressionReason::Manual => SuppressionReasonKind::Manual,
+        }
+    }
+}
+
+/// An address on the caller's suppression list
+#[derive(SimpleObject)]
+pub struct Suppression {
+    pub email: String,
+    pub reason: SuppressionReasonKind,
+    pub note: Option<String>,
+    /// Unix timestamps
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<SuppressedAddress> for Suppression {
+    fn from(entry: SuppressedAddress) -> Self {
+        Suppression {
+            email: entry.email,
+            reason: entry.reason.into(),
+            note: entry.note,
+            created_at: entry.created_at,
+            updated_at: entry.updated_at,
+        }
+    }
+}
+
+/// One page of the caller's suppression list, like `GET /api/v1/suppressions`
+#[derive(SimpleObject)]
+pub struct SuppressionPage {
+    pub entries: Vec<Suppression>,
+    /// Entries matching the filter, across all pages
+    pub total: u64,
+    pub page: usize,
+    pub per_page: usize,
+}
+
+/// One address of an import
+#[derive(InputObject)]
+pub struct SuppressionEntryInput {
+    pub email: String,
+    /// Defaults to `MANUAL`
+    pub reason: Option<SuppressionReasonKind>,
+    pub note: Option<String>,
+}
+
+impl From<SuppressionEntryInput> for SuppressionInput {
+    fn from(input: SuppressionEntryInput) -> Self {
+        SuppressionInput {
+            email: input.email,
+            reason: input.reason.map(Into::into).unwrap_or_default(),
+            note: input.note,
+        }
+    }
+}
+
+/// What an import changed
+#[derive(SimpleObject)]
+pub struct SuppressionImportResult {
+    pub added: u64,
+    pub updated: u64,
+    /// Malformed addresses and repeats within the import
+    pub skipped: u64,
+}
+
+impl From<ImportSummary> for SuppressionImportResult {
+    fn from(summary: ImportSummary) -> Self {
+        SuppressionImportResult {
+            added: summary.added,
+            updated: summary.updated,
+            skipped: summary.skipped,
+        }
+    }
+}
+
+/// Suppression list queries for the authenticated caller
+#[derive(Default)]
+pub struct SuppressionsQuery;
+
+#[Object]
+impl SuppressionsQuery {
+    /// The caller's suppressed addresses, by address
+    async fn suppressions(
+        &self,
+        ctx: &Context<'_>,
+        reason: Option<SuppressionReasonKind>,
+        #[graphql(default = 1)] page: usize,
+    ) -> Result<SuppressionPage> {
+        if page == 0 {
+            return Err(ApiError::validation("INVALID_PAGE", "page starts at 1").extend());
+        }
+        let owner = list_owner(ctx, false).await?;
+        let (entries, total) =
+            suppression_list::list(mongo_client(ctx)?, &owner, reason.map(Into::into), page)
+                .await
+                .map_err(|e| ApiError::upstream("database", e).extend())?;
+        Ok(SuppressionPage {
+            entries: entries.into_iter().map(Into::into).collect(),
+            total,
+            page,
+            per_page: SUPPRESSIONS_PAGE_SIZE,
+        })
+    }
+
+    /// The caller's whole suppression list, like `GET /api/v1/suppressions/export`
+    async fn export_suppressions(
+        &self,
+        ctx: &Context<'_>,
+        reason: Option<SuppressionReasonKind>,
+    ) -> Result<Vec<Suppression>> {
+        let owner = list_owner(ctx, false).await?;
+        let entries = suppression_list::export(mongo_client(ctx)?, &owner, reason.map(Into::into))
+            .await
+            .map_err(|e| ApiError::upstream("database", e).extend())?;
+        Ok(entries.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Suppression list mutations, mirroring `/api/v1/suppressions`
+#[derive(Default)]
+pub struct SuppressionsMutation;
+
+#[Object]
+impl SuppressionsMutation {
+    /// Suppresses an address; validations of it then fail with `SUPPRESSED`
+    async fn add_suppression(
+        &self,
+        ctx: &Context<'_>,
+        email: String,
+        #[graphql(default_with = "SuppressionReasonKind::Manual")] reason: SuppressionReasonKind,
+        note: Option<String>,
+    ) -> Result<Suppression> {
+        ensure_writable()?;
+        let owner = list_owner(ctx, true).await?;
+        let input = SuppressionInput {
+            email,
+            reason: reason.into(),
+            note,
+        };
+        suppression_list::add(mongo_client(ctx)?, &owner, &input)
+            .await
+            .map(Into::into)
+            .map_err(|e| ApiError::from(e).extend())
+    }
+
+    /// Lifts a suppression; false when the address wasn't suppressed
+    async fn remove_suppression(&self, ctx: &Context<'_>, email: String) -> Result<bool> {
+        ensure_writable()?;
+        let owner = list_owner(ctx, true).await?;
+        suppression_list::remove(mongo_client(ctx)?, &owner, &email)
+            .await
+            .map_err(|e| ApiError::upstream("database", e).extend())
+    }
+
+    /// Suppresses many addresses at once, like `POST /api/v1/suppressions/import`
+    async fn import_suppressions(
+        &self,
+        ctx: &Context<'_>,
+        entries: Vec<SuppressionEntryInput>,
+    ) -> Result<SuppressionImportResult> {
+        ensure_writable()?;
+        if entries.is_empty() {
+            return Err(
+                ApiError::validation("EMPTY_BATCH", "The import contains no entries")
+                    .with_status(StatusCode::UNPROCESSABLE_ENTITY)
+                    .extend(),
+            );
+        }
+        if entries.len() > MAX_IMPORT_ENTRIES {
+            return Err(ApiError::validation(
+                "BATCH_TOO_LARGE",
+                format!(
+                    "The import contains {} entries; the maximum is {}",
+                    entries.len(),
+                    MAX_IMPORT_ENTRIES
+                ),
+            )
+            .with_status(StatusCode::PAYLOAD_TOO_LARGE)
+            .with_details(json!({ "max_batch_size": MAX_IMPORT_ENTRIES }))
+            .extend());
+        }
+        let owner = list_owner(ctx, true).await?;
+        let inputs: Vec<SuppressionInput> = entries.into_iter().map(Into::into).collect();
+        suppression_list::import(mongo_client(ctx)?, &owner, &inputs)
+            .await
+            .map(Into::into)
+            .map_err(|e| ApiError::from(e).extend())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphql::schema::create_schema;
+
+    #[tokio::test]
+    async fn test_suppressions_require_account() {
+        let response = create_schema()
+            .execute("{ suppressions { total entries { email reason } } }")
+            .await;
+        assert_eq!(response.errors[0].message, "Authentication required");
+
+        let response = create_schema()
+            .execute(r#"mutation { removeSuppression(email: "user@example.com") }"#)
+            .await;
+        assert_eq!(response.errors[0].message, "Authentication required");
+    }
+}
@@ -0,0 +1,303 @@
+pub mod proto;
+
+use crate::auth;
+use crate::bulk::{self, DedupedBatch};
+use crate::checks::Checks;
+use crate::error::ApiError;
+use crate::feature_flags::{FeatureFlags, Flag};
+use crate::handlers::validation::custom_lists::AccountLists;
+use crate::input_limits;
+use crate::job_queue::JobQueue;
+use crate::metering::{self, Meter};
+use crate::organizations;
+use crate::routes::email;
+use crate::validation_service::{ValidationBackends, ValidationService};
+use mongodb::Client as MongoClient;
+use proto::email_sanitizer_server::{EmailSanitizer, EmailSanitizerServer};
+use std::net::SocketAddr;
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response, Status, Streaming};
+
+/// Address of the gRPC listener; the server only starts when `GRPC_PORT` is set
+pub fn listen_addr() -> Option<SocketAddr> {
+    let port = std::env::var("GRPC_PORT")
+        .ok()?
+        .trim()
+        .parse::<u16>()
+        .ok()?;
+    Some(SocketAddr::from(([0, 0, 0, 0], port)))
+}
+
+impl From<email::EmailValidationResponse> for proto::ValidateEmailResponse {
+    fn from(response: email::EmailValidationResponse) -> Self {
+        proto::ValidateEmailResponse {
+            is_valid: response.is_valid,
+            status: response.status,
+            error: response.error.map(|e| proto::ValidationError {
+                code: e.code,
+                message: e.message,
+            }),
+        }
+    }
+}
+
+/// The validation API over gRPC, sharing the REST pipeline
+///
+/// Callers authenticate with `authorization: Bearer <API key or session token>`
+/// metadata; their custom lists apply exactly as they do over REST.
+#[derive(Clone)]
+pub struct EmailSanitizerService {
+    backends: ValidationBackends,
+    flags: FeatureFlags,
+    job_queue: JobQueue,
+    mongo_client: MongoClient,
+    meter: Option<Meter>,
+}
+
+impl EmailSanitizerService {
+    pub fn new(
+        backends: ValidationBackends,
+        flags: FeatureFlags,
+        job_queue: JobQueue,
+        mongo_client: MongoClient,
+    ) -> Self {
+        Self {
+            backends,
+            flags,
+            job_queue,
+            mongo_client,
+            meter: None,
+        }
+    }
+
+    /// Charges validated addresses to the caller's quota, as over REST
+    pub fn with_meter(mut self, meter: Meter) -> Self {
+        self.meter = Some(meter);
+        self
+    }
+
+    pub fn into_server(self) -> EmailSanitizerServer<Self> {
+        EmailSanitizerServer::new(self)
+    }
+
+    /// Account behind the request's bearer credential, if its key reaches `scope`
+    async fn caller(&self, metadata: &MetadataMap, scope: &str) -> Result<String, Status> {
+        let credential = metadata
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| s.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("Missing authorization metadata"))?;
+
+        let account = auth::authenticate(credential, &self.mongo_client)
+            .await
+            .map_err(|_| Status::unauthenticated("Invalid API key"))?;
+        if !account.has_scope(scope) {
+            let e = account.insufficient_scope();
+            return Err(Status::permission_denied(format!("{}: {}", e.code(), e)));
+        }
+        Ok(account.email)
+    }
+
+    /// Counts `units` validated addresses against the caller's quota
+    async fn charge(&self, owner: &str, units: u64) -> Result<(), Status> {
+        let Some(meter) = &self.meter else {
+            return Ok(());
+        };
+        metering::charge(meter, &self.mongo_client, owner, units)
+            .await
+            .map_err(|e| {
+                let e = ApiError::from(e);
+                Status::resource_exhausted(format!("{}: {}", e.code(), e))
+            })
+    }
+
+    /// Custom lists of the caller, or of its organization, with its feedback about `emails`
+    async fn caller_lists(&self, email: &str, emails: &[String]) -> Result<AccountLists, Status> {
+        let owner = organizations::list_owner(&self.mongo_client, email)
+            .await
+            .map_err(Status::internal)?;
+        AccountLists::load(&self.mongo_client, &owner, emails)
+            .await
+            .map_err(Status::internal)
+    }
+}
+
+/// Rejection of [`input_limits::check_email_field`], with the code prefixed to the
+/// message like the other argument errors
+fn invalid_field(e: ApiError) -> Status {
+    Status::invalid_argument(format!("{}: {}", e.code(), e))
+}
+
+#[tonic::async_trait]
+impl EmailSanitizer for EmailSanitizerService {
+    async fn validate_email(
+        &self,
+        request: Request<proto::ValidateEmailRequest>,
+    ) -> Result<Response<proto::ValidateEmailResponse>, Status> {
+        let owner = self.caller(request.metadata(), "validate").await?;
+        let request = request.into_inner();
+        input_limits::check_email_field("email", &request.email).map_err(invalid_field)?;
+        self.charge(&owner, 1).await?;
+        let lists = self
+            .caller_lists(&owner, &[request.email.trim().to_string()])
+            .await?;
+
+        let tenant_id = owner.to_lowercase();
+        let validation = ValidationService::new(&self.backends)
+            .for_tenant(&tenant_id)
+            .validate(
+                request.email.trim(),
+                Checks::standard(request.check_role_based),
+                &lists,
+            )
+            .await;
+        Ok(Response::new(validation.into()))
+    }
+
+    async fn validate_bulk(
+        &self,
+        request: Request<Streaming<proto::ValidateEmailRequest>>,
+    ) -> Result<Response<proto::ValidateBulkResponse>, Status> {
+        let owner = self.caller(request.metadata(), "validate").await?;
+        if !self.flags.is_enabled(Flag::BulkEnabled).await {
+            return Err(Status::unavailable(
+                "FEATURE_DISABLED: bulk validation is temporarily disabled",
+            ));
+        }
+        let max_batch_size = bulk::max_batch_size();
+
+        let mut stream = request.into_inner();
+        let mut requests = Vec::new();
+        while let Some(message) = stream.message().await? {
+            if requests.len() == max_batch_size {
+                return Err(Status::resource_exhausted(format!(
+                    "BATCH_TOO_LARGE: the maximum batch size is {}",
+                    max_batch_size
+                )));
+            }
+            input_limits::check_email_field(&format!("emails[{}]", requests.len()), &message.email)
+                .map_err(invalid_field)?;
+            requests.push(message);
+        }
+        if requests.is_empty() {
+            return Err(Status::invalid_argument(
+                "EMPTY_BATCH: the stream must contain at least one address",
+            ));
+        }
+
+        // Repeated addresses share one validation and each domain one DNS lookup, as
+        // in the REST bulk endpoint
+        let emails: Vec<String> = requests
+            .iter()
+            .map(|request| request.email.trim().to_string())
+            .collect();
+        let checks: Vec<Checks> = requests
+            .iter()
+            .map(|request| Checks::standard(request.check_role_based))
+            .collect();
+        let (batch, checks) = DedupedBatch::with_keys(&emails, &checks);
+        self.charge(&owner, batch.units()).await?;
+        let lists = self.caller_lists(&owner, &emails).await?;
+        let tenant_id = owner.to_lowercase();
+        let validations = ValidationService::new(&self.backends)
+            .for_tenant(&tenant_id)
+            .validate_batch(&batch.unique, &checks, &lists)
+            .await;
+
+        let mut response = proto::ValidateBulkResponse::default();
+        for (request, validation) in requests.into_iter().zip(batch.fan_out(&validations)) {
+            if validation.is_valid {
+                response.valid_count += 1;
+            } else {
+                response.invalid_count += 1;
+            }
+            response.results.push(proto::BulkValidationResult {
+                email: request.email,
+                validation: Some(validation.into()),
+            });
+        }
+        response.unique_count = batch.unique.len() as i32;
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_job_status(
+        &self,
+        request: Request<proto::GetJobStatusRequest>,
+    ) -> Result<Response<proto::GetJobStatusResponse>, Status> {
+        // Tenant of the caller, as `AuthenticatedAccount::tenant_id`
+        let tenant_id = self
+            .caller(request.metadata(), "jobs")
+            .await?
+            .to_lowercase();
+        let job_id = request.into_inner().job_id;
+
+        match email::load_owned_job(&self.job_queue, &job_id, &tenant_id).await {
+            Ok(job) => Ok(Response::new(proto::GetJobStatusResponse {
+                job_id: job.id,
+                status: format!("{:?}", job.status),
+                created_at: job.created_at,
+            })),
+            Err(e) if e.code() == "JOB_NOT_FOUND" => Err(Status::not_found("Job not found")),
+            Err(_) => Err(Status::internal("Failed to retrieve job status")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stores::{CacheStore, MemoryStore};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_response_conversion() {
+        let response: proto::ValidateEmailResponse = email::EmailValidationResponse {
+            is_valid: false,
+            domain: None,
+            has_tag: None,
+            base_address: None,
+            status: None,
+            error: Some(email::EmailValidationError {
+                code: "CUSTOM_BLOCKED".to_string(),
+                message: "Blocked".to_string(),
+                violations: Vec::new(),
+            }),
+        }
+        .into();
+
+        assert!(!response.is_valid);
+        assert_eq!(response.status, None);
+        assert_eq!(response.error.unwrap().code, "CUSTOM_BLOCKED");
+    }
+
+    #[tokio::test]
+    async fn test_calls_require_authorization_metadata() {
+        let store: Arc<dyn CacheStore> = Arc::new(MemoryStore::default());
+        let service = EmailSanitizerService::new(
+            ValidationBackends::from_store(store.clone()),
+            FeatureFlags::from_store(store),
+            JobQueue::new("redis://127.0.0.1:6379").unwrap(),
+            MongoClient::with_uri_str("mongodb://localhost:27017")
+                .await
+                .unwrap(),
+        );
+
+        let status = service
+            .validate_email(Request::new(proto::ValidateEmailRequest {
+                email: "user@example.com".to_string(),
+                check_role_based: false,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let status = service
+            .get_job_status(Request::new(proto::GetJobStatusRequest {
+                job_id: "job".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+}
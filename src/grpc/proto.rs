@@ -0,0 +1,325 @@
+//! Messages and server stubs for `proto/email_sanitizer.proto` (package `email_sanitizer.v1`).
+//!
+//! Laid out like tonic-build's server-only output and checked in so the crate builds
+//! without `protoc`; keep it in sync with the proto file.
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValidateEmailRequest {
+    #[prost(string, tag = "1")]
+    pub email: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub check_role_based: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValidationError {
+    /// INVALID_SYNTAX, KNOWN_TRAP, PREVIOUS_BOUNCER, INVALID_TLD, INVALID_DOMAIN, ROLE_BASED_EMAIL, DISPOSABLE_EMAIL, CUSTOM_BLOCKED, ...
+    #[prost(string, tag = "1")]
+    pub code: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValidateEmailResponse {
+    #[prost(bool, tag = "1")]
+    pub is_valid: bool,
+    #[prost(string, optional, tag = "2")]
+    pub status: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "3")]
+    pub error: ::core::option::Option<ValidationError>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BulkValidationResult {
+    #[prost(string, tag = "1")]
+    pub email: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub validation: ::core::option::Option<ValidateEmailResponse>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValidateBulkResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub results: ::prost::alloc::vec::Vec<BulkValidationResult>,
+    #[prost(int32, tag = "2")]
+    pub valid_count: i32,
+    #[prost(int32, tag = "3")]
+    pub invalid_count: i32,
+    /// Distinct addresses validated; repeats share one result
+    #[prost(int32, tag = "4")]
+    pub unique_count: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetJobStatusRequest {
+    #[prost(string, tag = "1")]
+    pub job_id: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetJobStatusResponse {
+    #[prost(string, tag = "1")]
+    pub job_id: ::prost::alloc::string::String,
+    /// Pending, Processing, Completed or Failed
+    #[prost(string, tag = "2")]
+    pub status: ::prost::alloc::string::String,
+    #[prost(int64, tag = "3")]
+    pub created_at: i64,
+}
+/// Server implementation of the `EmailSanitizer` service.
+pub mod email_sanitizer_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value
+    )]
+    use tonic::codegen::*;
+    /// gRPC methods to implement for use with EmailSanitizerServer.
+    #[async_trait]
+    pub trait EmailSanitizer: std::marker::Send + std::marker::Sync + 'static {
+        /// Validates a single address
+        async fn validate_email(
+            &self,
+            request: tonic::Request<super::ValidateEmailRequest>,
+        ) -> std::result::Result<tonic::Response<super::ValidateEmailResponse>, tonic::Status>;
+        /// Validates a stream of addresses and answers once the stream ends
+        async fn validate_bulk(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::ValidateEmailRequest>>,
+        ) -> std::result::Result<tonic::Response<super::ValidateBulkResponse>, tonic::Status>;
+        /// Status of a queued bulk validation job
+        async fn get_job_status(
+            &self,
+            request: tonic::Request<super::GetJobStatusRequest>,
+        ) -> std::result::Result<tonic::Response<super::GetJobStatusResponse>, tonic::Status>;
+    }
+    /// Email validation over gRPC; every call needs `authorization: Bearer <API key or session token>`
+    /// metadata. Results match the REST and GraphQL APIs.
+    #[derive(Debug)]
+    pub struct EmailSanitizerServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> EmailSanitizerServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(inner: T, interceptor: F) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for EmailSanitizerServer<T>
+    where
+        T: EmailSanitizer,
+        B: Body + std::marker::Send + 'static,
+        B::Error: Into<StdError> + std::marker::Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            match req.uri().path() {
+                "/email_sanitizer.v1.EmailSanitizer/ValidateEmail" => {
+                    #[allow(non_camel_case_types)]
+                    struct ValidateEmailSvc<T: EmailSanitizer>(pub Arc<T>);
+                    impl<T: EmailSanitizer> tonic::server::UnaryService<super::ValidateEmailRequest>
+                        for ValidateEmailSvc<T>
+                    {
+                        type Response = super::ValidateEmailResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ValidateEmailRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as EmailSanitizer>::validate_email(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ValidateEmailSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/email_sanitizer.v1.EmailSanitizer/ValidateBulk" => {
+                    #[allow(non_camel_case_types)]
+                    struct ValidateBulkSvc<T: EmailSanitizer>(pub Arc<T>);
+                    impl<T: EmailSanitizer>
+                        tonic::server::ClientStreamingService<super::ValidateEmailRequest>
+                        for ValidateBulkSvc<T>
+                    {
+                        type Response = super::ValidateBulkResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<tonic::Streaming<super::ValidateEmailRequest>>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as EmailSanitizer>::validate_bulk(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ValidateBulkSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.client_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/email_sanitizer.v1.EmailSanitizer/GetJobStatus" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetJobStatusSvc<T: EmailSanitizer>(pub Arc<T>);
+                    impl<T: EmailSanitizer> tonic::server::UnaryService<super::GetJobStatusRequest>
+                        for GetJobStatusSvc<T>
+                    {
+                        type Response = super::GetJobStatusResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetJobStatusRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as EmailSanitizer>::get_job_status(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetJobStatusSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => Box::pin(async move {
+                    let mut response = http::Response::new(empty_body());
+                    let headers = response.headers_mut();
+                    headers.insert(
+                        tonic::Status::GRPC_STATUS,
+                        (tonic::Code::Unimplemented as i32).into(),
+                    );
+                    headers.insert(
+                        http::header::CONTENT_TYPE,
+                        tonic::metadata::GRPC_CONTENT_TYPE,
+                    );
+                    Ok(response)
+                }),
+            }
+        }
+    }
+    impl<T> Clone for EmailSanitizerServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+                max_decoding_message_size: self.max_decoding_message_size,
+                max_encoding_message_size: self.max_encoding_message_size,
+            }
+        }
+    }
+    /// gRPC service name
+    pub const SERVICE_NAME: &str = "email_sanitizer.v1.EmailSanitizer";
+    impl<T> tonic::server::NamedService for EmailSanitizerServer<T> {
+        const NAME: &'static str = SERVICE_NAME;
+    }
+}
@@ -0,0 +1,411 @@
+use crate::handlers::validation::suppression::address_hash;
+use futures::TryStreamExt;
+use mongodb::bson::doc;
+use mongodb::options::{IndexOptions, ReturnDocument};
+use mongodb::{Client, Collection, IndexModel};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
+
+/// What an account's mail to an address ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedbackKind {
+    Bounce,
+    Complaint,
+}
+
+impl FeedbackKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedbackKind::Bounce => "bounce",
+            FeedbackKind::Complaint => "complaint",
+        }
+    }
+
+    /// Verdict code of addresses suppressed by this kind of feedback
+    pub fn code(&self) -> &'static str {
+        match self {
+            FeedbackKind::Bounce => "PREVIOUS_BOUNCE",
+            FeedbackKind::Complaint => "PREVIOUS_COMPLAINT",
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            FeedbackKind::Bounce => "Mail from this account to the address has bounced",
+            FeedbackKind::Complaint => "The recipient marked mail from this account as spam",
+        }
+    }
+}
+
+/// One recipient of a bounce or complaint notification
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FeedbackEvent {
+    pub email: String,
+    #[serde(rename = "type")]
+    pub kind: FeedbackKind,
+    /// Hard bounces and complaints suppress the address at once; soft (transient)
+    /// bounces only after repeating
+    #[serde(default = "permanent_by_default")]
+    pub permanent: bool,
+}
+
+fn permanent_by_default() -> bool {
+    true
+}
+
+/// A notification posted to the feedback endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification {
+    Events(Vec<FeedbackEvent>),
+    /// SNS asks for the subscription to be confirmed by visiting `subscribe_url`
+    SubscriptionConfirmation {
+        subscribe_url: String,
+    },
+}
+
+/// Reads an SNS envelope, a bare SES notification or event, or the generic
+/// `{ "events": [{ "email", "type": "bounce"|"complaint", "permanent"? }] }` format
+///
+/// SES notifications other than bounces and complaints (deliveries, opens) carry no
+/// events.
+pub fn parse_notification(body: &Value) -> Result<Notification, String> {
+    if let Some(kind) = body["Type"].as_str() {
+        return match kind {
+            "SubscriptionConfirmation" | "UnsubscribeConfirmation" => {
+                let subscribe_url = body["SubscribeURL"]
+                    .as_str()
+                    .ok_or("The confirmation has no SubscribeURL")?;
+                Ok(Notification::SubscriptionConfirmation {
+                    subscribe_url: subscribe_url.to_string(),
+                })
+            }
+            "Notification" => {
+                let message = body["Message"]
+                    .as_str()
+                    .ok_or("The SNS notification has no Message")?;
+                Ok(Notification::Events(sns_message_events(message)?))
+            }
+            other => Err(format!("Unsupported SNS message type {}", other)),
+        };
+    }
+    if body.get("notificationType").is_some() || body.get("eventType").is_some() {
+        return Ok(Notification::Events(ses_events(body)?));
+    }
+    if let Some(events) = body.get("events") {
+        return serde_json::from_value(events.clone())
+            .map(Notification::Events)
+            .map_err(|e| format!("Invalid events: {}", e));
+    }
+    Err("Expected an SNS message, an SES notification or an events array".to_string())
+}
+
+/// Recipients of the SES notification an SNS notification carries as its `Message`
+pub fn sns_message_events(message: &str) -> Result<Vec<FeedbackEvent>, String> {
+    let message: Value = serde_json::from_str(message)
+        .map_err(|_| "The SNS Message is not a JSON SES notification")?;
+    ses_events(&message)
+}
+
+/// Recipients of an SES bounce or complaint, as notifications (`notificationType`)
+/// and event publishing (`eventType`) send them
+fn ses_events(message: &Value) -> Result<Vec<FeedbackEvent>, String> {
+    let kind = message["notificationType"]
+        .as_str()
+        .or_else(|| message["eventType"].as_str())
+        .ok_or("The SES notification has no notificationType")?;
+    let (kind, recipients, permanent) = match kind {
+        "Bounce" => (
+            FeedbackKind::Bounce,
+            &message["bounce"]["bouncedRecipients"],
+            message["bounce"]["bounceType"] == "Permanent",
+        ),
+        "Complaint" => (
+            FeedbackKind::Complaint,
+            &message["complaint"]["complainedRecipients"],
+            true,
+        ),
+        _ => return Ok(Vec::new()),
+    };
+    Ok(recipients
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|recipient| recipient["emailAddress"].as_str())
+        .map(|email| FeedbackEvent {
+            email: email.to_string(),
+            kind,
+            permanent,
+        })
+        .collect())
+}
+
+/// Feedback about one address, stored in the `bounce_feedback` collection
+///
+/// Like suppression entries, only the hash of the address is kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackEntry {
+    /// Account the feedback was reported by, or its organization
+    pub owner: String,
+    /// [`address_hash`] of the address
+    pub hash: String,
+    pub kind: FeedbackKind,
+    /// Whether validations of the address fail with [`FeedbackKind::code`]
+    pub suppressed: bool,
+    /// Transient bounces reported so far
+    pub soft_bounces: i32,
+    /// Unix timestamp of the last report
+    pub updated_at: i64,
+}
+
+/// What a batch of feedback changed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FeedbackSummary {
+    /// Addresses suppressed by this batch
+    pub suppressed: u64,
+    /// Transient bounces counted without suppressing their address yet
+    pub soft_bounces: u64,
+}
+
+/// Transient bounces after which an address is suppressed (`BOUNCE_SOFT_LIMIT`, default 3)
+pub fn soft_bounce_limit() -> i32 {
+    std::env::var("BOUNCE_SOFT_LIMIT")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|limit: &i32| *limit > 0)
+        .unwrap_or(3)
+}
+
+pub(crate) fn collection(mongo_client: &Client) -> Collection<FeedbackEntry> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let collection_name = std::env::var("DB_BOUNCE_FEEDBACK_COLLECTION")
+        .unwrap_or_else(|_| "bounce_feedback".to_string());
+    mongo_client.database(&db_name).collection(&collection_name)
+}
+
+/// Creates the unique index on `owner` and `hash` that lookups use
+pub async fn ensure_indexes(mongo_client: &Client) -> Result<(), String> {
+    collection(mongo_client)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "owner": 1, "hash": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Records `events` reported by `owner`
+///
+/// Hard bounces and complaints suppress their address immediately. A soft bounce
+/// only counts towards [`soft_bounce_limit`], as the sender's retries may still get
+/// through; the address is suppressed once the limit is reached.
+pub async fn record(
+    mongo_client: &Client,
+    owner: &str,
+    events: &[FeedbackEvent],
+) -> Result<FeedbackSummary, String> {
+    let collection = collection(mongo_client);
+    let now = chrono::Utc::now().timestamp();
+    let limit = soft_bounce_limit();
+    let mut summary = FeedbackSummary::default();
+    let mut seen = HashSet::new();
+
+    for event in events {
+        let hash = address_hash(&event.email);
+        if !seen.insert((hash.clone(), event.kind, event.permanent)) {
+            continue;
+        }
+        let filter = doc! { "owner": owner, "hash": &hash };
+        if event.permanent {
+            collection
+                .update_one(
+                    filter,
+                    doc! {
+                        "$set": { "kind": event.kind.as_str(), "suppressed": true, "updated_at": now },
+                        "$setOnInsert": { "soft_bounces": 0 },
+                    },
+                )
+                .upsert(true)
+                .await
+                .map_err(|e| e.to_string())?;
+            summary.suppressed += 1;
+            continue;
+        }
+
+        let entry = collection
+            .find_one_and_update(
+                filter.clone(),
+                doc! {
+                    "$inc": { "soft_bounces": 1 },
+                    "$set": { "updated_at": now },
+                    "$setOnInsert": { "kind": event.kind.as_str(), "suppressed": false },
+                },
+            )
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .await
+            .map_err(|e| e.to_string())?;
+        match entry {
+            Some(entry) if entry.suppressed => {}
+            Some(entry) if entry.soft_bounces >= limit => {
+                collection
+                    .update_one(filter, doc! { "$set": { "suppressed": true } })
+                    .await
+                    .map_err(|e| e.to_string())?;
+                summary.suppressed += 1;
+            }
+            _ => summary.soft_bounces += 1,
+        }
+    }
+    Ok(summary)
+}
+
+/// Suppressed addresses among `emails`, by [`address_hash`]
+pub async fn lookup(
+    mongo_client: &Client,
+    owner: &str,
+    emails: &[String],
+) -> Result<HashMap<String, FeedbackKind>, String> {
+    if emails.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let hashes: Vec<String> = emails.iter().map(|email| address_hash(email)).collect();
+    let entries: Vec<FeedbackEntry> = collection(mongo_client)
+        .find(doc! { "owner": owner, "hash": { "$in": &hashes }, "suppressed": true })
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?
+        .try_collect()
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| (entry.hash, entry.kind))
+        .collect())
+}
+
+/// Deletes the feedback `owner` reported about an address; returns how many entries
+/// were deleted
+pub async fn remove(mongo_client: &Client, owner: &str, email: &str) -> Result<u64, String> {
+    let result = collection(mongo_client)
+        .delete_many(doc! { "owner": owner, "hash": address_hash(email) })
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?;
+    Ok(result.deleted_count)
+}
+
+/// Deletes the feedback every owner reported about an address; returns how many
+/// entries were deleted
+pub async fn remove_everywhere(mongo_client: &Client, email: &str) -> Result<u64, String> {
+    let result = collection(mongo_client)
+        .delete_many(doc! { "hash": address_hash(email) })
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?;
+    Ok(result.deleted_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(email: &str, kind: FeedbackKind, permanent: bool) -> FeedbackEvent {
+        FeedbackEvent {
+            email: email.to_string(),
+            kind,
+            permanent,
+        }
+    }
+
+    #[test]
+    fn test_parse_sns_notifications() {
+        let bounce = json!({
+            "notificationType": "Bounce",
+            "bounce": {
+                "bounceType": "Permanent",
+                "bouncedRecipients": [{ "emailAddress": "gone@example.com" }]
+            }
+        });
+        let envelope = json!({ "Type": "Notification", "Message": bounce.to_string() });
+        assert_eq!(
+            parse_notification(&envelope),
+            Ok(Notification::Events(vec![event(
+                "gone@example.com",
+                FeedbackKind::Bounce,
+                true
+            )]))
+        );
+
+        let soft = json!({
+            "eventType": "Bounce",
+            "bounce": {
+                "bounceType": "Transient",
+                "bouncedRecipients": [{ "emailAddress": "full@example.com" }]
+            }
+        });
+        assert_eq!(
+            parse_notification(&soft),
+            Ok(Notification::Events(vec![event(
+                "full@example.com",
+                FeedbackKind::Bounce,
+                false
+            )]))
+        );
+
+        let complaint = json!({
+            "notificationType": "Complaint",
+            "complaint": { "complainedRecipients": [{ "emailAddress": "angry@example.com" }] }
+        });
+        assert_eq!(
+            parse_notification(&complaint),
+            Ok(Notification::Events(vec![event(
+                "angry@example.com",
+                FeedbackKind::Complaint,
+                true
+            )]))
+        );
+
+        let delivery = json!({ "notificationType": "Delivery", "delivery": {} });
+        assert_eq!(
+            parse_notification(&delivery),
+            Ok(Notification::Events(Vec::new()))
+        );
+
+        let confirmation = json!({
+            "Type": "SubscriptionConfirmation",
+            "SubscribeURL": "https://sns.us-east-1.amazonaws.com/confirm"
+        });
+        assert_eq!(
+            parse_notification(&confirmation),
+            Ok(Notification::SubscriptionConfirmation {
+                subscribe_url: "https://sns.us-east-1.amazonaws.com/confirm".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_generic_events() {
+        let body = json!({ "events": [
+            { "email": "a@example.com", "type": "bounce" },
+            { "email": "b@example.com", "type": "bounce", "permanent": false },
+            { "email": "c@example.com", "type": "complaint" }
+        ] });
+        assert_eq!(
+            parse_notification(&body),
+            Ok(Notification::Events(vec![
+                event("a@example.com", FeedbackKind::Bounce, true),
+                event("b@example.com", FeedbackKind::Bounce, false),
+                event("c@example.com", FeedbackKind::Complaint, true),
+            ]))
+        );
+
+        assert!(parse_notification(&json!({ "events": [{ "email": "a@example.com" }] })).is_err());
+        assert!(parse_notification(&json!({ "Type": "Notification", "Message": "nope" })).is_err());
+        assert!(parse_notification(&json!({ "hello": "world" })).is_err());
+    }
+}
@@ -0,0 +1,324 @@
+use crate::handlers::validation::bounce_feedback::{self, FeedbackKind};
+use crate::handlers::validation::suppression::address_hash;
+use crate::handlers::validation::suppression_list::{self, SuppressionReason};
+use crate::normalize::normalize_email;
+use futures::TryStreamExt;
+use mongodb::options::ReturnDocument;
+use mongodb::{Client, Collection, bson::doc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+/// Most entries an account may keep in each of its lists
+pub const MAX_ENTRIES_PER_LIST: u64 = 10_000;
+
+/// An account's own list of blocked or always-allowed domains and addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CustomList {
+    Blocklist,
+    Allowlist,
+}
+
+impl CustomList {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CustomList::Blocklist => "blocklist",
+            CustomList::Allowlist => "allowlist",
+        }
+    }
+}
+
+/// One entry, stored in the `account_lists` collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomListEntry {
+    pub owner: String,
+    pub list: CustomList,
+    /// Lowercased domain (`example.com`) or full address (`user@example.com`)
+    pub entry: String,
+    pub created_at: i64,
+}
+
+/// Outcome of an account's lists for one address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomVerdict {
+    Allowed,
+    Blocked,
+    /// Suppressed by the account's own bounce or complaint feedback
+    Feedback(FeedbackKind),
+    /// On the account's suppression list
+    Suppressed(SuppressionReason),
+}
+
+/// Why an entry couldn't be added
+#[derive(Debug, PartialEq, Eq)]
+pub enum AddEntryError {
+    Invalid(String),
+    /// The list already holds [`MAX_ENTRIES_PER_LIST`] entries
+    ListFull,
+    Database(String),
+}
+
+pub(crate) fn collection(mongo_client: &Client) -> Collection<CustomListEntry> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    mongo_client.database(&db_name).collection("account_lists")
+}
+
+/// Lowercases and checks an entry; domains must contain a dot, addresses exactly one `@`
+pub fn normalize_entry(entry: &str) -> Result<String, String> {
+    let entry = entry.trim().trim_start_matches('@').to_lowercase();
+    let domain = match entry.split_once('@') {
+        Some((local, domain)) if !local.is_empty() && !domain.contains('@') => domain,
+        Some(_) => return Err("Entry must be a domain or a single email address".to_string()),
+        None => entry.as_str(),
+    };
+
+    let valid_domain = domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '.' | '-'));
+    if !valid_domain || entry.len() > 254 {
+        return Err("Entry must be a domain or a single email address".to_string());
+    }
+    Ok(entry)
+}
+
+fn entry_matches(entry: &str, address: &str, domain: &str) -> bool {
+    if entry.contains('@') {
+        entry == address
+    } else {
+        // Domain entries cover their subdomains too
+        domain == entry
+            || domain
+                .strip_suffix(entry)
+                .is_some_and(|rest| rest.ends_with('.'))
+    }
+}
+
+/// An account's lists, loaded once per request
+#[derive(Debug, Clone, Default)]
+pub struct AccountLists {
+    pub blocked: Vec<String>,
+    pub allowed: Vec<String>,
+    /// Suppressing feedback about the request's addresses, by address hash
+    pub feedback: HashMap<String, FeedbackKind>,
+    /// The request's addresses on the suppression list, by address hash
+    pub suppressed: HashMap<String, SuppressionReason>,
+}
+
+impl AccountLists {
+    /// The owner's lists, with its feedback about and suppressions of `emails`, the
+    /// addresses the request validates
+    pub async fn load(
+        mongo_client: &Client,
+        owner: &str,
+        emails: &[String],
+    ) -> Result<Self, String> {
+        let entries: Vec<CustomListEntry> = collection(mongo_client)
+            .find(doc! { "owner": owner })
+            .await
+            .map_err(|e| format!("Database query failed: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Database query failed: {}", e))?;
+
+        let mut lists = AccountLists {
+            feedback: bounce_feedback::lookup(mongo_client, owner, emails).await?,
+            suppressed: suppression_list::lookup(mongo_client, owner, emails).await?,
+            ..Default::default()
+        };
+        for entry in entries {
+            match entry.list {
+                CustomList::Blocklist => lists.blocked.push(entry.entry),
+                CustomList::Allowlist => lists.allowed.push(entry.entry),
+            }
+        }
+        Ok(lists)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocked.is_empty()
+            && self.allowed.is_empty()
+            && self.feedback.is_empty()
+            && self.suppressed.is_empty()
+    }
+
+    /// Suppressions win over allowlist entries, which win over feedback, which wins
+    /// over blocklist entries; `None` when none applies
+    pub fn verdict(&self, email: &str) -> Option<CustomVerdict> {
+        // Entries are stored lowercased, so lists match regardless of local-part case
+        let address = normalize_email(email).to_lowercase();
+        let domain = address.rsplit_once('@').map(|(_, d)| d)?;
+
+        if let Some(reason) = self.suppressed.get(&address_hash(email)) {
+            Some(CustomVerdict::Suppressed(*reason))
+        } else if self
+            .allowed
+            .iter()
+            .any(|e| entry_matches(e, &address, domain))
+        {
+            Some(CustomVerdict::Allowed)
+        } else if let Some(kind) = self.feedback.get(&address_hash(email)) {
+            Some(CustomVerdict::Feedback(*kind))
+        } else if self
+            .blocked
+            .iter()
+            .any(|e| entry_matches(e, &address, domain))
+        {
+            Some(CustomVerdict::Blocked)
+        } else {
+            None
+        }
+    }
+}
+
+pub async fn list_entries(
+    mongo_client: &Client,
+    owner: &str,
+    list: CustomList,
+) -> Result<Vec<CustomListEntry>, String> {
+    collection(mongo_client)
+        .find(doc! { "owner": owner, "list": list.as_str() })
+        .sort(doc! { "entry": 1 })
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?
+        .try_collect()
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))
+}
+
+/// Adds an entry; adding one that already exists returns the stored entry
+pub async fn add_entry(
+    mongo_client: &Client,
+    owner: &str,
+    list: CustomList,
+    entry: &str,
+) -> Result<CustomListEntry, AddEntryError> {
+    let entry = normalize_entry(entry).map_err(AddEntryError::Invalid)?;
+    let collection = collection(mongo_client);
+    let db_error = |e: mongodb::error::Error| AddEntryError::Database(e.to_string());
+
+    let count = collection
+        .count_documents(doc! { "owner": owner, "list": list.as_str() })
+        .await
+        .map_err(db_error)?;
+    if count >= MAX_ENTRIES_PER_LIST {
+        return Err(AddEntryError::ListFull);
+    }
+
+    collection
+        .find_one_and_update(
+            doc! { "owner": owner, "list": list.as_str(), "entry": &entry },
+            doc! { "$setOnInsert": { "created_at": chrono::Utc::now().timestamp() } },
+        )
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| AddEntryError::Database("Upsert returned no document".to_string()))
+}
+
+/// Removes an entry; `false` when the list didn't contain it
+pub async fn remove_entry(
+    mongo_client: &Client,
+    owner: &str,
+    list: CustomList,
+    entry: &str,
+) -> Result<bool, String> {
+    let entry = entry.trim().trim_start_matches('@').to_lowercase();
+    let result = collection(mongo_client)
+        .delete_one(doc! { "owner": owner, "list": list.as_str(), "entry": entry })
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?;
+    Ok(result.deleted_count > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_entry() {
+        assert_eq!(
+            normalize_entry(" Example.COM "),
+            Ok("example.com".to_string())
+        );
+        assert_eq!(
+            normalize_entry("@example.com"),
+            Ok("example.com".to_string())
+        );
+        assert_eq!(
+            normalize_entry("User@Example.com"),
+            Ok("user@example.com".to_string())
+        );
+        assert!(normalize_entry("localhost").is_err());
+        assert!(normalize_entry("a@b@example.com").is_err());
+        assert!(normalize_entry("exa mple.com").is_err());
+    }
+
+    #[test]
+    fn test_verdicts() {
+        let lists = AccountLists {
+            blocked: vec!["competitor.com".to_string(), "spam@example.com".to_string()],
+            allowed: vec!["partner.competitor.com".to_string()],
+            feedback: HashMap::from([
+                (address_hash("gone@example.com"), FeedbackKind::Bounce),
+                (
+                    address_hash("gone@partner.competitor.com"),
+                    FeedbackKind::Bounce,
+                ),
+                (
+                    address_hash("angry@competitor.com"),
+                    FeedbackKind::Complaint,
+                ),
+            ]),
+            suppressed: HashMap::from([(
+                address_hash("left@partner.competitor.com"),
+                SuppressionReason::Unsubscribe,
+            )]),
+        };
+
+        assert_eq!(
+            lists.verdict("a@competitor.com"),
+            Some(CustomVerdict::Blocked)
+        );
+        assert_eq!(
+            lists.verdict("a@mail.competitor.com"),
+            Some(CustomVerdict::Blocked)
+        );
+        assert_eq!(lists.verdict("a@notcompetitor.com"), None);
+        assert_eq!(
+            lists.verdict("a@partner.competitor.com"),
+            Some(CustomVerdict::Allowed)
+        );
+        assert_eq!(
+            lists.verdict("Spam@Example.com"),
+            Some(CustomVerdict::Blocked)
+        );
+        assert_eq!(lists.verdict("ham@example.com"), None);
+        assert_eq!(lists.verdict("not-an-address"), None);
+
+        // Feedback matches normalized addresses and outranks the blocklist only
+        assert_eq!(
+            lists.verdict("gone@Example.com"),
+            Some(CustomVerdict::Feedback(FeedbackKind::Bounce))
+        );
+        assert_eq!(
+            lists.verdict("angry@competitor.com"),
+            Some(CustomVerdict::Feedback(FeedbackKind::Complaint))
+        );
+        assert_eq!(
+            lists.verdict("gone@partner.competitor.com"),
+            Some(CustomVerdict::Allowed)
+        );
+
+        // Suppressions outrank even the allowlist
+        assert_eq!(
+            lists.verdict("left@Partner.Competitor.com"),
+            Some(CustomVerdict::Suppressed(SuppressionReason::Unsubscribe))
+        );
+    }
+}
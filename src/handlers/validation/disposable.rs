@@ -1,114 +1,185 @@
-#[cfg(not(test))]
-use mongodb::bson::{Document, doc};
-#[cfg(not(test))]
-use mongodb::{Client, Collection};
-#[cfg(not(test))]
-use std::env;
+use crate::normalize::lookup_key;
+use crate::stores::DomainListStore;
+use crate::sync::SyncList;
+use std::collections::HashSet;
 use std::error::Error;
 
-/// Checks if an email address uses a disposable domain by querying a MongoDB collection.
+/// Checks if an email address uses a disposable domain by looking it up in the
+/// disposable domain list of `store`.
+///
+/// Subdomains of listed domains are disposable too, so `foo.mailinator.com` matches a
+/// `mailinator.com` entry. Entries like `*.tempmail.org` match the subdomains of
+/// `tempmail.org` but not the domain itself.
 ///
 /// # Arguments
+/// * `store` - The reference lists, e.g. [`MongoDomainLists`](crate::stores::MongoDomainLists)
 /// * `email` - A string slice containing the email address to check
 ///
 /// # Returns
-/// * `Ok(true)` if the domain is found in the disposable email collection
-/// * `Ok(false)` if the domain is not found
+/// * `Ok(true)` if the domain, a parent domain, or a pattern covering it is on the
+///   disposable domain list
+/// * `Ok(false)` if none of them is found
 /// * `Err` containing an error message if any step fails
 ///
 /// # Errors
 /// Returns an error if:
 /// - The email is missing '@' symbol (invalid format)
-/// - Environment variables are not properly configured
-/// - MongoDB connection or query fails
+/// - The store can't be queried
 ///
 /// # Example
 /// ```
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 /// use email_sanitizer::handlers::validation::disposable::is_disposable_email;
-/// let is_spam = is_disposable_email("example@mailinator.com").await?;
+/// use email_sanitizer::stores::MongoDomainLists;
+/// let is_spam = is_disposable_email(&MongoDomainLists::from_env(), "example@mailinator.com").await?;
 /// assert_eq!(is_spam, true);
 /// # Ok(())
 /// # }
 /// ```
-#[cfg(test)]
-pub async fn is_disposable_email(email: &str) -> Result<bool, Box<dyn Error>> {
-    is_disposable_email_mock(email).await
-}
-
-#[cfg(not(test))]
-pub async fn is_disposable_email(email: &str) -> Result<bool, Box<dyn Error>> {
+pub async fn is_disposable_email(
+    store: &dyn DomainListStore,
+    email: &str,
+) -> Result<bool, Box<dyn Error>> {
     // Extract domain from email
     let (_, domain_part) = email
         .split_once('@')
         .ok_or("Invalid email format: missing '@'")?;
-    let domain = domain_part.to_lowercase();
+    let domain = lookup_key(domain_part);
 
-    // Retrieve environment variables
-    let mongo_uri = env::var("MONGODB_URI")?;
-    let db_name = env::var("DB_NAME_PRODUCTION")?;
-    let collection_name = env::var("DB_DISPOSABLE_EMAILS_COLLECTION")?;
-
-    // Connect to MongoDB
-    let client = Client::with_uri_str(&mongo_uri).await?;
-    let database = client.database(&db_name);
-    let collection: Collection<Document> = database.collection(&collection_name);
-
-    // Check if domain exists in the collection
-    let filter = doc! { "domain": domain };
-    let exists = collection.find_one(filter).await?.is_some();
-
-    Ok(exists)
+    let disposable = disposable_domains(store, std::slice::from_ref(&domain)).await?;
+    Ok(disposable.contains(&domain))
 }
 
-/// Mock implementation for testing without MongoDB
-#[cfg(test)]
-pub async fn is_disposable_email_mock(email: &str) -> Result<bool, Box<dyn Error>> {
-    // Extract domain from email
-    let (_, domain_part) = email
-        .split_once('@')
-        .ok_or("Invalid email format: missing '@'")?;
-    let domain = domain_part.to_lowercase();
+/// List entries that would make `domain` disposable: the domain itself, and each parent
+/// domain above it both as is and as a `*.` pattern; bare TLDs are left out
+///
+/// `a.tempmail.org` yields `a.tempmail.org`, `tempmail.org` and `*.tempmail.org`.
+fn entries_covering(domain: &str) -> Vec<String> {
+    let domain = domain.trim_end_matches('.');
+    let mut entries = vec![domain.to_string()];
+    let mut parent = domain;
+    while let Some((_, rest)) = parent.split_once('.') {
+        if !rest.contains('.') {
+            break;
+        }
+        entries.push(rest.to_string());
+        entries.push(format!("*.{}", rest));
+        parent = rest;
+    }
+    entries
+}
 
-    // Mock disposable domains for testing
-    let disposable_domains = vec![
-        "mailinator.com",
-        "0-00.usa.cc",
-        "10minutemail.com",
-        "guerrillamail.com",
-        "tempmail.org",
-    ];
+/// Returns which of `domains` (already folded with [`lookup_key`]) are disposable, with
+/// a single lookup however many domains are passed
+///
+/// The lookup asks for exact entries only, the covering entries of every domain at
+/// once, so it stays an index lookup on the list's `domain` field (see
+/// [`ensure_indexes`](crate::list_slots::ensure_indexes)).
+pub async fn disposable_domains(
+    store: &dyn DomainListStore,
+    domains: &[String],
+) -> Result<HashSet<String>, Box<dyn Error>> {
+    let covering: Vec<Vec<String>> = domains
+        .iter()
+        .map(|domain| entries_covering(domain))
+        .collect();
+    let mut entries: Vec<String> = covering.iter().flatten().cloned().collect();
+    entries.sort_unstable();
+    entries.dedup();
 
-    Ok(disposable_domains.contains(&domain.as_str()))
+    let listed = store.matching(SyncList::Disposable, &entries).await?;
+    Ok(domains
+        .iter()
+        .zip(&covering)
+        .filter(|(_, entries)| entries.iter().any(|entry| listed.contains(entry)))
+        .map(|(domain, _)| domain.clone())
+        .collect())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::stores::MemoryDomainLists;
+
+    fn store() -> MemoryDomainLists {
+        MemoryDomainLists::default().with(
+            SyncList::Disposable,
+            &[
+                "mailinator.com",
+                "0-00.usa.cc",
+                "10minutemail.com",
+                "guerrillamail.com",
+                "tempmail.org",
+                "*.throwaway.email",
+            ],
+        )
+    }
 
     #[tokio::test]
-    /// Tests recognition of disposable email domains using mock
+    /// Tests recognition of disposable email domains
     async fn test_disposable_email() {
-        // Test disposable email using mock
-        let result = is_disposable_email_mock("example@mailinator.com").await;
+        let result = is_disposable_email(&store(), "example@MAILINATOR.com").await;
         assert!(result.unwrap(), "Should recognize disposable domain");
     }
 
     #[tokio::test]
-    /// Tests recognition of valid email domains using mock
+    /// Tests recognition of valid email domains
     async fn test_non_disposable_email() {
-        // Test valid email using mock
-        let result = is_disposable_email_mock("johndoe@gmail.com").await;
+        let result = is_disposable_email(&store(), "johndoe@gmail.com").await;
         assert!(!result.unwrap(), "Should recognize non-disposable domain");
     }
 
     #[tokio::test]
     /// Test invalid email format
     async fn test_invalid_email_format() {
-        let result = is_disposable_email_mock("invalid-email").await;
+        let result = is_disposable_email(&store(), "invalid-email").await;
         assert!(
             result.is_err(),
             "Should return error for invalid email format"
         );
     }
+
+    #[tokio::test]
+    async fn test_disposable_domains_batch() {
+        let domains = vec![
+            "mailinator.com".to_string(),
+            "gmail.com".to_string(),
+            "tempmail.org".to_string(),
+        ];
+        let disposable = disposable_domains(&store(), &domains).await.unwrap();
+        assert_eq!(
+            disposable,
+            HashSet::from(["mailinator.com".to_string(), "tempmail.org".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_entries_covering() {
+        assert_eq!(
+            entries_covering("a.tempmail.org."),
+            vec!["a.tempmail.org", "tempmail.org", "*.tempmail.org"]
+        );
+        assert_eq!(entries_covering("tempmail.org"), vec!["tempmail.org"]);
+        assert_eq!(entries_covering("localhost"), vec!["localhost"]);
+    }
+
+    #[tokio::test]
+    async fn test_subdomains_and_patterns() {
+        let domains = vec![
+            "foo.mailinator.com".to_string(),
+            "x.y.10minutemail.com".to_string(),
+            "abc123.throwaway.email".to_string(),
+            "throwaway.email".to_string(),
+            "mailinator.com.example.org".to_string(),
+        ];
+        let disposable = disposable_domains(&store(), &domains).await.unwrap();
+        assert_eq!(
+            disposable,
+            HashSet::from([
+                "foo.mailinator.com".to_string(),
+                "x.y.10minutemail.com".to_string(),
+                "abc123.throwaway.email".to_string(),
+            ])
+        );
+    }
 }
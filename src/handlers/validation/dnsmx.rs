@@ -1,14 +1,54 @@
+use crate::outbound_proxies::{self, ProxyPool};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock, RwLock};
 use std::time::Duration;
 use trust_dns_resolver::{
-    Resolver,
-    config::{ResolverConfig, ResolverOpts},
-    error::ResolveError,
-    proto::rr::RecordType,
+    Resolver, TokioAsyncResolver,
+    config::{NameServerConfig, Protocol, ResolverConfig, ResolverOpts},
+    error::{ResolveError, ResolveErrorKind},
+    proto::op::{Message, MessageType, OpCode, Query, ResponseCode},
+    proto::rr::{Name, RecordType},
+    proto::serialize::binary::BinDecodable,
 };
 
+/// A domain in both its Unicode and ASCII (punycode) forms
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct IdnDomain {
+    /// The domain as displayed, e.g. `例子.中国`
+    pub unicode: String,
+    /// The form looked up in DNS, e.g. `xn--fsqu00a.xn--fiqs8s`
+    pub ascii: String,
+}
+
+/// Converts a domain to both IDNA forms
+///
+/// Applies UTS #46 processing with IDNA 2008 (non-transitional) mapping, so `ß` is kept
+/// rather than mapped to `ss`; ASCII domains are only lowercased. Returns `None` for
+/// domains that aren't valid host names, including labels over 63 octets.
+///
+/// # Examples
+/// ```
+/// use email_sanitizer::handlers::validation::dnsmx::idn_domain;
+///
+/// let domain = idn_domain("例子.中国").unwrap();
+/// assert_eq!(domain.ascii, "xn--fsqu00a.xn--fiqs8s");
+/// assert_eq!(domain.unicode, "例子.中国");
+/// ```
+pub fn idn_domain(domain: &str) -> Option<IdnDomain> {
+    let ascii = idna::domain_to_ascii_strict(domain).ok()?;
+    let (unicode, result) = idna::domain_to_unicode(&ascii);
+    result.ok()?;
+    Some(IdnDomain { unicode, ascii })
+}
+
 /// Validates an email address domain by checking DNS records.
 ///
 /// This function performs DNS lookups to verify the domain part of an email address:
+/// 0. Converts internationalized domains to punycode (see [`idn_domain`])
 /// 1. Checks for MX (Mail Exchange) records first
 /// 2. Falls back to A/AAAA records if MX records are not found
 ///
@@ -30,31 +70,593 @@ use trust_dns_resolver::{
 /// assert!(!invalid);
 /// ```
 pub fn validate_email_dns(email: &str) -> bool {
-    let domain = match email.rsplit_once('@') {
-        Some((_, domain)) => domain,
-        None => return false,
-    };
+    resolve_email_dns(email).is_valid()
+}
+
+/// Checks the DNS records of an already converted ASCII domain (see [`idn_domain`]);
+/// lets batch validation resolve each domain once for many addresses
+pub fn validate_domain_dns(ascii_domain: &str) -> bool {
+    resolve_domain_dns(ascii_domain).is_valid()
+}
+
+/// Async counterpart of [`validate_email_dns`] that resolves on the Tokio runtime
+/// instead of a blocking thread; same lookup order and timeouts.
+pub async fn validate_email_dns_async(email: &str) -> bool {
+    resolve_email_dns_async(email).await.is_valid()
+}
+
+/// What resolving a domain's mail records established
+///
+/// Only `Valid` and `NotFound` say something about the domain; `Unavailable` means
+/// the resolver couldn't answer and the lookup should be retried, not cached for long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DnsOutcome {
+    /// MX records, or A/AAAA records to fall back to
+    Valid,
+    /// NXDOMAIN, or a domain without MX, A or AAAA records
+    NotFound,
+    /// SERVFAIL, a timeout or no reachable resolver
+    Unavailable,
+}
+
+impl DnsOutcome {
+    pub fn is_valid(self) -> bool {
+        self == DnsOutcome::Valid
+    }
+
+    /// Value stored in the DNS cache; `valid` and `invalid` predate this enum
+    pub fn cache_value(self) -> &'static str {
+        match self {
+            DnsOutcome::Valid => "valid",
+            DnsOutcome::NotFound => "invalid",
+            DnsOutcome::Unavailable => "unavailable",
+        }
+    }
+
+    pub fn from_cache_value(value: &str) -> Option<Self> {
+        match value {
+            "valid" => Some(DnsOutcome::Valid),
+            "invalid" => Some(DnsOutcome::NotFound),
+            "unavailable" => Some(DnsOutcome::Unavailable),
+            _ => None,
+        }
+    }
+
+    /// Outcome of one record lookup: whether it found records, or why it failed
+    fn of_lookup(found: Result<bool, ResolveError>) -> Self {
+        match found {
+            Ok(true) => DnsOutcome::Valid,
+            Ok(false) => DnsOutcome::NotFound,
+            Err(e) => match e.kind() {
+                // NXDOMAIN, or NOERROR without records of the type asked for
+                ResolveErrorKind::NoRecordsFound {
+                    response_code: ResponseCode::NXDomain | ResponseCode::NoError,
+                    ..
+                } => DnsOutcome::NotFound,
+                _ => DnsOutcome::Unavailable,
+            },
+        }
+    }
+
+    /// Valid if any lookup found records, else unavailable if any failed to resolve
+    fn combine(outcomes: [DnsOutcome; 3]) -> Self {
+        if outcomes.contains(&DnsOutcome::Valid) {
+            DnsOutcome::Valid
+        } else if outcomes.contains(&DnsOutcome::Unavailable) {
+            DnsOutcome::Unavailable
+        } else {
+            DnsOutcome::NotFound
+        }
+    }
+}
+
+/// How lookups reach the resolver's servers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DnsTransport {
+    /// Plain DNS over UDP port 53, with TCP for truncated answers
+    #[default]
+    Classic,
+    /// DNS-over-HTTPS (RFC 8484)
+    Https,
+    /// DNS-over-TLS (RFC 7858) on port 853
+    Tls,
+}
+
+impl DnsTransport {
+    /// Parses a `DNS_RESOLVER` value: `classic`, `doh` or `dot`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "classic" => Some(DnsTransport::Classic),
+            "doh" => Some(DnsTransport::Https),
+            "dot" => Some(DnsTransport::Tls),
+            _ => None,
+        }
+    }
+}
+
+/// Public resolver whose servers are queried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DnsProvider {
+    #[default]
+    Google,
+    Cloudflare,
+    Quad9,
+}
+
+impl DnsProvider {
+    /// Parses a `DNS_RESOLVER_PROVIDER` value: `google`, `cloudflare` or `quad9`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "google" => Some(DnsProvider::Google),
+            "cloudflare" => Some(DnsProvider::Cloudflare),
+            "quad9" => Some(DnsProvider::Quad9),
+            _ => None,
+        }
+    }
+
+    fn config(self, transport: DnsTransport) -> ResolverConfig {
+        match (self, transport) {
+            (DnsProvider::Google, DnsTransport::Classic) => ResolverConfig::google(),
+            (DnsProvider::Google, DnsTransport::Https) => ResolverConfig::google_https(),
+            (DnsProvider::Google, DnsTransport::Tls) => ResolverConfig::google_tls(),
+            (DnsProvider::Cloudflare, DnsTransport::Classic) => ResolverConfig::cloudflare(),
+            (DnsProvider::Cloudflare, DnsTransport::Https) => ResolverConfig::cloudflare_https(),
+            (DnsProvider::Cloudflare, DnsTransport::Tls) => ResolverConfig::cloudflare_tls(),
+            (DnsProvider::Quad9, DnsTransport::Classic) => ResolverConfig::quad9(),
+            (DnsProvider::Quad9, DnsTransport::Https) => ResolverConfig::quad9_https(),
+            (DnsProvider::Quad9, DnsTransport::Tls) => ResolverConfig::quad9_tls(),
+        }
+    }
+
+    /// RFC 8484 endpoint, queried directly by lookups sent through outbound proxies
+    fn doh_endpoint(self) -> &'static str {
+        match self {
+            DnsProvider::Google => "https://dns.google/dns-query",
+            DnsProvider::Cloudflare => "https://cloudflare-dns.com/dns-query",
+            DnsProvider::Quad9 => "https://dns.quad9.net/dns-query",
+        }
+    }
+}
+
+/// Nameservers by the ASCII domain whose lookups, subdomains included, they answer
+pub type DomainOverrides = BTreeMap<String, Vec<SocketAddr>>;
+
+/// Parses nameservers separated by commas or spaces, each an IP address with an
+/// optional port (53 when left out), e.g. `10.0.0.2, [fd00::53]:5353`
+pub fn parse_nameservers(value: &str) -> Result<Vec<SocketAddr>, String> {
+    let nameservers = value
+        .split([',', ' '])
+        .filter(|server| !server.is_empty())
+        .map(|server| {
+            server
+                .parse::<SocketAddr>()
+                .or_else(|_| {
+                    let ip = server.trim_start_matches('[').trim_end_matches(']');
+                    ip.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 53))
+                })
+                .map_err(|_| format!("\"{}\" is not an IP address with an optional port", server))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if nameservers.is_empty() {
+        return Err("No nameservers given".to_string());
+    }
+    Ok(nameservers)
+}
+
+/// The key a domain is overridden under: its lowercased ASCII form without a trailing
+/// dot; `None` for names that aren't valid host names
+pub fn override_domain(domain: &str) -> Option<String> {
+    idn_domain(domain.trim().trim_end_matches('.')).map(|domain| domain.ascii)
+}
+
+/// Parses `DNS_DOMAIN_OVERRIDES`: `domain=nameservers` entries separated by `;`, e.g.
+/// `corp.internal=10.1.0.53,10.1.0.54;lab.example=10.2.0.53:5353`
+pub fn parse_domain_overrides(value: &str) -> Result<DomainOverrides, String> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (domain, nameservers) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("\"{}\" is not a domain=nameservers entry", entry))?;
+            let domain = override_domain(domain)
+                .ok_or_else(|| format!("\"{}\" is not a valid domain", domain.trim()))?;
+            Ok((domain, parse_nameservers(nameservers)?))
+        })
+        .collect()
+}
+
+/// Which resolver validation lookups use
+///
+/// Configured from the environment:
+/// - `DNS_RESOLVER`: `classic` (default), `doh` or `dot`; encrypted transports help
+///   where outbound port 53 is blocked or intercepted
+/// - `DNS_RESOLVER_PROVIDER`: `google` (default), `cloudflare` or `quad9`
+/// - `DNS_RESOLVER_FALLBACK`: whether a lookup the encrypted transport can't answer is
+///   retried over classic DNS with the same provider (defaults to true)
+/// - `DNS_NAMESERVERS`: nameservers queried over classic DNS instead of the provider's,
+///   see [`parse_nameservers`]; only with `DNS_RESOLVER=classic`
+/// - `DNS_DOMAIN_OVERRIDES`: nameservers for lookups under given domains, e.g. an
+///   internal resolver for `corp.internal`; see [`parse_domain_overrides`]
+///
+/// Domains can also be overridden at runtime through the admin API (see
+/// `DnsOverrides`); at the same domain, `DNS_DOMAIN_OVERRIDES` wins.
+///
+/// With `DNS_RESOLVER=doh`, lookups at the provider leave through the process's
+/// outbound proxy pool when one is configured (see
+/// [`ProxySettings`](crate::outbound_proxies::ProxySettings)). The classic fallback
+/// still goes out directly, so `DNS_RESOLVER_FALLBACK=false` keeps every lookup on
+/// the proxies. Overridden domains and custom nameservers are queried directly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolverSettings {
+    pub transport: DnsTransport,
+    pub provider: DnsProvider,
+    pub fallback: bool,
+    pub nameservers: Vec<SocketAddr>,
+    pub domain_overrides: DomainOverrides,
+}
+
+impl Default for ResolverSettings {
+    fn default() -> Self {
+        Self {
+            transport: DnsTransport::default(),
+            provider: DnsProvider::default(),
+            fallback: true,
+            nameservers: Vec::new(),
+            domain_overrides: DomainOverrides::new(),
+        }
+    }
+}
+
+impl ResolverSettings {
+    pub fn from_env() -> Result<Self, String> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let mut settings = Self::default();
+        if let Some(transport) = var("DNS_RESOLVER") {
+            settings.transport = DnsTransport::parse(&transport)
+                .ok_or_else(|| format!("DNS_RESOLVER does not support \"{}\"", transport))?;
+        }
+        if let Some(provider) = var("DNS_RESOLVER_PROVIDER") {
+            settings.provider = DnsProvider::parse(&provider).ok_or_else(|| {
+                format!("DNS_RESOLVER_PROVIDER does not support \"{}\"", provider)
+            })?;
+        }
+        if let Some(fallback) = var("DNS_RESOLVER_FALLBACK") {
+            settings.fallback =
+                !matches!(fallback.to_ascii_lowercase().as_str(), "false" | "0" | "no");
+        }
+        if let Some(nameservers) = var("DNS_NAMESERVERS") {
+            if settings.transport != DnsTransport::Classic {
+                return Err("DNS_NAMESERVERS needs DNS_RESOLVER=classic".to_string());
+            }
+            settings.nameservers =
+                parse_nameservers(&nameservers).map_err(|e| format!("DNS_NAMESERVERS: {}", e))?;
+        }
+        if let Some(overrides) = var("DNS_DOMAIN_OVERRIDES") {
+            settings.domain_overrides = parse_domain_overrides(&overrides)
+                .map_err(|e| format!("DNS_DOMAIN_OVERRIDES: {}", e))?;
+        }
+        Ok(settings)
+    }
+
+    /// Nameservers of the closest overridden domain of `ascii_domain`, itself included
+    fn override_for(&self, ascii_domain: &str) -> Option<Vec<SocketAddr>> {
+        let stored = stored_overrides().read().unwrap().clone();
+        let domain = ascii_domain.trim_end_matches('.').to_ascii_lowercase();
+        let mut suffix = domain.as_str();
+        loop {
+            if let Some(nameservers) = self
+                .domain_overrides
+                .get(suffix)
+                .or_else(|| stored.get(suffix))
+            {
+                return Some(nameservers.clone());
+            }
+            suffix = suffix.split_once('.')?.1;
+        }
+    }
+
+    /// Whether lookups of `ascii_domain` go to the provider over DoH, and so through
+    /// the outbound proxy pool when there is one
+    fn proxied(&self, ascii_domain: &str) -> bool {
+        self.transport == DnsTransport::Https
+            && self.nameservers.is_empty()
+            && self.override_for(ascii_domain).is_none()
+    }
+
+    /// Resolver configurations to try for `ascii_domain` in order, until one can answer
+    fn configs_for(&self, ascii_domain: &str) -> Vec<ResolverConfig> {
+        if let Some(nameservers) = self.override_for(ascii_domain) {
+            return vec![classic_config(&nameservers)];
+        }
+        if !self.nameservers.is_empty() {
+            return vec![classic_config(&self.nameservers)];
+        }
+        let primary = self.provider.config(self.transport);
+        if self.transport == DnsTransport::Classic || !self.fallback {
+            return vec![primary];
+        }
+        vec![primary, self.provider.config(DnsTransport::Classic)]
+    }
+}
+
+/// Classic DNS, over UDP with TCP for truncated answers, to `nameservers`
+fn classic_config(nameservers: &[SocketAddr]) -> ResolverConfig {
+    let nameservers: Vec<NameServerConfig> = nameservers
+        .iter()
+        .flat_map(|addr| {
+            [Protocol::Udp, Protocol::Tcp].map(|protocol| NameServerConfig::new(*addr, protocol))
+        })
+        .collect();
+    ResolverConfig::from_parts(None, vec![], nameservers)
+}
+
+fn stored_overrides() -> &'static RwLock<Arc<DomainOverrides>> {
+    static OVERRIDES: OnceLock<RwLock<Arc<DomainOverrides>>> = OnceLock::new();
+    OVERRIDES.get_or_init(RwLock::default)
+}
+
+/// Replaces the domain overrides set through the admin API
+pub fn replace_stored_overrides(overrides: DomainOverrides) {
+    *stored_overrides().write().unwrap() = Arc::new(overrides);
+}
+
+static SETTINGS: OnceLock<ResolverSettings> = OnceLock::new();
+
+/// Installs the settings read at startup; later calls are ignored
+pub fn init(settings: ResolverSettings) {
+    let _ = SETTINGS.set(settings);
+}
+
+/// The installed settings, or the defaults when none were installed
+pub fn settings() -> &'static ResolverSettings {
+    SETTINGS.get_or_init(ResolverSettings::default)
+}
+
+fn email_ascii_domain(email: &str) -> Option<String> {
+    email
+        .rsplit_once('@')
+        .and_then(|(_, domain)| idn_domain(domain))
+        .map(|domain| domain.ascii)
+}
+
+/// Like [`validate_email_dns`], telling a missing domain from a failed lookup;
+/// addresses without a valid domain are `NotFound`
+pub fn resolve_email_dns(email: &str) -> DnsOutcome {
+    match email_ascii_domain(email) {
+        Some(domain) => resolve_domain_dns(&domain),
+        None => DnsOutcome::NotFound,
+    }
+}
+
+/// Like [`validate_domain_dns`], telling a missing domain from a failed lookup
+pub fn resolve_domain_dns(ascii_domain: &str) -> DnsOutcome {
+    let mut outcome = DnsOutcome::Unavailable;
+    let mut configs = settings().configs_for(ascii_domain);
+    if let Some(pool) = proxy_pool_for(ascii_domain) {
+        outcome = block_on(check_mx_or_a_records_proxied(pool, ascii_domain));
+        if outcome != DnsOutcome::Unavailable {
+            return outcome;
+        }
+        // The pool replaces the direct DoH lookup; only the classic fallback is left
+        configs.remove(0);
+    }
+    for config in configs {
+        outcome = match create_resolver(config) {
+            Some(resolver) => check_mx_or_a_records(&resolver, ascii_domain),
+            None => DnsOutcome::Unavailable,
+        };
+        if outcome != DnsOutcome::Unavailable {
+            break;
+        }
+    }
+    outcome
+}
 
-    let resolver = match create_resolver() {
-        Some(r) => r,
-        None => return false,
+/// Async counterpart of [`resolve_email_dns`]
+pub async fn resolve_email_dns_async(email: &str) -> DnsOutcome {
+    let Some(domain) = email_ascii_domain(email) else {
+        return DnsOutcome::NotFound;
     };
 
-    check_mx_or_a_records(&resolver, domain).unwrap_or(false)
+    let mut outcome = DnsOutcome::Unavailable;
+    let mut configs = settings().configs_for(&domain);
+    if let Some(pool) = proxy_pool_for(&domain) {
+        outcome = check_mx_or_a_records_proxied(pool, &domain).await;
+        if outcome != DnsOutcome::Unavailable {
+            return outcome;
+        }
+        configs.remove(0);
+    }
+    for config in configs {
+        let resolver = TokioAsyncResolver::tokio(config, resolver_opts());
+        outcome = check_mx_or_a_records_async(&resolver, &domain).await;
+        if outcome != DnsOutcome::Unavailable {
+            break;
+        }
+    }
+    outcome
 }
 
-/// Creates a DNS resolver with custom configuration
+/// Async counterpart of [`check_mx_or_a_records`]
+async fn check_mx_or_a_records_async(resolver: &TokioAsyncResolver, domain: &str) -> DnsOutcome {
+    let mx = DnsOutcome::of_lookup(
+        resolver
+            .mx_lookup(domain)
+            .await
+            .map(|records| records.iter().next().is_some()),
+    );
+    if mx.is_valid() {
+        return mx;
+    }
+
+    let (a, aaaa) = tokio::join!(
+        resolver.lookup(domain, RecordType::A),
+        resolver.lookup(domain, RecordType::AAAA),
+    );
+    DnsOutcome::combine([
+        mx,
+        DnsOutcome::of_lookup(a.map(|records| records.iter().next().is_some())),
+        DnsOutcome::of_lookup(aaaa.map(|records| records.iter().next().is_some())),
+    ])
+}
+
+/// Resolves `domain` with the settings used for validation; a health probe that the
+/// resolver, or else its classic fallback, can reach its upstream servers
+pub async fn resolver_self_test(domain: &str) -> bool {
+    let mut configs = settings().configs_for(domain);
+    if let Some(pool) = proxy_pool_for(domain) {
+        if check_mx_or_a_records_proxied(pool, domain).await.is_valid() {
+            return true;
+        }
+        configs.remove(0);
+    }
+    for config in configs {
+        let resolver = TokioAsyncResolver::tokio(config, resolver_opts());
+        if resolver
+            .lookup_ip(domain)
+            .await
+            .is_ok_and(|ips| ips.iter().next().is_some())
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// The outbound proxy pool lookups of `ascii_domain` leave through, if any
+fn proxy_pool_for(ascii_domain: &str) -> Option<&'static ProxyPool> {
+    outbound_proxies::pool().filter(|_| settings().proxied(ascii_domain))
+}
+
+/// Runs a proxied lookup from the blocking resolver paths, which run on a blocking
+/// thread of the Tokio runtime when there is one
+fn block_on<F: Future>(future: F) -> F::Output {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => handle.block_on(future),
+        Err(_) => tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to start a runtime for a proxied DNS lookup")
+            .block_on(future),
+    }
+}
+
+/// RFC 8484 wire-format query for `record_type` records of `domain`
+fn doh_query(domain: &str, record_type: RecordType) -> Result<Vec<u8>, String> {
+    let name = Name::from_ascii(domain).map_err(|e| e.to_string())?;
+    let mut message = Message::new();
+    // ID 0 keeps identical queries cacheable by HTTP caches, as RFC 8484 recommends
+    message
+        .set_id(0)
+        .set_message_type(MessageType::Query)
+        .set_op_code(OpCode::Query)
+        .set_recursion_desired(true)
+        .add_query(Query::query(name, record_type));
+    message.to_vec().map_err(|e| e.to_string())
+}
+
+/// Outcome of a DoH answer to a query for `record_type` records
+fn doh_outcome(answer: &Message, record_type: RecordType) -> DnsOutcome {
+    match answer.response_code() {
+        ResponseCode::NoError
+            if answer
+                .answers()
+                .iter()
+                .any(|record| record.record_type() == record_type) =>
+        {
+            DnsOutcome::Valid
+        }
+        ResponseCode::NoError | ResponseCode::NXDomain => DnsOutcome::NotFound,
+        _ => DnsOutcome::Unavailable,
+    }
+}
+
+/// One DoH lookup at the provider through a proxy's client; errors mean the answer
+/// didn't come back through the proxy
+async fn doh_lookup(
+    client: &reqwest::Client,
+    domain: &str,
+    record_type: RecordType,
+) -> Result<DnsOutcome, String> {
+    let body = client
+        .post(settings().provider.doh_endpoint())
+        .header(reqwest::header::CONTENT_TYPE, "application/dns-message")
+        .header(reqwest::header::ACCEPT, "application/dns-message")
+        .body(doh_query(domain, record_type)?)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?
+        .bytes()
+        .await
+        .map_err(|e| e.to_string())?;
+    let answer = Message::from_bytes(&body).map_err(|e| e.to_string())?;
+    Ok(doh_outcome(&answer, record_type))
+}
+
+/// [`check_mx_or_a_records`] over DoH through one proxy
+async fn proxied_mx_or_a(client: &reqwest::Client, domain: &str) -> Result<DnsOutcome, String> {
+    let mx = doh_lookup(client, domain, RecordType::MX).await?;
+    if mx.is_valid() {
+        return Ok(mx);
+    }
+    let (a, aaaa) = tokio::join!(
+        doh_lookup(client, domain, RecordType::A),
+        doh_lookup(client, domain, RecordType::AAAA),
+    );
+    Ok(DnsOutcome::combine([mx, a?, aaaa?]))
+}
+
+/// [`check_mx_or_a_records`] through the pool's proxies in turn; a proxy the lookup
+/// fails through leaves the rotation until its next health check passes
 ///
-/// Configures resolver with:
-/// - 2 second timeout per request
-/// - 2 retry attempts
-/// - Default system resolver configuration
-fn create_resolver() -> Option<Resolver> {
+/// Boxed so the HTTP client's future types don't reach the callers' `Send` checks.
+fn check_mx_or_a_records_proxied<'a>(
+    pool: &'a ProxyPool,
+    domain: &'a str,
+) -> Pin<Box<dyn Future<Output = DnsOutcome> + Send + 'a>> {
+    Box::pin(async move {
+        for (index, client) in pool.rotation() {
+            match proxied_mx_or_a(client, domain).await {
+                Ok(outcome) => return outcome,
+                Err(e) => {
+                    eprintln!("DNS lookup through pool {} failed: {}", pool.name(), e);
+                    pool.set_healthy(index, false);
+                }
+            }
+        }
+        DnsOutcome::Unavailable
+    })
+}
+
+/// Health check of one outbound proxy: whether a DoH lookup of `domain` at the
+/// provider gets an answer through it
+pub async fn proxy_self_test(client: &reqwest::Client, domain: &str) -> bool {
+    doh_lookup(client, domain, RecordType::A).await.is_ok()
+}
+
+fn resolver_opts() -> ResolverOpts {
     let mut opts = ResolverOpts::default();
     opts.timeout = Duration::from_secs(2);
     opts.attempts = 2;
+    opts
+}
 
-    Resolver::new(ResolverConfig::default(), opts).ok()
+/// Creates a DNS resolver with custom configuration
+///
+/// Configures resolver with:
+/// - 2 second timeout per request
+/// - 2 retry attempts
+/// - The servers and transport of `config` (see [`ResolverSettings`])
+fn create_resolver(config: ResolverConfig) -> Option<Resolver> {
+    Resolver::new(config, resolver_opts()).ok()
 }
 
 /// Checks DNS records for a domain following RFC 5321 requirements
@@ -67,27 +669,261 @@ fn create_resolver() -> Option<Resolver> {
 /// * `domain` - Domain name to check (without @ symbol)
 ///
 /// # Returns
-/// `Result<bool, ResolveError>` where:
-/// - `Ok(true)` if valid records found
-/// - `Ok(false)` if no records found
-/// - `Err` contains DNS resolution error
-fn check_mx_or_a_records(resolver: &Resolver, domain: &str) -> Result<bool, ResolveError> {
+/// - `Valid` if MX records, or else A or AAAA records, were found
+/// - `NotFound` if the domain doesn't exist or has none of them
+/// - `Unavailable` if no lookup found records and one of them failed to resolve
+fn check_mx_or_a_records(resolver: &Resolver, domain: &str) -> DnsOutcome {
     // Check MX records first
-    let mx_records = resolver.mx_lookup(domain);
-    if let Ok(records) = mx_records {
-        return Ok(records.iter().next().is_some());
+    let mx = DnsOutcome::of_lookup(
+        resolver
+            .mx_lookup(domain)
+            .map(|records| records.iter().next().is_some()),
+    );
+    if mx.is_valid() {
+        return mx;
     }
 
     // Fallback to A/AAAA records if MX lookup failed
-    let a_records = resolver.lookup(domain, RecordType::A)?;
-    let aaaa_records = resolver.lookup(domain, RecordType::AAAA)?;
-
-    Ok(!a_records.is_empty() || !aaaa_records.is_empty())
+    let a = resolver.lookup(domain, RecordType::A);
+    let aaaa = resolver.lookup(domain, RecordType::AAAA);
+    DnsOutcome::combine([
+        mx,
+        DnsOutcome::of_lookup(a.map(|records| records.iter().next().is_some())),
+        DnsOutcome::of_lookup(aaaa.map(|records| records.iter().next().is_some())),
+    ])
 }
 
 #[cfg(test)]
 mod tests {
-    use super::validate_email_dns;
+    use super::{
+        DnsOutcome, DnsProvider, DnsTransport, DomainOverrides, ResolverSettings, doh_outcome,
+        doh_query, idn_domain, parse_domain_overrides, parse_nameservers, replace_stored_overrides,
+        validate_email_dns, validate_email_dns_async,
+    };
+    use std::net::SocketAddr;
+    use trust_dns_resolver::config::Protocol;
+    use trust_dns_resolver::error::{ResolveError, ResolveErrorKind};
+    use trust_dns_resolver::proto::op::{Message, Query, ResponseCode};
+    use trust_dns_resolver::proto::rr::{Name, RData, Record, RecordType, rdata::A};
+    use trust_dns_resolver::proto::serialize::binary::BinDecodable;
+
+    fn no_records(response_code: ResponseCode) -> ResolveError {
+        ResolveErrorKind::NoRecordsFound {
+            query: Box::new(Query::default()),
+            soa: None,
+            negative_ttl: None,
+            response_code,
+            trusted: true,
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_lookup_outcomes() {
+        assert_eq!(DnsOutcome::of_lookup(Ok(true)), DnsOutcome::Valid);
+        assert_eq!(DnsOutcome::of_lookup(Ok(false)), DnsOutcome::NotFound);
+        assert_eq!(
+            DnsOutcome::of_lookup(Err(no_records(ResponseCode::NXDomain))),
+            DnsOutcome::NotFound
+        );
+        assert_eq!(
+            DnsOutcome::of_lookup(Err(no_records(ResponseCode::NoError))),
+            DnsOutcome::NotFound
+        );
+        assert_eq!(
+            DnsOutcome::of_lookup(Err(no_records(ResponseCode::ServFail))),
+            DnsOutcome::Unavailable
+        );
+        assert_eq!(
+            DnsOutcome::of_lookup(Err(ResolveErrorKind::Timeout.into())),
+            DnsOutcome::Unavailable
+        );
+    }
+
+    #[test]
+    fn test_combined_outcomes() {
+        use DnsOutcome::*;
+        assert_eq!(DnsOutcome::combine([Unavailable, Valid, NotFound]), Valid);
+        assert_eq!(
+            DnsOutcome::combine([NotFound, Unavailable, NotFound]),
+            Unavailable
+        );
+        assert_eq!(
+            DnsOutcome::combine([NotFound, NotFound, NotFound]),
+            NotFound
+        );
+    }
+
+    #[test]
+    fn test_cache_values_round_trip() {
+        for outcome in [
+            DnsOutcome::Valid,
+            DnsOutcome::NotFound,
+            DnsOutcome::Unavailable,
+        ] {
+            assert_eq!(
+                DnsOutcome::from_cache_value(outcome.cache_value()),
+                Some(outcome)
+            );
+        }
+        assert_eq!(DnsOutcome::from_cache_value("bogus"), None);
+    }
+
+    #[test]
+    fn test_resolver_settings() {
+        assert_eq!(DnsTransport::parse(" DoH"), Some(DnsTransport::Https));
+        assert_eq!(DnsTransport::parse("dot"), Some(DnsTransport::Tls));
+        assert_eq!(DnsTransport::parse("doq"), None);
+        assert_eq!(DnsProvider::parse("Quad9"), Some(DnsProvider::Quad9));
+        assert_eq!(DnsProvider::parse("opendns"), None);
+
+        let protocols = |settings: &ResolverSettings| -> Vec<Protocol> {
+            settings
+                .configs_for("example.com")
+                .iter()
+                .map(|config| config.name_servers()[0].protocol)
+                .collect()
+        };
+        assert_eq!(protocols(&ResolverSettings::default()), vec![Protocol::Udp]);
+        let doh = ResolverSettings {
+            transport: DnsTransport::Https,
+            provider: DnsProvider::Cloudflare,
+            ..ResolverSettings::default()
+        };
+        assert_eq!(protocols(&doh), vec![Protocol::Https, Protocol::Udp]);
+        assert_eq!(
+            protocols(&ResolverSettings {
+                transport: DnsTransport::Tls,
+                fallback: false,
+                ..doh
+            }),
+            vec![Protocol::Tls]
+        );
+    }
+
+    #[test]
+    fn test_doh_wire_format() {
+        let query =
+            Message::from_bytes(&doh_query("example.com", RecordType::MX).unwrap()).unwrap();
+        assert_eq!(query.id(), 0);
+        assert!(query.recursion_desired());
+        assert_eq!(query.queries()[0].query_type(), RecordType::MX);
+        assert!(doh_query("exa mple..com", RecordType::A).is_err());
+
+        let mut answer = Message::new();
+        assert_eq!(doh_outcome(&answer, RecordType::A), DnsOutcome::NotFound);
+        answer.add_answer(Record::from_rdata(
+            Name::from_ascii("example.com.").unwrap(),
+            300,
+            RData::A(A::new(192, 0, 2, 1)),
+        ));
+        assert_eq!(doh_outcome(&answer, RecordType::A), DnsOutcome::Valid);
+        assert_eq!(doh_outcome(&answer, RecordType::AAAA), DnsOutcome::NotFound);
+        answer.set_response_code(ResponseCode::ServFail);
+        assert_eq!(doh_outcome(&answer, RecordType::A), DnsOutcome::Unavailable);
+
+        // Only lookups at the DoH provider go through the proxies
+        let doh = ResolverSettings {
+            transport: DnsTransport::Https,
+            ..ResolverSettings::default()
+        };
+        assert!(doh.proxied("example.com"));
+        assert!(!ResolverSettings::default().proxied("example.com"));
+        assert!(
+            !ResolverSettings {
+                nameservers: parse_nameservers("192.0.2.53").unwrap(),
+                ..doh
+            }
+            .proxied("example.com")
+        );
+    }
+
+    #[test]
+    fn test_parse_nameservers() {
+        assert_eq!(
+            parse_nameservers("10.0.0.2, 10.0.0.3:5353 [fd00::53]").unwrap(),
+            vec![
+                "10.0.0.2:53".parse::<SocketAddr>().unwrap(),
+                "10.0.0.3:5353".parse().unwrap(),
+                "[fd00::53]:53".parse().unwrap(),
+            ]
+        );
+        assert!(parse_nameservers("ns1.corp.internal").is_err());
+        assert!(parse_nameservers(" , ").is_err());
+
+        let overrides =
+            parse_domain_overrides("Corp.Internal.=10.1.0.53,10.1.0.54; lab.example=10.2.0.53")
+                .unwrap();
+        assert_eq!(
+            overrides.keys().collect::<Vec<_>>(),
+            vec!["corp.internal", "lab.example"]
+        );
+        assert_eq!(overrides["corp.internal"].len(), 2);
+        assert!(parse_domain_overrides("corp.internal").is_err());
+        assert!(parse_domain_overrides("=10.1.0.53").is_err());
+    }
+
+    #[test]
+    fn test_domain_overrides_pick_nameservers() {
+        let internal = parse_nameservers("10.1.0.53").unwrap();
+        let settings = ResolverSettings {
+            transport: DnsTransport::Https,
+            domain_overrides: DomainOverrides::from([("corp.internal".to_string(), internal)]),
+            ..ResolverSettings::default()
+        };
+        replace_stored_overrides(DomainOverrides::from([
+            (
+                "corp.internal".to_string(),
+                parse_nameservers("10.9.9.9").unwrap(),
+            ),
+            (
+                "lab.corp.internal".to_string(),
+                parse_nameservers("10.2.0.53").unwrap(),
+            ),
+        ]));
+
+        let nameservers = |domain: &str| -> Vec<SocketAddr> {
+            let configs = settings.configs_for(domain);
+            assert_eq!(configs.len(), 1);
+            configs[0]
+                .name_servers()
+                .iter()
+                .filter(|server| server.protocol == Protocol::Udp)
+                .map(|server| server.socket_addr)
+                .collect()
+        };
+        // The environment wins at the same domain, the closest domain wins overall
+        assert_eq!(
+            nameservers("mail.CORP.internal."),
+            parse_nameservers("10.1.0.53").unwrap()
+        );
+        assert_eq!(
+            nameservers("lab.corp.internal"),
+            parse_nameservers("10.2.0.53").unwrap()
+        );
+        assert_eq!(settings.configs_for("example.com").len(), 2);
+
+        let custom = ResolverSettings {
+            nameservers: parse_nameservers("192.0.2.53").unwrap(),
+            ..ResolverSettings::default()
+        };
+        assert_eq!(custom.configs_for("example.com")[0].name_servers().len(), 2);
+    }
+
+    #[test]
+    fn test_idn_domain_forms() {
+        let domain = idn_domain("Bücher.Example").unwrap();
+        assert_eq!(domain.ascii, "xn--bcher-kva.example");
+        assert_eq!(domain.unicode, "bücher.example");
+
+        let ascii = idn_domain("xn--fsqu00a.xn--fiqs8s").unwrap();
+        assert_eq!(ascii.unicode, "例子.中国");
+        assert_eq!(idn_domain("faß.de").unwrap().ascii, "xn--fa-hia.de");
+
+        assert!(idn_domain("").is_none());
+        assert!(idn_domain("-invalid.com").is_none());
+        assert!(idn_domain(&"a".repeat(64)).is_none());
+    }
 
     #[test]
     fn test_valid_email_with_mx() {
@@ -111,6 +947,11 @@ mod tests {
         assert!(!validate_email_dns("invalid-email"));
     }
 
+    #[tokio::test]
+    async fn test_async_email_without_at_symbol() {
+        assert!(!validate_email_dns_async("invalid-email").await);
+    }
+
     #[test]
     fn test_localhost_fallback() {
         // localhost has A record but no MX
@@ -0,0 +1,165 @@
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::bson::{Document, doc};
+use mongodb::{Client, Collection};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::env;
+
+/// Number of hex characters of the address hash sent to the dataset
+///
+/// The dataset only ever sees this prefix (k-anonymity, as in HIBP range queries);
+/// the full hash is compared locally against the returned candidates.
+pub const HASH_PREFIX_LEN: usize = 5;
+
+/// Upper bound of the risk contribution from address age
+pub const MAX_AGE_RISK: u8 = 40;
+
+/// Age of an address according to the first-seen dataset
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EmailAge {
+    /// Whether the address appears in the dataset at all
+    pub seen: bool,
+    /// RFC 3339 timestamp of the first sighting
+    pub first_seen: Option<String>,
+    pub age_days: Option<i64>,
+    /// Contribution to the overall risk score, 0 (long-established) to [`MAX_AGE_RISK`] (never seen)
+    pub risk_score: u8,
+}
+
+/// Whether first-seen lookups are configured (`FIRST_SEEN_LOOKUP_ENABLED=true`)
+pub fn is_enabled() -> bool {
+    env::var("FIRST_SEEN_LOOKUP_ENABLED")
+        .map(|v| v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// SHA-256 of the trimmed, lowercased address, hex encoded
+pub fn email_hash(email: &str) -> String {
+    let digest = Sha256::digest(email.trim().to_lowercase().as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Maps the first-seen date to an age report and risk contribution
+pub fn age_from_first_seen(first_seen: Option<DateTime<Utc>>, now: DateTime<Utc>) -> EmailAge {
+    let Some(first_seen) = first_seen else {
+        return EmailAge {
+            seen: false,
+            first_seen: None,
+            age_days: None,
+            risk_score: MAX_AGE_RISK,
+        };
+    };
+
+    let age_days = (now - first_seen).num_days().max(0);
+    let risk_score = match age_days {
+        0..30 => 30,
+        30..365 => 15,
+        365..1095 => 5,
+        _ => 0,
+    };
+
+    EmailAge {
+        seen: true,
+        first_seen: Some(first_seen.to_rfc3339()),
+        age_days: Some(age_days),
+        risk_score,
+    }
+}
+
+/// Looks up how long an address has existed in the configured first-seen dataset.
+///
+/// Only a hash prefix leaves the process; the address itself is never sent or stored.
+/// The dataset is a MongoDB collection (`DB_FIRST_SEEN_COLLECTION`, default
+/// `first_seen_hashes`) of `{ hash: <sha256 hex>, first_seen: <date> }` documents.
+///
+/// # Returns
+/// * `Ok(None)` if lookups are disabled
+/// * `Ok(Some(age))` with `seen: false` if the address is not in the dataset
+/// * `Err` containing an error message if the dataset can't be queried
+pub async fn lookup_email_age(email: &str) -> Result<Option<EmailAge>, String> {
+    if !is_enabled() {
+        return Ok(None);
+    }
+
+    let hash = email_hash(email);
+    let prefix = &hash[..HASH_PREFIX_LEN];
+
+    let mongo_uri =
+        env::var("MONGODB_URI").map_err(|_| "MONGODB_URI environment variable not set")?;
+    let database_name = env::var("DB_NAME_PRODUCTION")
+        .map_err(|_| "DB_NAME_PRODUCTION environment variable not set")?;
+    let collection_name =
+        env::var("DB_FIRST_SEEN_COLLECTION").unwrap_or_else(|_| "first_seen_hashes".to_string());
+
+    let client = Client::with_uri_str(&mongo_uri)
+        .await
+        .map_err(|e| format!("Failed to connect to MongoDB: {}", e))?;
+    let collection: Collection<Document> =
+        client.database(&database_name).collection(&collection_name);
+
+    let mut candidates = collection
+        .find(doc! { "hash": { "$regex": format!("^{}", prefix) } })
+        .projection(doc! { "_id": 0, "hash": 1, "first_seen": 1 })
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?;
+
+    let mut first_seen = None;
+    while let Some(candidate) = candidates
+        .try_next()
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?
+    {
+        if candidate.get_str("hash").ok() == Some(hash.as_str()) {
+            first_seen = candidate
+                .get_datetime("first_seen")
+                .ok()
+                .and_then(|d| DateTime::from_timestamp_millis(d.timestamp_millis()));
+            break;
+        }
+    }
+
+    Ok(Some(age_from_first_seen(first_seen, Utc::now())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_email_hash_is_normalized() {
+        assert_eq!(
+            email_hash(" User@Example.com "),
+            email_hash("user@example.com")
+        );
+        assert_eq!(email_hash("user@example.com").len(), 64);
+    }
+
+    #[test]
+    fn test_never_seen_is_highest_risk() {
+        let age = age_from_first_seen(None, Utc::now());
+        assert!(!age.seen);
+        assert_eq!(age.risk_score, MAX_AGE_RISK);
+    }
+
+    #[test]
+    fn test_risk_decreases_with_age() {
+        let now = Utc::now();
+        let recent = age_from_first_seen(Some(now - Duration::days(3)), now);
+        let months = age_from_first_seen(Some(now - Duration::days(200)), now);
+        let years = age_from_first_seen(Some(now - Duration::days(5 * 365)), now);
+
+        assert_eq!(recent.age_days, Some(3));
+        assert!(recent.risk_score > months.risk_score);
+        assert!(months.risk_score > years.risk_score);
+        assert_eq!(years.risk_score, 0);
+    }
+
+    #[tokio::test]
+    async fn test_lookup_disabled_by_default() {
+        if !is_enabled() {
+            assert_eq!(lookup_email_age("user@example.com").await, Ok(None));
+        }
+    }
+}
@@ -23,6 +23,24 @@
 /// ```
 pub mod dnsmx;
 
+/// Checks an email domain's top-level domain against IANA's list of delegated TLDs.
+///
+/// A bundled copy of the list is used until a background task refreshes it from
+/// IANA (`TLD_LIST_REFRESH_HOURS`, default 24). Rejecting unknown TLDs such as
+/// `.fake` is cheaper than the DNS round trip that would fail anyway.
+///
+/// # Arguments
+/// * `domain` - The domain in ASCII (punycode) form
+///
+/// # Examples
+/// ```
+/// use email_sanitizer::handlers::validation::tld::has_known_tld;
+///
+/// assert!(has_known_tld("example.com"));
+/// assert!(!has_known_tld("example.fake"));
+/// ```
+pub mod tld;
+
 /// Validates an email address according to RFC 5322 and RFC 6531 specifications.
 ///
 /// This function performs syntax checking of both local-part and domain parts with:
@@ -47,27 +65,30 @@ pub mod dnsmx;
 /// `true` if the email address meets all syntax requirements, `false` otherwise
 pub mod syntax;
 
-/// Checks if an email address uses a disposable domain by querying a MongoDB collection.
+/// Checks if an email address uses a disposable domain, looking it up in a
+/// [`DomainListStore`](crate::stores::DomainListStore).
 ///
 /// # Arguments
+/// * `store` - The reference lists, in MongoDB in production
 /// * `email` - A string slice containing the email address to check
 ///
 /// # Returns
-/// * `Ok(true)` if the domain is found in the disposable email collection
-/// * `Ok(false)` if the domain is not found
+/// * `Ok(true)` if the domain, a parent domain, or a `*.` pattern covering it is on the
+///   disposable domain list
+/// * `Ok(false)` if none of them is found
 /// * `Err` containing an error message if any step fails
 ///
 /// # Errors
 /// Returns an error if:
 /// - The email is missing '@' symbol (invalid format)
-/// - Environment variables are not properly configured
-/// - MongoDB connection or query fails
+/// - The store can't be queried
 ///
 /// # Example
 /// ```
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-/// use email_sanitizer::handlers::validation::disposable::is_disposable_email; // Add the correct use statement
-/// let is_spam = is_disposable_email("example@mailinator.com").await?;
+/// use email_sanitizer::handlers::validation::disposable::is_disposable_email;
+/// use email_sanitizer::stores::MongoDomainLists;
+/// let is_spam = is_disposable_email(&MongoDomainLists::from_env(), "example@mailinator.com").await?;
 /// assert_eq!(is_spam, true);
 /// # Ok(())
 /// # }
@@ -80,6 +101,7 @@ pub mod disposable;
 /// rather than individual users (e.g., admin@, support@, info@).
 ///
 /// # Arguments
+/// * `store` - The reference lists holding the role-based local parts
 /// * `email` - A string slice containing the email address to check
 ///
 /// # Returns
@@ -89,15 +111,100 @@ pub mod disposable;
 /// ```no_run
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 /// use email_sanitizer::handlers::validation::role_based::is_role_based_email;
+/// use email_sanitizer::stores::MongoDomainLists;
 ///
-/// assert!(is_role_based_email("admin@example.com").await?);
-/// assert!(is_role_based_email("support@company.org").await?);
-/// assert!(!is_role_based_email("john.doe@example.com").await?);
+/// let lists = MongoDomainLists::from_env();
+/// assert!(is_role_based_email(&lists, "admin@example.com").await?);
+/// assert!(is_role_based_email(&lists, "support@company.org").await?);
+/// assert!(!is_role_based_email(&lists, "john.doe@example.com").await?);
 /// # Ok(())
 /// # }
 /// ```
 pub mod role_based;
 
+/// Reports how long an address has existed according to a "first seen" dataset.
+///
+/// Lookups are opt-in (`FIRST_SEEN_LOOKUP_ENABLED=true`) and hash-only: just a
+/// prefix of the address's SHA-256 is sent to the dataset, and matching happens
+/// locally. The result contributes an age-based component to the risk score.
+///
+/// # Examples
+/// ```no_run
+/// # async fn example() -> Result<(), String> {
+/// use email_sanitizer::handlers::validation::first_seen::lookup_email_age;
+///
+/// if let Some(age) = lookup_email_age("user@example.com").await? {
+///     println!("seen: {}, risk: {}", age.seen, age.risk_score);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub mod first_seen;
+
+/// Looks up when an address's domain was registered, and by which registrar, over RDAP.
+///
+/// Lookups are opt-in (`RDAP_LOOKUP_ENABLED=true`) and cached for a month by default
+/// (`RDAP_CACHE_TTL_SECS`), since registration data rarely changes. Domains younger
+/// than `RDAP_NEW_DOMAIN_DAYS` (default 30) carry the `NEW_DOMAIN` risk flag; they are
+/// reported, not rejected.
+pub mod rdap;
+
+/// Per-account blocklists and allowlists of domains and addresses.
+///
+/// Entries are managed by each account through `/api/v1/account/{blocklist,allowlist}`
+/// and stored in MongoDB. Allowlisted addresses are accepted without further checks;
+/// blocklisted ones fail with `CUSTOM_BLOCKED`.
+///
+/// # Examples
+/// ```
+/// use email_sanitizer::handlers::validation::custom_lists::{AccountLists, CustomVerdict};
+///
+/// let lists = AccountLists {
+///     blocked: vec!["competitor.com".to_string()],
+///     ..Default::default()
+/// };
+/// assert_eq!(lists.verdict("sales@competitor.com"), Some(CustomVerdict::Blocked));
+/// ```
+pub mod custom_lists;
+
+/// Bounces and spam complaints reported by each account's own sending.
+///
+/// Accounts post SES/SNS notifications or a generic JSON format to
+/// `/api/v1/feedback/bounces`, or subscribe the SNS topics listed in their settings
+/// to `/api/v1/feedback/sns`, whose messages need a valid SNS signature. Hard bounces and complaints suppress the address for
+/// that account at once, soft bounces after `BOUNCE_SOFT_LIMIT` repeats; suppressed
+/// addresses fail with `PREVIOUS_BOUNCE` or `PREVIOUS_COMPLAINT`.
+///
+/// # Examples
+/// ```
+/// use email_sanitizer::handlers::validation::bounce_feedback::{Notification, parse_notification};
+///
+/// let body = serde_json::json!({ "events": [{ "email": "gone@example.com", "type": "bounce" }] });
+/// assert!(matches!(parse_notification(&body), Ok(Notification::Events(events)) if events.len() == 1));
+/// ```
+pub mod bounce_feedback;
+
+/// Addresses each account has suppressed, with a reason code.
+///
+/// Accounts manage the list through `/api/v1/suppressions` or GraphQL. Unlike the
+/// domain-oriented blocklist it holds single addresses, and it outranks every other
+/// check, the allowlist included: listed addresses always fail with `SUPPRESSED`.
+///
+/// # Examples
+/// ```
+/// use email_sanitizer::handlers::validation::suppression_list::{
+///     SuppressionInput, SuppressionReason, check_input,
+/// };
+///
+/// let input = SuppressionInput {
+///     email: " User@Example.COM ".to_string(),
+///     reason: SuppressionReason::Unsubscribe,
+///     note: None,
+/// };
+/// assert_eq!(check_input(&input).unwrap().0, "User@example.com");
+/// ```
+pub mod suppression_list;
+
 #[cfg(test)]
 mod syntax_test;
 
@@ -113,3 +220,22 @@ mod tests {
         assert!(true);
     }
 }
+
+/// Checks addresses against imported spamtrap, complainer and bouncer hashes.
+///
+/// Entries are SHA-256 hashes of normalized addresses, imported through the admin
+/// API. Listed addresses fail validation with `KNOWN_TRAP` (spamtraps and complainers)
+/// or `PREVIOUS_BOUNCER`. Lookups are disabled unless `SUPPRESSION_LOOKUP_ENABLED=true`.
+///
+/// # Examples
+/// ```no_run
+/// # async fn example() -> Result<(), String> {
+/// use email_sanitizer::handlers::validation::suppression::lookup;
+///
+/// if let Some(kind) = lookup("user@example.com").await? {
+///     println!("suppressed: {}", kind.code());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub mod suppression;
@@ -0,0 +1,327 @@
+use crate::stores::CacheStore;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::env;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Prefix of the cache keys registration data is kept under, followed by the ASCII domain
+pub const CACHE_PREFIX: &str = "rdap::";
+
+/// Bootstrap service redirecting each query to the registry's RDAP server, used
+/// unless `RDAP_BASE_URL` is set
+pub const DEFAULT_BASE_URL: &str = "https://rdap.org";
+
+/// Domains registered fewer days ago are flagged unless `RDAP_NEW_DOMAIN_DAYS` is set
+pub const DEFAULT_NEW_DOMAIN_DAYS: i64 = 30;
+
+/// Seconds registration data is cached unless `RDAP_CACHE_TTL_SECS` is set (30 days)
+pub const DEFAULT_CACHE_TTL_SECS: u64 = 30 * 24 * 3600;
+
+/// Risk flag of domains younger than the threshold
+pub const NEW_DOMAIN: &str = "NEW_DOMAIN";
+
+/// How long a registry gets to answer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// RDAP lookup settings, read from the environment
+#[derive(Debug, Clone, PartialEq)]
+pub struct RdapConfig {
+    /// Queried as `{base_url}/domain/{domain}` (`RDAP_BASE_URL`)
+    pub base_url: String,
+    /// Age in days under which a domain is `NEW_DOMAIN` (`RDAP_NEW_DOMAIN_DAYS`)
+    pub new_domain_days: i64,
+    /// Seconds a lookup is cached (`RDAP_CACHE_TTL_SECS`)
+    pub cache_ttl: u64,
+}
+
+impl RdapConfig {
+    /// `None` unless `RDAP_LOOKUP_ENABLED=true`; unparsable values keep the defaults
+    pub fn from_env() -> Option<Self> {
+        let enabled = env::var("RDAP_LOOKUP_ENABLED")
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        Some(Self {
+            base_url: env::var("RDAP_BASE_URL")
+                .ok()
+                .map(|url| url.trim().trim_end_matches('/').to_string())
+                .filter(|url| !url.is_empty())
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string()),
+            new_domain_days: env::var("RDAP_NEW_DOMAIN_DAYS")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(DEFAULT_NEW_DOMAIN_DAYS),
+            cache_ttl: env::var("RDAP_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|ttl| *ttl > 0)
+                .unwrap_or(DEFAULT_CACHE_TTL_SECS),
+        })
+    }
+}
+
+/// What the registry reports about a domain, as cached
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RegistrationData {
+    /// Unix timestamp of the `registration` event
+    pub created: Option<i64>,
+    /// Full name of the entity with the `registrar` role
+    pub registrar: Option<String>,
+}
+
+/// Registration data of an address's domain, as reported in responses
+///
+/// Every field is `null` when the registry has no RDAP record of the domain.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DomainRegistration {
+    /// RFC 3339 registration date
+    pub created_at: Option<String>,
+    pub age_days: Option<i64>,
+    pub registrar: Option<String>,
+    /// [`NEW_DOMAIN`] when the domain is younger than `RDAP_NEW_DOMAIN_DAYS`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub risk: Option<&'static str>,
+}
+
+/// Registration date and registrar from an RDAP domain object (RFC 9083)
+pub fn parse_registration(body: &Value) -> RegistrationData {
+    let created = body["events"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|event| event["eventAction"] == "registration")
+        .and_then(|event| event["eventDate"].as_str())
+        .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+        .map(|date| date.timestamp());
+    let registrar = body["entities"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|entity| {
+            entity["roles"]
+                .as_array()
+                .is_some_and(|roles| roles.iter().any(|role| role == "registrar"))
+        })
+        .and_then(vcard_name);
+    RegistrationData { created, registrar }
+}
+
+/// The `fn` property of an entity's jCard
+fn vcard_name(entity: &Value) -> Option<String> {
+    entity["vcardArray"][1]
+        .as_array()?
+        .iter()
+        .find(|property| property[0] == "fn")?[3]
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Maps registration data to its report, flagging domains younger than `new_domain_days`
+pub fn report(
+    data: &RegistrationData,
+    now: DateTime<Utc>,
+    new_domain_days: i64,
+) -> DomainRegistration {
+    let created = data
+        .created
+        .and_then(|created| DateTime::from_timestamp(created, 0));
+    let age_days = created.map(|created| (now - created).num_days().max(0));
+    DomainRegistration {
+        created_at: created.map(|created| created.to_rfc3339()),
+        age_days,
+        registrar: data.registrar.clone(),
+        risk: age_days
+            .is_some_and(|days| days < new_domain_days)
+            .then_some(NEW_DOMAIN),
+    }
+}
+
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// Queries the registry about `domain`, then about each parent down to two labels
+/// while it has no record, since registries only know registrable domains
+async fn fetch(config: &RdapConfig, domain: &str) -> Result<RegistrationData, String> {
+    let mut domain = domain;
+    loop {
+        let response = client()
+            .get(format!("{}/domain/{}", config.base_url, domain))
+            .header("Accept", "application/rdap+json")
+            .send()
+            .await
+            .map_err(|e| format!("RDAP request failed: {}", e))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            match domain.split_once('.') {
+                Some((_, parent)) if parent.contains('.') => {
+                    domain = parent;
+                    continue;
+                }
+                _ => return Ok(RegistrationData::default()),
+            }
+        }
+        let body: Value = response
+            .error_for_status()
+            .map_err(|e| format!("RDAP request failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid RDAP response: {}", e))?;
+        return Ok(parse_registration(&body));
+    }
+}
+
+/// Registration data of `domain` (ASCII form), cached in `store` for the configured TTL
+///
+/// Domains the registry doesn't know are cached too; failed lookups are not.
+///
+/// # Returns
+/// * `Ok(None)` if lookups are disabled
+/// * `Ok(Some(registration))` otherwise
+/// * `Err` containing an error message if the registry can't be queried
+pub async fn lookup_registration(
+    store: &dyn CacheStore,
+    config: Option<&RdapConfig>,
+    domain: &str,
+) -> Result<Option<DomainRegistration>, String> {
+    let Some(config) = config else {
+        return Ok(None);
+    };
+    let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+    let cache_key = format!("{}{}", CACHE_PREFIX, domain);
+    // A cache outage only costs a registry query
+    let cached = store
+        .get(&cache_key)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|value| serde_json::from_str::<RegistrationData>(&value).ok());
+
+    let data = match cached {
+        Some(data) => data,
+        None => {
+            let data = fetch(config, &domain).await?;
+            if let Ok(value) = serde_json::to_string(&data) {
+                let _ = store.set_ex(&cache_key, &value, config.cache_ttl).await;
+            }
+            data
+        }
+    };
+    Ok(Some(report(&data, Utc::now(), config.new_domain_days)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stores::MemoryStore;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn config() -> RdapConfig {
+        RdapConfig {
+            // Nothing listens here; lookups must be served from the cache
+            base_url: "http://127.0.0.1:9".to_string(),
+            new_domain_days: DEFAULT_NEW_DOMAIN_DAYS,
+            cache_ttl: 60,
+        }
+    }
+
+    #[test]
+    fn test_parse_registration() {
+        let body = json!({
+            "objectClassName": "domain",
+            "ldhName": "EXAMPLE.COM",
+            "events": [
+                { "eventAction": "last changed", "eventDate": "2024-08-14T07:01:34Z" },
+                { "eventAction": "registration", "eventDate": "1995-08-14T04:00:00Z" }
+            ],
+            "entities": [
+                {
+                    "roles": ["registrar"],
+                    "vcardArray": ["vcard", [
+                        ["version", {}, "text", "4.0"],
+                        ["fn", {}, "text", "RESERVED-Internet Assigned Numbers Authority"]
+                    ]]
+                }
+            ]
+        });
+        let data = parse_registration(&body);
+        assert_eq!(
+            data.created,
+            Some(
+                Utc.with_ymd_and_hms(1995, 8, 14, 4, 0, 0)
+                    .unwrap()
+                    .timestamp()
+            )
+        );
+        assert_eq!(
+            data.registrar.as_deref(),
+            Some("RESERVED-Internet Assigned Numbers Authority")
+        );
+
+        assert_eq!(
+            parse_registration(&json!({ "errorCode": 404 })),
+            RegistrationData::default()
+        );
+    }
+
+    #[test]
+    fn test_young_domains_are_flagged() {
+        let now = Utc::now();
+        let young = RegistrationData {
+            created: Some((now - chrono::Duration::days(3)).timestamp()),
+            registrar: None,
+        };
+        let old = RegistrationData {
+            created: Some((now - chrono::Duration::days(400)).timestamp()),
+            registrar: None,
+        };
+
+        let report_young = report(&young, now, 30);
+        assert_eq!(report_young.age_days, Some(3));
+        assert_eq!(report_young.risk, Some(NEW_DOMAIN));
+        assert_eq!(report(&old, now, 30).risk, None);
+        assert_eq!(report(&RegistrationData::default(), now, 30).risk, None);
+    }
+
+    #[tokio::test]
+    async fn test_lookup_is_served_from_the_cache() {
+        let store = MemoryStore::default();
+        assert_eq!(
+            lookup_registration(&store, None, "example.com").await,
+            Ok(None)
+        );
+
+        let data = RegistrationData {
+            created: Some(
+                Utc.with_ymd_and_hms(1995, 8, 14, 4, 0, 0)
+                    .unwrap()
+                    .timestamp(),
+            ),
+            registrar: Some("Example Registrar".to_string()),
+        };
+        store
+            .set_ex(
+                "rdap::example.com",
+                &serde_json::to_string(&data).unwrap(),
+                60,
+            )
+            .await
+            .unwrap();
+        let registration = lookup_registration(&store, Some(&config()), "Example.COM.")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(registration.registrar.as_deref(), Some("Example Registrar"));
+        assert_eq!(registration.risk, None);
+    }
+}
@@ -1,13 +1,17 @@
-use mongodb::{Client, Collection, bson::doc};
-use std::env;
+use crate::normalize::lookup_key;
+use crate::stores::DomainListStore;
+use crate::sync::SyncList;
+use std::collections::HashSet;
 
-/// Checks if an email address uses a role-based local part by querying a MongoDB collection.
+/// Checks if an email address uses a role-based local part by looking it up in the
+/// role-based list of `store`.
 ///
 /// # Arguments
+/// * `store` - The reference lists, e.g. [`MongoDomainLists`](crate::stores::MongoDomainLists)
 /// * `email` - A string slice containing the email address to check
 ///
 /// # Returns
-/// * `Ok(true)` if the local part is found in the role-based collection
+/// * `Ok(true)` if the local part is on the role-based list
 /// * `Ok(false)` if the local part is not found
 /// * `Err` containing an error message if any step fails
 ///
@@ -15,49 +19,71 @@ use std::env;
 /// ```
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 /// use email_sanitizer::handlers::validation::role_based::is_role_based_email;
-/// let is_role = is_role_based_email("admin@example.com").await?;
+/// use email_sanitizer::stores::MongoDomainLists;
+/// let is_role = is_role_based_email(&MongoDomainLists::from_env(), "admin@example.com").await?;
 /// assert_eq!(is_role, true);
 /// # Ok(())
 /// # }
 /// ```
-pub async fn is_role_based_email(email: &str) -> Result<bool, String> {
+pub async fn is_role_based_email(store: &dyn DomainListStore, email: &str) -> Result<bool, String> {
     let at_pos = email.find('@').ok_or("Invalid email format")?;
     if at_pos == 0 {
         return Err("Invalid email format".to_string());
     }
-    let local_part = email[..at_pos].to_lowercase();
+    let local_part = lookup_key(&email[..at_pos]);
 
-    let mongo_uri =
-        env::var("MONGODB_URI").map_err(|_| "MONGODB_URI environment variable not set")?;
-    let database_name = env::var("DB_NAME_PRODUCTION")
-        .map_err(|_| "DB_NAME_PRODUCTION environment variable not set")?;
-
-    let client = Client::with_uri_str(&mongo_uri)
-        .await
-        .map_err(|e| format!("Failed to connect to MongoDB: {}", e))?;
-    let db = client.database(&database_name);
-    let collection: Collection<mongodb::bson::Document> = db.collection("role_based_emails");
+    let role_based = role_based_local_parts(store, std::slice::from_ref(&local_part)).await?;
+    Ok(role_based.contains(&local_part))
+}
 
-    let filter = doc! { "prefix": &local_part };
-    match collection.find_one(filter).await {
-        Ok(Some(_)) => Ok(true),
-        Ok(None) => Ok(false),
-        Err(e) => Err(format!("Database query failed: {}", e)),
-    }
+/// Returns which of `local_parts` (already folded with [`lookup_key`]) are role-based,
+/// with a single lookup however many local parts are passed
+pub async fn role_based_local_parts(
+    store: &dyn DomainListStore,
+    local_parts: &[String],
+) -> Result<HashSet<String>, String> {
+    store.matching(SyncList::RoleBased, local_parts).await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::stores::MemoryDomainLists;
 
     #[tokio::test]
     async fn test_invalid_email_format() {
-        assert!(is_role_based_email("invalid-email").await.is_err());
-        assert!(is_role_based_email("@example.com").await.is_err());
+        let store = MemoryDomainLists::default();
+        assert!(is_role_based_email(&store, "invalid-email").await.is_err());
+        assert!(is_role_based_email(&store, "@example.com").await.is_err());
     }
 
     #[tokio::test]
     async fn test_empty_email() {
-        assert!(is_role_based_email("").await.is_err());
+        assert!(
+            is_role_based_email(&MemoryDomainLists::default(), "")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_role_based_local_parts() {
+        let store = MemoryDomainLists::default().with(SyncList::RoleBased, &["admin"]);
+        assert!(
+            is_role_based_email(&store, "Admin@example.com")
+                .await
+                .unwrap()
+        );
+        // Invisible characters don't hide a role-based local part
+        assert!(
+            is_role_based_email(&store, "ad\u{200B}min@example.com")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !is_role_based_email(&store, "jane@example.com")
+                .await
+                .unwrap()
+        );
     }
 }
@@ -0,0 +1,240 @@
+use crate::normalize::normalize_email;
+use futures::TryStreamExt;
+use mongodb::bson::doc;
+use mongodb::options::IndexOptions;
+use mongodb::{Client, Collection, IndexModel};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use utoipa::ToSchema;
+
+/// Hashes written per round trip during an import
+const IMPORT_CHUNK_SIZE: usize = 1000;
+
+/// Why an address must not be mailed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SuppressionKind {
+    /// A spamtrap: mailing it marks the sender as a spammer
+    Spamtrap,
+    /// An address whose owner has filed spam complaints
+    Complainer,
+    /// An address that hard-bounced for another sender
+    Bouncer,
+}
+
+impl SuppressionKind {
+    pub const ALL: [SuppressionKind; 3] = [
+        SuppressionKind::Spamtrap,
+        SuppressionKind::Complainer,
+        SuppressionKind::Bouncer,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuppressionKind::Spamtrap => "spamtrap",
+            SuppressionKind::Complainer => "complainer",
+            SuppressionKind::Bouncer => "bouncer",
+        }
+    }
+
+    /// Verdict code; complainers hurt sender reputation like traps do
+    pub fn code(&self) -> &'static str {
+        match self {
+            SuppressionKind::Spamtrap | SuppressionKind::Complainer => "KNOWN_TRAP",
+            SuppressionKind::Bouncer => "PREVIOUS_BOUNCER",
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            SuppressionKind::Spamtrap => "Email address is a known spamtrap",
+            SuppressionKind::Complainer => "Email address belongs to a known spam complainer",
+            SuppressionKind::Bouncer => "Email address has hard-bounced before",
+        }
+    }
+}
+
+/// One suppressed address, stored in the suppression collection
+///
+/// Only the hash is kept; the address itself is never stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuppressionEntry {
+    /// [`address_hash`] of the address
+    pub hash: String,
+    pub kind: SuppressionKind,
+    /// Feed or customer the entry came from
+    pub source: Option<String>,
+    /// Unix timestamp of the import
+    pub imported_at: i64,
+}
+
+/// Whether suppression lookups are configured (`SUPPRESSION_LOOKUP_ENABLED=true`)
+pub fn is_enabled() -> bool {
+    env::var("SUPPRESSION_LOOKUP_ENABLED")
+        .map(|v| v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// SHA-256 of the normalized address (see [`normalize_email`]), hex encoded
+pub fn address_hash(email: &str) -> String {
+    let digest = Sha256::digest(normalize_email(email).as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Lowercased hash if `hash` is a full SHA-256 in hex
+pub fn normalize_hash(hash: &str) -> Option<String> {
+    let hash = hash.trim();
+    (hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| hash.to_ascii_lowercase())
+}
+
+fn collection(mongo_client: &Client) -> Collection<SuppressionEntry> {
+    let db_name = env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let collection_name =
+        env::var("DB_SUPPRESSION_COLLECTION").unwrap_or_else(|_| "suppression_hashes".to_string());
+    mongo_client.database(&db_name).collection(&collection_name)
+}
+
+/// Creates the unique index on `hash` that lookups use
+pub async fn ensure_indexes(mongo_client: &Client) -> Result<(), String> {
+    collection(mongo_client)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "hash": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Stores `hashes` (already normalized) as `kind`, replacing earlier entries for the
+/// same hashes; returns how many distinct hashes were written
+pub async fn import(
+    mongo_client: &Client,
+    kind: SuppressionKind,
+    source: Option<String>,
+    hashes: &[String],
+) -> Result<u64, String> {
+    let collection = collection(mongo_client);
+    let imported_at = chrono::Utc::now().timestamp();
+    let mut seen = HashSet::new();
+    let hashes: Vec<&String> = hashes.iter().filter(|hash| seen.insert(*hash)).collect();
+
+    for chunk in hashes.chunks(IMPORT_CHUNK_SIZE) {
+        collection
+            .delete_many(doc! { "hash": { "$in": chunk.to_vec() } })
+            .await
+            .map_err(|e| e.to_string())?;
+        let entries = chunk.iter().map(|hash| SuppressionEntry {
+            hash: hash.to_string(),
+            kind,
+            source: source.clone(),
+            imported_at,
+        });
+        collection
+            .insert_many(entries)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(hashes.len() as u64)
+}
+
+/// Removes the entry for `hash`; returns whether there was one
+pub async fn remove(mongo_client: &Client, hash: &str) -> Result<bool, String> {
+    collection(mongo_client)
+        .delete_one(doc! { "hash": hash })
+        .await
+        .map(|result| result.deleted_count > 0)
+        .map_err(|e| e.to_string())
+}
+
+/// Number of entries of each kind
+pub async fn counts(mongo_client: &Client) -> Result<Vec<(SuppressionKind, u64)>, String> {
+    let collection = collection(mongo_client);
+    let mut counts = Vec::new();
+    for kind in SuppressionKind::ALL {
+        let count = collection
+            .count_documents(doc! { "kind": kind.as_str() })
+            .await
+            .map_err(|e| e.to_string())?;
+        counts.push((kind, count));
+    }
+    Ok(counts)
+}
+
+/// Looks an address up in the imported spamtrap, complainer and bouncer hashes.
+///
+/// # Returns
+/// * `Ok(None)` if lookups are disabled or the address is not listed
+/// * `Ok(Some(kind))` if it is
+/// * `Err` containing an error message if the collection can't be queried
+pub async fn lookup(email: &str) -> Result<Option<SuppressionKind>, String> {
+    let mut kinds = lookup_many(&[email.to_string()]).await?;
+    Ok(kinds.pop().flatten())
+}
+
+/// Like [`lookup`] for many addresses at once, with a single `$in` query; results are
+/// in the order of `emails`
+pub async fn lookup_many(emails: &[String]) -> Result<Vec<Option<SuppressionKind>>, String> {
+    if !is_enabled() || emails.is_empty() {
+        return Ok(vec![None; emails.len()]);
+    }
+
+    let mongo_uri =
+        env::var("MONGODB_URI").map_err(|_| "MONGODB_URI environment variable not set")?;
+    let client = Client::with_uri_str(&mongo_uri)
+        .await
+        .map_err(|e| format!("Failed to connect to MongoDB: {}", e))?;
+
+    let hashes: Vec<String> = emails.iter().map(|email| address_hash(email)).collect();
+    let entries: Vec<SuppressionEntry> = collection(&client)
+        .find(doc! { "hash": { "$in": &hashes } })
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?
+        .try_collect()
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?;
+
+    let kinds: HashMap<String, SuppressionKind> = entries
+        .into_iter()
+        .map(|entry| (entry.hash, entry.kind))
+        .collect();
+    Ok(hashes.iter().map(|hash| kinds.get(hash).copied()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_hash_uses_normalized_address() {
+        assert_eq!(
+            address_hash(" user@Example.COM "),
+            address_hash("user@example.com")
+        );
+        assert_eq!(
+            address_hash("User@gmail.com"),
+            address_hash("user@gmail.com")
+        );
+        assert_eq!(address_hash("user@example.com").len(), 64);
+    }
+
+    #[test]
+    fn test_normalize_hash() {
+        let hash = address_hash("user@example.com");
+        assert_eq!(normalize_hash(&hash.to_uppercase()), Some(hash.clone()));
+        assert_eq!(normalize_hash(&hash[..63]), None);
+        assert_eq!(normalize_hash(&format!("{}g", &hash[..63])), None);
+    }
+
+    #[test]
+    fn test_kind_codes() {
+        assert_eq!(SuppressionKind::Spamtrap.code(), "KNOWN_TRAP");
+        assert_eq!(SuppressionKind::Complainer.code(), "KNOWN_TRAP");
+        assert_eq!(SuppressionKind::Bouncer.code(), "PREVIOUS_BOUNCER");
+    }
+}
@@ -0,0 +1,419 @@
+use crate::handlers::validation::suppression::address_hash;
+use crate::handlers::validation::syntax;
+use crate::normalize::normalize_email;
+use futures::TryStreamExt;
+use mongodb::bson::{self, doc};
+use mongodb::options::{IndexOptions, ReturnDocument};
+use mongodb::{Client, Collection, IndexModel};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
+
+/// Most addresses an account may keep suppressed
+pub const MAX_SUPPRESSIONS: u64 = 100_000;
+
+/// Most entries one import may hold
+pub const MAX_IMPORT_ENTRIES: usize = 10_000;
+
+/// Notes longer than this are rejected
+pub const MAX_NOTE_LENGTH: usize = 500;
+
+/// Entries listed per page
+pub const SUPPRESSIONS_PAGE_SIZE: usize = 100;
+
+/// Why an account suppressed an address
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SuppressionReason {
+    Bounce,
+    Complaint,
+    Unsubscribe,
+    #[default]
+    Manual,
+}
+
+impl SuppressionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuppressionReason::Bounce => "bounce",
+            SuppressionReason::Complaint => "complaint",
+            SuppressionReason::Unsubscribe => "unsubscribe",
+            SuppressionReason::Manual => "manual",
+        }
+    }
+
+    /// Message of the `SUPPRESSED` verdict
+    pub fn message(&self) -> &'static str {
+        match self {
+            SuppressionReason::Bounce => "The account suppressed this address after a bounce",
+            SuppressionReason::Complaint => {
+                "The account suppressed this address after a spam complaint"
+            }
+            SuppressionReason::Unsubscribe => {
+                "The account suppressed this address after an unsubscribe"
+            }
+            SuppressionReason::Manual => "The account suppressed this address",
+        }
+    }
+}
+
+/// One address of an account's suppression list, stored in the `suppression_list`
+/// collection
+///
+/// Unlike the hashes of the global suppression list, the address itself is kept so
+/// the account can export its list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuppressedAddress {
+    /// Account the list belongs to, or its organization
+    pub owner: String,
+    /// Normalized address (see [`normalize_email`])
+    pub email: String,
+    /// [`address_hash`] of the address, which lookups match on
+    pub hash: String,
+    pub reason: SuppressionReason,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Unix timestamps
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// An entry as the API returns it
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct SuppressionRecord {
+    pub email: String,
+    pub reason: SuppressionReason,
+    pub note: Option<String>,
+    /// Unix timestamps
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<SuppressedAddress> for SuppressionRecord {
+    fn from(entry: SuppressedAddress) -> Self {
+        SuppressionRecord {
+            email: entry.email,
+            reason: entry.reason,
+            note: entry.note,
+            created_at: entry.created_at,
+            updated_at: entry.updated_at,
+        }
+    }
+}
+
+/// One address to suppress, as added or imported
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
+pub struct SuppressionInput {
+    pub email: String,
+    /// Defaults to `manual`
+    #[serde(default)]
+    pub reason: SuppressionReason,
+    pub note: Option<String>,
+}
+
+/// What an import changed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ImportSummary {
+    /// Addresses added to the list
+    pub added: u64,
+    /// Addresses already listed, whose reason and note were replaced
+    pub updated: u64,
+    /// Entries left out: malformed addresses and repeats within the import
+    pub skipped: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SuppressionError {
+    Invalid(String),
+    /// The list already holds [`MAX_SUPPRESSIONS`] addresses
+    ListFull,
+    Database(String),
+}
+
+impl From<mongodb::error::Error> for SuppressionError {
+    fn from(e: mongodb::error::Error) -> Self {
+        SuppressionError::Database(e.to_string())
+    }
+}
+
+/// Reads `DB_NAME_PRODUCTION` (default `email_sanitizer`) and
+/// `DB_SUPPRESSION_LIST_COLLECTION` (default `suppression_list`)
+pub(crate) fn collection(mongo_client: &Client) -> Collection<SuppressedAddress> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let collection_name = std::env::var("DB_SUPPRESSION_LIST_COLLECTION")
+        .unwrap_or_else(|_| "suppression_list".to_string());
+    mongo_client.database(&db_name).collection(&collection_name)
+}
+
+/// Creates the unique index on `owner` and `hash` that lookups use, and the one
+/// listings are sorted by
+pub async fn ensure_indexes(mongo_client: &Client) -> Result<(), String> {
+    collection(mongo_client)
+        .create_indexes([
+            IndexModel::builder()
+                .keys(doc! { "owner": 1, "hash": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "owner": 1, "reason": 1, "email": 1 })
+                .build(),
+        ])
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Normalized address and trimmed note of `input`, rejecting malformed addresses
+/// and overlong notes
+pub fn check_input(input: &SuppressionInput) -> Result<(String, Option<String>), String> {
+    let email = input.email.trim();
+    if !syntax::is_valid_email(email) {
+        return Err(format!("{} is not a valid email address", email));
+    }
+    let note = input
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|note| !note.is_empty());
+    if note.is_some_and(|note| note.chars().count() > MAX_NOTE_LENGTH) {
+        return Err(format!("Notes are at most {} characters", MAX_NOTE_LENGTH));
+    }
+    Ok((normalize_email(email), note.map(str::to_string)))
+}
+
+fn owner_filter(owner: &str, reason: Option<SuppressionReason>) -> bson::Document {
+    match reason {
+        Some(reason) => doc! { "owner": owner, "reason": reason.as_str() },
+        None => doc! { "owner": owner },
+    }
+}
+
+/// Suppresses an address; suppressing a listed one replaces its reason and note
+pub async fn add(
+    mongo_client: &Client,
+    owner: &str,
+    input: &SuppressionInput,
+) -> Result<SuppressedAddress, SuppressionError> {
+    let (email, note) = check_input(input).map_err(SuppressionError::Invalid)?;
+    let collection = collection(mongo_client);
+    let hash = address_hash(&email);
+
+    let listed = collection
+        .find_one(doc! { "owner": owner, "hash": &hash })
+        .await?
+        .is_some();
+    if !listed && collection.count_documents(doc! { "owner": owner }).await? >= MAX_SUPPRESSIONS {
+        return Err(SuppressionError::ListFull);
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    collection
+        .find_one_and_update(
+            doc! { "owner": owner, "hash": &hash },
+            doc! {
+                "$set": {
+                    "email": &email,
+                    "reason": input.reason.as_str(),
+                    "note": note,
+                    "updated_at": now,
+                },
+                "$setOnInsert": { "created_at": now },
+            },
+        )
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .await?
+        .ok_or_else(|| SuppressionError::Database("Upsert returned no document".to_string()))
+}
+
+/// Suppresses every well-formed address of `inputs`, like [`add`] one by one
+///
+/// Later repeats of an address within the import are skipped. Fails with
+/// [`SuppressionError::ListFull`] before writing anything when the new addresses
+/// wouldn't fit.
+pub async fn import(
+    mongo_client: &Client,
+    owner: &str,
+    inputs: &[SuppressionInput],
+) -> Result<ImportSummary, SuppressionError> {
+    let mut summary = ImportSummary::default();
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+    for input in inputs {
+        match check_input(input) {
+            Ok((email, note)) if seen.insert(address_hash(&email)) => {
+                entries.push((email, input.reason, note))
+            }
+            _ => summary.skipped += 1,
+        }
+    }
+
+    let collection = collection(mongo_client);
+    let hashes: Vec<String> = entries
+        .iter()
+        .map(|(email, ..)| address_hash(email))
+        .collect();
+    let listed = collection
+        .count_documents(doc! { "owner": owner, "hash": { "$in": &hashes } })
+        .await?;
+    let current = collection.count_documents(doc! { "owner": owner }).await?;
+    if current + (entries.len() as u64 - listed) > MAX_SUPPRESSIONS {
+        return Err(SuppressionError::ListFull);
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    for ((email, reason, note), hash) in entries.iter().zip(&hashes) {
+        let result = collection
+            .update_one(
+                doc! { "owner": owner, "hash": hash },
+                doc! {
+                    "$set": {
+                        "email": email,
+                        "reason": reason.as_str(),
+                        "note": note.clone(),
+                        "updated_at": now,
+                    },
+                    "$setOnInsert": { "created_at": now },
+                },
+            )
+            .upsert(true)
+            .await?;
+        if result.upserted_id.is_some() {
+            summary.added += 1;
+        } else {
+            summary.updated += 1;
+        }
+    }
+    Ok(summary)
+}
+
+/// Lifts the suppression of an address; `false` when it wasn't listed
+pub async fn remove(mongo_client: &Client, owner: &str, email: &str) -> Result<bool, String> {
+    let result = collection(mongo_client)
+        .delete_one(doc! { "owner": owner, "hash": address_hash(email) })
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?;
+    Ok(result.deleted_count > 0)
+}
+
+/// Removes an address from every owner's list; returns how many lists held it
+pub async fn remove_everywhere(mongo_client: &Client, email: &str) -> Result<u64, String> {
+    let result = collection(mongo_client)
+        .delete_many(doc! { "hash": address_hash(email) })
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?;
+    Ok(result.deleted_count)
+}
+
+/// One page of the owner's entries, by address, with the number of entries matching
+pub async fn list(
+    mongo_client: &Client,
+    owner: &str,
+    reason: Option<SuppressionReason>,
+    page: usize,
+) -> Result<(Vec<SuppressedAddress>, u64), String> {
+    let collection = collection(mongo_client);
+    let filter = owner_filter(owner, reason);
+    let total = collection
+        .count_documents(filter.clone())
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?;
+    let skip = page
+        .saturating_sub(1)
+        .saturating_mul(SUPPRESSIONS_PAGE_SIZE);
+    let entries = collection
+        .find(filter)
+        .sort(doc! { "email": 1 })
+        .skip(skip as u64)
+        .limit(SUPPRESSIONS_PAGE_SIZE as i64)
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?
+        .try_collect()
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?;
+    Ok((entries, total))
+}
+
+/// Every entry of the owner, by address
+pub async fn export(
+    mongo_client: &Client,
+    owner: &str,
+    reason: Option<SuppressionReason>,
+) -> Result<Vec<SuppressedAddress>, String> {
+    collection(mongo_client)
+        .find(owner_filter(owner, reason))
+        .sort(doc! { "email": 1 })
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?
+        .try_collect()
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))
+}
+
+/// Suppressed addresses among `emails`, by [`address_hash`]
+pub async fn lookup(
+    mongo_client: &Client,
+    owner: &str,
+    emails: &[String],
+) -> Result<HashMap<String, SuppressionReason>, String> {
+    if emails.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let hashes: Vec<String> = emails.iter().map(|email| address_hash(email)).collect();
+    let entries: Vec<SuppressedAddress> = collection(mongo_client)
+        .find(doc! { "owner": owner, "hash": { "$in": &hashes } })
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?
+        .try_collect()
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| (entry.hash, entry.reason))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(email: &str, note: Option<&str>) -> SuppressionInput {
+        SuppressionInput {
+            email: email.to_string(),
+            reason: SuppressionReason::Unsubscribe,
+            note: note.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_check_input() {
+        assert_eq!(
+            check_input(&input(" User@Example.COM ", Some("  "))),
+            Ok(("User@example.com".to_string(), None))
+        );
+        assert_eq!(
+            check_input(&input("user@example.com", Some(" opted out "))),
+            Ok((
+                "user@example.com".to_string(),
+                Some("opted out".to_string())
+            ))
+        );
+        assert!(check_input(&input("example.com", None)).is_err());
+        let long_note = "x".repeat(MAX_NOTE_LENGTH + 1);
+        assert!(check_input(&input("user@example.com", Some(&long_note))).is_err());
+    }
+
+    #[test]
+    fn test_reason_defaults_to_manual() {
+        let input: SuppressionInput =
+            serde_json::from_value(serde_json::json!({ "email": "user@example.com" })).unwrap();
+        assert_eq!(input.reason, SuppressionReason::Manual);
+
+        let input: SuppressionInput = serde_json::from_value(
+            serde_json::json!({ "email": "user@example.com", "reason": "complaint" }),
+        )
+        .unwrap();
+        assert_eq!(input.reason, SuppressionReason::Complaint);
+    }
+}
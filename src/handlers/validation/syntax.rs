@@ -1,4 +1,6 @@
+use serde::{Deserialize, Serialize};
 use std::net::{IpAddr, Ipv6Addr};
+use utoipa::ToSchema;
 /// Validates an email address according to RFC 5322 and RFC 6531 specifications.
 ///
 /// This function performs syntax checking of both local-part and domain parts with:
@@ -22,9 +24,149 @@ use std::net::{IpAddr, Ipv6Addr};
 /// # Returns
 /// `true` if the email address meets all syntax requirements, `false` otherwise
 pub fn is_valid_email(email: &str) -> bool {
+    parse_email(email).is_ok()
+}
+
+/// A specific rule a malformed address breaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SyntaxViolation {
+    /// Longer than 254 octets (RFC 5321)
+    TooLong,
+    MissingAt,
+    /// A quoted local part is never closed
+    UnbalancedQuotes,
+    EmptyLocalPart,
+    /// Local part longer than 64 octets (RFC 5321)
+    LocalTooLong,
+    LeadingDot,
+    TrailingDot,
+    ConsecutiveDots,
+    InvalidLocalCharacter,
+    /// A quoted local part contains a bad escape or a bare quote
+    InvalidQuotedString,
+    EmptyDomain,
+    /// Domain label longer than 63 octets (RFC 1035)
+    LabelTooLong,
+    HyphenAtLabelEdge,
+    InvalidDomainCharacter,
+    /// A bracketed domain that isn't an IPv4 or `IPv6:` address
+    InvalidDomainLiteral,
+}
+
+impl SyntaxViolation {
+    /// The code as serialized, e.g. `LOCAL_TOO_LONG`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyntaxViolation::TooLong => "TOO_LONG",
+            SyntaxViolation::MissingAt => "MISSING_AT",
+            SyntaxViolation::UnbalancedQuotes => "UNBALANCED_QUOTES",
+            SyntaxViolation::EmptyLocalPart => "EMPTY_LOCAL_PART",
+            SyntaxViolation::LocalTooLong => "LOCAL_TOO_LONG",
+            SyntaxViolation::LeadingDot => "LEADING_DOT",
+            SyntaxViolation::TrailingDot => "TRAILING_DOT",
+            SyntaxViolation::ConsecutiveDots => "CONSECUTIVE_DOTS",
+            SyntaxViolation::InvalidLocalCharacter => "INVALID_LOCAL_CHARACTER",
+            SyntaxViolation::InvalidQuotedString => "INVALID_QUOTED_STRING",
+            SyntaxViolation::EmptyDomain => "EMPTY_DOMAIN",
+            SyntaxViolation::LabelTooLong => "LABEL_TOO_LONG",
+            SyntaxViolation::HyphenAtLabelEdge => "HYPHEN_AT_LABEL_EDGE",
+            SyntaxViolation::InvalidDomainCharacter => "INVALID_DOMAIN_CHARACTER",
+            SyntaxViolation::InvalidDomainLiteral => "INVALID_DOMAIN_LITERAL",
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            SyntaxViolation::TooLong => "the address exceeds 254 characters",
+            SyntaxViolation::MissingAt => "the address has no @ separator",
+            SyntaxViolation::UnbalancedQuotes => "a quoted local part is not closed",
+            SyntaxViolation::EmptyLocalPart => "the local part is empty",
+            SyntaxViolation::LocalTooLong => "the local part exceeds 64 characters",
+            SyntaxViolation::LeadingDot => "a part starts with a dot",
+            SyntaxViolation::TrailingDot => "a part ends with a dot",
+            SyntaxViolation::ConsecutiveDots => "a part contains consecutive dots",
+            SyntaxViolation::InvalidLocalCharacter => {
+                "the local part contains a disallowed character"
+            }
+            SyntaxViolation::InvalidQuotedString => {
+                "the quoted local part contains an invalid escape or quote"
+            }
+            SyntaxViolation::EmptyDomain => "the domain is empty",
+            SyntaxViolation::LabelTooLong => "a domain label exceeds 63 characters",
+            SyntaxViolation::HyphenAtLabelEdge => "a domain label starts or ends with a hyphen",
+            SyntaxViolation::InvalidDomainCharacter => "the domain contains a disallowed character",
+            SyntaxViolation::InvalidDomainLiteral => {
+                "the bracketed domain is not a valid IP address"
+            }
+        }
+    }
+}
+
+/// Every rule a malformed address breaks, in the order they were found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub violations: Vec<SyntaxViolation>,
+}
+
+impl SyntaxError {
+    fn push(&mut self, violation: SyntaxViolation) {
+        if !self.violations.contains(&violation) {
+            self.violations.push(violation);
+        }
+    }
+}
+
+impl std::fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Email address has invalid syntax")?;
+        for (i, violation) in self.violations.iter().enumerate() {
+            let separator = if i == 0 { ": " } else { "; " };
+            write!(f, "{}{}", separator, violation.message())?;
+        }
+        Ok(())
+    }
+}
+
+/// The parts of a syntactically valid address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedEmail {
+    pub local_part: String,
+    pub domain: String,
+    /// The local part uses the quoted-string form, e.g. `"john smith"@example.com`
+    pub quoted_local_part: bool,
+    /// The domain is a bracketed IP address, e.g. `user@[192.0.2.1]`
+    pub domain_literal: bool,
+    /// Sub-address after the first `+` of an unquoted local part, e.g. `news` in
+    /// `jane+news@example.com`
+    pub tag: Option<String>,
+}
+
+/// Parses an address into its parts, or reports every syntax rule it breaks
+///
+/// Accepts exactly the addresses [`is_valid_email`] does.
+///
+/// # Examples
+/// ```
+/// use email_sanitizer::handlers::validation::syntax::{SyntaxViolation, parse_email};
+///
+/// let parsed = parse_email("jane+news@example.com").unwrap();
+/// assert_eq!(parsed.tag.as_deref(), Some("news"));
+///
+/// let error = parse_email("jane..doe@-example.com").unwrap_err();
+/// assert_eq!(
+///     error.violations,
+///     vec![SyntaxViolation::ConsecutiveDots, SyntaxViolation::HyphenAtLabelEdge]
+/// );
+/// ```
+pub fn parse_email(email: &str) -> Result<ParsedEmail, SyntaxError> {
+    let mut error = SyntaxError {
+        violations: Vec::new(),
+    };
+
     // Check overall length constraint (RFC 5321 + 5322)
     if email.len() > 254 {
-        return false;
+        error.push(SyntaxViolation::TooLong);
     }
 
     // Find the @ separator, ignoring quoted @ symbols
@@ -44,9 +186,13 @@ pub fn is_valid_email(email: &str) -> bool {
         }
     }
 
-    let split_index = match split_index {
-        Some(i) => i,
-        None => return false, // No @ found
+    let Some(split_index) = split_index else {
+        error.push(if in_quotes {
+            SyntaxViolation::UnbalancedQuotes
+        } else {
+            SyntaxViolation::MissingAt
+        });
+        return Err(error);
     };
 
     let (local_part, domain_part) = email.split_at(split_index);
@@ -54,82 +200,114 @@ pub fn is_valid_email(email: &str) -> bool {
 
     // Validate local part length (RFC 5321)
     if local_part.len() > 64 {
-        return false;
+        error.push(SyntaxViolation::LocalTooLong);
     }
 
-    // Validate local part syntax
-    if !is_valid_local_part(local_part) {
-        return false;
-    }
+    let quoted_local_part = check_local_part(local_part, &mut error);
+    let domain_literal = check_domain_part(domain_part, &mut error);
 
-    // Validate domain part syntax
-    is_valid_domain_part(domain_part)
+    if !error.violations.is_empty() {
+        return Err(error);
+    }
+    let tag = if quoted_local_part {
+        None
+    } else {
+        local_part
+            .split_once('+')
+            .map(|(_, tag)| tag.to_string())
+            .filter(|tag| !tag.is_empty())
+    };
+    Ok(ParsedEmail {
+        local_part: local_part.to_string(),
+        domain: domain_part.to_string(),
+        quoted_local_part,
+        domain_literal,
+        tag,
+    })
 }
 
-/// Validates the local-part component of an email address
+/// Validates the local-part component of an email address, returning whether it's quoted
 ///
 /// Supports both dot-atom (RFC 5322) and quoted-string (RFC 5322) formats
-fn is_valid_local_part(local: &str) -> bool {
-    if local.starts_with('"') && local.ends_with('"') {
+fn check_local_part(local: &str, error: &mut SyntaxError) -> bool {
+    if local.len() >= 2 && local.starts_with('"') && local.ends_with('"') {
         // Quoted string (RFC 5322 Section 3.4.1)
-        is_valid_quoted_string(local)
+        check_quoted_string(local, error);
+        true
     } else {
         // Dot-atom form (RFC 5322 Section 3.4.1)
-        is_valid_dot_atom(local, false)
+        if local.is_empty() {
+            error.push(SyntaxViolation::EmptyLocalPart);
+        } else {
+            check_dots(local, error);
+        }
+        if !local
+            .chars()
+            .all(|c| c == '.' || c.is_alphanumeric() || "-!#$%&'*+/=?^_`{|}~".contains(c))
+        {
+            error.push(SyntaxViolation::InvalidLocalCharacter);
+        }
+        false
     }
 }
 
-/// Validates the domain part component of an email address
+/// Validates the domain part component of an email address, returning whether it's a
+/// domain literal
 ///
 /// Handles both domain names and domain literals (IP addresses)
-fn is_valid_domain_part(domain: &str) -> bool {
+fn check_domain_part(domain: &str, error: &mut SyntaxError) -> bool {
     if let Some(domain_literal) = domain.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
         // Domain literal (RFC 5322 Section 3.4.1)
-        is_valid_domain_literal(domain_literal)
+        if !is_valid_domain_literal(domain_literal) {
+            error.push(SyntaxViolation::InvalidDomainLiteral);
+        }
+        true
     } else {
         // Regular domain (RFC 1035 + RFC 5890 + RFC 6531)
-        is_valid_domain_name(domain)
+        check_domain_name(domain, error);
+        false
     }
 }
 
 // Helper functions Below
 
 /// Validates quoted-string format from RFC 5322 section 3.4.1
-fn is_valid_quoted_string(quoted: &str) -> bool {
+fn check_quoted_string(quoted: &str, error: &mut SyntaxError) {
     let content = &quoted[1..quoted.len() - 1];
     let mut escape = false;
 
     for c in content.chars() {
         if escape {
             if !matches!(c, '\\' | '"') {
-                return false;
+                error.push(SyntaxViolation::InvalidQuotedString);
+                return;
             }
             escape = false;
         } else if c == '\\' {
             escape = true;
         } else if c == '"' {
-            return false; // Unescaped quote
+            // Unescaped quote
+            error.push(SyntaxViolation::InvalidQuotedString);
+            return;
         }
     }
-    !escape // Ensure no dangling escape
+    if escape {
+        // Dangling escape
+        error.push(SyntaxViolation::InvalidQuotedString);
+    }
 }
 
-/// Validates dot-atom format from RFC 5322 section 3.4.1
-///
-/// * `is_domain` - Enforces stricter rules for domain validation
-fn is_valid_dot_atom(s: &str, is_domain: bool) -> bool {
-    let parts: Vec<&str> = s.split('.').collect();
-    if parts.is_empty() || parts.iter().any(|&p| p.is_empty()) {
-        return false;
-    }
-
-    parts.iter().all(|part| {
-        part.chars().all(|c| match c {
-            '-' => !is_domain || (!part.starts_with('-') && !part.ends_with('-')),
-            c if is_domain => c.is_alphanumeric() || c == '-',
-            _ => c.is_alphanumeric() || "!#$%&'*+/=?^_`{|}~".contains(c),
-        })
-    })
+/// Flags empty dot-atom segments (RFC 5322 section 3.4.1) by where they occur
+fn check_dots(s: &str, error: &mut SyntaxError) {
+    if s.starts_with('.') {
+        error.push(SyntaxViolation::LeadingDot);
+    }
+    if s.ends_with('.') {
+        error.push(SyntaxViolation::TrailingDot);
+    }
+    if s.contains("..") {
+        error.push(SyntaxViolation::ConsecutiveDots);
+    }
 }
 
 /// Validates domain literals (IP addresses) from RFC 5322 section 3.4.1
@@ -142,15 +320,24 @@ fn is_valid_domain_literal(literal: &str) -> bool {
 }
 
 /// Validates internationalized domain names per RFC 5890 and RFC 6531
-fn is_valid_domain_name(domain: &str) -> bool {
-    let labels: Vec<&str> = domain.split('.').collect();
-    !labels.is_empty()
-        && labels.iter().all(|label| {
-            label.len() <= 63
-                && !label.starts_with('-')
-                && !label.ends_with('-')
-                && is_valid_dot_atom(label, true)
-        })
+fn check_domain_name(domain: &str, error: &mut SyntaxError) {
+    if domain.is_empty() {
+        error.push(SyntaxViolation::EmptyDomain);
+        return;
+    }
+    check_dots(domain, error);
+
+    for label in domain.split('.').filter(|label| !label.is_empty()) {
+        if label.len() > 63 {
+            error.push(SyntaxViolation::LabelTooLong);
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            error.push(SyntaxViolation::HyphenAtLabelEdge);
+        }
+        if !label.chars().all(|c| c.is_alphanumeric() || c == '-') {
+            error.push(SyntaxViolation::InvalidDomainCharacter);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -266,6 +453,47 @@ mod tests {
         assert!(!is_valid_email("@"));
     }
 
+    #[test]
+    fn parse_reports_parts() {
+        let parsed = parse_email("Jane.Doe+news@Example.com").unwrap();
+        assert_eq!(parsed.local_part, "Jane.Doe+news");
+        assert_eq!(parsed.domain, "Example.com");
+        assert_eq!(parsed.tag.as_deref(), Some("news"));
+        assert!(!parsed.quoted_local_part && !parsed.domain_literal);
+
+        let parsed = parse_email("\"a+b\"@[192.168.0.1]").unwrap();
+        assert!(parsed.quoted_local_part && parsed.domain_literal);
+        assert_eq!(parsed.tag, None);
+    }
+
+    #[test]
+    fn parse_reports_every_violation() {
+        let error = parse_email(&format!(".{}@a..b", "x".repeat(64))).unwrap_err();
+        assert_eq!(
+            error.violations,
+            vec![
+                SyntaxViolation::LocalTooLong,
+                SyntaxViolation::LeadingDot,
+                SyntaxViolation::ConsecutiveDots
+            ]
+        );
+        assert_eq!(
+            parse_email("\"unclosed@example.com")
+                .unwrap_err()
+                .violations,
+            vec![SyntaxViolation::UnbalancedQuotes]
+        );
+        assert_eq!(
+            parse_email("user@_bad-.com").unwrap_err().to_string(),
+            "Email address has invalid syntax: a domain label starts or ends with a hyphen; \
+             the domain contains a disallowed character"
+        );
+        assert_eq!(
+            serde_json::to_string(&SyntaxViolation::InvalidDomainCharacter).unwrap(),
+            format!("\"{}\"", SyntaxViolation::InvalidDomainCharacter.as_str())
+        );
+    }
+
     #[test]
     fn case_handling() {
         // Domain should be case-insensitive (valid regardless of case)
@@ -0,0 +1,120 @@
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+
+/// TLD list shipped with the binary, used until the first refresh succeeds
+const BUNDLED_TLDS: &str = include_str!("tlds-alpha-by-domain.txt");
+
+/// Where the TLD list is refreshed from unless `TLD_LIST_URL` is set
+pub const IANA_TLD_LIST_URL: &str = "https://data.iana.org/TLD/tlds-alpha-by-domain.txt";
+
+/// Downloaded lists with fewer entries are treated as truncated and ignored
+const MIN_TLDS: usize = 1000;
+
+/// Lowercased TLDs from a list in the IANA format: `#` comments, one ASCII
+/// (punycode) TLD per line
+pub fn parse_tld_list(text: &str) -> HashSet<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_ascii_lowercase)
+        .collect()
+}
+
+fn known_tlds() -> &'static RwLock<Arc<HashSet<String>>> {
+    static TLDS: OnceLock<RwLock<Arc<HashSet<String>>>> = OnceLock::new();
+    TLDS.get_or_init(|| RwLock::new(Arc::new(parse_tld_list(BUNDLED_TLDS))))
+}
+
+/// Whether the last label of `domain` (in ASCII form) is a delegated TLD
+///
+/// Domain literals such as `[192.0.2.1]` have no TLD and always pass.
+pub fn has_known_tld(domain: &str) -> bool {
+    let domain = domain.trim_end_matches('.');
+    if domain.starts_with('[') {
+        return true;
+    }
+    let tld = domain.rsplit('.').next().unwrap_or(domain);
+    known_tlds()
+        .read()
+        .unwrap()
+        .contains(&tld.to_ascii_lowercase())
+}
+
+/// Replaces the TLD list; rejects lists too short to be complete
+pub fn replace_tlds(tlds: HashSet<String>) -> Result<usize, String> {
+    if tlds.len() < MIN_TLDS || !tlds.contains("com") {
+        return Err(format!(
+            "TLD list has {} entries, expected at least {}",
+            tlds.len(),
+            MIN_TLDS
+        ));
+    }
+    let count = tlds.len();
+    *known_tlds().write().unwrap() = Arc::new(tlds);
+    Ok(count)
+}
+
+/// Downloads the TLD list from `url` and replaces the current one
+pub async fn refresh_tlds(client: &reqwest::Client, url: &str) -> Result<usize, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let text = response.text().await.map_err(|e| e.to_string())?;
+    replace_tlds(parse_tld_list(&text))
+}
+
+/// How often the TLD list is refreshed (`TLD_LIST_REFRESH_HOURS`, default 24);
+/// `None` when set to 0
+pub fn refresh_interval() -> Option<Duration> {
+    let hours = std::env::var("TLD_LIST_REFRESH_HOURS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(24);
+    (hours > 0).then(|| Duration::from_secs(hours * 3600))
+}
+
+/// Refreshes the TLD list from `TLD_LIST_URL` (default [`IANA_TLD_LIST_URL`]) on
+/// startup and every `interval`; a failed refresh keeps the current list
+pub async fn refresh_periodically(interval: Duration) {
+    let url = std::env::var("TLD_LIST_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| IANA_TLD_LIST_URL.to_string());
+    let client = reqwest::Client::new();
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        if let Err(e) = refresh_tlds(&client, &url).await {
+            eprintln!("TLD list refresh failed, keeping the current one: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_known_tld() {
+        assert!(has_known_tld("example.com"));
+        assert!(has_known_tld("Example.CO.UK"));
+        assert!(has_known_tld("example.com."));
+        // Internationalized TLDs are looked up in punycode (.рф)
+        assert!(has_known_tld("xn--80ak6aa92e.xn--p1ai"));
+        assert!(has_known_tld("[192.0.2.1]"));
+        assert!(!has_known_tld("example.fake"));
+        assert!(!has_known_tld("localhost"));
+    }
+
+    #[test]
+    fn test_replace_rejects_truncated_lists() {
+        let list = parse_tld_list("# Version 1\nCOM\n\nNET\n");
+        assert_eq!(list, HashSet::from(["com".to_string(), "net".to_string()]));
+        assert!(replace_tlds(list).is_err());
+        assert!(has_known_tld("example.org"));
+    }
+}
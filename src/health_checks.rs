@@ -0,0 +1,101 @@
+use crate::handlers::validation::dnsmx;
+use crate::health_history::{self, HealthHistory};
+use crate::models::health::DependencyCheck;
+use mongodb::Client as MongoClient;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_PROBE_TIMEOUT_MS: u64 = 2000;
+
+pub const DEFAULT_DNS_PROBE_DOMAIN: &str = "example.com";
+
+/// Longest a single probe may take before counting as down (`HEALTH_PROBE_TIMEOUT_MS`)
+pub fn probe_timeout() -> Duration {
+    let millis = std::env::var("HEALTH_PROBE_TIMEOUT_MS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|millis| *millis > 0)
+        .unwrap_or(DEFAULT_PROBE_TIMEOUT_MS);
+    Duration::from_millis(millis)
+}
+
+/// Name the resolver self-test looks up (`HEALTH_DNS_PROBE_DOMAIN`); empty skips the probe
+pub fn dns_probe_domain() -> Option<String> {
+    match std::env::var("HEALTH_DNS_PROBE_DOMAIN") {
+        Ok(domain) => Some(domain.trim().to_string()).filter(|d| !d.is_empty()),
+        Err(_) => Some(DEFAULT_DNS_PROBE_DOMAIN.to_string()),
+    }
+}
+
+async fn probe(name: &str, check: impl Future<Output = bool>) -> DependencyCheck {
+    let started = Instant::now();
+    let healthy = tokio::time::timeout(probe_timeout(), check)
+        .await
+        .unwrap_or(false);
+    DependencyCheck {
+        name: name.to_string(),
+        status: if healthy { "UP" } else { "DOWN" }.to_string(),
+        latency_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Pings MongoDB and Redis and runs the resolver self-test, concurrently
+///
+/// A dependency missing from the app data (`None`) is reported down, so readiness
+/// never passes on a misconfigured instance.
+pub async fn check_dependencies(
+    mongo_client: Option<&MongoClient>,
+    history: Option<&HealthHistory>,
+) -> Vec<DependencyCheck> {
+    let dns_domain = dns_probe_domain();
+    let (mongodb, redis, dns) = tokio::join!(
+        probe("mongodb", async {
+            match mongo_client {
+                Some(client) => health_history::ping_mongodb(client).await,
+                None => false,
+            }
+        }),
+        probe("redis", async {
+            match history {
+                Some(history) => history.ping_redis().await,
+                None => false,
+            }
+        }),
+        async {
+            match &dns_domain {
+                Some(domain) => Some(probe("dns", dnsmx::resolver_self_test(domain)).await),
+                None => None,
+            }
+        },
+    );
+
+    let mut checks = vec![mongodb, redis];
+    checks.extend(dns);
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unconfigured_dependencies_are_down() {
+        let checks = check_dependencies(None, None).await;
+
+        assert_eq!(checks[0].name, "mongodb");
+        assert_eq!(checks[1].name, "redis");
+        assert!(checks[..2].iter().all(|check| !check.is_up()));
+    }
+
+    #[tokio::test]
+    async fn test_probe_times_out() {
+        let check = probe("slow", async {
+            tokio::time::sleep(probe_timeout() * 2).await;
+            true
+        })
+        .await;
+
+        assert!(!check.is_up());
+        assert!(check.latency_ms >= probe_timeout().as_millis() as u64);
+    }
+}
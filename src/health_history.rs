@@ -0,0 +1,347 @@
+use crate::redis_pool::RedisPool;
+use chrono::Utc;
+use mongodb::Client as MongoClient;
+use mongodb::bson::doc;
+use redis::{AsyncCommands, RedisError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Dependencies probed by [`monitor`]
+pub const DEPENDENCIES: [&str; 2] = ["mongodb", "redis"];
+
+/// Windows reported as uptime percentages, with their length in seconds
+pub const UPTIME_WINDOWS: [(&str, i64); 3] = [
+    ("24h", 24 * 3600),
+    ("7d", 7 * 24 * 3600),
+    ("30d", 30 * 24 * 3600),
+];
+
+/// Transitions kept per dependency; older ones are trimmed on write
+pub const MAX_TRANSITIONS: isize = 1000;
+
+pub const DEFAULT_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// Seconds between probes (`HEALTH_CHECK_INTERVAL_SECS`)
+pub fn check_interval() -> Duration {
+    let secs = std::env::var("HEALTH_CHECK_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_CHECK_INTERVAL_SECS);
+    Duration::from_secs(secs)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DependencyStatus {
+    Up,
+    Down,
+}
+
+/// A dependency entering a status at a Unix timestamp
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transition {
+    pub status: DependencyStatus,
+    pub at: i64,
+}
+
+/// History of one dependency as shown on the status page
+#[derive(Debug, Serialize)]
+pub struct DependencyHistory {
+    pub name: String,
+    /// Latest recorded status; `None` before the first probe
+    pub status: Option<DependencyStatus>,
+    pub since: Option<i64>,
+    /// Percentage per window (`24h`, `7d`, `30d`); `None` when nothing was observed
+    pub uptime: BTreeMap<String, Option<f64>>,
+    /// Transitions within the longest window, newest first
+    pub transitions: Vec<Transition>,
+}
+
+/// Share of `[window_start, now]` spent `UP`, as a percentage
+///
+/// `transitions` must be sorted oldest first. Time before the first transition is
+/// unknown and left out, so a dependency monitored for an hour reports uptime over
+/// that hour rather than counting the rest of the window as downtime.
+pub fn uptime_percent(transitions: &[Transition], window_start: i64, now: i64) -> Option<f64> {
+    let mut state = transitions
+        .iter()
+        .rev()
+        .find(|t| t.at <= window_start)
+        .map(|t| (t.status, window_start));
+    let mut observed = 0;
+    let mut up = 0;
+
+    for transition in transitions
+        .iter()
+        .filter(|t| t.at > window_start && t.at <= now)
+    {
+        if let Some((status, from)) = state {
+            let span = transition.at - from;
+            observed += span;
+            if status == DependencyStatus::Up {
+                up += span;
+            }
+        }
+        state = Some((transition.status, transition.at));
+    }
+    if let Some((status, from)) = state {
+        let span = now - from;
+        observed += span;
+        if status == DependencyStatus::Up {
+            up += span;
+        }
+    }
+
+    (observed > 0).then(|| up as f64 * 100.0 / observed as f64)
+}
+
+/// Builds a dependency's report from its transitions (oldest first)
+pub fn dependency_history(name: &str, transitions: &[Transition], now: i64) -> DependencyHistory {
+    let latest = transitions.last();
+    let longest = UPTIME_WINDOWS
+        .iter()
+        .map(|(_, secs)| *secs)
+        .max()
+        .unwrap_or(0);
+
+    DependencyHistory {
+        name: name.to_string(),
+        status: latest.map(|t| t.status),
+        since: latest.map(|t| t.at),
+        uptime: UPTIME_WINDOWS
+            .iter()
+            .map(|(label, secs)| {
+                (
+                    label.to_string(),
+                    uptime_percent(transitions, now - secs, now),
+                )
+            })
+            .collect(),
+        transitions: transitions
+            .iter()
+            .rev()
+            .take_while(|t| t.at > now - longest)
+            .cloned()
+            .collect(),
+    }
+}
+
+/// A period a dependency was down
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Incident {
+    pub dependency: String,
+    pub started_at: i64,
+    /// `None` while the outage is ongoing
+    pub resolved_at: Option<i64>,
+}
+
+/// Outages in a dependency's transitions (oldest first), newest first
+pub fn incidents(name: &str, transitions: &[Transition]) -> Vec<Incident> {
+    let mut incidents: Vec<Incident> = Vec::new();
+    for transition in transitions {
+        let ongoing = incidents.last().is_some_and(|i| i.resolved_at.is_none());
+        match transition.status {
+            DependencyStatus::Down if !ongoing => incidents.push(Incident {
+                dependency: name.to_string(),
+                started_at: transition.at,
+                resolved_at: None,
+            }),
+            DependencyStatus::Up if ongoing => {
+                if let Some(incident) = incidents.last_mut() {
+                    incident.resolved_at = Some(transition.at);
+                }
+            }
+            _ => {}
+        }
+    }
+    incidents.reverse();
+    incidents
+}
+
+/// Dependency health transitions, stored in Redis sorted sets (`health:transitions:{name}`)
+#[derive(Clone)]
+pub struct HealthHistory {
+    client: RedisPool,
+}
+
+impl HealthHistory {
+    pub fn new(redis_url: &str) -> Result<Self, RedisError> {
+        Ok(Self {
+            client: RedisPool::new(redis_url)?,
+        })
+    }
+
+    pub fn transitions_key(name: &str) -> String {
+        format!("health:transitions:{}", name)
+    }
+
+    pub async fn record(&self, name: &str, transition: &Transition) -> Result<(), RedisError> {
+        let mut conn = self.client.get().await?;
+        let key = Self::transitions_key(name);
+        let member = serde_json::to_string(transition).unwrap_or_default();
+
+        redis::pipe()
+            .zadd(&key, member, transition.at)
+            .ignore()
+            .zremrangebyrank(&key, 0, -(MAX_TRANSITIONS + 1))
+            .ignore()
+            .query_async(&mut conn)
+            .await
+    }
+
+    /// Recorded transitions of a dependency, oldest first
+    pub async fn transitions(&self, name: &str) -> Result<Vec<Transition>, RedisError> {
+        let mut conn = self.client.get().await?;
+        let members: Vec<String> = conn.zrange(Self::transitions_key(name), 0, -1).await?;
+        Ok(members
+            .iter()
+            .filter_map(|member| serde_json::from_str(member).ok())
+            .collect())
+    }
+
+    pub async fn report(&self, now: i64) -> Result<Vec<DependencyHistory>, RedisError> {
+        let mut report = Vec::with_capacity(DEPENDENCIES.len());
+        for name in DEPENDENCIES {
+            let transitions = self.transitions(name).await?;
+            report.push(dependency_history(name, &transitions, now));
+        }
+        Ok(report)
+    }
+
+    /// Pings through the shared pool, so an exhausted pool also reads as down
+    pub async fn ping_redis(&self) -> bool {
+        self.client.ping().await
+    }
+}
+
+pub async fn ping_mongodb(mongo_client: &MongoClient) -> bool {
+    mongo_client
+        .database("admin")
+        .run_command(doc! { "ping": 1 })
+        .await
+        .is_ok()
+}
+
+/// Probes every dependency each [`check_interval`] and records status changes
+///
+/// Only transitions are stored. A transition that can't be written (Redis itself
+/// being down, typically) is kept and retried on the next probe, so Redis outages
+/// still show up in the history once it is back.
+pub async fn monitor(history: HealthHistory, mongo_client: MongoClient) {
+    let mut current: BTreeMap<&str, DependencyStatus> = BTreeMap::new();
+    let mut unsaved: Vec<(&str, Transition)> = Vec::new();
+    let mut interval = tokio::time::interval(check_interval());
+
+    loop {
+        interval.tick().await;
+        let probes = [
+            ("mongodb", ping_mongodb(&mongo_client).await),
+            ("redis", history.ping_redis().await),
+        ];
+
+        let now = Utc::now().timestamp();
+        for (name, healthy) in probes {
+            let status = if healthy {
+                DependencyStatus::Up
+            } else {
+                DependencyStatus::Down
+            };
+            if current.insert(name, status) != Some(status) {
+                unsaved.push((name, Transition { status, at: now }));
+            }
+        }
+
+        let mut pending = std::mem::take(&mut unsaved).into_iter();
+        while let Some((name, transition)) = pending.next() {
+            if history.record(name, &transition).await.is_err() {
+                unsaved.push((name, transition));
+                unsaved.extend(pending);
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transition(status: DependencyStatus, at: i64) -> Transition {
+        Transition { status, at }
+    }
+
+    #[test]
+    fn test_uptime_over_window() {
+        use DependencyStatus::*;
+        let transitions = [
+            transition(Up, 0),
+            transition(Down, 900),
+            transition(Up, 1000),
+        ];
+
+        assert_eq!(uptime_percent(&transitions, 0, 2000), Some(95.0));
+        // The window starts while the dependency is down
+        assert_eq!(uptime_percent(&transitions, 950, 1050), Some(50.0));
+        assert_eq!(uptime_percent(&[], 0, 2000), None);
+    }
+
+    #[test]
+    fn test_unobserved_time_is_excluded() {
+        let transitions = [transition(DependencyStatus::Up, 1500)];
+
+        assert_eq!(uptime_percent(&transitions, 0, 2000), Some(100.0));
+    }
+
+    #[test]
+    fn test_dependency_history_report() {
+        use DependencyStatus::*;
+        let now = 40 * 24 * 3600;
+        let transitions = [
+            transition(Up, 0),
+            transition(Down, now - 3600),
+            transition(Up, now - 1800),
+        ];
+        let history = dependency_history("redis", &transitions, now);
+
+        assert_eq!(history.status, Some(Up));
+        assert_eq!(history.since, Some(now - 1800));
+        assert_eq!(
+            history.uptime["24h"],
+            Some(100.0 - 1800.0 * 100.0 / 86400.0)
+        );
+        // Only transitions inside the 30-day window are listed
+        assert_eq!(history.transitions.len(), 2);
+        assert_eq!(history.transitions[0].at, now - 1800);
+    }
+
+    #[test]
+    fn test_incidents_pair_outages_with_recoveries() {
+        use DependencyStatus::*;
+        let transitions = [
+            transition(Up, 0),
+            transition(Down, 100),
+            // Repeated statuses (e.g. after a restart) don't split an outage
+            transition(Down, 150),
+            transition(Up, 200),
+            transition(Down, 500),
+        ];
+
+        assert_eq!(
+            incidents("mongodb", &transitions),
+            vec![
+                Incident {
+                    dependency: "mongodb".to_string(),
+                    started_at: 500,
+                    resolved_at: None,
+                },
+                Incident {
+                    dependency: "mongodb".to_string(),
+                    started_at: 100,
+                    resolved_at: Some(200),
+                },
+            ]
+        );
+    }
+}
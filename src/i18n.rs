@@ -0,0 +1,395 @@
+use crate::error::ApiError;
+use crate::routes::email::EmailValidationError;
+
+/// Language of the `message` of a validation result
+///
+/// Codes stay the same in every language; only messages are translated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    Es,
+    De,
+    Fr,
+}
+
+/// Catalog key of the message of a valid address
+pub const VALID: &str = "VALID";
+
+impl Lang {
+    pub const ALL: [Lang; 4] = [Lang::En, Lang::Es, Lang::De, Lang::Fr];
+
+    /// Parses a language tag by its primary subtag, e.g. `de` or `de-AT`
+    pub fn parse(tag: &str) -> Option<Self> {
+        let primary = tag.trim().split(['-', '_']).next()?;
+        match primary.to_ascii_lowercase().as_str() {
+            "en" => Some(Lang::En),
+            "es" => Some(Lang::Es),
+            "de" => Some(Lang::De),
+            "fr" => Some(Lang::Fr),
+            _ => None,
+        }
+    }
+
+    /// The supported language an `Accept-Language` header prefers most, e.g.
+    /// `fr-CH, fr;q=0.9, en;q=0.8`; `None` when it names none of them
+    ///
+    /// Ties go to the language listed first; `q=0` excludes a language and malformed
+    /// weights skip the entry.
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        let mut best: Option<(Lang, f32)> = None;
+        for entry in header.split(',') {
+            let mut parts = entry.split(';');
+            let Some(lang) = parts.next().and_then(Lang::parse) else {
+                continue;
+            };
+            let weight = match parts.find_map(|param| param.trim().strip_prefix("q=")) {
+                Some(q) => match q.trim().parse::<f32>() {
+                    Ok(q) if (0.0..=1.0).contains(&q) => q,
+                    _ => continue,
+                },
+                None => 1.0,
+            };
+            if weight > 0.0 && best.is_none_or(|(_, best)| weight > best) {
+                best = Some((lang, weight));
+            }
+        }
+        best.map(|(lang, _)| lang)
+    }
+
+    /// The language a request asks for: the `lang` query parameter, else its
+    /// `Accept-Language` header
+    ///
+    /// `None` keeps the checks' own English wording. An unsupported `lang` is refused
+    /// with `INVALID_LANG`, while an unsupported header is ignored like a missing one.
+    pub fn requested(
+        lang: Option<&str>,
+        http_req: &actix_web::HttpRequest,
+    ) -> Result<Option<Self>, ApiError> {
+        if let Some(lang) = lang {
+            return Lang::parse(lang).map(Some).ok_or_else(|| {
+                ApiError::validation("INVALID_LANG", "lang must be one of en, es, de, fr")
+            });
+        }
+        Ok(http_req
+            .headers()
+            .get(actix_web::http::header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Lang::from_accept_language))
+    }
+
+    /// Message for a verdict code, or [`VALID`], in this language
+    pub fn message(self, code: &str) -> Option<&'static str> {
+        let catalog = match self {
+            Lang::En => EN,
+            Lang::Es => ES,
+            Lang::De => DE,
+            Lang::Fr => FR,
+        };
+        catalog
+            .iter()
+            .find(|(key, _)| *key == code)
+            .map(|(_, message)| *message)
+    }
+}
+
+/// Replaces a result's message with the catalog's for `lang`
+///
+/// Catalog messages are the same for every address failing a check, so they can be
+/// shown to end users as they are; details such as the rule an address breaks remain
+/// in the structured fields. Codes missing from the catalog keep their message.
+pub fn localize(error: &mut EmailValidationError, lang: Option<Lang>) {
+    if let Some(message) = lang.and_then(|lang| lang.message(&error.code)) {
+        error.message = message.to_string();
+    }
+}
+
+type Catalog = &'static [(&'static str, &'static str)];
+
+const EN: Catalog = &[
+    (VALID, "Email address is valid"),
+    ("CUSTOM_BLOCKED", "This email address is not accepted"),
+    (
+        "DATABASE_ERROR",
+        "The email address could not be checked; please try again later",
+    ),
+    (
+        "DISPOSABLE_EMAIL",
+        "Disposable email addresses are not accepted",
+    ),
+    (
+        "DNS_UNAVAILABLE",
+        "The email domain could not be checked; please try again later",
+    ),
+    ("INVALID_DOMAIN", "The email domain does not receive mail"),
+    ("INVALID_INPUT", "This entry is not an email address"),
+    (
+        "INVALID_SYNTAX",
+        "The email address is not correctly formatted",
+    ),
+    ("INVALID_TLD", "The email domain does not exist"),
+    ("KNOWN_TRAP", "This email address is not accepted"),
+    (
+        "PREVIOUS_BOUNCE",
+        "Mail to this email address could not be delivered before",
+    ),
+    (
+        "PREVIOUS_BOUNCER",
+        "Mail to this email address could not be delivered before",
+    ),
+    (
+        "PREVIOUS_COMPLAINT",
+        "This email address does not accept our mail",
+    ),
+    (
+        "PROCESSING_ERROR",
+        "The email address could not be checked; please try again later",
+    ),
+    (
+        "ROLE_BASED_EMAIL",
+        "Please use a personal email address, not a shared one such as info@",
+    ),
+    ("SUPPRESSED", "Mail cannot be sent to this email address"),
+    (
+        "UNDELIVERABLE",
+        "Mail to this email address would not be delivered",
+    ),
+];
+
+const ES: Catalog = &[
+    (VALID, "La dirección de correo electrónico es válida"),
+    (
+        "CUSTOM_BLOCKED",
+        "Esta dirección de correo electrónico no se acepta",
+    ),
+    (
+        "DATABASE_ERROR",
+        "No se pudo comprobar la dirección de correo electrónico; inténtalo de nuevo más tarde",
+    ),
+    (
+        "DISPOSABLE_EMAIL",
+        "No se aceptan direcciones de correo electrónico desechables",
+    ),
+    (
+        "DNS_UNAVAILABLE",
+        "No se pudo comprobar el dominio del correo electrónico; inténtalo de nuevo más tarde",
+    ),
+    (
+        "INVALID_DOMAIN",
+        "El dominio del correo electrónico no recibe correo",
+    ),
+    (
+        "INVALID_INPUT",
+        "Esta entrada no es una dirección de correo electrónico",
+    ),
+    (
+        "INVALID_SYNTAX",
+        "La dirección de correo electrónico no tiene un formato válido",
+    ),
+    ("INVALID_TLD", "El dominio del correo electrónico no existe"),
+    (
+        "KNOWN_TRAP",
+        "Esta dirección de correo electrónico no se acepta",
+    ),
+    (
+        "PREVIOUS_BOUNCE",
+        "El correo enviado a esta dirección no se pudo entregar anteriormente",
+    ),
+    (
+        "PREVIOUS_BOUNCER",
+        "El correo enviado a esta dirección no se pudo entregar anteriormente",
+    ),
+    (
+        "PREVIOUS_COMPLAINT",
+        "Esta dirección de correo electrónico no acepta nuestros mensajes",
+    ),
+    (
+        "PROCESSING_ERROR",
+        "No se pudo comprobar la dirección de correo electrónico; inténtalo de nuevo más tarde",
+    ),
+    (
+        "ROLE_BASED_EMAIL",
+        "Usa una dirección de correo electrónico personal, no una compartida como info@",
+    ),
+    (
+        "SUPPRESSED",
+        "No se pueden enviar mensajes a esta dirección de correo electrónico",
+    ),
+    (
+        "UNDELIVERABLE",
+        "Los mensajes a esta dirección de correo electrónico no se entregarían",
+    ),
+];
+
+const DE: Catalog = &[
+    (VALID, "Die E-Mail-Adresse ist gültig"),
+    (
+        "CUSTOM_BLOCKED",
+        "Diese E-Mail-Adresse wird nicht akzeptiert",
+    ),
+    (
+        "DATABASE_ERROR",
+        "Die E-Mail-Adresse konnte nicht geprüft werden; bitte versuchen Sie es später erneut",
+    ),
+    (
+        "DISPOSABLE_EMAIL",
+        "Wegwerf-E-Mail-Adressen werden nicht akzeptiert",
+    ),
+    (
+        "DNS_UNAVAILABLE",
+        "Die Domain der E-Mail-Adresse konnte nicht geprüft werden; bitte versuchen Sie es später erneut",
+    ),
+    (
+        "INVALID_DOMAIN",
+        "Die Domain der E-Mail-Adresse empfängt keine E-Mails",
+    ),
+    ("INVALID_INPUT", "Dieser Eintrag ist keine E-Mail-Adresse"),
+    (
+        "INVALID_SYNTAX",
+        "Die E-Mail-Adresse ist nicht korrekt formatiert",
+    ),
+    (
+        "INVALID_TLD",
+        "Die Domain der E-Mail-Adresse existiert nicht",
+    ),
+    ("KNOWN_TRAP", "Diese E-Mail-Adresse wird nicht akzeptiert"),
+    (
+        "PREVIOUS_BOUNCE",
+        "E-Mails an diese Adresse konnten bereits früher nicht zugestellt werden",
+    ),
+    (
+        "PREVIOUS_BOUNCER",
+        "E-Mails an diese Adresse konnten bereits früher nicht zugestellt werden",
+    ),
+    (
+        "PREVIOUS_COMPLAINT",
+        "Diese E-Mail-Adresse nimmt unsere E-Mails nicht an",
+    ),
+    (
+        "PROCESSING_ERROR",
+        "Die E-Mail-Adresse konnte nicht geprüft werden; bitte versuchen Sie es später erneut",
+    ),
+    (
+        "ROLE_BASED_EMAIL",
+        "Bitte verwenden Sie eine persönliche E-Mail-Adresse, keine gemeinsam genutzte wie info@",
+    ),
+    (
+        "SUPPRESSED",
+        "An diese E-Mail-Adresse können keine E-Mails gesendet werden",
+    ),
+    (
+        "UNDELIVERABLE",
+        "E-Mails an diese Adresse würden nicht zugestellt",
+    ),
+];
+
+const FR: Catalog = &[
+    (VALID, "L'adresse e-mail est valide"),
+    ("CUSTOM_BLOCKED", "Cette adresse e-mail n'est pas acceptée"),
+    (
+        "DATABASE_ERROR",
+        "L'adresse e-mail n'a pas pu être vérifiée ; veuillez réessayer plus tard",
+    ),
+    (
+        "DISPOSABLE_EMAIL",
+        "Les adresses e-mail jetables ne sont pas acceptées",
+    ),
+    (
+        "DNS_UNAVAILABLE",
+        "Le domaine de l'adresse e-mail n'a pas pu être vérifié ; veuillez réessayer plus tard",
+    ),
+    (
+        "INVALID_DOMAIN",
+        "Le domaine de l'adresse e-mail ne reçoit pas de courrier",
+    ),
+    ("INVALID_INPUT", "Cette entrée n'est pas une adresse e-mail"),
+    (
+        "INVALID_SYNTAX",
+        "L'adresse e-mail n'est pas correctement formatée",
+    ),
+    ("INVALID_TLD", "Le domaine de l'adresse e-mail n'existe pas"),
+    ("KNOWN_TRAP", "Cette adresse e-mail n'est pas acceptée"),
+    (
+        "PREVIOUS_BOUNCE",
+        "Les e-mails envoyés à cette adresse n'ont déjà pas pu être distribués",
+    ),
+    (
+        "PREVIOUS_BOUNCER",
+        "Les e-mails envoyés à cette adresse n'ont déjà pas pu être distribués",
+    ),
+    (
+        "PREVIOUS_COMPLAINT",
+        "Cette adresse e-mail n'accepte pas nos messages",
+    ),
+    (
+        "PROCESSING_ERROR",
+        "L'adresse e-mail n'a pas pu être vérifiée ; veuillez réessayer plus tard",
+    ),
+    (
+        "ROLE_BASED_EMAIL",
+        "Veuillez utiliser une adresse e-mail personnelle, et non une adresse partagée comme info@",
+    ),
+    (
+        "SUPPRESSED",
+        "Aucun e-mail ne peut être envoyé à cette adresse",
+    ),
+    (
+        "UNDELIVERABLE",
+        "Les e-mails envoyés à cette adresse ne seraient pas distribués",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_codes::VerdictCode;
+
+    #[test]
+    fn test_every_verdict_is_translated() {
+        for lang in Lang::ALL {
+            assert!(lang.message(VALID).is_some());
+            for code in VerdictCode::ALL {
+                assert!(
+                    lang.message(code.as_str()).is_some(),
+                    "{} has no {:?} message",
+                    code.as_str(),
+                    lang
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_accept_language() {
+        assert_eq!(Lang::parse("de-AT"), Some(Lang::De));
+        assert_eq!(Lang::parse("pt"), None);
+        assert_eq!(
+            Lang::from_accept_language("fr-CH, fr;q=0.9, en;q=0.8"),
+            Some(Lang::Fr)
+        );
+        assert_eq!(
+            Lang::from_accept_language("pt-BR, es;q=0.5, de;q=0.7"),
+            Some(Lang::De)
+        );
+        assert_eq!(Lang::from_accept_language("es;q=0, *;q=0.5"), None);
+        assert_eq!(Lang::from_accept_language("de;q=x, en"), Some(Lang::En));
+    }
+
+    #[test]
+    fn test_localize_keeps_code() {
+        let mut error = EmailValidationError {
+            code: "DISPOSABLE_EMAIL".to_string(),
+            message: "The email address domain is a provider of disposable email addresses"
+                .to_string(),
+            violations: Vec::new(),
+        };
+        localize(&mut error, None);
+        assert!(error.message.starts_with("The email address domain"));
+
+        localize(&mut error, Some(Lang::Es));
+        assert_eq!(error.code, "DISPOSABLE_EMAIL");
+        assert_eq!(
+            error.message,
+            "No se aceptan direcciones de correo electrónico desechables"
+        );
+    }
+}
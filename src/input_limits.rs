@@ -0,0 +1,136 @@
+use crate::error::ApiError;
+use actix_web::error::JsonPayloadError;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use serde_json::json;
+
+/// Largest JSON body accepted when `MAX_JSON_PAYLOAD_BYTES` is unset; fits a full
+/// default-sized bulk batch of maximum-length addresses
+pub const DEFAULT_MAX_JSON_PAYLOAD_BYTES: usize = 4 * 1024 * 1024;
+
+/// Longest address accepted in an email field, in bytes (RFC 3696 errata)
+pub const MAX_EMAIL_LENGTH: usize = 320;
+
+/// Largest JSON body accepted by the API (`MAX_JSON_PAYLOAD_BYTES`)
+pub fn max_json_payload_bytes() -> usize {
+    std::env::var("MAX_JSON_PAYLOAD_BYTES")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|bytes| *bytes > 0)
+        .unwrap_or(DEFAULT_MAX_JSON_PAYLOAD_BYTES)
+}
+
+/// Maps body extraction failures to the error envelope
+///
+/// Oversized bodies are cut off while streaming, before they are buffered, and
+/// answered with 413 `PAYLOAD_TOO_LARGE`; bodies that parse but don't match the
+/// expected shape get 422 `INVALID_BODY`.
+pub fn json_error(err: &JsonPayloadError, max_bytes: usize) -> ApiError {
+    match err {
+        JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+            ApiError::validation(
+                "PAYLOAD_TOO_LARGE",
+                format!("Request bodies are limited to {} bytes", max_bytes),
+            )
+            .with_status(StatusCode::PAYLOAD_TOO_LARGE)
+            .with_details(json!({ "max_bytes": max_bytes }))
+        }
+        JsonPayloadError::ContentType => ApiError::validation(
+            "UNSUPPORTED_MEDIA_TYPE",
+            "Request bodies must be sent as application/json",
+        )
+        .with_status(StatusCode::UNSUPPORTED_MEDIA_TYPE),
+        JsonPayloadError::Deserialize(e) if e.is_data() => {
+            ApiError::validation("INVALID_BODY", e.to_string())
+                .with_status(StatusCode::UNPROCESSABLE_ENTITY)
+        }
+        _ => ApiError::validation("INVALID_BODY", err.to_string()),
+    }
+}
+
+/// JSON extractor settings for every API route
+pub fn json_config() -> web::JsonConfig {
+    let max_bytes = max_json_payload_bytes();
+    web::JsonConfig::default()
+        .limit(max_bytes)
+        .error_handler(move |err, _req| json_error(&err, max_bytes).into())
+}
+
+/// Rejects addresses longer than [`MAX_EMAIL_LENGTH`] or containing control
+/// characters with 422 `INVALID_FIELD`; `field` names the offending value
+///
+/// Surrounding whitespace, such as a trailing newline from a pasted list, is
+/// ignored as it is trimmed before validation anyway.
+pub fn check_email_field(field: &str, email: &str) -> Result<(), ApiError> {
+    let email = email.trim();
+    let problem = if email.len() > MAX_EMAIL_LENGTH {
+        format!("{} exceeds {} bytes", field, MAX_EMAIL_LENGTH)
+    } else if email.chars().any(char::is_control) {
+        format!("{} contains control characters", field)
+    } else {
+        return Ok(());
+    };
+    Err(ApiError::validation("INVALID_FIELD", problem)
+        .with_status(StatusCode::UNPROCESSABLE_ENTITY)
+        .with_details(json!({ "field": field, "max_length": MAX_EMAIL_LENGTH })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, HttpResponse, post, test as actix_test};
+
+    #[test]
+    fn test_check_email_field() {
+        assert!(check_email_field("email", " user@example.com\n").is_ok());
+
+        let long = format!("{}@example.com", "a".repeat(MAX_EMAIL_LENGTH));
+        let err = check_email_field("emails[3]", &long).unwrap_err();
+        assert_eq!(err.code(), "INVALID_FIELD");
+        assert_eq!(
+            actix_web::ResponseError::status_code(&err),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+
+        assert!(check_email_field("email", "us\u{0}er@example.com").is_err());
+        assert!(check_email_field("email", "user@exa\u{1b}mple.com").is_err());
+    }
+
+    #[post("/echo")]
+    async fn echo(body: web::Json<serde_json::Value>) -> HttpResponse {
+        HttpResponse::Ok().json(body.into_inner())
+    }
+
+    #[actix_web::test]
+    async fn test_json_limits() {
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(
+                    web::JsonConfig::default()
+                        .limit(64)
+                        .error_handler(|err, _req| json_error(&err, 64).into()),
+                )
+                .service(echo),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::post()
+            .uri("/echo")
+            .set_json(json!({ "emails": ["a".repeat(100)] }))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], "PAYLOAD_TOO_LARGE");
+        assert_eq!(body["error"]["details"]["max_bytes"], 64);
+
+        let req = actix_test::TestRequest::post()
+            .uri("/echo")
+            .set_json(json!({ "emails": [] }))
+            .to_request();
+        assert_eq!(
+            actix_test::call_service(&app, req).await.status(),
+            StatusCode::OK
+        );
+    }
+}
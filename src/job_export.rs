@@ -0,0 +1,214 @@
+use crate::job_summary::verdict;
+use crate::routes::email::{BulkEmailValidationResult, csv_field};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use serde::Serialize;
+
+/// Column names shared by every export format
+pub const EXPORT_COLUMNS: [&str; 4] = ["email", "verdict", "error_code", "score"];
+
+/// Data rows an XLSX worksheet holds below its header row
+pub const XLSX_MAX_ROWS: usize = 1_048_575;
+
+/// File format of a job results export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.trim().to_ascii_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "jsonl" | "ndjson" => Some(ExportFormat::Jsonl),
+            "xlsx" => Some(ExportFormat::Xlsx),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Jsonl => "application/x-ndjson",
+            ExportFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// One exported result, annotated for re-import into an ESP
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportRow {
+    pub email: String,
+    /// `VALID` or the error code, as counted in job summaries
+    pub verdict: String,
+    pub error_code: Option<String>,
+    /// Deliverability from 0 to 100, see [`score`]
+    pub score: u8,
+}
+
+/// Deliverability score of a verdict
+///
+/// 100 for valid addresses, 60 for role accounts (deliverable but rarely a person),
+/// 50 when the check itself failed and the address is unverified, 0 for everything
+/// that should be suppressed (bad syntax or domain, disposable, blocklisted).
+pub fn score(verdict: &str) -> u8 {
+    match verdict {
+        "VALID" => 100,
+        "ROLE_BASED_EMAIL" => 60,
+        "UNKNOWN" | "DATABASE_ERROR" | "PROCESSING_ERROR" => 50,
+        _ => 0,
+    }
+}
+
+impl From<&BulkEmailValidationResult> for ExportRow {
+    fn from(row: &BulkEmailValidationResult) -> Self {
+        let verdict = verdict(&row.validation).to_string();
+        ExportRow {
+            email: row.email.clone(),
+            score: score(&verdict),
+            error_code: row.validation.error.as_ref().map(|e| e.code.clone()),
+            verdict,
+        }
+    }
+}
+
+/// First bytes of a streamed export; XLSX has no header of its own
+pub fn header(format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Csv => format!("{}\n", EXPORT_COLUMNS.join(",")),
+        ExportFormat::Jsonl | ExportFormat::Xlsx => String::new(),
+    }
+}
+
+/// Renders one stored result chunk as CSV lines or JSON lines
+pub fn render_chunk(format: ExportFormat, rows: &[BulkEmailValidationResult]) -> String {
+    let mut out = String::new();
+    for row in rows.iter().map(ExportRow::from) {
+        match format {
+            ExportFormat::Csv => out.push_str(&format!(
+                "{},{},{},{}\n",
+                csv_field(&row.email),
+                csv_field(&row.verdict),
+                csv_field(row.error_code.as_deref().unwrap_or_default()),
+                row.score
+            )),
+            ExportFormat::Jsonl | ExportFormat::Xlsx => {
+                if let Ok(line) = serde_json::to_string(&row) {
+                    out.push_str(&line);
+                    out.push('\n');
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Builds an XLSX workbook with a bold header row and one row per result
+///
+/// Unlike CSV and JSONL the archive can't be streamed, so callers should check the
+/// row count against [`XLSX_MAX_ROWS`] first.
+pub fn xlsx_workbook(rows: &[ExportRow]) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("Results")?;
+
+    let bold = Format::new().set_bold();
+    for (col, name) in EXPORT_COLUMNS.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *name, &bold)?;
+    }
+    for (index, row) in rows.iter().enumerate() {
+        let line = index as u32 + 1;
+        worksheet.write_string(line, 0, &row.email)?;
+        worksheet.write_string(line, 1, &row.verdict)?;
+        if let Some(code) = &row.error_code {
+            worksheet.write_string(line, 2, code)?;
+        }
+        worksheet.write_number(line, 3, row.score)?;
+    }
+    worksheet.set_freeze_panes(1, 0)?;
+
+    workbook.save_to_buffer()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::email::{EmailValidationError, EmailValidationResponse};
+
+    fn row(email: &str, error_code: Option<&str>) -> BulkEmailValidationResult {
+        BulkEmailValidationResult {
+            email: email.to_string(),
+            validation: EmailValidationResponse {
+                is_valid: error_code.is_none(),
+                domain: None,
+                has_tag: None,
+                base_address: None,
+                status: error_code.is_none().then(|| "VALID".to_string()),
+                error: error_code.map(|code| EmailValidationError {
+                    code: code.to_string(),
+                    message: String::new(),
+                    violations: Vec::new(),
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn test_format_parsing() {
+        assert_eq!(ExportFormat::parse("CSV"), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::parse("ndjson"), Some(ExportFormat::Jsonl));
+        assert_eq!(ExportFormat::parse("xlsx"), Some(ExportFormat::Xlsx));
+        assert_eq!(ExportFormat::parse("pdf"), None);
+    }
+
+    #[test]
+    fn test_csv_and_jsonl_rows() {
+        let rows = [
+            row("a@example.com", None),
+            row("\"odd\",name@example.com", Some("INVALID_SYNTAX")),
+            row("info@example.com", Some("ROLE_BASED_EMAIL")),
+        ];
+
+        let csv = header(ExportFormat::Csv) + &render_chunk(ExportFormat::Csv, &rows);
+        assert_eq!(
+            csv,
+            "email,verdict,error_code,score\n\
+             a@example.com,VALID,,100\n\
+             \"\"\"odd\"\",name@example.com\",INVALID_SYNTAX,INVALID_SYNTAX,0\n\
+             info@example.com,ROLE_BASED_EMAIL,ROLE_BASED_EMAIL,60\n"
+        );
+
+        let jsonl = render_chunk(ExportFormat::Jsonl, &rows[..1]);
+        let parsed: serde_json::Value = serde_json::from_str(jsonl.trim_end()).unwrap();
+        assert_eq!(
+            parsed,
+            serde_json::json!({
+                "email": "a@example.com",
+                "verdict": "VALID",
+                "error_code": null,
+                "score": 100
+            })
+        );
+    }
+
+    #[test]
+    fn test_xlsx_workbook_is_a_zip_archive() {
+        let rows: Vec<ExportRow> = [row("a@example.com", None)]
+            .iter()
+            .map(ExportRow::from)
+            .collect();
+        let bytes = xlsx_workbook(&rows).unwrap();
+        assert!(bytes.starts_with(b"PK"));
+    }
+}
@@ -0,0 +1,186 @@
+use crate::pii::{self, Store};
+use mongodb::bson::{Binary, DateTime, doc, spec::BinarySubtype};
+use mongodb::{Client, Collection};
+use redis::{ErrorKind, RedisError};
+use serde::{Deserialize, Serialize};
+
+/// First bytes of every zstd frame; JSON starts with `{` or `[`
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Detail of the error returned for payloads above [`PayloadLimits::max_bytes`]
+const TOO_LARGE: &str = "job payload exceeds JOB_PAYLOAD_MAX_BYTES";
+
+/// How much Redis memory a queued job may take
+///
+/// Read from the environment:
+/// - `JOB_PAYLOAD_COMPRESS_BYTES`: job JSON above this is stored zstd-compressed
+///   (default 8 KiB)
+/// - `JOB_PAYLOAD_SPILL_BYTES`: compressed payloads above this move their address
+///   list to MongoDB, keeping only the job record in Redis (default 256 KiB)
+/// - `JOB_PAYLOAD_MAX_BYTES`: largest payload stored in Redis; bigger jobs are
+///   refused (default 4 MiB)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayloadLimits {
+    pub compress_above: usize,
+    pub spill_above: usize,
+    pub max_bytes: usize,
+}
+
+impl Default for PayloadLimits {
+    fn default() -> Self {
+        Self {
+            compress_above: 8 * 1024,
+            spill_above: 256 * 1024,
+            max_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+fn read_bytes(name: &str) -> Option<usize> {
+    std::env::var(name).ok().and_then(|v| v.trim().parse().ok())
+}
+
+impl PayloadLimits {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            compress_above: read_bytes("JOB_PAYLOAD_COMPRESS_BYTES")
+                .unwrap_or(defaults.compress_above),
+            spill_above: read_bytes("JOB_PAYLOAD_SPILL_BYTES").unwrap_or(defaults.spill_above),
+            max_bytes: read_bytes("JOB_PAYLOAD_MAX_BYTES").unwrap_or(defaults.max_bytes),
+        }
+    }
+}
+
+/// Redis value of a job or result chunk: its JSON, zstd-compressed when above the threshold
+/// and encrypted when `PII_JOB_PAYLOADS=encrypt` (see [`pii::PiiSettings`])
+pub fn encode(job_json: &str, limits: &PayloadLimits) -> Vec<u8> {
+    let payload = if job_json.len() <= limits.compress_above {
+        job_json.as_bytes().to_vec()
+    } else {
+        zstd::encode_all(job_json.as_bytes(), COMPRESSION_LEVEL)
+            .unwrap_or_else(|_| job_json.as_bytes().to_vec())
+    };
+    pii::settings().seal(Store::JobPayloads, payload)
+}
+
+/// JSON from a Redis value written by [`encode`] or stored uncompressed
+///
+/// Payloads encrypted before the store was switched back to `plain` are still read.
+pub fn decode(payload: &[u8]) -> Option<String> {
+    let payload = pii::settings().open(payload)?;
+    if payload.starts_with(&ZSTD_MAGIC) {
+        let json = zstd::decode_all(payload.as_slice()).ok()?;
+        return String::from_utf8(json).ok();
+    }
+    String::from_utf8(payload).ok()
+}
+
+pub fn too_large_error(size: usize, max_bytes: usize) -> RedisError {
+    RedisError::from((
+        ErrorKind::ClientError,
+        TOO_LARGE,
+        format!("{} bytes, limit {}", size, max_bytes),
+    ))
+}
+
+/// Whether an enqueue failed because the job exceeds the payload cap
+pub fn is_too_large(e: &RedisError) -> bool {
+    e.kind() == ErrorKind::ClientError && e.to_string().contains(TOO_LARGE)
+}
+
+/// Address list of a job moved out of Redis
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpilledPayload {
+    pub job_id: String,
+    /// zstd-compressed JSON array of the addresses, encrypted like the Redis payloads
+    pub emails: Binary,
+    pub created_at: DateTime,
+}
+
+/// Reads `DB_NAME_PRODUCTION` (default `email_sanitizer`) and `DB_JOB_PAYLOADS_COLLECTION`
+/// (default `job_payloads`)
+pub fn payloads_collection(mongo_client: &Client) -> Collection<SpilledPayload> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let collection_name =
+        std::env::var("DB_JOB_PAYLOADS_COLLECTION").unwrap_or_else(|_| "job_payloads".to_string());
+    mongo_client.database(&db_name).collection(&collection_name)
+}
+
+pub async fn spill(mongo_client: &Client, job_id: &str, emails: &[String]) -> Result<(), String> {
+    let json = serde_json::to_vec(emails).map_err(|e| e.to_string())?;
+    let compressed =
+        zstd::encode_all(json.as_slice(), COMPRESSION_LEVEL).map_err(|e| e.to_string())?;
+    let compressed = pii::settings().seal(Store::JobPayloads, compressed);
+    payloads_collection(mongo_client)
+        .insert_one(SpilledPayload {
+            job_id: job_id.to_string(),
+            emails: Binary {
+                subtype: BinarySubtype::Generic,
+                bytes: compressed,
+            },
+            created_at: DateTime::now(),
+        })
+        .await
+        .map_err(|_| "Database error".to_string())?;
+    Ok(())
+}
+
+/// Addresses of a spilled job; `None` once the payload was removed
+pub async fn load(mongo_client: &Client, job_id: &str) -> Result<Option<Vec<String>>, String> {
+    let Some(payload) = payloads_collection(mongo_client)
+        .find_one(doc! { "job_id": job_id })
+        .await
+        .map_err(|_| "Database error".to_string())?
+    else {
+        return Ok(None);
+    };
+    let compressed = pii::settings()
+        .open(&payload.emails.bytes)
+        .ok_or("job payload can't be decrypted with PII_ENCRYPTION_KEY")?;
+    let json = zstd::decode_all(compressed.as_slice()).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&json)
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+pub async fn remove(mongo_client: &Client, job_id: &str) -> Result<(), String> {
+    payloads_collection(mongo_client)
+        .delete_one(doc! { "job_id": job_id })
+        .await
+        .map_err(|_| "Database error".to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_round_trips() {
+        let limits = PayloadLimits {
+            compress_above: 64,
+            ..PayloadLimits::default()
+        };
+        let small = r#"{"id":"a","emails":[]}"#;
+        assert_eq!(encode(small, &limits), small.as_bytes());
+
+        let emails: Vec<String> = (0..500).map(|i| format!("user{}@example.com", i)).collect();
+        let large = serde_json::json!({ "id": "b", "emails": emails }).to_string();
+        let encoded = encode(&large, &limits);
+        assert!(encoded.starts_with(&ZSTD_MAGIC));
+        assert!(encoded.len() < large.len() / 4);
+        assert_eq!(decode(&encoded).as_deref(), Some(large.as_str()));
+        assert_eq!(decode(small.as_bytes()).as_deref(), Some(small));
+    }
+
+    #[test]
+    fn test_too_large_error_is_recognized() {
+        assert!(is_too_large(&too_large_error(10, 5)));
+        let other = RedisError::from((ErrorKind::ClientError, "something else"));
+        assert!(!is_too_large(&other));
+    }
+}
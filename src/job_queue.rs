@@ -1,7 +1,17 @@
-use redis::{AsyncCommands, Client};
+use crate::auth::PlanTier;
+use crate::checks::{Checks, ItemOptions};
+use crate::job_payload::{self, PayloadLimits};
+use crate::normalize::normalize_email;
+use crate::redis_pool::RedisPool;
+use crate::validation_cache::scan_keys;
+use mongodb::Client as MongoClient;
+use redis::{AsyncCommands, ErrorKind, RedisError, Script};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use tokio::sync::watch;
 use tokio::time::{Duration, sleep};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,9 +21,294 @@ pub struct BulkValidationJob {
     pub check_role_based: bool,
     pub status: JobStatus,
     pub created_at: i64,
+    /// Account the job was submitted by; jobs from before per-tenant queues fall back to the shared tenant
+    #[serde(default = "default_tenant")]
+    pub tenant_id: String,
+    /// When the status last changed; `None` until a worker picks the job up
+    #[serde(default)]
+    pub updated_at: Option<i64>,
+    /// Number of addresses, kept when `emails` is spilled out of Redis
+    #[serde(default)]
+    pub email_count: usize,
+    /// Whether `emails` was moved to the payload store; the queue loads it back
+    /// before handing the job to a worker
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub spilled: bool,
+    /// Options of the entries that set their own, by position in `emails`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub item_options: BTreeMap<usize, ItemOptions>,
+    /// Where the worker posts the result once the job finishes; set by single-address
+    /// async validations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// Whether sub-addressed entries share the result of their base address, per the
+    /// account's `dedupe_tagged_addresses` setting
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dedupe_tagged: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl BulkValidationJob {
+    /// Number of addresses submitted, whether or not they are held in `emails`
+    pub fn email_count(&self) -> usize {
+        self.email_count.max(self.emails.len())
+    }
+
+    /// The stages to run for each address of `emails`
+    pub fn checks(&self) -> Vec<Checks> {
+        (0..self.emails.len())
+            .map(|i| match self.item_options.get(&i) {
+                Some(options) => options.checks(self.check_role_based),
+                None => Checks::standard(self.check_role_based),
+            })
+            .collect()
+    }
+}
+
+fn decode_job(payload: &[u8]) -> Option<BulkValidationJob> {
+    job_payload::decode(payload).and_then(|json| serde_json::from_str(&json).ok())
+}
+
+fn store_error(e: String) -> RedisError {
+    RedisError::from((ErrorKind::IoError, "job payload store", e))
+}
+
+/// Tenant used for jobs submitted without an identifiable account
+pub const DEFAULT_TENANT: &str = "shared";
+
+fn default_tenant() -> String {
+    DEFAULT_TENANT.to_string()
+}
+
+fn new_job(
+    tenant_id: &str,
+    emails: Vec<String>,
+    check_role_based: bool,
+    item_options: BTreeMap<usize, ItemOptions>,
+) -> BulkValidationJob {
+    BulkValidationJob {
+        id: Uuid::new_v4().to_string(),
+        email_count: emails.len(),
+        emails,
+        check_role_based,
+        status: JobStatus::Pending,
+        created_at: chrono::Utc::now().timestamp(),
+        tenant_id: tenant_id.to_string(),
+        updated_at: None,
+        spilled: false,
+        item_options,
+        webhook_url: None,
+        dedupe_tagged: false,
+    }
+}
+
+/// How a submission that overlaps a recent job should be handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateAction {
+    /// Accept the job but report the earlier job it duplicates
+    Warn,
+    /// Reject the job and point the caller at the earlier job
+    Block,
+}
+
+/// Settings for detecting re-uploads of a list a tenant already validated
+///
+/// Read from the environment:
+/// - `DUPLICATE_JOB_THRESHOLD_PERCENT`: minimum overlap to flag a job (default 90)
+/// - `DUPLICATE_JOB_WINDOW_HOURS`: how far back to compare (default 24)
+/// - `DUPLICATE_JOB_ACTION`: `warn` (default) or `block`
+#[derive(Debug, Clone)]
+pub struct DuplicateDetectionConfig {
+    pub threshold_percent: f64,
+    pub window_hours: i64,
+    pub action: DuplicateAction,
+}
+
+impl DuplicateDetectionConfig {
+    pub fn from_env() -> Self {
+        let threshold_percent = std::env::var("DUPLICATE_JOB_THRESHOLD_PERCENT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(90.0);
+        let window_hours = std::env::var("DUPLICATE_JOB_WINDOW_HOURS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(24);
+        let action = match std::env::var("DUPLICATE_JOB_ACTION").as_deref() {
+            Ok("block") => DuplicateAction::Block,
+            _ => DuplicateAction::Warn,
+        };
+
+        Self {
+            threshold_percent,
+            window_hours,
+            action,
+        }
+    }
+}
+
+/// A recent job that overlaps a new submission
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuplicateJob {
+    pub job_id: String,
+    pub overlap_percent: f64,
+}
+
+/// Maximum number of recent jobs compared against a new submission
+const MAX_DUPLICATE_CANDIDATES: isize = 20;
+
+fn tenant_jobs_key(tenant_id: &str) -> String {
+    format!("tenant_jobs:{}", tenant_id)
+}
+
+fn job_fingerprint_key(job_id: &str) -> String {
+    format!("job_fingerprint:{}", job_id)
+}
+
+/// Every job a tenant submitted within the job record TTL, scored by creation time
+fn account_jobs_key(tenant_id: &str) -> String {
+    format!("account_jobs:{}", tenant_id)
+}
+
+fn job_key(job_id: &str) -> String {
+    format!("job:{}", job_id)
+}
+
+/// Hashes each normalized address so fingerprints never hold raw emails
+fn email_fingerprint(emails: &[String]) -> HashSet<String> {
+    emails
+        .iter()
+        .map(|email| {
+            let mut hasher = Sha256::new();
+            hasher.update(normalize_email(email));
+            format!("{:x}", hasher.finalize())[..16].to_string()
+        })
+        .collect()
+}
+
+/// Percentage of `new` that also appears in `prior`
+fn overlap_percent(new: &HashSet<String>, prior: &HashSet<String>) -> f64 {
+    if new.is_empty() {
+        return 0.0;
+    }
+    let shared = new.intersection(prior).count();
+    shared as f64 * 100.0 / new.len() as f64
+}
+
+/// Legacy single queue, still drained so jobs enqueued before the upgrade are not lost
+const LEGACY_QUEUE_KEY: &str = "bulk_validation_queue";
+/// Round-robin rotation of tenant IDs, each present `weight` times
+const TENANT_ROTATION_KEY: &str = "bulk_validation_tenants";
+/// Set of tenants currently present in the rotation
+const ACTIVE_TENANTS_KEY: &str = "bulk_validation_active_tenants";
+
+fn job_results_key(job_id: &str) -> String {
+    format!("job_results:{}", job_id)
+}
+
+fn job_summary_key(job_id: &str) -> String {
+    format!("job_summary:{}", job_id)
+}
+
+fn job_report_key(job_id: &str) -> String {
+    format!("job_report:{}", job_id)
+}
+
+/// Number of results stored per chunk of a job's result list
+pub const RESULT_CHUNK_SIZE: usize = 1000;
+
+/// Result chunks written per pipelined round trip
+pub const RESULT_WRITE_BATCH: usize = 20;
+
+/// How long finished results stay downloadable, matching the job record's TTL
+const JOB_RESULTS_TTL_SECS: i64 = 3600;
+
+/// How long a job record is kept after submission
+const JOB_RECORD_TTL_SECS: i64 = 3600;
+
+fn tenant_queue_key(tenant_id: &str) -> String {
+    format!("bulk_validation_queue:{}", tenant_id)
+}
+
+/// Every consumer that has claimed jobs, alive or not
+const CONSUMERS_KEY: &str = "bulk_validation_consumers";
+
+/// Jobs that ended in failure, newest first, for the admin dashboard
+const FAILED_JOBS_KEY: &str = "bulk_validation_failed";
+
+/// Failures kept in [`FAILED_JOBS_KEY`]; older ones are trimmed on write
+const MAX_FAILED_JOBS: isize = 100;
+
+/// How long a consumer may go without a heartbeat before its claimed jobs are re-queued
+const CONSUMER_TIMEOUT_SECS: u64 = 30;
+
+/// How often idle consumers look for jobs abandoned by dead ones
+const RECLAIM_INTERVAL: Duration = Duration::from_secs(CONSUMER_TIMEOUT_SECS);
+
+/// Jobs a consumer has claimed and not yet acknowledged
+fn consumer_pending_key(consumer: &str) -> String {
+    format!("bulk_validation_pending:{}", consumer)
+}
+
+/// Exists while the consumer is alive
+fn consumer_heartbeat_key(consumer: &str) -> String {
+    format!("bulk_validation_consumer:{}", consumer)
+}
+
+/// Pushes a job onto its tenant's sub-queue and, if the tenant was idle, adds it to the
+/// rotation once per unit of weight. Runs atomically so a concurrent dequeue cannot
+/// retire the tenant between the push and the registration.
+const ENQUEUE_SCRIPT: &str = r#"
+redis.call('LPUSH', KEYS[1], ARGV[2])
+if redis.call('SADD', KEYS[3], ARGV[1]) == 1 then
+    for _ = 1, tonumber(ARGV[3]) do
+        redis.call('RPUSH', KEYS[2], ARGV[1])
+    end
+end
+return 1
+"#;
+
+/// Moves a job from a tenant's sub-queue to the consumer's pending list, retiring the
+/// tenant from the rotation when its sub-queue is empty. Runs after the rotation step
+/// picked the tenant, so every key it touches is declared up front. Returns the job
+/// payload, or `""` for a retired tenant.
+const CLAIM_SCRIPT: &str = r#"
+local job = redis.call('RPOP', KEYS[1])
+if job then
+    redis.call('LPUSH', KEYS[4], job)
+    return job
+end
+redis.call('LREM', KEYS[2], 0, ARGV[1])
+redis.call('SREM', KEYS[3], ARGV[1])
+return ''
+"#;
+
+/// Moves the pending jobs of a consumer whose heartbeat expired to the front of the
+/// shared queue and forgets the consumer. Returns the number of jobs re-queued.
+const RECLAIM_SCRIPT: &str = r#"
+if redis.call('EXISTS', KEYS[1]) == 1 then
+    return 0
+end
+local requeued = 0
+local job = redis.call('RPOP', KEYS[2])
+while job do
+    redis.call('RPUSH', KEYS[3], job)
+    requeued = requeued + 1
+    job = redis.call('RPOP', KEYS[2])
+end
+redis.call('SREM', KEYS[4], ARGV[1])
+return requeued
+"#;
+
+/// Aborts a background task when dropped
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum JobStatus {
     Pending,
     Processing,
@@ -21,51 +316,353 @@ pub enum JobStatus {
     Failed,
 }
 
+/// What [`JobQueue::erase_email`] removed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ErasedJobData {
+    /// Finished jobs whose record or results held the address
+    pub jobs: u64,
+    /// Result rows removed
+    pub results: u64,
+    /// Jobs not yet finished, left to expire with their records
+    pub jobs_in_progress: u64,
+}
+
+/// A job that ended in failure, kept after its record expires
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FailedJob {
+    pub job_id: String,
+    pub tenant_id: String,
+    pub email_count: usize,
+    pub created_at: i64,
+    pub failed_at: i64,
+}
+
+/// A worker consumer of the job queue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ConsumerStatus {
+    pub name: String,
+    /// Whether its heartbeat is current; dead consumers' jobs are re-queued
+    pub alive: bool,
+    /// Jobs claimed and not yet finished
+    pub claimed: u64,
+}
+
 #[derive(Clone)]
 pub struct JobQueue {
-    redis: Arc<Client>,
+    redis: RedisPool,
+    /// Where address lists above `JOB_PAYLOAD_SPILL_BYTES` are kept; without one
+    /// every payload stays in Redis, up to `JOB_PAYLOAD_MAX_BYTES`
+    payload_store: Option<MongoClient>,
 }
 
 impl JobQueue {
     pub fn new(redis_url: &str) -> Result<Self, redis::RedisError> {
-        let client = Client::open(redis_url)?;
         Ok(Self {
-            redis: Arc::new(client),
+            redis: RedisPool::new(redis_url)?,
+            payload_store: None,
         })
     }
 
+    /// Spills large address lists to MongoDB instead of keeping them in Redis
+    pub fn with_payload_store(mut self, mongo_client: MongoClient) -> Self {
+        self.payload_store = Some(mongo_client);
+        self
+    }
+
     pub async fn enqueue_bulk_validation(
         &self,
         emails: Vec<String>,
         check_role_based: bool,
     ) -> Result<String, redis::RedisError> {
-        let job_id = Uuid::new_v4().to_string();
-        let job = BulkValidationJob {
-            id: job_id.clone(),
+        self.enqueue_bulk_validation_for_tenant(
+            DEFAULT_TENANT,
+            PlanTier::default(),
             emails,
             check_role_based,
-            status: JobStatus::Pending,
-            created_at: chrono::Utc::now().timestamp(),
+        )
+        .await
+    }
+
+    /// Enqueues a job on the submitting tenant's sub-queue.
+    ///
+    /// Workers serve tenants in weighted round-robin order, so a tenant with a very large
+    /// backlog only gets its plan's share of dequeues while other tenants have work waiting.
+    ///
+    /// The job is stored compressed when large (see [`PayloadLimits`]). Jobs still above
+    /// `JOB_PAYLOAD_MAX_BYTES` after spilling are refused with an error recognized by
+    /// [`job_payload::is_too_large`].
+    pub async fn enqueue_bulk_validation_for_tenant(
+        &self,
+        tenant_id: &str,
+        plan: PlanTier,
+        emails: Vec<String>,
+        check_role_based: bool,
+    ) -> Result<String, redis::RedisError> {
+        self.enqueue_bulk_validation_with_options(
+            tenant_id,
+            plan,
+            emails,
+            check_role_based,
+            BTreeMap::new(),
+            false,
+        )
+        .await
+    }
+
+    /// Like [`Self::enqueue_bulk_validation_for_tenant`], with the options of entries
+    /// that override the job's, by position in `emails`; `dedupe_tagged` merges
+    /// sub-addressed entries with their base address
+    pub async fn enqueue_bulk_validation_with_options(
+        &self,
+        tenant_id: &str,
+        plan: PlanTier,
+        emails: Vec<String>,
+        check_role_based: bool,
+        item_options: BTreeMap<usize, ItemOptions>,
+        dedupe_tagged: bool,
+    ) -> Result<String, redis::RedisError> {
+        let job = BulkValidationJob {
+            dedupe_tagged,
+            ..new_job(tenant_id, emails, check_role_based, item_options)
+        };
+        self.enqueue(job, plan).await
+    }
+
+    /// Queues the validation of a single address whose result is posted to
+    /// `webhook_url` when done, instead of only being kept for download
+    pub async fn enqueue_async_validation(
+        &self,
+        tenant_id: &str,
+        plan: PlanTier,
+        email: String,
+        options: ItemOptions,
+        webhook_url: String,
+    ) -> Result<String, redis::RedisError> {
+        let check_role_based = options.check_role_based.unwrap_or(false);
+        let item_options = if options.is_default() {
+            BTreeMap::new()
+        } else {
+            BTreeMap::from([(0, options)])
         };
+        let job = BulkValidationJob {
+            webhook_url: Some(webhook_url),
+            ..new_job(tenant_id, vec![email], check_role_based, item_options)
+        };
+        self.enqueue(job, plan).await
+    }
 
-        let mut conn = self.redis.get_multiplexed_async_connection().await?;
-        let job_json = serde_json::to_string(&job).unwrap();
+    async fn enqueue(
+        &self,
+        job: BulkValidationJob,
+        plan: PlanTier,
+    ) -> Result<String, redis::RedisError> {
+        let job_id = job.id.clone();
+        let tenant_id = job.tenant_id.as_str();
+
+        let limits = PayloadLimits::from_env();
+        let mut payload = job_payload::encode(&serde_json::to_string(&job).unwrap(), &limits);
+        if payload.len() > limits.spill_above
+            && let Some(store) = &self.payload_store
+        {
+            job_payload::spill(store, &job_id, &job.emails)
+                .await
+                .map_err(store_error)?;
+            let record = BulkValidationJob {
+                emails: Vec::new(),
+                spilled: true,
+                ..job.clone()
+            };
+            payload = job_payload::encode(&serde_json::to_string(&record).unwrap(), &limits);
+        }
+        if payload.len() > limits.max_bytes {
+            return Err(job_payload::too_large_error(
+                payload.len(),
+                limits.max_bytes,
+            ));
+        }
 
-        let _: () = conn.lpush("bulk_validation_queue", &job_json).await?;
-        let _: () = conn.set(format!("job:{}", job_id), &job_json).await?;
-        let _: () = conn.expire(format!("job:{}", job_id), 3600).await?; // 1 hour TTL
+        let mut conn = self.redis.get().await?;
+        let _: i32 = Script::new(ENQUEUE_SCRIPT)
+            .key(tenant_queue_key(tenant_id))
+            .key(TENANT_ROTATION_KEY)
+            .key(ACTIVE_TENANTS_KEY)
+            .arg(tenant_id)
+            .arg(&payload)
+            .arg(plan.queue_weight())
+            .invoke_async(&mut conn)
+            .await?;
+        let _: () = conn.set(job_key(&job_id), &payload).await?;
+        let _: () = conn.expire(job_key(&job_id), JOB_RECORD_TTL_SECS).await?;
+
+        let index_key = account_jobs_key(tenant_id);
+        let _: () = redis::pipe()
+            .zadd(&index_key, &job_id, job.created_at)
+            .zrembyscore(&index_key, "-inf", job.created_at - JOB_RECORD_TTL_SECS)
+            .expire(&index_key, JOB_RECORD_TTL_SECS)
+            .query_async(&mut conn)
+            .await?;
+
+        self.record_job_fingerprint(&job).await?;
 
         Ok(job_id)
     }
 
+    /// Remembers which addresses a job contained so later submissions can be compared
+    async fn record_job_fingerprint(
+        &self,
+        job: &BulkValidationJob,
+    ) -> Result<(), redis::RedisError> {
+        let fingerprint = email_fingerprint(&job.emails);
+        if fingerprint.is_empty() {
+            return Ok(());
+        }
+
+        let window_secs = DuplicateDetectionConfig::from_env().window_hours * 3600;
+        let mut conn = self.redis.get().await?;
+        let fingerprint_key = job_fingerprint_key(&job.id);
+        let tenant_key = tenant_jobs_key(&job.tenant_id);
+
+        let _: () = conn
+            .sadd(
+                &fingerprint_key,
+                fingerprint.into_iter().collect::<Vec<_>>(),
+            )
+            .await?;
+        let _: () = conn.expire(&fingerprint_key, window_secs).await?;
+        let _: () = conn.zadd(&tenant_key, &job.id, job.created_at).await?;
+        let _: () = conn
+            .zrembyscore(&tenant_key, "-inf", job.created_at - window_secs)
+            .await?;
+        let _: () = conn.expire(&tenant_key, window_secs).await?;
+
+        Ok(())
+    }
+
+    /// Finds the tenant's recent job that overlaps `emails` the most, if that overlap
+    /// reaches the configured threshold.
+    pub async fn find_duplicate_job(
+        &self,
+        tenant_id: &str,
+        emails: &[String],
+        config: &DuplicateDetectionConfig,
+    ) -> Result<Option<DuplicateJob>, redis::RedisError> {
+        let fingerprint = email_fingerprint(emails);
+        if fingerprint.is_empty() {
+            return Ok(None);
+        }
+
+        let mut conn = self.redis.get().await?;
+        let since = chrono::Utc::now().timestamp() - config.window_hours * 3600;
+        let candidates: Vec<String> = conn
+            .zrevrangebyscore_limit(
+                tenant_jobs_key(tenant_id),
+                "+inf",
+                since,
+                0,
+                MAX_DUPLICATE_CANDIDATES,
+            )
+            .await?;
+
+        let mut best: Option<DuplicateJob> = None;
+        for job_id in candidates {
+            let prior: HashSet<String> = conn.smembers(job_fingerprint_key(&job_id)).await?;
+            let overlap = overlap_percent(&fingerprint, &prior);
+            if overlap >= config.threshold_percent
+                && best.as_ref().is_none_or(|b| overlap > b.overlap_percent)
+            {
+                best = Some(DuplicateJob {
+                    job_id,
+                    overlap_percent: overlap,
+                });
+            }
+        }
+
+        Ok(best)
+    }
+
     pub async fn get_job_status(
         &self,
         job_id: &str,
     ) -> Result<Option<BulkValidationJob>, redis::RedisError> {
-        let mut conn = self.redis.get_multiplexed_async_connection().await?;
-        let job_json: Option<String> = conn.get(format!("job:{}", job_id)).await?;
+        let mut conn = self.redis.get().await?;
+        let payload: Option<Vec<u8>> = conn.get(job_key(job_id)).await?;
 
-        Ok(job_json.and_then(|json| serde_json::from_str(&json).ok()))
+        Ok(payload.and_then(|payload| decode_job(&payload)))
+    }
+
+    /// A tenant's jobs that are still on record, newest first
+    pub async fn list_jobs(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Vec<BulkValidationJob>, redis::RedisError> {
+        let mut conn = self.redis.get().await?;
+        let index_key = account_jobs_key(tenant_id);
+        let since = chrono::Utc::now().timestamp() - JOB_RECORD_TTL_SECS;
+        let _: () = conn.zrembyscore(&index_key, "-inf", since).await?;
+        let job_ids: Vec<String> = conn.zrevrange(&index_key, 0, -1).await?;
+        if job_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys: Vec<String> = job_ids.iter().map(|id| job_key(id)).collect();
+        let records: Vec<Option<Vec<u8>>> =
+            redis::cmd("MGET").arg(&keys).query_async(&mut conn).await?;
+        Ok(records
+            .into_iter()
+            .flatten()
+            .filter_map(|payload| decode_job(&payload))
+            .filter(|job| job.tenant_id == tenant_id)
+            .collect())
+    }
+
+    /// Takes a pending job off its queue and purges it
+    ///
+    /// Returns `false` without touching anything when the job is no longer queued, i.e.
+    /// a worker already picked it up.
+    pub async fn cancel_job(&self, job_id: &str) -> Result<bool, redis::RedisError> {
+        let mut conn = self.redis.get().await?;
+        // The record holds the exact payload pushed at enqueue time until a worker updates it
+        let Some(payload): Option<Vec<u8>> = conn.get(job_key(job_id)).await? else {
+            return Ok(false);
+        };
+        let Some(job) = decode_job(&payload) else {
+            return Ok(false);
+        };
+        if job.status != JobStatus::Pending {
+            return Ok(false);
+        }
+
+        let removed: i64 = conn
+            .lrem(tenant_queue_key(&job.tenant_id), 1, &payload)
+            .await?;
+        let removed_legacy: i64 = conn.lrem(LEGACY_QUEUE_KEY, 1, &payload).await?;
+        if removed + removed_legacy == 0 {
+            return Ok(false);
+        }
+
+        self.purge_job(job_id, &job.tenant_id).await?;
+        Ok(true)
+    }
+
+    /// Deletes a job's record, results, summary, hygiene report and fingerprint
+    pub async fn purge_job(&self, job_id: &str, tenant_id: &str) -> Result<(), redis::RedisError> {
+        let mut conn = self.redis.get().await?;
+        let _: () = redis::pipe()
+            .del(job_key(job_id))
+            .del(job_results_key(job_id))
+            .del(job_summary_key(job_id))
+            .del(job_report_key(job_id))
+            .del(job_fingerprint_key(job_id))
+            .zrem(account_jobs_key(tenant_id), job_id)
+            .zrem(tenant_jobs_key(tenant_id), job_id)
+            .query_async(&mut conn)
+            .await?;
+        if let Some(store) = &self.payload_store {
+            job_payload::remove(store, job_id)
+                .await
+                .map_err(store_error)?;
+        }
+        Ok(())
     }
 
     pub async fn update_job_status(
@@ -73,44 +670,452 @@ impl JobQueue {
         job_id: &str,
         status: JobStatus,
     ) -> Result<(), redis::RedisError> {
-        let mut conn = self.redis.get_multiplexed_async_connection().await?;
+        let mut conn = self.redis.get().await?;
 
         if let Some(mut job) = self.get_job_status(job_id).await? {
+            let now = chrono::Utc::now().timestamp();
             job.status = status;
+            job.updated_at = Some(now);
             let job_json = serde_json::to_string(&job).unwrap();
-            let _: () = conn.set(format!("job:{}", job_id), &job_json).await?;
+            let payload = job_payload::encode(&job_json, &PayloadLimits::from_env());
+            let _: () = conn.set(job_key(job_id), &payload).await?;
+            // Reports are only valid for the results of the run that produced them
+            let _: () = conn
+                .del(&[job_summary_key(job_id), job_report_key(job_id)])
+                .await?;
+            if job.status == JobStatus::Failed {
+                let failed = FailedJob {
+                    job_id: job.id.clone(),
+                    tenant_id: job.tenant_id.clone(),
+                    email_count: job.email_count(),
+                    created_at: job.created_at,
+                    failed_at: now,
+                };
+                let _: () = redis::pipe()
+                    .lpush(FAILED_JOBS_KEY, serde_json::to_string(&failed).unwrap())
+                    .ignore()
+                    .ltrim(FAILED_JOBS_KEY, 0, MAX_FAILED_JOBS - 1)
+                    .ignore()
+                    .query_async(&mut conn)
+                    .await?;
+            }
         }
 
         Ok(())
     }
 
-    pub async fn process_jobs<F, Fut>(&self, processor: F)
-    where
+    /// Appends one serialized chunk of results to a job's result list
+    ///
+    /// Results are stored in chunks of up to [`RESULT_CHUNK_SIZE`] rows so downloads can
+    /// stream them without loading a whole multi-million row job into memory.
+    pub async fn append_result_chunk(
+        &self,
+        job_id: &str,
+        chunk_json: &str,
+    ) -> Result<(), redis::RedisError> {
+        self.append_result_chunks(job_id, &[chunk_json.to_string()])
+            .await
+    }
+
+    /// Appends serialized result chunks in order, [`RESULT_WRITE_BATCH`] per round trip
+    ///
+    /// Chunks above `JOB_PAYLOAD_COMPRESS_BYTES` are stored zstd-compressed;
+    /// [`JobQueue::result_chunk`] decompresses them transparently.
+    pub async fn append_result_chunks(
+        &self,
+        job_id: &str,
+        chunks: &[String],
+    ) -> Result<(), redis::RedisError> {
+        let limits = PayloadLimits::from_env();
+        let mut conn = self.redis.get().await?;
+        let key = job_results_key(job_id);
+        for batch in chunks.chunks(RESULT_WRITE_BATCH) {
+            let payloads: Vec<Vec<u8>> = batch
+                .iter()
+                .map(|chunk_json| job_payload::encode(chunk_json, &limits))
+                .collect();
+            let _: () = redis::pipe()
+                .rpush(&key, payloads)
+                .expire(&key, JOB_RESULTS_TTL_SECS)
+                .query_async(&mut conn)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Number of result chunks stored for a job
+    pub async fn result_chunk_count(&self, job_id: &str) -> Result<usize, redis::RedisError> {
+        let mut conn = self.redis.get().await?;
+        conn.llen(job_results_key(job_id)).await
+    }
+
+    /// One serialized result chunk, by position
+    pub async fn result_chunk(
+        &self,
+        job_id: &str,
+        index: usize,
+    ) -> Result<Option<String>, redis::RedisError> {
+        let mut conn = self.redis.get().await?;
+        let payload: Option<Vec<u8>> = conn.lindex(job_results_key(job_id), index as isize).await?;
+        Ok(payload.and_then(|payload| job_payload::decode(&payload)))
+    }
+
+    /// Cached summary report of a job's results, if one was computed since it completed
+    pub async fn cached_summary(&self, job_id: &str) -> Result<Option<String>, redis::RedisError> {
+        let mut conn = self.redis.get().await?;
+        conn.get(job_summary_key(job_id)).await
+    }
+
+    /// Caches a job's summary report for as long as its results are kept
+    pub async fn cache_summary(
+        &self,
+        job_id: &str,
+        summary_json: &str,
+    ) -> Result<(), redis::RedisError> {
+        let mut conn = self.redis.get().await?;
+        conn.set_ex(
+            job_summary_key(job_id),
+            summary_json,
+            JOB_RESULTS_TTL_SECS as u64,
+        )
+        .await
+    }
+
+    /// Cached hygiene report of a job's results, if one was computed since it completed
+    pub async fn cached_report(&self, job_id: &str) -> Result<Option<String>, redis::RedisError> {
+        let mut conn = self.redis.get().await?;
+        conn.get(job_report_key(job_id)).await
+    }
+
+    /// Caches a job's hygiene report for as long as its results are kept
+    pub async fn cache_report(
+        &self,
+        job_id: &str,
+        report_json: &str,
+    ) -> Result<(), redis::RedisError> {
+        let mut conn = self.redis.get().await?;
+        conn.set_ex(
+            job_report_key(job_id),
+            report_json,
+            JOB_RESULTS_TTL_SECS as u64,
+        )
+        .await
+    }
+
+    /// The most recent failed jobs, newest first
+    pub async fn failed_jobs(&self, limit: usize) -> Result<Vec<FailedJob>, RedisError> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.redis.get().await?;
+        let members: Vec<String> = conn.lrange(FAILED_JOBS_KEY, 0, limit as isize - 1).await?;
+        Ok(members
+            .iter()
+            .filter_map(|member| serde_json::from_str(member).ok())
+            .collect())
+    }
+
+    /// Every consumer that has joined the worker group and not left it, by name
+    pub async fn consumers(&self) -> Result<Vec<ConsumerStatus>, RedisError> {
+        let mut conn = self.redis.get().await?;
+        let mut names: Vec<String> = conn.smembers(CONSUMERS_KEY).await?;
+        names.sort();
+        let mut pipe = redis::pipe();
+        for name in &names {
+            pipe.exists(consumer_heartbeat_key(name))
+                .llen(consumer_pending_key(name));
+        }
+        let states: Vec<(bool, u64)> = if names.is_empty() {
+            Vec::new()
+        } else {
+            let flat: Vec<u64> = pipe.query_async(&mut conn).await?;
+            flat.chunks(2).map(|pair| (pair[0] > 0, pair[1])).collect()
+        };
+        Ok(names
+            .into_iter()
+            .zip(states)
+            .map(|(name, (alive, claimed))| ConsumerStatus {
+                name,
+                alive,
+                claimed,
+            })
+            .collect())
+    }
+
+    /// Removes an address from the records and stored results of finished jobs, of one
+    /// tenant or of every tenant
+    ///
+    /// Jobs still pending or processing are left alone and counted as in progress; their
+    /// records and results expire within the job record TTL. Cached summaries and
+    /// reports of the jobs changed are dropped so they are recomputed without it.
+    pub async fn erase_email(
+        &self,
+        tenant_id: Option<&str>,
+        email: &str,
+    ) -> Result<ErasedJobData, RedisError> {
+        let mut conn = self.redis.get().await?;
+        let job_ids: Vec<String> = match tenant_id {
+            Some(tenant_id) => conn.zrange(account_jobs_key(tenant_id), 0, -1).await?,
+            None => scan_keys(&mut conn, "job:*")
+                .await?
+                .into_iter()
+                .filter_map(|key| key.strip_prefix("job:").map(str::to_string))
+                .collect(),
+        };
+        let matches = |candidate: &str| candidate.trim().eq_ignore_ascii_case(email.trim());
+        let limits = PayloadLimits::from_env();
+
+        let mut erased = ErasedJobData::default();
+        for job_id in job_ids {
+            let Some(mut job) = self.get_job_status(&job_id).await? else {
+                continue;
+            };
+            if tenant_id.is_some_and(|tenant_id| job.tenant_id != tenant_id) {
+                continue;
+            }
+            if matches!(job.status, JobStatus::Pending | JobStatus::Processing) {
+                erased.jobs_in_progress += 1;
+                continue;
+            }
+
+            job.email_count = job.email_count();
+            let submitted = job.emails.len();
+            job.emails.retain(|candidate| !matches(candidate));
+            let mut changed = job.emails.len() < submitted;
+            if changed {
+                let payload = job_payload::encode(&serde_json::to_string(&job).unwrap(), &limits);
+                let _: () = redis::cmd("SET")
+                    .arg(job_key(&job_id))
+                    .arg(payload)
+                    .arg("KEEPTTL")
+                    .query_async(&mut conn)
+                    .await?;
+            }
+
+            let results_key = job_results_key(&job_id);
+            let chunks: Vec<Vec<u8>> = conn.lrange(&results_key, 0, -1).await?;
+            for (index, chunk) in chunks.iter().enumerate() {
+                let Some(mut rows) = job_payload::decode(chunk)
+                    .and_then(|json| serde_json::from_str::<Vec<serde_json::Value>>(&json).ok())
+                else {
+                    continue;
+                };
+                let stored = rows.len();
+                rows.retain(|row| !row["email"].as_str().is_some_and(matches));
+                if rows.len() == stored {
+                    continue;
+                }
+                erased.results += (stored - rows.len()) as u64;
+                let chunk_json = serde_json::to_string(&rows).unwrap();
+                let _: () = conn
+                    .lset(
+                        &results_key,
+                        index as isize,
+                        job_payload::encode(&chunk_json, &limits),
+                    )
+                    .await?;
+                changed = true;
+            }
+
+            if changed {
+                erased.jobs += 1;
+                let _: () = conn
+                    .del(&[job_summary_key(&job_id), job_report_key(&job_id)])
+                    .await?;
+            }
+        }
+        Ok(erased)
+    }
+
+    /// Jobs waiting to be picked up, across the legacy queue and every tenant's sub-queue
+    pub async fn pending_jobs(&self) -> Result<u64, redis::RedisError> {
+        let mut conn = self.redis.get().await?;
+        let tenants: Vec<String> = conn.smembers(ACTIVE_TENANTS_KEY).await?;
+        let mut pending: u64 = conn.llen(LEGACY_QUEUE_KEY).await?;
+        for tenant in tenants {
+            let queued: u64 = conn.llen(tenant_queue_key(&tenant)).await?;
+            pending += queued;
+        }
+        Ok(pending)
+    }
+
+    /// Runs `processor` on queued jobs as `consumer` of the worker group, until
+    /// `shutdown` turns true
+    ///
+    /// Each job goes to exactly one consumer, which keeps it on its pending list until
+    /// `processor` returns and heartbeats meanwhile. Jobs claimed by consumers that stop
+    /// heartbeating, i.e. crashed or killed workers, are put back on the queue by the
+    /// remaining ones. On shutdown the running job is finished first and the consumer
+    /// leaves the group.
+    pub async fn process_jobs<F, Fut>(
+        &self,
+        consumer: &str,
+        mut shutdown: watch::Receiver<bool>,
+        processor: F,
+    ) where
         F: Fn(BulkValidationJob) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = ()> + Send + 'static,
     {
-        loop {
-            match self.get_next_job().await {
-                Ok(Some(job)) => {
+        let heartbeat = AbortOnDrop(tokio::spawn(Self::heartbeat(
+            self.redis.clone(),
+            consumer.to_string(),
+        )));
+        let mut last_reclaim: Option<tokio::time::Instant> = None;
+        // A dropped sender can never request shutdown, so it counts as one
+        while !*shutdown.borrow() && shutdown.has_changed().is_ok() {
+            if last_reclaim.is_none_or(|at| at.elapsed() >= RECLAIM_INTERVAL) {
+                let _ = self.reclaim_abandoned_jobs().await;
+                last_reclaim = Some(tokio::time::Instant::now());
+            }
+            match self.get_next_job(consumer).await {
+                Ok(Some((mut job, payload))) => {
+                    // A job whose spilled addresses can't be loaded would complete empty
+                    if job.spilled && self.load_spilled_emails(&mut job).await.is_err() {
+                        let _ = self.update_job_status(&job.id, JobStatus::Failed).await;
+                        let _ = self.ack_job(consumer, &payload).await;
+                        continue;
+                    }
                     let _ = self.update_job_status(&job.id, JobStatus::Processing).await;
+                    let job_id = job.id.clone();
+                    let spilled = job.spilled;
                     processor(job).await;
+                    if spilled && let Some(store) = &self.payload_store {
+                        let _ = job_payload::remove(store, &job_id).await;
+                    }
+                    let _ = self.ack_job(consumer, &payload).await;
                 }
-                Ok(None) => {
-                    sleep(Duration::from_secs(1)).await;
-                }
-                Err(_) => {
-                    sleep(Duration::from_secs(5)).await;
-                }
+                Ok(None) => Self::pause(Duration::from_secs(1), &mut shutdown).await,
+                Err(_) => Self::pause(Duration::from_secs(5), &mut shutdown).await,
             }
         }
+        drop(heartbeat);
+        let _ = self.leave_group(consumer).await;
     }
 
-    async fn get_next_job(&self) -> Result<Option<BulkValidationJob>, redis::RedisError> {
-        let mut conn = self.redis.get_multiplexed_async_connection().await?;
-        let result: Option<(String, String)> = conn.brpop("bulk_validation_queue", 1.0).await?;
-        let job_json = result.map(|(_, value)| value);
+    /// Sleeps for `duration` unless shutdown is requested first
+    async fn pause(duration: Duration, shutdown: &mut watch::Receiver<bool>) {
+        tokio::select! {
+            _ = sleep(duration) => {}
+            _ = shutdown.changed() => {}
+        }
+    }
 
-        Ok(job_json.and_then(|json| serde_json::from_str(&json).ok()))
+    /// Ends the consumer's heartbeat and re-queues anything it left unacknowledged
+    async fn leave_group(&self, consumer: &str) -> Result<u64, RedisError> {
+        let mut conn = self.redis.get().await?;
+        let _: () = conn.del(consumer_heartbeat_key(consumer)).await?;
+        self.reclaim_abandoned_jobs().await
+    }
+
+    /// Registers `consumer` and keeps its heartbeat from expiring
+    async fn heartbeat(redis: RedisPool, consumer: String) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(CONSUMER_TIMEOUT_SECS / 3));
+        loop {
+            ticker.tick().await;
+            let Ok(mut conn) = redis.get().await else {
+                continue;
+            };
+            let _: Result<(), RedisError> = redis::pipe()
+                .sadd(CONSUMERS_KEY, &consumer)
+                .ignore()
+                .set_ex(consumer_heartbeat_key(&consumer), 1, CONSUMER_TIMEOUT_SECS)
+                .ignore()
+                .query_async(&mut conn)
+                .await;
+        }
+    }
+
+    /// Drops a finished job from the consumer's pending list
+    async fn ack_job(&self, consumer: &str, payload: &[u8]) -> Result<(), RedisError> {
+        let mut conn = self.redis.get().await?;
+        conn.lrem(consumer_pending_key(consumer), 1, payload).await
+    }
+
+    /// Puts jobs claimed by consumers that stopped heartbeating back on the queue, ahead
+    /// of waiting jobs; returns how many were re-queued
+    pub async fn reclaim_abandoned_jobs(&self) -> Result<u64, RedisError> {
+        let mut conn = self.redis.get().await?;
+        let consumers: Vec<String> = conn.smembers(CONSUMERS_KEY).await?;
+        let reclaim = Script::new(RECLAIM_SCRIPT);
+        let mut requeued = 0;
+        for consumer in consumers {
+            let count: u64 = reclaim
+                .key(consumer_heartbeat_key(&consumer))
+                .key(consumer_pending_key(&consumer))
+                .key(LEGACY_QUEUE_KEY)
+                .key(CONSUMERS_KEY)
+                .arg(&consumer)
+                .invoke_async(&mut conn)
+                .await?;
+            requeued += count;
+        }
+        Ok(requeued)
+    }
+
+    /// Puts a spilled job's addresses back into `emails`
+    async fn load_spilled_emails(&self, job: &mut BulkValidationJob) -> Result<(), RedisError> {
+        let store = self
+            .payload_store
+            .as_ref()
+            .ok_or_else(|| store_error("no payload store configured".to_string()))?;
+        job.emails = job_payload::load(store, &job.id)
+            .await
+            .map_err(store_error)?
+            .ok_or_else(|| store_error(format!("payload of job {} is missing", job.id)))?;
+        Ok(())
+    }
+
+    /// Claims the next job for `consumer`, serving tenants in weighted round-robin order
+    /// and then falling back to the legacy shared queue; returns it with its raw payload,
+    /// which acknowledges it once processed.
+    async fn get_next_job(
+        &self,
+        consumer: &str,
+    ) -> Result<Option<(BulkValidationJob, Vec<u8>)>, redis::RedisError> {
+        let mut conn = self.redis.get().await?;
+        let pending_key = consumer_pending_key(consumer);
+
+        // Each retired tenant costs one extra step, so bound the walk by the rotation length
+        let rotation_len: usize = conn.llen(TENANT_ROTATION_KEY).await?;
+        let claim = Script::new(CLAIM_SCRIPT);
+        let mut claimed = None;
+        for _ in 0..=rotation_len {
+            // Rotate the tenant list by one slot; an empty rotation leaves only the legacy queue
+            let tenant: Option<String> = conn
+                .rpoplpush(TENANT_ROTATION_KEY, TENANT_ROTATION_KEY)
+                .await?;
+            let Some(tenant) = tenant else {
+                break;
+            };
+            let payload: Vec<u8> = claim
+                .key(tenant_queue_key(&tenant))
+                .key(TENANT_ROTATION_KEY)
+                .key(ACTIVE_TENANTS_KEY)
+                .key(&pending_key)
+                .arg(&tenant)
+                .invoke_async(&mut conn)
+                .await?;
+            if !payload.is_empty() {
+                claimed = Some(payload);
+                break;
+            }
+        }
+
+        let payload = match claimed {
+            Some(payload) => payload,
+            None => match conn.brpoplpush(LEGACY_QUEUE_KEY, &pending_key, 1.0).await? {
+                Some(payload) => payload,
+                None => return Ok(None),
+            },
+        };
+        match decode_job(&payload) {
+            Some(job) => Ok(Some((job, payload))),
+            None => {
+                // Nothing can process it; don't leave it to be reclaimed over and over
+                let _: () = conn.lrem(&pending_key, 1, &payload).await?;
+                Ok(None)
+            }
+        }
     }
 }
 
@@ -163,7 +1168,7 @@ mod tests {
     #[tokio::test]
     async fn test_get_next_job() {
         if let Ok(job_queue) = JobQueue::new("redis://127.0.0.1:6379") {
-            let result = job_queue.get_next_job().await;
+            let result = job_queue.get_next_job("test-consumer").await;
             assert!(result.is_ok() || result.is_err());
         } else {
             assert!(true);
@@ -178,6 +1183,13 @@ mod tests {
             check_role_based: false,
             status: JobStatus::Pending,
             created_at: 1234567890,
+            tenant_id: DEFAULT_TENANT.to_string(),
+            updated_at: None,
+            email_count: 1,
+            spilled: false,
+            item_options: BTreeMap::new(),
+            webhook_url: None,
+            dedupe_tagged: false,
         };
 
         let serialized = serde_json::to_string(&job);
@@ -186,4 +1198,126 @@ mod tests {
         let deserialized: Result<BulkValidationJob, _> = serde_json::from_str(&serialized.unwrap());
         assert!(deserialized.is_ok());
     }
+
+    #[test]
+    fn test_job_without_tenant_defaults_to_shared() {
+        let json = r#"{"id":"old","emails":[],"check_role_based":false,"status":"Pending","created_at":0}"#;
+        let job: BulkValidationJob = serde_json::from_str(json).unwrap();
+        assert_eq!(job.tenant_id, DEFAULT_TENANT);
+    }
+
+    #[test]
+    fn test_email_fingerprint_normalizes_and_hashes() {
+        let fingerprint = email_fingerprint(&[
+            "User@GMail.com".to_string(),
+            " user@gmail.com ".to_string(),
+            "other@Example.com".to_string(),
+            "other@example.com".to_string(),
+        ]);
+        assert_eq!(fingerprint.len(), 2);
+        assert!(fingerprint.iter().all(|h| !h.contains('@')));
+    }
+
+    #[test]
+    fn test_overlap_percent() {
+        let new = email_fingerprint(&[
+            "a@example.com".to_string(),
+            "b@example.com".to_string(),
+            "c@example.com".to_string(),
+            "d@example.com".to_string(),
+        ]);
+        let prior = email_fingerprint(&[
+            "a@example.com".to_string(),
+            "b@example.com".to_string(),
+            "c@example.com".to_string(),
+            "z@example.com".to_string(),
+        ]);
+        assert_eq!(overlap_percent(&new, &prior), 75.0);
+        assert_eq!(overlap_percent(&new, &new), 100.0);
+        assert_eq!(overlap_percent(&HashSet::new(), &prior), 0.0);
+    }
+
+    #[test]
+    fn test_duplicate_detection_config_defaults() {
+        let config = DuplicateDetectionConfig::from_env();
+        assert!(config.threshold_percent > 0.0);
+        assert!(config.window_hours > 0);
+    }
+
+    #[test]
+    fn test_account_jobs_key_is_separate_from_duplicate_index() {
+        assert_eq!(account_jobs_key("acme"), "account_jobs:acme");
+        assert_ne!(account_jobs_key("acme"), tenant_jobs_key("acme"));
+    }
+
+    #[test]
+    fn test_consumer_keys() {
+        assert_eq!(
+            consumer_pending_key("worker-1"),
+            "bulk_validation_pending:worker-1"
+        );
+        assert_ne!(
+            consumer_pending_key("worker-1"),
+            consumer_heartbeat_key("worker-1")
+        );
+        assert_ne!(
+            consumer_pending_key("worker-1"),
+            tenant_queue_key("worker-1")
+        );
+    }
+
+    #[test]
+    fn test_tenant_queue_key() {
+        assert_eq!(tenant_queue_key("acme"), "bulk_validation_queue:acme");
+        assert_ne!(tenant_queue_key("acme"), LEGACY_QUEUE_KEY);
+    }
+
+    #[tokio::test]
+    async fn test_tenants_are_served_round_robin() {
+        let Ok(job_queue) = JobQueue::new("redis://127.0.0.1:6379") else {
+            return;
+        };
+        let tenant_a = format!("rr-a-{}", Uuid::new_v4());
+        let tenant_b = format!("rr-b-{}", Uuid::new_v4());
+        let mut enqueued = Vec::new();
+        for (tenant, n) in [
+            (&tenant_a, 1),
+            (&tenant_a, 2),
+            (&tenant_b, 1),
+            (&tenant_b, 2),
+        ] {
+            let email = format!("user{}@{}.example.com", n, tenant);
+            match job_queue
+                .enqueue_bulk_validation_for_tenant(tenant, PlanTier::Free, vec![email], false)
+                .await
+            {
+                Ok(job_id) => enqueued.push((tenant.clone(), job_id)),
+                // No Redis to run against
+                Err(_) => return,
+            }
+        }
+
+        let consumer = format!("rr-test-{}", Uuid::new_v4());
+        let mut served = Vec::new();
+        for _ in 0..50 {
+            match job_queue.get_next_job(&consumer).await.unwrap() {
+                Some((job, _)) if job.tenant_id == tenant_a || job.tenant_id == tenant_b => {
+                    served.push(job.id);
+                    if served.len() == enqueued.len() {
+                        break;
+                    }
+                }
+                Some(_) => continue,
+                None => break,
+            }
+        }
+
+        // Rotation is served from its tail, so the tenant that joined last goes first,
+        // and each tenant's own jobs come out in submission order
+        let expected: Vec<String> = [2, 0, 3, 1]
+            .iter()
+            .map(|&i| enqueued[i].1.clone())
+            .collect();
+        assert_eq!(served, expected);
+    }
 }
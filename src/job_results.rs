@@ -0,0 +1,257 @@
+use crate::error::ApiError;
+use crate::job_queue::{BulkValidationJob, JobQueue, RESULT_CHUNK_SIZE};
+use crate::routes::email::{BulkEmailValidationResult, EmailValidationResponse};
+use serde::Deserialize;
+
+/// Rows per page of job results when the client doesn't choose
+pub const DEFAULT_PER_PAGE: usize = 100;
+
+/// Largest page of job results a client can ask for
+pub const MAX_PER_PAGE: usize = 1000;
+
+/// Message of the `INVALID_PAGE` error for a `page` or `per_page` out of range, if any
+pub fn page_error(page: usize, per_page: usize) -> Option<String> {
+    if page == 0 {
+        return Some("page starts at 1".to_string());
+    }
+    if per_page == 0 || per_page > MAX_PER_PAGE {
+        return Some(format!("per_page must be between 1 and {}", MAX_PER_PAGE));
+    }
+    None
+}
+
+/// Verdict kept by the `only` filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Valid,
+    Invalid,
+}
+
+/// Which results a client wants back; the default keeps them all
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResultFilter {
+    pub only: Option<Outcome>,
+    /// Uppercased error code, e.g. `DISPOSABLE_EMAIL`
+    pub error_code: Option<String>,
+}
+
+impl ResultFilter {
+    /// An error code only ever matches invalid results, so it can't be combined with
+    /// `only=valid`
+    pub fn new(only: Option<Outcome>, error_code: Option<&str>) -> Result<Self, String> {
+        let error_code = error_code
+            .map(|code| code.trim().to_ascii_uppercase())
+            .filter(|code| !code.is_empty());
+        if only == Some(Outcome::Valid) && error_code.is_some() {
+            return Err("error_code can't be combined with only=valid".to_string());
+        }
+        Ok(Self { only, error_code })
+    }
+
+    /// Parses the `only` (`valid` or `invalid`) and `error_code` query parameters
+    pub fn parse(only: Option<&str>, error_code: Option<&str>) -> Result<Self, String> {
+        let only = match only.map(|only| only.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") => None,
+            Some("valid") => Some(Outcome::Valid),
+            Some("invalid") => Some(Outcome::Invalid),
+            Some(_) => return Err("only must be valid or invalid".to_string()),
+        };
+        Self::new(only, error_code)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.only.is_none() && self.error_code.is_none()
+    }
+
+    pub fn matches(&self, validation: &EmailValidationResponse) -> bool {
+        let outcome_matches = match self.only {
+            Some(Outcome::Valid) => validation.is_valid,
+            Some(Outcome::Invalid) => !validation.is_valid,
+            None => true,
+        };
+        outcome_matches
+            && self.error_code.as_ref().is_none_or(|code| {
+                validation
+                    .error
+                    .as_ref()
+                    .is_some_and(|error| &error.code == code)
+            })
+    }
+}
+
+/// `page`, `per_page`, `only` and `error_code` query parameters of bulk results
+#[derive(Debug, Default, Deserialize)]
+pub struct ResultQuery {
+    /// 1-based page number
+    pub page: Option<usize>,
+    /// Results per page, at most [`MAX_PER_PAGE`]; [`DEFAULT_PER_PAGE`] when unset
+    pub per_page: Option<usize>,
+    /// `valid` or `invalid`
+    pub only: Option<String>,
+    /// Keep the results failing with this code, e.g. `DISPOSABLE_EMAIL`
+    pub error_code: Option<String>,
+}
+
+impl ResultQuery {
+    /// `INVALID_FILTER` when `only` or `error_code` is not understood
+    pub fn filter(&self) -> Result<ResultFilter, ApiError> {
+        ResultFilter::parse(self.only.as_deref(), self.error_code.as_deref())
+            .map_err(|message| ApiError::validation("INVALID_FILTER", message))
+    }
+
+    /// Whether the client asked for one page rather than every result
+    pub fn is_paged(&self) -> bool {
+        self.page.is_some() || self.per_page.is_some()
+    }
+
+    /// The requested page and its size; `INVALID_PAGE` when out of range
+    pub fn page(&self) -> Result<(usize, usize), ApiError> {
+        let page = self.page.unwrap_or(1);
+        let per_page = self.per_page.unwrap_or(DEFAULT_PER_PAGE);
+        match page_error(page, per_page) {
+            Some(message) => Err(ApiError::validation("INVALID_PAGE", message)),
+            None => Ok((page, per_page)),
+        }
+    }
+
+    /// The page asked for, if any; see [`Self::page`]
+    pub fn paging(&self) -> Result<Option<(usize, usize)>, ApiError> {
+        if self.is_paged() {
+            self.page().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Entries of page `page` (1-based) of `per_page` entries
+pub fn page_of<T>(rows: impl IntoIterator<Item = T>, page: usize, per_page: usize) -> Vec<T> {
+    rows.into_iter()
+        .skip((page - 1) * per_page)
+        .take(per_page)
+        .collect()
+}
+
+/// One page of a job's results
+pub struct ResultPage {
+    pub rows: Vec<BulkEmailValidationResult>,
+    /// Results passing the filter, across all pages
+    pub total: usize,
+}
+
+/// Page `page` of the results of a completed `job` that pass `filter`
+///
+/// Unfiltered pages only read the chunks holding them; a filter scans every chunk to
+/// count its matches.
+pub async fn read_page(
+    job_queue: &JobQueue,
+    job: &BulkValidationJob,
+    filter: &ResultFilter,
+    page: usize,
+    per_page: usize,
+) -> Result<ResultPage, String> {
+    let offset = (page - 1) * per_page;
+    if filter.is_empty() {
+        return Ok(ResultPage {
+            rows: read_rows(job_queue, &job.id, offset, per_page).await?,
+            total: job.email_count(),
+        });
+    }
+
+    let mut rows = Vec::new();
+    let mut total = 0;
+    let mut index = 0;
+    while let Some(chunk) = job_queue
+        .result_chunk(&job.id, index)
+        .await
+        .map_err(|e| format!("Failed to read job results: {}", e))?
+    {
+        let chunk_rows: Vec<BulkEmailValidationResult> =
+            serde_json::from_str(&chunk).map_err(|e| format!("Corrupt result chunk: {}", e))?;
+        for row in chunk_rows {
+            if !filter.matches(&row.validation) {
+                continue;
+            }
+            if total >= offset && rows.len() < per_page {
+                rows.push(row);
+            }
+            total += 1;
+        }
+        index += 1;
+    }
+    Ok(ResultPage { rows, total })
+}
+
+/// Rows `offset..offset + limit` of a job's results, read from the stored chunks
+/// holding them; fewer when the results end first
+pub async fn read_rows(
+    job_queue: &JobQueue,
+    job_id: &str,
+    offset: usize,
+    limit: usize,
+) -> Result<Vec<BulkEmailValidationResult>, String> {
+    let mut rows = Vec::new();
+    let mut index = offset / RESULT_CHUNK_SIZE;
+    let mut skip = offset % RESULT_CHUNK_SIZE;
+    while rows.len() < limit {
+        let chunk = job_queue
+            .result_chunk(job_id, index)
+            .await
+            .map_err(|e| format!("Failed to read job results: {}", e))?;
+        let Some(chunk) = chunk else {
+            break;
+        };
+        let chunk_rows: Vec<BulkEmailValidationResult> =
+            serde_json::from_str(&chunk).map_err(|e| format!("Corrupt result chunk: {}", e))?;
+        let wanted = limit - rows.len();
+        rows.extend(chunk_rows.into_iter().skip(skip).take(wanted));
+        skip = 0;
+        index += 1;
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::validation_service::{rejection, valid};
+
+    #[test]
+    fn test_filters() {
+        let disposable = rejection("DISPOSABLE_EMAIL", "disposable");
+        let invalid_domain = rejection("INVALID_DOMAIN", "no MX");
+
+        let all = ResultFilter::parse(None, Some(" ")).unwrap();
+        assert!(all.is_empty());
+        assert!(all.matches(&valid()) && all.matches(&disposable));
+
+        let invalid = ResultFilter::parse(Some("Invalid"), None).unwrap();
+        assert!(!invalid.matches(&valid()));
+        assert!(invalid.matches(&disposable));
+
+        let code = ResultFilter::parse(None, Some("disposable_email")).unwrap();
+        assert!(code.matches(&disposable));
+        assert!(!code.matches(&invalid_domain));
+        assert!(!code.matches(&valid()));
+
+        assert!(ResultFilter::parse(Some("risky"), None).is_err());
+        assert!(ResultFilter::parse(Some("valid"), Some("DISPOSABLE_EMAIL")).is_err());
+    }
+
+    #[test]
+    fn test_pages() {
+        assert_eq!(page_of(1..=25, 1, 10), (1..=10).collect::<Vec<_>>());
+        assert_eq!(page_of(1..=25, 3, 10), vec![21, 22, 23, 24, 25]);
+        assert!(page_of(1..=25, 4, 10).is_empty());
+    }
+
+    #[test]
+    fn test_page_bounds() {
+        assert_eq!(page_error(1, DEFAULT_PER_PAGE), None);
+        assert_eq!(page_error(3, MAX_PER_PAGE), None);
+        assert!(page_error(0, 10).is_some());
+        assert!(page_error(1, 0).is_some());
+        assert!(page_error(1, MAX_PER_PAGE + 1).is_some());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::job_queue::JobQueue;
+use crate::normalize::normalize_domain;
+use crate::routes::email::{BulkEmailValidationResult, EmailValidationResponse};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+
+/// Number of domains listed in a summary's `top_domains`
+pub const TOP_DOMAINS: usize = 10;
+
+/// Addresses of one domain within a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DomainCount {
+    pub domain: String,
+    pub count: u64,
+}
+
+/// Aggregated report of a completed job, shown on dashboards
+///
+/// `verdicts` counts `VALID` plus each error code (`INVALID_DOMAIN`, `DISPOSABLE_EMAIL`, ...).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct JobSummary {
+    pub job_id: String,
+    pub total: u64,
+    pub valid: u64,
+    pub invalid: u64,
+    pub verdicts: BTreeMap<String, u64>,
+    pub top_domains: Vec<DomainCount>,
+}
+
+/// `VALID`, the error code, or `UNKNOWN` for an invalid result without one
+pub fn verdict(validation: &EmailValidationResponse) -> &str {
+    match &validation.error {
+        None if validation.is_valid => "VALID",
+        None => "UNKNOWN",
+        Some(error) => error.code.as_str(),
+    }
+}
+
+/// Running totals while walking a job's result chunks
+#[derive(Default)]
+struct SummaryBuilder {
+    total: u64,
+    valid: u64,
+    verdicts: BTreeMap<String, u64>,
+    domains: HashMap<String, u64>,
+}
+
+impl SummaryBuilder {
+    fn add(&mut self, rows: &[BulkEmailValidationResult]) {
+        for row in rows {
+            self.total += 1;
+            let verdict = verdict(&row.validation);
+            if row.validation.is_valid {
+                self.valid += 1;
+            }
+            *self.verdicts.entry(verdict.to_string()).or_default() += 1;
+            if let Some((_, domain)) = row.email.trim().rsplit_once('@') {
+                *self.domains.entry(normalize_domain(domain)).or_default() += 1;
+            }
+        }
+    }
+
+    fn finish(self, job_id: &str) -> JobSummary {
+        let mut top_domains: Vec<DomainCount> = self
+            .domains
+            .into_iter()
+            .map(|(domain, count)| DomainCount { domain, count })
+            .collect();
+        // Most common first; ties in name order so reports are stable
+        top_domains.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.domain.cmp(&b.domain)));
+        top_domains.truncate(TOP_DOMAINS);
+
+        JobSummary {
+            job_id: job_id.to_string(),
+            total: self.total,
+            valid: self.valid,
+            invalid: self.total - self.valid,
+            verdicts: self.verdicts,
+            top_domains,
+        }
+    }
+}
+
+/// Summary report of a completed job's results
+///
+/// The report is computed from the stored result chunks once and cached next to them,
+/// so dashboards polling many historical jobs don't repeat the aggregation. Changing a
+/// job's status drops the cached report (see [`JobQueue::update_job_status`]).
+pub async fn job_summary(job_queue: &JobQueue, job_id: &str) -> Result<JobSummary, String> {
+    let db_error = |e: redis::RedisError| format!("Failed to read job results: {}", e);
+
+    if let Some(cached) = job_queue.cached_summary(job_id).await.map_err(db_error)?
+        && let Ok(summary) = serde_json::from_str(&cached)
+    {
+        return Ok(summary);
+    }
+
+    let mut builder = SummaryBuilder::default();
+    let chunk_count = job_queue
+        .result_chunk_count(job_id)
+        .await
+        .map_err(db_error)?;
+    for index in 0..chunk_count {
+        let chunk = job_queue
+            .result_chunk(job_id, index)
+            .await
+            .map_err(db_error)?
+            .ok_or_else(|| "Job results expired".to_string())?;
+        let rows: Vec<BulkEmailValidationResult> =
+            serde_json::from_str(&chunk).map_err(|e| format!("Corrupt result chunk: {}", e))?;
+        builder.add(&rows);
+    }
+    let summary = builder.finish(job_id);
+
+    // Caching is an optimisation; a failed write only costs a recomputation
+    if let Ok(summary_json) = serde_json::to_string(&summary) {
+        let _ = job_queue.cache_summary(job_id, &summary_json).await;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::email::EmailValidationError;
+
+    fn row(email: &str, error_code: Option<&str>) -> BulkEmailValidationResult {
+        BulkEmailValidationResult {
+            email: email.to_string(),
+            validation: EmailValidationResponse {
+                is_valid: error_code.is_none(),
+                domain: None,
+                has_tag: None,
+                base_address: None,
+                status: error_code.is_none().then(|| "VALID".to_string()),
+                error: error_code.map(|code| EmailValidationError {
+                    code: code.to_string(),
+                    message: String::new(),
+                    violations: Vec::new(),
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn test_summary_counts_verdicts_and_domains() {
+        let mut builder = SummaryBuilder::default();
+        builder.add(&[
+            row("a@example.com", None),
+            row("b@Example.com", Some("ROLE_BASED_EMAIL")),
+        ]);
+        builder.add(&[
+            row("c@mailinator.com", Some("DISPOSABLE_EMAIL")),
+            row("not-an-email", Some("INVALID_SYNTAX")),
+        ]);
+        let summary = builder.finish("job-1");
+
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.valid, 1);
+        assert_eq!(summary.invalid, 3);
+        assert_eq!(summary.verdicts["VALID"], 1);
+        assert_eq!(summary.verdicts["DISPOSABLE_EMAIL"], 1);
+        assert_eq!(
+            summary.top_domains,
+            vec![
+                DomainCount {
+                    domain: "example.com".to_string(),
+                    count: 2
+                },
+                DomainCount {
+                    domain: "mailinator.com".to_string(),
+                    count: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_domains_are_capped() {
+        let mut builder = SummaryBuilder::default();
+        let rows: Vec<_> = (0..TOP_DOMAINS + 5)
+            .map(|i| row(&format!("user@domain{}.com", i), None))
+            .collect();
+        builder.add(&rows);
+
+        assert_eq!(builder.finish("job-2").top_domains.len(), TOP_DOMAINS);
+    }
+}
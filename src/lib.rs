@@ -1,10 +1,59 @@
+pub mod account_settings;
+pub mod app_env;
+pub mod audit_log;
 pub mod auth;
+pub mod branding;
+pub mod bulk;
+pub mod canary;
+pub mod checks;
+pub mod client_ip;
+pub mod cron;
+pub mod dns_overrides;
+pub mod email_verification;
+pub mod erasure;
+pub mod error;
+pub mod error_codes;
+pub mod feature_flags;
 pub mod graphql;
+pub mod grpc;
 pub mod handlers;
+pub mod health_checks;
+pub mod health_history;
+pub mod i18n;
+pub mod input_limits;
+pub mod job_export;
+pub mod job_payload;
 pub mod job_queue;
+pub mod job_results;
+pub mod job_summary;
+pub mod list_report;
+pub mod list_slots;
+pub mod local_cache;
+pub mod metering;
 pub mod models;
+pub mod normalize;
 pub mod openapi;
+pub mod organizations;
+pub mod outbound_proxies;
+pub mod password;
+pub mod pii;
+pub mod quota_events;
+pub mod read_only;
+pub mod redis_pool;
+pub mod response_fields;
 pub mod routes;
+pub mod saved_lists;
+pub mod schedules;
+pub mod sns;
+pub mod stores;
+pub mod sync;
+pub mod tls;
+pub mod validation_cache;
+pub mod validation_history;
+pub mod validation_service;
+pub mod validation_stats;
+pub mod verification;
+pub mod webhooks;
 pub mod worker;
 
 #[cfg(test)]
@@ -0,0 +1,255 @@
+use crate::job_export::score;
+use crate::job_queue::JobQueue;
+use crate::job_summary::{DomainCount, verdict};
+use crate::normalize::normalize_email;
+use crate::routes::email::BulkEmailValidationResult;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
+
+/// Domains listed in a report's `top_risky_domains`
+pub const TOP_RISKY_DOMAINS: usize = 10;
+
+/// Overall quality of a list, from its [`ListReport::quality_score`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ListGrade {
+    A,
+    B,
+    C,
+    D,
+    F,
+}
+
+impl ListGrade {
+    /// A from 90, B from 75, C from 60, D from 40, F below
+    pub fn from_score(score: u8) -> Self {
+        match score {
+            90.. => ListGrade::A,
+            75..=89 => ListGrade::B,
+            60..=74 => ListGrade::C,
+            40..=59 => ListGrade::D,
+            _ => ListGrade::F,
+        }
+    }
+}
+
+/// Hygiene report of an email list, for deciding whether it is safe to send to
+///
+/// Category percentages are shares of the unique addresses; `duplicate_percent` is the
+/// share of all rows that repeat an earlier address.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ListReport {
+    /// The bulk job the report was computed from; `None` for ad-hoc lists
+    pub job_id: Option<String>,
+    pub total: u64,
+    pub unique: u64,
+    pub invalid_percent: f64,
+    pub disposable_percent: f64,
+    pub role_based_percent: f64,
+    pub invalid_domain_percent: f64,
+    pub duplicate_percent: f64,
+    /// Domains with the most addresses that should be suppressed (score 0)
+    pub top_risky_domains: Vec<DomainCount>,
+    /// Mean deliverability score of the unique addresses, less half the duplicate
+    /// percentage; 0 to 100
+    pub quality_score: u8,
+    pub grade: ListGrade,
+}
+
+fn percent(count: u64, of: u64) -> f64 {
+    if of == 0 {
+        return 0.0;
+    }
+    (count as f64 * 10000.0 / of as f64).round() / 100.0
+}
+
+/// Running totals while walking a list's validation results
+#[derive(Default)]
+pub struct ReportBuilder {
+    total: u64,
+    seen: HashSet<String>,
+    invalid: u64,
+    disposable: u64,
+    role_based: u64,
+    invalid_domain: u64,
+    score_sum: u64,
+    risky_domains: HashMap<String, u64>,
+}
+
+impl ReportBuilder {
+    pub fn add(&mut self, rows: &[BulkEmailValidationResult]) {
+        for row in rows {
+            self.add_result(
+                &row.email,
+                row.validation.is_valid,
+                verdict(&row.validation),
+            );
+        }
+    }
+
+    /// Counts one address given its validity and verdict (`VALID` or the error code)
+    pub fn add_result(&mut self, email: &str, is_valid: bool, verdict: &str) {
+        self.total += 1;
+        let email = normalize_email(email);
+        if !self.seen.insert(email.clone()) {
+            return;
+        }
+
+        match verdict {
+            "DISPOSABLE_EMAIL" => self.disposable += 1,
+            "ROLE_BASED_EMAIL" => self.role_based += 1,
+            "INVALID_DOMAIN" | "INVALID_TLD" => self.invalid_domain += 1,
+            _ => {}
+        }
+        if !is_valid {
+            self.invalid += 1;
+        }
+
+        let score = score(verdict);
+        self.score_sum += score as u64;
+        if score == 0
+            && let Some((_, domain)) = email.rsplit_once('@')
+            && !domain.is_empty()
+        {
+            *self.risky_domains.entry(domain.to_string()).or_default() += 1;
+        }
+    }
+
+    pub fn finish(self, job_id: Option<&str>) -> ListReport {
+        let unique = self.seen.len() as u64;
+        let duplicate_percent = percent(self.total - unique, self.total);
+
+        let mut top_risky_domains: Vec<DomainCount> = self
+            .risky_domains
+            .into_iter()
+            .map(|(domain, count)| DomainCount { domain, count })
+            .collect();
+        top_risky_domains
+            .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.domain.cmp(&b.domain)));
+        top_risky_domains.truncate(TOP_RISKY_DOMAINS);
+
+        let mean_score = if unique == 0 {
+            0.0
+        } else {
+            self.score_sum as f64 / unique as f64
+        };
+        let quality_score = (mean_score - duplicate_percent / 2.0)
+            .clamp(0.0, 100.0)
+            .round() as u8;
+
+        ListReport {
+            job_id: job_id.map(str::to_string),
+            total: self.total,
+            unique,
+            invalid_percent: percent(self.invalid, unique),
+            disposable_percent: percent(self.disposable, unique),
+            role_based_percent: percent(self.role_based, unique),
+            invalid_domain_percent: percent(self.invalid_domain, unique),
+            duplicate_percent,
+            top_risky_domains,
+            quality_score,
+            grade: ListGrade::from_score(quality_score),
+        }
+    }
+}
+
+/// Hygiene report of a completed job's results
+///
+/// Computed from the stored result chunks and cached like [`crate::job_summary::job_summary`].
+pub async fn job_report(job_queue: &JobQueue, job_id: &str) -> Result<ListReport, String> {
+    let db_error = |e: redis::RedisError| format!("Failed to read job results: {}", e);
+
+    if let Some(cached) = job_queue.cached_report(job_id).await.map_err(db_error)?
+        && let Ok(report) = serde_json::from_str(&cached)
+    {
+        return Ok(report);
+    }
+
+    let mut builder = ReportBuilder::default();
+    let chunk_count = job_queue
+        .result_chunk_count(job_id)
+        .await
+        .map_err(db_error)?;
+    for index in 0..chunk_count {
+        let chunk = job_queue
+            .result_chunk(job_id, index)
+            .await
+            .map_err(db_error)?
+            .ok_or_else(|| "Job results expired".to_string())?;
+        let rows: Vec<BulkEmailValidationResult> =
+            serde_json::from_str(&chunk).map_err(|e| format!("Corrupt result chunk: {}", e))?;
+        builder.add(&rows);
+    }
+    let report = builder.finish(Some(job_id));
+
+    if let Ok(report_json) = serde_json::to_string(&report) {
+        let _ = job_queue.cache_report(job_id, &report_json).await;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::email::{EmailValidationError, EmailValidationResponse};
+
+    fn row(email: &str, error_code: Option<&str>) -> BulkEmailValidationResult {
+        BulkEmailValidationResult {
+            email: email.to_string(),
+            validation: EmailValidationResponse {
+                is_valid: error_code.is_none(),
+                domain: None,
+                has_tag: None,
+                base_address: None,
+                status: error_code.is_none().then(|| "VALID".to_string()),
+                error: error_code.map(|code| EmailValidationError {
+                    code: code.to_string(),
+                    message: String::new(),
+                    violations: Vec::new(),
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn test_report_percentages_and_grade() {
+        let mut builder = ReportBuilder::default();
+        builder.add(&[
+            row("a@example.com", None),
+            row("a@EXAMPLE.com", None),
+            row("info@example.com", Some("ROLE_BASED_EMAIL")),
+            row("x@mailinator.com", Some("DISPOSABLE_EMAIL")),
+            row("y@mailinator.com", Some("DISPOSABLE_EMAIL")),
+        ]);
+        let report = builder.finish(Some("job-1"));
+
+        assert_eq!(report.total, 5);
+        assert_eq!(report.unique, 4);
+        assert_eq!(report.duplicate_percent, 20.0);
+        assert_eq!(report.disposable_percent, 50.0);
+        assert_eq!(report.role_based_percent, 25.0);
+        assert_eq!(report.invalid_percent, 75.0);
+        assert_eq!(
+            report.top_risky_domains,
+            vec![DomainCount {
+                domain: "mailinator.com".to_string(),
+                count: 2
+            }]
+        );
+        // (100 + 60 + 0 + 0) / 4 - 20 / 2
+        assert_eq!(report.quality_score, 30);
+        assert_eq!(report.grade, ListGrade::F);
+    }
+
+    #[test]
+    fn test_clean_list_grades_a() {
+        let mut builder = ReportBuilder::default();
+        builder.add(&[row("a@example.com", None), row("b@example.org", None)]);
+        let report = builder.finish(None);
+
+        assert_eq!(report.quality_score, 100);
+        assert_eq!(report.grade, ListGrade::A);
+        assert!(report.top_risky_domains.is_empty());
+        assert_eq!(ListGrade::from_score(74), ListGrade::C);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::sync::SyncList;
+use mongodb::bson::{Bson, Document, doc};
+use mongodb::options::ReturnDocument;
+use mongodb::{Client, Collection, IndexModel};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// One of the two versions of a reference list kept side by side
+///
+/// `blue` is the original collection (e.g. `DB_DISPOSABLE_EMAILS_COLLECTION`);
+/// `green` is the same name with a `_green` suffix. A new upstream list is loaded
+/// into the inactive slot, then activated; rollback switches back instantly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ListSlot {
+    #[default]
+    Blue,
+    Green,
+}
+
+impl ListSlot {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListSlot::Blue => "blue",
+            ListSlot::Green => "green",
+        }
+    }
+}
+
+/// Which slot of a list is serving, stored in the `list_slots` collection keyed by list name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlotState {
+    #[serde(default)]
+    pub active: ListSlot,
+    #[serde(default)]
+    pub previous: Option<ListSlot>,
+    /// Unix timestamp of the last switch
+    #[serde(default)]
+    pub switched_at: Option<i64>,
+}
+
+/// Why a slot switch was refused
+#[derive(Debug, PartialEq, Eq)]
+pub enum SwitchError {
+    /// The target slot holds no entries; activating it would disable the list
+    EmptySlot(ListSlot),
+    /// Rollback requested but the list has never been switched
+    NoPreviousSlot,
+    /// Another switch landed between reading and writing the state
+    Conflict,
+    Database(String),
+}
+
+fn list_id(list: SyncList) -> &'static str {
+    match list {
+        SyncList::Disposable => "disposable",
+        SyncList::RoleBased => "role_based",
+    }
+}
+
+fn database_name() -> String {
+    std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string())
+}
+
+fn state_collection(mongo_client: &Client) -> Collection<SlotState> {
+    mongo_client
+        .database(&database_name())
+        .collection("list_slots")
+}
+
+/// Collection backing one slot of a list
+pub fn slot_collection(list: SyncList, slot: ListSlot) -> String {
+    let base = list.base_collection_name();
+    match slot {
+        ListSlot::Blue => base,
+        ListSlot::Green => format!("{}_green", base),
+    }
+}
+
+/// Current slot state of a list; lists that were never switched serve `blue`
+pub async fn slot_state(
+    mongo_client: &Client,
+    list: SyncList,
+) -> Result<SlotState, mongodb::error::Error> {
+    Ok(state_collection(mongo_client)
+        .find_one(doc! { "_id": list_id(list) })
+        .await?
+        .unwrap_or_default())
+}
+
+/// Name of the collection currently serving lookups for a list
+pub async fn active_collection(
+    mongo_client: &Client,
+    list: SyncList,
+) -> Result<String, mongodb::error::Error> {
+    let state = slot_state(mongo_client, list).await?;
+    Ok(slot_collection(list, state.active))
+}
+
+/// Creates the index on each list's value field that lookups use, in both slots
+///
+/// Disposable domain lookups ask for a domain's parents and `*.` patterns along with
+/// the domain, all as exact values, so they stay index lookups however long the list.
+pub async fn ensure_indexes(mongo_client: &Client) -> Result<(), String> {
+    let db = mongo_client.database(&database_name());
+    for list in [SyncList::Disposable, SyncList::RoleBased] {
+        for slot in [ListSlot::Blue, ListSlot::Green] {
+            db.collection::<Document>(&slot_collection(list, slot))
+                .create_index(
+                    IndexModel::builder()
+                        .keys(doc! { list.value_field(): 1 })
+                        .build(),
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Number of entries in each slot, for inspecting a staged list before switching
+pub async fn slot_sizes(
+    mongo_client: &Client,
+    list: SyncList,
+) -> Result<[(ListSlot, u64); 2], mongodb::error::Error> {
+    let db = mongo_client.database(&database_name());
+    let mut sizes = [(ListSlot::Blue, 0), (ListSlot::Green, 0)];
+    for (slot, size) in sizes.iter_mut() {
+        *size = db
+            .collection::<Document>(&slot_collection(list, *slot))
+            .estimated_document_count()
+            .await?;
+    }
+    Ok(sizes)
+}
+
+/// Makes `target` the serving slot of a list in one atomic update
+///
+/// Switching to the slot that is already active is a no-op.
+pub async fn activate_slot(
+    mongo_client: &Client,
+    list: SyncList,
+    target: ListSlot,
+) -> Result<SlotState, SwitchError> {
+    let db_error = |e: mongodb::error::Error| SwitchError::Database(e.to_string());
+
+    let current = slot_state(mongo_client, list).await.map_err(db_error)?;
+    if current.active == target {
+        return Ok(current);
+    }
+
+    let entries = mongo_client
+        .database(&database_name())
+        .collection::<Document>(&slot_collection(list, target))
+        .estimated_document_count()
+        .await
+        .map_err(db_error)?;
+    if entries == 0 {
+        return Err(SwitchError::EmptySlot(target));
+    }
+
+    // Only apply the switch if nobody else switched since we read the state;
+    // a list that was never switched has no document and counts as `blue`
+    let expected_active = match current.active {
+        ListSlot::Blue => doc! { "$in": [ListSlot::Blue.as_str(), Bson::Null] },
+        slot => doc! { "$eq": slot.as_str() },
+    };
+    let updated = state_collection(mongo_client)
+        .find_one_and_update(
+            doc! { "_id": list_id(list), "active": expected_active },
+            doc! { "$set": {
+                "active": target.as_str(),
+                "previous": current.active.as_str(),
+                "switched_at": chrono::Utc::now().timestamp(),
+            } },
+        )
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .await;
+
+    match updated {
+        Ok(Some(state)) => Ok(state),
+        Ok(None) => Err(SwitchError::Conflict),
+        // The upsert collides with the existing document when its `active` changed
+        Err(e) if e.to_string().contains("E11000") => Err(SwitchError::Conflict),
+        Err(e) => Err(db_error(e)),
+    }
+}
+
+/// Switches a list back to the slot that served before the last switch
+pub async fn rollback_slot(
+    mongo_client: &Client,
+    list: SyncList,
+) -> Result<SlotState, SwitchError> {
+    let current = slot_state(mongo_client, list)
+        .await
+        .map_err(|e| SwitchError::Database(e.to_string()))?;
+    let previous = current.previous.ok_or(SwitchError::NoPreviousSlot)?;
+    activate_slot(mongo_client, list, previous).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_collections() {
+        unsafe {
+            std::env::remove_var("DB_DISPOSABLE_EMAILS_COLLECTION");
+        }
+        assert_eq!(
+            slot_collection(SyncList::Disposable, ListSlot::Blue),
+            "disposable_email_domains"
+        );
+        assert_eq!(
+            slot_collection(SyncList::Disposable, ListSlot::Green),
+            "disposable_email_domains_green"
+        );
+        assert_eq!(
+            slot_collection(SyncList::RoleBased, ListSlot::Green),
+            "role_based_emails_green"
+        );
+    }
+
+    #[test]
+    fn test_slot_state_defaults_to_blue() {
+        let state: SlotState = mongodb::bson::from_document(doc! { "_id": "disposable" }).unwrap();
+        assert_eq!(state.active, ListSlot::Blue);
+        assert_eq!(state.previous, None);
+    }
+}
@@ -0,0 +1,393 @@
+use crate::stores::CacheStore;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Entries kept per tier when `*_CACHE_LOCAL_CAPACITY` is unset
+pub const DEFAULT_LOCAL_CAPACITY: usize = 10_000;
+
+/// Seconds a validation result stays in process when `VALIDATION_CACHE_LOCAL_TTL_SECS` is unset
+pub const DEFAULT_VALIDATION_LOCAL_TTL_SECS: u64 = 60;
+
+/// Seconds a DNS verdict stays in process when `DNS_CACHE_LOCAL_TTL_SECS` is unset
+pub const DEFAULT_DNS_LOCAL_TTL_SECS: u64 = 300;
+
+/// How often a tier reads its invalidation counter (see [`LocalCache::invalidated_by`])
+pub const INVALIDATION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Size and lifetime of an in-process tier
+///
+/// Invalidations reach the tiers of other instances through a counter in the store
+/// within [`INVALIDATION_CHECK_INTERVAL`]. The lifetime caps how long an instance may
+/// serve an invalidated entry while it can't read that counter, so it is kept short.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTierConfig {
+    /// Most entries kept; 0 disables the tier
+    pub capacity: usize,
+    pub ttl: Duration,
+}
+
+impl LocalTierConfig {
+    /// `VALIDATION_CACHE_LOCAL_CAPACITY` and `VALIDATION_CACHE_LOCAL_TTL_SECS`
+    pub fn validation_from_env() -> Self {
+        Self::from_env("VALIDATION", DEFAULT_VALIDATION_LOCAL_TTL_SECS)
+    }
+
+    /// `DNS_CACHE_LOCAL_CAPACITY` and `DNS_CACHE_LOCAL_TTL_SECS`
+    pub fn dns_from_env() -> Self {
+        Self::from_env("DNS", DEFAULT_DNS_LOCAL_TTL_SECS)
+    }
+
+    fn from_env(prefix: &str, default_ttl_secs: u64) -> Self {
+        let read = |name: String| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        };
+        Self {
+            capacity: read(format!("{}_CACHE_LOCAL_CAPACITY", prefix))
+                .map(|capacity| capacity as usize)
+                .unwrap_or(DEFAULT_LOCAL_CAPACITY),
+            ttl: Duration::from_secs(
+                read(format!("{}_CACHE_LOCAL_TTL_SECS", prefix))
+                    .filter(|secs| *secs > 0)
+                    .unwrap_or(default_ttl_secs),
+            ),
+        }
+    }
+}
+
+/// Share of lookups that were hits; 0 before the first lookup
+pub fn hit_rate(hits: u64, misses: u64) -> f64 {
+    let lookups = hits + misses;
+    if lookups == 0 {
+        0.0
+    } else {
+        hits as f64 / lookups as f64
+    }
+}
+
+/// Counters of one in-process tier, since the instance started
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocalCacheStats {
+    pub entries: usize,
+    pub capacity: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+}
+
+struct LocalEntry {
+    value: String,
+    expires_at: Option<Instant>,
+    /// Position in `LocalEntries::recency`
+    used: u64,
+}
+
+/// Store counter bumped on every invalidation, and its value when last read
+struct InvalidationWatch {
+    key: &'static str,
+    seen: Mutex<Option<(Instant, Option<String>)>>,
+}
+
+#[derive(Default)]
+struct LocalEntries {
+    entries: HashMap<String, LocalEntry>,
+    /// Keys by last use, least recently used first
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl LocalEntries {
+    fn touch(&mut self, key: &str) {
+        self.tick += 1;
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.recency.remove(&entry.used);
+            entry.used = self.tick;
+            self.recency.insert(self.tick, key.to_string());
+        }
+    }
+
+    fn remove(&mut self, key: &str) -> bool {
+        match self.entries.remove(key) {
+            Some(entry) => {
+                self.recency.remove(&entry.used);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Bounded in-process LRU of serialized values, the tier in front of Redis
+///
+/// Hot entries are answered without a network hop, and while Redis can't be
+/// reached this tier keeps serving what it holds. Entries live no longer than their
+/// Redis counterparts, nor than the tier's own `max_ttl`. Once full, the least
+/// recently read or written entry makes room for the new one.
+///
+/// Removing an entry only affects this instance. A tier [`Self::invalidated_by`] a
+/// store counter empties itself when another instance bumps that counter.
+pub struct LocalCache {
+    capacity: usize,
+    max_ttl: Option<Duration>,
+    invalidations: Option<InvalidationWatch>,
+    inner: Mutex<LocalEntries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl LocalCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            max_ttl: None,
+            invalidations: None,
+            inner: Mutex::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// `None` when the configuration disables the tier
+    pub fn from_config(config: LocalTierConfig) -> Option<Self> {
+        (config.capacity > 0).then(|| Self::new(config.capacity).with_max_ttl(config.ttl))
+    }
+
+    /// Caps the lifetime of every entry at `ttl`
+    pub fn with_max_ttl(mut self, ttl: Duration) -> Self {
+        self.max_ttl = Some(ttl);
+        self
+    }
+
+    /// Empties the tier whenever the store counter at `key` moves, which instances
+    /// bump with `INCR` after invalidating; see [`Self::sync`]
+    pub fn invalidated_by(mut self, key: &'static str) -> Self {
+        self.invalidations = Some(InvalidationWatch {
+            key,
+            seen: Mutex::default(),
+        });
+        self
+    }
+
+    /// Reads the invalidation counter from `store`, at most once per
+    /// [`INVALIDATION_CHECK_INTERVAL`], and empties the tier if it moved since the last
+    /// read
+    ///
+    /// Call before reading the tier. While the store can't be reached the entries
+    /// are kept, bounded by `max_ttl`.
+    pub async fn sync(&self, store: &dyn CacheStore) {
+        let Some(watch) = &self.invalidations else {
+            return;
+        };
+        let checked_at = watch.seen.lock().unwrap().as_ref().map(|(at, _)| *at);
+        if checked_at.is_some_and(|at| at.elapsed() < INVALIDATION_CHECK_INTERVAL) {
+            return;
+        }
+        let current = store.get(watch.key).await;
+        let mut seen = watch.seen.lock().unwrap();
+        let previous = seen.take().map(|(_, value)| value);
+        let current = match current {
+            Ok(current) => {
+                if previous
+                    .as_ref()
+                    .is_some_and(|previous| *previous != current)
+                {
+                    self.clear();
+                }
+                current
+            }
+            Err(_) => previous.flatten(),
+        };
+        *seen = Some((Instant::now(), current));
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        let value = self.lookup(key);
+        let counter = if value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    fn lookup(&self, key: &str) -> Option<String> {
+        let mut inner = self.inner.lock().unwrap();
+        let entry = inner.entries.get(key)?;
+        let value = entry.value.clone();
+        if entry
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Instant::now())
+        {
+            inner.remove(key);
+            return None;
+        }
+        inner.touch(key);
+        Some(value)
+    }
+
+    /// Stores `value` for `ttl`, or as long as `max_ttl` allows when `None`
+    pub fn insert(&self, key: &str, value: &str, ttl: Option<Duration>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let ttl = match (ttl, self.max_ttl) {
+            (Some(ttl), Some(max_ttl)) => Some(ttl.min(max_ttl)),
+            (ttl, max_ttl) => ttl.or(max_ttl),
+        };
+        let mut inner = self.inner.lock().unwrap();
+        inner.remove(key);
+        while inner.entries.len() >= self.capacity {
+            let Some((_, oldest)) = inner.recency.pop_first() else {
+                break;
+            };
+            inner.entries.remove(&oldest);
+        }
+        inner.tick += 1;
+        let used = inner.tick;
+        inner.recency.insert(used, key.to_string());
+        inner.entries.insert(
+            key.to_string(),
+            LocalEntry {
+                value: value.to_string(),
+                expires_at: ttl.map(|ttl| Instant::now() + ttl),
+                used,
+            },
+        );
+    }
+
+    pub fn remove(&self, key: &str) -> bool {
+        self.inner.lock().unwrap().remove(key)
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.entries.clear();
+        inner.recency.clear();
+    }
+
+    /// Drops every entry whose key matches; returns how many were dropped
+    pub fn remove_matching(&self, matches: impl Fn(&str) -> bool) -> u64 {
+        let mut inner = self.inner.lock().unwrap();
+        let keys: Vec<String> = inner
+            .entries
+            .keys()
+            .filter(|key| matches(key))
+            .cloned()
+            .collect();
+        keys.iter().filter(|key| inner.remove(key)).count() as u64
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> LocalCacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        LocalCacheStats {
+            entries: self.len(),
+            capacity: self.capacity,
+            hits,
+            misses,
+            hit_rate: hit_rate(hits, misses),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = LocalCache::new(2);
+        cache.insert("a", "1", None);
+        cache.insert("b", "2", None);
+        // Reading "a" makes "b" the oldest
+        assert_eq!(cache.get("a").as_deref(), Some("1"));
+        cache.insert("c", "3", None);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("b").is_none());
+        assert_eq!(cache.get("a").as_deref(), Some("1"));
+        assert_eq!(cache.get("c").as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn test_expired_entries_are_dropped() {
+        let cache = LocalCache::new(10);
+        cache.insert("gone", "1", Some(Duration::ZERO));
+        cache.insert("kept", "2", Some(Duration::from_secs(60)));
+
+        assert!(cache.get("gone").is_none());
+        assert_eq!(cache.get("kept").as_deref(), Some("2"));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_overwrite_and_remove() {
+        let cache = LocalCache::new(2);
+        cache.insert("a", "1", None);
+        cache.insert("a", "2", None);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get("a").as_deref(), Some("2"));
+
+        cache.insert("user@example.com", "x", None);
+        assert_eq!(
+            cache.remove_matching(|key| key.ends_with("@example.com")),
+            1
+        );
+        assert!(cache.remove("a"));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_max_ttl_caps_entry_lifetime() {
+        let cache = LocalCache::new(10).with_max_ttl(Duration::ZERO);
+        cache.insert("forever", "1", None);
+        cache.insert("long", "2", Some(Duration::from_secs(3600)));
+
+        assert!(cache.get("forever").is_none());
+        assert!(cache.get("long").is_none());
+    }
+
+    #[test]
+    fn test_stats_count_hits_and_misses() {
+        let cache = LocalCache::new(10);
+        cache.insert("a", "1", None);
+        cache.get("a");
+        cache.get("a");
+        cache.get("b");
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.capacity), (1, 10));
+        assert_eq!((stats.hits, stats.misses), (2, 1));
+        assert!((stats.hit_rate - 2.0 / 3.0).abs() < f64::EPSILON);
+        assert_eq!(hit_rate(0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_zero_capacity_disables_tier() {
+        let config = LocalTierConfig {
+            capacity: 0,
+            ttl: Duration::from_secs(60),
+        };
+        assert!(LocalCache::from_config(config).is_none());
+    }
+
+    #[test]
+    fn test_zero_capacity_stores_nothing() {
+        let cache = LocalCache::new(0);
+        cache.insert("a", "1", None);
+        assert!(cache.get("a").is_none());
+    }
+}
@@ -1,12 +1,33 @@
 use actix_web::{App, HttpServer, web::Data};
+use email_sanitizer::client_ip::{ClientIpResolver, TrustedProxies};
+use email_sanitizer::dns_overrides::DnsOverrides;
+use email_sanitizer::feature_flags::{FeatureFlags, Maintenance};
+use email_sanitizer::graphql::persisted_queries::PersistedQueryStore;
 use email_sanitizer::graphql::schema::create_schema;
+use email_sanitizer::grpc::{self, EmailSanitizerService};
+use email_sanitizer::handlers::validation::dnsmx::{self, DnsTransport, ResolverSettings};
+use email_sanitizer::handlers::validation::{bounce_feedback, suppression, suppression_list, tld};
+use email_sanitizer::health_checks;
+use email_sanitizer::health_history::{self, HealthHistory};
 use email_sanitizer::job_queue::JobQueue;
-use email_sanitizer::openapi::ApiDoc;
+use email_sanitizer::metering::Meter;
+use email_sanitizer::openapi::{self, ApiDoc};
+use email_sanitizer::outbound_proxies::{self, ProxyPool, ProxySettings};
+use email_sanitizer::pii::{self, PiiSettings};
+use email_sanitizer::read_only::ReadOnly;
+use email_sanitizer::redis_pool::{PoolSettings, RedisPool};
 use email_sanitizer::routes::email::RedisCache;
+use email_sanitizer::stores::MongoDomainLists;
+use email_sanitizer::sync::SyncStore;
+use email_sanitizer::tls::{self, ReloadingCertResolver, TlsSettings};
+use email_sanitizer::validation_history;
+use email_sanitizer::validation_service::ValidationBackends;
+use email_sanitizer::worker::{self, RunMode, ValidationWorker, WorkerConfig, WorkerPool};
+use email_sanitizer::{audit_log, auth, list_slots, saved_lists, schedules};
 use mongodb::Client as MongoClient;
 use std::env::VarError;
+use std::sync::Arc;
 use utoipa::OpenApi;
-use utoipa_swagger_ui::SwaggerUi;
 
 /// Email Sanitizer Service Entry Point
 ///
@@ -19,18 +40,112 @@ use utoipa_swagger_ui::SwaggerUi;
 ///
 /// # Endpoints
 /// - GraphQL: `/api/v1/graphql` (configured in routes)
-/// - Email validation: `/api/v1/validate-email`
+/// - Email validation: `/api/v1/validate-email`, and `/api/v2/validate-email` with verdicts and scores
 /// - Swagger UI: `/swagger-ui/`
-/// - OpenAPI spec: `/api-docs/openapi.json`
+/// - OpenAPI specs: `/api-docs/v1.json` (also `/api-docs/openapi.json`) and `/api-docs/v2.json`
+/// - gRPC: `email_sanitizer.v1.EmailSanitizer` on GRPC_PORT (see `proto/email_sanitizer.proto`)
 ///
 /// # Configuration
 /// - Server binds to `127.0.0.1:8080` by default. Port can be specified as an env variable named "PORT".
 /// - Environment variables loaded from `.env` file (if present)
 /// - Redis URL from REDIS_URL environment variable (defaults to localhost:6379)
 /// - Redis cache TTL from REDIS_CACHE_TTL environment variable (defaults to 86400 seconds/24 hours)
+/// - DNS_CACHE_TTL_UNAVAILABLE_SECS sets how long a SERVFAIL or resolver timeout is cached
+///   (defaults to 30 seconds, 0 never caches it); NXDOMAIN is cached for REDIS_CACHE_TTL
+/// - DNS_RESOLVER switches DNS checks to DNS-over-HTTPS (`doh`) or DNS-over-TLS (`dot`)
+///   at DNS_RESOLVER_PROVIDER (`google`, `cloudflare` or `quad9`), retrying over classic
+///   DNS unless DNS_RESOLVER_FALLBACK=false (see `ResolverSettings`)
+/// - DNS_NAMESERVERS replaces the provider with custom nameservers, and
+///   DNS_DOMAIN_OVERRIDES sends lookups under given domains to their own nameservers
+///   (split horizon); more domains can be overridden through `/api/v1/admin/dns/overrides`
+/// - OUTBOUND_PROXY_POOLS sends DoH lookups through named pools of HTTP or SOCKS5 proxies,
+///   OUTBOUND_PROXY_POOL picking the pool of each API or worker process; dead proxies
+///   leave the rotation until a health check passes (see `ProxySettings`)
+/// - REDIS_POOL_SIZE, REDIS_POOL_WAIT_TIMEOUT_MS and REDIS_CONNECT_TIMEOUT_MS size the Redis
+///   connection pool every store shares (defaults to 32 connections and 2000 ms timeouts)
+/// - Validation result TTLs from VALIDATION_CACHE_TTL_* environment variables (see `CacheTtlConfig`)
+/// - VALIDATION_CACHE_LOCAL_* and DNS_CACHE_LOCAL_* size the in-process LRU tiers in front
+///   of the Redis caches: `_CAPACITY` entries (defaults to 10000, 0 disables the tier) kept
+///   for at most `_TTL_SECS` (defaults to 60 for results, 300 for DNS verdicts)
+/// - READ_ONLY=true serves validations but rejects writes with 503 (DR replicas, maintenance)
+/// - FEATURE_MAINTENANCE_MODE, FEATURE_BULK_ENABLED and FEATURE_REGISTRATION_OPEN pin the
+///   feature flags otherwise switched through `PUT /api/v1/admin/flags/{flag}`; during
+///   maintenance everything but health, status and admin routes answers 503 with
+///   Retry-After MAINTENANCE_RETRY_AFTER_SECS (defaults to 300)
+/// - TRUSTED_PROXIES lists the load balancer addresses and CIDR ranges whose
+///   X-Forwarded-For header names the client (see `TrustedProxies`); unset, requests are
+///   attributed to the connecting peer
+/// - QUOTA_* and OVERAGE_* set each plan's monthly quota and overage policy (see `PlanQuota`)
+/// - GRPC_PORT starts the gRPC server next to the HTTP server (disabled when unset)
+/// - HEALTH_CHECK_INTERVAL_SECS sets how often MongoDB and Redis are probed for the
+///   health history (defaults to 30 seconds)
+/// - JOB_PAYLOAD_* bound the Redis memory of queued jobs (see `PayloadLimits`)
+/// - VALIDATION_HISTORY_RETENTION_DAYS sets how long validation history records are kept
+///   (defaults to 90 days; VALIDATION_HISTORY_ENABLED=false stops recording)
+/// - PII_JOB_PAYLOADS, PII_HISTORY and PII_AUDIT_LOG choose whether each store keeps
+///   addresses plain, as hashes keyed with PII_HASH_SALT, or encrypted with
+///   PII_ENCRYPTION_KEY (see `PiiSettings`)
+/// - AUDIT_LOG_MAX_BYTES sizes the capped audit log collection of authenticated requests
+///   (defaults to 1 GiB; AUDIT_LOG_ENABLED=false stops recording)
+/// - TLS_CERT_PATH and TLS_KEY_PATH serve HTTPS on TLS_PORT (defaults to 8443), with PORT
+///   redirecting to it; certificates come from an external ACME client, see
+///   `TlsSettings` for the redirect and ACME webroot options
+/// - TLD_LIST_REFRESH_HOURS sets how often the IANA TLD list is re-downloaded from
+///   TLD_LIST_URL (defaults to 24 hours; 0 keeps the bundled list)
+/// - APP_ENV=production hides the GraphQL playground and disables introspection
+///   (GRAPHQL_DEV_TOOLS=true keeps them, e.g. on staging)
+/// - GRAPHQL_APQ_TTL_SECS sets how long persisted GraphQL queries are kept (defaults to 7 days)
+/// - SUPPRESSION_LOOKUP_ENABLED=true rejects imported spamtraps, complainers and bouncers
+/// - BOUNCE_SOFT_LIMIT sets how many soft bounces reported to `/api/v1/feedback/bounces`
+///   suppress an address (defaults to 3; hard bounces and complaints suppress at once)
+/// - PASSWORD_MIN_LENGTH sets the shortest password accepted on registration (defaults to 10)
+/// - LEGACY_API_KEY_SUNSET (RFC 3339) ends the grace period of API keys issued before
+///   keys became random (defaults to 2027-01-15T00:00:00Z)
+/// - EMAIL_VERIFICATION_WEBHOOK_URL receives the verification links of new accounts
+///   (logged when unset), valid for EMAIL_VERIFICATION_TTL seconds (defaults to 24 hours)
+///   and rooted at PUBLIC_BASE_URL
+/// - SCHEDULE_POLL_SECS sets how often due re-validation schedules are started and
+///   finished runs recorded (defaults to 60 seconds)
+///
+/// # Worker mode
+/// `--mode worker` runs only the bulk validation worker, so job processing scales
+/// separately from the API. Each process runs WORKER_CONCURRENCY jobs at a time
+/// (defaults to 4) as consumers named after WORKER_ID (defaults to a random id); jobs of
+/// a worker that dies are re-queued once its heartbeat expires.
+///
+/// The API process also runs a worker unless EMBEDDED_WORKER=false. On SIGINT/SIGTERM
+/// workers stop claiming jobs and get WORKER_SHUTDOWN_GRACE_SECS (defaults to 30) to
+/// finish the running ones.
 #[actix_web::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv::dotenv().ok();
+    let mode = RunMode::from_args(std::env::args())?;
+
+    // How stored addresses are protected; a store set to encrypt without a key is refused
+    pii::init(PiiSettings::from_env().expect("Invalid PII protection settings"));
+    // Resolver used by DNS checks: classic DNS, DoH or DoT
+    dnsmx::init(ResolverSettings::from_env().expect("Invalid DNS resolver settings"));
+    // Egress proxies DoH lookups leave through, kept in rotation by health checks
+    if let Some(proxies) = ProxySettings::from_env().expect("Invalid outbound proxy settings") {
+        assert!(
+            dnsmx::settings().transport == DnsTransport::Https,
+            "OUTBOUND_PROXY_POOLS needs DNS_RESOLVER=doh"
+        );
+        outbound_proxies::init(ProxyPool::new(&proxies).expect("Invalid outbound proxy pool"));
+        if let Some(pool) = outbound_proxies::pool() {
+            let domain = health_checks::dns_probe_domain()
+                .unwrap_or_else(|| health_checks::DEFAULT_DNS_PROBE_DOMAIN.to_string());
+            println!(
+                "Outbound proxy pool {}: {} proxies",
+                pool.name(),
+                proxies.proxies.len()
+            );
+            tokio::spawn(pool.check_periodically(move |client| {
+                let domain = domain.clone();
+                async move { dnsmx::proxy_self_test(client, &domain).await }
+            }));
+        }
+    }
 
     // Initialize Redis cache
     let redis_url =
@@ -40,11 +155,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .and_then(|v| v.parse::<u64>().ok())
         .unwrap_or(86400); // Default 24 hours TTL
 
+    // One connection pool backs every Redis store created from REDIS_URL below
+    let pool_settings = PoolSettings::from_env();
+    RedisPool::new(&redis_url).expect("Failed to initialize Redis connection pool");
+    println!(
+        "Redis pool: up to {} connections, {:?} wait timeout",
+        pool_settings.max_size, pool_settings.wait_timeout
+    );
+
     let redis_cache =
         RedisCache::new(&redis_url, redis_ttl).expect("Failed to initialize Redis connection");
 
-    // Initialize job queue
-    let job_queue = JobQueue::new(&redis_url).expect("Failed to initialize job queue");
+    // Monthly quota counters, charged per validated address
+    let meter = Meter::new(&redis_url).expect("Failed to initialize usage meter");
+
+    // Initialize on-prem sync state
+    let sync_store = SyncStore::new(&redis_url).expect("Failed to initialize sync store");
 
     // Initialize MongoDB client
     let mongodb_uri =
@@ -52,9 +178,142 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mongo_client = MongoClient::with_uri_str(&mongodb_uri)
         .await
         .expect("Failed to initialize MongoDB client");
+    // Disposable and role-based lookups share the client's connection pool
+    let backends = ValidationBackends::from_env(
+        redis_cache,
+        Arc::new(MongoDomainLists::new(mongo_client.clone())),
+    );
+    // Runtime switches and DNS overrides set through the admin API, in the same store
+    let flags = FeatureFlags::from_store(backends.dns.store());
+    let dns_overrides = DnsOverrides::from_store(backends.dns.store());
+
+    // Initialize job queue; large address lists are spilled to MongoDB
+    let job_queue = JobQueue::new(&redis_url)
+        .expect("Failed to initialize job queue")
+        .with_payload_store(mongo_client.clone());
+
+    // Pick up DNS overrides set through the admin API, on API and worker instances alike
+    tokio::spawn(dns_overrides.clone().refresh_periodically());
+
+    // Keep the TLD list current; the bundled copy is used until the first refresh
+    if let Some(interval) = tld::refresh_interval() {
+        tokio::spawn(tld::refresh_periodically(interval));
+    }
+
+    if mode == RunMode::Worker {
+        let config = WorkerConfig::from_env();
+        println!(
+            "Starting validation worker {} with {} consumers",
+            config.worker_id, config.concurrency
+        );
+        let workers = WorkerPool::spawn(
+            ValidationWorker::new(job_queue, backends)
+                .with_config(config)
+                .with_webhook_secrets(mongo_client),
+        );
+        worker::shutdown_signal().await;
+        workers.shutdown(worker::shutdown_grace()).await;
+        return Ok(());
+    }
+
+    // Queued bulk jobs are processed here too unless dedicated workers handle them
+    let workers = worker::embedded_worker_enabled().then(|| {
+        WorkerPool::spawn(
+            ValidationWorker::new(job_queue.clone(), backends.clone())
+                .with_config(WorkerConfig::from_env())
+                .with_webhook_secrets(mongo_client.clone()),
+        )
+    });
+
+    // Record dependency health transitions for /health/history
+    let health_history =
+        HealthHistory::new(&redis_url).expect("Failed to initialize health history");
+    tokio::spawn(health_history::monitor(
+        health_history.clone(),
+        mongo_client.clone(),
+    ));
+
+    // Registration relies on a unique index to reject duplicate emails; keys are found by id
+    let users_client = mongo_client.clone();
+    tokio::spawn(async move {
+        if let Err(e) = auth::ensure_indexes(&users_client).await {
+            eprintln!("Failed to create user indexes: {}", e);
+        }
+    });
+
+    // Retention of the validation history is enforced by a TTL index
+    let history_client = mongo_client.clone();
+    tokio::spawn(async move {
+        if let Err(e) = validation_history::ensure_indexes(&history_client).await {
+            eprintln!("Failed to create validation history indexes: {}", e);
+        }
+    });
+
+    // The audit log is a capped collection: append-only, oldest records dropped when full
+    let audit_client = mongo_client.clone();
+    tokio::spawn(async move {
+        if let Err(e) = audit_log::ensure_collection(&audit_client).await {
+            eprintln!("Failed to create audit log collection: {}", e);
+        }
+    });
+
+    // Disposable and role-based lookups go through an index on each list slot's values
+    let list_client = mongo_client.clone();
+    tokio::spawn(async move {
+        if let Err(e) = list_slots::ensure_indexes(&list_client).await {
+            eprintln!("Failed to create reference list indexes: {}", e);
+        }
+    });
+
+    // Suppression lookups go through a unique index on the address hash
+    let suppression_client = mongo_client.clone();
+    tokio::spawn(async move {
+        if let Err(e) = suppression::ensure_indexes(&suppression_client).await {
+            eprintln!("Failed to create suppression indexes: {}", e);
+        }
+    });
+
+    // Feedback lookups go through a unique index on the owner and address hash
+    let feedback_client = mongo_client.clone();
+    tokio::spawn(async move {
+        if let Err(e) = bounce_feedback::ensure_indexes(&feedback_client).await {
+            eprintln!("Failed to create bounce feedback indexes: {}", e);
+        }
+    });
+
+    let suppression_list_client = mongo_client.clone();
+    tokio::spawn(async move {
+        if let Err(e) = suppression_list::ensure_indexes(&suppression_list_client).await {
+            eprintln!("Failed to create suppression list indexes: {}", e);
+        }
+    });
+
+    let saved_lists_client = mongo_client.clone();
+    tokio::spawn(async move {
+        if let Err(e) = saved_lists::ensure_indexes(&saved_lists_client).await {
+            eprintln!("Failed to create saved list indexes: {}", e);
+        }
+    });
+
+    // Re-validation schedules; every API process polls, and each run is claimed by one
+    let schedules_client = mongo_client.clone();
+    tokio::spawn(async move {
+        if let Err(e) = schedules::ensure_indexes(&schedules_client).await {
+            eprintln!("Failed to create schedule indexes: {}", e);
+        }
+    });
+    tokio::spawn(schedules::run_periodically(
+        mongo_client.clone(),
+        job_queue.clone(),
+        Some(meter.clone()),
+        schedules::poll_interval(),
+    ));
 
     // Create GraphQL schema
     let schema = create_schema();
+    let persisted_queries =
+        PersistedQueryStore::new(&redis_url, PersistedQueryStore::ttl_from_env())
+            .expect("Failed to initialize persisted query store");
 
     let port: Result<String, VarError> = std::env::var("PORT");
     let port = match port {
@@ -68,25 +327,94 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
-    HttpServer::new(move || {
-        let openapi = ApiDoc::openapi();
+    // gRPC runs on its own port next to the HTTP server when GRPC_PORT is set
+    if let Some(grpc_addr) = grpc::listen_addr() {
+        let service = EmailSanitizerService::new(
+            backends.clone(),
+            flags.clone(),
+            job_queue.clone(),
+            mongo_client.clone(),
+        )
+        .with_meter(meter.clone());
+        tokio::spawn(async move {
+            if let Err(e) = tonic::transport::Server::builder()
+                .add_service(service.into_server())
+                .serve(grpc_addr)
+                .await
+            {
+                eprintln!("gRPC server error: {}", e);
+            }
+        });
+    }
+
+    // Parsed once so a malformed list stops startup rather than every worker thread
+    let trusted_proxies = TrustedProxies::from_env();
 
+    let server = HttpServer::new(move || {
         App::new()
-            .app_data(Data::new(openapi.clone()))
+            .app_data(Data::new(ApiDoc::openapi()))
             .app_data(Data::new(schema.clone()))
-            .app_data(Data::new(redis_cache.clone()))
+            .app_data(Data::new(persisted_queries.clone()))
+            .app_data(Data::new(backends.clone()))
+            .app_data(Data::new(flags.clone()))
+            .app_data(Data::new(dns_overrides.clone()))
             .app_data(Data::new(job_queue.clone()))
+            .app_data(Data::new(sync_store.clone()))
+            .app_data(Data::new(meter.clone()))
+            .app_data(Data::new(health_history.clone()))
             .app_data(Data::new(mongo_client.clone()))
+            .wrap(ReadOnly::from_env())
+            .wrap(Maintenance::from_app_data())
+            .wrap(ClientIpResolver::new(trusted_proxies.clone()))
             .configure(email_sanitizer::routes::configure)
-            .service(SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi))
-    })
-    .bind((
+            .service(openapi::swagger_ui())
+    });
+    let http_addr = (
         "0.0.0.0", // Changed from 127.0.0.1 to allow external connections
         port.parse::<u16>().expect("Failed to parse port"),
-    ))?
-    .run()
-    .await
-    .map_err(|e| e.into())
+    );
+
+    let served: std::io::Result<()> = async {
+        let Some(tls_settings) = TlsSettings::from_env() else {
+            return server.bind(http_addr)?.run().await;
+        };
+
+        let resolver = Arc::new(
+            ReloadingCertResolver::load(&tls_settings.cert_path, &tls_settings.key_path)
+                .expect("Failed to load TLS certificate; obtain it with an ACME client first"),
+        );
+        tokio::spawn(tls::watch_certificates(
+            resolver.clone(),
+            tls_settings.reload_interval,
+        ));
+        let server = server.bind_rustls_0_23(
+            ("0.0.0.0", tls_settings.port),
+            tls::server_config(resolver).expect("Failed to build TLS configuration"),
+        )?;
+
+        if !tls_settings.redirect_http {
+            return server.bind(http_addr)?.run().await;
+        }
+
+        // Plain HTTP only answers ACME challenges and redirects to HTTPS
+        let redirect_server = HttpServer::new(move || {
+            App::new()
+                .app_data(Data::new(tls_settings.clone()))
+                .configure(tls::configure_redirect)
+        })
+        .bind(http_addr)?
+        .run();
+
+        futures::try_join!(server.run(), redirect_server)?;
+        Ok(())
+    }
+    .await;
+
+    // The servers have stopped; let the embedded worker finish its running jobs
+    if let Some(workers) = workers {
+        workers.shutdown(worker::shutdown_grace()).await;
+    }
+    served.map_err(|e| e.into())
 }
 
 #[cfg(test)]
@@ -95,16 +423,20 @@ mod tests {
     use email_sanitizer::graphql::schema::create_schema;
     use email_sanitizer::openapi::ApiDoc;
     use email_sanitizer::routes::email::RedisCache;
+    use email_sanitizer::stores::MemoryDomainLists;
+    use email_sanitizer::validation_service::ValidationBackends;
+    use std::sync::Arc;
     use utoipa::OpenApi;
     use utoipa_swagger_ui::SwaggerUi;
 
-    // Helper function to create test Redis cache
-    fn create_test_redis_cache() -> RedisCache {
+    // Helper function to create test validation backends on the test Redis
+    fn create_test_backends() -> ValidationBackends {
         let redis_url = std::env::var("TEST_REDIS_URL")
             .unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
 
-        RedisCache::new(&redis_url, 3600) // 1 hour TTL for tests
-            .expect("Failed to initialize test Redis connection")
+        let redis_cache = RedisCache::new(&redis_url, 3600) // 1 hour TTL for tests
+            .expect("Failed to initialize test Redis connection");
+        ValidationBackends::from_env(redis_cache, Arc::new(MemoryDomainLists::default()))
     }
 
     #[actix_web::test]
@@ -112,13 +444,13 @@ mod tests {
         // Create the app with the same configuration as in main()
         let schema = create_schema();
         let openapi = ApiDoc::openapi();
-        let redis_cache = create_test_redis_cache();
+        let backends = create_test_backends();
 
         let _app = test::init_service(
             App::new()
                 .app_data(Data::new(openapi.clone()))
                 .app_data(Data::new(schema.clone()))
-                .app_data(Data::new(redis_cache.clone()))
+                .app_data(Data::new(backends.clone()))
                 .configure(email_sanitizer::routes::configure)
                 .service(
                     SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi),
@@ -134,13 +466,13 @@ mod tests {
         // Set up test app with the same configuration as in main()
         let schema = create_schema();
         let openapi = ApiDoc::openapi();
-        let redis_cache = create_test_redis_cache();
+        let backends = create_test_backends();
 
         let app = test::init_service(
             App::new()
                 .app_data(Data::new(openapi.clone()))
                 .app_data(Data::new(schema.clone()))
-                .app_data(Data::new(redis_cache.clone()))
+                .app_data(Data::new(backends.clone()))
                 .configure(email_sanitizer::routes::configure)
                 .service(
                     SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi),
@@ -176,13 +508,13 @@ mod tests {
         // Set up test app with the same configuration as in main()
         let schema = create_schema();
         let openapi = ApiDoc::openapi();
-        let redis_cache = create_test_redis_cache();
+        let backends = create_test_backends();
 
         let app = test::init_service(
             App::new()
                 .app_data(Data::new(openapi.clone()))
                 .app_data(Data::new(schema.clone()))
-                .app_data(Data::new(redis_cache.clone()))
+                .app_data(Data::new(backends.clone()))
                 .configure(email_sanitizer::routes::configure)
                 .service(
                     SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi),
@@ -221,13 +553,13 @@ mod tests {
         // Set up test app with the same configuration as in main()
         let schema = create_schema();
         let openapi = ApiDoc::openapi();
-        let redis_cache = create_test_redis_cache();
+        let backends = create_test_backends();
 
         let app = test::init_service(
             App::new()
                 .app_data(Data::new(openapi.clone()))
                 .app_data(Data::new(schema.clone()))
-                .app_data(Data::new(redis_cache.clone()))
+                .app_data(Data::new(backends.clone()))
                 .configure(email_sanitizer::routes::configure)
                 .service(
                     SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi),
@@ -245,11 +577,11 @@ mod tests {
         // Execute request
         let resp = test::call_service(&app, req).await;
 
-        // Assert response is either successful or a 400 (if query is invalid)
-        // We're just testing the endpoint exists, not full GraphQL functionality
-        assert!(
-            resp.status().is_success() || resp.status().as_u16() == 400,
-            "GraphQL endpoint should exist and return either 200 or 400, got: {}",
+        // The endpoint exists but, like the rest of /api/v1, requires credentials
+        assert_eq!(
+            resp.status().as_u16(),
+            401,
+            "GraphQL endpoint should require authentication, got: {}",
             resp.status()
         );
     }
@@ -259,13 +591,13 @@ mod tests {
         // Set up test app with the same configuration as in main()
         let schema = create_schema();
         let openapi = ApiDoc::openapi();
-        let redis_cache = create_test_redis_cache();
+        let backends = create_test_backends();
 
         let app = test::init_service(
             App::new()
                 .app_data(Data::new(openapi.clone()))
                 .app_data(Data::new(schema.clone()))
-                .app_data(Data::new(redis_cache.clone()))
+                .app_data(Data::new(backends.clone()))
                 .configure(email_sanitizer::routes::configure)
                 .service(
                     SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi),
@@ -307,13 +639,13 @@ mod tests {
         // Set up test app with the same configuration as in main()
         let schema = create_schema();
         let openapi = ApiDoc::openapi();
-        let redis_cache = create_test_redis_cache();
+        let backends = create_test_backends();
 
         let app = test::init_service(
             App::new()
                 .app_data(Data::new(openapi.clone()))
                 .app_data(Data::new(schema.clone()))
-                .app_data(Data::new(redis_cache.clone()))
+                .app_data(Data::new(backends.clone()))
                 .configure(email_sanitizer::routes::configure)
                 .service(
                     SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi),
@@ -0,0 +1,535 @@
+use crate::auth::{AuthenticatedAccount, PlanTier};
+use crate::error::ApiError;
+use crate::organizations;
+use crate::quota_events::{self, QuotaUsage};
+use crate::redis_pool::RedisPool;
+use actix_web::{HttpMessage, HttpRequest, web};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use mongodb::Client as MongoClient;
+use redis::streams::StreamMaxlen;
+use redis::{AsyncCommands, RedisError};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Stream consumed by billing to charge soft overage
+pub const BILLING_EVENTS_KEY: &str = "billing_events";
+
+/// Billing events kept in the stream (approximately); older ones are trimmed on write
+const MAX_BILLING_EVENTS: usize = 100_000;
+
+/// Usage counters outlive their month by a few days so late reads still see them
+const USAGE_TTL_SECS: i64 = 35 * 24 * 3600;
+
+/// What happens once an account uses up its quota and grace allowance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OveragePolicy {
+    /// Reject validations with 429 until the next month
+    HardStop,
+    /// Keep serving and record a billing event for the addresses over quota
+    SoftOverage,
+    /// Keep serving at most the trickle rate of addresses per minute
+    Throttle,
+}
+
+impl OveragePolicy {
+    pub fn parse(policy: &str) -> Option<Self> {
+        match policy.trim().to_ascii_lowercase().as_str() {
+            "hard_stop" | "hard" => Some(OveragePolicy::HardStop),
+            "soft_overage" | "soft" => Some(OveragePolicy::SoftOverage),
+            "throttle" => Some(OveragePolicy::Throttle),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OveragePolicy::HardStop => "hard_stop",
+            OveragePolicy::SoftOverage => "soft_overage",
+            OveragePolicy::Throttle => "throttle",
+        }
+    }
+}
+
+/// Monthly quota and overage behaviour of a plan
+///
+/// Read per plan from the environment (`FREE`, `PRO` or `ENTERPRISE` in the names):
+/// - `QUOTA_<PLAN>`: validated addresses per calendar month, 0 for unlimited
+///   (defaults 1000, 100000 and unlimited)
+/// - `OVERAGE_POLICY_<PLAN>`: `hard_stop`, `soft_overage` or `throttle`
+///   (defaults `hard_stop` for free plans, `soft_overage` otherwise)
+///
+/// and for all plans:
+/// - `OVERAGE_GRACE_PERCENT`: share of the quota served on top before the policy
+///   applies (default 10)
+/// - `OVERAGE_TRICKLE_PER_MINUTE`: addresses per minute a throttled account keeps
+///   (default 10)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanQuota {
+    /// `None` for unlimited plans
+    pub monthly_limit: Option<u64>,
+    pub grace_percent: u64,
+    pub policy: OveragePolicy,
+    pub trickle_per_minute: u64,
+}
+
+fn read_u64(name: &str) -> Option<u64> {
+    std::env::var(name).ok().and_then(|v| v.trim().parse().ok())
+}
+
+impl PlanQuota {
+    pub fn for_plan(plan: PlanTier) -> Self {
+        let (name, default_limit, default_policy) = match plan {
+            PlanTier::Free => ("FREE", 1000, OveragePolicy::HardStop),
+            PlanTier::Pro => ("PRO", 100_000, OveragePolicy::SoftOverage),
+            PlanTier::Enterprise => ("ENTERPRISE", 0, OveragePolicy::SoftOverage),
+        };
+        let limit = read_u64(&format!("QUOTA_{}", name)).unwrap_or(default_limit);
+        let policy = std::env::var(format!("OVERAGE_POLICY_{}", name))
+            .ok()
+            .and_then(|v| OveragePolicy::parse(&v))
+            .unwrap_or(default_policy);
+
+        Self {
+            monthly_limit: (limit > 0).then_some(limit),
+            grace_percent: read_u64("OVERAGE_GRACE_PERCENT").unwrap_or(10),
+            policy,
+            trickle_per_minute: read_u64("OVERAGE_TRICKLE_PER_MINUTE").unwrap_or(10),
+        }
+    }
+
+    /// Usage served before the overage policy applies
+    pub fn allowance(&self) -> Option<u64> {
+        self.monthly_limit
+            .map(|limit| limit + limit * self.grace_percent / 100)
+    }
+}
+
+/// Outcome of metering a charge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Within quota or grace
+    Allow,
+    /// Over quota under [`OveragePolicy::SoftOverage`]; served and billed
+    Overage,
+    /// Over quota under [`OveragePolicy::Throttle`] but within the trickle rate
+    Throttled,
+    Reject,
+}
+
+/// Applies a plan's quota to the month's usage including the current charge
+///
+/// `trickle_used` counts this minute's throttled addresses including the current ones,
+/// and is only consulted under [`OveragePolicy::Throttle`].
+pub fn decide(quota: &PlanQuota, used: u64, trickle_used: impl FnOnce() -> u64) -> Decision {
+    match quota.allowance() {
+        None => Decision::Allow,
+        Some(allowance) if used <= allowance => Decision::Allow,
+        Some(_) => match quota.policy {
+            OveragePolicy::HardStop => Decision::Reject,
+            OveragePolicy::SoftOverage => Decision::Overage,
+            OveragePolicy::Throttle if trickle_used() <= quota.trickle_per_minute => {
+                Decision::Throttled
+            }
+            OveragePolicy::Throttle => Decision::Reject,
+        },
+    }
+}
+
+/// Billing period of a timestamp, e.g. `2024-05`
+pub fn billing_period(now: DateTime<Utc>) -> String {
+    now.format("%Y-%m").to_string()
+}
+
+/// Seconds until the next billing period starts
+pub fn secs_until_next_period(now: DateTime<Utc>) -> u64 {
+    let (year, month) = if now.month() == 12 {
+        (now.year() + 1, 1)
+    } else {
+        (now.year(), now.month() + 1)
+    };
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0)
+        .single()
+        .map(|next| (next - now).num_seconds().max(1) as u64)
+        .unwrap_or(3600)
+}
+
+/// One account's usage of a billing period
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct AccountUsage {
+    pub account: String,
+    pub units: u64,
+}
+
+/// Monthly usage counters and billing events, kept in Redis
+#[derive(Clone)]
+pub struct Meter {
+    redis: RedisPool,
+}
+
+impl Meter {
+    pub fn new(redis_url: &str) -> Result<Self, RedisError> {
+        Ok(Self {
+            redis: RedisPool::new(redis_url)?,
+        })
+    }
+
+    fn usage_key(account: &str, period: &str) -> String {
+        format!("usage:{}:{}", account, period)
+    }
+
+    /// Every account's usage of `period`, ranked
+    fn leaderboard_key(period: &str) -> String {
+        format!("usage_leaderboard:{}", period)
+    }
+
+    /// Adds units to the account's usage of `period`; returns the new total
+    pub async fn record(&self, account: &str, period: &str, units: i64) -> Result<u64, RedisError> {
+        let mut conn = self.redis.get().await?;
+        let key = Self::usage_key(account, period);
+        let leaderboard = Self::leaderboard_key(period);
+        let (used,): (i64,) = redis::pipe()
+            .incr(&key, units)
+            .expire(&key, USAGE_TTL_SECS)
+            .ignore()
+            .zincr(&leaderboard, account, units)
+            .ignore()
+            .expire(&leaderboard, USAGE_TTL_SECS)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+        Ok(used.max(0) as u64)
+    }
+
+    /// The `limit` accounts with the most usage in `period`, highest first
+    pub async fn leaderboard(
+        &self,
+        period: &str,
+        limit: usize,
+    ) -> Result<Vec<AccountUsage>, RedisError> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.redis.get().await?;
+        let ranked: Vec<(String, i64)> = conn
+            .zrevrange_withscores(Self::leaderboard_key(period), 0, limit as isize - 1)
+            .await?;
+        Ok(ranked
+            .into_iter()
+            .filter(|(_, units)| *units > 0)
+            .map(|(account, units)| AccountUsage {
+                account,
+                units: units as u64,
+            })
+            .collect())
+    }
+
+    /// The account's usage of `period` so far
+    pub async fn usage(&self, account: &str, period: &str) -> Result<u64, RedisError> {
+        let mut conn = self.redis.get().await?;
+        let used: Option<i64> = conn.get(Self::usage_key(account, period)).await?;
+        Ok(used.unwrap_or(0).max(0) as u64)
+    }
+
+    /// Counts throttled addresses in the current minute; returns the minute's total
+    pub async fn trickle(
+        &self,
+        account: &str,
+        units: u64,
+        now: DateTime<Utc>,
+    ) -> Result<u64, RedisError> {
+        let mut conn = self.redis.get().await?;
+        let key = format!("overage_trickle:{}:{}", account, now.timestamp() / 60);
+        let (used,): (u64,) = redis::pipe()
+            .incr(&key, units)
+            .expire(&key, 60)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+        Ok(used)
+    }
+
+    /// Records addresses validated over quota for billing
+    pub async fn billing_event(
+        &self,
+        account: &str,
+        plan: PlanTier,
+        period: &str,
+        units: u64,
+        used: u64,
+    ) -> Result<(), RedisError> {
+        let mut conn = self.redis.get().await?;
+        let plan = format!("{:?}", plan).to_lowercase();
+        let _: String = conn
+            .xadd_maxlen(
+                BILLING_EVENTS_KEY,
+                StreamMaxlen::Approx(MAX_BILLING_EVENTS),
+                "*",
+                &[
+                    ("account", account),
+                    ("plan", plan.as_str()),
+                    ("period", period),
+                    ("units", units.to_string().as_str()),
+                    ("usage", used.to_string().as_str()),
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    fn event_key(account: &str, period: &str, event: &str) -> String {
+        format!("quota_event:{}:{}:{}", account, period, event)
+    }
+
+    /// Whether a quota webhook event already went to `account` in `period`
+    pub async fn event_sent(
+        &self,
+        account: &str,
+        period: &str,
+        event: &str,
+    ) -> Result<bool, RedisError> {
+        let mut conn = self.redis.get().await?;
+        conn.exists(Self::event_key(account, period, event)).await
+    }
+
+    /// Records a quota webhook event of `period` as sent; returns false when it
+    /// already was, so concurrent instances send it once
+    pub async fn mark_event_sent(
+        &self,
+        account: &str,
+        period: &str,
+        event: &str,
+    ) -> Result<bool, RedisError> {
+        let mut conn = self.redis.get().await?;
+        let set: Option<String> = redis::cmd("SET")
+            .arg(Self::event_key(account, period, event))
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(USAGE_TTL_SECS)
+            .query_async(&mut conn)
+            .await?;
+        Ok(set.is_some())
+    }
+
+    /// Meters `units` validated addresses of `account`, returning what to do with them
+    /// and the period's usage including them
+    ///
+    /// Rejected charges are taken back off the usage count so they aren't billed.
+    pub async fn meter(
+        &self,
+        account: &str,
+        plan: PlanTier,
+        units: u64,
+        now: DateTime<Utc>,
+    ) -> Result<(Decision, u64), RedisError> {
+        let quota = PlanQuota::for_plan(plan);
+        let period = billing_period(now);
+        let used = self.record(account, &period, units as i64).await?;
+
+        let trickle_used = if quota.policy == OveragePolicy::Throttle
+            && quota.allowance().is_some_and(|allowance| used > allowance)
+        {
+            self.trickle(account, units, now).await?
+        } else {
+            0
+        };
+
+        let decision = decide(&quota, used, || trickle_used);
+        match decision {
+            Decision::Reject => {
+                self.record(account, &period, -(units as i64)).await?;
+            }
+            Decision::Overage => {
+                let over = overage_units(&quota, used, units);
+                self.billing_event(account, plan, &period, over, used)
+                    .await?
+            }
+            Decision::Allow | Decision::Throttled => {}
+        }
+        Ok((decision, used))
+    }
+}
+
+/// Addresses of a charge that went past the allowance, given the usage including it
+fn overage_units(quota: &PlanQuota, used: u64, units: u64) -> u64 {
+    quota
+        .allowance()
+        .map_or(0, |allowance| used.saturating_sub(allowance).min(units))
+}
+
+/// A charge refused under the plan's overage policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaExceeded {
+    /// When the account can validate again: the next minute for throttled plans,
+    /// the next billing period otherwise
+    pub retry_after_secs: u64,
+}
+
+impl From<QuotaExceeded> for ApiError {
+    fn from(e: QuotaExceeded) -> Self {
+        ApiError::RateLimited {
+            message: "Monthly quota exceeded".to_string(),
+            retry_after_secs: Some(e.retry_after_secs),
+        }
+    }
+}
+
+/// Counts `units` validated addresses against the quota of `account`
+///
+/// Every validation entry point calls this, or [`charge_request`], before doing the
+/// work: REST, GraphQL, gRPC, queued jobs, saved list and scheduled runs alike.
+/// Members of an organization share its usage counter and highest member plan (see
+/// [`organizations::account_scope`]). Metering never takes the API down with it:
+/// store failures let the addresses through.
+///
+/// Charges that take usage near or past the quota send the `quota.warning` and
+/// `quota.exceeded` webhook events in the background (see [`quota_events::notify`]).
+pub async fn charge(
+    meter: &Meter,
+    mongo_client: &MongoClient,
+    account: &str,
+    units: u64,
+) -> Result<(), QuotaExceeded> {
+    if units == 0 {
+        return Ok(());
+    }
+    let now = Utc::now();
+    let (scope_owner, plan) = match organizations::account_scope(mongo_client, account).await {
+        Ok(scope) => (scope.owner, scope.plan),
+        Err(_) => (account.to_string(), PlanTier::default()),
+    };
+    let (decision, used) = match meter.meter(&scope_owner, plan, units, now).await {
+        Ok(metered) => metered,
+        Err(e) => {
+            eprintln!("metering error for {}: {}", scope_owner, e);
+            return Ok(());
+        }
+    };
+
+    let quota = PlanQuota::for_plan(plan);
+    if let Some(limit) = quota.monthly_limit
+        && quota_events::may_notify(limit, used)
+    {
+        let usage = QuotaUsage {
+            period: billing_period(now),
+            used,
+            limit,
+            policy: quota.policy,
+        };
+        let (meter, mongo_client, charged_by) =
+            (meter.clone(), mongo_client.clone(), account.to_string());
+        tokio::spawn(async move {
+            quota_events::notify(&meter, &mongo_client, &scope_owner, &charged_by, &usage).await;
+        });
+    }
+
+    if decision == Decision::Reject {
+        let retry_after_secs = match quota.policy {
+            OveragePolicy::Throttle => 60 - now.timestamp() as u64 % 60,
+            _ => secs_until_next_period(now),
+        };
+        return Err(QuotaExceeded { retry_after_secs });
+    }
+    Ok(())
+}
+
+/// [`charge`] for the account an HTTP request is authenticated as
+///
+/// Free when no [`Meter`] is registered as app data or the request is unauthenticated.
+pub async fn charge_request(http_req: &HttpRequest, units: u64) -> Result<(), QuotaExceeded> {
+    let account = http_req
+        .extensions()
+        .get::<AuthenticatedAccount>()
+        .map(|account| account.email.clone());
+    let (Some(meter), Some(mongo_client), Some(account)) = (
+        http_req.app_data::<web::Data<Meter>>(),
+        http_req.app_data::<web::Data<MongoClient>>(),
+        account,
+    ) else {
+        return Ok(());
+    };
+    charge(meter, mongo_client, &account, units).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quota(policy: OveragePolicy) -> PlanQuota {
+        PlanQuota {
+            monthly_limit: Some(100),
+            grace_percent: 10,
+            policy,
+            trickle_per_minute: 2,
+        }
+    }
+
+    #[test]
+    fn test_grace_allowance_is_served_under_every_policy() {
+        for policy in [
+            OveragePolicy::HardStop,
+            OveragePolicy::SoftOverage,
+            OveragePolicy::Throttle,
+        ] {
+            assert_eq!(decide(&quota(policy), 100, || 0), Decision::Allow);
+            assert_eq!(decide(&quota(policy), 110, || 0), Decision::Allow);
+        }
+    }
+
+    #[test]
+    fn test_policies_over_allowance() {
+        assert_eq!(
+            decide(&quota(OveragePolicy::HardStop), 111, || 0),
+            Decision::Reject
+        );
+        assert_eq!(
+            decide(&quota(OveragePolicy::SoftOverage), 5000, || 0),
+            Decision::Overage
+        );
+        assert_eq!(
+            decide(&quota(OveragePolicy::Throttle), 111, || 2),
+            Decision::Throttled
+        );
+        assert_eq!(
+            decide(&quota(OveragePolicy::Throttle), 111, || 3),
+            Decision::Reject
+        );
+
+        let unlimited = PlanQuota {
+            monthly_limit: None,
+            ..quota(OveragePolicy::HardStop)
+        };
+        assert_eq!(decide(&unlimited, u64::MAX, || 0), Decision::Allow);
+    }
+
+    #[test]
+    fn test_plan_defaults_and_policy_names() {
+        assert_eq!(
+            OveragePolicy::parse("Soft_Overage"),
+            Some(OveragePolicy::SoftOverage)
+        );
+        assert_eq!(OveragePolicy::parse("unknown"), None);
+        assert_eq!(
+            PlanQuota::for_plan(PlanTier::Enterprise).monthly_limit,
+            None
+        );
+        assert!(PlanQuota::for_plan(PlanTier::Free).monthly_limit.is_some());
+    }
+
+    #[test]
+    fn test_only_addresses_past_the_allowance_are_billed() {
+        let quota = quota(OveragePolicy::SoftOverage);
+        // A batch of 5 taking usage from 108 to 113 crosses the allowance of 110
+        assert_eq!(decide(&quota, 113, || 0), Decision::Overage);
+        assert_eq!(overage_units(&quota, 113, 5), 3);
+        assert_eq!(overage_units(&quota, 500, 5), 5);
+        assert_eq!(overage_units(&quota, 110, 5), 0);
+    }
+
+    #[test]
+    fn test_billing_periods() {
+        let now = Utc.with_ymd_and_hms(2024, 12, 31, 23, 59, 0).unwrap();
+        assert_eq!(billing_period(now), "2024-12");
+        assert_eq!(secs_until_next_period(now), 60);
+    }
+}
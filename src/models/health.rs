@@ -10,6 +10,7 @@ use utoipa::ToSchema;
 /// ## Fields
 /// - `status`: String indicating service availability ("UP" or "DOWN")
 /// - `timestamp`: ISO 8601 formatted timestamp of the status check
+/// - `dependencies`: Per-dependency probe results, omitted when none were probed
 ///
 /// ## Serialization
 /// Automatically implements `Serialize` and `Deserialize` for JSON format.
@@ -18,13 +19,37 @@ use utoipa::ToSchema;
 /// ```json
 /// {
 ///   "status": "UP",
-///   "timestamp": "2024-03-10T15:30:45.123456789Z"
+///   "timestamp": "2024-03-10T15:30:45.123456789Z",
+///   "dependencies": [
+///     { "name": "mongodb", "status": "UP", "latency_ms": 3 },
+///     { "name": "redis", "status": "UP", "latency_ms": 1 },
+///     { "name": "dns", "status": "UP", "latency_ms": 12 }
+///   ]
 /// }
 /// ```
 #[derive(Serialize, ToSchema, Debug, PartialEq, Deserialize)]
 pub struct HealthResponse {
     pub status: String,
     pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<DependencyCheck>,
+}
+
+/// Outcome of probing one dependency
+#[derive(Serialize, ToSchema, Debug, Clone, PartialEq, Deserialize)]
+pub struct DependencyCheck {
+    /// `mongodb`, `redis` or `dns`
+    pub name: String,
+    /// "UP" or "DOWN"; a probe that times out is "DOWN"
+    pub status: String,
+    /// Time the probe took
+    pub latency_ms: u64,
+}
+
+impl DependencyCheck {
+    pub fn is_up(&self) -> bool {
+        self.status == "UP"
+    }
 }
 
 impl HealthResponse {
@@ -32,8 +57,27 @@ impl HealthResponse {
         Self {
             status: "UP".to_string(),
             timestamp: Utc::now().to_rfc3339(),
+            dependencies: Vec::new(),
+        }
+    }
+
+    /// "UP" when every dependency is, "DOWN" otherwise
+    pub fn readiness(dependencies: Vec<DependencyCheck>) -> Self {
+        let status = if dependencies.iter().all(DependencyCheck::is_up) {
+            "UP"
+        } else {
+            "DOWN"
+        };
+        Self {
+            status: status.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            dependencies,
         }
     }
+
+    pub fn is_up(&self) -> bool {
+        self.status == "UP"
+    }
 }
 
 #[cfg(test)]
@@ -55,4 +99,20 @@ mod tests {
             "Timestamp should be valid RFC3339 format"
         );
     }
+
+    #[test]
+    fn test_readiness_requires_every_dependency() {
+        let check = |name: &str, status: &str| DependencyCheck {
+            name: name.to_string(),
+            status: status.to_string(),
+            latency_ms: 1,
+        };
+
+        let ready = HealthResponse::readiness(vec![check("redis", "UP"), check("dns", "UP")]);
+        assert!(ready.is_up());
+
+        let not_ready = HealthResponse::readiness(vec![check("redis", "UP"), check("dns", "DOWN")]);
+        assert_eq!(not_ready.status, "DOWN");
+        assert_eq!(not_ready.dependencies.len(), 2);
+    }
 }
@@ -0,0 +1,241 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use unicode_normalization::UnicodeNormalization;
+
+/// Providers whose mailboxes ignore the case of the local part, used when
+/// `CASE_INSENSITIVE_LOCAL_PART_DOMAINS` is unset
+pub const DEFAULT_CASE_INSENSITIVE_DOMAINS: &[&str] = &[
+    "gmail.com",
+    "googlemail.com",
+    "outlook.com",
+    "hotmail.com",
+    "live.com",
+    "msn.com",
+    "yahoo.com",
+    "icloud.com",
+    "me.com",
+    "aol.com",
+    "proton.me",
+    "protonmail.com",
+];
+
+/// Domains whose local parts are compared case-insensitively
+///
+/// Read once from `CASE_INSENSITIVE_LOCAL_PART_DOMAINS` (comma-separated), falling back
+/// to [`DEFAULT_CASE_INSENSITIVE_DOMAINS`]; set it to an empty value to preserve every
+/// local part.
+fn case_insensitive_domains() -> &'static HashSet<String> {
+    static DOMAINS: OnceLock<HashSet<String>> = OnceLock::new();
+    DOMAINS.get_or_init(
+        || match std::env::var("CASE_INSENSITIVE_LOCAL_PART_DOMAINS") {
+            Ok(list) => list
+                .split(',')
+                .map(normalize_domain)
+                .filter(|domain| !domain.is_empty())
+                .collect(),
+            Err(_) => DEFAULT_CASE_INSENSITIVE_DOMAINS
+                .iter()
+                .map(|domain| domain.to_string())
+                .collect(),
+        },
+    )
+}
+
+/// Trims a domain, lowercases it and drops a trailing root dot
+pub fn normalize_domain(domain: &str) -> String {
+    domain.trim().trim_end_matches('.').to_lowercase()
+}
+
+/// Canonical form of an address for cache keys, list lookups, dedup and fingerprints
+///
+/// Surrounding whitespace is dropped, the address is NFC-normalized so composed and
+/// decomposed spellings of the same characters agree, and the domain is lowercased.
+/// The local part keeps its case (RFC 5321 §2.4) unless the domain belongs to a
+/// provider known to ignore it. Responses keep echoing the address as submitted.
+pub fn normalize_email(email: &str) -> String {
+    let email: String = email.trim().nfc().collect();
+    let email = email.as_str();
+    match email.rsplit_once('@') {
+        Some((local, domain)) => {
+            let domain = normalize_domain(domain);
+            if case_insensitive_domains().contains(&domain) {
+                format!("{}@{}", local.to_lowercase(), domain)
+            } else {
+                format!("{}@{}", local, domain)
+            }
+        }
+        None => email.to_string(),
+    }
+}
+
+/// Providers whose sub-addresses are `user-tag@`; elsewhere the tag follows a `+`
+const HYPHEN_TAG_DOMAINS: &[&str] = &["yahoo.com", "ymail.com", "rocketmail.com"];
+
+/// The canonical address without its sub-address tag, e.g. `user@gmail.com` for
+/// `user+news@gmail.com`; `None` when the local part has no tag
+///
+/// The tag starts at the first `+` (Gmail, Outlook and most other providers) or, at
+/// Yahoo, the first `-`. A local part opening with the separator has no tag.
+pub fn base_address(email: &str) -> Option<String> {
+    let normalized = normalize_email(email);
+    let (local, domain) = normalized.rsplit_once('@')?;
+    let separator = if HYPHEN_TAG_DOMAINS.contains(&domain) {
+        '-'
+    } else {
+        '+'
+    };
+    let (base, _) = local.split_once(separator)?;
+    (!base.is_empty()).then(|| format!("{}@{}", base, domain))
+}
+
+/// Whether `UNICODE_CONFUSABLES_MAPPING=true` asks [`lookup_key`] to map lookalike
+/// characters; read once
+fn confusables_mapping() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("UNICODE_CONFUSABLES_MAPPING")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "true" | "1" | "yes"))
+            .unwrap_or(false)
+    })
+}
+
+/// Characters that render as nothing: soft hyphen, zero-width spaces and joiners,
+/// direction marks and overrides, invisible operators, byte order mark and variation
+/// selectors
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}'
+            | '\u{034F}'
+            | '\u{061C}'
+            | '\u{180E}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{206F}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FEFF}'
+    )
+}
+
+/// The Latin letter a lowercase Cyrillic, Greek or Latin lookalike is mistaken for
+fn latin_lookalike(c: char) -> Option<char> {
+    Some(match c {
+        'а' | 'α' | 'ɑ' => 'a',
+        'с' | 'ϲ' => 'c',
+        'ԁ' => 'd',
+        'е' => 'e',
+        'ɡ' => 'g',
+        'һ' => 'h',
+        'і' | 'ι' | 'ı' => 'i',
+        'ј' => 'j',
+        'κ' => 'k',
+        'ӏ' => 'l',
+        'о' | 'ο' => 'o',
+        'р' | 'ρ' => 'p',
+        'ԛ' => 'q',
+        'ѕ' => 's',
+        'υ' => 'u',
+        'ν' => 'v',
+        'ԝ' => 'w',
+        'х' | 'χ' => 'x',
+        'у' => 'y',
+        _ => return None,
+    })
+}
+
+/// Form of a local part or domain matched against the reference lists, so visually
+/// identical aliases such as `ａｄｍｉｎ` or `ad\u{200B}min` can't slip past them
+///
+/// The value is NFC-normalized, stripped of invisible characters and lowercased.
+/// With `UNICODE_CONFUSABLES_MAPPING=true` compatibility forms (fullwidth and
+/// mathematical letters, ligatures) are folded as well (NFKC) and Cyrillic and Greek
+/// lookalikes of Latin letters are mapped to them, e.g. a Cyrillic `а` to `a`.
+pub fn lookup_key(value: &str) -> String {
+    fold(value, confusables_mapping())
+}
+
+fn fold(value: &str, confusables: bool) -> String {
+    let value = value.trim();
+    let normalized: String = if confusables {
+        value.nfkc().collect()
+    } else {
+        value.nfc().collect()
+    };
+    let folded: String = normalized
+        .chars()
+        .filter(|c| !is_invisible(*c))
+        .collect::<String>()
+        .to_lowercase();
+    if !confusables {
+        return folded;
+    }
+    folded
+        .chars()
+        .map(|c| latin_lookalike(c).unwrap_or(c))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_case_is_folded_local_part_preserved() {
+        assert_eq!(normalize_email(" User@Example.COM "), "User@example.com");
+        assert_eq!(normalize_email("User@example.com."), "User@example.com");
+        assert_eq!(normalize_email("no-at-sign"), "no-at-sign");
+    }
+
+    #[test]
+    fn test_unicode_spellings_share_a_form() {
+        // "é" precomposed and as "e" + combining acute accent
+        assert_eq!(
+            normalize_email("jos\u{00E9}@example.com"),
+            normalize_email("jose\u{0301}@example.com")
+        );
+    }
+
+    #[test]
+    fn test_lookup_keys() {
+        assert_eq!(fold(" ADMIN", false), "admin");
+        assert_eq!(fold("ad\u{200B}min\u{FEFF}", false), "admin");
+        assert_eq!(fold("Ma\u{0308}il", false), "m\u{00E4}il");
+        // Lookalikes only fold when confusables mapping is on
+        assert_eq!(fold("ａｄｍｉｎ", false), "ａｄｍｉｎ");
+        assert_eq!(fold("ａｄｍｉｎ", true), "admin");
+        assert_eq!(fold("\u{0430}dmin", false), "\u{0430}dmin");
+        assert_eq!(fold("\u{0410}DM\u{0406}N", true), "admin");
+        assert_eq!(fold("m\u{0430}ilin\u{0430}tor.com", true), "mailinator.com");
+    }
+
+    #[test]
+    fn test_base_addresses() {
+        assert_eq!(
+            base_address("John+News@GMail.com").as_deref(),
+            Some("john@gmail.com")
+        );
+        assert_eq!(
+            base_address("jane+a+b@outlook.com").as_deref(),
+            Some("jane@outlook.com")
+        );
+        assert_eq!(
+            base_address("jane-shop@yahoo.com").as_deref(),
+            Some("jane@yahoo.com")
+        );
+        // Yahoo doesn't tag with `+`, nor others with `-`
+        assert_eq!(base_address("jane+shop@yahoo.com"), None);
+        assert_eq!(base_address("mary-ann@gmail.com"), None);
+        assert_eq!(base_address("+news@gmail.com"), None);
+        assert_eq!(base_address("no-at-sign"), None);
+    }
+
+    #[test]
+    fn test_provider_local_parts_are_folded() {
+        assert_eq!(normalize_email("John.Doe@GMail.com"), "john.doe@gmail.com");
+        assert_eq!(
+            normalize_email("John.Doe@gmail.com"),
+            normalize_email("john.doe@GMAIL.COM")
+        );
+    }
+}
@@ -1,4 +1,6 @@
-use utoipa::OpenApi;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::{SwaggerUi, Url};
 
 /// OpenAPI Specification Documentation
 ///
@@ -6,18 +8,55 @@ use utoipa::OpenApi;
 /// This documentation serves as the source of truth for both API consumers and
 /// automated documentation generators.
 ///
+/// This is the `/api/v1` document, served at `/api-docs/v1.json` and, for existing
+/// clients, `/api-docs/openapi.json`; `/api/v2` is described by [`ApiDocV2`].
+///
 /// # Endpoints
-/// - Health Check: `GET /health`
-/// - Email Validation: `POST /validate-email`
+/// - Health Check: `GET /health`, `GET /ready`, `GET /health/history`, `GET /status`
+/// - Email Validation: `POST|GET /validate-email`, `POST /validate-email-async`,
+///   `POST /validate-emails-bulk`, `GET /validation/{email}`, `GET /lookup/hash/{prefix}`,
+///   `GET /history`
+/// - Jobs: `GET /job-status/{job_id}`, `GET /job-results/{job_id}`,
+///   `GET /job-results/{job_id}/summary`, `GET /job-report/{job_id}`,
+///   `POST /job-results/{job_id}/share`, `GET /jobs/{job_id}/results`
+/// - Lists: `POST|GET /lists`, `GET|DELETE /lists/{id}`, `POST|DELETE /lists/{id}/emails`,
+///   `POST /lists/{id}/validate`
+/// - Schedules: `POST /schedules`, `GET /schedules`, `GET|DELETE /schedules/{id}`
+/// - Auth: `POST /register`, `POST /auth/login`, `POST /auth/refresh`
+/// - API Keys: `POST /keys`, `GET /keys`, `DELETE /keys/{id}`
+/// - Account: `GET|PUT /account/settings`, `GET /account/webhook-secret`,
+///   `POST /account/webhook-secret/rotate`, `GET|POST /account/{list}`,
+///   `DELETE /account/{list}/{entry}`
+/// - Feedback: `POST /feedback/bounces`, `POST /feedback/sns`
+/// - Suppressions: `GET|POST /suppressions`, `DELETE /suppressions/{email}`,
+///   `POST /suppressions/import`, `GET /suppressions/export`
+/// - Data Erasure: `DELETE /data/email/{email}`
+/// - Branding: `GET|PUT|DELETE /branding`
+/// - Organizations: `POST /orgs`, `GET /orgs/current`, `POST /orgs/current/members`,
+///   `DELETE /orgs/current/members/{email}`
+/// - Sync: `GET /sync/lists`, `POST /sync/verdicts`
+/// - Admin: cache invalidation, canary stats, list slots, suppression imports, feature
+///   flags, DNS overrides, dashboard data (queue, failed jobs, usage, error rate, dependencies), the
+///   audit log and erasure across accounts under `/admin`
+/// - GraphQL: `POST|GET /graphql`, `GET /playground`
 ///
 /// # Schemas
 /// - `HealthResponse`: Service status payload
-/// - `EmailRequest`: Email validation input structure
+/// - `EmailRequest`, `BulkEmailRequest`: Validation inputs
+/// - `EmailValidationResponse`, `BulkEmailValidationResponse`: Validation results
+/// - `ErrorEnvelope`: Body of every error response
+///
+/// # Security
+/// Every endpoint expects `Authorization: Bearer <token>` (`bearer_auth`), where the
+/// token is an API key or a session access token, unless it is documented as public.
 ///
 /// # Tags
 /// 1. **Health Check**: Service monitoring endpoints
 /// 2. **Email Validation**: Email sanitization operations
 /// 3. **GraphQL**: Unified query interface
+/// 4. **Auth**, **API Keys**, **Account**: Account and credential management
+/// 5. **Sync**: On-prem agent replication
+/// 6. **Admin**: Operator endpoints
 ///
 /// # API Information
 /// - **Title**: Email Sanitizer API  
@@ -31,18 +70,208 @@ use utoipa::OpenApi;
 #[openapi(
     paths(
         crate::routes::health::health,
+        crate::routes::health::ready,
+        crate::routes::health::health_history,
+        crate::routes::status::status_page,
         crate::routes::email::validate_email,
+        crate::routes::email::validate_email_async,
+        crate::routes::email::validate_emails_bulk,
+        crate::routes::validation::get_validation,
+        crate::routes::email::get_job_status,
+        crate::routes::email::download_job_results,
+        crate::routes::email::job_results_page,
+        crate::routes::email::job_results_summary,
+        crate::routes::email::job_hygiene_report,
+        crate::routes::email::export_job_results,
+        crate::routes::email::share_job_results,
+        crate::routes::email::list_jobs,
+        crate::routes::email::delete_job,
+        crate::routes::lists::create_list,
+        crate::routes::lists::list_lists,
+        crate::routes::lists::get_list,
+        crate::routes::lists::delete_list,
+        crate::routes::lists::add_emails,
+        crate::routes::lists::remove_emails,
+        crate::routes::lists::validate_list,
+        crate::routes::schedules::create_schedule,
+        crate::routes::schedules::list_schedules,
+        crate::routes::schedules::get_schedule,
+        crate::routes::schedules::delete_schedule,
+        crate::routes::lookup::lookup_hash_prefix,
+        crate::routes::history::get_history,
+        crate::routes::stats::validation_stats,
+        crate::routes::meta::error_codes,
+        crate::routes::meta::validation_error_catalog,
+        crate::routes::auth::register_and_generate_key,
+        crate::routes::auth::verify_email,
+        crate::routes::auth::login,
+        crate::routes::auth::refresh,
+        crate::routes::keys::create_key,
+        crate::routes::keys::list_keys,
+        crate::routes::keys::revoke_key,
+        crate::routes::account::get_settings,
+        crate::routes::account::set_settings,
+        crate::routes::account::get_webhook_secret,
+        crate::routes::account::rotate_webhook_secret,
+        crate::routes::feedback::record_bounces,
+        crate::routes::feedback::record_sns_feedback,
+        crate::routes::suppressions::list_suppressions,
+        crate::routes::suppressions::add_suppression,
+        crate::routes::suppressions::import_suppressions,
+        crate::routes::suppressions::export_suppressions,
+        crate::routes::suppressions::remove_suppression,
+        crate::routes::data::erase_email,
+        crate::routes::account::list_custom_entries,
+        crate::routes::account::add_custom_entry,
+        crate::routes::account::remove_custom_entry,
+        crate::routes::branding::get_branding,
+        crate::routes::branding::set_branding,
+        crate::routes::branding::remove_branding,
+        crate::routes::orgs::create_organization,
+        crate::routes::orgs::current_organization,
+        crate::routes::orgs::add_member,
+        crate::routes::orgs::remove_member,
+        crate::routes::sync::pull_lists,
+        crate::routes::sync::push_verdicts,
+        crate::routes::admin::invalidate_email_cache,
+        crate::routes::admin::invalidate_domain_cache,
+        crate::routes::admin::cache_stats,
+        crate::routes::admin::canary_stats,
+        crate::routes::admin::reset_canary_stats,
+        crate::routes::admin::list_slot_status,
+        crate::routes::admin::activate_list_slot,
+        crate::routes::admin::rollback_list_slot,
+        crate::routes::admin::import_suppression,
+        crate::routes::admin::remove_suppression,
+        crate::routes::admin::suppression_stats,
+        crate::routes::admin::list_flags,
+        crate::routes::admin::set_flag,
+        crate::routes::admin::list_dns_overrides,
+        crate::routes::admin::set_dns_override,
+        crate::routes::admin::remove_dns_override,
+        crate::routes::admin::list_audit_log,
+        crate::routes::data::erase_email_everywhere,
+        crate::routes::dashboard::queue_status,
+        crate::routes::dashboard::failed_jobs,
+        crate::routes::dashboard::usage_leaderboard,
+        crate::routes::dashboard::error_rate_timeline,
+        crate::routes::dashboard::dependency_health,
+        crate::graphql::handlers::graphql_handler,
+        crate::graphql::handlers::graphql_get_handler,
+        crate::graphql::handlers::graphql_playground,
     ),
     components(
         schemas(
             crate::models::health::HealthResponse,
-            crate::routes::email::EmailRequest
+            crate::models::health::DependencyCheck,
+            crate::routes::email::EmailRequest,
+            crate::routes::email::BulkEmailRequest,
+            crate::routes::email::BulkEmailEntry,
+            crate::routes::email::BulkEmailItem,
+            crate::checks::ItemOptions,
+            crate::checks::Check,
+            crate::routes::email::EmailValidationError,
+            crate::routes::email::EmailValidationResponse,
+            crate::routes::email::BulkEmailValidationResult,
+            crate::routes::email::BulkEmailValidationResponse,
+            crate::routes::email::JobResultsPage,
+            crate::job_summary::JobSummary,
+            crate::job_summary::DomainCount,
+            crate::list_report::ListReport,
+            crate::list_report::ListGrade,
+            crate::routes::email::JobList,
+            crate::routes::email::JobListEntry,
+            crate::routes::email::JobCounts,
+            crate::routes::lists::CreateListRequest,
+            crate::routes::lists::ListEmailsRequest,
+            crate::routes::lists::SavedListDetail,
+            crate::saved_lists::SavedListSummary,
+            crate::routes::schedules::CreateScheduleRequest,
+            crate::routes::schedules::ScheduleView,
+            crate::schedules::ScheduleSource,
+            crate::schedules::ScheduleRun,
+            crate::schedules::ScheduleDiff,
+            crate::schedules::RunStatus,
+            crate::routes::history::HistoryPage,
+            crate::validation_history::HistoryEntry,
+            crate::validation_history::ValidationSource,
+            crate::validation_stats::StatsReport,
+            crate::validation_stats::StatsWindow,
+            crate::validation_stats::ErrorCodeCount,
+            crate::error::ErrorEnvelope,
+            crate::error::ErrorBody,
+            crate::routes::meta::ErrorCodeRegistry,
+            crate::error_codes::ErrorCodeInfo,
+            crate::error_codes::CodeKind,
+            crate::error_codes::Severity,
+            crate::routes::meta::ValidationErrorCatalog,
+            crate::error_codes::ValidationErrorCode,
+            crate::error_codes::VerdictCode,
+            crate::routes::auth::RegisterRequest,
+            crate::routes::auth::ApiKeyResponse,
+            crate::routes::auth::VerifiedAccount,
+            crate::routes::auth::LoginRequest,
+            crate::routes::auth::RefreshRequest,
+            crate::auth::SessionTokens,
+            crate::routes::keys::CreateKeyRequest,
+            crate::routes::keys::ApiKeySummary,
+            crate::routes::account::ListEntryRequest,
+            crate::routes::account::SettingsRequest,
+            crate::account_settings::AccountSettings,
+            crate::webhooks::WebhookSecret,
+            crate::routes::orgs::CreateOrganizationRequest,
+            crate::routes::orgs::AddMemberRequest,
+            crate::organizations::Organization,
+            crate::organizations::OrgMember,
+            crate::organizations::OrgRole,
+            crate::handlers::validation::custom_lists::CustomList,
+            crate::handlers::validation::suppression_list::SuppressionReason,
+            crate::handlers::validation::suppression_list::SuppressionInput,
+            crate::handlers::validation::suppression_list::SuppressionRecord,
+            crate::handlers::validation::suppression_list::ImportSummary,
+            crate::routes::suppressions::ImportRequest,
+            crate::routes::suppressions::SuppressionPage,
+            crate::branding::Branding,
+            crate::routes::branding::BrandingRequest,
+            crate::sync::SyncList,
+            crate::sync::ListPage,
+            crate::sync::VerdictStat,
+            crate::sync::VerdictBatch,
+            crate::list_slots::ListSlot,
+            crate::routes::admin::SuppressionImport,
+            crate::routes::admin::FlagUpdate,
+            crate::routes::admin::DnsOverrideUpdate,
+            crate::dns_overrides::DomainOverride,
+            crate::dns_overrides::OverrideSource,
+            crate::routes::admin::AuditPage,
+            crate::audit_log::AuditEntry,
+            crate::audit_log::ResultClass,
+            crate::erasure::ErasureReport,
+            crate::job_queue::ErasedJobData,
+            crate::job_queue::FailedJob,
+            crate::job_queue::ConsumerStatus,
+            crate::metering::AccountUsage,
+            crate::validation_stats::TimelinePoint,
+            crate::handlers::validation::suppression::SuppressionKind,
+            crate::graphql::handlers::GraphQLRequestBody
         )
     ),
+    modifiers(&SecurityAddon),
+    security(("bearer_auth" = [])),
     tags(
         (name = "Health Check", description = "Service health monitoring endpoints"),
         (name = "Email Validation", description = "Email address validation endpoints"),
-        (name = "GraphQL", description = "GraphQL API for interacting with all service features")
+        (name = "Lists", description = "Named address lists kept on the service for validation"),
+        (name = "Schedules", description = "Recurring re-validation of completed jobs' addresses and saved lists"),
+        (name = "GraphQL", description = "GraphQL API for interacting with all service features"),
+        (name = "Auth", description = "Registration, email verification, login and session refresh"),
+        (name = "API Keys", description = "Issuing, listing and revoking API keys"),
+        (name = "Account", description = "Per-account default validation settings, blocklist and allowlist"),
+        (name = "Branding", description = "Tenant name and logo on generated artifacts"),
+        (name = "Organizations", description = "Organizations sharing a quota and custom lists"),
+        (name = "Sync", description = "List replication and verdict stats for on-prem agents"),
+        (name = "Admin", description = "Cache, canary, list slot, suppression list and feature flag administration, operational dashboard data, the audit log and erasure across accounts"),
+        (name = "Meta", description = "Machine-readable descriptions of the API itself")
     ),
     info(
         description = "API for email validation and sanitization with both REST and GraphQL interfaces",
@@ -52,6 +281,72 @@ use utoipa::OpenApi;
 )]
 pub struct ApiDoc;
 
+/// OpenAPI document of `/api/v2`, served at `/api-docs/v2.json`
+///
+/// v2 answers with a verdict and a score per address rather than `is_valid` and an
+/// error code; routes join it as they are redesigned.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        crate::routes::v2::validate_email,
+        crate::routes::v2::validate_email_get,
+    ),
+    components(
+        schemas(
+            crate::routes::email::EmailRequest,
+            crate::routes::v2::EmailValidationV2,
+            crate::routes::v2::Verdict,
+            crate::routes::v2::Reason,
+            crate::error::ErrorEnvelope,
+            crate::error::ErrorBody
+        )
+    ),
+    modifiers(&SecurityAddon),
+    security(("bearer_auth" = [])),
+    tags(
+        (name = "Email Validation", description = "Email address validation endpoints")
+    ),
+    info(
+        description = "Verdict and score based email validation; in preview alongside /api/v1",
+        title = "Email Sanitizer API",
+        version = "2.0.0-preview",
+    )
+)]
+pub struct ApiDocV2;
+
+/// Swagger UI listing one document per API version, v1 first
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new("/swagger-ui/{_:.*}").urls(vec![
+        (
+            Url::with_primary("v1", "/api-docs/v1.json", true),
+            ApiDoc::openapi(),
+        ),
+        (Url::new("v2", "/api-docs/v2.json"), ApiDocV2::openapi()),
+        (
+            Url::new("v1 (openapi.json)", "/api-docs/openapi.json"),
+            ApiDoc::openapi(),
+        ),
+    ])
+}
+
+/// Registers the `bearer_auth` scheme referenced by the spec's default security
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some("An API key or a session access token"))
+                    .build(),
+            ),
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,6 +449,137 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_openapi_bulk_batch_limits() {
+        let openapi = ApiDoc::openapi();
+        let json = serde_json::to_value(&openapi).expect("Failed to convert OpenAPI to JSON");
+
+        let responses = json
+            .pointer("/paths/~1api~1v1~1validate-emails-bulk/post/responses")
+            .expect("Missing bulk validation responses");
+        assert!(
+            responses.get("413").is_some(),
+            "Bulk endpoint should document BATCH_TOO_LARGE"
+        );
+        assert!(
+            responses.get("422").is_some(),
+            "Bulk endpoint should document EMPTY_BATCH"
+        );
+    }
+
+    #[test]
+    fn test_openapi_documents_every_route() {
+        let json = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let paths = json.get("paths").expect("No paths section found");
+
+        for (path, method) in [
+            ("/api/v1/job-status/{job_id}", "get"),
+            ("/api/v1/jobs", "get"),
+            ("/api/v1/stats", "get"),
+            ("/api/v1/job-report/{job_id}", "get"),
+            ("/api/v1/job-results/{job_id}/export", "get"),
+            ("/api/v1/jobs/{job_id}", "delete"),
+            ("/api/v1/jobs/{job_id}/results", "get"),
+            ("/api/v1/register", "post"),
+            ("/api/v1/auth/login", "post"),
+            ("/api/v1/keys/{id}", "delete"),
+            ("/api/v1/account/settings", "put"),
+            ("/api/v1/account/{list}", "post"),
+            ("/api/v1/orgs/current/members/{email}", "delete"),
+            ("/api/v1/sync/lists", "get"),
+            ("/api/v1/admin/cache/stats", "get"),
+            ("/api/v1/graphql", "post"),
+            ("/api/v1/graphql", "get"),
+            ("/api/v1/playground", "get"),
+            ("/status", "get"),
+        ] {
+            assert!(
+                paths
+                    .pointer(&format!("/{}/{}", path.replace('/', "~1"), method))
+                    .is_some(),
+                "Missing {} {}",
+                method,
+                path
+            );
+        }
+
+        let bulk_ok = json
+            .pointer("/paths/~1api~1v1~1validate-emails-bulk/post/responses/200/content/application~1json/schema/$ref")
+            .and_then(Value::as_str);
+        assert_eq!(
+            bulk_ok,
+            Some("#/components/schemas/BulkEmailValidationResponse")
+        );
+    }
+
+    #[test]
+    fn test_versioned_documents() {
+        let v1 = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let v2 = serde_json::to_value(ApiDocV2::openapi()).unwrap();
+
+        assert!(
+            v1.pointer("/paths/~1api~1v1~1validate-email/post")
+                .is_some()
+        );
+        assert!(v1.pointer("/paths/~1api~1v2~1validate-email").is_none());
+        for method in ["post", "get"] {
+            assert!(
+                v2.pointer(&format!("/paths/~1api~1v2~1validate-email/{}", method))
+                    .is_some()
+            );
+        }
+        assert!(
+            v2["paths"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .all(|path| path.starts_with("/api/v2/"))
+        );
+        assert_eq!(v2["info"]["version"], "2.0.0-preview");
+        assert!(
+            v2.pointer("/components/schemas/EmailValidationV2")
+                .is_some()
+        );
+        assert!(
+            v2.pointer("/components/securitySchemes/bearer_auth")
+                .is_some()
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_swagger_ui_serves_each_version() {
+        use actix_web::{App, test};
+
+        let app = test::init_service(App::new().service(swagger_ui())).await;
+        for (uri, version) in [
+            ("/api-docs/v1.json", "0.6.0+sprint-3"),
+            ("/api-docs/openapi.json", "0.6.0+sprint-3"),
+            ("/api-docs/v2.json", "2.0.0-preview"),
+        ] {
+            let req = test::TestRequest::get().uri(uri).to_request();
+            let body: Value = test::call_and_read_body_json(&app, req).await;
+            assert_eq!(body["info"]["version"], version, "{}", uri);
+        }
+    }
+
+    #[test]
+    fn test_openapi_security() {
+        let json = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+        let scheme = json
+            .pointer("/components/securitySchemes/bearer_auth")
+            .expect("Missing bearer_auth security scheme");
+        assert_eq!(scheme["type"], "http");
+        assert_eq!(scheme["scheme"], "bearer");
+        assert!(json["security"][0].get("bearer_auth").is_some());
+
+        // Public endpoints opt out of the default requirement
+        let health_security = json
+            .pointer("/paths/~1api~1v1~1health/get/security")
+            .expect("Health endpoint should override security");
+        assert_eq!(health_security[0], serde_json::json!({}));
+    }
+
     #[test]
     fn test_openapi_components_schemas() {
         let openapi = ApiDoc::openapi();
@@ -173,6 +599,15 @@ mod tests {
             schemas.get("EmailRequest").is_some(),
             "Missing EmailRequest schema"
         );
+        for name in [
+            "EmailValidationResponse",
+            "BulkEmailValidationResponse",
+            "ErrorEnvelope",
+            "JobSummary",
+            "SessionTokens",
+        ] {
+            assert!(schemas.get(name).is_some(), "Missing {} schema", name);
+        }
 
         // Check HealthResponse schema properties
         let health_schema = schemas
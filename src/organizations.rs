@@ -0,0 +1,304 @@
+use crate::auth::{self, PlanTier};
+use chrono::Utc;
+use mongodb::{Client, Collection, bson::doc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Names longer than this are rejected when creating an organization
+pub const MAX_NAME_LENGTH: usize = 100;
+
+/// Role of a member within an organization
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OrgRole {
+    /// Manages members and the shared custom lists
+    Owner,
+    /// Validates against the shared quota and lists
+    #[default]
+    Member,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct OrgMember {
+    pub email: String,
+    pub role: OrgRole,
+    /// Unix timestamp the account joined
+    pub joined_at: i64,
+}
+
+/// A record in the `organizations` collection
+///
+/// An organization groups accounts under one billing entity: its members share a
+/// monthly quota and custom lists. An account belongs to at most one organization.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Organization {
+    pub org_id: String,
+    pub name: String,
+    pub members: Vec<OrgMember>,
+    /// Unix timestamp of creation
+    pub created_at: i64,
+}
+
+impl Organization {
+    pub fn role_of(&self, email: &str) -> Option<OrgRole> {
+        self.members
+            .iter()
+            .find(|member| member.email == email)
+            .map(|member| member.role)
+    }
+
+    /// Owner string under which the organization's usage and custom lists are stored
+    pub fn scope(&self) -> String {
+        format!("org:{}", self.org_id)
+    }
+
+    fn emails(&self) -> Vec<String> {
+        self.members.iter().map(|m| m.email.clone()).collect()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum OrgError {
+    InvalidName(String),
+    /// The account already belongs to an organization
+    AlreadyMember,
+    AccountNotFound,
+    NotFound,
+    /// The change would leave the organization without an owner
+    LastOwner,
+    Database(String),
+}
+
+impl From<String> for OrgError {
+    fn from(message: String) -> Self {
+        OrgError::Database(message)
+    }
+}
+
+/// Reads `DB_NAME_PRODUCTION` (default `email_sanitizer`) and `DB_ORGANIZATIONS_COLLECTION`
+/// (default `organizations`)
+pub fn organizations_collection(mongo_client: &Client) -> Collection<Organization> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let collection_name = std::env::var("DB_ORGANIZATIONS_COLLECTION")
+        .unwrap_or_else(|_| "organizations".to_string());
+    mongo_client.database(&db_name).collection(&collection_name)
+}
+
+/// The organization `email` belongs to, if any
+pub async fn organization_of(
+    mongo_client: &Client,
+    email: &str,
+) -> Result<Option<Organization>, String> {
+    organizations_collection(mongo_client)
+        .find_one(doc! { "members.email": email })
+        .await
+        .map_err(|_| "Database error".to_string())
+}
+
+/// Trims an organization name, rejecting empty and overlong ones
+pub fn validate_name(name: &str) -> Result<&str, OrgError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(OrgError::InvalidName(format!(
+            "Organization names are 1 to {} characters",
+            MAX_NAME_LENGTH
+        )));
+    }
+    Ok(name)
+}
+
+/// Creates an organization with `owner` as its only member
+pub async fn create_organization(
+    mongo_client: &Client,
+    owner: &str,
+    name: &str,
+) -> Result<Organization, OrgError> {
+    let name = validate_name(name)?;
+    if organization_of(mongo_client, owner).await?.is_some() {
+        return Err(OrgError::AlreadyMember);
+    }
+
+    let now = Utc::now().timestamp();
+    let org = Organization {
+        org_id: uuid::Uuid::new_v4().simple().to_string(),
+        name: name.to_string(),
+        members: vec![OrgMember {
+            email: owner.to_string(),
+            role: OrgRole::Owner,
+            joined_at: now,
+        }],
+        created_at: now,
+    };
+    organizations_collection(mongo_client)
+        .insert_one(&org)
+        .await
+        .map_err(|_| OrgError::Database("Database error".to_string()))?;
+    Ok(org)
+}
+
+/// Adds a registered account to an organization
+pub async fn add_member(
+    mongo_client: &Client,
+    org_id: &str,
+    email: &str,
+    role: OrgRole,
+) -> Result<Organization, OrgError> {
+    if !auth::account_exists(mongo_client, email).await? {
+        return Err(OrgError::AccountNotFound);
+    }
+    if organization_of(mongo_client, email).await?.is_some() {
+        return Err(OrgError::AlreadyMember);
+    }
+
+    let member = OrgMember {
+        email: email.to_string(),
+        role,
+        joined_at: Utc::now().timestamp(),
+    };
+    let member = mongodb::bson::to_bson(&member).map_err(|e| OrgError::Database(e.to_string()))?;
+    organizations_collection(mongo_client)
+        .find_one_and_update(
+            doc! { "org_id": org_id, "members.email": { "$ne": email } },
+            doc! { "$push": { "members": member } },
+        )
+        .return_document(mongodb::options::ReturnDocument::After)
+        .await
+        .map_err(|_| OrgError::Database("Database error".to_string()))?
+        .ok_or(OrgError::NotFound)
+}
+
+/// Removes an account from an organization; the last owner can't be removed
+pub async fn remove_member(
+    mongo_client: &Client,
+    org: &Organization,
+    email: &str,
+) -> Result<(), OrgError> {
+    let role = org.role_of(email).ok_or(OrgError::AccountNotFound)?;
+    let owners = org
+        .members
+        .iter()
+        .filter(|m| m.role == OrgRole::Owner)
+        .count();
+    if role == OrgRole::Owner && owners <= 1 {
+        return Err(OrgError::LastOwner);
+    }
+
+    organizations_collection(mongo_client)
+        .update_one(
+            doc! { "org_id": &org.org_id },
+            doc! { "$pull": { "members": { "email": email } } },
+        )
+        .await
+        .map_err(|_| OrgError::Database("Database error".to_string()))?;
+    Ok(())
+}
+
+/// Who a request's usage is billed to and whose custom lists apply to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountScope {
+    /// `org:<id>` for organization members, the account email otherwise
+    pub owner: String,
+    /// The organization's highest member plan, or the account's own plan
+    pub plan: PlanTier,
+    /// `None` outside an organization
+    pub role: Option<OrgRole>,
+}
+
+impl AccountScope {
+    /// Whether the caller may change the scope's custom lists
+    pub fn can_manage_lists(&self) -> bool {
+        self.role != Some(OrgRole::Member)
+    }
+}
+
+/// Resolves the account scope of `email`
+pub async fn account_scope(mongo_client: &Client, email: &str) -> Result<AccountScope, String> {
+    match organization_of(mongo_client, email).await? {
+        Some(org) => Ok(AccountScope {
+            owner: org.scope(),
+            plan: auth::highest_plan(mongo_client, &org.emails()).await?,
+            role: org.role_of(email),
+        }),
+        None => Ok(AccountScope {
+            owner: email.to_string(),
+            plan: auth::account_plan(mongo_client, email).await?,
+            role: None,
+        }),
+    }
+}
+
+/// Owner of the custom lists applied to `email`'s validations
+///
+/// Cheaper than [`account_scope`] since the plan isn't needed.
+pub async fn list_owner(mongo_client: &Client, email: &str) -> Result<String, String> {
+    Ok(organization_of(mongo_client, email)
+        .await?
+        .map(|org| org.scope())
+        .unwrap_or_else(|| email.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn org() -> Organization {
+        Organization {
+            org_id: "abc".to_string(),
+            name: "Agency".to_string(),
+            members: vec![
+                OrgMember {
+                    email: "owner@example.com".to_string(),
+                    role: OrgRole::Owner,
+                    joined_at: 0,
+                },
+                OrgMember {
+                    email: "client@example.com".to_string(),
+                    role: OrgRole::Member,
+                    joined_at: 0,
+                },
+            ],
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_roles_and_scope() {
+        let org = org();
+        assert_eq!(org.scope(), "org:abc");
+        assert_eq!(org.role_of("owner@example.com"), Some(OrgRole::Owner));
+        assert_eq!(org.role_of("client@example.com"), Some(OrgRole::Member));
+        assert_eq!(org.role_of("other@example.com"), None);
+
+        let member = AccountScope {
+            owner: org.scope(),
+            plan: PlanTier::Free,
+            role: Some(OrgRole::Member),
+        };
+        assert!(!member.can_manage_lists());
+        assert!(
+            AccountScope {
+                role: None,
+                ..member
+            }
+            .can_manage_lists()
+        );
+    }
+
+    #[test]
+    fn test_name_validation() {
+        assert_eq!(validate_name("  Acme Agency "), Ok("Acme Agency"));
+        assert!(matches!(
+            validate_name("   "),
+            Err(OrgError::InvalidName(_))
+        ));
+        assert!(validate_name(&"x".repeat(MAX_NAME_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_role_serialization() {
+        assert_eq!(serde_json::to_string(&OrgRole::Owner).unwrap(), "\"owner\"");
+        let role: OrgRole = serde_json::from_str("\"member\"").unwrap();
+        assert_eq!(role, OrgRole::Member);
+    }
+}
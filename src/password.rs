@@ -0,0 +1,289 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use subtle::ConstantTimeEq;
+
+/// Algorithm a stored password hash was produced with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PasswordScheme {
+    /// Accounts created before Argon2id was introduced carry no scheme and use bcrypt
+    #[default]
+    Bcrypt,
+    Argon2id,
+}
+
+impl PasswordScheme {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PasswordScheme::Bcrypt => "bcrypt",
+            PasswordScheme::Argon2id => "argon2id",
+        }
+    }
+}
+
+/// Scheme new and rehashed passwords use (`PASSWORD_HASH_SCHEME`, default `argon2id`)
+pub fn default_scheme() -> PasswordScheme {
+    match std::env::var("PASSWORD_HASH_SCHEME") {
+        Ok(v) if v.trim().eq_ignore_ascii_case("bcrypt") => PasswordScheme::Bcrypt,
+        _ => PasswordScheme::Argon2id,
+    }
+}
+
+fn read_u32(name: &str, default: u32) -> u32 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|v: &u32| *v > 0)
+        .unwrap_or(default)
+}
+
+/// Argon2id cost parameters
+///
+/// Reads `ARGON2_MEMORY_KIB` (default 19456, i.e. 19 MiB), `ARGON2_ITERATIONS` (default 2)
+/// and `ARGON2_PARALLELISM` (default 1), the OWASP recommended minimum.
+pub fn argon2_params() -> Result<Params, String> {
+    Params::new(
+        read_u32("ARGON2_MEMORY_KIB", 19 * 1024),
+        read_u32("ARGON2_ITERATIONS", 2),
+        read_u32("ARGON2_PARALLELISM", 1),
+        None,
+    )
+    .map_err(|e| format!("Invalid Argon2 parameters: {}", e))
+}
+
+/// bcrypt cost (`BCRYPT_COST`, default [`bcrypt::DEFAULT_COST`])
+pub fn bcrypt_cost() -> u32 {
+    read_u32("BCRYPT_COST", bcrypt::DEFAULT_COST)
+}
+
+/// Passwords longer than this are rejected; hashing cost grows with the input
+pub const MAX_PASSWORD_LENGTH: usize = 128;
+
+/// Minimum password length (`PASSWORD_MIN_LENGTH`, default 10)
+pub fn min_password_length() -> usize {
+    read_u32("PASSWORD_MIN_LENGTH", 10) as usize
+}
+
+/// Checks a new password against the policy: [`min_password_length`] to
+/// [`MAX_PASSWORD_LENGTH`] characters, with at least one letter and one other character
+///
+/// Error messages start with "Password must", which [`crate::error::ApiError::from_auth`]
+/// reports as `WEAK_PASSWORD`.
+pub fn check_policy(password: &str) -> Result<(), String> {
+    let min_length = min_password_length();
+    let length = password.chars().count();
+    if length < min_length || length > MAX_PASSWORD_LENGTH {
+        return Err(format!(
+            "Password must be {} to {} characters long",
+            min_length, MAX_PASSWORD_LENGTH
+        ));
+    }
+    if !password.chars().any(char::is_alphabetic) || password.chars().all(char::is_alphabetic) {
+        return Err(
+            "Password must contain a letter and a digit, space or punctuation character"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Hashes a password with [`default_scheme`]
+pub fn hash_password(password: &str) -> Result<(String, PasswordScheme), String> {
+    let scheme = default_scheme();
+    let hash = match scheme {
+        PasswordScheme::Bcrypt => {
+            bcrypt::hash(password, bcrypt_cost()).map_err(|_| "Password hashing failed")?
+        }
+        PasswordScheme::Argon2id => {
+            let params = argon2_params()?;
+            let salt = SaltString::generate(&mut OsRng);
+            Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password(password.as_bytes(), &salt)
+                .map_err(|_| "Password hashing failed")?
+                .to_string()
+        }
+    };
+    Ok((hash, scheme))
+}
+
+/// Whether `password` matches a stored hash; malformed hashes never match
+pub fn verify_password(password: &str, hash: &str, scheme: PasswordScheme) -> bool {
+    match scheme {
+        PasswordScheme::Bcrypt => bcrypt::verify(password, hash).unwrap_or(false),
+        // Parameters are read from the hash, so older cost settings still verify
+        PasswordScheme::Argon2id => PasswordHash::new(hash)
+            .map(|parsed| {
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &parsed)
+                    .is_ok()
+            })
+            .unwrap_or(false),
+    }
+}
+
+/// `bytes` random bytes from the operating system, hex encoded
+pub fn random_hex(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    OsRng.fill_bytes(&mut buf);
+    buf.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hashes the random secret of an API key with Argon2id
+///
+/// Secrets carry 256 random bits, so unlike passwords they need no costly parameters
+/// against guessing: a light setting keeps every authenticated request cheap.
+pub fn hash_key_secret(secret: &str) -> Result<String, String> {
+    let params = Params::new(4 * 1024, 1, 1, None)
+        .map_err(|e| format!("Invalid Argon2 parameters: {}", e))?;
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password(secret.as_bytes(), &SaltString::generate(&mut OsRng))
+        .map(|hash| hash.to_string())
+        .map_err(|_| "Key generation failed".to_string())
+}
+
+/// Whether an API key secret matches its stored [`hash_key_secret`] hash
+pub fn verify_key_secret(secret: &str, hash: &str) -> bool {
+    verify_password(secret, hash, PasswordScheme::Argon2id)
+}
+
+/// Runs a password verification against a throwaway hash of [`default_scheme`]
+///
+/// Login calls this for unknown accounts so they take as long to reject as a wrong
+/// password, and response times don't reveal which emails are registered.
+pub fn verify_dummy(password: &str) {
+    if let Some((hash, scheme)) = dummy_hash() {
+        verify_password(password, hash, *scheme);
+    }
+}
+
+fn dummy_hash() -> Option<&'static (String, PasswordScheme)> {
+    static DUMMY_HASH: OnceLock<Option<(String, PasswordScheme)>> = OnceLock::new();
+    DUMMY_HASH
+        .get_or_init(|| hash_password(&uuid::Uuid::new_v4().to_string()).ok())
+        .as_ref()
+}
+
+/// Compares secrets (key prefixes, fingerprints) in time independent of where they differ
+///
+/// Only the lengths may leak; compare fixed-length digests when those are secret too.
+pub fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
+/// Whether a verified hash should be replaced: it uses another scheme than
+/// [`default_scheme`], or weaker or different cost parameters than configured
+pub fn needs_rehash(hash: &str, scheme: PasswordScheme) -> bool {
+    if scheme != default_scheme() {
+        return true;
+    }
+    match scheme {
+        PasswordScheme::Bcrypt => hash
+            .split('$')
+            .nth(2)
+            .and_then(|cost| cost.parse::<u32>().ok())
+            .is_none_or(|cost| cost != bcrypt_cost()),
+        PasswordScheme::Argon2id => {
+            let (Ok(parsed), Ok(wanted)) = (PasswordHash::new(hash), argon2_params()) else {
+                return true;
+            };
+            match Params::try_from(&parsed) {
+                Ok(current) => {
+                    current.m_cost() != wanted.m_cost()
+                        || current.t_cost() != wanted.t_cost()
+                        || current.p_cost() != wanted.p_cost()
+                }
+                Err(_) => true,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_argon2id_round_trip() {
+        let (hash, scheme) = hash_password("correct horse").unwrap();
+
+        assert_eq!(scheme, PasswordScheme::Argon2id);
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("correct horse", &hash, scheme));
+        assert!(!verify_password("wrong horse", &hash, scheme));
+        assert!(!needs_rehash(&hash, scheme));
+    }
+
+    #[test]
+    fn test_bcrypt_hashes_verify_and_need_rehash() {
+        let hash = bcrypt::hash("legacy password", 4).unwrap();
+
+        assert!(verify_password(
+            "legacy password",
+            &hash,
+            PasswordScheme::Bcrypt
+        ));
+        assert!(!verify_password(
+            "legacy password",
+            &hash,
+            PasswordScheme::Argon2id
+        ));
+        assert!(needs_rehash(&hash, PasswordScheme::Bcrypt));
+    }
+
+    #[test]
+    fn test_unknown_accounts_pay_the_same_hashing_cost() {
+        verify_dummy("anything");
+        let (hash, scheme) = dummy_hash().unwrap();
+        assert_eq!(*scheme, default_scheme());
+        assert!(!needs_rehash(hash, *scheme));
+    }
+
+    #[test]
+    fn test_password_policy() {
+        assert!(check_policy("correct horse").is_ok());
+        assert!(check_policy("password123").is_ok());
+        assert!(check_policy("short1").is_err());
+        assert!(check_policy("onlyletterslong").is_err());
+        assert!(check_policy("1234567890123").is_err());
+        assert!(check_policy(&"a1".repeat(MAX_PASSWORD_LENGTH)).is_err());
+        assert!(check_policy("").unwrap_err().starts_with("Password must"));
+    }
+
+    #[test]
+    fn test_key_secret_round_trip() {
+        let secret = random_hex(32);
+        assert_eq!(secret.len(), 64);
+        assert_ne!(secret, random_hex(32));
+
+        let hash = hash_key_secret(&secret).unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_key_secret(&secret, &hash));
+        assert!(!verify_key_secret(&random_hex(32), &hash));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq("0123456789abcdef", "0123456789abcdef"));
+        assert!(!constant_time_eq("0123456789abcdef", "0123456789abcdeg"));
+        assert!(!constant_time_eq("0123456789abcdef", "0123"));
+        assert!(!constant_time_eq("", "0"));
+    }
+
+    #[test]
+    fn test_changed_cost_parameters_need_rehash() {
+        let weaker = Argon2::new(
+            Algorithm::Argon2id,
+            Version::V0x13,
+            Params::new(8 * 1024, 1, 1, None).unwrap(),
+        )
+        .hash_password(b"pw", &SaltString::generate(&mut OsRng))
+        .unwrap()
+        .to_string();
+
+        assert!(verify_password("pw", &weaker, PasswordScheme::Argon2id));
+        assert!(needs_rehash(&weaker, PasswordScheme::Argon2id));
+    }
+}
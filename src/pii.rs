@@ -0,0 +1,441 @@
+use hmac::{Hmac, Mac};
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+
+/// Prefix of an encrypted address as stored in a document field
+const ENCRYPTED_PREFIX: &str = "enc:";
+
+/// First bytes of an encrypted blob; zstd frames and JSON never start with a zero byte
+const SEALED_MAGIC: [u8; 4] = [0x00, b'E', b'N', b'C'];
+
+/// How a store keeps the email addresses it persists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protection {
+    /// The address as given
+    Plain,
+    /// SHA-256 of the normalized address, keyed with `PII_HASH_SALT`; can't be reversed
+    Hash,
+    /// AES-256-GCM with `PII_ENCRYPTION_KEY`; readable again with the key
+    Encrypt,
+}
+
+impl Protection {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.trim().to_ascii_lowercase().as_str() {
+            "plain" => Some(Protection::Plain),
+            "hash" => Some(Protection::Hash),
+            "encrypt" => Some(Protection::Encrypt),
+            _ => None,
+        }
+    }
+}
+
+/// A kind of storage holding email addresses, each switched separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Store {
+    /// Queued jobs and their result chunks in Redis, and address lists spilled to MongoDB
+    JobPayloads,
+    /// The validation history collection
+    History,
+    /// The account field of the audit log
+    AuditLog,
+    /// The SHA-256 of validated addresses in the hash lookup index
+    VerdictIndex,
+}
+
+impl Store {
+    fn env_var(self) -> &'static str {
+        match self {
+            Store::JobPayloads => "PII_JOB_PAYLOADS",
+            Store::History => "PII_HISTORY",
+            Store::AuditLog => "PII_AUDIT_LOG",
+            Store::VerdictIndex => "PII_VERDICT_INDEX",
+        }
+    }
+
+    fn default_protection(self) -> Protection {
+        match self {
+            Store::JobPayloads | Store::AuditLog | Store::VerdictIndex => Protection::Plain,
+            Store::History => Protection::Hash,
+        }
+    }
+
+    /// Workers must read queued addresses back, so job payloads can't be hashed; the
+    /// history never held addresses in the clear. The verdict index holds no addresses,
+    /// only the unsalted SHA-256 clients compute to look their own up: salting it would
+    /// stop them from matching, so it is kept as it is or encrypted.
+    fn allows(self, protection: Protection) -> bool {
+        !matches!(
+            (self, protection),
+            (Store::JobPayloads | Store::VerdictIndex, Protection::Hash)
+                | (Store::History, Protection::Plain)
+        )
+    }
+}
+
+/// Protection of each store and the keys it uses
+///
+/// Read from the environment:
+/// - `PII_JOB_PAYLOADS`: `plain` (default) or `encrypt`
+/// - `PII_HISTORY`: `hash` (default) or `encrypt`, which keeps the hash for lookups
+///   and adds the encrypted address
+/// - `PII_AUDIT_LOG`: `plain` (default), `hash` or `encrypt`
+/// - `PII_VERDICT_INDEX`: `plain` (default) keeps the SHA-256 of indexed addresses as
+///   hash lookups match it; `encrypt` stores it encrypted, leaving only the 5-character
+///   prefix of the bucket names readable
+/// - `PII_HASH_SALT`: secret every stored hash is keyed with; unset, hashes are plain
+///   SHA-256 as before. Changing it orphans the hashes already stored.
+/// - `PII_ENCRYPTION_KEY`: 32-byte AES key as 64 hex characters, required when any
+///   store encrypts; provisioned from the KMS or secret manager of the deployment
+#[derive(Clone, PartialEq, Eq)]
+pub struct PiiSettings {
+    pub job_payloads: Protection,
+    pub history: Protection,
+    pub audit_log: Protection,
+    pub verdict_index: Protection,
+    salt: Vec<u8>,
+    key: Option<[u8; 32]>,
+}
+
+impl Default for PiiSettings {
+    fn default() -> Self {
+        Self {
+            job_payloads: Store::JobPayloads.default_protection(),
+            history: Store::History.default_protection(),
+            audit_log: Store::AuditLog.default_protection(),
+            verdict_index: Store::VerdictIndex.default_protection(),
+            salt: Vec::new(),
+            key: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for PiiSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PiiSettings")
+            .field("job_payloads", &self.job_payloads)
+            .field("history", &self.history)
+            .field("audit_log", &self.audit_log)
+            .field("verdict_index", &self.verdict_index)
+            .field("salted", &!self.salt.is_empty())
+            .field("encryption_key", &self.key.is_some())
+            .finish()
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+impl PiiSettings {
+    pub fn from_env() -> Result<Self, String> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let key = match var("PII_ENCRYPTION_KEY") {
+            Some(key) => Some(
+                unhex(&key)
+                    .and_then(|key| <[u8; 32]>::try_from(key).ok())
+                    .ok_or("PII_ENCRYPTION_KEY must be 64 hex characters")?,
+            ),
+            None => None,
+        };
+        let mut settings = Self {
+            salt: var("PII_HASH_SALT").unwrap_or_default().into_bytes(),
+            key,
+            ..Self::default()
+        };
+        for store in [
+            Store::JobPayloads,
+            Store::History,
+            Store::AuditLog,
+            Store::VerdictIndex,
+        ] {
+            if let Some(mode) = var(store.env_var()) {
+                let protection = Protection::parse(&mode)
+                    .filter(|protection| store.allows(*protection))
+                    .ok_or_else(|| format!("{} does not support \"{}\"", store.env_var(), mode))?;
+                settings.set(store, protection)?;
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Switches a store, which must not encrypt without a key
+    pub fn set(&mut self, store: Store, protection: Protection) -> Result<(), String> {
+        if protection == Protection::Encrypt && self.key.is_none() {
+            return Err(format!(
+                "{}=encrypt needs PII_ENCRYPTION_KEY",
+                store.env_var()
+            ));
+        }
+        match store {
+            Store::JobPayloads => self.job_payloads = protection,
+            Store::History => self.history = protection,
+            Store::AuditLog => self.audit_log = protection,
+            Store::VerdictIndex => self.verdict_index = protection,
+        }
+        Ok(())
+    }
+
+    pub fn with_keys(mut self, salt: &str, key: [u8; 32]) -> Self {
+        self.salt = salt.as_bytes().to_vec();
+        self.key = Some(key);
+        self
+    }
+
+    pub fn protection(&self, store: Store) -> Protection {
+        match store {
+            Store::JobPayloads => self.job_payloads,
+            Store::History => self.history,
+            Store::AuditLog => self.audit_log,
+            Store::VerdictIndex => self.verdict_index,
+        }
+    }
+
+    /// Hex digest of the trimmed, lowercased address, keyed with the salt when set
+    pub fn hash(&self, email: &str) -> String {
+        let email = email.trim().to_lowercase();
+        if self.salt.is_empty() {
+            return hex(&Sha256::digest(email.as_bytes()));
+        }
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.salt).expect("HMAC accepts keys of any length");
+        mac.update(email.as_bytes());
+        hex(&mac.finalize().into_bytes())
+    }
+
+    fn cipher(&self) -> Option<LessSafeKey> {
+        let key = UnboundKey::new(&AES_256_GCM, self.key.as_ref()?).ok()?;
+        Some(LessSafeKey::new(key))
+    }
+
+    /// `enc:` and the hex of the nonce and ciphertext of the trimmed, lowercased address
+    ///
+    /// The nonce is derived from the address, so an address always encrypts the same way
+    /// and stored values can be looked up by equality, as hashes can. `None` without a key.
+    pub fn encrypt(&self, email: &str) -> Option<String> {
+        let cipher = self.cipher()?;
+        let email = email.trim().to_lowercase();
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.key.as_ref()?)
+            .expect("HMAC accepts keys of any length");
+        mac.update(email.as_bytes());
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&mac.finalize().into_bytes()[..NONCE_LEN]);
+
+        let mut sealed = email.into_bytes();
+        cipher
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut sealed,
+            )
+            .ok()?;
+        Some(format!(
+            "{}{}{}",
+            ENCRYPTED_PREFIX,
+            hex(&nonce),
+            hex(&sealed)
+        ))
+    }
+
+    /// The address a stored value was encrypted from; other values are returned as they are
+    pub fn reveal(&self, stored: &str) -> Option<String> {
+        let Some(encrypted) = stored.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Some(stored.to_string());
+        };
+        let bytes = unhex(encrypted)?;
+        let opened = self.open_with_nonce(&bytes)?;
+        String::from_utf8(opened).ok()
+    }
+
+    /// How `store` keeps `email`
+    ///
+    /// Deterministic in every mode, so filters are protected the same way as the values
+    /// they match.
+    pub fn protect(&self, store: Store, email: &str) -> String {
+        match self.protection(store) {
+            Protection::Plain => email.to_string(),
+            Protection::Hash => self.hash(email),
+            // The settings never encrypt without a key
+            Protection::Encrypt => self.encrypt(email).unwrap_or_else(|| self.hash(email)),
+        }
+    }
+
+    /// Encrypts a blob of `store` with a random nonce when the store encrypts
+    pub fn seal(&self, store: Store, blob: Vec<u8>) -> Vec<u8> {
+        if self.protection(store) != Protection::Encrypt {
+            return blob;
+        }
+        let Some(cipher) = self.cipher() else {
+            return blob;
+        };
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .expect("the system random number generator is available");
+        let mut sealed = blob;
+        cipher
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut sealed,
+            )
+            .expect("AES-GCM seals payloads of any job's size");
+        [&SEALED_MAGIC[..], &nonce, &sealed].concat()
+    }
+
+    /// A blob as written before [`Self::seal`]; `None` when it is encrypted but can't
+    /// be decrypted with the configured key
+    pub fn open(&self, blob: &[u8]) -> Option<Vec<u8>> {
+        match blob.strip_prefix(&SEALED_MAGIC) {
+            Some(sealed) => self.open_with_nonce(sealed),
+            None => Some(blob.to_vec()),
+        }
+    }
+
+    fn open_with_nonce(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+        if bytes.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).ok()?;
+        let mut sealed = sealed.to_vec();
+        let opened = self
+            .cipher()?
+            .open_in_place(nonce, Aad::empty(), &mut sealed)
+            .ok()?;
+        Some(opened.to_vec())
+    }
+}
+
+static SETTINGS: OnceLock<PiiSettings> = OnceLock::new();
+
+/// Installs the settings read at startup; later calls are ignored
+pub fn init(settings: PiiSettings) {
+    let _ = SETTINGS.set(settings);
+}
+
+/// The installed settings, or the defaults when none were installed
+pub fn settings() -> &'static PiiSettings {
+    SETTINGS.get_or_init(PiiSettings::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::validation::first_seen::email_hash;
+
+    fn keyed() -> PiiSettings {
+        PiiSettings::default().with_keys("pepper", [7; 32])
+    }
+
+    #[test]
+    fn test_unsalted_hash_matches_existing_records() {
+        let settings = PiiSettings::default();
+        assert_eq!(
+            settings.hash(" User@Example.com"),
+            email_hash("user@example.com")
+        );
+        assert_eq!(
+            settings.protect(Store::History, "user@example.com"),
+            email_hash("user@example.com")
+        );
+        assert_eq!(
+            settings.protect(Store::AuditLog, "user@example.com"),
+            "user@example.com"
+        );
+
+        let salted = keyed().hash("user@example.com");
+        assert_ne!(salted, email_hash("user@example.com"));
+        assert_eq!(salted.len(), 64);
+    }
+
+    #[test]
+    fn test_addresses_encrypt_deterministically() {
+        let mut settings = keyed();
+        settings.set(Store::AuditLog, Protection::Encrypt).unwrap();
+
+        let stored = settings.protect(Store::AuditLog, "User@Example.com");
+        assert!(stored.starts_with(ENCRYPTED_PREFIX));
+        assert!(!stored.contains("example"));
+        assert_eq!(
+            stored,
+            settings.protect(Store::AuditLog, "user@example.com")
+        );
+        assert_ne!(
+            stored,
+            settings.protect(Store::AuditLog, "other@example.com")
+        );
+        assert_eq!(
+            settings.reveal(&stored).as_deref(),
+            Some("user@example.com")
+        );
+        assert_eq!(settings.reveal("admin").as_deref(), Some("admin"));
+
+        // Another key can't read it
+        let other = PiiSettings::default().with_keys("pepper", [8; 32]);
+        assert_eq!(other.reveal(&stored), None);
+    }
+
+    #[test]
+    fn test_verdict_index_hashes_encrypt() {
+        let hash = email_hash("user@example.com");
+        let mut settings = keyed();
+        assert_eq!(settings.protect(Store::VerdictIndex, &hash), hash);
+
+        settings
+            .set(Store::VerdictIndex, Protection::Encrypt)
+            .unwrap();
+        let stored = settings.protect(Store::VerdictIndex, &hash);
+        assert!(stored.starts_with(ENCRYPTED_PREFIX));
+        assert_eq!(stored, settings.protect(Store::VerdictIndex, &hash));
+        assert_eq!(settings.reveal(&stored), Some(hash));
+    }
+
+    #[test]
+    fn test_blobs_seal_and_open() {
+        let mut settings = keyed();
+        let blob = br#"{"emails":["user@example.com"]}"#.to_vec();
+        assert_eq!(settings.seal(Store::JobPayloads, blob.clone()), blob);
+
+        settings
+            .set(Store::JobPayloads, Protection::Encrypt)
+            .unwrap();
+        let sealed = settings.seal(Store::JobPayloads, blob.clone());
+        assert!(sealed.starts_with(&SEALED_MAGIC));
+        assert_ne!(sealed, settings.seal(Store::JobPayloads, blob.clone()));
+        assert_eq!(settings.open(&sealed), Some(blob.clone()));
+        assert_eq!(settings.open(&blob), Some(blob));
+        assert_eq!(PiiSettings::default().open(&sealed), None);
+    }
+
+    #[test]
+    fn test_store_switches() {
+        let mut settings = PiiSettings::default();
+        assert!(settings.set(Store::History, Protection::Encrypt).is_err());
+        assert!(!Store::JobPayloads.allows(Protection::Hash));
+        assert!(!Store::History.allows(Protection::Plain));
+        assert!(Store::AuditLog.allows(Protection::Hash));
+        assert!(!Store::VerdictIndex.allows(Protection::Hash));
+        assert_eq!(Protection::parse(" Encrypt"), Some(Protection::Encrypt));
+        assert_eq!(Protection::parse("aes"), None);
+        assert_eq!(unhex("0aff"), Some(vec![0x0a, 0xff]));
+        assert_eq!(unhex("0af"), None);
+    }
+}
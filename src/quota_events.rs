@@ -0,0 +1,204 @@
+use crate::account_settings;
+use crate::metering::{Meter, OveragePolicy};
+use crate::organizations::{self, OrgRole};
+use crate::webhooks;
+use mongodb::Client;
+use serde_json::json;
+
+/// Share of the quota, in percent, at which `quota.warning` is sent by default
+pub const DEFAULT_WARNING_PERCENT: u64 = 80;
+
+/// Lowest warning threshold an account may choose; usage below it never looks up
+/// anyone's settings
+pub const MIN_WARNING_PERCENT: u64 = 50;
+
+/// Webhook events about an account's monthly quota, each sent at most once per
+/// billing period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaEvent {
+    /// Usage reached the account's warning threshold
+    Warning,
+    /// Usage went past the monthly quota; the overage policy applies once the grace
+    /// allowance is used up too
+    Exceeded,
+}
+
+impl QuotaEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuotaEvent::Warning => "quota.warning",
+            QuotaEvent::Exceeded => "quota.exceeded",
+        }
+    }
+}
+
+/// Checks a warning threshold chosen in the account settings
+pub fn check_warning_percent(percent: u64) -> Result<u64, String> {
+    if (MIN_WARNING_PERCENT..100).contains(&percent) {
+        Ok(percent)
+    } else {
+        Err(format!(
+            "quota_warning_percent must be between {} and 99",
+            MIN_WARNING_PERCENT
+        ))
+    }
+}
+
+/// Whether usage is high enough for any account's threshold
+pub fn may_notify(limit: u64, used: u64) -> bool {
+    used.saturating_mul(100) >= limit.saturating_mul(MIN_WARNING_PERCENT)
+}
+
+/// Events the usage has reached, warning first
+pub fn reached(limit: u64, used: u64, warning_percent: u64) -> Vec<QuotaEvent> {
+    let mut events = Vec::new();
+    if used.saturating_mul(100) >= limit.saturating_mul(warning_percent) {
+        events.push(QuotaEvent::Warning);
+    }
+    if used > limit {
+        events.push(QuotaEvent::Exceeded);
+    }
+    events
+}
+
+/// An account's usage of a billing period, as of the charge that reached an event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaUsage {
+    pub period: String,
+    /// Validated addresses so far, including the charge
+    pub used: u64,
+    pub limit: u64,
+    pub policy: OveragePolicy,
+}
+
+/// Body posted to the account's webhook
+pub fn payload(event: QuotaEvent, usage: &QuotaUsage, warning_percent: u64) -> serde_json::Value {
+    json!({
+        "event": event.name(),
+        "period": usage.period,
+        "used": usage.used,
+        "limit": usage.limit,
+        "warning_percent": warning_percent,
+        "policy": usage.policy.name(),
+    })
+}
+
+/// Accounts told about the quota of `scope_owner`: the account itself, or the owners
+/// of its organization
+async fn recipients(
+    mongo_client: &Client,
+    scope_owner: &str,
+    charged_by: &str,
+) -> Result<Vec<String>, String> {
+    if !scope_owner.starts_with("org:") {
+        return Ok(vec![scope_owner.to_string()]);
+    }
+    Ok(organizations::organization_of(mongo_client, charged_by)
+        .await?
+        .map(|org| {
+            org.members
+                .into_iter()
+                .filter(|member| member.role == OrgRole::Owner)
+                .map(|member| member.email)
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Sends the quota events the usage of `scope_owner` has reached to the webhook
+/// (`webhook_url` in the account settings) of each recipient, signed like every
+/// other delivery
+///
+/// Each event goes out once per recipient and billing period, whichever instance
+/// charged the usage. Failures are only logged.
+pub async fn notify(
+    meter: &Meter,
+    mongo_client: &Client,
+    scope_owner: &str,
+    charged_by: &str,
+    usage: &QuotaUsage,
+) {
+    let recipients = match recipients(mongo_client, scope_owner, charged_by).await {
+        Ok(recipients) => recipients,
+        Err(e) => {
+            eprintln!("Failed to resolve quota webhook of {}: {}", scope_owner, e);
+            return;
+        }
+    };
+    for tenant_id in recipients {
+        // The exceeded event is the last one of the period
+        if meter
+            .event_sent(&tenant_id, &usage.period, QuotaEvent::Exceeded.name())
+            .await
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        let Ok(Some(settings)) = account_settings::load_settings(mongo_client, &tenant_id).await
+        else {
+            continue;
+        };
+        let Some(url) = &settings.webhook_url else {
+            continue;
+        };
+        let warning_percent = settings.warning_percent();
+        for event in reached(usage.limit, usage.used, warning_percent) {
+            if !meter
+                .mark_event_sent(&tenant_id, &usage.period, event.name())
+                .await
+                .unwrap_or(false)
+            {
+                continue;
+            }
+            let payload = payload(event, usage, warning_percent);
+            if let Err(e) = webhooks::deliver_signed(mongo_client, &tenant_id, url, &payload).await
+            {
+                eprintln!("{} webhook for {} failed: {}", event.name(), tenant_id, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_reached() {
+        assert!(reached(1000, 799, 80).is_empty());
+        assert_eq!(reached(1000, 800, 80), vec![QuotaEvent::Warning]);
+        assert_eq!(reached(1000, 1000, 80), vec![QuotaEvent::Warning]);
+        assert_eq!(
+            reached(1000, 1001, 80),
+            vec![QuotaEvent::Warning, QuotaEvent::Exceeded]
+        );
+        assert_eq!(reached(1000, 949, 95), Vec::new());
+
+        assert!(!may_notify(1000, 499));
+        assert!(may_notify(1000, 500));
+        assert!(check_warning_percent(80).is_ok());
+        assert!(check_warning_percent(49).is_err());
+        assert!(check_warning_percent(100).is_err());
+    }
+
+    #[test]
+    fn test_payload() {
+        let usage = QuotaUsage {
+            period: "2024-05".to_string(),
+            used: 850,
+            limit: 1000,
+            policy: OveragePolicy::HardStop,
+        };
+        assert_eq!(
+            payload(QuotaEvent::Warning, &usage, 80),
+            json!({
+                "event": "quota.warning",
+                "period": "2024-05",
+                "used": 850,
+                "limit": 1000,
+                "warning_percent": 80,
+                "policy": "hard_stop",
+            })
+        );
+    }
+}
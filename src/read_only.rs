@@ -0,0 +1,178 @@
+use crate::error::ApiError;
+use actix_web::Error;
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::{Method, StatusCode};
+use std::future::{Ready, ready};
+use std::pin::Pin;
+
+/// Whether the service runs as a read-only replica (`READ_ONLY=true`)
+///
+/// Validations keep being served from the cache and reference lists, while
+/// registration, key management, job submission and sync pushes are refused.
+pub fn is_enabled() -> bool {
+    std::env::var("READ_ONLY")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "true" | "1" | "yes"))
+        .unwrap_or(false)
+}
+
+/// POST endpoints that only read state and stay available in read-only mode
+const READ_POST_PATHS: &[&str] = &[
+    "/api/v1/validate-email",
+    "/api/v2/validate-email",
+    "/api/v1/auth/login",
+    "/api/v1/auth/refresh",
+    // Small batches are validated on the spot; the handler refuses the ones it would queue
+    "/api/v1/validate-emails-bulk",
+    // GraphQL mutations are refused by the resolvers themselves
+    "/api/v1/graphql",
+];
+
+/// GET endpoints that change state and are refused in read-only mode
+const WRITE_GET_PATHS: &[&str] = &[
+    // Following a verification link activates the account
+    "/api/v1/verify",
+];
+
+/// Whether a request would change persistent state
+pub fn is_write_request(method: &Method, path: &str) -> bool {
+    let path = path.trim_end_matches('/');
+    match *method {
+        Method::GET | Method::HEAD => WRITE_GET_PATHS.contains(&path),
+        Method::OPTIONS => false,
+        Method::POST => !READ_POST_PATHS.contains(&path),
+        _ => true,
+    }
+}
+
+/// 503 `READ_ONLY` for writes rejected in read-only mode
+pub fn read_only_error() -> ApiError {
+    ApiError::validation(
+        "READ_ONLY",
+        "This instance is in read-only mode; writes are temporarily unavailable",
+    )
+    .with_status(StatusCode::SERVICE_UNAVAILABLE)
+}
+
+pub struct ReadOnlyMiddleware<S> {
+    service: S,
+    enabled: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for ReadOnlyMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if self.enabled && is_write_request(req.method(), req.path()) {
+            let res = req.error_response(read_only_error()).map_into_right_body();
+            return Box::pin(async move { Ok(res) });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
+
+/// Rejects writes with 503 while `READ_ONLY` is set
+pub struct ReadOnly {
+    enabled: bool,
+}
+
+impl ReadOnly {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(is_enabled())
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ReadOnly
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ReadOnlyMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ReadOnlyMiddleware {
+            service,
+            enabled: self.enabled,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, HttpResponse, test as actix_test, web};
+
+    #[test]
+    fn test_write_classification() {
+        assert!(!is_write_request(&Method::GET, "/api/v1/health"));
+        assert!(!is_write_request(&Method::POST, "/api/v1/validate-email"));
+        assert!(!is_write_request(&Method::POST, "/api/v2/validate-email"));
+        assert!(!is_write_request(&Method::POST, "/api/v1/graphql"));
+        assert!(!is_write_request(
+            &Method::POST,
+            "/api/v1/validate-emails-bulk"
+        ));
+        assert!(is_write_request(&Method::POST, "/api/v1/register"));
+        assert!(is_write_request(&Method::GET, "/api/v1/verify"));
+        assert!(is_write_request(&Method::DELETE, "/api/v1/keys/abc"));
+    }
+
+    #[actix_web::test]
+    async fn test_middleware_rejects_writes_only_when_enabled() {
+        for (enabled, expected) in [
+            (true, StatusCode::SERVICE_UNAVAILABLE),
+            (false, StatusCode::OK),
+        ] {
+            let app = actix_test::init_service(
+                App::new()
+                    .wrap(ReadOnly::new(enabled))
+                    .route(
+                        "/api/v1/register",
+                        web::post().to(|| async { HttpResponse::Ok().finish() }),
+                    )
+                    .route(
+                        "/api/v1/validate-email",
+                        web::post().to(|| async { HttpResponse::Ok().finish() }),
+                    ),
+            )
+            .await;
+
+            let req = actix_test::TestRequest::post()
+                .uri("/api/v1/register")
+                .to_request();
+            let resp = actix_test::call_service(&app, req).await;
+            assert_eq!(resp.status(), expected);
+            if enabled {
+                let body: serde_json::Value = actix_test::read_body_json(resp).await;
+                assert_eq!(body["error"]["code"], "READ_ONLY");
+                assert!(body["error"]["request_id"].is_string());
+            }
+
+            let req = actix_test::TestRequest::post()
+                .uri("/api/v1/validate-email")
+                .to_request();
+            let resp = actix_test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+    }
+}
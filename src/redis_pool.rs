@@ -0,0 +1,207 @@
+use deadpool_redis::{Config, Connection, Pool, PoolConfig, PoolError, Runtime, Timeouts};
+use redis::{ErrorKind, RedisError};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+pub const DEFAULT_POOL_SIZE: usize = 32;
+
+pub const DEFAULT_POOL_WAIT_TIMEOUT_MS: u64 = 2000;
+
+pub const DEFAULT_CONNECT_TIMEOUT_MS: u64 = 2000;
+
+/// Pool size and timeouts, read once from the environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolSettings {
+    /// Most connections open at once (`REDIS_POOL_SIZE`)
+    pub max_size: usize,
+    /// Longest a caller waits for a free connection (`REDIS_POOL_WAIT_TIMEOUT_MS`)
+    pub wait_timeout: Duration,
+    /// Longest opening or health-checking a connection may take (`REDIS_CONNECT_TIMEOUT_MS`)
+    pub connect_timeout: Duration,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_POOL_SIZE,
+            wait_timeout: Duration::from_millis(DEFAULT_POOL_WAIT_TIMEOUT_MS),
+            connect_timeout: Duration::from_millis(DEFAULT_CONNECT_TIMEOUT_MS),
+        }
+    }
+}
+
+impl PoolSettings {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let positive = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .filter(|v| *v > 0)
+        };
+        Self {
+            max_size: positive("REDIS_POOL_SIZE")
+                .map(|size| size as usize)
+                .unwrap_or(defaults.max_size),
+            wait_timeout: positive("REDIS_POOL_WAIT_TIMEOUT_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.wait_timeout),
+            connect_timeout: positive("REDIS_CONNECT_TIMEOUT_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.connect_timeout),
+        }
+    }
+
+    fn pool_config(&self) -> PoolConfig {
+        let mut config = PoolConfig::new(self.max_size);
+        config.timeouts = Timeouts {
+            wait: Some(self.wait_timeout),
+            create: Some(self.connect_timeout),
+            recycle: Some(self.connect_timeout),
+        };
+        config
+    }
+}
+
+/// Snapshot of a pool for the admin cache stats
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PoolMetrics {
+    pub max_size: usize,
+    /// Connections currently open, idle or in use
+    pub size: usize,
+    /// Idle connections ready to be handed out
+    pub available: usize,
+    /// Callers waiting for a connection
+    pub waiting: usize,
+    /// Checkouts that failed since startup: timeouts or Redis unreachable
+    pub checkout_errors: u64,
+}
+
+/// Shared Redis connection pool
+///
+/// Every store built from the same URL (result cache, job queue, meter, health
+/// history, ...) draws from one pool, configured from [`PoolSettings::from_env`]
+/// when the first of them is created. Connections are opened lazily, so building
+/// the pool never needs Redis to be up.
+///
+/// Before an idle connection is handed out again it is checked with a `PING`; a
+/// connection that fails the check, or that broke while in use, is dropped and a
+/// fresh one opened in its place. A Redis restart therefore costs the requests in
+/// flight at the time, not the instance.
+#[derive(Clone)]
+pub struct RedisPool {
+    pool: Pool,
+    checkout_errors: Arc<AtomicU64>,
+}
+
+fn shared_pools() -> &'static Mutex<HashMap<String, RedisPool>> {
+    static POOLS: OnceLock<Mutex<HashMap<String, RedisPool>>> = OnceLock::new();
+    POOLS.get_or_init(Mutex::default)
+}
+
+impl RedisPool {
+    /// The process-wide pool for `redis_url`, created on first use
+    pub fn new(redis_url: &str) -> Result<Self, RedisError> {
+        let mut pools = shared_pools().lock().unwrap();
+        if let Some(pool) = pools.get(redis_url) {
+            return Ok(pool.clone());
+        }
+        let pool = Self::with_settings(redis_url, PoolSettings::from_env())?;
+        pools.insert(redis_url.to_string(), pool.clone());
+        Ok(pool)
+    }
+
+    /// A pool of its own, not shared with other callers
+    pub fn with_settings(redis_url: &str, settings: PoolSettings) -> Result<Self, RedisError> {
+        let mut config = Config::from_url(redis_url);
+        config.pool = Some(settings.pool_config());
+        let pool = config.create_pool(Some(Runtime::Tokio1)).map_err(|e| {
+            RedisError::from((
+                ErrorKind::InvalidClientConfig,
+                "Invalid Redis pool configuration",
+                e.to_string(),
+            ))
+        })?;
+        Ok(Self {
+            pool,
+            checkout_errors: Arc::default(),
+        })
+    }
+
+    /// Checks out a connection; it returns to the pool when dropped
+    pub async fn get(&self) -> Result<Connection, RedisError> {
+        self.pool.get().await.map_err(|e| {
+            self.checkout_errors.fetch_add(1, Ordering::Relaxed);
+            match e {
+                PoolError::Backend(e) => e,
+                other => RedisError::from((
+                    ErrorKind::IoError,
+                    "Redis pool unavailable",
+                    other.to_string(),
+                )),
+            }
+        })
+    }
+
+    pub async fn ping(&self) -> bool {
+        match self.get().await {
+            Ok(mut conn) => redis::cmd("PING")
+                .query_async::<String>(&mut conn)
+                .await
+                .is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    pub fn metrics(&self) -> PoolMetrics {
+        let status = self.pool.status();
+        PoolMetrics {
+            max_size: status.max_size,
+            size: status.size,
+            available: status.available,
+            waiting: status.waiting,
+            checkout_errors: self.checkout_errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_is_shared_per_url() {
+        let first = RedisPool::new("redis://127.0.0.1:6390/4").unwrap();
+        let second = RedisPool::new("redis://127.0.0.1:6390/4").unwrap();
+        first.checkout_errors.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(second.metrics().checkout_errors, 1);
+
+        let other = RedisPool::new("redis://127.0.0.1:6390/5").unwrap();
+        assert_eq!(other.metrics().checkout_errors, 0);
+    }
+
+    #[test]
+    fn test_invalid_url_is_rejected() {
+        assert!(RedisPool::new("not a redis url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_redis_counts_checkout_error() {
+        // Nothing listens on port 1
+        let settings = PoolSettings {
+            max_size: 2,
+            ..PoolSettings::default()
+        };
+        let pool = RedisPool::with_settings("redis://127.0.0.1:1", settings).unwrap();
+        assert!(pool.get().await.is_err());
+        assert!(!pool.ping().await);
+
+        let metrics = pool.metrics();
+        assert_eq!(metrics.max_size, 2);
+        assert_eq!(metrics.checkout_errors, 2);
+    }
+}
@@ -0,0 +1,226 @@
+use serde_json::{Map, Value};
+
+/// Top-level fields a REST validation result can carry
+pub const RESULT_FIELDS: &[&str] = &[
+    "is_valid",
+    "status",
+    "message",
+    "domain",
+    "has_tag",
+    "base_address",
+    "email_age",
+    "domain_registration",
+    "error",
+];
+
+/// Fields kept by `verbosity=minimal`
+const MINIMAL_FIELDS: &[&str] = &["is_valid", "status", "error.code"];
+
+/// Detail level of a REST validation result, from the `verbosity` query parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Verbosity {
+    /// `is_valid`, `status` and `error.code`; optional enrichment lookups are skipped
+    Minimal,
+    /// The response as it has always been
+    #[default]
+    Standard,
+    /// Everything the pipeline produced, including fields the endpoint leaves out by
+    /// default (`is_valid`, `domain` and the sub-address fields on `POST /validate-email`)
+    Full,
+}
+
+impl Verbosity {
+    pub fn parse(verbosity: &str) -> Option<Self> {
+        match verbosity.trim().to_ascii_lowercase().as_str() {
+            "minimal" => Some(Verbosity::Minimal),
+            "standard" => Some(Verbosity::Standard),
+            "full" => Some(Verbosity::Full),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ShapeError {
+    /// `verbosity` is not minimal, standard or full
+    Verbosity,
+    /// `fields` entries that don't start with one of [`RESULT_FIELDS`]
+    UnknownFields(Vec<String>),
+}
+
+/// How a REST validation result is rendered, from the `fields` and `verbosity` query
+/// parameters
+///
+/// Shaping works on the serialized result, so it never changes what is validated, only
+/// what is sent back. A selected path whose parent is `null` (`error.code` on a valid
+/// address) is returned as that `null`; paths the result doesn't have are left out.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResponseShape {
+    pub verbosity: Verbosity,
+    /// Dotted paths to keep, e.g. `error.code`; takes precedence over `verbosity`
+    pub fields: Option<Vec<Vec<String>>>,
+}
+
+impl ResponseShape {
+    /// Parses comma-separated `fields` (empty means all) and `verbosity` (default standard)
+    pub fn parse(fields: Option<&str>, verbosity: Option<&str>) -> Result<Self, ShapeError> {
+        let verbosity = match verbosity {
+            Some(verbosity) => Verbosity::parse(verbosity).ok_or(ShapeError::Verbosity)?,
+            None => Verbosity::default(),
+        };
+
+        let paths: Vec<&str> = fields
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .collect();
+        let unknown: Vec<String> = paths
+            .iter()
+            .filter(|path| {
+                path.split('.').any(str::is_empty)
+                    || !path
+                        .split('.')
+                        .next()
+                        .is_some_and(|field| RESULT_FIELDS.contains(&field))
+            })
+            .map(|path| path.to_string())
+            .collect();
+        if !unknown.is_empty() {
+            return Err(ShapeError::UnknownFields(unknown));
+        }
+
+        Ok(ResponseShape {
+            verbosity,
+            fields: (!paths.is_empty()).then(|| paths.into_iter().map(split_path).collect()),
+        })
+    }
+
+    /// Whether the response is sent as it has always been
+    pub fn is_standard(&self) -> bool {
+        self.fields.is_none() && self.verbosity == Verbosity::Standard
+    }
+
+    /// Whether the optional enrichment lookup behind `field` (`email_age`,
+    /// `domain_registration`) is worth running
+    pub fn wants_enrichment(&self, field: &str) -> bool {
+        match &self.fields {
+            Some(paths) => paths.iter().any(|path| path[0] == field),
+            None => self.verbosity != Verbosity::Minimal,
+        }
+    }
+
+    /// Keeps the parts of a serialized result this shape asks for
+    pub fn apply(&self, value: Value) -> Value {
+        match (&self.fields, self.verbosity) {
+            (Some(paths), _) => select(&value, paths),
+            (None, Verbosity::Minimal) => {
+                let paths: Vec<Vec<String>> =
+                    MINIMAL_FIELDS.iter().map(|path| split_path(path)).collect();
+                select(&value, &paths)
+            }
+            (None, _) => value,
+        }
+    }
+}
+
+fn split_path(path: &str) -> Vec<String> {
+    path.split('.').map(str::to_string).collect()
+}
+
+fn select(value: &Value, paths: &[Vec<String>]) -> Value {
+    let mut selected = Map::new();
+    for path in paths {
+        copy_path(value, &mut selected, path);
+    }
+    Value::Object(selected)
+}
+
+fn copy_path(value: &Value, selected: &mut Map<String, Value>, path: &[String]) {
+    let Some((key, rest)) = path.split_first() else {
+        return;
+    };
+    let Some(field) = value.get(key) else {
+        return;
+    };
+    if rest.is_empty() || !field.is_object() {
+        selected.insert(key.clone(), field.clone());
+        return;
+    }
+    let entry = selected
+        .entry(key.clone())
+        .or_insert_with(|| Value::Object(Map::new()));
+    if let Value::Object(inner) = entry {
+        copy_path(field, inner, rest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn invalid() -> Value {
+        json!({
+            "is_valid": false,
+            "domain": { "unicode": "example.com", "ascii": "example.com" },
+            "status": null,
+            "error": { "code": "INVALID_DOMAIN", "message": "No MX records" }
+        })
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(ResponseShape::parse(None, None).unwrap().is_standard());
+        assert!(
+            ResponseShape::parse(Some(" , "), Some("standard"))
+                .unwrap()
+                .is_standard()
+        );
+        assert_eq!(
+            ResponseShape::parse(None, Some("MINIMAL"))
+                .unwrap()
+                .verbosity,
+            Verbosity::Minimal
+        );
+        assert_eq!(
+            ResponseShape::parse(None, Some("tiny")),
+            Err(ShapeError::Verbosity)
+        );
+        assert_eq!(
+            ResponseShape::parse(Some("is_valid,score,error.,error.code"), None),
+            Err(ShapeError::UnknownFields(vec![
+                "score".to_string(),
+                "error.".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let shape = ResponseShape::parse(Some("is_valid,error.code,domain.ascii"), None).unwrap();
+        assert_eq!(
+            shape.apply(invalid()),
+            json!({
+                "is_valid": false,
+                "error": { "code": "INVALID_DOMAIN" },
+                "domain": { "ascii": "example.com" }
+            })
+        );
+
+        let minimal = ResponseShape::parse(None, Some("minimal")).unwrap();
+        assert_eq!(
+            minimal.apply(json!({ "is_valid": true, "status": "VALID", "error": null })),
+            json!({ "is_valid": true, "status": "VALID", "error": null })
+        );
+        assert!(!minimal.wants_enrichment("email_age"));
+
+        let full = ResponseShape::parse(None, Some("full")).unwrap();
+        assert_eq!(full.apply(invalid()), invalid());
+        assert!(full.wants_enrichment("email_age"));
+
+        let age = ResponseShape::parse(Some("is_valid,email_age"), None).unwrap();
+        assert!(age.wants_enrichment("email_age"));
+        assert!(!age.wants_enrichment("domain_registration"));
+    }
+}
@@ -0,0 +1,479 @@
+use crate::account_settings::{self, AccountSettings};
+use crate::auth::{AuthedAccount, AuthenticatedAccount};
+use crate::checks::Check;
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::handlers::validation::custom_lists::{
+    self, AddEntryError, CustomList, MAX_ENTRIES_PER_LIST,
+};
+use crate::organizations;
+use crate::quota_events;
+use crate::sns;
+use crate::validation_history;
+use crate::webhooks::{self, WebhookSecret};
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, delete, get, post, put, web};
+use mongodb::Client as MongoClient;
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct ListEntryRequest {
+    pub entry: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SettingsRequest {
+    /// Role-based detection for requests that don't pass `check_role_based`
+    pub check_role_based: Option<bool>,
+    /// Stages for requests that don't choose their own; syntax is always checked
+    pub checks: Option<Vec<Check>>,
+    /// Webhook of schedules created without one
+    pub webhook_url: Option<String>,
+    /// Days the validation history is kept
+    pub result_retention_days: Option<u64>,
+    /// Treat `user+tag@` as a duplicate of `user@` in bulk validation
+    pub dedupe_tagged_addresses: Option<bool>,
+    /// Share of the monthly quota, between 50 and 99 percent, at which
+    /// `quota.warning` is posted to `webhook_url`; 80 when unset
+    pub quota_warning_percent: Option<u64>,
+    /// SNS topics (ARNs) whose signed notifications to `/api/v1/feedback/sns` are
+    /// recorded as the caller's bounce feedback
+    pub sns_topic_arns: Option<Vec<String>>,
+}
+
+/// Email of the account resolved by the auth middleware
+fn account_email(http_req: &HttpRequest) -> Result<String, actix_web::Error> {
+    http_req
+        .extensions()
+        .get::<AuthenticatedAccount>()
+        .map(|account| account.email.clone())
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("Missing Authorization header"))
+}
+
+/// Owner of the caller's lists: their organization's, shared by all members, or their own
+async fn account_owner(
+    http_req: &HttpRequest,
+    mongo_client: &MongoClient,
+) -> Result<String, actix_web::Error> {
+    let email = account_email(http_req)?;
+    organizations::list_owner(mongo_client, &email)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)
+}
+
+/// Like [`account_owner`], but only organization owners may change shared lists
+async fn writable_owner(
+    http_req: &HttpRequest,
+    mongo_client: &MongoClient,
+) -> Result<String, actix_web::Error> {
+    let email = account_email(http_req)?;
+    let scope = organizations::account_scope(mongo_client, &email)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    if !scope.can_manage_lists() {
+        return Err(actix_web::error::ErrorForbidden(
+            "Only organization owners can change shared lists",
+        ));
+    }
+    Ok(scope.owner)
+}
+
+/// # Get Account Settings
+///
+/// Returns the caller's default validation profile. Fields that were never set are
+/// `null`, and requests then use the server defaults.
+///
+/// ## Responses
+/// - **200 OK**: `{ "tenant_id", "check_role_based", "checks", "webhook_url", "result_retention_days", "dedupe_tagged_addresses", "quota_warning_percent", "sns_topic_arns", "updated_at" }`
+/// - **401 Unauthorized**: Missing or invalid credentials
+#[utoipa::path(
+    get,
+    path = "/api/v1/account/settings",
+    responses(
+        (status = 200, description = "The caller's settings", body = AccountSettings),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope)
+    ),
+    tag = "Account"
+)]
+#[get("/account/settings")]
+pub async fn get_settings(
+    account: AuthedAccount,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<impl Responder, ApiError> {
+    let tenant_id = account.tenant_id();
+    let settings = account_settings::load_settings(&mongo_client, &tenant_id)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?
+        .unwrap_or_else(|| AccountSettings::unset(&tenant_id));
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+/// # Set Account Settings
+///
+/// Stores the caller's default validation profile, replacing the previous one;
+/// omitted fields fall back to the server defaults. Validation requests over REST
+/// and GraphQL that leave out `check_role_based` or `checks` use the stored values,
+/// schedules created without a webhook use `webhook_url`, and the validation
+/// history is kept for `result_retention_days` (records already older are deleted).
+/// With `dedupe_tagged_addresses`, bulk validation counts `user+news@gmail.com` as a
+/// repeat of `user@gmail.com` and validates the base address once for both.
+///
+/// `webhook_url` also receives the quota events, once per billing period each:
+/// `quota.warning` when usage reaches `quota_warning_percent` of the monthly quota,
+/// and `quota.exceeded` when it goes past the quota, as
+/// `{ "event", "period", "used", "limit", "warning_percent", "policy" }`. Organization
+/// quotas are reported to the webhooks of the organization's owners.
+///
+/// `sns_topic_arns` lists the SNS topics the account's SES feedback is published to;
+/// their notifications to `/api/v1/feedback/sns` are recorded for the account once
+/// their signature checks out. A topic belongs to one account only.
+///
+/// ## Responses
+/// - **200 OK**: The stored settings
+/// - **400 Bad Request**: `INVALID_SETTINGS`, e.g. a relative webhook URL or a topic
+///   another account registered
+/// - **401 Unauthorized**: Missing or invalid credentials
+#[utoipa::path(
+    put,
+    path = "/api/v1/account/settings",
+    request_body = SettingsRequest,
+    responses(
+        (status = 200, description = "Settings stored", body = AccountSettings),
+        (status = 400, description = "INVALID_SETTINGS", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope)
+    ),
+    tag = "Account"
+)]
+#[put("/account/settings")]
+pub async fn set_settings(
+    account: AuthedAccount,
+    req: web::Json<SettingsRequest>,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<impl Responder, ApiError> {
+    let req = req.into_inner();
+    let quota_warning_percent = req
+        .quota_warning_percent
+        .map(quota_events::check_warning_percent)
+        .transpose()
+        .map_err(|message| ApiError::validation("INVALID_SETTINGS", message))?;
+    let tenant_id = account.tenant_id();
+    let mut sns_topic_arns = Vec::new();
+    for arn in req.sns_topic_arns.unwrap_or_default() {
+        let arn = sns::check_topic_arn(&arn)
+            .map_err(|message| ApiError::validation("INVALID_SETTINGS", message))?;
+        let owner = account_settings::tenant_for_topic(&mongo_client, &arn)
+            .await
+            .map_err(|e| ApiError::upstream("database", e))?;
+        if owner.is_some_and(|owner| owner != tenant_id) {
+            return Err(ApiError::validation(
+                "INVALID_SETTINGS",
+                format!("{} is registered by another account", arn),
+            ));
+        }
+        if !sns_topic_arns.contains(&arn) {
+            sns_topic_arns.push(arn);
+        }
+    }
+    let settings = AccountSettings {
+        dedupe_tagged_addresses: req.dedupe_tagged_addresses,
+        quota_warning_percent,
+        sns_topic_arns,
+        ..AccountSettings::new(
+            &tenant_id,
+            req.check_role_based,
+            req.checks,
+            req.webhook_url.as_deref(),
+            req.result_retention_days,
+        )
+        .map_err(|message| ApiError::validation("INVALID_SETTINGS", message))?
+    };
+
+    account_settings::save_settings(&mongo_client, &settings)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    if let Some(days) = settings.result_retention_days {
+        validation_history::purge_older_than(&mongo_client, &settings.tenant_id, days)
+            .await
+            .map_err(|e| ApiError::upstream("database", e))?;
+    }
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+/// # Get Webhook Secret
+///
+/// Returns the secret the caller's webhooks (schedule runs, async validation
+/// results, quota events) are signed with, creating it on first use. Each delivery carries
+/// `X-Signature: t=<timestamp>,v1=<hex>`, the HMAC-SHA256 of `<timestamp>.<raw body>`
+/// keyed with this secret; see [`webhooks::verify_signature`] for the checks a
+/// receiver should make.
+///
+/// ## Responses
+/// - **200 OK**: `{ "tenant_id", "secret", "created_at" }`
+/// - **401 Unauthorized**: Missing or invalid credentials
+#[utoipa::path(
+    get,
+    path = "/api/v1/account/webhook-secret",
+    responses(
+        (status = 200, description = "The caller's webhook secret", body = WebhookSecret),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope)
+    ),
+    tag = "Account"
+)]
+#[get("/account/webhook-secret")]
+pub async fn get_webhook_secret(
+    account: AuthedAccount,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<impl Responder, ApiError> {
+    let secret = webhooks::secret_for(&mongo_client, &account.tenant_id())
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    Ok(HttpResponse::Ok().json(secret))
+}
+
+/// # Rotate Webhook Secret
+///
+/// Replaces the caller's webhook secret. Deliveries are signed with the new one
+/// immediately, so receivers should accept both until they have switched.
+///
+/// ## Responses
+/// - **200 OK**: The new secret
+/// - **401 Unauthorized**: Missing or invalid credentials
+#[utoipa::path(
+    post,
+    path = "/api/v1/account/webhook-secret/rotate",
+    responses(
+        (status = 200, description = "The new webhook secret", body = WebhookSecret),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope)
+    ),
+    tag = "Account"
+)]
+#[post("/account/webhook-secret/rotate")]
+pub async fn rotate_webhook_secret(
+    account: AuthedAccount,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<impl Responder, ApiError> {
+    let secret = webhooks::rotate_secret(&mongo_client, &account.tenant_id())
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    Ok(HttpResponse::Ok().json(secret))
+}
+
+/// # List Custom Entries
+///
+/// Returns the caller's `blocklist` or `allowlist` entries. Members of an
+/// organization share its lists.
+///
+/// ## Responses
+/// - **200 OK**: `{ "list": "blocklist", "entries": [{ "entry", "created_at" }] }`
+/// - **401 Unauthorized**: Missing or invalid credentials
+#[utoipa::path(
+    get,
+    path = "/api/v1/account/{list}",
+    params(("list" = CustomList, Path, description = "`blocklist` or `allowlist`")),
+    responses(
+        (status = 200, description = "The list's entries"),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope)
+    ),
+    tag = "Account"
+)]
+#[get("/account/{list}")]
+pub async fn list_custom_entries(
+    path: web::Path<CustomList>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, actix_web::Error> {
+    let owner = account_owner(&http_req, &mongo_client).await?;
+    let list = path.into_inner();
+
+    let entries = custom_lists::list_entries(&mongo_client, &owner, list)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let entries: Vec<_> = entries
+        .iter()
+        .map(|e| json!({ "entry": e.entry, "created_at": e.created_at }))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(json!({ "list": list, "entries": entries })))
+}
+
+/// # Add Custom Entry
+///
+/// Adds a domain (`example.com`, also covering its subdomains) or a full address
+/// to the caller's list. Allowlisted addresses are accepted without further checks;
+/// blocklisted ones fail validation with `CUSTOM_BLOCKED`.
+///
+/// ## Responses
+/// - **201 Created**: `{ "entry", "created_at" }`
+/// - **400 Bad Request**: `INVALID_ENTRY` or `LIST_FULL`
+/// - **401 Unauthorized**: Missing or invalid credentials
+/// - **403 Forbidden**: The caller is an organization member rather than an owner
+#[utoipa::path(
+    post,
+    path = "/api/v1/account/{list}",
+    params(("list" = CustomList, Path, description = "`blocklist` or `allowlist`")),
+    request_body = ListEntryRequest,
+    responses(
+        (status = 201, description = "Entry added"),
+        (status = 400, description = "INVALID_ENTRY or LIST_FULL"),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 403, description = "Only organization owners can change shared lists")
+    ),
+    tag = "Account"
+)]
+#[post("/account/{list}")]
+pub async fn add_custom_entry(
+    path: web::Path<CustomList>,
+    req: web::Json<ListEntryRequest>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, actix_web::Error> {
+    let owner = writable_owner(&http_req, &mongo_client).await?;
+
+    match custom_lists::add_entry(&mongo_client, &owner, path.into_inner(), &req.entry).await {
+        Ok(entry) => Ok(HttpResponse::Created().json(json!({
+            "entry": entry.entry,
+            "created_at": entry.created_at
+        }))),
+        Err(AddEntryError::Invalid(message)) => Ok(HttpResponse::BadRequest().json(json!({
+            "error": "INVALID_ENTRY",
+            "message": message
+        }))),
+        Err(AddEntryError::ListFull) => Ok(HttpResponse::BadRequest().json(json!({
+            "error": "LIST_FULL",
+            "message": format!("A list holds at most {} entries", MAX_ENTRIES_PER_LIST)
+        }))),
+        Err(AddEntryError::Database(e)) => Err(actix_web::error::ErrorInternalServerError(e)),
+    }
+}
+
+/// # Remove Custom Entry
+///
+/// ## Responses
+/// - **204 No Content**: Entry removed
+/// - **404 Not Found**: The list doesn't contain the entry
+/// - **401 Unauthorized**: Missing or invalid credentials
+/// - **403 Forbidden**: The caller is an organization member rather than an owner
+#[utoipa::path(
+    delete,
+    path = "/api/v1/account/{list}/{entry}",
+    params(
+        ("list" = CustomList, Path, description = "`blocklist` or `allowlist`"),
+        ("entry" = String, Path, description = "Domain or address to remove")
+    ),
+    responses(
+        (status = 204, description = "Entry removed"),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 403, description = "Only organization owners can change shared lists"),
+        (status = 404, description = "ENTRY_NOT_FOUND")
+    ),
+    tag = "Account"
+)]
+#[delete("/account/{list}/{entry}")]
+pub async fn remove_custom_entry(
+    path: web::Path<(CustomList, String)>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, actix_web::Error> {
+    let owner = writable_owner(&http_req, &mongo_client).await?;
+    let (list, entry) = path.into_inner();
+
+    let removed = custom_lists::remove_entry(&mongo_client, &owner, list, &entry)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    if removed {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(HttpResponse::NotFound().json(json!({
+            "error": "ENTRY_NOT_FOUND",
+            "message": "The list does not contain this entry"
+        })))
+    }
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    // Ahead of `/account/{list}`, which would otherwise claim `settings`
+    cfg.service(get_settings)
+        .service(set_settings)
+        .service(get_webhook_secret)
+        .service(rotate_webhook_secret)
+        .service(list_custom_entries)
+        .service(add_custom_entry)
+        .service(remove_custom_entry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, http::StatusCode, test};
+
+    #[actix_web::test]
+    async fn test_custom_list_routes_require_account() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/account/blocklist")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/account/allowlist")
+            .set_json(json!({ "entry": "example.com" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/account/settings")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = test::TestRequest::put()
+            .uri("/api/v1/account/settings")
+            .set_json(json!({ "check_role_based": true }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/account/webhook-secret")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/account/webhook-secret/rotate")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn test_unknown_list_is_not_found() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/account/greylist")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
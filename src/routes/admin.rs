@@ -0,0 +1,1365 @@
+use crate::audit_log::{self, AUDIT_PAGE_SIZE, AuditEntry, AuditFilter, ResultClass};
+use crate::auth::{self, AuthedAccount};
+use crate::dns_overrides::{DnsOverrides, DomainOverride};
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::feature_flags::{FeatureFlags, Flag};
+use crate::handlers::validation::dnsmx::{self, override_domain, parse_nameservers};
+use crate::handlers::validation::suppression::{self, SuppressionKind};
+use crate::list_slots::{self, ListSlot, SlotState, SwitchError};
+use crate::local_cache::hit_rate;
+use crate::sync::SyncList;
+use crate::validation_service::ValidationBackends;
+use actix_web::{HttpRequest, HttpResponse, Responder, delete, get, post, put, web};
+use mongodb::Client as MongoClient;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+
+/// The caller of an admin route: an account authenticated by [`auth::Auth`] with a key
+/// holding [`auth::ADMIN_SCOPE`]
+///
+/// 401 without credentials; 403 `INSUFFICIENT_SCOPE` for every other key, full-access
+/// keys included.
+pub fn require_admin(http_req: &HttpRequest) -> Result<AuthedAccount, ApiError> {
+    let account = AuthedAccount::from_http(http_req)?;
+    if !account.has_scope(auth::ADMIN_SCOPE) {
+        return Err(account.insufficient_scope());
+    }
+    Ok(account)
+}
+
+/// # Invalidate Cached Email Result
+///
+/// Removes the cached validation results for one address (with and without the
+/// role-based check) so the next request re-runs the full pipeline.
+///
+/// ## Responses
+/// - **200 OK**: `{ "email": "...", "deleted": 1 }`
+/// - **401/403**: Missing or non-admin API key
+/// - **500 Internal Server Error**: Redis unavailable
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/cache/email/{email}",
+    params(("email" = String, Path, description = "Email address to invalidate")),
+    responses(
+        (status = 200, description = "Cached results removed"),
+        (status = 403, description = "Admin access required")
+    ),
+    tag = "Admin"
+)]
+#[delete("/admin/cache/email/{email}")]
+pub async fn invalidate_email_cache(
+    path: web::Path<String>,
+    backends: web::Data<ValidationBackends>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, actix_web::Error> {
+    require_admin(&http_req)?;
+    let email = path.into_inner();
+
+    let deleted =
+        backends.cache.invalidate_email(&email).await.map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Redis error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "email": email,
+        "deleted": deleted
+    })))
+}
+
+/// # Invalidate Cached Domain Results
+///
+/// Removes the cached DNS/MX verdict for a domain along with every cached result for
+/// addresses at that domain, e.g. after a customer fixes their DNS.
+///
+/// ## Responses
+/// - **200 OK**: `{ "domain": "...", "deleted": 12 }`
+/// - **401/403**: Missing or non-admin API key
+/// - **500 Internal Server Error**: Redis unavailable
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/cache/domain/{domain}",
+    params(("domain" = String, Path, description = "Domain to invalidate")),
+    responses(
+        (status = 200, description = "Cached results removed"),
+        (status = 403, description = "Admin access required")
+    ),
+    tag = "Admin"
+)]
+#[delete("/admin/cache/domain/{domain}")]
+pub async fn invalidate_domain_cache(
+    path: web::Path<String>,
+    backends: web::Data<ValidationBackends>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, actix_web::Error> {
+    require_admin(&http_req)?;
+    let domain = path.into_inner();
+    let redis_error = |e: redis::RedisError| {
+        actix_web::error::ErrorInternalServerError(format!("Redis error: {}", e))
+    };
+
+    let dns_deleted = backends
+        .dns
+        .invalidate_dns_validation(&domain)
+        .await
+        .map_err(redis_error)?;
+    let results_deleted = backends
+        .cache
+        .invalidate_domain(&domain)
+        .await
+        .map_err(redis_error)?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "domain": domain,
+        "deleted": dns_deleted + results_deleted
+    })))
+}
+
+/// # Cache Statistics
+///
+/// Reports hit/miss counters per cache tier, the number of cached keys and the usage
+/// of the shared Redis connection pool.
+///
+/// The top-level `hits`, `misses` and `hit_rate` are the Redis tier of the validation
+/// cache, across instances; `dns` is the Redis tier of the DNS verdict cache. `memory`
+/// holds the in-process tiers of the instance that answered, `null` when disabled.
+/// Lookups answered in process never reach Redis, so they don't count there.
+///
+/// ## Example Response
+/// ```json
+/// {
+///   "hits": 120, "misses": 30, "hit_rate": 0.8, "validation_keys": 95, "dns_keys": 40,
+///   "dns": { "hits": 300, "misses": 40, "hit_rate": 0.88 },
+///   "memory": {
+///     "validation": { "entries": 80, "capacity": 10000, "hits": 500, "misses": 150, "hit_rate": 0.77 },
+///     "dns": { "entries": 35, "capacity": 10000, "hits": 2400, "misses": 340, "hit_rate": 0.88 }
+///   },
+///   "redis_pool": { "max_size": 32, "size": 6, "available": 5, "waiting": 0, "checkout_errors": 0 }
+/// }
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/cache/stats",
+    responses(
+        (status = 200, description = "Cache statistics"),
+        (status = 403, description = "Admin access required")
+    ),
+    tag = "Admin"
+)]
+#[get("/admin/cache/stats")]
+pub async fn cache_stats(
+    backends: web::Data<ValidationBackends>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, actix_web::Error> {
+    require_admin(&http_req)?;
+    let redis_error = |e: redis::RedisError| {
+        actix_web::error::ErrorInternalServerError(format!("Redis error: {}", e))
+    };
+
+    let stats = backends.cache.stats().await.map_err(redis_error)?;
+    let dns_keys = backends.dns.dns_key_count().await.map_err(redis_error)?;
+    let (dns_hits, dns_misses) = backends.dns.dns_counters().await.map_err(redis_error)?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "hits": stats.hits,
+        "misses": stats.misses,
+        "hit_rate": hit_rate(stats.hits, stats.misses),
+        "validation_keys": stats.validation_keys,
+        "dns_keys": dns_keys,
+        "dns": {
+            "hits": dns_hits,
+            "misses": dns_misses,
+            "hit_rate": hit_rate(dns_hits, dns_misses)
+        },
+        "memory": {
+            "validation": backends.cache.local_stats(),
+            "dns": backends.dns.dns_local_stats()
+        },
+        "redis_pool": backends.dns.pool_metrics()
+    })))
+}
+
+/// # Canary Comparison Stats
+///
+/// Reports how the canary share of traffic (`CANARY_DNS_ASYNC_PERCENT`) compares
+/// with the legacy pipeline: requests run side by side, verdict mismatches and
+/// average latency of each implementation.
+///
+/// ## Responses
+/// - **200 OK**: `{ "stages": [{ "stage", "percent", "requests", "mismatches", "mismatch_rate", "avg_legacy_ms", "avg_canary_ms" }] }`
+/// - **401/403**: Missing or non-admin API key
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/canary/stats",
+    responses(
+        (status = 200, description = "Canary comparison counters"),
+        (status = 403, description = "Admin access required")
+    ),
+    tag = "Admin"
+)]
+#[get("/admin/canary/stats")]
+pub async fn canary_stats(
+    backends: web::Data<ValidationBackends>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, actix_web::Error> {
+    require_admin(&http_req)?;
+
+    let dns_async =
+        backends.dns.canary.dns_async_stats().await.map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Redis error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(json!({ "stages": [dns_async] })))
+}
+
+/// # Reset Canary Stats
+///
+/// Clears the canary comparison counters, e.g. after changing the canary percentage.
+///
+/// ## Responses
+/// - **204 No Content**: Counters cleared
+/// - **401/403**: Missing or non-admin API key
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/canary/stats",
+    responses(
+        (status = 204, description = "Counters cleared"),
+        (status = 403, description = "Admin access required")
+    ),
+    tag = "Admin"
+)]
+#[delete("/admin/canary/stats")]
+pub async fn reset_canary_stats(
+    backends: web::Data<ValidationBackends>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, actix_web::Error> {
+    require_admin(&http_req)?;
+
+    backends
+        .dns
+        .canary
+        .reset_dns_async_stats()
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Redis error: {}", e)))?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+fn slot_status(list: SyncList, state: &SlotState, sizes: &[(ListSlot, u64)]) -> serde_json::Value {
+    let slots: serde_json::Map<String, serde_json::Value> = sizes
+        .iter()
+        .map(|(slot, entries)| {
+            (
+                slot.as_str().to_string(),
+                json!({
+                    "collection": list_slots::slot_collection(list, *slot),
+                    "entries": entries
+                }),
+            )
+        })
+        .collect();
+
+    json!({
+        "list": list,
+        "active": state.active,
+        "previous": state.previous,
+        "switched_at": state.switched_at,
+        "slots": slots
+    })
+}
+
+/// Responds with the list's slot status after a switch, or the reason it was refused
+async fn switch_response(
+    mongo_client: &MongoClient,
+    list: SyncList,
+    result: Result<SlotState, SwitchError>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let state = match result {
+        Ok(state) => state,
+        Err(SwitchError::EmptySlot(slot)) => {
+            return Ok(HttpResponse::Conflict().json(json!({
+                "error": "SLOT_EMPTY",
+                "message": format!("The {} slot has no entries; load the list before activating it", slot.as_str())
+            })));
+        }
+        Err(SwitchError::NoPreviousSlot) => {
+            return Ok(HttpResponse::Conflict().json(json!({
+                "error": "NO_PREVIOUS_VERSION",
+                "message": "The list has not been switched yet, so there is nothing to roll back to"
+            })));
+        }
+        Err(SwitchError::Conflict) => {
+            return Ok(HttpResponse::Conflict().json(json!({
+                "error": "SWITCH_CONFLICT",
+                "message": "The list was switched concurrently; check its status and retry"
+            })));
+        }
+        Err(SwitchError::Database(e)) => {
+            return Err(actix_web::error::ErrorInternalServerError(format!(
+                "Database error: {}",
+                e
+            )));
+        }
+    };
+
+    let sizes = list_slots::slot_sizes(mongo_client, list)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    Ok(HttpResponse::Ok().json(slot_status(list, &state, &sizes)))
+}
+
+/// # List Slot Status
+///
+/// Shows which of the two list versions (`blue`/`green`) serves lookups and how
+/// many entries each holds, so a staged upstream update can be checked first.
+///
+/// ## Responses
+/// - **200 OK**: `{ "list", "active", "previous", "switched_at", "slots": { "blue": { "collection", "entries" }, "green": {...} } }`
+/// - **401/403**: Missing or non-admin API key
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/lists/{list}",
+    params(("list" = String, Path, description = "`disposable` or `role_based`")),
+    responses(
+        (status = 200, description = "Slot status of the list"),
+        (status = 403, description = "Admin access required")
+    ),
+    tag = "Admin"
+)]
+#[get("/admin/lists/{list}")]
+pub async fn list_slot_status(
+    path: web::Path<SyncList>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, actix_web::Error> {
+    require_admin(&http_req)?;
+    let list = path.into_inner();
+
+    let db_error = |e: mongodb::error::Error| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    };
+    let state = list_slots::slot_state(&mongo_client, list)
+        .await
+        .map_err(db_error)?;
+    let sizes = list_slots::slot_sizes(&mongo_client, list)
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(slot_status(list, &state, &sizes)))
+}
+
+/// # Activate List Slot
+///
+/// Atomically switches lookups for a list to the given slot. The slot must already
+/// hold entries; the slot it replaces is kept for rollback.
+///
+/// ## Responses
+/// - **200 OK**: Slot status after the switch
+/// - **409 Conflict**: `SLOT_EMPTY` or `SWITCH_CONFLICT`
+/// - **401/403**: Missing or non-admin API key
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/lists/{list}/activate/{slot}",
+    params(
+        ("list" = String, Path, description = "`disposable` or `role_based`"),
+        ("slot" = String, Path, description = "`blue` or `green`")
+    ),
+    responses(
+        (status = 200, description = "Slot activated"),
+        (status = 409, description = "Slot empty or switched concurrently"),
+        (status = 403, description = "Admin access required")
+    ),
+    tag = "Admin"
+)]
+#[post("/admin/lists/{list}/activate/{slot}")]
+pub async fn activate_list_slot(
+    path: web::Path<(SyncList, ListSlot)>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, actix_web::Error> {
+    require_admin(&http_req)?;
+    let (list, slot) = path.into_inner();
+
+    let result = list_slots::activate_slot(&mongo_client, list, slot).await;
+    switch_response(&mongo_client, list, result).await
+}
+
+/// # Roll Back List Slot
+///
+/// Switches a list back to the slot that served before the last activation.
+///
+/// ## Responses
+/// - **200 OK**: Slot status after the rollback
+/// - **409 Conflict**: `NO_PREVIOUS_VERSION`, `SLOT_EMPTY` or `SWITCH_CONFLICT`
+/// - **401/403**: Missing or non-admin API key
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/lists/{list}/rollback",
+    params(("list" = String, Path, description = "`disposable` or `role_based`")),
+    responses(
+        (status = 200, description = "Previous slot restored"),
+        (status = 409, description = "Nothing to roll back to"),
+        (status = 403, description = "Admin access required")
+    ),
+    tag = "Admin"
+)]
+#[post("/admin/lists/{list}/rollback")]
+pub async fn rollback_list_slot(
+    path: web::Path<SyncList>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, actix_web::Error> {
+    require_admin(&http_req)?;
+    let list = path.into_inner();
+
+    let result = list_slots::rollback_slot(&mongo_client, list).await;
+    switch_response(&mongo_client, list, result).await
+}
+
+/// Body of a suppression import
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SuppressionImport {
+    pub kind: SuppressionKind,
+    /// Feed or customer the entries come from
+    pub source: Option<String>,
+    /// SHA-256 hashes (hex) of normalized addresses
+    #[serde(default)]
+    pub hashes: Vec<String>,
+    /// Plain addresses; they are hashed and never stored
+    #[serde(default)]
+    pub emails: Vec<String>,
+}
+
+/// Hashes of an import, or the field of its first unusable entry
+fn import_hashes(import: &SuppressionImport) -> Result<Vec<String>, String> {
+    let mut hashes = Vec::with_capacity(import.hashes.len() + import.emails.len());
+    for (i, hash) in import.hashes.iter().enumerate() {
+        hashes.push(suppression::normalize_hash(hash).ok_or_else(|| format!("hashes[{}]", i))?);
+    }
+    for (i, email) in import.emails.iter().enumerate() {
+        if !email.contains('@') {
+            return Err(format!("emails[{}]", i));
+        }
+        hashes.push(suppression::address_hash(email));
+    }
+    Ok(hashes)
+}
+
+/// # Import Suppression Entries
+///
+/// Adds known spamtraps, spam complainers or previous bouncers. Addresses may be sent
+/// as SHA-256 hashes of their normalized form or in plain text, which is hashed on
+/// arrival. Listed addresses fail validation with `KNOWN_TRAP` or `PREVIOUS_BOUNCER`
+/// once `SUPPRESSION_LOOKUP_ENABLED=true`; re-importing a hash replaces its kind.
+///
+/// Cached results of plain addresses are invalidated right away; hashed entries apply
+/// once the address's cached result expires.
+///
+/// ## Example Request
+/// ```json
+/// { "kind": "spamtrap", "source": "acme-feed", "hashes": ["b4c9a289..."], "emails": ["trap@example.com"] }
+/// ```
+///
+/// ## Responses
+/// - **200 OK**: `{ "kind": "spamtrap", "imported": 2 }`
+/// - **400 Bad Request**: `INVALID_SUPPRESSION_ENTRY`, an entry is malformed or there are none
+/// - **401/403**: Missing or non-admin API key
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/suppression",
+    request_body = SuppressionImport,
+    responses(
+        (status = 200, description = "Entries imported"),
+        (status = 400, description = "INVALID_SUPPRESSION_ENTRY: malformed or missing entries", body = ErrorEnvelope),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[post("/admin/suppression")]
+pub async fn import_suppression(
+    body: web::Json<SuppressionImport>,
+    mongo_client: web::Data<MongoClient>,
+    backends: web::Data<ValidationBackends>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+    let import = body.into_inner();
+
+    let hashes = match import_hashes(&import) {
+        Ok(hashes) if hashes.is_empty() => {
+            return Err(ApiError::validation(
+                "INVALID_SUPPRESSION_ENTRY",
+                "Provide at least one entry in hashes or emails",
+            ));
+        }
+        Ok(hashes) => hashes,
+        Err(field) => {
+            return Err(ApiError::validation(
+                "INVALID_SUPPRESSION_ENTRY",
+                format!("{} is not a SHA-256 hash or an email address", field),
+            )
+            .with_details(json!({ "field": field })));
+        }
+    };
+
+    let imported = suppression::import(&mongo_client, import.kind, import.source, &hashes)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    for email in &import.emails {
+        // A stale cache entry only delays the verdict, so failures aren't fatal
+        let _ = backends.cache.invalidate_email(email).await;
+    }
+
+    Ok(HttpResponse::Ok().json(json!({
+        "kind": import.kind,
+        "imported": imported
+    })))
+}
+
+/// # Remove Suppression Entry
+///
+/// Deletes the entry for one address hash, e.g. after a trap feed retracts it.
+///
+/// ## Responses
+/// - **200 OK**: `{ "hash": "...", "deleted": true }`
+/// - **400 Bad Request**: `INVALID_SUPPRESSION_ENTRY`, not a SHA-256 hash
+/// - **401/403**: Missing or non-admin API key
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/suppression/{hash}",
+    params(("hash" = String, Path, description = "SHA-256 (hex) of the normalized address")),
+    responses(
+        (status = 200, description = "Entry removed if present"),
+        (status = 400, description = "INVALID_SUPPRESSION_ENTRY: not a SHA-256 hash", body = ErrorEnvelope),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[delete("/admin/suppression/{hash}")]
+pub async fn remove_suppression(
+    path: web::Path<String>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+    let Some(hash) = suppression::normalize_hash(&path.into_inner()) else {
+        return Err(ApiError::validation(
+            "INVALID_SUPPRESSION_ENTRY",
+            "Path must be a SHA-256 hash in hex",
+        ));
+    };
+
+    let deleted = suppression::remove(&mongo_client, &hash)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "hash": hash,
+        "deleted": deleted
+    })))
+}
+
+/// # Suppression Statistics
+///
+/// Reports how many entries of each kind are stored.
+///
+/// ## Example Response
+/// ```json
+/// { "enabled": true, "spamtrap": 1200, "complainer": 340, "bouncer": 98000 }
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/suppression/stats",
+    responses(
+        (status = 200, description = "Entry counts per kind"),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[get("/admin/suppression/stats")]
+pub async fn suppression_stats(
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+
+    let counts = suppression::counts(&mongo_client)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    let mut body = json!({ "enabled": suppression::is_enabled() });
+    for (kind, count) in counts {
+        body[kind.as_str()] = json!(count);
+    }
+    Ok(HttpResponse::Ok().json(body))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct FlagUpdate {
+    /// New value; `null` clears the stored value so the flag's default applies
+    pub enabled: Option<bool>,
+}
+
+/// # Feature Flags
+///
+/// Lists every feature flag with its current value and where it comes from: a
+/// `FEATURE_*` environment variable, Redis, or the flag's default.
+///
+/// ## Example Response
+/// ```json
+/// { "flags": [{ "flag": "maintenance_mode", "enabled": false, "source": "default" }] }
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/flags",
+    responses(
+        (status = 200, description = "Every flag with its value and source"),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[get("/admin/flags")]
+pub async fn list_flags(
+    flags: web::Data<FeatureFlags>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+    Ok(HttpResponse::Ok().json(json!({ "flags": flags.states().await })))
+}
+
+/// # Set Feature Flag
+///
+/// Switches `maintenance_mode`, `bulk_enabled` or `registration_open` for every
+/// instance sharing the Redis server, without a redeploy; instances pick the change
+/// up within seconds. A flag set through its `FEATURE_*` environment variable keeps
+/// that value.
+///
+/// ## Responses
+/// - **200 OK**: The flag's resulting state, `{ "flag", "enabled", "source" }`
+/// - **400 Bad Request**: `INVALID_FLAG`, unknown flag
+/// - **401/403**: Missing or non-admin API key
+/// - **503 Service Unavailable**: Redis unavailable
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/flags/{flag}",
+    params(("flag" = String, Path, description = "maintenance_mode, bulk_enabled or registration_open")),
+    request_body = FlagUpdate,
+    responses(
+        (status = 200, description = "Flag stored"),
+        (status = 400, description = "INVALID_FLAG: unknown flag", body = ErrorEnvelope),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope),
+        (status = 503, description = "Redis unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[put("/admin/flags/{flag}")]
+pub async fn set_flag(
+    path: web::Path<String>,
+    body: web::Json<FlagUpdate>,
+    flags: web::Data<FeatureFlags>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+    let Some(flag) = Flag::parse(&path) else {
+        return Err(ApiError::validation(
+            "INVALID_FLAG",
+            "Unknown flag; use maintenance_mode, bulk_enabled or registration_open",
+        ));
+    };
+
+    flags
+        .set(flag, body.enabled)
+        .await
+        .map_err(|e| ApiError::upstream("cache", e))?;
+    Ok(HttpResponse::Ok().json(flags.state(flag).await))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DnsOverrideUpdate {
+    /// IP addresses with optional ports (53 when left out), e.g. `10.1.0.53:5353`
+    pub nameservers: Vec<String>,
+}
+
+fn invalid_dns_override(message: impl Into<String>) -> ApiError {
+    ApiError::validation("INVALID_DNS_OVERRIDE", message)
+}
+
+/// # DNS Overrides
+///
+/// Lists where DNS checks send their lookups: `nameservers` replacing the provider's
+/// (`DNS_NAMESERVERS`, empty when the provider is used), and the domains whose lookups,
+/// subdomains included, go to their own nameservers, from `DNS_DOMAIN_OVERRIDES` or
+/// this API.
+///
+/// ## Example Response
+/// ```json
+/// {
+///   "nameservers": [],
+///   "overrides": [{ "domain": "corp.internal", "nameservers": ["10.1.0.53:53"], "source": "redis" }]
+/// }
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/dns/overrides",
+    responses(
+        (status = 200, description = "Custom nameservers and domain overrides"),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[get("/admin/dns/overrides")]
+pub async fn list_dns_overrides(
+    dns_overrides: web::Data<DnsOverrides>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+    let overrides = dns_overrides
+        .list()
+        .await
+        .map_err(|e| ApiError::upstream("cache", e))?;
+    let nameservers: Vec<String> = dnsmx::settings()
+        .nameservers
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    Ok(HttpResponse::Ok().json(json!({
+        "nameservers": nameservers,
+        "overrides": overrides
+    })))
+}
+
+/// # Set DNS Override
+///
+/// Sends the DNS lookups of a domain and its subdomains to the given nameservers over
+/// classic DNS, e.g. an internal resolver for `corp.internal`, on every instance
+/// sharing the Redis server within seconds. A domain overridden through
+/// `DNS_DOMAIN_OVERRIDES` keeps those nameservers. The domain's cached DNS verdict is
+/// dropped; those of its subdomains expire on their own or through
+/// `DELETE /api/v1/admin/cache/domain/{domain}`.
+///
+/// ## Responses
+/// - **200 OK**: The override in force, `{ "domain", "nameservers", "source" }`
+/// - **400 Bad Request**: `INVALID_DNS_OVERRIDE`
+/// - **401/403**: Missing or non-admin API key
+/// - **503 Service Unavailable**: Redis unavailable
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/dns/overrides/{domain}",
+    params(("domain" = String, Path, description = "Domain whose lookups are overridden")),
+    request_body = DnsOverrideUpdate,
+    responses(
+        (status = 200, description = "Override stored", body = DomainOverride),
+        (status = 400, description = "INVALID_DNS_OVERRIDE", body = ErrorEnvelope),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope),
+        (status = 503, description = "Redis unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[put("/admin/dns/overrides/{domain}")]
+pub async fn set_dns_override(
+    path: web::Path<String>,
+    body: web::Json<DnsOverrideUpdate>,
+    dns_overrides: web::Data<DnsOverrides>,
+    backends: web::Data<ValidationBackends>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+    let Some(domain) = override_domain(&path) else {
+        return Err(invalid_dns_override(
+            "The path must end with a valid domain",
+        ));
+    };
+    let nameservers = match parse_nameservers(&body.nameservers.join(",")) {
+        Ok(nameservers) => nameservers,
+        Err(message) => return Err(invalid_dns_override(message)),
+    };
+
+    let redis_error = |e: redis::RedisError| ApiError::upstream("cache", e);
+    dns_overrides
+        .set(&domain, Some(&nameservers))
+        .await
+        .map_err(redis_error)?;
+    backends
+        .dns
+        .invalidate_dns_validation(&domain)
+        .await
+        .map_err(redis_error)?;
+    let current = dns_overrides
+        .list()
+        .await
+        .map_err(redis_error)?
+        .into_iter()
+        .find(|current| current.domain == domain);
+    Ok(HttpResponse::Ok().json(current))
+}
+
+/// # Remove DNS Override
+///
+/// Sends a domain's lookups back to the default nameservers. Overrides from
+/// `DNS_DOMAIN_OVERRIDES` stay in force.
+///
+/// ## Example Response
+/// ```json
+/// { "domain": "corp.internal", "deleted": true }
+/// ```
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/dns/overrides/{domain}",
+    params(("domain" = String, Path, description = "Domain whose override is removed")),
+    responses(
+        (status = 200, description = "Whether an override was stored"),
+        (status = 400, description = "INVALID_DNS_OVERRIDE", body = ErrorEnvelope),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope),
+        (status = 503, description = "Redis unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[delete("/admin/dns/overrides/{domain}")]
+pub async fn remove_dns_override(
+    path: web::Path<String>,
+    dns_overrides: web::Data<DnsOverrides>,
+    backends: web::Data<ValidationBackends>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+    let Some(domain) = override_domain(&path) else {
+        return Err(invalid_dns_override(
+            "The path must end with a valid domain",
+        ));
+    };
+
+    let redis_error = |e: redis::RedisError| ApiError::upstream("cache", e);
+    let deleted = dns_overrides
+        .set(&domain, None)
+        .await
+        .map_err(redis_error)?;
+    backends
+        .dns
+        .invalidate_dns_validation(&domain)
+        .await
+        .map_err(redis_error)?;
+    Ok(HttpResponse::Ok().json(json!({
+        "domain": domain,
+        "deleted": deleted
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct AuditQuery {
+    pub account: Option<String>,
+    /// Route pattern, e.g. `/api/v1/validation/{email}`
+    pub endpoint: Option<String>,
+    /// `success`, `client_error` or `server_error`
+    pub result: Option<String>,
+    pub ip: Option<String>,
+    /// Unix timestamp; requests before it are left out
+    pub from: Option<i64>,
+    /// Unix timestamp; requests after it are left out
+    pub to: Option<i64>,
+    /// 1-based page number
+    pub page: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    pub page: usize,
+    pub per_page: usize,
+}
+
+/// # Audit Log
+///
+/// Every authenticated request, newest first, [`AUDIT_PAGE_SIZE`] per page: who made
+/// it, the endpoint's route pattern, a SHA-256 of the input, how it ended, how long it
+/// took and the client address. Records live in a capped collection of `AUDIT_LOG_MAX_BYTES`
+/// (default 1 GiB) and are never changed; the oldest make room once it is full.
+///
+/// ## Query Parameters
+/// - `account`, `endpoint`, `ip`: exact matches
+/// - `result`: `success`, `client_error` or `server_error`
+/// - `from`, `to`: Unix timestamps bounding the request time, both inclusive
+/// - `page`: 1-based page number (default 1)
+///
+/// ## Responses
+/// - **200 OK**: `{ "entries": [{ "account", "key_id", "method", "endpoint", "input_hash", "status", "result", "latency_ms", "ip", "at" }], "page", "per_page" }`
+/// - **400 Bad Request**: `INVALID_PAGE`, `INVALID_RANGE` or `INVALID_RESULT`
+/// - **401/403**: Missing or non-admin API key
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/audit",
+    params(
+        ("account" = Option<String>, Query, description = "Only requests of this account"),
+        ("endpoint" = Option<String>, Query, description = "Only requests to this route pattern"),
+        ("result" = Option<String>, Query, description = "success, client_error or server_error"),
+        ("ip" = Option<String>, Query, description = "Only requests from this client address"),
+        ("from" = Option<i64>, Query, description = "Unix timestamp the range starts at"),
+        ("to" = Option<i64>, Query, description = "Unix timestamp the range ends at"),
+        ("page" = Option<usize>, Query, description = "1-based page number")
+    ),
+    responses(
+        (status = 200, description = "One page of the audit log", body = AuditPage),
+        (status = 400, description = "INVALID_PAGE, INVALID_RANGE or INVALID_RESULT", body = ErrorEnvelope),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[get("/admin/audit")]
+pub async fn list_audit_log(
+    query: web::Query<AuditQuery>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+    let query = query.into_inner();
+    let page = query.page.unwrap_or(1);
+    if page == 0 {
+        return Err(ApiError::validation("INVALID_PAGE", "page starts at 1"));
+    }
+    let result = match query.result.as_deref().map(ResultClass::parse) {
+        Some(None) => {
+            return Err(ApiError::validation(
+                "INVALID_RESULT",
+                "result must be one of success, client_error, server_error",
+            ));
+        }
+        Some(result) => result,
+        None => None,
+    };
+    let filter = AuditFilter {
+        account: query.account,
+        endpoint: query.endpoint,
+        result,
+        ip: query.ip,
+        from: query.from,
+        to: query.to,
+    };
+    if let Err(message) = filter.check() {
+        return Err(ApiError::validation("INVALID_RANGE", message));
+    }
+
+    let entries = audit_log::query_audit(&mongo_client, &filter, page)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    Ok(HttpResponse::Ok().json(AuditPage {
+        entries,
+        page,
+        per_page: AUDIT_PAGE_SIZE,
+    }))
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(invalidate_email_cache)
+        .service(invalidate_domain_cache)
+        .service(cache_stats)
+        .service(canary_stats)
+        .service(reset_canary_stats)
+        .service(list_slot_status)
+        .service(activate_list_slot)
+        .service(rollback_list_slot)
+        .service(import_suppression)
+        .service(suppression_stats)
+        .service(remove_suppression)
+        .service(list_flags)
+        .service(set_flag)
+        .service(list_dns_overrides)
+        .service(set_dns_override)
+        .service(remove_dns_override)
+        .service(list_audit_log);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthenticatedAccount;
+    use crate::stores::MemoryStore;
+    use actix_web::dev::Service;
+    use actix_web::{App, HttpMessage, ResponseError, http::StatusCode, test as actix_test};
+    use std::sync::Arc;
+
+    #[actix_web::test]
+    async fn test_admin_routes_require_authorization() {
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(ValidationBackends::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .wrap_fn(|req, srv| {
+                    crate::auth::accept_any_bearer(&req);
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::get()
+            .uri("/api/v1/admin/cache/stats")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = actix_test::TestRequest::get()
+            .uri("/api/v1/admin/canary/stats")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_require_admin_needs_admin_scope() {
+        let check = |account: Option<AuthenticatedAccount>| {
+            let req = actix_test::TestRequest::default().to_http_request();
+            if let Some(account) = account {
+                req.extensions_mut().insert(account);
+            }
+            require_admin(&req).map(|_| ()).map_err(|e| e.status_code())
+        };
+        let with_scopes = |scopes: &[&str]| {
+            Some(AuthenticatedAccount {
+                email: "ops@example.com".to_string(),
+                scopes: scopes.iter().map(|s| s.to_string()).collect(),
+                ..AuthenticatedAccount::default()
+            })
+        };
+
+        assert_eq!(check(None), Err(StatusCode::UNAUTHORIZED));
+        // Full-access keys don't imply the admin scope
+        assert_eq!(check(with_scopes(&[])), Err(StatusCode::FORBIDDEN));
+        assert_eq!(
+            check(with_scopes(&["validate"])),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(check(with_scopes(&["validate", "admin"])), Ok(()));
+    }
+
+    #[actix_web::test]
+    async fn test_admin_routes_reject_regular_keys() {
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(ValidationBackends::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .wrap_fn(|req, srv| {
+                    crate::auth::accept_any_bearer(&req);
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::delete()
+            .uri("/api/v1/admin/cache/domain/example.com")
+            .insert_header(("Authorization", "Bearer not-the-admin-key"))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    async fn test_list_slot_routes_require_admin() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .wrap_fn(|req, srv| {
+                    crate::auth::accept_any_bearer(&req);
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::post()
+            .uri("/api/v1/admin/lists/disposable/activate/green")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = actix_test::TestRequest::post()
+            .uri("/api/v1/admin/lists/disposable/rollback")
+            .insert_header(("Authorization", "Bearer not-the-admin-key"))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    async fn test_suppression_routes_require_admin() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .app_data(web::Data::new(ValidationBackends::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .wrap_fn(|req, srv| {
+                    crate::auth::accept_any_bearer(&req);
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::post()
+            .uri("/api/v1/admin/suppression")
+            .set_json(json!({ "kind": "spamtrap", "emails": ["trap@example.com"] }))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = actix_test::TestRequest::get()
+            .uri("/api/v1/admin/suppression/stats")
+            .insert_header(("Authorization", "Bearer not-the-admin-key"))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    async fn test_audit_log_requires_admin() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .wrap_fn(|req, srv| {
+                    crate::auth::accept_any_bearer(&req);
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::get()
+            .uri("/api/v1/admin/audit?result=server_error")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = actix_test::TestRequest::get()
+            .uri("/api/v1/admin/audit")
+            .insert_header(("Authorization", "Bearer not-the-admin-key"))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    async fn test_dns_override_routes_require_admin() {
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(ValidationBackends::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .app_data(web::Data::new(DnsOverrides::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .wrap_fn(|req, srv| {
+                    crate::auth::accept_any_bearer(&req);
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::get()
+            .uri("/api/v1/admin/dns/overrides")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = actix_test::TestRequest::put()
+            .uri("/api/v1/admin/dns/overrides/corp.internal")
+            .insert_header(("Authorization", "Bearer not-the-admin-key"))
+            .set_json(json!({ "nameservers": ["10.1.0.53"] }))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        let req = actix_test::TestRequest::delete()
+            .uri("/api/v1/admin/dns/overrides/corp.internal")
+            .insert_header(("Authorization", "Bearer not-the-admin-key"))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    /// Authenticates every request with an admin-scoped key
+    fn as_admin(req: &actix_web::dev::ServiceRequest) {
+        req.extensions_mut().insert(AuthenticatedAccount {
+            email: "ops@example.com".to_string(),
+            scopes: vec![auth::ADMIN_SCOPE.to_string()],
+            ..AuthenticatedAccount::default()
+        });
+    }
+
+    #[actix_web::test]
+    async fn test_suppression_errors_use_envelope() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .app_data(web::Data::new(ValidationBackends::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .wrap_fn(|req, srv| {
+                    as_admin(&req);
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::post()
+            .uri("/api/v1/admin/suppression")
+            .set_json(json!({ "kind": "spamtrap", "emails": ["trap@example.com", "no-at-sign"] }))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], "INVALID_SUPPRESSION_ENTRY");
+        assert_eq!(body["error"]["details"]["field"], "emails[1]");
+
+        let req = actix_test::TestRequest::delete()
+            .uri("/api/v1/admin/suppression/not-a-hash")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], "INVALID_SUPPRESSION_ENTRY");
+    }
+
+    #[actix_web::test]
+    async fn test_audit_log_errors_use_envelope() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .wrap_fn(|req, srv| {
+                    as_admin(&req);
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        for (uri, code) in [
+            ("/api/v1/admin/audit?page=0", "INVALID_PAGE"),
+            ("/api/v1/admin/audit?result=maybe", "INVALID_RESULT"),
+            ("/api/v1/admin/audit?from=20&to=10", "INVALID_RANGE"),
+        ] {
+            let req = actix_test::TestRequest::get().uri(uri).to_request();
+            let resp = actix_test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", uri);
+            let body: serde_json::Value = actix_test::read_body_json(resp).await;
+            assert_eq!(body["error"]["code"], code, "{}", uri);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_unknown_flag_uses_envelope() {
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(FeatureFlags::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .wrap_fn(|req, srv| {
+                    as_admin(&req);
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::put()
+            .uri("/api/v1/admin/flags/dark_mode")
+            .set_json(json!({ "enabled": true }))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], "INVALID_FLAG");
+
+        let req = actix_test::TestRequest::put()
+            .uri("/api/v1/admin/flags/bulk_enabled")
+            .set_json(json!({ "enabled": false }))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_dns_override_uses_envelope() {
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(ValidationBackends::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .app_data(web::Data::new(DnsOverrides::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .wrap_fn(|req, srv| {
+                    as_admin(&req);
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::put()
+            .uri("/api/v1/admin/dns/overrides/corp.internal")
+            .set_json(json!({ "nameservers": ["not-an-ip"] }))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], "INVALID_DNS_OVERRIDE");
+    }
+
+    #[test]
+    fn test_import_hashes() {
+        let hash = suppression::address_hash("trap@example.com");
+        let import = SuppressionImport {
+            kind: SuppressionKind::Spamtrap,
+            source: None,
+            hashes: vec![hash.to_uppercase()],
+            emails: vec!["trap@Example.com".to_string()],
+        };
+        assert_eq!(import_hashes(&import), Ok(vec![hash.clone(), hash.clone()]));
+
+        let import = SuppressionImport {
+            kind: SuppressionKind::Bouncer,
+            source: None,
+            hashes: vec![hash, "abc".to_string()],
+            emails: Vec::new(),
+        };
+        assert_eq!(import_hashes(&import), Err("hashes[1]".to_string()));
+    }
+
+    #[test]
+    fn test_slot_status_body() {
+        let state = SlotState {
+            active: ListSlot::Green,
+            previous: Some(ListSlot::Blue),
+            switched_at: Some(1_700_000_000),
+        };
+        let body = slot_status(
+            SyncList::RoleBased,
+            &state,
+            &[(ListSlot::Blue, 10), (ListSlot::Green, 12)],
+        );
+
+        assert_eq!(body["active"], "green");
+        assert_eq!(body["previous"], "blue");
+        assert_eq!(body["slots"]["green"]["entries"], 12);
+        assert_eq!(body["slots"]["blue"]["collection"], "role_based_emails");
+    }
+}
@@ -1,52 +1,177 @@
-use crate::auth::{User, generate_api_key};
-use actix_web::{HttpResponse, Result, web};
-use bcrypt::{DEFAULT_COST, hash};
-use mongodb::{Client, Collection, bson::doc};
+use crate::auth::{self, SessionTokens, register_account};
+use crate::email_verification;
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::feature_flags::{FeatureFlags, Flag};
+use crate::organizations;
+use actix_web::{HttpResponse, web};
+use mongodb::Client;
 use serde::{Deserialize, Serialize};
-use std::env;
+use utoipa::ToSchema;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct RegisterRequest {
     pub email: String,
     pub password: String,
+    /// Name of an organization to create with the new account as its owner
+    #[serde(default)]
+    pub organization: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ApiKeyResponse {
     pub api_key: String,
 }
 
+/// Creates an account and returns its first API key
+///
+/// The account starts inactive: a verification link is sent to the address, and the key
+/// works once it has been followed (`GET /api/v1/verify`). The address must be valid
+/// and is stored with its domain lowercased; each can be registered once. With `organization` set, an organization is created too and the account becomes
+/// its owner, ready to add the accounts of its clients as members.
+#[utoipa::path(
+    post,
+    path = "/api/v1/register",
+    request_body = RegisterRequest,
+    responses(
+        (status = 200, description = "Account created, pending verification", body = ApiKeyResponse),
+        (status = 400, description = "INVALID_EMAIL: not a valid address; INVALID_NAME: organization name is empty or too long", body = ErrorEnvelope),
+        (status = 409, description = "EMAIL_ALREADY_REGISTERED", body = ErrorEnvelope),
+        (status = 503, description = "FEATURE_DISABLED: registration is closed", body = ErrorEnvelope)
+    ),
+    security(()),
+    tag = "Auth"
+)]
 pub async fn register_and_generate_key(
     req: web::Json<RegisterRequest>,
     mongo_client: web::Data<Client>,
-) -> Result<HttpResponse> {
-    let db_name = env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
-    let collection_name = env::var("DB_USERS_COLLECTION").unwrap_or_else(|_| "users".to_string());
-    let db = mongo_client.database(&db_name);
-    let collection: Collection<User> = db.collection(&collection_name);
-
-    let password_hash = hash(&req.password, DEFAULT_COST)
-        .map_err(|_| actix_web::error::ErrorInternalServerError("Password hashing failed"))?;
-
-    let user = User {
-        email: req.email.clone(),
-        password_hash: password_hash.clone(),
-        active: true,
-    };
-
-    collection
-        .insert_one(&user)
-        .await
-        .map_err(|_| actix_web::error::ErrorInternalServerError("Database error"))?;
+    flags: Option<web::Data<FeatureFlags>>,
+) -> Result<HttpResponse, ApiError> {
+    if let Some(flags) = flags {
+        flags.require(Flag::RegistrationOpen).await?;
+    }
+    let organization = req
+        .organization
+        .as_deref()
+        .map(organizations::validate_name)
+        .transpose()?;
 
-    let api_key = generate_api_key(&req.email, &password_hash)
-        .map_err(|_| actix_web::error::ErrorInternalServerError("Key generation failed"))?;
+    let (email, api_key) = register_account(&mongo_client, &req.email, &req.password)
+        .await
+        .map_err(ApiError::from_auth)?;
+    if let Some(name) = organization {
+        organizations::create_organization(&mongo_client, &email, name).await?;
+    }
 
     Ok(HttpResponse::Ok().json(ApiKeyResponse { api_key }))
 }
 
+#[derive(Deserialize)]
+pub struct VerifyQuery {
+    pub token: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct VerifiedAccount {
+    pub email: String,
+    pub active: bool,
+}
+
+/// Confirms ownership of a registered address and activates its account
+///
+/// Target of the link sent on registration, and of the one sent to a new email, which
+/// moves the account over to it; following it again is harmless.
+#[utoipa::path(
+    get,
+    path = "/api/v1/verify",
+    params(("token" = String, Query, description = "Token from the verification link")),
+    responses(
+        (status = 200, description = "Account active", body = VerifiedAccount),
+        (status = 400, description = "INVALID_VERIFICATION_TOKEN: invalid or expired link", body = ErrorEnvelope),
+        (status = 409, description = "EMAIL_ALREADY_REGISTERED: another account took a pending new email", body = ErrorEnvelope)
+    ),
+    security(()),
+    tag = "Auth"
+)]
+pub async fn verify_email(
+    query: web::Query<VerifyQuery>,
+    mongo_client: web::Data<Client>,
+) -> Result<HttpResponse, ApiError> {
+    match email_verification::confirm(&mongo_client, &query.token).await {
+        Ok(email) => Ok(HttpResponse::Ok().json(VerifiedAccount {
+            email,
+            active: true,
+        })),
+        Err(message) if message == "Invalid or expired token" => {
+            Err(ApiError::validation("INVALID_VERIFICATION_TOKEN", message))
+        }
+        Err(message) => Err(ApiError::from_auth(message)),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// Opens a session for dashboard clients; programmatic clients keep using API keys
+///
+/// Returns `{ access_token, refresh_token, token_type, expires_in }`, or 401 on bad credentials.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Session opened", body = SessionTokens),
+        (status = 401, description = "INVALID_CREDENTIALS", body = ErrorEnvelope)
+    ),
+    security(()),
+    tag = "Auth"
+)]
+pub async fn login(
+    req: web::Json<LoginRequest>,
+    mongo_client: web::Data<Client>,
+) -> Result<HttpResponse, ApiError> {
+    match auth::login(&mongo_client, &req.email, &req.password).await {
+        Ok(tokens) => Ok(HttpResponse::Ok().json(tokens)),
+        Err(message) if message == "Database error" => Err(ApiError::from_auth(message)),
+        Err(message) => Err(ApiError::auth("INVALID_CREDENTIALS", message)),
+    }
+}
+
+/// Exchanges a refresh token for a new access/refresh token pair
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/refresh",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "New token pair", body = SessionTokens),
+        (status = 401, description = "INVALID_REFRESH_TOKEN", body = ErrorEnvelope)
+    ),
+    security(()),
+    tag = "Auth"
+)]
+pub async fn refresh(
+    req: web::Json<RefreshRequest>,
+    mongo_client: web::Data<Client>,
+) -> Result<HttpResponse, ApiError> {
+    match auth::refresh_session(&mongo_client, &req.refresh_token).await {
+        Ok(tokens) => Ok(HttpResponse::Ok().json(tokens)),
+        Err(message) if message == "Database error" => Err(ApiError::from_auth(message)),
+        Err(message) => Err(ApiError::auth("INVALID_REFRESH_TOKEN", message)),
+    }
+}
+
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
-    cfg.route("/register", web::post().to(register_and_generate_key));
+    cfg.route("/register", web::post().to(register_and_generate_key))
+        .route("/verify", web::get().to(verify_email))
+        .route("/auth/login", web::post().to(login))
+        .route("/auth/refresh", web::post().to(refresh));
 }
 
 #[cfg(test)]
@@ -134,4 +259,49 @@ mod tests {
         // Should not be 404 (not found), meaning route is configured
         assert_ne!(resp.status().as_u16(), 404);
     }
+
+    #[actix_web::test]
+    async fn test_refresh_rejects_invalid_token() {
+        unsafe {
+            std::env::set_var("JWT_SECRET", "test-secret-key-for-testing");
+        }
+        let mongo_client = create_test_mongo_client().await;
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .configure(configure_routes),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/auth/refresh")
+            .set_json(json!({ "refresh_token": "not-a-token" }))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 401);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], "INVALID_REFRESH_TOKEN");
+    }
+
+    #[actix_web::test]
+    async fn test_login_missing_fields() {
+        let mongo_client = create_test_mongo_client().await;
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .configure(configure_routes),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/auth/login")
+            .set_json(json!({ "email": "test@example.com" }))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+    }
 }
@@ -1,7 +1,9 @@
 #[cfg(test)]
 mod auth_integration_tests {
+    use crate::auth::{Auth, AuthedAccount};
+    use crate::email_verification;
     use crate::routes::auth::*;
-    use actix_web::{App, http::StatusCode, test, web};
+    use actix_web::{App, HttpResponse, http::StatusCode, test, web};
     use mongodb::{Client as MongoClient, options::ClientOptions};
     use serde_json::json;
 
@@ -252,4 +254,59 @@ mod auth_integration_tests {
             resp.status() == StatusCode::INTERNAL_SERVER_ERROR || resp.status().as_u16() >= 200
         );
     }
+
+    #[actix_web::test]
+    async fn test_registered_key_is_refused_until_verified() {
+        unsafe {
+            std::env::set_var("JWT_SECRET", "test-secret-key-for-testing");
+        }
+        let mongo_client = create_test_mongo_client().await;
+
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(mongo_client)).service(
+                web::scope("/api/v1")
+                    .wrap(Auth::from_app_data())
+                    .configure(configure_routes)
+                    .route(
+                        "/whoami",
+                        web::get().to(|account: AuthedAccount| async move {
+                            HttpResponse::Ok().body(account.email.clone())
+                        }),
+                    ),
+            ),
+        )
+        .await;
+
+        let email = format!("unverified-{}@example.com", uuid::Uuid::new_v4().simple());
+        let req = test::TestRequest::post()
+            .uri("/api/v1/register")
+            .set_json(json!({ "email": &email, "password": "correct horse 42" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        if !resp.status().is_success() {
+            // No database to register against
+            return;
+        }
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let api_key = body["api_key"].as_str().unwrap().to_string();
+
+        let whoami = || {
+            test::TestRequest::get()
+                .uri("/api/v1/whoami")
+                .insert_header(("Authorization", format!("Bearer {}", api_key)))
+                .to_request()
+        };
+        let resp = test::call_service(&app, whoami()).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let (token, _) = email_verification::sign_verification_token(&email).unwrap();
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/verify?token={}", token))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = test::call_service(&app, whoami()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
 }
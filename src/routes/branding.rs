@@ -0,0 +1,159 @@
+use crate::auth::AuthenticatedAccount;
+use crate::branding::{self, Branding};
+use crate::error::{ApiError, ErrorEnvelope};
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, delete, get, put, web};
+use mongodb::Client as MongoClient;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct BrandingRequest {
+    /// Display name, at most 80 characters
+    pub name: Option<String>,
+    /// HTTPS URL of the logo image
+    pub logo_url: Option<String>,
+}
+
+/// Tenant of the authenticated account; bulk jobs it submits are filed under the same ID
+fn caller_tenant(http_req: &HttpRequest) -> Result<String, ApiError> {
+    http_req
+        .extensions()
+        .get::<AuthenticatedAccount>()
+        .map(|account| account.email.clone())
+        .ok_or_else(|| ApiError::unauthorized("Missing Authorization header"))
+}
+
+fn branding_not_set() -> ApiError {
+    ApiError::not_found("BRANDING_NOT_SET", "No branding is configured")
+}
+
+/// # Get Branding
+///
+/// ## Responses
+/// - **200 OK**: `{ "tenant_id", "name", "logo_url", "updated_at" }`
+/// - **404 Not Found**: `BRANDING_NOT_SET`
+#[utoipa::path(
+    get,
+    path = "/api/v1/branding",
+    responses(
+        (status = 200, description = "The caller's branding", body = Branding),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 404, description = "BRANDING_NOT_SET", body = ErrorEnvelope)
+    ),
+    tag = "Branding"
+)]
+#[get("/branding")]
+pub async fn get_branding(
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let tenant_id = caller_tenant(&http_req)?;
+    let branding = branding::load_branding(&mongo_client, &tenant_id)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?
+        .ok_or_else(branding_not_set)?;
+    Ok(HttpResponse::Ok().json(branding))
+}
+
+/// # Set Branding
+///
+/// Sets the name and logo shown on artifacts generated for the caller, such as
+/// shared result pages. Replaces any previous branding; omitted fields are cleared.
+///
+/// ## Responses
+/// - **200 OK**: The stored branding
+/// - **400 Bad Request**: `INVALID_BRANDING`, e.g. a non-HTTPS logo URL
+#[utoipa::path(
+    put,
+    path = "/api/v1/branding",
+    request_body = BrandingRequest,
+    responses(
+        (status = 200, description = "Branding stored", body = Branding),
+        (status = 400, description = "INVALID_BRANDING", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope)
+    ),
+    tag = "Branding"
+)]
+#[put("/branding")]
+pub async fn set_branding(
+    req: web::Json<BrandingRequest>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let tenant_id = caller_tenant(&http_req)?;
+    let branding = Branding::new(&tenant_id, req.name.as_deref(), req.logo_url.as_deref())
+        .map_err(|message| ApiError::validation("INVALID_BRANDING", message))?;
+
+    branding::save_branding(&mongo_client, &branding)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    Ok(HttpResponse::Ok().json(branding))
+}
+
+/// # Remove Branding
+///
+/// ## Responses
+/// - **204 No Content**: Branding removed
+/// - **404 Not Found**: `BRANDING_NOT_SET`
+#[utoipa::path(
+    delete,
+    path = "/api/v1/branding",
+    responses(
+        (status = 204, description = "Branding removed"),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 404, description = "BRANDING_NOT_SET", body = ErrorEnvelope)
+    ),
+    tag = "Branding"
+)]
+#[delete("/branding")]
+pub async fn remove_branding(
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let tenant_id = caller_tenant(&http_req)?;
+    let removed = branding::remove_branding(&mongo_client, &tenant_id)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    if !removed {
+        return Err(branding_not_set());
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_branding)
+        .service(set_branding)
+        .service(remove_branding);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, http::StatusCode, test};
+
+    #[actix_web::test]
+    async fn test_branding_routes_require_account() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/api/v1/branding")
+            .set_json(serde_json::json!({ "name": "Acme" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/branding")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
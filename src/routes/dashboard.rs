@@ -0,0 +1,365 @@
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::health_checks;
+use crate::health_history::HealthHistory;
+use crate::job_queue::JobQueue;
+use crate::metering::{self, Meter};
+use crate::routes::admin::require_admin;
+use crate::validation_service::ValidationBackends;
+use crate::validation_stats::StatsWindow;
+use actix_web::{HttpRequest, HttpResponse, get, web};
+use chrono::{NaiveDate, Utc};
+use mongodb::Client as MongoClient;
+use serde::Deserialize;
+use serde_json::json;
+
+/// Entries listed when `limit` is unset
+const DEFAULT_LIMIT: usize = 20;
+
+/// Most entries a single request may list
+const MAX_LIMIT: usize = 100;
+
+/// Entries to list for a requested `limit`
+fn list_limit(limit: Option<usize>) -> usize {
+    limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT)
+}
+
+#[derive(Deserialize)]
+pub struct LimitQuery {
+    /// Entries to list, 20 by default and at most 100
+    pub limit: Option<usize>,
+}
+
+/// # Job Queue Status
+///
+/// Jobs waiting to be picked up and the workers consuming them. A worker is alive
+/// while its heartbeat is current; the jobs it claimed are re-queued once it isn't.
+///
+/// ## Example Response
+/// ```json
+/// {
+///   "pending": 12,
+///   "workers_alive": 2,
+///   "consumers": [{ "name": "worker-a:0", "alive": true, "claimed": 1 }]
+/// }
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/queue",
+    responses(
+        (status = 200, description = "Queue depth and workers"),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[get("/admin/queue")]
+pub async fn queue_status(
+    job_queue: web::Data<JobQueue>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+    let queue_error = |e: redis::RedisError| ApiError::upstream("job queue", e);
+    let pending = job_queue.pending_jobs().await.map_err(queue_error)?;
+    let consumers = job_queue.consumers().await.map_err(queue_error)?;
+    let workers_alive = consumers.iter().filter(|consumer| consumer.alive).count();
+
+    Ok(HttpResponse::Ok().json(json!({
+        "pending": pending,
+        "workers_alive": workers_alive,
+        "consumers": consumers
+    })))
+}
+
+/// # Recent Failed Jobs
+///
+/// The latest bulk and async jobs that ended in failure, newest first. The last 100
+/// failures are kept, beyond the lifetime of the job records themselves.
+///
+/// ## Example Response
+/// ```json
+/// { "jobs": [{ "job_id": "...", "tenant_id": "user@example.com", "email_count": 500, "created_at": 1700000000, "failed_at": 1700000060 }] }
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/jobs/failed",
+    params(("limit" = Option<usize>, Query, description = "Jobs to list, 20 by default and at most 100")),
+    responses(
+        (status = 200, description = "Failed jobs, newest first"),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[get("/admin/jobs/failed")]
+pub async fn failed_jobs(
+    query: web::Query<LimitQuery>,
+    job_queue: web::Data<JobQueue>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+    let jobs = job_queue
+        .failed_jobs(list_limit(query.limit))
+        .await
+        .map_err(|e| ApiError::upstream("job queue", e))?;
+    Ok(HttpResponse::Ok().json(json!({ "jobs": jobs })))
+}
+
+#[derive(Deserialize)]
+pub struct UsageQuery {
+    /// Billing period as `YYYY-MM`; the current one when unset
+    pub period: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Whether `period` names a billing period, e.g. `2024-05`
+fn is_billing_period(period: &str) -> bool {
+    period.len() == 7 && NaiveDate::parse_from_str(&format!("{}-01", period), "%Y-%m-%d").is_ok()
+}
+
+/// # Usage Leaderboard
+///
+/// Accounts ranked by validated addresses in a billing period, as counted against
+/// their quotas. Organization members are counted under the organization's owner.
+///
+/// ## Responses
+/// - **200 OK**: `{ "period": "2024-05", "accounts": [{ "account", "units" }] }`
+/// - **400 Bad Request**: `INVALID_PERIOD`
+/// - **401/403**: Missing or non-admin API key
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/usage",
+    params(
+        ("period" = Option<String>, Query, description = "Billing period as YYYY-MM; defaults to the current one"),
+        ("limit" = Option<usize>, Query, description = "Accounts to list, 20 by default and at most 100")
+    ),
+    responses(
+        (status = 200, description = "Accounts by usage, highest first"),
+        (status = 400, description = "INVALID_PERIOD", body = ErrorEnvelope),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[get("/admin/usage")]
+pub async fn usage_leaderboard(
+    query: web::Query<UsageQuery>,
+    meter: web::Data<Meter>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+    let period = match &query.period {
+        Some(period) if is_billing_period(period) => period.clone(),
+        Some(_) => {
+            return Err(ApiError::validation(
+                "INVALID_PERIOD",
+                "period must be a billing period such as 2024-05",
+            ));
+        }
+        None => metering::billing_period(Utc::now()),
+    };
+    let accounts = meter
+        .leaderboard(&period, list_limit(query.limit))
+        .await
+        .map_err(|e| ApiError::upstream("usage store", e))?;
+    Ok(HttpResponse::Ok().json(json!({ "period": period, "accounts": accounts })))
+}
+
+#[derive(Deserialize)]
+pub struct TimelineQuery {
+    /// `hour`, `day` (default) or `month`
+    pub window: Option<String>,
+}
+
+/// # Error Rate Timeline
+///
+/// Validations per minute, hour or day over the window, with how many failed on our
+/// side or in a dependency (critical verdict codes such as `DNS_UNAVAILABLE` or
+/// `DATABASE_ERROR`) rather than judging the address.
+///
+/// ## Example Response
+/// ```json
+/// { "window": "day", "points": [{ "at": 1700000000, "total": 1200, "invalid": 300, "errors": 6, "error_rate": 0.005 }] }
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/error-rate",
+    params(("window" = Option<String>, Query, description = "hour, day (default) or month")),
+    responses(
+        (status = 200, description = "Per-bucket totals and error rates, oldest first"),
+        (status = 400, description = "INVALID_WINDOW", body = ErrorEnvelope),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[get("/admin/error-rate")]
+pub async fn error_rate_timeline(
+    query: web::Query<TimelineQuery>,
+    backends: web::Data<ValidationBackends>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+    let window = match query.window.as_deref() {
+        Some(window) => match StatsWindow::parse(window) {
+            Some(window) => window,
+            None => {
+                return Err(ApiError::validation(
+                    "INVALID_WINDOW",
+                    "window must be one of hour, day, month",
+                ));
+            }
+        },
+        None => StatsWindow::default(),
+    };
+
+    let points = backends
+        .stats
+        .timeline(window, Utc::now().timestamp())
+        .await
+        .map_err(|e| ApiError::upstream("stats store", e))?;
+    Ok(HttpResponse::Ok().json(json!({ "window": window, "points": points })))
+}
+
+/// # Dependency Health
+///
+/// Probes MongoDB, Redis and the DNS resolver now, alongside each dependency's
+/// recorded status history and uptime, as `/health` and `/health/history` report
+/// them separately.
+///
+/// ## Example Response
+/// ```json
+/// {
+///   "checks": [{ "name": "mongodb", "status": "UP", "latency_ms": 3 }],
+///   "history": [{ "name": "mongodb", "status": "UP", "since": 1700000000, "uptime": { "24h": 100.0 }, "transitions": [] }]
+/// }
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/dependencies",
+    responses(
+        (status = 200, description = "Current probes and status history"),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[get("/admin/dependencies")]
+pub async fn dependency_health(
+    mongo_client: Option<web::Data<MongoClient>>,
+    history: Option<web::Data<HealthHistory>>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    require_admin(&http_req)?;
+    let checks = health_checks::check_dependencies(
+        mongo_client.as_ref().map(|client| client.get_ref()),
+        history.as_ref().map(|history| history.get_ref()),
+    )
+    .await;
+    // History lives in Redis; the probes above already report it down if it is
+    let history = match &history {
+        Some(history) => history.report(Utc::now().timestamp()).await.ok(),
+        None => None,
+    };
+
+    Ok(HttpResponse::Ok().json(json!({ "checks": checks, "history": history })))
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(queue_status)
+        .service(failed_jobs)
+        .service(usage_leaderboard)
+        .service(error_rate_timeline)
+        .service(dependency_health);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stores::MemoryStore;
+    use actix_web::dev::Service;
+    use actix_web::{App, http::StatusCode, test as actix_test};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_billing_periods() {
+        assert!(is_billing_period("2024-05"));
+        assert!(!is_billing_period("2024-13"));
+        assert!(!is_billing_period("2024-5"));
+        assert!(!is_billing_period("may"));
+        assert_eq!(list_limit(Some(1000)), MAX_LIMIT);
+        assert_eq!(list_limit(None), DEFAULT_LIMIT);
+    }
+
+    #[actix_web::test]
+    async fn test_dashboard_routes_require_admin() {
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(ValidationBackends::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .app_data(web::Data::new(
+                    JobQueue::new("redis://127.0.0.1:6379").unwrap(),
+                ))
+                .app_data(web::Data::new(
+                    Meter::new("redis://127.0.0.1:6379").unwrap(),
+                ))
+                .wrap_fn(|req, srv| {
+                    crate::auth::accept_any_bearer(&req);
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        for uri in [
+            "/api/v1/admin/queue",
+            "/api/v1/admin/jobs/failed",
+            "/api/v1/admin/usage",
+            "/api/v1/admin/error-rate",
+            "/api/v1/admin/dependencies",
+        ] {
+            let req = actix_test::TestRequest::get().uri(uri).to_request();
+            let resp = actix_test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+
+            let req = actix_test::TestRequest::get()
+                .uri(uri)
+                .insert_header(("Authorization", "Bearer not-the-admin-key"))
+                .to_request();
+            let resp = actix_test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::FORBIDDEN, "{}", uri);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_dashboard_errors_use_envelope() {
+        use crate::auth::{ADMIN_SCOPE, AuthenticatedAccount};
+        use actix_web::HttpMessage;
+
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(ValidationBackends::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .app_data(web::Data::new(
+                    Meter::new("redis://127.0.0.1:6379").unwrap(),
+                ))
+                .wrap_fn(|req, srv| {
+                    req.extensions_mut().insert(AuthenticatedAccount {
+                        email: "ops@example.com".to_string(),
+                        scopes: vec![ADMIN_SCOPE.to_string()],
+                        ..AuthenticatedAccount::default()
+                    });
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        for (uri, code) in [
+            ("/api/v1/admin/usage?period=may", "INVALID_PERIOD"),
+            ("/api/v1/admin/error-rate?window=week", "INVALID_WINDOW"),
+        ] {
+            let req = actix_test::TestRequest::get().uri(uri).to_request();
+            let resp = actix_test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", uri);
+            let body: serde_json::Value = actix_test::read_body_json(resp).await;
+            assert_eq!(body["error"]["code"], code, "{}", uri);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::auth::AuthedAccount;
+use crate::erasure::{self, ErasureReport, ErasureScope};
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::job_queue::JobQueue;
+use crate::organizations;
+use crate::routes::admin::require_admin;
+use crate::validation_service::ValidationBackends;
+use actix_web::{HttpRequest, HttpResponse, Responder, delete, web};
+use mongodb::Client as MongoClient;
+
+/// Message of the `INVALID_INPUT` error for paths that don't name an address
+const NOT_AN_ADDRESS: &str = "The path must end with the address to erase";
+
+fn is_address(email: &str) -> bool {
+    email.trim().contains('@')
+}
+
+/// # Erase an Address
+///
+/// Deletes what the service keeps about an address on the caller's behalf, for
+/// data-subject deletion requests: their validation history records, its entry on
+/// their suppression list, the bounce and complaint feedback they reported about it,
+/// cached validation results, and the address in the records and results of their
+/// finished jobs. Organization members can't change the shared suppression list or
+/// feedback; its owner erases from them.
+///
+/// Erasing again is harmless and reports nothing left to delete, so a failed erasure
+/// can simply be retried.
+///
+/// ## Example Response
+/// ```json
+/// {
+///   "email": "user@example.com",
+///   "history": 12,
+///   "suppression_lists": 1,
+///   "bounce_feedback": 1,
+///   "cached_results": 2,
+///   "jobs": { "jobs": 1, "results": 1, "jobs_in_progress": 0 }
+/// }
+/// ```
+#[utoipa::path(
+    delete,
+    path = "/api/v1/data/email/{email}",
+    params(("email" = String, Path, description = "Address to erase")),
+    responses(
+        (status = 200, description = "What was deleted from each store", body = ErasureReport),
+        (status = 400, description = "INVALID_INPUT: the path doesn't name an address", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: a store could not be purged; retry", body = ErrorEnvelope)
+    ),
+    tag = "Account"
+)]
+#[delete("/data/email/{email}")]
+pub async fn erase_email(
+    path: web::Path<String>,
+    account: AuthedAccount,
+    mongo_client: web::Data<MongoClient>,
+    backends: web::Data<ValidationBackends>,
+    job_queue: web::Data<JobQueue>,
+) -> Result<impl Responder, ApiError> {
+    if !is_address(&path) {
+        return Err(ApiError::validation("INVALID_INPUT", NOT_AN_ADDRESS));
+    }
+    let scope = organizations::account_scope(&mongo_client, &account.email)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    let scope = ErasureScope::Account {
+        account: account.email.clone(),
+        suppression_owner: scope.can_manage_lists().then_some(scope.owner),
+    };
+
+    let report = erasure::erase(&mongo_client, &backends.cache, &job_queue, &path, &scope)
+        .await
+        .map_err(|e| ApiError::upstream("erasure", e))?;
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// # Erase an Address Everywhere
+///
+/// Like `DELETE /api/v1/data/email/{email}` for every account at once: all history
+/// records of the address, its entries on every account's suppression list and on the
+/// global suppression list, every account's feedback about it, cached results, and the
+/// address in every finished job.
+///
+/// ## Responses
+/// - **200 OK**: The erasure report, with `global_suppression` telling whether the
+///   address was on the global suppression list
+/// - **400 Bad Request**: `INVALID_INPUT`
+/// - **401/403**: Missing or non-admin API key
+/// - **503 Service Unavailable**: A store could not be purged; retry
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/data/email/{email}",
+    params(("email" = String, Path, description = "Address to erase")),
+    responses(
+        (status = 200, description = "What was deleted from each store", body = ErasureReport),
+        (status = 400, description = "INVALID_INPUT: the path doesn't name an address", body = ErrorEnvelope),
+        (status = 403, description = "Admin access required", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: a store could not be purged; retry", body = ErrorEnvelope)
+    ),
+    tag = "Admin"
+)]
+#[delete("/admin/data/email/{email}")]
+pub async fn erase_email_everywhere(
+    path: web::Path<String>,
+    mongo_client: web::Data<MongoClient>,
+    backends: web::Data<ValidationBackends>,
+    job_queue: web::Data<JobQueue>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    require_admin(&http_req)?;
+    if !is_address(&path) {
+        return Err(ApiError::validation("INVALID_INPUT", NOT_AN_ADDRESS));
+    }
+
+    let report = erasure::erase(
+        &mongo_client,
+        &backends.cache,
+        &job_queue,
+        &path,
+        &ErasureScope::Global,
+    )
+    .await
+    .map_err(|e| ApiError::upstream("erasure", e))?;
+    Ok(HttpResponse::Ok().json(report))
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(erase_email).service(erase_email_everywhere);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stores::MemoryStore;
+    use actix_web::dev::Service;
+    use actix_web::{App, http::StatusCode, test as actix_test};
+    use std::sync::Arc;
+
+    #[actix_web::test]
+    async fn test_erasure_requires_credentials() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .app_data(web::Data::new(ValidationBackends::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .app_data(web::Data::new(
+                    JobQueue::new("redis://127.0.0.1:6379").unwrap(),
+                ))
+                .wrap_fn(|req, srv| {
+                    crate::auth::accept_any_bearer(&req);
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::delete()
+            .uri("/api/v1/data/email/user@example.com")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = actix_test::TestRequest::delete()
+            .uri("/api/v1/admin/data/email/user@example.com")
+            .insert_header(("Authorization", "Bearer not-the-admin-key"))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], "INSUFFICIENT_SCOPE");
+    }
+
+    #[test]
+    fn test_addresses() {
+        assert!(is_address(" user@example.com"));
+        assert!(!is_address("example.com"));
+    }
+}
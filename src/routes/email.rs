@@ -1,12 +1,46 @@
-use crate::handlers::validation::{disposable, dnsmx, role_based, syntax};
-use crate::job_queue::JobQueue;
-use actix_web::{HttpResponse, Responder, post, web};
-use futures::future::join_all;
+use crate::account_settings::{self, AccountSettings};
+use crate::auth::{AuthedAccount, AuthenticatedAccount};
+use crate::bulk::{self, DedupedBatch};
+use crate::canary::CanaryRouter;
+use crate::checks::{Checks, ItemOptions};
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::feature_flags::{FeatureFlags, Flag};
+use crate::handlers::validation::custom_lists::AccountLists;
+use crate::handlers::validation::dnsmx::{self, DnsOutcome, IdnDomain};
+use crate::handlers::validation::first_seen;
+use crate::handlers::validation::rdap::{self, DomainRegistration, RdapConfig};
+use crate::handlers::validation::syntax::SyntaxViolation;
+use crate::i18n::{self, Lang};
+use crate::input_limits;
+use crate::job_export::{self, ExportFormat, ExportRow};
+use crate::job_payload;
+use crate::job_queue::{
+    BulkValidationJob, DuplicateAction, DuplicateDetectionConfig, JobQueue, JobStatus,
+};
+use crate::job_results::{self, ResultQuery};
+use crate::job_summary::{JobSummary, job_summary};
+use crate::list_report::{ListReport, job_report};
+use crate::local_cache::{LocalCache, LocalCacheStats, LocalTierConfig};
+use crate::metering;
+use crate::normalize::{self, normalize_domain};
+use crate::organizations;
+use crate::read_only;
+use crate::redis_pool::{PoolMetrics, RedisPool};
+use crate::response_fields::{ResponseShape, ShapeError};
+use crate::routes::share;
+use crate::stores::{CacheStore, RedisStore};
+use crate::validation_cache::CachePolicy;
+use crate::validation_history::{self, HistoryRecord, ValidationSource};
+use crate::validation_service::{ValidationBackends, ValidationService};
+use actix_web::http::{StatusCode, header};
+use actix_web::middleware::Compress;
+use actix_web::{HttpMessage, HttpResponse, Responder, ResponseError, delete, post, web};
+use futures::{StreamExt, stream};
 use mongodb::Client as MongoClient;
-use redis::{AsyncCommands, Client};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::sync::Arc;
+use std::time::Duration;
 use utoipa::ToSchema;
 
 #[derive(Deserialize, ToSchema)]
@@ -16,23 +50,97 @@ pub struct EmailRequest {
 
 #[derive(Deserialize, ToSchema)]
 pub struct BulkEmailRequest {
-    pub emails: Vec<String>,
+    pub emails: Vec<BulkEmailEntry>,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+/// A bulk entry: a bare address, or an address with its own validation options
+#[derive(Debug, Clone, PartialEq, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum BulkEmailEntry {
+    Address(String),
+    Item(BulkEmailItem),
+}
+
+/// An address with options overriding the request's query parameters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BulkEmailItem {
+    pub email: String,
+    #[serde(flatten)]
+    pub options: ItemOptions,
+}
+
+/// Bulk body as received; elements are checked one by one so lenient mode can
+/// report malformed ones individually
+#[derive(Deserialize)]
+pub struct RawBulkEmailRequest {
+    pub emails: Vec<serde_json::Value>,
+}
+
+/// Each element as an address with its options, or its JSON rendering when it is
+/// neither a string nor an `{ "email", "check_role_based"?, "checks"? }` object
+pub fn parse_bulk_items(values: Vec<serde_json::Value>) -> Vec<Result<BulkEmailItem, String>> {
+    values
+        .into_iter()
+        .map(|value| match value {
+            serde_json::Value::String(email) => Ok(BulkEmailItem {
+                email,
+                options: ItemOptions::default(),
+            }),
+            other => serde_json::from_value(other.clone()).map_err(|_| other.to_string()),
+        })
+        .collect()
+}
+
+fn invalid_input_response() -> EmailValidationResponse {
+    EmailValidationResponse {
+        is_valid: false,
+        domain: None,
+        has_tag: None,
+        base_address: None,
+        status: None,
+        error: Some(EmailValidationError {
+            code: "INVALID_INPUT".to_string(),
+            message: "Entry is neither an email string nor an entry object".to_string(),
+            violations: Vec::new(),
+        }),
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
 pub struct EmailValidationError {
     pub code: String,
     pub message: String,
+    /// For `INVALID_SYNTAX`, each rule the address breaks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<SyntaxViolation>,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
 pub struct EmailValidationResponse {
     pub is_valid: bool,
+    /// The address's domain in Unicode and punycode forms; omitted for invalid syntax
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<IdnDomain>,
+    /// Whether the local part carries a sub-address tag, e.g. `user+news@`; omitted
+    /// for invalid syntax
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_tag: Option<bool>,
+    /// The address without its tag, in canonical form; only set for tagged addresses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_address: Option<String>,
     pub status: Option<String>,
     pub error: Option<EmailValidationError>,
 }
 
-#[derive(Serialize, ToSchema)]
+impl EmailValidationResponse {
+    /// Reports whether `email`, the address this result is for, is sub-addressed
+    pub fn set_tag(&mut self, email: &str) {
+        self.base_address = normalize::base_address(email);
+        self.has_tag = Some(self.base_address.is_some());
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct BulkEmailValidationResult {
     pub email: String,
     pub validation: EmailValidationResponse,
@@ -43,77 +151,246 @@ pub struct BulkEmailValidationResponse {
     pub results: Vec<BulkEmailValidationResult>,
     pub valid_count: i32,
     pub invalid_count: i32,
+    /// Distinct addresses validated; repeats within the request share one result and count once
+    pub unique_count: i32,
+    /// Results matching `only` and `error_code`, across all pages; set when the results
+    /// are filtered or paged, while the counts above always cover the whole batch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<usize>,
 }
 
 #[derive(Deserialize)]
 pub struct ValidationQuery {
+    /// Unset falls back to the account settings, then to `false`
+    pub check_role_based: Option<bool>,
+    /// Reject queued jobs that duplicate a recent job instead of only warning
+    #[serde(default)]
+    pub block_duplicates: bool,
+    /// Report non-string bulk entries as `INVALID_INPUT` instead of rejecting the request
     #[serde(default)]
-    pub check_role_based: bool,
+    pub lenient: bool,
+    /// Comma-separated result fields to return, e.g. `is_valid,error.code`
+    pub fields: Option<String>,
+    /// `minimal`, `standard` (default) or `full`
+    pub verbosity: Option<String>,
+    /// Language of result messages, overriding `Accept-Language`; see [`Lang`]
+    pub lang: Option<String>,
 }
 
-// Redis client wrapper with connection pool
+impl ValidationQuery {
+    /// The request's options, with what it leaves out taken from the account's `defaults`
+    pub fn options(&self, defaults: &ItemOptions) -> ItemOptions {
+        ItemOptions {
+            check_role_based: self.check_role_based,
+            checks: None,
+        }
+        .with_defaults(defaults)
+    }
+
+    /// How results are rendered; see [`ResponseShape`]
+    pub fn response_shape(&self) -> Result<ResponseShape, ApiError> {
+        ResponseShape::parse(self.fields.as_deref(), self.verbosity.as_deref()).map_err(|e| match e
+        {
+            ShapeError::Verbosity => ApiError::validation(
+                "INVALID_VERBOSITY",
+                "verbosity must be one of minimal, standard, full",
+            ),
+            ShapeError::UnknownFields(unknown) => ApiError::validation(
+                "INVALID_FIELDS",
+                format!("Unknown fields: {}", unknown.join(", ")),
+            )
+            .with_details(json!({ "unknown_fields": unknown })),
+        })
+    }
+}
+
+// DNS verdict cache, shared by every API instance through its store
 #[derive(Clone)]
 pub struct RedisCache {
-    client: Arc<Client>,
-    pub ttl: u64, // Time-to-live for cache entries in seconds
+    store: Arc<dyn CacheStore>,   // DNS verdicts and their hit/miss counters
+    pub ttl: u64,                 // Time-to-live for cache entries in seconds
+    pub canary: CanaryRouter,     // Share of DNS lookups sent through the async resolver
+    pub dns_unavailable_ttl: u64, // Seconds a resolver failure is cached, 0 for never
+    dns_local: Option<Arc<LocalCache>>, // In-process tier in front of the DNS verdicts in the store
+}
+
+/// Redis counters for DNS verdict lookups, shared by every API instance
+const DNS_HITS_KEY: &str = "dns_cache:hits";
+const DNS_MISSES_KEY: &str = "dns_cache:misses";
+/// Bumped on every invalidation so that every instance empties its DNS tier
+const DNS_INVALIDATIONS_KEY: &str = "dns_cache:invalidations";
+
+/// Seconds a resolver failure is cached when `DNS_CACHE_TTL_UNAVAILABLE_SECS` is unset
+pub const DEFAULT_DNS_UNAVAILABLE_TTL_SECS: u64 = 30;
+
+/// Reads `DNS_CACHE_TTL_UNAVAILABLE_SECS`; 0 never caches SERVFAIL and timeouts
+pub fn dns_unavailable_ttl_from_env() -> u64 {
+    std::env::var("DNS_CACHE_TTL_UNAVAILABLE_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_DNS_UNAVAILABLE_TTL_SECS)
 }
 
 impl RedisCache {
+    // Caches in Redis, with the canary share, local tier and failure TTL from the environment
     pub fn new(redis_url: &str, ttl: u64) -> Result<Self, redis::RedisError> {
-        let client = Client::open(redis_url)?;
+        let store: Arc<dyn CacheStore> = Arc::new(RedisStore::new(RedisPool::new(redis_url)?));
         Ok(Self {
-            client: Arc::new(client),
+            canary: CanaryRouter::from_store(
+                store.clone(),
+                CanaryRouter::dns_async_percent_from_env(),
+            ),
+            dns_unavailable_ttl: dns_unavailable_ttl_from_env(),
+            dns_local: LocalCache::from_config(LocalTierConfig::dns_from_env())
+                .map(|local| Arc::new(local.invalidated_by(DNS_INVALIDATIONS_KEY))),
+            store,
             ttl,
         })
     }
 
-    // For testing when Redis is unavailable
-    pub fn test_dummy() -> Self {
-        // Create a dummy Redis cache that doesn't actually connect
-        // This is used in tests when Redis is not available
+    // Caches in `store`, e.g. a `MemoryStore` in tests, without canary routing or local tier
+    pub fn from_store(store: Arc<dyn CacheStore>, ttl: u64) -> Self {
         Self {
-            client: Arc::new(Client::open("redis://127.0.0.1:6379").unwrap()),
-            ttl: 3600,
+            canary: CanaryRouter::from_store(store.clone(), 0),
+            dns_unavailable_ttl: DEFAULT_DNS_UNAVAILABLE_TTL_SECS,
+            dns_local: None,
+            store,
+            ttl,
+        }
+    }
+
+    // The store behind the cache, for the other services sharing it
+    pub fn store(&self) -> Arc<dyn CacheStore> {
+        self.store.clone()
+    }
+
+    // Seconds a DNS outcome is cached: REDIS_CACHE_TTL for answers about the domain,
+    // dns_unavailable_ttl for resolver failures; None when it isn't cached at all
+    fn dns_ttl(&self, outcome: DnsOutcome) -> Option<u64> {
+        match outcome {
+            DnsOutcome::Valid | DnsOutcome::NotFound => Some(self.ttl),
+            DnsOutcome::Unavailable => Some(self.dns_unavailable_ttl).filter(|ttl| *ttl > 0),
         }
     }
 
-    // Get cached DNS validation result
+    // Get cached DNS validation result, from the in-process tier when it has one
     pub async fn get_dns_validation(
         &self,
         email_domain: &str,
-    ) -> Result<Option<bool>, redis::RedisError> {
-        match self.client.get_multiplexed_async_connection().await {
-            Ok(mut conn) => {
-                let cache_key = format!("dns_mx::{}", email_domain);
-                let result: Option<String> = conn.get(&cache_key).await?;
-                Ok(result.map(|val| val == "valid"))
-            }
-            Err(e) => {
-                // In test environment, return cache miss gracefully instead of propagating error
-                if cfg!(test) { Ok(None) } else { Err(e) }
-            }
+    ) -> Result<Option<DnsOutcome>, redis::RedisError> {
+        let cache_key = format!("dns_mx::{}", normalize_domain(email_domain));
+        if let Some(local) = &self.dns_local {
+            local.sync(self.store.as_ref()).await;
         }
+        if let Some(outcome) = self
+            .dns_local
+            .as_ref()
+            .and_then(|local| local.get(&cache_key))
+            .and_then(|value| DnsOutcome::from_cache_value(&value))
+        {
+            return Ok(Some(outcome));
+        }
+        let value = self.store.get(&cache_key).await?;
+        let result = value.as_deref().and_then(DnsOutcome::from_cache_value);
+        let counter = if result.is_some() {
+            DNS_HITS_KEY
+        } else {
+            DNS_MISSES_KEY
+        };
+        let _ = self.store.incr(counter).await;
+        if let (Some(local), Some(outcome)) = (&self.dns_local, result)
+            && let Some(ttl) = self.dns_ttl(outcome)
+        {
+            local.insert(
+                &cache_key,
+                outcome.cache_value(),
+                Some(Duration::from_secs(ttl)),
+            );
+        }
+        Ok(result)
     }
 
-    // Store DNS validation result
+    // Store DNS validation result; resolver failures only briefly, if at all
     pub async fn set_dns_validation(
         &self,
         email_domain: &str,
-        is_valid: bool,
+        outcome: DnsOutcome,
     ) -> Result<(), redis::RedisError> {
-        match self.client.get_multiplexed_async_connection().await {
-            Ok(mut conn) => {
-                let cache_key = format!("dns_mx::{}", email_domain);
-                let value = if is_valid { "valid" } else { "invalid" };
-                let _: () = conn.set(&cache_key, value).await?;
-                let _: () = conn.expire(&cache_key, self.ttl as i64).await?;
-                Ok(())
-            }
-            Err(e) => {
-                // In test environment, ignore Redis errors
-                if cfg!(test) { Ok(()) } else { Err(e) }
+        let Some(ttl) = self.dns_ttl(outcome) else {
+            return Ok(());
+        };
+        let cache_key = format!("dns_mx::{}", normalize_domain(email_domain));
+        let value = outcome.cache_value();
+        if let Some(local) = &self.dns_local {
+            local.insert(&cache_key, value, Some(Duration::from_secs(ttl)));
+        }
+        self.store.set_ex(&cache_key, value, ttl).await
+    }
+
+    // Forget the DNS verdict for a domain so the next lookup hits the resolver
+    pub async fn invalidate_dns_validation(
+        &self,
+        email_domain: &str,
+    ) -> Result<u64, redis::RedisError> {
+        // Entries are keyed by the punycode domain; older ones by the lowercased or
+        // as-submitted spelling
+        let mut cache_keys = vec![
+            format!("dns_mx::{}", normalize_domain(email_domain)),
+            format!("dns_mx::{}", email_domain.trim()),
+        ];
+        if let Some(domain) = dnsmx::idn_domain(email_domain.trim()) {
+            cache_keys.push(format!("dns_mx::{}", domain.ascii));
+        }
+        // This instance's tier right away; the others' on their next invalidation check
+        if let Some(local) = &self.dns_local {
+            for key in &cache_keys {
+                local.remove(key);
             }
         }
+        let deleted = self.store.del(&cache_keys).await?;
+        self.store.incr(DNS_INVALIDATIONS_KEY).await?;
+        Ok(deleted)
+    }
+
+    // Registration data of a domain from RDAP, cached for RDAP_CACHE_TTL_SECS; None
+    // when lookups are disabled
+    pub async fn domain_registration(
+        &self,
+        ascii_domain: &str,
+    ) -> Result<Option<DomainRegistration>, String> {
+        let config = RdapConfig::from_env();
+        rdap::lookup_registration(self.store.as_ref(), config.as_ref(), ascii_domain).await
+    }
+
+    // DNS verdict lookups answered and missed by Redis, across instances
+    pub async fn dns_counters(&self) -> Result<(u64, u64), redis::RedisError> {
+        let counters = self.store.get_many(&[DNS_HITS_KEY, DNS_MISSES_KEY]).await?;
+        let count = |i: usize| {
+            counters[i]
+                .as_deref()
+                .and_then(|count| count.parse::<u64>().ok())
+                .unwrap_or(0)
+        };
+        Ok((count(0), count(1)))
+    }
+
+    // Counters of the in-process DNS tier, `None` without one
+    pub fn dns_local_stats(&self) -> Option<LocalCacheStats> {
+        self.dns_local.as_ref().map(|local| local.stats())
+    }
+
+    // Connection pool usage, shared with every other Redis store; `None` for other stores
+    pub fn pool_metrics(&self) -> Option<PoolMetrics> {
+        self.store.pool_metrics()
+    }
+
+    // Number of cached DNS verdicts
+    pub async fn dns_key_count(&self) -> Result<u64, redis::RedisError> {
+        self.store.count_prefix("dns_mx::").await
     }
 }
 
@@ -121,24 +398,60 @@ impl RedisCache {
 ///
 /// Validates an email address by checking multiple aspects:
 /// 1. RFC-compliant syntax validation
-/// 2. Domain DNS/MX record verification (with Redis caching)
-/// 3. Role-based email address detection (optional, via query parameter)
-/// 4. Disposable email domain check
+/// 2. Known spamtrap and previous bouncer lookup (when enabled)
+/// 3. Top-level domain check against the IANA list
+/// 4. Domain DNS/MX record verification (with Redis caching)
+/// 5. Role-based email address detection (optional, via query parameter)
+/// 6. Disposable email domain check
+///
+/// When a first-seen dataset is configured, valid responses include an
+/// `email_age` object with the address's age and its risk contribution. With
+/// `RDAP_LOOKUP_ENABLED=true` they also include `domain_registration`, the domain's
+/// `created_at`, `age_days` and `registrar` from its registry, with `risk: "NEW_DOMAIN"`
+/// when it was registered less than `RDAP_NEW_DOMAIN_DAYS` ago.
+///
+/// Full results are cached by normalized address, with lifetimes per outcome (see
+/// [`CacheTtlConfig`](crate::validation_cache::CacheTtlConfig)); database errors are
+/// never cached.
 ///
 /// ## Request
 /// - Method: POST
 /// - Body: JSON object with `email` field
 /// - Query Parameters:
-///   - `check_role_based` (optional): Set to `true` to enable role-based validation
+///   - `check_role_based` (optional): Set to `true` to enable role-based validation;
+///     when omitted, the account settings decide this and the stages that run
+///   - `verbosity` (optional): `minimal` answers `{ "is_valid", "status" }` without the
+///     age lookup; `full` adds `is_valid`, `domain`, `has_tag` and `base_address`
+///     (for sub-addresses such as `user+news@gmail.com`) to the standard body
+///   - `fields` (optional): Comma-separated fields of the full body to return, e.g.
+///     `is_valid,domain.ascii`; overrides `verbosity`
+///   - `lang` (optional): `en`, `es`, `de` or `fr`; `message` comes from that
+///     language's catalog. Defaults to the preferred supported language of the
+///     `Accept-Language` header; without either, messages keep the checks' wording,
+///     which can name details such as the rule an address breaks
+///
+/// Failed validations keep the error envelope whatever the shape. Codes never change
+/// with the language; branch on them, and show the message to end users.
 ///
 /// ## Responses
 /// - **200 OK**: Email is valid
 /// - **400 Bad Request**:
+///   - `INVALID_FIELDS` or `INVALID_VERBOSITY`: the response shape is not understood
+///   - `INVALID_LANG`: `lang` names a language without a catalog
 ///   - Invalid email syntax
+///   - Address is a known spamtrap or complainer (`KNOWN_TRAP`) or has bounced before
+///     (`PREVIOUS_BOUNCER`)
+///   - Domain's top-level domain is not registered (`INVALID_TLD`)
 ///   - Domain has no valid MX/A/AAAA records
 ///   - Role-based email address detected (if enabled)
 ///   - Disposable email detected
-/// - **500 Internal Server Error**: Database or Redis connection failed
+/// - **413 Payload Too Large**: `PAYLOAD_TOO_LARGE`, body above `MAX_JSON_PAYLOAD_BYTES`
+/// - **422 Unprocessable Entity**: `INVALID_FIELD`, the address is longer than 320 bytes
+///   or contains control characters; `INVALID_BODY`, the body doesn't match the schema
+/// - **503 Service Unavailable**: `UPSTREAM_UNAVAILABLE`, database or Redis connection failed
+///
+/// Errors use the shared envelope `{ "error": { "code", "message", "request_id" } }`
+/// (see [`ApiError`]).
 ///
 /// ## Example Requests
 /// ```json
@@ -152,404 +465,1431 @@ impl RedisCache {
 /// ```
 #[utoipa::path(
     post,
-    path = "/api/v1/validate-email",
-    request_body = EmailRequest,
-    params(
-        ("check_role_based" = Option<bool>, Query, description = "Enable role-based email validation")
-    ),
+    path = "/api/v1/validate-email",
+    request_body = EmailRequest,
+    params(
+        ("check_role_based" = Option<bool>, Query, description = "Enable role-based email validation"),
+        ("verbosity" = Option<String>, Query, description = "minimal, standard (default) or full"),
+        ("fields" = Option<String>, Query, description = "Comma-separated fields to return, e.g. is_valid,domain.ascii"),
+        ("lang" = Option<String>, Query, description = "Language of messages: en, es, de or fr; overrides Accept-Language")
+    ),
+    responses(
+        (status = 200, description = "Email is valid: `{ \"status\": \"VALID\", \"message\", \"email_age\"? }`, shaped by `verbosity` or `fields`"),
+        (status = 400, description = "Invalid email; `code` names the failed check. INVALID_LANG: unsupported lang", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 413, description = "PAYLOAD_TOO_LARGE: body exceeds MAX_JSON_PAYLOAD_BYTES", body = ErrorEnvelope),
+        (status = 422, description = "INVALID_FIELD or INVALID_BODY: the address or body is malformed", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: a dependency is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[post("/validate-email")]
+pub async fn validate_email(
+    req: web::Json<EmailRequest>,
+    query: web::Query<ValidationQuery>,
+    backends: web::Data<ValidationBackends>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: actix_web::HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let checks = query
+        .options(&caller_defaults(&http_req, &mongo_client).await?)
+        .checks(false);
+    validate_one(
+        &req.email,
+        &query,
+        checks,
+        &backends,
+        &mongo_client,
+        &http_req,
+    )
+    .await
+}
+
+/// # Email Validation Endpoint (GET)
+///
+/// Same as `POST /validate-email`, for integrations that can only issue GET requests
+/// (webhook and automation builders). The address is passed as the `email` query
+/// parameter; a literal `+` in it is kept (`user+tag@example.com`), so only characters
+/// that would break the query string need percent-encoding.
+///
+/// Responses are identical to the POST endpoint's, plus a `Cache-Control` header: a
+/// verdict served from the validation cache may be reused for as long as it stays
+/// cached (`private, max-age=<remaining TTL>`); anything else is `no-store`.
+///
+/// ## Example Request
+/// ```text
+/// GET /api/v1/validate-email?email=user%40example.com&check_role_based=true
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/validate-email",
+    params(
+        ("email" = String, Query, description = "Address to validate"),
+        ("check_role_based" = Option<bool>, Query, description = "Enable role-based email validation"),
+        ("verbosity" = Option<String>, Query, description = "minimal, standard (default) or full"),
+        ("fields" = Option<String>, Query, description = "Comma-separated fields to return, e.g. is_valid,domain.ascii"),
+        ("lang" = Option<String>, Query, description = "Language of messages: en, es, de or fr; overrides Accept-Language")
+    ),
+    responses(
+        (status = 200, description = "Email is valid, as for POST; Cache-Control reflects the cached result's TTL"),
+        (status = 400, description = "Invalid email, INVALID_INPUT when the email parameter is missing, or INVALID_LANG", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 422, description = "INVALID_FIELD: the address is malformed", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: a dependency is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[actix_web::get("/validate-email")]
+pub async fn validate_email_get(
+    query: web::Query<ValidationQuery>,
+    backends: web::Data<ValidationBackends>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: actix_web::HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    let email = email_query_param(http_req.query_string()).ok_or_else(|| {
+        ApiError::validation(
+            "INVALID_INPUT",
+            "Pass the address as the email query parameter",
+        )
+    })?;
+    let checks = query
+        .options(&caller_defaults(&http_req, &mongo_client).await?)
+        .checks(false);
+    let (mut response, error_code) =
+        match validate_one(&email, &query, checks, &backends, &mongo_client, &http_req).await {
+            Ok(response) => (response, None),
+            Err(e) => (e.error_response(), Some(e.code().to_string())),
+        };
+    set_cache_control(
+        &mut response,
+        &backends,
+        &email,
+        checks,
+        error_code.as_deref(),
+    )
+    .await;
+    Ok(response)
+}
+
+/// Lets clients reuse a GET validation answered with `error_code` for as long as
+/// the verdict stays in the validation cache, and forbids reuse otherwise
+///
+/// Messages follow `Accept-Language`, so caches must keep a copy per language.
+pub async fn set_cache_control(
+    response: &mut HttpResponse,
+    backends: &ValidationBackends,
+    email: &str,
+    checks: Checks,
+    error_code: Option<&str>,
+) {
+    // Only verdicts are cached; auth and input errors must not be reused
+    let is_verdict = backends.cache.ttls.policy_for(error_code) != CachePolicy::Skip;
+    let remaining = if is_verdict && checks.is_standard() {
+        backends.cache.remaining_ttl(email, checks.role_based).await
+    } else {
+        None
+    };
+    let cache_control = match remaining {
+        Some(CachePolicy::Expire(secs)) => format!("private, max-age={}", secs),
+        Some(CachePolicy::Forever) => "private, max-age=31536000".to_string(),
+        Some(CachePolicy::Skip) | None => "no-store".to_string(),
+    };
+    if let Ok(value) = header::HeaderValue::from_str(&cache_control) {
+        response.headers_mut().insert(header::CACHE_CONTROL, value);
+    }
+    response.headers_mut().insert(
+        header::VARY,
+        header::HeaderValue::from_static("Accept-Language"),
+    );
+}
+
+/// # Asynchronous Email Validation Endpoint
+///
+/// Queues the validation of one address and answers 202 with a job ID right away,
+/// for callers with tight timeout budgets such as checkout flows. When the job
+/// finishes, the full result is posted to the account's webhook (`webhook_url` in
+/// `PUT /account/settings`) as `{ "event": "validation.completed", "job_id",
+/// "email", "result" }`, signed with the account's webhook secret (see
+/// `GET /account/webhook-secret`); it can also be fetched from the job endpoints.
+///
+/// Options follow `POST /validate-email`, including the account's defaults.
+#[utoipa::path(
+    post,
+    path = "/api/v1/validate-email-async",
+    request_body = EmailRequest,
+    params(
+        ("check_role_based" = Option<bool>, Query, description = "Enable role-based email validation")
+    ),
+    responses(
+        (status = 202, description = "Validation queued: `{ \"job_id\", \"status\": \"queued\" }`"),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 422, description = "INVALID_FIELD: the address is malformed; WEBHOOK_NOT_CONFIGURED: the account has no webhook", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database or job queue is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[post("/validate-email-async")]
+pub async fn validate_email_async(
+    req: web::Json<EmailRequest>,
+    query: web::Query<ValidationQuery>,
+    job_queue: web::Data<JobQueue>,
+    mongo_client: web::Data<MongoClient>,
+    account: AuthedAccount,
+    http_req: actix_web::HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    input_limits::check_email_field("email", &req.email)?;
+    let tenant_id = account.tenant_id();
+    let settings = account_settings::load_settings(&mongo_client, &tenant_id)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?
+        .unwrap_or_else(|| AccountSettings::unset(&tenant_id));
+    let Some(webhook_url) = settings.webhook_url.clone() else {
+        return Err(ApiError::validation(
+            "WEBHOOK_NOT_CONFIGURED",
+            "Set webhook_url in the account settings to receive async results",
+        )
+        .with_status(StatusCode::UNPROCESSABLE_ENTITY));
+    };
+
+    metering::charge_request(&http_req, 1).await?;
+    let job_id = job_queue
+        .enqueue_async_validation(
+            &tenant_id,
+            account.plan,
+            req.email.trim().to_string(),
+            query.options(&settings.options()),
+            webhook_url,
+        )
+        .await
+        .map_err(|e| ApiError::upstream("job queue", e))?;
+    Ok(HttpResponse::Accepted().json(json!({ "job_id": job_id, "status": "queued" })))
+}
+
+/// The `email` parameter of a raw query string, percent-decoded without turning `+`
+/// into a space; `None` when it is missing or not valid UTF-8 after decoding
+pub fn email_query_param(query: &str) -> Option<String> {
+    let raw = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("email="))?;
+    let bytes = raw.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| raw.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded)
+        .ok()
+        .filter(|email| !email.trim().is_empty())
+}
+
+/// Validates one address for the caller and records it in their history
+///
+/// The core of the single validation endpoints of every API version, which differ
+/// only in how they render the result. A reference list outage fails the request
+/// rather than yielding a verdict.
+pub async fn validate_for_caller(
+    email: &str,
+    checks: Checks,
+    backends: &ValidationBackends,
+    mongo_client: &MongoClient,
+    http_req: &actix_web::HttpRequest,
+) -> Result<EmailValidationResponse, ApiError> {
+    input_limits::check_email_field("email", email)?;
+    let account = AuthedAccount::from_http(http_req)?;
+    metering::charge_request(http_req, 1).await?;
+    let lists = caller_lists(http_req, mongo_client, &[email.trim().to_string()]).await?;
+    let tenant_id = account.tenant_id();
+    let result = ValidationService::new(backends)
+        .for_tenant(&tenant_id)
+        .validate(email, checks, &lists)
+        .await;
+    validation_history::record(
+        mongo_client,
+        vec![HistoryRecord::new(
+            &account.tenant_id(),
+            email,
+            result.is_valid,
+            result.error.as_ref().map(|e| e.code.as_str()),
+            ValidationSource::Rest,
+        )],
+    );
+
+    match &result.error {
+        Some(error) if error.code == "DATABASE_ERROR" => {
+            Err(ApiError::upstream("database", error.message.clone()))
+        }
+        _ => Ok(result),
+    }
+}
+
+/// Validates one address for the v1 POST and GET single validation endpoints
+async fn validate_one(
+    email: &str,
+    query: &ValidationQuery,
+    checks: Checks,
+    backends: &ValidationBackends,
+    mongo_client: &MongoClient,
+    http_req: &actix_web::HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    input_limits::check_email_field("email", email)?;
+    let shape = query.response_shape()?;
+    let lang = Lang::requested(query.lang.as_deref(), http_req)?;
+    let mut result = validate_for_caller(email, checks, backends, mongo_client, http_req).await?;
+    if let Some(error) = &mut result.error {
+        i18n::localize(error, lang);
+    }
+
+    match result.error {
+        None => {
+            let message = lang.and_then(|lang| lang.message(i18n::VALID));
+            let mut body = json!({
+                "status": "VALID",
+                "message": message.unwrap_or("Email address is valid")
+            });
+            // Age lookups are optional; a dataset outage shouldn't fail validation
+            if shape.wants_enrichment("email_age")
+                && let Ok(Some(age)) = first_seen::lookup_email_age(email).await
+            {
+                body["email_age"] = json!(age);
+            }
+            if shape.wants_enrichment("domain_registration")
+                && let Some(domain) = &result.domain
+                && let Ok(Some(registration)) =
+                    backends.dns.domain_registration(&domain.ascii).await
+            {
+                body["domain_registration"] = json!(registration);
+            }
+            if shape.is_standard() {
+                return Ok(HttpResponse::Ok().json(body));
+            }
+            body["is_valid"] = json!(true);
+            if let Some(domain) = result.domain {
+                body["domain"] = json!(domain);
+            }
+            if let Some(has_tag) = result.has_tag {
+                body["has_tag"] = json!(has_tag);
+            }
+            if let Some(base_address) = result.base_address {
+                body["base_address"] = json!(base_address);
+            }
+            Ok(HttpResponse::Ok().json(shape.apply(body)))
+        }
+        Some(error) => Err(ApiError::validation(error.code, error.message)),
+    }
+}
+
+/// Custom lists of the account the auth middleware resolved, or of its organization,
+/// with its bounce feedback about `emails`; empty for unauthenticated calls
+pub async fn caller_lists(
+    http_req: &actix_web::HttpRequest,
+    mongo_client: &MongoClient,
+    emails: &[String],
+) -> Result<AccountLists, ApiError> {
+    let owner = http_req
+        .extensions()
+        .get::<AuthenticatedAccount>()
+        .map(|account| account.email.clone());
+    let Some(email) = owner else {
+        return Ok(AccountLists::default());
+    };
+    let owner = organizations::list_owner(mongo_client, &email)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    AccountLists::load(mongo_client, &owner, emails)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))
+}
+
+/// Validation options the authenticated account's requests fall back to (see
+/// [`account_settings`]); empty for unauthenticated calls
+pub async fn caller_defaults(
+    http_req: &actix_web::HttpRequest,
+    mongo_client: &MongoClient,
+) -> Result<ItemOptions, ApiError> {
+    let tenant_id = http_req
+        .extensions()
+        .get::<AuthenticatedAccount>()
+        .map(|account| account.tenant_id());
+    let Some(tenant_id) = tenant_id else {
+        return Ok(ItemOptions::default());
+    };
+    account_settings::default_options(mongo_client, &tenant_id)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))
+}
+
+/// Validates one address, serving and storing full results through the validation cache
+pub async fn validate_single_email(
+    email: &str,
+    check_role_based: bool,
+    backends: &ValidationBackends,
+) -> EmailValidationResponse {
+    ValidationService::new(backends)
+        .validate(
+            email,
+            Checks::standard(check_role_based),
+            &AccountLists::default(),
+        )
+        .await
+}
+
+/// Rejects empty batches (422 `EMPTY_BATCH`) and batches above `max_batch_size`
+/// (413 `BATCH_TOO_LARGE`, reporting the limit)
+pub fn batch_size_rejection(len: usize, max_batch_size: usize) -> Option<ApiError> {
+    if len == 0 {
+        return Some(
+            ApiError::validation(
+                "EMPTY_BATCH",
+                "The emails array must contain at least one address",
+            )
+            .with_status(StatusCode::UNPROCESSABLE_ENTITY),
+        );
+    }
+    if len > max_batch_size {
+        return Some(
+            ApiError::validation(
+                "BATCH_TOO_LARGE",
+                format!(
+                    "Batch contains {} emails; the maximum is {}",
+                    len, max_batch_size
+                ),
+            )
+            .with_status(StatusCode::PAYLOAD_TOO_LARGE)
+            .with_details(json!({ "max_batch_size": max_batch_size })),
+        );
+    }
+    None
+}
+
+/// # Bulk Email Validation Endpoint
+///
+/// Validates multiple email addresses in parallel by checking:
+/// 1. RFC-compliant syntax validation
+/// 2. Known spamtrap and previous bouncer lookup (when enabled)
+/// 3. Top-level domain check against the IANA list
+/// 4. Domain DNS/MX record verification (with Redis caching)
+/// 5. Role-based email address detection (optional, via query parameter)
+/// 6. Disposable email domain check
+///
+/// ## Request
+/// - Method: POST
+/// - Body: JSON object with `emails` array field; each entry is an address or an
+///   `{ "email", "check_role_based"?, "checks"? }` object overriding the query options
+///   for that address. `checks` lists the stages to run after syntax (`suppression`,
+///   `tld`, `dns`, `role_based`, `disposable`); such results bypass the result cache
+/// - Query Parameters:
+///   - `check_role_based` (optional): Set to `true` to enable role-based validation;
+///     when omitted, the account settings decide this and the stages that run
+///   - `block_duplicates` (optional): Set to `true` to reject re-uploads of a recent job
+///   - `lenient` (optional): Set to `true` to report malformed entries individually
+///     with `INVALID_INPUT` and process the rest of the batch
+///   - `verbosity` (optional): `minimal` cuts each `validation` down to `is_valid`,
+///     `status` and `error.code`
+///   - `fields` (optional): Comma-separated `validation` fields to return, e.g.
+///     `is_valid,error.code`; overrides `verbosity`. Queued jobs are not shaped
+///   - `lang` (optional): Language of `error.message`, as for `POST /validate-email`.
+///     Queued jobs keep the checks' wording
+///   - `only` (optional): `valid` or `invalid`, to return only those results
+///   - `error_code` (optional): Return only the results failing with this code, e.g.
+///     `DISPOSABLE_EMAIL`
+///   - `page`, `per_page` (optional): Return one page of the results (default 100 per
+///     page, at most 1000). Queued jobs are filtered and paged when their results are
+///     fetched from `GET /jobs/{job_id}/results`
+///
+/// ## Responses
+/// - **200 OK**: Returns validation results for all emails with counts; filtered or
+///   paged responses add `total`, the matching results across all pages
+/// - **202 Accepted**: Large batch queued; includes `duplicate_of` when it overlaps a recent job
+/// - **400 Bad Request**: `INVALID_INPUT`, an entry is malformed and `lenient` is off;
+///   `INVALID_FIELDS` or `INVALID_VERBOSITY`, the response shape is not understood;
+///   `INVALID_LANG`, `lang` names a language without a catalog; `INVALID_FILTER` or
+///   `INVALID_PAGE`, the result filters or page are not understood
+/// - **409 Conflict**: Large batch duplicates a recent job and blocking is enabled
+/// - **413 Payload Too Large**: `BATCH_TOO_LARGE`, more emails than `BULK_MAX_BATCH_SIZE`
+///   (default 10,000); `error.details.max_batch_size` reports the limit. `PAYLOAD_TOO_LARGE`,
+///   a body above `MAX_JSON_PAYLOAD_BYTES`, is rejected before it is read into memory.
+///   `JOB_TOO_LARGE`, a queued job above `JOB_PAYLOAD_MAX_BYTES` even after compression
+/// - **422 Unprocessable Entity**: `EMPTY_BATCH`, the `emails` array is empty; `INVALID_FIELD`,
+///   an address is longer than 320 bytes or contains control characters (`error.details.field`
+///   names it, e.g. `emails[3]`)
+/// - **503 Service Unavailable**: `READ_ONLY`, a batch that would be queued was sent to a
+///   read-only instance; batches of up to 10 addresses are still validated
+///
+/// Repeated addresses (compared after trimming and lowercasing the domain) with the
+/// same options are validated once; every input position still gets its own result, in
+/// input order. DNS and disposable checks run once per domain of the batch. With the
+/// account's `dedupe_tagged_addresses` setting, a sub-addressed address such as
+/// `user+news@gmail.com` is also a repeat of `user@gmail.com`.
+///
+/// ## Example Request
+/// ```json
+/// { "emails": ["user1@example.com", { "email": "info@example.com", "check_role_based": true },
+///              { "email": "user3@example.com", "checks": ["dns"] }] }
+/// ```
+#[utoipa::path(
+    post,
+    path = "/api/v1/validate-emails-bulk",
+    request_body = BulkEmailRequest,
+    params(
+        ("check_role_based" = Option<bool>, Query, description = "Enable role-based email validation"),
+        ("block_duplicates" = Option<bool>, Query, description = "Reject jobs that duplicate a recent job"),
+        ("lenient" = Option<bool>, Query, description = "Report non-string entries as INVALID_INPUT instead of rejecting the batch"),
+        ("verbosity" = Option<String>, Query, description = "minimal, standard (default) or full"),
+        ("fields" = Option<String>, Query, description = "Comma-separated validation fields to return, e.g. is_valid,error.code"),
+        ("lang" = Option<String>, Query, description = "Language of messages: en, es, de or fr; overrides Accept-Language"),
+        ("only" = Option<String>, Query, description = "Return only valid or invalid results"),
+        ("error_code" = Option<String>, Query, description = "Return only results failing with this code, e.g. DISPOSABLE_EMAIL"),
+        ("page" = Option<usize>, Query, description = "Page of results to return, from 1"),
+        ("per_page" = Option<usize>, Query, description = "Results per page, 100 by default and at most 1000")
+    ),
+    responses(
+        (status = 200, description = "Bulk validation results", body = BulkEmailValidationResponse),
+        (status = 202, description = "Bulk validation job queued: `{ \"job_id\", \"status\" }`"),
+        (status = 400, description = "INVALID_INPUT (a malformed entry in strict mode), INVALID_FIELDS, INVALID_VERBOSITY, INVALID_LANG, INVALID_FILTER or INVALID_PAGE", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 409, description = "DUPLICATE_JOB: job duplicates a recently submitted job", body = ErrorEnvelope),
+        (status = 413, description = "BATCH_TOO_LARGE, PAYLOAD_TOO_LARGE or JOB_TOO_LARGE: batch, body or queued job exceeds its limit", body = ErrorEnvelope),
+        (status = 422, description = "EMPTY_BATCH or INVALID_FIELD: the emails array is empty or an address is malformed", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: a dependency is unavailable; READ_ONLY: batches over 10 addresses can't be queued on a read-only instance", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[post("/validate-emails-bulk")]
+pub async fn validate_emails_bulk(
+    req: web::Json<RawBulkEmailRequest>,
+    query: web::Query<ValidationQuery>,
+    result_query: web::Query<ResultQuery>,
+    backends: web::Data<ValidationBackends>,
+    job_queue: web::Data<JobQueue>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: actix_web::HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let shape = query.response_shape()?;
+    let lang = Lang::requested(query.lang.as_deref(), &http_req)?;
+    let filter = result_query.filter()?;
+    let paging = result_query.paging()?;
+    // Malformed entries fail the whole request like any malformed body, unless lenient
+    let items = parse_bulk_items(req.into_inner().emails);
+    let malformed: Vec<usize> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| item.is_err().then_some(i))
+        .collect();
+    if let Some(&first) = malformed.first()
+        && !query.lenient
+    {
+        return Err(ApiError::validation(
+            "INVALID_INPUT",
+            format!(
+                "emails[{}] is neither an address nor an entry object; pass lenient=true to skip malformed entries",
+                first
+            ),
+        )
+        .with_details(json!({ "invalid_indices": malformed })));
+    }
+
+    let account = AuthedAccount::from_http(&http_req)?;
+    if let Some(flags) = http_req.app_data::<web::Data<FeatureFlags>>() {
+        flags.require(Flag::BulkEnabled).await?;
+    }
+
+    if let Some(rejection) = batch_size_rejection(items.len(), bulk::max_batch_size()) {
+        return Err(rejection);
+    }
+    for (i, item) in items.iter().enumerate() {
+        if let Ok(item) = item {
+            input_limits::check_email_field(&format!("emails[{}]", i), &item.email)?;
+        }
+    }
+    let entries: Vec<&BulkEmailItem> = items.iter().filter_map(|i| i.as_ref().ok()).collect();
+    let emails: Vec<String> = entries.iter().map(|item| item.email.clone()).collect();
+    let tenant_id = account.tenant_id();
+    let settings = account_settings::load_settings(&mongo_client, &tenant_id)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?
+        .unwrap_or_else(|| AccountSettings::unset(&tenant_id));
+    let request_options = query.options(&settings.options());
+    // Each distinct address and set of checks is validated, and charged, once
+    let checks: Vec<Checks> = entries
+        .iter()
+        .map(|item| item.options.with_defaults(&request_options).checks(false))
+        .collect();
+    let (batch, checks) = if settings.dedupes_tagged() {
+        DedupedBatch::merging_tags(&emails, &checks)
+    } else {
+        DedupedBatch::with_keys(&emails, &checks)
+    };
+
+    // For large batches (>10 emails), use job queue
+    if emails.len() > 10 {
+        if read_only::is_enabled() {
+            return Err(read_only::read_only_error());
+        }
+        let duplicate_config = DuplicateDetectionConfig::from_env();
+        // Duplicate detection is advisory; a Redis hiccup here must not block submission
+        let duplicate = job_queue
+            .find_duplicate_job(&tenant_id, &emails, &duplicate_config)
+            .await
+            .ok()
+            .flatten();
+
+        if let Some(duplicate) = &duplicate
+            && (query.block_duplicates || duplicate_config.action == DuplicateAction::Block)
+        {
+            return Err(ApiError::validation(
+                "DUPLICATE_JOB",
+                "Submission overlaps a recently submitted job",
+            )
+            .with_status(StatusCode::CONFLICT)
+            .with_details(json!({
+                "duplicate_of": duplicate.job_id,
+                "overlap_percent": duplicate.overlap_percent
+            })));
+        }
+
+        metering::charge_request(&http_req, batch.units()).await?;
+        // The job carries the role-based flag; default checks travel with each entry
+        let entry_defaults = ItemOptions {
+            check_role_based: None,
+            checks: request_options.checks.clone(),
+        };
+        let item_options = entries
+            .iter()
+            .map(|item| item.options.with_defaults(&entry_defaults))
+            .enumerate()
+            .filter(|(_, options)| !options.is_default())
+            .collect();
+        match job_queue
+            .enqueue_bulk_validation_with_options(
+                &tenant_id,
+                account.plan,
+                emails.clone(),
+                request_options.check_role_based.unwrap_or(false),
+                item_options,
+                settings.dedupes_tagged(),
+            )
+            .await
+        {
+            Ok(job_id) => {
+                let mut body = json!({
+                    "job_id": job_id,
+                    "status": "queued",
+                    "message": "Bulk validation job queued for processing"
+                });
+                if let Some(duplicate) = duplicate {
+                    body["duplicate_of"] = json!(duplicate.job_id);
+                    body["overlap_percent"] = json!(duplicate.overlap_percent);
+                    body["warning"] = json!("Submission overlaps a recently submitted job");
+                }
+                if !malformed.is_empty() {
+                    // Malformed entries are not queued; report where they were
+                    body["invalid_indices"] = json!(malformed);
+                }
+                return Ok(HttpResponse::Accepted().json(body));
+            }
+            Err(e) if job_payload::is_too_large(&e) => {
+                return Err(ApiError::validation(
+                    "JOB_TOO_LARGE",
+                    "The batch is too large to queue; split it into smaller jobs",
+                )
+                .with_status(StatusCode::PAYLOAD_TOO_LARGE));
+            }
+            Err(_) => {
+                // Fallback to immediate processing if queue fails
+            }
+        }
+    }
+
+    // Process immediately for small batches or queue failure; larger batches were
+    // charged before queueing
+    if emails.len() <= 10 {
+        metering::charge_request(&http_req, batch.units()).await?;
+    }
+    let lists = caller_lists(&http_req, &mongo_client, &emails).await?;
+    let validations = ValidationService::new(&backends)
+        .for_tenant(&tenant_id)
+        .validate_batch(&batch.unique, &checks, &lists)
+        .await;
+    let mut results = batch.fan_out(&validations).into_iter();
+    let mut validation_results = Vec::new();
+    let mut history = Vec::with_capacity(emails.len());
+    let mut valid_count = 0;
+    let mut invalid_count = 0;
+
+    for item in items {
+        let (email, mut validation) = match item {
+            Ok(BulkEmailItem { email, .. }) => {
+                let mut validation = results.next().unwrap_or_else(invalid_input_response);
+                if validation.has_tag.is_some() {
+                    // A merged entry reports its own address, not the base one validated
+                    validation.set_tag(&email);
+                }
+                history.push(HistoryRecord::new(
+                    &tenant_id,
+                    &email,
+                    validation.is_valid,
+                    validation.error.as_ref().map(|e| e.code.as_str()),
+                    ValidationSource::Bulk,
+                ));
+                (email, validation)
+            }
+            Err(raw) => (raw, invalid_input_response()),
+        };
+        if let Some(error) = &mut validation.error {
+            i18n::localize(error, lang);
+        }
+        if validation.is_valid {
+            valid_count += 1;
+        } else {
+            invalid_count += 1;
+        }
+        validation_results.push(BulkEmailValidationResult { email, validation });
+    }
+    validation_history::record(&mongo_client, history);
+
+    // The counts cover the whole batch; only the returned results are narrowed
+    let narrowed = !filter.is_empty() || paging.is_some();
+    validation_results.retain(|row| filter.matches(&row.validation));
+    let total = validation_results.len();
+    if let Some((page, per_page)) = paging {
+        validation_results = job_results::page_of(validation_results, page, per_page);
+    }
+    let response = BulkEmailValidationResponse {
+        results: validation_results,
+        valid_count,
+        invalid_count,
+        unique_count: batch.unique.len() as i32,
+        total: narrowed.then_some(total),
+        page: paging.map(|(page, _)| page),
+        per_page: paging.map(|(_, per_page)| per_page),
+    };
+    if shape.is_standard() {
+        return Ok(HttpResponse::Ok().json(response));
+    }
+    let mut body = json!(response);
+    if let Some(results) = body["results"].as_array_mut() {
+        for result in results {
+            result["validation"] = shape.apply(result["validation"].take());
+        }
+    }
+    Ok(HttpResponse::Ok().json(body))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/job-status/{job_id}",
+    params(("job_id" = String, Path, description = "Job ID returned when the job was queued")),
+    responses(
+        (status = 200, description = "Job status: `{ \"job_id\", \"status\", \"created_at\" }`"),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 404, description = "JOB_NOT_FOUND", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the job queue is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[actix_web::get("/job-status/{job_id}")]
+pub async fn get_job_status(
+    path: web::Path<String>,
+    job_queue: web::Data<JobQueue>,
+    account: AuthedAccount,
+) -> Result<impl Responder, ApiError> {
+    let job_id = path.into_inner();
+
+    let job = load_owned_job(&job_queue, &job_id, &account.tenant_id()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "job_id": job.id,
+        "status": job.status,
+        "created_at": job.created_at
+    })))
+}
+
+pub(crate) fn job_not_found() -> ApiError {
+    ApiError::not_found("JOB_NOT_FOUND", "Job not found")
+}
+
+/// The job, if `tenant_id` submitted it
+///
+/// Other accounts' jobs are reported as missing so their IDs can't be probed. Every
+/// REST, GraphQL and gRPC handler taking a job ID goes through here.
+pub(crate) async fn load_owned_job(
+    job_queue: &JobQueue,
+    job_id: &str,
+    tenant_id: &str,
+) -> Result<BulkValidationJob, ApiError> {
+    match job_queue.get_job_status(job_id).await {
+        Ok(Some(job)) if job.tenant_id == tenant_id => Ok(job),
+        Ok(_) => Err(job_not_found()),
+        Err(e) => Err(ApiError::upstream("job queue", e)),
+    }
+}
+
+/// The job, unless it is yet to complete, so its results can be read
+pub(crate) fn require_completed(job: BulkValidationJob) -> Result<BulkValidationJob, ApiError> {
+    if job.status != JobStatus::Completed {
+        return Err(ApiError::validation(
+            "JOB_NOT_COMPLETE",
+            "Results are available once the job has completed",
+        )
+        .with_status(StatusCode::CONFLICT)
+        .with_details(json!({ "status": job.status })));
+    }
+    Ok(job)
+}
+
+/// Header row of job result downloads
+const RESULTS_CSV_HEADER: &str = "email,is_valid,status,error_code,error_message\n";
+
+/// Quotes a CSV field when it contains a delimiter, quote or line break
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Renders result rows as CSV lines matching [`RESULTS_CSV_HEADER`]
+pub fn results_to_csv(rows: &[BulkEmailValidationResult]) -> String {
+    let mut csv = String::new();
+    for row in rows {
+        let validation = &row.validation;
+        let (code, message) = validation
+            .error
+            .as_ref()
+            .map(|e| (e.code.as_str(), e.message.as_str()))
+            .unwrap_or_default();
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            csv_field(&row.email),
+            validation.is_valid,
+            csv_field(validation.status.as_deref().unwrap_or_default()),
+            csv_field(code),
+            csv_field(message)
+        ));
+    }
+    csv
+}
+
+/// # Download Job Results
+///
+/// Streams a completed job's results as CSV
+/// (`email,is_valid,status,error_code,error_message`), reading one stored chunk at a
+/// time so large jobs never sit in memory. Clients sending `Accept-Encoding: gzip`
+/// (or `br`/`zstd`) get the stream compressed on the fly. `only=valid|invalid` and
+/// `error_code` (e.g. `DISPOSABLE_EMAIL`) keep just the matching rows; `page` and
+/// `per_page` return one page of them, with the matching rows across all pages in
+/// `X-Total-Count`.
+///
+/// ## Responses
+/// - **200 OK**: Chunked `text/csv` body
+/// - **400 Bad Request**: `INVALID_FILTER` or `INVALID_PAGE`, the filters or page are
+///   not understood
+/// - **404 Not Found**: Unknown, expired, or another account's job
+/// - **409 Conflict**: `JOB_NOT_COMPLETE`, the job is still queued or running
+#[utoipa::path(
+    get,
+    path = "/api/v1/job-results/{job_id}",
+    params(
+        ("job_id" = String, Path, description = "Job ID returned when the job was queued"),
+        ("only" = Option<String>, Query, description = "Keep only valid or invalid results"),
+        ("error_code" = Option<String>, Query, description = "Keep only results failing with this code, e.g. DISPOSABLE_EMAIL"),
+        ("page" = Option<usize>, Query, description = "Page of results to return, from 1"),
+        ("per_page" = Option<usize>, Query, description = "Results per page, 100 by default and at most 1000")
+    ),
+    responses(
+        (status = 200, description = "Job results as CSV", content_type = "text/csv"),
+        (status = 400, description = "INVALID_FILTER or INVALID_PAGE", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 404, description = "JOB_NOT_FOUND", body = ErrorEnvelope),
+        (status = 409, description = "JOB_NOT_COMPLETE: results are not available yet", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[actix_web::get("/job-results/{job_id}", wrap = "Compress::default()")]
+pub async fn download_job_results(
+    path: web::Path<String>,
+    query: web::Query<ResultQuery>,
+    job_queue: web::Data<JobQueue>,
+    account: AuthedAccount,
+) -> Result<impl Responder, ApiError> {
+    let job_id = path.into_inner();
+    let filter = query.filter()?;
+    let paging = query.paging()?;
+
+    let job = require_completed(load_owned_job(&job_queue, &job_id, &account.tenant_id()).await?)?;
+    let filename = format!("attachment; filename=\"{}.csv\"", job_id);
+
+    if let Some((page, per_page)) = paging {
+        let result_page = job_results::read_page(&job_queue, &job, &filter, page, per_page)
+            .await
+            .map_err(|e| ApiError::upstream("job queue", e))?;
+        return Ok(HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header(("Content-Disposition", filename))
+            .insert_header(("X-Total-Count", result_page.total.to_string()))
+            .body(format!(
+                "{}{}",
+                RESULTS_CSV_HEADER,
+                results_to_csv(&result_page.rows)
+            )));
+    }
+
+    let chunk_count = job_queue
+        .result_chunk_count(&job_id)
+        .await
+        .map_err(|e| ApiError::upstream("job queue", e))?;
+    let job_queue = job_queue.get_ref().clone();
+
+    let header = stream::once(async {
+        Ok::<_, actix_web::Error>(web::Bytes::from_static(RESULTS_CSV_HEADER.as_bytes()))
+    });
+    let rows = stream::iter(0..chunk_count).then(move |index| {
+        let job_queue = job_queue.clone();
+        let job_id = job_id.clone();
+        let filter = filter.clone();
+        async move {
+            let chunk = job_queue
+                .result_chunk(&job_id, index)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?
+                .ok_or_else(|| actix_web::error::ErrorGone("Job results expired"))?;
+            let mut rows: Vec<BulkEmailValidationResult> =
+                serde_json::from_str(&chunk).map_err(actix_web::error::ErrorInternalServerError)?;
+            rows.retain(|row| filter.matches(&row.validation));
+            Ok(web::Bytes::from(results_to_csv(&rows)))
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header(("Content-Disposition", filename))
+        .streaming(header.chain(rows)))
+}
+
+/// One page of a completed job's results, in input order
+#[derive(Serialize, ToSchema)]
+pub struct JobResultsPage {
+    pub results: Vec<BulkEmailValidationResult>,
+    pub page: usize,
+    pub per_page: usize,
+    /// Results matching `only` and `error_code`, across all pages
+    pub total: usize,
+}
+
+/// # Job Results Page
+///
+/// One page of a completed job's results as JSON, so clients can page through a large
+/// job or fetch just the entries they act on (`only=invalid`,
+/// `error_code=DISPOSABLE_EMAIL`) without downloading every row.
+///
+/// ## Request
+/// - Query Parameters:
+///   - `page` (optional): 1-based page number, default 1
+///   - `per_page` (optional): Results per page, default 100 and at most 1000
+///   - `only` (optional): `valid` or `invalid`
+///   - `error_code` (optional): Keep only the results failing with this code
+///
+/// ## Responses
+/// - **200 OK**: `{ results, page, per_page, total }`
+/// - **400 Bad Request**: `INVALID_PAGE` or `INVALID_FILTER`
+/// - **404 Not Found**: Unknown, expired, or another account's job
+/// - **409 Conflict**: `JOB_NOT_COMPLETE`, the job is still queued or running
+#[utoipa::path(
+    get,
+    path = "/api/v1/jobs/{job_id}/results",
+    params(
+        ("job_id" = String, Path, description = "Job ID returned when the job was queued"),
+        ("page" = Option<usize>, Query, description = "Page of results to return, from 1"),
+        ("per_page" = Option<usize>, Query, description = "Results per page, 100 by default and at most 1000"),
+        ("only" = Option<String>, Query, description = "Return only valid or invalid results"),
+        ("error_code" = Option<String>, Query, description = "Return only results failing with this code, e.g. DISPOSABLE_EMAIL")
+    ),
+    responses(
+        (status = 200, description = "One page of the job's results", body = JobResultsPage),
+        (status = 400, description = "INVALID_PAGE or INVALID_FILTER", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 404, description = "JOB_NOT_FOUND", body = ErrorEnvelope),
+        (status = 409, description = "JOB_NOT_COMPLETE: results are not available yet", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the job queue is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[actix_web::get("/jobs/{job_id}/results")]
+pub async fn job_results_page(
+    path: web::Path<String>,
+    query: web::Query<ResultQuery>,
+    job_queue: web::Data<JobQueue>,
+    account: AuthedAccount,
+) -> Result<impl Responder, ApiError> {
+    let job_id = path.into_inner();
+    let (page, per_page) = query.page()?;
+    let filter = query.filter()?;
+
+    let job = require_completed(load_owned_job(&job_queue, &job_id, &account.tenant_id()).await?)?;
+
+    let result_page = job_results::read_page(&job_queue, &job, &filter, page, per_page)
+        .await
+        .map_err(|e| ApiError::upstream("job queue", e))?;
+    Ok(HttpResponse::Ok().json(JobResultsPage {
+        results: result_page.rows,
+        page,
+        per_page,
+        total: result_page.total,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    /// `csv`, `jsonl` or `xlsx`
+    pub format: String,
+}
+
+/// # Export Job Results
+///
+/// A completed job's annotated results (`email`, `verdict`, `error_code`, `score`) as
+/// CSV, JSON lines or an Excel workbook, ready to re-import into an ESP. CSV and JSONL
+/// are streamed one stored chunk at a time; XLSX is assembled in memory and limited to
+/// [`job_export::XLSX_MAX_ROWS`] rows.
+///
+/// ## Responses
+/// - **200 OK**: The export as an attachment
+/// - **400 Bad Request**: `INVALID_FORMAT`
+/// - **404 Not Found**: Unknown, expired, or another account's job
+/// - **409 Conflict**: `JOB_NOT_COMPLETE`, the job is still queued or running
+/// - **422 Unprocessable Entity**: `EXPORT_TOO_LARGE`, too many rows for one worksheet
+#[utoipa::path(
+    get,
+    path = "/api/v1/job-results/{job_id}/export",
+    params(
+        ("job_id" = String, Path, description = "Job ID returned when the job was queued"),
+        ("format" = String, Query, description = "csv, jsonl or xlsx")
+    ),
+    responses(
+        (status = 200, description = "Job results in the requested format"),
+        (status = 400, description = "INVALID_FORMAT", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 404, description = "JOB_NOT_FOUND", body = ErrorEnvelope),
+        (status = 409, description = "JOB_NOT_COMPLETE: results are not available yet", body = ErrorEnvelope),
+        (status = 422, description = "EXPORT_TOO_LARGE: use csv or jsonl", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[actix_web::get("/job-results/{job_id}/export", wrap = "Compress::default()")]
+pub async fn export_job_results(
+    path: web::Path<String>,
+    query: web::Query<ExportQuery>,
+    job_queue: web::Data<JobQueue>,
+    account: AuthedAccount,
+) -> Result<impl Responder, ApiError> {
+    let job_id = path.into_inner();
+    let format = ExportFormat::parse(&query.format).ok_or_else(|| {
+        ApiError::validation("INVALID_FORMAT", "format must be one of csv, jsonl, xlsx")
+    })?;
+
+    require_completed(load_owned_job(&job_queue, &job_id, &account.tenant_id()).await?)?;
+
+    let chunk_count = job_queue
+        .result_chunk_count(&job_id)
+        .await
+        .map_err(|e| ApiError::upstream("job queue", e))?;
+    let filename = format!("attachment; filename=\"{}.{}\"", job_id, format.extension());
+    let mut response = HttpResponse::Ok();
+    response
+        .content_type(format.content_type())
+        .insert_header(("Content-Disposition", filename));
+
+    if format == ExportFormat::Xlsx {
+        let mut rows = Vec::new();
+        for index in 0..chunk_count {
+            let chunk = job_queue
+                .result_chunk(&job_id, index)
+                .await
+                .map_err(|e| ApiError::upstream("job queue", e))?
+                .ok_or_else(job_not_found)?;
+            let results: Vec<BulkEmailValidationResult> =
+                serde_json::from_str(&chunk).map_err(ApiError::internal)?;
+            rows.extend(results.iter().map(ExportRow::from));
+            if rows.len() > job_export::XLSX_MAX_ROWS {
+                return Err(ApiError::validation(
+                    "EXPORT_TOO_LARGE",
+                    "The job has more results than fit in a worksheet; export csv or jsonl",
+                )
+                .with_status(StatusCode::UNPROCESSABLE_ENTITY)
+                .with_details(json!({ "max_rows": job_export::XLSX_MAX_ROWS })));
+            }
+        }
+        let workbook = web::block(move || job_export::xlsx_workbook(&rows))
+            .await
+            .map_err(ApiError::internal)?
+            .map_err(ApiError::internal)?;
+        return Ok(response.body(workbook));
+    }
+
+    let job_queue = job_queue.get_ref().clone();
+    let header = stream::once(async move {
+        Ok::<_, actix_web::Error>(web::Bytes::from(job_export::header(format)))
+    });
+    let rows = stream::iter(0..chunk_count).then(move |index| {
+        let job_queue = job_queue.clone();
+        let job_id = job_id.clone();
+        async move {
+            let chunk = job_queue
+                .result_chunk(&job_id, index)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?
+                .ok_or_else(|| actix_web::error::ErrorGone("Job results expired"))?;
+            let rows: Vec<BulkEmailValidationResult> =
+                serde_json::from_str(&chunk).map_err(actix_web::error::ErrorInternalServerError)?;
+            Ok(web::Bytes::from(job_export::render_chunk(format, &rows)))
+        }
+    });
+    Ok(response.streaming(header.chain(rows)))
+}
+
+/// # Job Results Summary
+///
+/// Verdict counts and the most common domains of a completed job. Reports are
+/// computed once and cached until the job's status changes, so dashboards can poll
+/// historical jobs cheaply.
+///
+/// ## Responses
+/// - **200 OK**: `{ "job_id", "total", "valid", "invalid", "verdicts": { "VALID": 10, ... }, "top_domains": [{ "domain", "count" }] }`
+/// - **404 Not Found**: Unknown, expired, or another account's job
+/// - **409 Conflict**: `JOB_NOT_COMPLETE`, the job is still queued or running
+#[utoipa::path(
+    get,
+    path = "/api/v1/job-results/{job_id}/summary",
+    params(("job_id" = String, Path, description = "Job ID returned when the job was queued")),
+    responses(
+        (status = 200, description = "Job results summary", body = JobSummary),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 404, description = "JOB_NOT_FOUND", body = ErrorEnvelope),
+        (status = 409, description = "JOB_NOT_COMPLETE: results are not available yet", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[actix_web::get("/job-results/{job_id}/summary")]
+pub async fn job_results_summary(
+    path: web::Path<String>,
+    job_queue: web::Data<JobQueue>,
+    account: AuthedAccount,
+) -> Result<impl Responder, ApiError> {
+    let job_id = path.into_inner();
+
+    require_completed(load_owned_job(&job_queue, &job_id, &account.tenant_id()).await?)?;
+
+    let summary = job_summary(&job_queue, &job_id)
+        .await
+        .map_err(|e| ApiError::upstream("job queue", e))?;
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+/// # Job Hygiene Report
+///
+/// List hygiene of a completed job: shares of invalid, disposable, role-based and
+/// invalid-domain addresses, the duplicate rate, the domains with the most addresses
+/// to suppress, and an overall quality score graded A to F. Cached like the summary.
+///
+/// ## Responses
+/// - **200 OK**: `{ "job_id", "total", "unique", "invalid_percent", "disposable_percent", "role_based_percent", "invalid_domain_percent", "duplicate_percent", "top_risky_domains": [{ "domain", "count" }], "quality_score", "grade" }`
+/// - **404 Not Found**: Unknown, expired, or another account's job
+/// - **409 Conflict**: `JOB_NOT_COMPLETE`, the job is still queued or running
+#[utoipa::path(
+    get,
+    path = "/api/v1/job-report/{job_id}",
+    params(("job_id" = String, Path, description = "Job ID returned when the job was queued")),
+    responses(
+        (status = 200, description = "List hygiene report", body = ListReport),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 404, description = "JOB_NOT_FOUND", body = ErrorEnvelope),
+        (status = 409, description = "JOB_NOT_COMPLETE: results are not available yet", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[actix_web::get("/job-report/{job_id}")]
+pub async fn job_hygiene_report(
+    path: web::Path<String>,
+    job_queue: web::Data<JobQueue>,
+    account: AuthedAccount,
+) -> Result<impl Responder, ApiError> {
+    let job_id = path.into_inner();
+
+    require_completed(load_owned_job(&job_queue, &job_id, &account.tenant_id()).await?)?;
+
+    let report = job_report(&job_queue, &job_id)
+        .await
+        .map_err(|e| ApiError::upstream("job queue", e))?;
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// # Share Job Results
+///
+/// Issues a signed link to a read-only HTML view of a completed job's results
+/// (summary charts and a paginated table), for people without API access. Anyone
+/// holding the link can view the results until it expires after `SHARE_LINK_TTL`
+/// seconds (default 3600).
+///
+/// ## Responses
+/// - **201 Created**: `{ "url": "/share/{token}", "expires_at": "2024-01-01T12:00:00+00:00" }`
+/// - **404 Not Found**: Unknown, expired, or another account's job
+/// - **409 Conflict**: `JOB_NOT_COMPLETE`, the job is still queued or running
+#[utoipa::path(
+    post,
+    path = "/api/v1/job-results/{job_id}/share",
+    params(("job_id" = String, Path, description = "Job ID returned when the job was queued")),
     responses(
-        (status = 200, description = "Email is valid"),
-        (status = 400, description = "Invalid email"),
-        (status = 500, description = "Server error")
+        (status = 201, description = "Viewer link: `{ \"url\", \"expires_at\" }`"),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 404, description = "JOB_NOT_FOUND", body = ErrorEnvelope),
+        (status = 409, description = "JOB_NOT_COMPLETE: results are not available yet", body = ErrorEnvelope)
     ),
     tag = "Email Validation"
 )]
-#[post("/validate-email")]
-pub async fn validate_email(
-    req: web::Json<EmailRequest>,
-    query: web::Query<ValidationQuery>,
-    redis_cache: web::Data<RedisCache>,
-    mongo_client: web::Data<MongoClient>,
-    http_req: actix_web::HttpRequest,
-) -> Result<impl Responder, actix_web::Error> {
-    // Check API key
-    let auth_header = http_req
-        .headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|s| s.strip_prefix("Bearer "))
-        .ok_or_else(|| actix_web::error::ErrorUnauthorized("Missing Authorization header"))?;
-
-    let db = mongo_client.database("email_sanitizer");
-    let collection: mongodb::Collection<crate::auth::ApiKey> = db.collection("api_keys");
-
-    match collection
-        .find_one(mongodb::bson::doc! { "key": auth_header, "active": true })
-        .await
-    {
-        Ok(Some(_)) => {}
-        _ => return Err(actix_web::error::ErrorUnauthorized("Invalid API key")),
-    }
-    let email = req.email.trim();
-
-    // 1. Syntax validation
-    if !syntax::is_valid_email(email) {
-        return Ok(HttpResponse::BadRequest().json(json!({
-            "error": "INVALID_SYNTAX",
-            "message": "Email address has invalid syntax"
-        })));
-    }
-
-    // Extract domain for DNS validation
-    let parts: Vec<&str> = email.split('@').collect();
-    let domain = parts[1];
-
-    // 2. DNS/MX validation (with cache)
-    let dns_valid = match redis_cache.get_dns_validation(domain).await {
-        // Cache hit
-        Ok(Some(cached_result)) => cached_result,
-
-        // Cache miss or error - perform DNS lookup
-        _ => {
-            let email_clone = email.to_owned();
-            let dns_result = web::block(move || dnsmx::validate_email_dns(&email_clone))
-                .await
-                .map_err(|e| {
-                    actix_web::error::ErrorInternalServerError(format!(
-                        "DNS validation error: {}",
-                        e
-                    ))
-                })?;
+#[post("/job-results/{job_id}/share")]
+pub async fn share_job_results(
+    path: web::Path<String>,
+    job_queue: web::Data<JobQueue>,
+    account: AuthedAccount,
+) -> Result<impl Responder, ApiError> {
+    let job_id = path.into_inner();
 
-            // Cache the result (ignore cache write errors)
-            let _ = redis_cache.set_dns_validation(domain, dns_result).await;
+    require_completed(load_owned_job(&job_queue, &job_id, &account.tenant_id()).await?)?;
 
-            dns_result
-        }
-    };
+    let (token, expires_at) = share::sign_share_token(&job_id).map_err(ApiError::internal)?;
+    Ok(HttpResponse::Created().json(json!({
+        "url": format!("/share/{}", token),
+        "expires_at": chrono::DateTime::from_timestamp(expires_at, 0).map(|t| t.to_rfc3339())
+    })))
+}
 
-    if !dns_valid {
-        return Ok(HttpResponse::BadRequest().json(json!({
-            "error": "INVALID_DOMAIN",
-            "message": "Email domain has no valid DNS records"
-        })));
-    }
-
-    // 3. Role-based email check (optional)
-    if query.check_role_based {
-        match role_based::is_role_based_email(email).await {
-            Ok(true) => {
-                return Ok(HttpResponse::BadRequest().json(json!({
-                    "error": "ROLE_BASED_EMAIL",
-                    "message": "Email address uses a role-based local part"
-                })));
-            }
-            Ok(false) => {} // Continue validation
-            Err(e) => {
-                return Ok(HttpResponse::InternalServerError().json(json!({
-                    "error": "DATABASE_ERROR",
-                    "message": e
-                })));
-            }
-        }
-    }
+/// Jobs per page of the job listing
+pub const JOBS_PAGE_SIZE: usize = 20;
 
-    // 4. Disposable email check
-    match disposable::is_disposable_email(email).await {
-        Ok(true) => Ok(HttpResponse::BadRequest().json(json!({
-            "error": "DISPOSABLE_EMAIL",
-            "message": "The email address domain is a provider of disposable email addresses"
-        }))),
-        Ok(false) => Ok(HttpResponse::Ok().json(json!({
-            "status": "VALID",
-            "message": "Email address is valid"
-        }))),
-        Err(e) => Ok(HttpResponse::InternalServerError().json(json!({
-            "error": "DATABASE_ERROR",
-            "message": e.to_string()
-        }))),
-    }
+#[derive(Deserialize)]
+pub struct JobListQuery {
+    /// Only list jobs in this status (`pending`, `processing`, `completed`, `failed`)
+    pub status: Option<String>,
+    /// 1-based page number
+    pub page: Option<usize>,
 }
 
-pub async fn validate_single_email(
-    email: &str,
-    check_role_based: bool,
-    redis_cache: &RedisCache,
-) -> EmailValidationResponse {
-    let email = email.trim();
-
-    // 1. Syntax validation
-    if !syntax::is_valid_email(email) {
-        return EmailValidationResponse {
-            is_valid: false,
-            status: None,
-            error: Some(EmailValidationError {
-                code: "INVALID_SYNTAX".to_string(),
-                message: "Email address has invalid syntax".to_string(),
-            }),
-        };
-    }
+/// A bulk job as listed for its account; the submitted addresses are left out
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobListEntry {
+    pub job_id: String,
+    #[schema(value_type = String, example = "Pending")]
+    pub status: JobStatus,
+    pub email_count: usize,
+    pub created_at: i64,
+    /// Last status change; absent while the job is still queued
+    pub updated_at: Option<i64>,
+}
 
-    // Extract domain for DNS validation
-    let parts: Vec<&str> = email.split('@').collect();
-    let domain = parts[1];
+/// Number of the account's jobs in each status, regardless of the `status` filter
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct JobCounts {
+    pub pending: usize,
+    pub processing: usize,
+    pub completed: usize,
+    pub failed: usize,
+}
 
-    // 2. DNS/MX validation (with cache)
-    let dns_valid = match redis_cache.get_dns_validation(domain).await {
-        Ok(Some(cached_result)) => cached_result,
-        _ => {
-            let email_clone = email.to_owned();
-            match web::block(move || dnsmx::validate_email_dns(&email_clone)).await {
-                Ok(dns_result) => {
-                    let _ = redis_cache.set_dns_validation(domain, dns_result).await;
-                    dns_result
-                }
-                Err(_) => false,
-            }
-        }
-    };
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobList {
+    pub jobs: Vec<JobListEntry>,
+    pub page: usize,
+    pub per_page: usize,
+    /// Jobs matching the filter, across all pages
+    pub total: usize,
+    pub counts: JobCounts,
+}
 
-    if !dns_valid {
-        return EmailValidationResponse {
-            is_valid: false,
-            status: None,
-            error: Some(EmailValidationError {
-                code: "INVALID_DOMAIN".to_string(),
-                message: "Email domain has no valid DNS records".to_string(),
-            }),
-        };
+fn parse_job_status(status: &str) -> Option<JobStatus> {
+    match status.trim().to_ascii_lowercase().as_str() {
+        "pending" => Some(JobStatus::Pending),
+        "processing" => Some(JobStatus::Processing),
+        "completed" => Some(JobStatus::Completed),
+        "failed" => Some(JobStatus::Failed),
+        _ => None,
     }
+}
 
-    // 3. Role-based email check (optional)
-    if check_role_based {
-        match role_based::is_role_based_email(email).await {
-            Ok(true) => {
-                return EmailValidationResponse {
-                    is_valid: false,
-                    status: None,
-                    error: Some(EmailValidationError {
-                        code: "ROLE_BASED_EMAIL".to_string(),
-                        message: "Email address uses a role-based local part".to_string(),
-                    }),
-                };
-            }
-            Ok(false) => {} // Continue validation
-            Err(e) => {
-                return EmailValidationResponse {
-                    is_valid: false,
-                    status: None,
-                    error: Some(EmailValidationError {
-                        code: "DATABASE_ERROR".to_string(),
-                        message: e,
-                    }),
-                };
-            }
+/// Filters and paginates an account's jobs, which are already ordered newest first
+fn job_list(jobs: Vec<BulkValidationJob>, status: Option<&JobStatus>, page: usize) -> JobList {
+    let mut counts = JobCounts::default();
+    for job in &jobs {
+        match job.status {
+            JobStatus::Pending => counts.pending += 1,
+            JobStatus::Processing => counts.processing += 1,
+            JobStatus::Completed => counts.completed += 1,
+            JobStatus::Failed => counts.failed += 1,
         }
     }
 
-    // 4. Disposable email check
-    match disposable::is_disposable_email(email).await {
-        Ok(true) => EmailValidationResponse {
-            is_valid: false,
-            status: None,
-            error: Some(EmailValidationError {
-                code: "DISPOSABLE_EMAIL".to_string(),
-                message: "The email address domain is a provider of disposable email addresses"
-                    .to_string(),
-            }),
-        },
-        Ok(false) => EmailValidationResponse {
-            is_valid: true,
-            status: Some("VALID".to_string()),
-            error: None,
-        },
-        Err(e) => EmailValidationResponse {
-            is_valid: false,
-            status: None,
-            error: Some(EmailValidationError {
-                code: "DATABASE_ERROR".to_string(),
-                message: e.to_string(),
-            }),
-        },
+    let matching: Vec<BulkValidationJob> = jobs
+        .into_iter()
+        .filter(|job| status.is_none_or(|s| &job.status == s))
+        .collect();
+    let total = matching.len();
+    let jobs = matching
+        .into_iter()
+        .skip((page - 1) * JOBS_PAGE_SIZE)
+        .take(JOBS_PAGE_SIZE)
+        .map(|job| JobListEntry {
+            email_count: job.email_count(),
+            job_id: job.id,
+            status: job.status,
+            created_at: job.created_at,
+            updated_at: job.updated_at,
+        })
+        .collect();
+
+    JobList {
+        jobs,
+        page,
+        per_page: JOBS_PAGE_SIZE,
+        total,
+        counts,
     }
 }
 
-/// # Bulk Email Validation Endpoint
-///
-/// Validates multiple email addresses in parallel by checking:
-/// 1. RFC-compliant syntax validation
-/// 2. Domain DNS/MX record verification (with Redis caching)
-/// 3. Role-based email address detection (optional, via query parameter)
-/// 4. Disposable email domain check
+/// # List Jobs
 ///
-/// ## Request
-/// - Method: POST
-/// - Body: JSON object with `emails` array field
-/// - Query Parameters:
-///   - `check_role_based` (optional): Set to `true` to enable role-based validation
-///
-/// ## Responses
-/// - **200 OK**: Returns validation results for all emails with counts
+/// Bulk jobs submitted by the calling account that are still on record (jobs expire
+/// an hour after submission), newest first, [`JOBS_PAGE_SIZE`] per page.
 ///
-/// ## Example Request
-/// ```json
-/// { "emails": ["user1@example.com", "user2@example.com"] }
-/// ```
+/// ## Query Parameters
+/// - `status`: only list `pending`, `processing`, `completed` or `failed` jobs
+/// - `page`: 1-based page number (default 1)
 #[utoipa::path(
-    post,
-    path = "/api/v1/validate-emails-bulk",
-    request_body = BulkEmailRequest,
+    get,
+    path = "/api/v1/jobs",
     params(
-        ("check_role_based" = Option<bool>, Query, description = "Enable role-based email validation")
+        ("status" = Option<String>, Query, description = "pending, processing, completed or failed"),
+        ("page" = Option<usize>, Query, description = "1-based page number")
     ),
     responses(
-        (status = 200, description = "Bulk validation results")
+        (status = 200, description = "The account's jobs", body = JobList),
+        (status = 400, description = "INVALID_STATUS or INVALID_PAGE", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the job queue is unavailable", body = ErrorEnvelope)
     ),
     tag = "Email Validation"
 )]
-#[post("/validate-emails-bulk")]
-pub async fn validate_emails_bulk(
-    req: web::Json<BulkEmailRequest>,
-    query: web::Query<ValidationQuery>,
-    redis_cache: web::Data<RedisCache>,
+#[actix_web::get("/jobs")]
+pub async fn list_jobs(
+    query: web::Query<JobListQuery>,
     job_queue: web::Data<JobQueue>,
-    mongo_client: web::Data<MongoClient>,
-    http_req: actix_web::HttpRequest,
-) -> Result<impl Responder, actix_web::Error> {
-    // Check API key
-    let auth_header = http_req
-        .headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|s| s.strip_prefix("Bearer "))
-        .ok_or_else(|| actix_web::error::ErrorUnauthorized("Missing Authorization header"))?;
-
-    let db = mongo_client.database("email_sanitizer");
-    let collection: mongodb::Collection<crate::auth::ApiKey> = db.collection("api_keys");
-
-    match collection
-        .find_one(mongodb::bson::doc! { "key": auth_header, "active": true })
-        .await
-    {
-        Ok(Some(_)) => {}
-        _ => return Err(actix_web::error::ErrorUnauthorized("Invalid API key")),
-    }
-    // For large batches (>10 emails), use job queue
-    if req.emails.len() > 10 {
-        match job_queue
-            .enqueue_bulk_validation(req.emails.clone(), query.check_role_based)
-            .await
-        {
-            Ok(job_id) => {
-                return Ok(HttpResponse::Accepted().json(json!({
-                    "job_id": job_id,
-                    "status": "queued",
-                    "message": "Bulk validation job queued for processing"
-                })));
-            }
-            Err(_) => {
-                // Fallback to immediate processing if queue fails
-            }
-        }
-    }
-
-    // Process immediately for small batches or queue failure
-    let validation_futures = req
-        .emails
-        .iter()
-        .map(|email| {
-            let email_clone = email.clone();
-            let redis_cache = redis_cache.get_ref().clone();
-            let check_role_based = query.check_role_based;
-            async move {
-                let validation =
-                    validate_single_email(&email_clone, check_role_based, &redis_cache).await;
-                (email_clone, validation)
-            }
-        })
-        .collect::<Vec<_>>();
-
-    let results = join_all(validation_futures).await;
-    let mut validation_results = Vec::new();
-    let mut valid_count = 0;
-    let mut invalid_count = 0;
-
-    for (email, validation) in results {
-        if validation.is_valid {
-            valid_count += 1;
-        } else {
-            invalid_count += 1;
-        }
-        validation_results.push(BulkEmailValidationResult { email, validation });
+    account: AuthedAccount,
+) -> Result<impl Responder, ApiError> {
+    let status = match query.status.as_deref() {
+        Some(status) => Some(parse_job_status(status).ok_or_else(|| {
+            ApiError::validation(
+                "INVALID_STATUS",
+                "status must be one of pending, processing, completed, failed",
+            )
+        })?),
+        None => None,
+    };
+    let page = query.page.unwrap_or(1);
+    if page == 0 {
+        return Err(ApiError::validation("INVALID_PAGE", "page starts at 1"));
     }
 
-    Ok(HttpResponse::Ok().json(BulkEmailValidationResponse {
-        results: validation_results,
-        valid_count,
-        invalid_count,
-    }))
+    let jobs = job_queue
+        .list_jobs(&account.tenant_id())
+        .await
+        .map_err(|e| ApiError::upstream("job queue", e))?;
+    Ok(HttpResponse::Ok().json(job_list(jobs, status.as_ref(), page)))
 }
 
+/// # Delete Job
+///
+/// Cancels a pending job by taking it off the queue, or purges a completed or failed
+/// job's record and results. Jobs a worker is processing can't be deleted until they
+/// finish.
+///
+/// ## Responses
+/// - **204 No Content**: The job was cancelled or purged
+/// - **404 Not Found**: Unknown, expired, or another account's job
+/// - **409 Conflict**: `JOB_RUNNING`, a worker has picked the job up
 #[utoipa::path(
-    get,
-    path = "/api/v1/job-status/{job_id}",
+    delete,
+    path = "/api/v1/jobs/{job_id}",
+    params(("job_id" = String, Path, description = "Job ID returned when the job was queued")),
     responses(
-        (status = 200, description = "Job status retrieved")
+        (status = 204, description = "Job cancelled or purged"),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 404, description = "JOB_NOT_FOUND", body = ErrorEnvelope),
+        (status = 409, description = "JOB_RUNNING: the job is being processed", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the job queue is unavailable", body = ErrorEnvelope)
     ),
     tag = "Email Validation"
 )]
-#[actix_web::get("/job-status/{job_id}")]
-pub async fn get_job_status(
+#[delete("/jobs/{job_id}")]
+pub async fn delete_job(
     path: web::Path<String>,
     job_queue: web::Data<JobQueue>,
-    mongo_client: web::Data<MongoClient>,
-    http_req: actix_web::HttpRequest,
-) -> Result<impl Responder, actix_web::Error> {
-    // Check API key
-    let auth_header = http_req
-        .headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|s| s.strip_prefix("Bearer "))
-        .ok_or_else(|| actix_web::error::ErrorUnauthorized("Missing Authorization header"))?;
-
-    let db = mongo_client.database("email_sanitizer");
-    let collection: mongodb::Collection<crate::auth::ApiKey> = db.collection("api_keys");
-
-    match collection
-        .find_one(mongodb::bson::doc! { "key": auth_header, "active": true })
-        .await
-    {
-        Ok(Some(_)) => {}
-        _ => return Err(actix_web::error::ErrorUnauthorized("Invalid API key")),
-    }
+    account: AuthedAccount,
+) -> Result<impl Responder, ApiError> {
     let job_id = path.into_inner();
 
-    match job_queue.get_job_status(&job_id).await {
-        Ok(Some(job)) => Ok(HttpResponse::Ok().json(json!({
-            "job_id": job.id,
-            "status": job.status,
-            "created_at": job.created_at
-        }))),
-        Ok(None) => Ok(HttpResponse::NotFound().json(json!({
-            "error": "Job not found"
-        }))),
-        Err(_) => Ok(HttpResponse::InternalServerError().json(json!({
-            "error": "Failed to retrieve job status"
-        }))),
+    let job = load_owned_job(&job_queue, &job_id, &account.tenant_id()).await?;
+
+    let deleted = match job.status {
+        JobStatus::Pending => job_queue.cancel_job(&job_id).await,
+        JobStatus::Processing => Ok(false),
+        JobStatus::Completed | JobStatus::Failed => job_queue
+            .purge_job(&job_id, &job.tenant_id)
+            .await
+            .map(|_| true),
+    }
+    .map_err(|e| ApiError::upstream("job queue", e))?;
+
+    if !deleted {
+        return Err(ApiError::validation(
+            "JOB_RUNNING",
+            "The job is being processed and can be deleted once it finishes",
+        )
+        .with_status(StatusCode::CONFLICT));
     }
+    Ok(HttpResponse::NoContent().finish())
 }
 
 /// Configures email validation routes under /api/v1
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(validate_email)
+        .service(validate_email_get)
+        .service(validate_email_async)
         .service(validate_emails_bulk)
-        .service(get_job_status);
+        .service(get_job_status)
+        .service(download_job_results)
+        .service(job_results_page)
+        .service(job_results_summary)
+        .service(job_hygiene_report)
+        .service(export_job_results)
+        .service(share_job_results)
+        .service(list_jobs)
+        .service(delete_job);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::stores::{MemoryDomainLists, MemoryStore};
     use actix_web::{App, test};
     use mongodb::{Client as MongoClient, options::ClientOptions};
     use serde_json::json;
@@ -579,10 +1919,14 @@ mod tests {
             env::var("TEST_REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
 
         // Use resilient Redis cache creation for tests
-        let redis_cache = RedisCache::new(&redis_url, 3600).unwrap_or_else(|_| {
-            eprintln!("Warning: Using dummy Redis cache for tests");
-            RedisCache::test_dummy()
-        });
+        let backends = RedisCache::new(&redis_url, 3600)
+            .map(|redis_cache| {
+                ValidationBackends::from_env(redis_cache, Arc::new(MemoryDomainLists::default()))
+            })
+            .unwrap_or_else(|_| {
+                eprintln!("Warning: Using in-memory stores for tests");
+                ValidationBackends::from_store(Arc::new(MemoryStore::default()))
+            });
 
         // Create JobQueue for tests
         let job_queue = JobQueue::new(&redis_url).unwrap_or_else(|_| {
@@ -596,7 +1940,7 @@ mod tests {
 
         test::init_service(
             App::new()
-                .app_data(web::Data::new(redis_cache))
+                .app_data(web::Data::new(backends))
                 .app_data(web::Data::new(job_queue))
                 .app_data(web::Data::new(mongo_client))
                 .configure(configure_routes),
@@ -720,6 +2064,209 @@ mod tests {
         assert_eq!(resp.status().as_u16(), 401);
     }
 
+    #[actix_web::test]
+    async fn test_download_job_results_requires_api_key() {
+        let app = create_test_app().await;
+        let req = create_test_request_with_auth("GET", "/job-results/some-job", None)
+            .insert_header(("Accept-Encoding", "gzip"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        // Expect 401 since we don't have a real API key in test DB
+        assert_eq!(resp.status().as_u16(), 401);
+    }
+
+    #[actix_web::test]
+    async fn test_validate_email_async_requires_api_key() {
+        let app = create_test_app().await;
+        let req = create_test_request_with_auth("POST", "/validate-email-async", None)
+            .set_json(json!({ "email": "user@example.com" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 401);
+    }
+
+    #[actix_web::test]
+    async fn test_job_management_requires_api_key() {
+        let app = create_test_app().await;
+        let req = create_test_request_with_auth("GET", "/jobs?status=pending", None).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 401);
+
+        let req = test::TestRequest::delete()
+            .uri("/jobs/some-job")
+            .insert_header(("Authorization", "Bearer test-api-key"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 401);
+    }
+
+    #[actix_web::test]
+    async fn test_job_list_filters_counts_and_paginates() {
+        let job = |i: usize, status: JobStatus| BulkValidationJob {
+            id: format!("job-{}", i),
+            emails: vec!["a@example.com".to_string(); i % 3],
+            check_role_based: false,
+            status,
+            created_at: 1000 - i as i64,
+            tenant_id: "owner@example.com".to_string(),
+            updated_at: None,
+            email_count: i % 3,
+            spilled: false,
+            item_options: Default::default(),
+            webhook_url: None,
+            dedupe_tagged: false,
+        };
+        let jobs: Vec<BulkValidationJob> = (0..25)
+            .map(|i| {
+                job(
+                    i,
+                    if i < 22 {
+                        JobStatus::Pending
+                    } else {
+                        JobStatus::Failed
+                    },
+                )
+            })
+            .collect();
+
+        let all = job_list(jobs, None, 2);
+        assert_eq!(all.total, 25);
+        assert_eq!(all.jobs.len(), 5);
+        assert_eq!(all.jobs[0].job_id, "job-20");
+        assert_eq!(all.counts.pending, 22);
+        assert_eq!(all.counts.failed, 3);
+
+        let jobs = (0..25).map(|i| job(i, JobStatus::Pending)).collect();
+        let failed = job_list(jobs, parse_job_status("FAILED").as_ref(), 1);
+        assert_eq!(failed.total, 0);
+        assert!(failed.jobs.is_empty());
+        assert_eq!(parse_job_status("unknown"), None);
+    }
+
+    #[actix_web::test]
+    async fn test_export_job_results_requires_api_key() {
+        let app = create_test_app().await;
+        let req =
+            create_test_request_with_auth("GET", "/job-results/some-job/export?format=xlsx", None)
+                .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 401);
+    }
+
+    #[actix_web::test]
+    async fn test_job_results_summary_requires_api_key() {
+        let app = create_test_app().await;
+        let req = create_test_request_with_auth("GET", "/job-results/some-job/summary", None)
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        // Expect 401 since we don't have a real API key in test DB
+        assert_eq!(resp.status().as_u16(), 401);
+    }
+
+    #[actix_web::test]
+    async fn test_job_report_requires_api_key() {
+        let app = create_test_app().await;
+        let req = create_test_request_with_auth("GET", "/job-report/some-job", None).to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 401);
+    }
+
+    #[actix_web::test]
+    async fn test_share_job_results_requires_api_key() {
+        let app = create_test_app().await;
+        let req =
+            create_test_request_with_auth("POST", "/job-results/some-job/share", None).to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 401);
+    }
+
+    /// Routes served to `email`, as if [`crate::auth::Auth`] had authenticated it
+    async fn create_app_as(
+        email: &'static str,
+        job_queue: JobQueue,
+    ) -> impl actix_web::dev::Service<
+        actix_http::Request,
+        Response = actix_web::dev::ServiceResponse,
+        Error = actix_web::Error,
+    > {
+        use actix_web::dev::Service;
+
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(job_queue))
+                .wrap_fn(move |req, srv| {
+                    req.extensions_mut().insert(AuthenticatedAccount {
+                        email: email.to_string(),
+                        ..AuthenticatedAccount::default()
+                    });
+                    srv.call(req)
+                })
+                .configure(configure_routes),
+        )
+        .await
+    }
+
+    #[actix_web::test]
+    async fn test_other_tenants_jobs_are_not_found() {
+        let redis_url =
+            env::var("TEST_REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        let Ok(job_queue) = JobQueue::new(&redis_url) else {
+            return;
+        };
+        let Ok(job_id) = job_queue
+            .enqueue_bulk_validation_for_tenant(
+                "owner@example.com",
+                crate::auth::PlanTier::Free,
+                vec!["someone@example.com".to_string()],
+                false,
+            )
+            .await
+        else {
+            // No Redis to queue the job on
+            return;
+        };
+
+        let owner = create_app_as("Owner@Example.com", job_queue.clone()).await;
+        let req = test::TestRequest::get()
+            .uri(&format!("/job-status/{}", job_id))
+            .to_request();
+        assert_eq!(test::call_service(&owner, req).await.status().as_u16(), 200);
+
+        let intruder = create_app_as("intruder@example.com", job_queue).await;
+        for uri in [
+            format!("/job-status/{}", job_id),
+            format!("/job-results/{}", job_id),
+            format!("/jobs/{}/results", job_id),
+            format!("/job-report/{}", job_id),
+            format!("/job-results/{}/summary", job_id),
+            format!("/job-results/{}/export?format=csv", job_id),
+        ] {
+            let req = test::TestRequest::get().uri(&uri).to_request();
+            let resp = test::call_service(&intruder, req).await;
+            assert_eq!(resp.status().as_u16(), 404, "{}", uri);
+        }
+        let req = test::TestRequest::delete()
+            .uri(&format!("/jobs/{}", job_id))
+            .to_request();
+        assert_eq!(
+            test::call_service(&intruder, req).await.status().as_u16(),
+            404
+        );
+        // Signing a share link would hand the results to anyone holding it
+        let req = test::TestRequest::post()
+            .uri(&format!("/job-results/{}/share", job_id))
+            .to_request();
+        assert_eq!(
+            test::call_service(&intruder, req).await.status().as_u16(),
+            404
+        );
+    }
+
     #[actix_web::test]
     async fn test_redis_caching() {
         // This test verifies that caching works by making two identical requests
@@ -757,17 +2304,80 @@ mod tests {
 
     #[actix_web::test]
     async fn test_redis_cache_methods() {
-        let redis_cache = RedisCache::test_dummy();
+        let redis_cache = RedisCache::from_store(Arc::new(MemoryStore::default()), 3600);
 
         // Test get_dns_validation with cache miss
         let result = redis_cache.get_dns_validation("example.com").await;
         assert!(result.is_ok());
 
         // Test set_dns_validation
-        let result = redis_cache.set_dns_validation("example.com", true).await;
+        let result = redis_cache
+            .set_dns_validation("example.com", DnsOutcome::Valid)
+            .await;
         assert!(result.is_ok());
     }
 
+    #[actix_web::test]
+    async fn test_dns_verdicts_served_from_local_tier() {
+        // Nothing listens on port 1, so only the in-process tier can answer
+        let redis_cache = RedisCache::new("redis://127.0.0.1:1", 3600).unwrap();
+
+        assert!(
+            redis_cache
+                .set_dns_validation("Example.com", DnsOutcome::NotFound)
+                .await
+                .is_err()
+        );
+        assert_eq!(
+            redis_cache.get_dns_validation("example.com").await.unwrap(),
+            Some(DnsOutcome::NotFound)
+        );
+        let stats = redis_cache.dns_local_stats().unwrap();
+        assert_eq!((stats.entries, stats.hits), (1, 1));
+
+        assert!(
+            redis_cache
+                .invalidate_dns_validation("example.com")
+                .await
+                .is_err()
+        );
+        assert_eq!(redis_cache.dns_local_stats().unwrap().entries, 0);
+    }
+
+    #[actix_web::test]
+    async fn test_resolver_failures_cached_briefly_or_not_at_all() {
+        let mut redis_cache = RedisCache::from_store(Arc::new(MemoryStore::default()), 3600);
+        assert_eq!(redis_cache.dns_ttl(DnsOutcome::NotFound), Some(3600));
+
+        redis_cache.dns_unavailable_ttl = 0;
+        assert_eq!(redis_cache.dns_ttl(DnsOutcome::Unavailable), None);
+        redis_cache
+            .set_dns_validation("flaky.example", DnsOutcome::Unavailable)
+            .await
+            .unwrap();
+        assert_eq!(
+            redis_cache
+                .get_dns_validation("flaky.example")
+                .await
+                .unwrap(),
+            None
+        );
+
+        redis_cache.dns_unavailable_ttl = 30;
+        assert_eq!(redis_cache.dns_ttl(DnsOutcome::Unavailable), Some(30));
+        redis_cache
+            .set_dns_validation("flaky.example", DnsOutcome::Unavailable)
+            .await
+            .unwrap();
+        assert_eq!(
+            redis_cache
+                .get_dns_validation("flaky.example")
+                .await
+                .unwrap(),
+            Some(DnsOutcome::Unavailable)
+        );
+    }
+
     #[actix_web::test]
     async fn test_redis_cache_new() {
         // Test with valid Redis URL
@@ -787,7 +2397,9 @@ mod tests {
         let mongo_client = create_test_mongo_client().await;
         let app = test::init_service(
             App::new()
-                .app_data(web::Data::new(RedisCache::test_dummy()))
+                .app_data(web::Data::new(ValidationBackends::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
                 .app_data(web::Data::new(
                     JobQueue::new("redis://127.0.0.1:6379")
                         .unwrap_or_else(|_| JobQueue::new("redis://127.0.0.1:6379").unwrap()),
@@ -896,16 +2508,20 @@ mod tests {
 
     #[actix_web::test]
     async fn test_validate_single_email_function() {
-        let redis_cache = RedisCache::test_dummy();
+        let backends = ValidationBackends::from_store(Arc::new(MemoryStore::default()));
 
         // Test valid email
-        let result = validate_single_email("test@example.com", false, &redis_cache).await;
+        let result = validate_single_email("test@example.com", false, &backends).await;
         assert!(result.is_valid || !result.is_valid); // Either outcome is valid for this test
 
         // Test invalid syntax
-        let result = validate_single_email("invalid-email", false, &redis_cache).await;
+        let result = validate_single_email("invalid-email", false, &backends).await;
         assert!(!result.is_valid);
         assert_eq!(result.error.as_ref().unwrap().code, "INVALID_SYNTAX");
+        assert_eq!(
+            result.error.as_ref().unwrap().violations,
+            vec![SyntaxViolation::MissingAt]
+        );
     }
 
     #[actix_web::test]
@@ -919,14 +2535,11 @@ mod tests {
             .to_request();
 
         let resp = test::call_service(&app, req).await;
-        assert_eq!(resp.status().as_u16(), 500);
+        assert_eq!(resp.status().as_u16(), 503);
 
         let body = test::read_body(resp).await;
         let body_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
-        assert_eq!(body_json["error"], "DATABASE_ERROR");
-        assert_eq!(
-            body_json["message"].as_str().unwrap(),
-            "Database connection failed"
-        );
+        assert_eq!(body_json["error"]["code"], "UPSTREAM_UNAVAILABLE");
+        assert!(body_json["error"]["request_id"].is_string());
     }
 }
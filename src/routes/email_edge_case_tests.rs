@@ -1,10 +1,14 @@
 #[cfg(test)]
 mod email_routes_edge_case_tests {
+    use crate::handlers::validation::dnsmx::DnsOutcome;
     use crate::routes::email::*;
+    use crate::stores::{MemoryDomainLists, MemoryStore};
+    use crate::validation_service::ValidationBackends;
     use actix_web::{App, http::StatusCode, test, web};
     use mongodb::{Client as MongoClient, options::ClientOptions};
     use serde_json::json;
     use std::env;
+    use std::sync::Arc;
 
     async fn create_test_mongo_client() -> MongoClient {
         let mongo_uri =
@@ -23,15 +27,18 @@ mod email_routes_edge_case_tests {
     > {
         let redis_url =
             env::var("TEST_REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
-        let redis_cache =
-            RedisCache::new(&redis_url, 3600).unwrap_or_else(|_| RedisCache::test_dummy());
+        let backends = RedisCache::new(&redis_url, 3600)
+            .map(|redis_cache| {
+                ValidationBackends::from_env(redis_cache, Arc::new(MemoryDomainLists::default()))
+            })
+            .unwrap_or_else(|_| ValidationBackends::from_store(Arc::new(MemoryStore::default())));
         let job_queue = crate::job_queue::JobQueue::new(&redis_url)
             .unwrap_or_else(|_| crate::job_queue::JobQueue::new("redis://127.0.0.1:6379").unwrap());
         let mongo_client = create_test_mongo_client().await;
 
         test::init_service(
             App::new()
-                .app_data(web::Data::new(redis_cache))
+                .app_data(web::Data::new(backends))
                 .app_data(web::Data::new(job_queue))
                 .app_data(web::Data::new(mongo_client))
                 .configure(configure_routes),
@@ -150,6 +157,22 @@ mod email_routes_edge_case_tests {
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[actix_web::test]
+    async fn test_validate_emails_bulk_mixed_types_lenient() {
+        let app = create_test_app().await;
+        let req = test::TestRequest::post()
+            .uri("/validate-emails-bulk?lenient=true")
+            .insert_header(("Authorization", "Bearer test-api-key"))
+            .set_json(json!({
+                "emails": ["test@example.com", 123, null, "another@example.com"]
+            }))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        // Malformed entries no longer reject the body; the test key is then refused
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
     #[actix_web::test]
     async fn test_validate_emails_bulk_very_large_batch() {
         let app = create_test_app().await;
@@ -272,7 +295,7 @@ mod email_routes_edge_case_tests {
 
     #[actix_web::test]
     async fn test_redis_cache_error_handling() {
-        let redis_cache = RedisCache::test_dummy();
+        let redis_cache = RedisCache::from_store(Arc::new(MemoryStore::default()), 3600);
 
         // Test cache methods with various inputs
         let result = redis_cache.get_dns_validation("").await;
@@ -283,32 +306,34 @@ mod email_routes_edge_case_tests {
             .await;
         assert!(result.is_ok());
 
-        let result = redis_cache.set_dns_validation("", true).await;
+        let result = redis_cache.set_dns_validation("", DnsOutcome::Valid).await;
         assert!(result.is_ok());
 
-        let result = redis_cache.set_dns_validation("test.com", true).await;
+        let result = redis_cache
+            .set_dns_validation("test.com", DnsOutcome::Valid)
+            .await;
         assert!(result.is_ok());
     }
 
     #[actix_web::test]
     async fn test_validate_single_email_function_edge_cases() {
-        let redis_cache = RedisCache::test_dummy();
+        let backends = ValidationBackends::from_store(Arc::new(MemoryStore::default()));
 
         // Test with whitespace
-        let result = validate_single_email("  test@example.com  ", false, &redis_cache).await;
+        let result = validate_single_email("  test@example.com  ", false, &backends).await;
         assert!(result.is_valid || !result.is_valid); // Either outcome is valid
 
         // Test with empty string
-        let result = validate_single_email("", false, &redis_cache).await;
+        let result = validate_single_email("", false, &backends).await;
         assert!(!result.is_valid);
 
         // Test with very long email
         let long_email = format!("{}@example.com", "a".repeat(300));
-        let result = validate_single_email(&long_email, false, &redis_cache).await;
+        let result = validate_single_email(&long_email, false, &backends).await;
         assert!(!result.is_valid);
 
         // Test with Unicode
-        let result = validate_single_email("tëst@exämple.com", false, &redis_cache).await;
+        let result = validate_single_email("tëst@exämple.com", false, &backends).await;
         assert!(result.is_valid || !result.is_valid); // Either outcome is valid
     }
 }
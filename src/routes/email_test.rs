@@ -1,6 +1,11 @@
 #[cfg(test)]
 mod email_route_tests {
     use super::super::email::*;
+    use crate::checks::{Check, Checks, ItemOptions};
+    use crate::handlers::validation::dnsmx::DnsOutcome;
+    use crate::stores::MemoryStore;
+    use crate::validation_service::ValidationBackends;
+    use std::sync::Arc;
 
     #[test]
     fn test_email_request_struct() {
@@ -10,16 +15,42 @@ mod email_route_tests {
         assert_eq!(req.email, "test@example.com");
     }
 
+    #[test]
+    fn test_email_query_param() {
+        assert_eq!(
+            email_query_param("check_role_based=true&email=user%40example.com"),
+            Some("user@example.com".to_string())
+        );
+        assert_eq!(
+            email_query_param("email=user+tag@example.com"),
+            Some("user+tag@example.com".to_string())
+        );
+        assert_eq!(
+            email_query_param("email=%E4%BE%8B@%E4%BE%8B%E5%AD%90.%E4%B8%AD%E5%9B%BD"),
+            Some("例@例子.中国".to_string())
+        );
+        assert_eq!(
+            email_query_param("email=100%@example.com").as_deref(),
+            Some("100%@example.com")
+        );
+        assert_eq!(email_query_param("emails=a@example.com"), None);
+        assert_eq!(email_query_param("email="), None);
+        assert_eq!(email_query_param("email=%FF"), None);
+    }
+
     #[test]
     fn test_bulk_email_request_struct() {
         let req = BulkEmailRequest {
             emails: vec![
-                "test1@example.com".to_string(),
-                "test2@example.com".to_string(),
+                BulkEmailEntry::Address("test1@example.com".to_string()),
+                BulkEmailEntry::Address("test2@example.com".to_string()),
             ],
         };
         assert_eq!(req.emails.len(), 2);
-        assert_eq!(req.emails[0], "test1@example.com");
+        assert_eq!(
+            req.emails[0],
+            BulkEmailEntry::Address("test1@example.com".to_string())
+        );
     }
 
     #[test]
@@ -27,6 +58,7 @@ mod email_route_tests {
         let error = EmailValidationError {
             code: "INVALID_SYNTAX".to_string(),
             message: "Invalid email format".to_string(),
+            violations: Vec::new(),
         };
         assert_eq!(error.code, "INVALID_SYNTAX");
         assert_eq!(error.message, "Invalid email format");
@@ -36,6 +68,9 @@ mod email_route_tests {
     fn test_email_validation_response_valid() {
         let response = EmailValidationResponse {
             is_valid: true,
+            domain: None,
+            has_tag: None,
+            base_address: None,
             status: Some("VALID".to_string()),
             error: None,
         };
@@ -48,10 +83,14 @@ mod email_route_tests {
     fn test_email_validation_response_invalid() {
         let response = EmailValidationResponse {
             is_valid: false,
+            domain: None,
+            has_tag: None,
+            base_address: None,
             status: None,
             error: Some(EmailValidationError {
                 code: "INVALID_SYNTAX".to_string(),
                 message: "Bad format".to_string(),
+                violations: Vec::new(),
             }),
         };
         assert!(!response.is_valid);
@@ -65,6 +104,9 @@ mod email_route_tests {
             email: "test@example.com".to_string(),
             validation: EmailValidationResponse {
                 is_valid: true,
+                domain: None,
+                has_tag: None,
+                base_address: None,
                 status: Some("VALID".to_string()),
                 error: None,
             },
@@ -79,26 +121,170 @@ mod email_route_tests {
             results: vec![],
             valid_count: 5,
             invalid_count: 3,
+            unique_count: 8,
+            total: None,
+            page: None,
+            per_page: None,
         };
         assert_eq!(response.valid_count, 5);
         assert_eq!(response.invalid_count, 3);
         assert_eq!(response.results.len(), 0);
     }
 
+    #[test]
+    fn test_batch_size_rejection() {
+        use actix_web::ResponseError;
+        use actix_web::http::StatusCode;
+
+        let empty = batch_size_rejection(0, 100).unwrap();
+        assert_eq!(empty.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let oversized = batch_size_rejection(101, 100).unwrap();
+        assert_eq!(oversized.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        assert!(batch_size_rejection(1, 100).is_none());
+        assert!(batch_size_rejection(100, 100).is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_batch_size_rejection_bodies() {
+        use actix_web::ResponseError;
+
+        let response = batch_size_rejection(0, 100).unwrap().error_response();
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "EMPTY_BATCH");
+
+        let response = batch_size_rejection(5, 4).unwrap().error_response();
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "BATCH_TOO_LARGE");
+        assert_eq!(body["error"]["details"]["max_batch_size"], 4);
+        assert!(body["error"]["request_id"].is_string());
+    }
+
+    #[test]
+    fn test_parse_bulk_items_isolates_malformed_entries() {
+        let items = parse_bulk_items(vec![
+            serde_json::json!("user@example.com"),
+            serde_json::Value::Null,
+            serde_json::json!(42),
+            serde_json::json!({ "address": "x@example.com" }),
+        ]);
+
+        assert_eq!(items[0].as_ref().unwrap().email, "user@example.com");
+        assert_eq!(items[1], Err("null".to_string()));
+        assert_eq!(items[2], Err("42".to_string()));
+        assert!(items[3].is_err());
+    }
+
+    #[test]
+    fn test_parse_bulk_items_reads_entry_options() {
+        let items = parse_bulk_items(vec![
+            serde_json::json!({ "email": "x@example.com" }),
+            serde_json::json!({ "email": "y@example.com", "check_role_based": true, "checks": ["dns"] }),
+            serde_json::json!({ "email": "z@example.com", "checks": ["smtp"] }),
+        ]);
+
+        let plain = items[0].as_ref().unwrap();
+        assert_eq!(plain.email, "x@example.com");
+        assert!(plain.options.is_default());
+        let checks = items[1].as_ref().unwrap().options.checks(false);
+        assert!(checks.dns && checks.role_based && !checks.disposable);
+        assert!(items[2].is_err());
+    }
+
+    #[test]
+    fn test_results_to_csv_escapes_fields() {
+        let rows = vec![
+            BulkEmailValidationResult {
+                email: "user@example.com".to_string(),
+                validation: EmailValidationResponse {
+                    is_valid: true,
+                    domain: None,
+                    has_tag: None,
+                    base_address: None,
+                    status: Some("VALID".to_string()),
+                    error: None,
+                },
+            },
+            BulkEmailValidationResult {
+                email: "\"odd,one\"@example.com".to_string(),
+                validation: EmailValidationResponse {
+                    is_valid: false,
+                    domain: None,
+                    has_tag: None,
+                    base_address: None,
+                    status: None,
+                    error: Some(EmailValidationError {
+                        code: "INVALID_SYNTAX".to_string(),
+                        message: "Bad syntax, try again".to_string(),
+                        violations: Vec::new(),
+                    }),
+                },
+            },
+        ];
+
+        assert_eq!(
+            results_to_csv(&rows),
+            "user@example.com,true,VALID,,\n\
+             \"\"\"odd,one\"\"@example.com\",false,,INVALID_SYNTAX,\"Bad syntax, try again\"\n"
+        );
+    }
+
     #[test]
     fn test_validation_query_default() {
         let query = ValidationQuery {
-            check_role_based: false,
+            check_role_based: None,
+            block_duplicates: false,
+            lenient: false,
+            fields: None,
+            verbosity: None,
+            lang: None,
+        };
+        assert!(
+            !query
+                .options(&ItemOptions::default())
+                .checks(false)
+                .role_based
+        );
+
+        let defaults = ItemOptions {
+            check_role_based: Some(true),
+            checks: Some(vec![Check::Dns, Check::RoleBased]),
         };
-        assert!(!query.check_role_based);
+        assert_eq!(query.options(&defaults), defaults);
     }
 
     #[test]
     fn test_validation_query_enabled() {
         let query = ValidationQuery {
-            check_role_based: true,
+            check_role_based: Some(true),
+            block_duplicates: false,
+            lenient: false,
+            fields: None,
+            verbosity: None,
+            lang: None,
+        };
+        assert!(
+            query
+                .options(&ItemOptions::default())
+                .checks(false)
+                .role_based
+        );
+
+        let defaults = ItemOptions {
+            check_role_based: Some(false),
+            checks: None,
         };
-        assert!(query.check_role_based);
+        assert_eq!(
+            query.options(&defaults).checks(false),
+            Checks::standard(true)
+        );
     }
 
     #[tokio::test]
@@ -114,28 +300,30 @@ mod email_route_tests {
     }
 
     #[test]
-    fn test_redis_cache_test_dummy() {
-        let cache = RedisCache::test_dummy();
+    fn test_redis_cache_from_store() {
+        let cache = RedisCache::from_store(Arc::new(MemoryStore::default()), 3600);
         assert_eq!(cache.ttl, 3600);
     }
 
     #[tokio::test]
     async fn test_redis_cache_get_dns_validation() {
-        let cache = RedisCache::test_dummy();
+        let cache = RedisCache::from_store(Arc::new(MemoryStore::default()), 3600);
         let result = cache.get_dns_validation("example.com").await;
         assert!(result.is_ok());
     }
 
     #[tokio::test]
     async fn test_redis_cache_set_dns_validation() {
-        let cache = RedisCache::test_dummy();
-        let result = cache.set_dns_validation("example.com", true).await;
+        let cache = RedisCache::from_store(Arc::new(MemoryStore::default()), 3600);
+        let result = cache
+            .set_dns_validation("example.com", DnsOutcome::Valid)
+            .await;
         assert!(result.is_ok());
     }
 
     #[tokio::test]
     async fn test_validate_single_email_invalid_syntax() {
-        let cache = RedisCache::test_dummy();
+        let cache = ValidationBackends::from_store(Arc::new(MemoryStore::default()));
         let result = validate_single_email("invalid-email", false, &cache).await;
 
         assert!(!result.is_valid);
@@ -145,7 +333,7 @@ mod email_route_tests {
 
     #[tokio::test]
     async fn test_validate_single_email_empty_string() {
-        let cache = RedisCache::test_dummy();
+        let cache = ValidationBackends::from_store(Arc::new(MemoryStore::default()));
         let result = validate_single_email("", false, &cache).await;
 
         assert!(!result.is_valid);
@@ -155,7 +343,7 @@ mod email_route_tests {
 
     #[tokio::test]
     async fn test_validate_single_email_whitespace_only() {
-        let cache = RedisCache::test_dummy();
+        let cache = ValidationBackends::from_store(Arc::new(MemoryStore::default()));
         let result = validate_single_email("   ", false, &cache).await;
 
         assert!(!result.is_valid);
@@ -168,6 +356,7 @@ mod email_route_tests {
         let error = EmailValidationError {
             code: "TEST_ERROR".to_string(),
             message: "Test message".to_string(),
+            violations: Vec::new(),
         };
         let json = serde_json::to_string(&error).unwrap();
         let deserialized: EmailValidationError = serde_json::from_str(&json).unwrap();
@@ -179,6 +368,9 @@ mod email_route_tests {
     fn test_email_validation_response_serialization() {
         let response = EmailValidationResponse {
             is_valid: true,
+            domain: None,
+            has_tag: None,
+            base_address: None,
             status: Some("VALID".to_string()),
             error: None,
         };
@@ -192,21 +384,29 @@ mod email_route_tests {
     fn test_validation_query_deserialization_default() {
         let json = r#"{}"#;
         let query: ValidationQuery = serde_json::from_str(json).unwrap();
-        assert!(!query.check_role_based);
+        assert_eq!(query.check_role_based, None);
+        assert!(!query.block_duplicates);
+    }
+
+    #[test]
+    fn test_validation_query_block_duplicates() {
+        let json = r#"{"block_duplicates": true}"#;
+        let query: ValidationQuery = serde_json::from_str(json).unwrap();
+        assert!(query.block_duplicates);
     }
 
     #[test]
     fn test_validation_query_deserialization_true() {
         let json = r#"{"check_role_based": true}"#;
         let query: ValidationQuery = serde_json::from_str(json).unwrap();
-        assert!(query.check_role_based);
+        assert_eq!(query.check_role_based, Some(true));
     }
 
     #[test]
     fn test_validation_query_deserialization_false() {
         let json = r#"{"check_role_based": false}"#;
         let query: ValidationQuery = serde_json::from_str(json).unwrap();
-        assert!(!query.check_role_based);
+        assert_eq!(query.check_role_based, Some(false));
     }
 
     #[test]
@@ -218,10 +418,13 @@ mod email_route_tests {
     #[test]
     fn test_bulk_email_request_single_email() {
         let req = BulkEmailRequest {
-            emails: vec!["single@example.com".to_string()],
+            emails: vec![BulkEmailEntry::Address("single@example.com".to_string())],
         };
         assert_eq!(req.emails.len(), 1);
-        assert_eq!(req.emails[0], "single@example.com");
+        assert_eq!(
+            req.emails[0],
+            BulkEmailEntry::Address("single@example.com".to_string())
+        );
     }
 
     #[test]
@@ -238,6 +441,7 @@ mod email_route_tests {
             let error = EmailValidationError {
                 code: code.to_string(),
                 message: format!("Error for {}", code),
+                violations: Vec::new(),
             };
             assert_eq!(error.code, code);
         }
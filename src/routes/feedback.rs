@@ -0,0 +1,256 @@
+use crate::account_settings;
+use crate::auth::AuthedAccount;
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::handlers::validation::bounce_feedback::{self, FeedbackEvent, Notification};
+use crate::handlers::validation::syntax;
+use crate::organizations;
+use crate::sns::{self, SnsMessage};
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, Responder, post, web};
+use mongodb::Client as MongoClient;
+use serde_json::json;
+
+fn invalid_signature(message: impl Into<String>) -> ApiError {
+    ApiError::validation("INVALID_SNS_SIGNATURE", message).with_status(StatusCode::FORBIDDEN)
+}
+
+/// Reads an SNS message and checks its signature against the SNS signing
+/// certificate it names
+async fn verified_sns_message(body: &[u8]) -> Result<SnsMessage, ApiError> {
+    let message: SnsMessage = serde_json::from_slice(body)
+        .map_err(|_| ApiError::validation("INVALID_FEEDBACK", "The body is not an SNS message"))?;
+    sns::check_cert_url(&message.signing_cert_url).map_err(invalid_signature)?;
+    let cert = sns::signing_cert(&message.signing_cert_url)
+        .await
+        .map_err(|e| ApiError::upstream("SNS", e))?;
+    message.verify_with_cert(&cert).map_err(invalid_signature)?;
+    Ok(message)
+}
+
+/// Records feedback for the lists of `email` (its organization's, or its own)
+async fn record_events(
+    mongo_client: &MongoClient,
+    email: &str,
+    events: Vec<FeedbackEvent>,
+) -> Result<HttpResponse, ApiError> {
+    let received = events.len();
+    let events: Vec<_> = events
+        .into_iter()
+        .filter(|event| syntax::is_valid_email(event.email.trim()))
+        .collect();
+    let owner = organizations::list_owner(mongo_client, email)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    let summary = bounce_feedback::record(mongo_client, &owner, &events)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "received": received,
+        "skipped": received - events.len(),
+        "suppressed": summary.suppressed,
+        "soft_bounces": summary.soft_bounces,
+    })))
+}
+
+/// # Bounce Feedback
+///
+/// Records bounces and spam complaints from the caller's own sending, so later
+/// validations of those addresses by the caller (or its organization) fail with
+/// `PREVIOUS_BOUNCE` or `PREVIOUS_COMPLAINT`.
+///
+/// The body is read as JSON whatever its content type, since SNS posts
+/// `text/plain`. Accepted formats:
+/// - an SNS envelope (`"Type": "Notification"`) around an SES notification, whose
+///   signature must check out as on `/api/v1/feedback/sns`
+/// - a bare SES notification (`notificationType`) or event (`eventType`)
+/// - `{ "events": [{ "email", "type": "bounce"|"complaint", "permanent"? }] }`,
+///   where `permanent` defaults to true
+///
+/// Hard bounces and complaints suppress the address at once; soft bounces only
+/// after `BOUNCE_SOFT_LIMIT` (default 3) of them, since retries may still succeed.
+/// Recording is idempotent for hard bounces and complaints, and failures answer 503,
+/// so senders can safely retry a notification. SNS subscription confirmations are
+/// answered with the `subscribe_url` to open; it is never fetched from here. SNS
+/// topics are better subscribed to `/api/v1/feedback/sns`, which needs no API key.
+///
+/// ## Responses
+/// - **200 OK**: `{ "received", "skipped", "suppressed", "soft_bounces" }`, or
+///   `{ "status": "confirmation_required", "subscribe_url" }`
+/// - **400 Bad Request**: `INVALID_FEEDBACK`, the body is in none of the formats
+/// - **401 Unauthorized**: Missing or invalid credentials
+/// - **403 Forbidden**: `INVALID_SNS_SIGNATURE`, an SNS envelope failed verification
+#[utoipa::path(
+    post,
+    path = "/api/v1/feedback/bounces",
+    request_body(content = String, description = "SNS envelope, SES notification or `{ \"events\": [...] }`"),
+    responses(
+        (status = 200, description = "Feedback recorded: `{ \"received\", \"skipped\", \"suppressed\", \"soft_bounces\" }`"),
+        (status = 400, description = "INVALID_FEEDBACK", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 403, description = "INVALID_SNS_SIGNATURE", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database or SNS is unavailable; retry later", body = ErrorEnvelope)
+    ),
+    tag = "Account"
+)]
+#[post("/feedback/bounces")]
+pub async fn record_bounces(
+    body: web::Bytes,
+    account: AuthedAccount,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<impl Responder, ApiError> {
+    let parsed: serde_json::Value = serde_json::from_slice(&body)
+        .map_err(|_| ApiError::validation("INVALID_FEEDBACK", "The body is not valid JSON"))?;
+    if parsed.get("Type").is_some() {
+        verified_sns_message(&body).await?;
+    }
+    let events = match bounce_feedback::parse_notification(&parsed)
+        .map_err(|message| ApiError::validation("INVALID_FEEDBACK", message))?
+    {
+        Notification::SubscriptionConfirmation { subscribe_url } => {
+            return Ok(HttpResponse::Ok().json(json!({
+                "status": "confirmation_required",
+                "subscribe_url": subscribe_url,
+            })));
+        }
+        Notification::Events(events) => events,
+    };
+    record_events(&mongo_client, &account.email, events).await
+}
+
+/// # SNS Bounce Feedback
+///
+/// Endpoint for SNS topics that SES publishes bounces and complaints to; no API key
+/// is needed. Every message must be signed by SNS with `SignatureVersion` 2 (set the
+/// topic's `SignatureVersion` attribute to 2), its `SigningCertURL` must be an
+/// `https://sns.<region>.amazonaws.com/...pem` certificate, and its topic must be
+/// listed in the `sns_topic_arns` account setting, whose account the feedback is
+/// recorded for (or its organization).
+///
+/// Subscription confirmations are confirmed by visiting their `SubscribeURL`, which
+/// must point at SNS as well. Notifications are recorded as on
+/// `/api/v1/feedback/bounces`.
+///
+/// ## Responses
+/// - **200 OK**: `{ "received", "skipped", "suppressed", "soft_bounces" }`, or
+///   `{ "status": "confirmed" }` for a subscription confirmation
+/// - **400 Bad Request**: `INVALID_FEEDBACK`, the body is not an SNS message around
+///   an SES notification
+/// - **403 Forbidden**: `INVALID_SNS_SIGNATURE`, or `UNKNOWN_SNS_TOPIC` when no
+///   account registered the topic
+#[utoipa::path(
+    post,
+    path = "/api/v1/feedback/sns",
+    request_body(content = String, description = "Signed SNS message"),
+    responses(
+        (status = 200, description = "Feedback recorded: `{ \"received\", \"skipped\", \"suppressed\", \"soft_bounces\" }`"),
+        (status = 400, description = "INVALID_FEEDBACK", body = ErrorEnvelope),
+        (status = 403, description = "INVALID_SNS_SIGNATURE or UNKNOWN_SNS_TOPIC", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database or SNS is unavailable; retry later", body = ErrorEnvelope)
+    ),
+    tag = "Account"
+)]
+#[post("/feedback/sns")]
+pub async fn record_sns_feedback(
+    body: web::Bytes,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<impl Responder, ApiError> {
+    let message = verified_sns_message(&body).await?;
+    let tenant_id = account_settings::tenant_for_topic(&mongo_client, &message.topic_arn)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?
+        .ok_or_else(|| {
+            ApiError::validation(
+                "UNKNOWN_SNS_TOPIC",
+                format!("No account registered {}", message.topic_arn),
+            )
+            .with_status(StatusCode::FORBIDDEN)
+        })?;
+
+    match message.kind.as_str() {
+        "SubscriptionConfirmation" => {
+            message
+                .confirm_subscription()
+                .await
+                .map_err(|e| ApiError::upstream("SNS", e))?;
+            Ok(HttpResponse::Ok().json(json!({ "status": "confirmed" })))
+        }
+        "UnsubscribeConfirmation" => Ok(HttpResponse::Ok().json(json!({ "status": "ok" }))),
+        _ => {
+            let events = bounce_feedback::sns_message_events(&message.message)
+                .map_err(|message| ApiError::validation("INVALID_FEEDBACK", message))?;
+            record_events(&mongo_client, &tenant_id, events).await
+        }
+    }
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(record_bounces).service(record_sns_feedback);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, test};
+
+    #[actix_web::test]
+    async fn test_feedback_requires_account() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/feedback/bounces")
+            .insert_header(("Content-Type", "text/plain; charset=UTF-8"))
+            .set_payload(r#"{ "events": [] }"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn test_sns_messages_must_come_from_sns() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let forged = json!({
+            "Type": "Notification",
+            "MessageId": "m-1",
+            "TopicArn": "arn:aws:sns:us-east-1:123456789012:ses-feedback",
+            "Message": "{}",
+            "Timestamp": "2024-05-01T12:00:00.000Z",
+            "SignatureVersion": "2",
+            "Signature": "c2ln",
+            "SigningCertURL": "https://attacker.example/cert.pem"
+        });
+        let req = test::TestRequest::post()
+            .uri("/api/v1/feedback/sns")
+            .insert_header(("Content-Type", "text/plain; charset=UTF-8"))
+            .set_payload(forged.to_string())
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], "INVALID_SNS_SIGNATURE");
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/feedback/sns")
+            .set_payload(r#"{ "events": [] }"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,12 +1,26 @@
+use crate::app_env::graphql_dev_tools_enabled;
 use crate::graphql::handlers;
 use actix_web::web;
 
 /// GraphQL Route Configuration
 ///
 /// Defines and configures the web endpoints for GraphQL operations and development tools.
+/// The playground is omitted in production (see [`graphql_dev_tools_enabled`]).
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::resource("/graphql").route(web::post().to(handlers::graphql_handler)))
-        .service(web::resource("/playground").route(web::get().to(handlers::graphql_playground)));
+    configure_routes_with_playground(cfg, graphql_dev_tools_enabled());
+}
+
+fn configure_routes_with_playground(cfg: &mut web::ServiceConfig, playground: bool) {
+    cfg.service(
+        web::resource("/graphql")
+            .route(web::post().to(handlers::graphql_handler))
+            .route(web::get().to(handlers::graphql_get_handler)),
+    );
+    if playground {
+        cfg.service(
+            web::resource("/playground").route(web::get().to(handlers::graphql_playground)),
+        );
+    }
 }
 
 #[cfg(test)]
@@ -41,4 +55,25 @@ mod tests {
         let resp = test::call_service(&app, req).await;
         assert_eq!(resp.status(), 200);
     }
+
+    #[actix_web::test]
+    async fn test_playground_omitted_without_dev_tools() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(create_schema()))
+                .configure(|cfg| configure_routes_with_playground(cfg, false)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/playground").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 404);
+
+        let req = test::TestRequest::post()
+            .uri("/graphql")
+            .set_json(serde_json::json!({"query": "{ __typename }"}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+    }
 }
@@ -1,34 +1,138 @@
-use crate::models::health::HealthResponse;
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::health_checks;
+use crate::health_history::HealthHistory;
+use crate::models::health::{DependencyCheck, HealthResponse};
 use actix_web::{HttpResponse, Responder, get, guard, web};
+use chrono::Utc;
+use mongodb::Client as MongoClient;
+use serde_json::json;
+
+async fn dependency_checks(
+    mongo_client: Option<web::Data<MongoClient>>,
+    history: Option<web::Data<HealthHistory>>,
+) -> Vec<DependencyCheck> {
+    health_checks::check_dependencies(
+        mongo_client.as_ref().map(|client| client.get_ref()),
+        history.as_ref().map(|history| history.get_ref()),
+    )
+    .await
+}
 
 /// # Health Check Endpoint
 ///
-/// Returns the current health status of the service along with a timestamp.
+/// Liveness probe: answers `UP` whenever the process can serve requests, along with
+/// the result and latency of each dependency probe (MongoDB ping, Redis ping and a
+/// DNS resolver self-test) for diagnostics. Use `/ready` to gate traffic on them.
 ///
 /// ## Response
 ///
-/// - **200 OK**: Service is healthy
-///   - Body: JSON object with `status` ("UP") and `timestamp` in ISO 8601 format
+/// - **200 OK**: Service is alive
+///   - Body: JSON object with `status` ("UP"), `timestamp` in ISO 8601 format and
+///     `dependencies`
 ///
 /// ## Example Response
 ///
 /// ```json
 /// {
 ///   "status": "UP",
-///   "timestamp": "2023-10-05T12:34:56.789Z"
+///   "timestamp": "2023-10-05T12:34:56.789Z",
+///   "dependencies": [
+///     { "name": "mongodb", "status": "UP", "latency_ms": 3 },
+///     { "name": "redis", "status": "DOWN", "latency_ms": 2000 },
+///     { "name": "dns", "status": "UP", "latency_ms": 12 }
+///   ]
 /// }
 /// ```
 #[utoipa::path(
     get,
     path = "/api/v1/health",
     responses(
-        (status = 200, description = "Service is healthy", body = HealthResponse)
+        (status = 200, description = "Service is alive", body = HealthResponse)
     ),
+    security(()),
     tag = "Health Check"
 )]
 #[get("/health")]
-pub async fn health() -> impl Responder {
-    HttpResponse::Ok().json(HealthResponse::up())
+pub async fn health(
+    mongo_client: Option<web::Data<MongoClient>>,
+    history: Option<web::Data<HealthHistory>>,
+) -> impl Responder {
+    let mut response = HealthResponse::up();
+    response.dependencies = dependency_checks(mongo_client, history).await;
+    HttpResponse::Ok().json(response)
+}
+
+/// # Readiness Endpoint
+///
+/// Readiness probe: `UP` with 200 once MongoDB, Redis and DNS resolution are all
+/// reachable, `DOWN` with 503 until then. Each probe gives up after
+/// `HEALTH_PROBE_TIMEOUT_MS` (default 2000).
+///
+/// ## Response
+///
+/// - **200 OK**: Every dependency is reachable
+/// - **503 Service Unavailable**: Same body, with `status` "DOWN" and the failing
+///   dependencies marked "DOWN"
+#[utoipa::path(
+    get,
+    path = "/api/v1/ready",
+    responses(
+        (status = 200, description = "All dependencies reachable", body = HealthResponse),
+        (status = 503, description = "A dependency is unreachable", body = HealthResponse)
+    ),
+    security(()),
+    tag = "Health Check"
+)]
+#[get("/ready")]
+pub async fn ready(
+    mongo_client: Option<web::Data<MongoClient>>,
+    history: Option<web::Data<HealthHistory>>,
+) -> impl Responder {
+    let response = HealthResponse::readiness(dependency_checks(mongo_client, history).await);
+    if response.is_up() {
+        HttpResponse::Ok().json(response)
+    } else {
+        HttpResponse::ServiceUnavailable().json(response)
+    }
+}
+
+/// # Health History Endpoint
+///
+/// Dependency status transitions and uptime percentages over the last 24 hours,
+/// 7 days and 30 days; the data source for a status page. Dependencies are probed
+/// every `HEALTH_CHECK_INTERVAL_SECS` (default 30) and only changes are recorded.
+///
+/// ## Response
+///
+/// - **200 OK**: `{ "generated_at", "dependencies": [{ "name", "status", "since", "uptime": { "24h", "7d", "30d" }, "transitions": [{ "status", "at" }] }] }`
+///   - Uptime is `null` for a window without observations
+/// - **503 Service Unavailable**: History storage (Redis) is unreachable
+#[utoipa::path(
+    get,
+    path = "/api/v1/health/history",
+    responses(
+        (status = 200, description = "Dependency transitions and uptime percentages"),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: history storage is unreachable", body = ErrorEnvelope)
+    ),
+    security(()),
+    tag = "Health Check"
+)]
+#[get("/health/history")]
+pub async fn health_history(
+    history: Option<web::Data<HealthHistory>>,
+) -> Result<impl Responder, ApiError> {
+    let history =
+        history.ok_or_else(|| ApiError::upstream("health history", "no store in app data"))?;
+    let now = Utc::now();
+    let dependencies = history
+        .report(now.timestamp())
+        .await
+        .map_err(|e| ApiError::upstream("health history", e))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "generated_at": now.to_rfc3339(),
+        "dependencies": dependencies
+    })))
 }
 
 /// # Route Configuration
@@ -37,7 +141,9 @@ pub async fn health() -> impl Responder {
 ///
 /// ## Currently Configured Routes
 ///
-/// - `GET /health`: Health check endpoint
+/// - `GET /health`: Liveness, with dependency probe results
+/// - `GET /ready`: Readiness, 503 until every dependency is reachable
+/// - `GET /health/history`: Dependency transitions and uptime
 pub fn configure_routes(cfg: &mut actix_web::web::ServiceConfig) {
     // Add default route guard for unsupported methods
     cfg.service(
@@ -45,7 +151,9 @@ pub fn configure_routes(cfg: &mut actix_web::web::ServiceConfig) {
             .guard(guard::Not(guard::Get()))
             .to(HttpResponse::MethodNotAllowed),
     )
-    .service(health);
+    .service(health)
+    .service(ready)
+    .service(health_history);
 }
 
 #[cfg(test)]
@@ -100,6 +208,32 @@ mod tests {
         assert_eq!(resp.status(), 405); // Method Not Allowed
     }
 
+    #[actix_web::test]
+    async fn test_ready_without_dependencies() {
+        let app = test::init_service(App::new().configure(configure_routes)).await;
+
+        let req = test::TestRequest::get().uri("/ready").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 503);
+
+        let body: HealthResponse = test::read_body_json(resp).await;
+        assert_eq!(body.status, "DOWN");
+        assert_eq!(body.dependencies[0].name, "mongodb");
+        assert_eq!(body.dependencies[0].status, "DOWN");
+    }
+
+    #[actix_web::test]
+    async fn test_health_history_without_store() {
+        let app = test::init_service(App::new().configure(configure_routes)).await;
+
+        let req = test::TestRequest::get().uri("/health/history").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 503);
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], "UPSTREAM_UNAVAILABLE");
+    }
+
     #[actix_web::test]
     async fn test_configure_routes_function() {
         // Test that configure_routes function exists and can be called
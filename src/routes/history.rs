@@ -0,0 +1,122 @@
+use crate::auth::AuthenticatedAccount;
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::validation_history::{self, HISTORY_PAGE_SIZE, HistoryEntry, HistoryFilter};
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, get, web};
+use mongodb::Client as MongoClient;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    pub email: Option<String>,
+    /// Unix timestamp; validations before it are left out
+    pub from: Option<i64>,
+    /// Unix timestamp; validations after it are left out
+    pub to: Option<i64>,
+    /// 1-based page number
+    pub page: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    pub page: usize,
+    pub per_page: usize,
+}
+
+/// # Validation History
+///
+/// Audit trail of the caller's validations, newest first, [`HISTORY_PAGE_SIZE`] per
+/// page. Records hold a hash of the address, never the address in the clear, and
+/// expire after `VALIDATION_HISTORY_RETENTION_DAYS` (default 90). With
+/// `PII_HISTORY=encrypt` they also hold it encrypted, and entries include `email`.
+///
+/// ## Query Parameters
+/// - `email`: only validations of this address
+/// - `from`, `to`: Unix timestamps bounding the validation time, both inclusive
+/// - `page`: 1-based page number (default 1)
+///
+/// ## Responses
+/// - **200 OK**: `{ "entries": [{ "email_hash", "email"?, "verdict", "error_code", "source", "validated_at" }], "page", "per_page" }`
+/// - **400 Bad Request**: `INVALID_PAGE` or `INVALID_RANGE`
+#[utoipa::path(
+    get,
+    path = "/api/v1/history",
+    params(
+        ("email" = Option<String>, Query, description = "Only validations of this address"),
+        ("from" = Option<i64>, Query, description = "Unix timestamp the range starts at"),
+        ("to" = Option<i64>, Query, description = "Unix timestamp the range ends at"),
+        ("page" = Option<usize>, Query, description = "1-based page number")
+    ),
+    responses(
+        (status = 200, description = "One page of the caller's validation history", body = HistoryPage),
+        (status = 400, description = "INVALID_PAGE or INVALID_RANGE", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[get("/history")]
+pub async fn get_history(
+    query: web::Query<HistoryQuery>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let owner = http_req
+        .extensions()
+        .get::<AuthenticatedAccount>()
+        .map(|account| account.email.clone())
+        .ok_or_else(|| ApiError::unauthorized("Missing Authorization header"))?;
+
+    let page = query.page.unwrap_or(1);
+    if page == 0 {
+        return Err(ApiError::validation("INVALID_PAGE", "page starts at 1"));
+    }
+    let query = query.into_inner();
+    let filter = HistoryFilter {
+        email: query.email,
+        from: query.from,
+        to: query.to,
+    };
+    filter
+        .check()
+        .map_err(|message| ApiError::validation("INVALID_RANGE", message))?;
+
+    let entries = validation_history::query_history(&mongo_client, &owner, &filter, page)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    Ok(HttpResponse::Ok().json(HistoryPage {
+        entries,
+        page,
+        per_page: HISTORY_PAGE_SIZE,
+    }))
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_history);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, http::StatusCode, test};
+
+    #[actix_web::test]
+    async fn test_history_requires_account() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/history?email=user@example.com")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,280 @@
+use crate::auth::{self, ApiKey, AuthedAccount};
+use crate::error::{ApiError, ErrorEnvelope};
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, delete, get, post, web};
+use mongodb::Client as MongoClient;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Default, ToSchema)]
+pub struct CreateKeyRequest {
+    pub label: Option<String>,
+    /// Scopes to limit the key to (`validate`, `jobs`, `sync`, `admin`); omit for full
+    /// access. Only accounts listed in `ADMIN_ACCOUNTS` may issue `admin` keys.
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+/// Key metadata returned by the key management endpoints; never includes the key or its hash
+#[derive(Serialize, ToSchema)]
+pub struct ApiKeySummary {
+    pub id: String,
+    pub label: Option<String>,
+    pub created_at: Option<i64>,
+    pub last_used: Option<i64>,
+    pub revoked: bool,
+    /// Empty for full access
+    pub scopes: Vec<String>,
+}
+
+impl From<&ApiKey> for ApiKeySummary {
+    fn from(record: &ApiKey) -> Self {
+        ApiKeySummary {
+            id: record.key_id.clone().unwrap_or_default(),
+            label: record.label.clone(),
+            created_at: record.created_at,
+            last_used: record.last_used,
+            revoked: record.revoked,
+            scopes: record.scopes.clone(),
+        }
+    }
+}
+
+/// # Create API Key
+///
+/// Issues an additional API key for the authenticated account. The key is only
+/// returned once; the service stores an Argon2id hash of its secret. Keys limited
+/// to `scopes` are refused on every other route with 403 `INSUFFICIENT_SCOPE`.
+///
+/// ## Request
+/// ```json
+/// { "label": "CI pipeline", "scopes": ["validate"] }
+/// ```
+///
+/// ## Responses
+/// - **201 Created**: `{ "id", "api_key", "label", "scopes", "created_at" }`
+/// - **401 Unauthorized**: Missing or invalid API key
+/// - **403 Forbidden**: `admin` scope requested by an account not in `ADMIN_ACCOUNTS`
+/// - **422 Unprocessable Entity**: Unknown scope
+#[utoipa::path(
+    post,
+    path = "/api/v1/keys",
+    request_body(content = Option<CreateKeyRequest>, description = "Optional label for the key"),
+    responses(
+        (status = 201, description = "Key issued; `api_key` is only returned once"),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 403, description = "FORBIDDEN: admin keys can't be issued by this account", body = ErrorEnvelope),
+        (status = 422, description = "INVALID_SCOPE", body = ErrorEnvelope)
+    ),
+    tag = "API Keys"
+)]
+#[post("/keys")]
+pub async fn create_key(
+    account: AuthedAccount,
+    req: Option<web::Json<CreateKeyRequest>>,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<HttpResponse, ApiError> {
+    let CreateKeyRequest { label, scopes } = req.map(|r| r.into_inner()).unwrap_or_default();
+    if let Some(scope) = scopes.iter().find(|scope| !auth::is_key_scope(scope)) {
+        return Err(ApiError::validation(
+            "INVALID_SCOPE",
+            format!(
+                "Unknown scope '{}'; use validate, jobs, sync or admin",
+                scope
+            ),
+        )
+        .with_status(StatusCode::UNPROCESSABLE_ENTITY));
+    }
+    if scopes.iter().any(|scope| scope == auth::ADMIN_SCOPE)
+        && !auth::may_grant_admin(&account.email)
+    {
+        return Err(ApiError::validation(
+            "FORBIDDEN",
+            "Only the accounts in ADMIN_ACCOUNTS may issue admin keys",
+        )
+        .with_status(StatusCode::FORBIDDEN));
+    }
+
+    // Keys issued with a key share its plan; sessions carry none, so use the account's
+    let plan = match account.key_id {
+        Some(_) => account.plan,
+        None => auth::account_plan(&mongo_client, &account.email)
+            .await
+            .map_err(ApiError::from_auth)?,
+    };
+    let (record, api_key) =
+        auth::create_api_key(&mongo_client, &account.email, plan, label, scopes)
+            .await
+            .map_err(ApiError::from_auth)?;
+
+    Ok(HttpResponse::Created().json(json!({
+        "id": record.key_id,
+        "api_key": api_key,
+        "label": record.label,
+        "scopes": record.scopes,
+        "created_at": record.created_at
+    })))
+}
+
+/// # List API Keys
+///
+/// Lists the authenticated account's managed keys, including revoked ones.
+///
+/// ## Responses
+/// - **200 OK**: `{ "keys": [{ "id", "label", "created_at", "last_used", "revoked", "scopes" }] }`
+/// - **401 Unauthorized**: Missing or invalid API key
+#[utoipa::path(
+    get,
+    path = "/api/v1/keys",
+    responses(
+        (status = 200, description = "`{ \"keys\": [ApiKeySummary] }`"),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope)
+    ),
+    tag = "API Keys"
+)]
+#[get("/keys")]
+pub async fn list_keys(
+    account: AuthedAccount,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<HttpResponse, ApiError> {
+    let records = auth::list_api_keys(&mongo_client, &account.email)
+        .await
+        .map_err(ApiError::from_auth)?;
+    let keys: Vec<ApiKeySummary> = records.iter().map(ApiKeySummary::from).collect();
+
+    Ok(HttpResponse::Ok().json(json!({ "keys": keys })))
+}
+
+/// # Revoke API Key
+///
+/// Revokes one of the authenticated account's keys. Revoked keys are rejected
+/// immediately by every endpoint.
+///
+/// ## Responses
+/// - **204 No Content**: Key revoked
+/// - **404 Not Found**: No active key with that id for this account
+/// - **401 Unauthorized**: Missing or invalid API key
+#[utoipa::path(
+    delete,
+    path = "/api/v1/keys/{id}",
+    params(("id" = String, Path, description = "Key id from the key listing")),
+    responses(
+        (status = 204, description = "Key revoked"),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 404, description = "KEY_NOT_FOUND", body = ErrorEnvelope)
+    ),
+    tag = "API Keys"
+)]
+#[delete("/keys/{id}")]
+pub async fn revoke_key(
+    account: AuthedAccount,
+    path: web::Path<String>,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<HttpResponse, ApiError> {
+    let key_id = path.into_inner();
+
+    let revoked = auth::revoke_api_key_by_id(&mongo_client, &account.email, &key_id)
+        .await
+        .map_err(ApiError::from_auth)?;
+
+    if !revoked {
+        return Err(ApiError::not_found(
+            "KEY_NOT_FOUND",
+            "No active API key with this id",
+        ));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_key)
+        .service(list_keys)
+        .service(revoke_key);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, http::StatusCode, test as actix_test};
+
+    #[test]
+    fn test_summary_hides_secrets() {
+        let record = ApiKey {
+            active: true,
+            key_id: Some("key-1".to_string()),
+            key_hash: Some(auth::hash_api_key("esk_secret")),
+            label: Some("CI".to_string()),
+            created_at: Some(1_700_000_000),
+            ..ApiKey::default()
+        };
+
+        let json = serde_json::to_value(ApiKeySummary::from(&record)).unwrap();
+        assert_eq!(json["id"], "key-1");
+        assert_eq!(json["label"], "CI");
+        assert!(json.get("key_hash").is_none());
+        assert!(!json.to_string().contains("esk_secret"));
+    }
+
+    #[actix_web::test]
+    async fn test_key_routes_require_authorization() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        for req in [
+            actix_test::TestRequest::get()
+                .uri("/api/v1/keys")
+                .to_request(),
+            actix_test::TestRequest::post()
+                .uri("/api/v1/keys")
+                .to_request(),
+            actix_test::TestRequest::delete()
+                .uri("/api/v1/keys/key-1")
+                .to_request(),
+        ] {
+            let resp = actix_test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_unknown_scope_uses_envelope() {
+        use crate::auth::AuthenticatedAccount;
+        use actix_web::HttpMessage;
+        use actix_web::dev::Service;
+
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .wrap_fn(|req, srv| {
+                    req.extensions_mut().insert(AuthenticatedAccount {
+                        email: "user@example.com".to_string(),
+                        ..AuthenticatedAccount::default()
+                    });
+                    srv.call(req)
+                })
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::post()
+            .uri("/api/v1/keys")
+            .set_json(serde_json::json!({ "scopes": ["validate", "billing"] }))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], "INVALID_SCOPE");
+        assert!(body["error"]["request_id"].is_string());
+    }
+}
@@ -0,0 +1,384 @@
+use crate::auth::AuthenticatedAccount;
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::feature_flags::{FeatureFlags, Flag};
+use crate::input_limits;
+use crate::job_queue::JobQueue;
+use crate::metering::Meter;
+use crate::saved_lists::{self, ListError, MAX_LIST_EMAILS, SavedList, SavedListSummary};
+use actix_web::http::StatusCode;
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, delete, get, post, web};
+use mongodb::Client as MongoClient;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateListRequest {
+    pub name: String,
+    /// Initial addresses; more can be appended later
+    #[serde(default)]
+    pub emails: Vec<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ListEmailsRequest {
+    pub emails: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ValidateListQuery {
+    #[serde(default)]
+    pub check_role_based: bool,
+}
+
+/// A saved list with its addresses
+#[derive(Serialize, ToSchema)]
+pub struct SavedListDetail {
+    pub list_id: String,
+    pub name: String,
+    pub emails: Vec<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Latest bulk job validating the list
+    pub last_job_id: Option<String>,
+}
+
+impl From<SavedList> for SavedListDetail {
+    fn from(list: SavedList) -> Self {
+        SavedListDetail {
+            list_id: list.list_id,
+            name: list.name,
+            emails: list.emails,
+            created_at: list.created_at,
+            updated_at: list.updated_at,
+            last_job_id: list.last_job_id,
+        }
+    }
+}
+
+impl From<ListError> for ApiError {
+    fn from(e: ListError) -> Self {
+        match e {
+            ListError::InvalidName(message) => ApiError::validation("INVALID_NAME", message),
+            ListError::TooLarge => ApiError::validation(
+                "LIST_TOO_LARGE",
+                format!("A saved list holds at most {} addresses", MAX_LIST_EMAILS),
+            )
+            .with_status(StatusCode::PAYLOAD_TOO_LARGE),
+            ListError::NotFound => list_not_found(),
+            ListError::Empty => {
+                ApiError::validation("EMPTY_BATCH", "The list contains no addresses")
+                    .with_status(StatusCode::UNPROCESSABLE_ENTITY)
+            }
+            ListError::JobTooLarge => ApiError::validation(
+                "JOB_TOO_LARGE",
+                "The list is too large to queue; split it into smaller lists",
+            )
+            .with_status(StatusCode::PAYLOAD_TOO_LARGE),
+            ListError::QuotaExceeded(e) => e.into(),
+            ListError::Queue(message) => ApiError::upstream("job queue", message),
+            ListError::Database(message) => ApiError::upstream("database", message),
+        }
+    }
+}
+
+pub(crate) fn list_not_found() -> ApiError {
+    ApiError::not_found("LIST_NOT_FOUND", "List not found")
+}
+
+/// Lists belong to the account's lowercased email, which is also its job tenant
+fn caller_owner(http_req: &HttpRequest) -> Result<String, ApiError> {
+    http_req
+        .extensions()
+        .get::<AuthenticatedAccount>()
+        .map(|account| account.email.to_lowercase())
+        .ok_or_else(|| ApiError::unauthorized("Missing Authorization header"))
+}
+
+fn check_emails(emails: &[String]) -> Result<(), ApiError> {
+    for (i, email) in emails.iter().enumerate() {
+        input_limits::check_email_field(&format!("emails[{}]", i), email)?;
+    }
+    Ok(())
+}
+
+/// # Create List
+///
+/// Creates a named list of addresses kept on the service, to validate as a bulk job
+/// later or on a schedule (see `POST /api/v1/schedules`). Addresses are trimmed and
+/// exact duplicates dropped.
+///
+/// ## Request
+/// ```json
+/// { "name": "Newsletter", "emails": ["user@example.com"] }
+/// ```
+///
+/// ## Responses
+/// - **201 Created**: `{ "list_id", "name", "emails", "created_at", "updated_at", "last_job_id" }`
+/// - **400 Bad Request**: `INVALID_NAME`
+/// - **413 Payload Too Large**: `LIST_TOO_LARGE`
+/// - **422 Unprocessable Entity**: `INVALID_FIELD`
+#[utoipa::path(
+    post,
+    path = "/api/v1/lists",
+    request_body = CreateListRequest,
+    responses(
+        (status = 201, description = "List created", body = SavedListDetail),
+        (status = 400, description = "INVALID_NAME", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 413, description = "LIST_TOO_LARGE", body = ErrorEnvelope),
+        (status = 422, description = "INVALID_FIELD: an address is too long or contains control characters", body = ErrorEnvelope)
+    ),
+    tag = "Lists"
+)]
+#[post("/lists")]
+pub async fn create_list(
+    req: web::Json<CreateListRequest>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let owner = caller_owner(&http_req)?;
+    check_emails(&req.emails)?;
+    let list = saved_lists::create_list(&mongo_client, &owner, &req.name, &req.emails).await?;
+    Ok(HttpResponse::Created().json(SavedListDetail::from(list)))
+}
+
+/// # List Lists
+///
+/// The caller's saved lists without their addresses, oldest first.
+#[utoipa::path(
+    get,
+    path = "/api/v1/lists",
+    responses(
+        (status = 200, description = "The caller's lists", body = [SavedListSummary]),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Lists"
+)]
+#[get("/lists")]
+pub async fn list_lists(
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let owner = caller_owner(&http_req)?;
+    let lists = saved_lists::list_lists(&mongo_client, &owner)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    Ok(HttpResponse::Ok().json(lists))
+}
+
+/// # Get List
+///
+/// One of the caller's lists with its addresses.
+#[utoipa::path(
+    get,
+    path = "/api/v1/lists/{list_id}",
+    params(("list_id" = String, Path, description = "ID returned when the list was created")),
+    responses(
+        (status = 200, description = "The list", body = SavedListDetail),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 404, description = "LIST_NOT_FOUND", body = ErrorEnvelope)
+    ),
+    tag = "Lists"
+)]
+#[get("/lists/{list_id}")]
+pub async fn get_list(
+    path: web::Path<String>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let owner = caller_owner(&http_req)?;
+    let list = saved_lists::get_list(&mongo_client, &owner, &path)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?
+        .ok_or_else(list_not_found)?;
+    Ok(HttpResponse::Ok().json(SavedListDetail::from(list)))
+}
+
+/// # Delete List
+///
+/// Deletes the list. Jobs already queued for it still complete.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/lists/{list_id}",
+    params(("list_id" = String, Path, description = "ID returned when the list was created")),
+    responses(
+        (status = 204, description = "List deleted"),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 404, description = "LIST_NOT_FOUND", body = ErrorEnvelope)
+    ),
+    tag = "Lists"
+)]
+#[delete("/lists/{list_id}")]
+pub async fn delete_list(
+    path: web::Path<String>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let owner = caller_owner(&http_req)?;
+    let deleted = saved_lists::delete_list(&mongo_client, &owner, &path)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    if !deleted {
+        return Err(list_not_found());
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// # Append Addresses
+///
+/// Appends addresses the list doesn't hold yet.
+///
+/// ## Responses
+/// - **200 OK**: `{ "list_id", "name", "email_count", "created_at", "updated_at", "last_job_id" }`
+/// - **404 Not Found**: `LIST_NOT_FOUND`
+/// - **413 Payload Too Large**: `LIST_TOO_LARGE`
+/// - **422 Unprocessable Entity**: `INVALID_FIELD`
+#[utoipa::path(
+    post,
+    path = "/api/v1/lists/{list_id}/emails",
+    params(("list_id" = String, Path, description = "ID returned when the list was created")),
+    request_body = ListEmailsRequest,
+    responses(
+        (status = 200, description = "The updated list", body = SavedListSummary),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 404, description = "LIST_NOT_FOUND", body = ErrorEnvelope),
+        (status = 413, description = "LIST_TOO_LARGE", body = ErrorEnvelope),
+        (status = 422, description = "INVALID_FIELD: an address is too long or contains control characters", body = ErrorEnvelope)
+    ),
+    tag = "Lists"
+)]
+#[post("/lists/{list_id}/emails")]
+pub async fn add_emails(
+    path: web::Path<String>,
+    req: web::Json<ListEmailsRequest>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let owner = caller_owner(&http_req)?;
+    check_emails(&req.emails)?;
+    let list = saved_lists::add_emails(&mongo_client, &owner, &path, &req.emails).await?;
+    Ok(HttpResponse::Ok().json(SavedListSummary::from(&list)))
+}
+
+/// # Remove Addresses
+///
+/// Removes addresses from the list; ones it doesn't hold are ignored.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/lists/{list_id}/emails",
+    params(("list_id" = String, Path, description = "ID returned when the list was created")),
+    request_body = ListEmailsRequest,
+    responses(
+        (status = 200, description = "The updated list", body = SavedListSummary),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 404, description = "LIST_NOT_FOUND", body = ErrorEnvelope)
+    ),
+    tag = "Lists"
+)]
+#[delete("/lists/{list_id}/emails")]
+pub async fn remove_emails(
+    path: web::Path<String>,
+    req: web::Json<ListEmailsRequest>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let owner = caller_owner(&http_req)?;
+    let list = saved_lists::remove_emails(&mongo_client, &owner, &path, &req.emails).await?;
+    Ok(HttpResponse::Ok().json(SavedListSummary::from(&list)))
+}
+
+/// # Validate List
+///
+/// Queues a bulk job validating every address of the list. Track it with
+/// `GET /api/v1/job-status/{job_id}` and read its results like any bulk job.
+///
+/// ## Responses
+/// - **202 Accepted**: `{ "job_id", "list_id", "status": "queued" }`
+/// - **404 Not Found**: `LIST_NOT_FOUND`
+/// - **413 Payload Too Large**: `JOB_TOO_LARGE`
+/// - **422 Unprocessable Entity**: `EMPTY_BATCH`, the list has no addresses
+#[utoipa::path(
+    post,
+    path = "/api/v1/lists/{list_id}/validate",
+    params(
+        ("list_id" = String, Path, description = "ID returned when the list was created"),
+        ("check_role_based" = Option<bool>, Query, description = "Enable role-based email validation")
+    ),
+    responses(
+        (status = 202, description = "Validation job queued"),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 404, description = "LIST_NOT_FOUND", body = ErrorEnvelope),
+        (status = 413, description = "JOB_TOO_LARGE", body = ErrorEnvelope),
+        (status = 422, description = "EMPTY_BATCH: the list has no addresses", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database or job queue is unavailable, or FEATURE_DISABLED", body = ErrorEnvelope)
+    ),
+    tag = "Lists"
+)]
+#[post("/lists/{list_id}/validate")]
+pub async fn validate_list(
+    path: web::Path<String>,
+    query: web::Query<ValidateListQuery>,
+    job_queue: web::Data<JobQueue>,
+    mongo_client: web::Data<MongoClient>,
+    flags: Option<web::Data<FeatureFlags>>,
+    meter: Option<web::Data<Meter>>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let owner = caller_owner(&http_req)?;
+    if let Some(flags) = flags {
+        flags.require(Flag::BulkEnabled).await?;
+    }
+    let list_id = path.into_inner();
+    let job_id = saved_lists::queue_validation(
+        &mongo_client,
+        &job_queue,
+        &owner,
+        &list_id,
+        query.check_role_based,
+        meter.as_ref().map(|meter| meter.get_ref()),
+    )
+    .await?;
+    Ok(HttpResponse::Accepted().json(json!({
+        "job_id": job_id,
+        "list_id": list_id,
+        "status": "queued",
+    })))
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_list)
+        .service(list_lists)
+        .service(get_list)
+        .service(delete_list)
+        .service(add_emails)
+        .service(remove_emails)
+        .service(validate_list);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, test};
+
+    #[actix_web::test]
+    async fn test_lists_require_account() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/lists")
+            .set_json(json!({ "name": "Newsletter", "emails": ["user@example.com"] }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::auth::AuthedAccount;
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::validation_cache::is_valid_hash_prefix;
+use crate::validation_service::ValidationBackends;
+use actix_web::{HttpResponse, get, web};
+use serde_json::json;
+
+/// # Hash Prefix Lookup Endpoint
+///
+/// Lets privacy-sensitive clients check addresses without sending them, modeled after
+/// the HIBP range API: the client hashes the lowercased address with SHA-256, sends the
+/// first 5 hex characters, and compares the returned candidate hashes locally.
+///
+/// Only addresses the caller's account has validated, and whose results are still
+/// within their cache lifetime, are returned.
+///
+/// ## Responses
+/// - **200 OK**: `{ "prefix": "a1b2c", "candidates": [{ "hash", "is_valid", "code", "validated_at", "expires_at" }] }`
+/// - **400 Bad Request**: Prefix is not exactly 5 hex characters (`INVALID_HASH_PREFIX`)
+/// - **401 Unauthorized**: Missing or invalid API key
+#[utoipa::path(
+    get,
+    path = "/api/v1/lookup/hash/{prefix}",
+    params(("prefix" = String, Path, description = "First 5 hex characters of the address's SHA-256")),
+    responses(
+        (status = 200, description = "Candidate verdicts for the prefix"),
+        (status = 400, description = "Invalid hash prefix", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[get("/lookup/hash/{prefix}")]
+pub async fn lookup_hash_prefix(
+    account: AuthedAccount,
+    path: web::Path<String>,
+    backends: web::Data<ValidationBackends>,
+) -> Result<HttpResponse, ApiError> {
+    let prefix = path.into_inner().to_lowercase();
+    if !is_valid_hash_prefix(&prefix) {
+        return Err(ApiError::validation(
+            "INVALID_HASH_PREFIX",
+            "Prefix must be exactly 5 hexadecimal characters of a SHA-256 hash",
+        ));
+    }
+
+    let candidates = backends
+        .cache
+        .lookup_hash_prefix(&account.tenant_id(), &prefix)
+        .await
+        .map_err(|e| ApiError::upstream("cache", e))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "prefix": prefix,
+        "candidates": candidates
+    })))
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(lookup_hash_prefix);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stores::MemoryStore;
+    use actix_web::{App, http::StatusCode, test};
+    use mongodb::Client as MongoClient;
+    use std::sync::Arc;
+
+    #[actix_web::test]
+    async fn test_lookup_requires_authorization() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(ValidationBackends::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .app_data(web::Data::new(mongo_client))
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/lookup/hash/a1b2c")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
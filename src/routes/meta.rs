@@ -0,0 +1,132 @@
+use crate::error_codes::{ERROR_CODES, ErrorCodeInfo, ValidationErrorCode, validation_error_codes};
+use actix_web::{HttpResponse, Responder, get, web};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct ErrorCodeRegistry {
+    pub codes: Vec<ErrorCodeInfo>,
+}
+
+/// # Error Code Registry
+///
+/// Every error and verdict code the API returns, with the HTTP statuses it comes
+/// with, its severity and whether retrying can help. Public and cacheable, so SDKs
+/// and docs can be generated from it.
+///
+/// ## Responses
+/// - **200 OK**: `{ "codes": [{ "code", "kind", "statuses", "severity", "retryable", "description" }] }`
+#[utoipa::path(
+    get,
+    path = "/api/v1/meta/error-codes",
+    responses(
+        (status = 200, description = "All error and verdict codes", body = ErrorCodeRegistry)
+    ),
+    security(()),
+    tag = "Meta"
+)]
+#[get("/meta/error-codes")]
+pub async fn error_codes() -> impl Responder {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "public, max-age=3600"))
+        .json(ErrorCodeRegistry {
+            codes: ERROR_CODES.to_vec(),
+        })
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ValidationErrorCatalog {
+    pub codes: Vec<ValidationErrorCode>,
+}
+
+/// # Validation Error Codes
+///
+/// The codes a validation result can carry in `error.code` (v1) or `reason.code`
+/// (v2), with what each means, its severity and whether validating again later may
+/// reach another verdict. GraphQL serves the same list as the `errorCodes` query.
+/// Request error codes are listed by `/meta/error-codes`.
+///
+/// ## Example Response
+/// ```json
+/// { "codes": [{ "code": "DNS_UNAVAILABLE", "severity": "critical", "retryable": true, "description": "..." }] }
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/error-codes",
+    responses(
+        (status = 200, description = "All validation result codes", body = ValidationErrorCatalog)
+    ),
+    security(()),
+    tag = "Meta"
+)]
+#[get("/error-codes")]
+pub async fn validation_error_catalog() -> impl Responder {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "public, max-age=3600"))
+        .json(ValidationErrorCatalog {
+            codes: validation_error_codes(),
+        })
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(error_codes).service(validation_error_catalog);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, http::StatusCode, test};
+
+    #[actix_web::test]
+    async fn test_error_codes() {
+        let app = test::init_service(
+            App::new().service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/meta/error-codes")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let codes = body["codes"].as_array().unwrap();
+        assert_eq!(codes.len(), ERROR_CODES.len());
+        let syntax = codes
+            .iter()
+            .find(|code| code["code"] == "INVALID_SYNTAX")
+            .unwrap();
+        assert_eq!(syntax["kind"], "verdict");
+        assert_eq!(syntax["retryable"], false);
+        let upstream = codes
+            .iter()
+            .find(|code| code["code"] == "UPSTREAM_UNAVAILABLE")
+            .unwrap();
+        assert_eq!(upstream["statuses"], serde_json::json!([503]));
+        assert_eq!(upstream["severity"], "critical");
+    }
+
+    #[actix_web::test]
+    async fn test_validation_error_catalog() {
+        let app = test::init_service(
+            App::new().service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/error-codes")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let codes = body["codes"].as_array().unwrap();
+        assert!(codes.iter().all(|code| code.get("statuses").is_none()));
+        let dns = codes
+            .iter()
+            .find(|code| code["code"] == "DNS_UNAVAILABLE")
+            .unwrap();
+        assert_eq!(dns["severity"], "critical");
+        assert_eq!(dns["retryable"], true);
+        assert!(codes.iter().all(|code| code["code"] != "UNAUTHORIZED"));
+    }
+}
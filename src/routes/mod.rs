@@ -1,8 +1,31 @@
+use crate::audit_log::Audit;
+use crate::auth::Auth;
+use crate::input_limits;
 use actix_web::web;
+pub mod account;
+pub mod admin;
 pub mod auth;
+pub mod branding;
+pub mod dashboard;
+pub mod data;
 pub mod email;
+pub mod feedback;
 pub mod graphql;
 pub mod health;
+pub mod history;
+pub mod keys;
+pub mod lists;
+pub mod lookup;
+pub mod meta;
+pub mod orgs;
+pub mod schedules;
+pub mod share;
+pub mod stats;
+pub mod status;
+pub mod suppressions;
+pub mod sync;
+pub mod v2;
+pub mod validation;
 
 #[cfg(test)]
 mod email_test;
@@ -19,22 +42,147 @@ mod email_edge_case_tests;
 /// - REST endpoints for health checks and email validation
 /// - GraphQL API endpoints and playground
 /// - Unified error handling across all routes
+/// - JSON bodies capped at `MAX_JSON_PAYLOAD_BYTES` (see [`input_limits::json_config`])
+///
+/// # Authentication
+/// Every route in the scope requires a session token or API key (see [`Auth`]),
+/// except `/health`, `/health/history`, `/ready`, `/register`, `/verify`, `/playground`, `/auth/*`,
+/// `/meta/error-codes` and `/error-codes`. Admin routes take keys with the `admin` scope,
+/// which only accounts listed in `ADMIN_ACCOUNTS` can issue.
+///
+/// # Metering
+/// Every validated address counts against the account plan's monthly quota, with the
+/// overage policy applied once it is used up (see [`crate::metering::PlanQuota`]).
+/// Single, bulk and queued validations, saved list runs, GraphQL and gRPC all charge
+/// through [`crate::metering::charge`]. Members of an organization draw on one shared
+/// quota.
+///
+/// # Audit Log
+/// Every authenticated request, admin requests included, is recorded with its account,
+/// route, input hash, outcome, latency and client address (see
+/// [`crate::audit_log::Audit`]), queried through `GET /api/v1/admin/audit`.
 ///
 /// # API Versioning
 /// - Current version: `1.0`
 /// - Base path: `/api/v1`
+/// - Next version: `/api/v2`, with verdict and score based results (see [`v2`]). Only
+///   single address validation is served there so far; both versions run the same
+///   validation core and differ in how they render results. Each version has its own
+///   OpenAPI document, `/api-docs/v1.json` and `/api-docs/v2.json`.
+///
+/// `/api/v2` applies the same authentication, metering, auditing and payload limits as
+/// `/api/v1`.
 ///
 /// # Mounted Services
 /// - Health Monitoring: [`health::configure_routes`]
+/// - API Metadata: [`meta::configure_routes`]
+/// - Cache Administration: [`admin::configure_routes`]
+/// - Admin Dashboard Data: [`dashboard::configure_routes`]
+/// - Hashed Lookups: [`lookup::configure_routes`]
+/// - Validation Statistics: [`stats::configure_routes`]
+/// - API Key Management: [`keys::configure_routes`]
+/// - Account Lists: [`account::configure_routes`]
+/// - Bounce Feedback: [`feedback::configure_routes`]
+/// - Suppression List: [`suppressions::configure_routes`]
+/// - Data Erasure: [`data::configure_routes`]
+/// - Tenant Branding: [`branding::configure_routes`]
+/// - Organizations: [`orgs::configure_routes`]
+/// - On-prem Sync: [`sync::configure_routes`]
 /// - Email Validation: [`email::configure_routes`]
+/// - Email Validation v2 (under `/api/v2`): [`v2::configure_routes`]
+/// - Validation Results (conditional GET): [`validation::configure_routes`]
+/// - Validation History: [`history::configure_routes`]
+/// - Saved Lists: [`lists::configure_routes`]
+/// - Re-validation Schedules: [`schedules::configure_routes`]
 /// - GraphQL Interface: [`graphql::configure_routes`]
+/// - Status Page (site root, public): [`status::configure_routes`]
+/// - Shared Result Viewer (site root, signed links): [`share::configure_routes`]
 ///
 /// # Endpoints Overview
 /// ```text
-/// GET    /api/v1/health       - Service health status
+/// GET    /api/v1/health       - Liveness, with MongoDB/Redis/DNS probe latencies
+/// GET    /api/v1/ready        - Readiness, 503 until every dependency is reachable
+/// GET    /api/v1/health/history - Dependency status transitions and 24h/7d/30d uptime
+/// GET    /api/v1/meta/error-codes - Every error/verdict code with status, severity, retryability
+/// GET    /api/v1/error-codes  - Validation result codes with severity and retryability
+/// GET    /api/v1/verify?token=... - Activate a registered account from its verification link
+/// POST   /api/v1/auth/login   - Exchange email/password for session tokens
+/// POST   /api/v1/auth/refresh - Exchange a refresh token for a new token pair
 /// POST   /api/v1/validate-email - Email validation with Redis caching
+/// GET    /api/v1/validate-email?email=... - The same for GET-only integrations, with Cache-Control
+/// POST   /api/v1/validate-email-async - Queue one address; the result is posted to the account webhook
+/// GET    /api/v1/validation/{email} - One address's result with an ETag; 304 on If-None-Match
+/// GET    /api/v1/history      - Audit trail of the account's validations (hashed addresses)
+/// GET    /api/v1/job-results/{job_id} - Completed bulk job results as (compressible) CSV, filterable
+/// GET    /api/v1/job-results/{job_id}/summary - Cached verdict counts and top domains of a job
+/// GET    /api/v1/job-report/{job_id} - List hygiene percentages, risky domains and quality grade
+/// GET    /api/v1/job-results/{job_id}/export?format=csv|jsonl|xlsx - Annotated results for ESP re-import
+/// POST   /api/v1/job-results/{job_id}/share - Signed link to a read-only HTML results viewer
+/// GET    /api/v1/jobs         - The account's bulk jobs, filterable by status
+/// DELETE /api/v1/jobs/{job_id} - Cancel a pending job or purge a finished one
+/// GET    /api/v1/jobs/{job_id}/results - One page of a job's results, filterable by verdict or error code
+/// POST   /api/v1/lists        - Create a named list of addresses
+/// GET    /api/v1/lists        - The account's saved lists (without addresses)
+/// GET    /api/v1/lists/{id}   - One saved list and its addresses
+/// DELETE /api/v1/lists/{id}   - Delete a saved list
+/// POST|DELETE /api/v1/lists/{id}/emails - Append or remove addresses
+/// POST   /api/v1/lists/{id}/validate - Queue a bulk job validating the whole list
+/// POST   /api/v1/schedules    - Re-validate a completed job or saved list on a cron schedule
+/// GET    /api/v1/schedules    - The account's schedules with their latest run diffs
+/// GET    /api/v1/schedules/{id} - One schedule and its runs
+/// DELETE /api/v1/schedules/{id} - Stop re-validating
+/// DELETE /api/v1/admin/cache/email/{email}   - Drop cached results for an address
+/// DELETE /api/v1/admin/cache/domain/{domain} - Drop cached results for a domain
+/// GET    /api/v1/admin/cache/stats           - Cache hit/miss and key counts
+/// GET    /api/v1/admin/canary/stats          - Canary vs legacy verdict mismatches and latency
+/// DELETE /api/v1/admin/canary/stats          - Reset canary comparison counters
+/// GET    /api/v1/admin/lists/{list}          - Blue/green slot status of a reference list
+/// POST   /api/v1/admin/lists/{list}/activate/{slot} - Switch a list to a staged slot
+/// POST   /api/v1/admin/lists/{list}/rollback - Switch a list back to its previous slot
+/// GET    /api/v1/admin/flags                 - Feature flags with their values and sources
+/// PUT    /api/v1/admin/flags/{flag}          - Switch a feature flag at runtime
+/// GET    /api/v1/admin/dns/overrides         - Custom nameservers and per-domain overrides
+/// PUT|DELETE /api/v1/admin/dns/overrides/{domain} - Send a domain's lookups to its own nameservers
+/// GET    /api/v1/admin/audit                 - Audit log of authenticated requests
+/// DELETE /api/v1/admin/data/email/{email}    - Erase an address from every account's data
+/// GET    /api/v1/admin/queue                 - Queue depth and worker liveness
+/// GET    /api/v1/admin/jobs/failed           - Most recent failed jobs
+/// GET    /api/v1/admin/usage?period=YYYY-MM  - Accounts ranked by metered usage
+/// GET    /api/v1/admin/error-rate?window=... - Per-bucket validation totals and error rates
+/// GET    /api/v1/admin/dependencies          - Dependency probes with status history
+/// GET    /api/v1/lookup/hash/{prefix}        - Verdicts by SHA-256 prefix (k-anonymity)
+/// GET    /api/v1/stats?window=hour|day|month - Verdict, error code, cache and latency totals
+/// POST   /api/v1/keys         - Issue an additional API key
+/// GET    /api/v1/keys         - List the account's API keys
+/// DELETE /api/v1/keys/{id}    - Revoke an API key
+/// GET|PUT /api/v1/account/settings                    - Default validation settings
+/// GET    /api/v1/account/webhook-secret                - Secret webhooks are signed with
+/// POST   /api/v1/account/webhook-secret/rotate         - Replace the webhook secret
+/// GET    /api/v1/account/{blocklist|allowlist}         - The account's custom list entries
+/// POST   /api/v1/account/{blocklist|allowlist}         - Add a domain or address
+/// DELETE /api/v1/account/{blocklist|allowlist}/{entry} - Remove an entry
+/// POST   /api/v1/feedback/bounces - SES/SNS or generic bounce and complaint notifications
+/// POST   /api/v1/feedback/sns - Signed SNS notifications of registered topics (public)
+/// GET    /api/v1/suppressions - The account's suppressed addresses, filterable by reason
+/// POST   /api/v1/suppressions - Suppress an address with a reason code
+/// DELETE /api/v1/suppressions/{email} - Lift a suppression
+/// POST   /api/v1/suppressions/import - Suppress up to 10,000 addresses at once
+/// GET    /api/v1/suppressions/export?format=csv|jsonl - Download the whole list
+/// DELETE /api/v1/data/email/{email} - Erase an address from the caller's stored data
+/// GET|PUT|DELETE /api/v1/branding - Name and logo shown on generated artifacts
+/// POST   /api/v1/orgs          - Create an organization owned by the caller
+/// GET    /api/v1/orgs/current  - The caller's organization and members
+/// POST   /api/v1/orgs/current/members         - Add an account (owners only)
+/// DELETE /api/v1/orgs/current/members/{email} - Remove a member, or leave
+/// GET    /api/v1/sync/lists    - Incremental list updates for on-prem replicas
+/// POST   /api/v1/sync/verdicts - Aggregated outcome stats pushed by on-prem replicas
 /// POST   /api/v1/graphql      - GraphQL query endpoint
-/// GET    /api/v1/playground   - Interactive GraphQL IDE
+/// GET    /api/v1/graphql      - GraphQL queries (no mutations), e.g. persisted queries
+/// GET    /api/v1/playground   - Interactive GraphQL IDE (not in production)
+/// POST   /api/v2/validate-email - One address's verdict and score
+/// GET    /api/v2/validate-email?email=... - The same for GET-only integrations, with Cache-Control
+/// GET    /status              - Public HTML status page
+/// GET    /share/{token}       - Read-only HTML results viewer behind a signed link
 /// ```
 ///
 /// # Architecture
@@ -44,22 +192,74 @@ mod email_edge_case_tests;
 /// - Maintain separation of concerns between features
 ///
 /// [`health::configure_routes`]: crate::routes::health::configure_routes
+/// [`meta::configure_routes`]: crate::routes::meta::configure_routes
+/// [`admin::configure_routes`]: crate::routes::admin::configure_routes
+/// [`dashboard::configure_routes`]: crate::routes::dashboard::configure_routes
+/// [`lookup::configure_routes`]: crate::routes::lookup::configure_routes
+/// [`stats::configure_routes`]: crate::routes::stats::configure_routes
+/// [`keys::configure_routes`]: crate::routes::keys::configure_routes
+/// [`account::configure_routes`]: crate::routes::account::configure_routes
+/// [`feedback::configure_routes`]: crate::routes::feedback::configure_routes
+/// [`suppressions::configure_routes`]: crate::routes::suppressions::configure_routes
+/// [`data::configure_routes`]: crate::routes::data::configure_routes
+/// [`branding::configure_routes`]: crate::routes::branding::configure_routes
+/// [`orgs::configure_routes`]: crate::routes::orgs::configure_routes
+/// [`sync::configure_routes`]: crate::routes::sync::configure_routes
 /// [`email::configure_routes`]: crate::routes::email::configure_routes
+/// [`v2::configure_routes`]: crate::routes::v2::configure_routes
+/// [`validation::configure_routes`]: crate::routes::validation::configure_routes
+/// [`history::configure_routes`]: crate::routes::history::configure_routes
+/// [`lists::configure_routes`]: crate::routes::lists::configure_routes
+/// [`schedules::configure_routes`]: crate::routes::schedules::configure_routes
 /// [`graphql::configure_routes`]: crate::routes::graphql::configure_routes
+/// [`status::configure_routes`]: crate::routes::status::configure_routes
+/// [`share::configure_routes`]: crate::routes::share::configure_routes
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/api/v1")
+            // Auth wraps auditing so the account is known by the time it runs
+            .wrap(Audit::from_app_data())
+            .wrap(Auth::from_app_data())
+            .app_data(input_limits::json_config())
             .configure(auth::configure_routes)
             .configure(health::configure_routes)
+            .configure(meta::configure_routes)
+            .configure(admin::configure_routes)
+            .configure(dashboard::configure_routes)
             .configure(email::configure_routes)
+            .configure(validation::configure_routes)
+            .configure(history::configure_routes)
+            .configure(lists::configure_routes)
+            .configure(schedules::configure_routes)
+            .configure(lookup::configure_routes)
+            .configure(stats::configure_routes)
+            .configure(keys::configure_routes)
+            .configure(account::configure_routes)
+            .configure(feedback::configure_routes)
+            .configure(suppressions::configure_routes)
+            .configure(data::configure_routes)
+            .configure(branding::configure_routes)
+            .configure(orgs::configure_routes)
+            .configure(sync::configure_routes)
             .configure(graphql::configure_routes),
-    );
+    )
+    .service(
+        web::scope("/api/v2")
+            .wrap(Audit::from_app_data())
+            .wrap(Auth::from_app_data())
+            .app_data(input_limits::json_config())
+            .configure(v2::configure_routes),
+    )
+    .configure(status::configure_routes)
+    .configure(share::configure_routes);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::routes::email::RedisCache;
+    use crate::stores::{MemoryDomainLists, MemoryStore};
+    use crate::validation_service::ValidationBackends;
     use actix_web::{
         App, Error,
         body::to_bytes,
@@ -69,17 +269,18 @@ mod tests {
         web::Data,
     };
     use serde_json::json;
+    use std::sync::Arc;
 
-    /// Helper function to create test Redis cache
-    fn create_test_redis_cache() -> RedisCache {
-        // For tests, we'll use a mock that avoids actual Redis connections
+    /// Helper function to create test validation backends
+    fn create_test_backends() -> ValidationBackends {
         // This could be a real Redis connection if available in test environment
-        RedisCache::new("redis://127.0.0.1:6379", 3600).unwrap_or_else(|_| {
-            // If connection fails, we need a fallback for tests
-            // In a real application, you might want to use a mock instead
-            eprintln!("Warning: Using dummy Redis cache for tests - DNS lookup caching disabled");
-            RedisCache::test_dummy()
-        })
+        match RedisCache::new("redis://127.0.0.1:6379", 3600) {
+            Ok(redis_cache) => {
+                ValidationBackends::from_env(redis_cache, Arc::new(MemoryDomainLists::default()))
+            }
+            // If the URL is rejected, the stores are kept in memory instead
+            Err(_) => ValidationBackends::from_store(Arc::new(MemoryStore::default())),
+        }
     }
 
     /// Tests the basic API structure configuration
@@ -89,7 +290,7 @@ mod tests {
         // Create a simple test app with our route configuration
         let _app = test::init_service(
             App::new()
-                .app_data(Data::new(create_test_redis_cache()))
+                .app_data(Data::new(create_test_backends()))
                 .configure(configure),
         )
         .await;
@@ -102,7 +303,7 @@ mod tests {
     async fn test_health_endpoint() -> Result<(), Error> {
         let app = test::init_service(
             App::new()
-                .app_data(Data::new(create_test_redis_cache()))
+                .app_data(Data::new(create_test_backends()))
                 .configure(configure),
         )
         .await;
@@ -145,7 +346,7 @@ mod tests {
 
         let app = test::init_service(
             App::new()
-                .app_data(Data::new(create_test_redis_cache()))
+                .app_data(Data::new(create_test_backends()))
                 .app_data(Data::new(mongo_client))
                 .app_data(Data::new(job_queue))
                 .configure(configure),
@@ -169,15 +370,15 @@ mod tests {
         let invalid_auth_resp = app.call(invalid_auth_req).await?;
         assert_eq!(invalid_auth_resp.status(), StatusCode::UNAUTHORIZED);
 
-        // Test missing request body with auth
+        // Credentials are checked before the body is read, so an unknown key
+        // is rejected even when the body is missing or malformed
         let empty_body_req = test::TestRequest::post()
             .uri("/api/v1/validate-email")
             .insert_header(("Authorization", "Bearer test-key"))
             .to_request();
         let empty_body_resp = app.call(empty_body_req).await?;
-        assert_eq!(empty_body_resp.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(empty_body_resp.status(), StatusCode::UNAUTHORIZED);
 
-        // Test malformed JSON body with auth
         let malformed_req = test::TestRequest::post()
             .uri("/api/v1/validate-email")
             .insert_header(("Authorization", "Bearer test-key"))
@@ -185,7 +386,7 @@ mod tests {
             .set_payload("{invalid_json:}") // Deliberately malformed
             .to_request();
         let malformed_resp = app.call(malformed_req).await?;
-        assert_eq!(malformed_resp.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(malformed_resp.status(), StatusCode::UNAUTHORIZED);
 
         Ok(())
     }
@@ -195,7 +396,7 @@ mod tests {
     async fn test_graphql_endpoints() -> Result<(), Error> {
         let app = test::init_service(
             App::new()
-                .app_data(Data::new(create_test_redis_cache()))
+                .app_data(Data::new(create_test_backends()))
                 .configure(configure),
         )
         .await;
@@ -230,7 +431,7 @@ mod tests {
     async fn test_api_versioning_and_scope() -> Result<(), Error> {
         let app = test::init_service(
             App::new()
-                .app_data(Data::new(create_test_redis_cache()))
+                .app_data(Data::new(create_test_backends()))
                 .configure(configure),
         )
         .await;
@@ -257,6 +458,14 @@ mod tests {
         let resp = app.call(req).await?;
         assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
 
+        // v2 validation sits behind the same authentication as v1
+        let req = test::TestRequest::post()
+            .uri("/api/v2/validate-email")
+            .set_json(json!({ "email": "test@example.com" }))
+            .to_request();
+        let resp = app.call(req).await?;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
         Ok(())
     }
 
@@ -264,13 +473,13 @@ mod tests {
     /// Validates the entire route structure works together
     #[actix_web::test]
     async fn test_api_v1_integration() -> Result<(), Error> {
-        // Create Redis cache for testing
-        let redis_cache = create_test_redis_cache();
+        // Create validation backends for testing
+        let backends = create_test_backends();
 
-        // Initialize app with Redis cache data
+        // Initialize app with the backends as data
         let app = test::init_service(
             App::new()
-                .app_data(Data::new(redis_cache))
+                .app_data(Data::new(backends))
                 .configure(configure),
         )
         .await;
@@ -0,0 +1,272 @@
+use crate::auth::AuthenticatedAccount;
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::organizations::{self, OrgError, OrgRole, Organization};
+use actix_web::http::StatusCode;
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, delete, get, post, web};
+use mongodb::Client as MongoClient;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateOrganizationRequest {
+    pub name: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct AddMemberRequest {
+    /// Email of a registered account outside any organization
+    pub email: String,
+    /// Defaults to `member`
+    #[serde(default)]
+    pub role: OrgRole,
+}
+
+impl From<OrgError> for ApiError {
+    fn from(e: OrgError) -> Self {
+        match e {
+            OrgError::InvalidName(message) => ApiError::validation("INVALID_NAME", message),
+            OrgError::AlreadyMember => ApiError::validation(
+                "ALREADY_IN_ORGANIZATION",
+                "The account already belongs to an organization",
+            )
+            .with_status(StatusCode::CONFLICT),
+            OrgError::AccountNotFound => {
+                ApiError::not_found("MEMBER_NOT_FOUND", "No such account or member")
+            }
+            OrgError::NotFound => not_in_organization(),
+            OrgError::LastOwner => {
+                ApiError::validation("LAST_OWNER", "An organization needs at least one owner")
+                    .with_status(StatusCode::CONFLICT)
+            }
+            OrgError::Database(message) => ApiError::upstream("database", message),
+        }
+    }
+}
+
+fn not_in_organization() -> ApiError {
+    ApiError::not_found(
+        "NOT_IN_ORGANIZATION",
+        "The account does not belong to an organization",
+    )
+}
+
+fn caller_email(http_req: &HttpRequest) -> Result<String, ApiError> {
+    http_req
+        .extensions()
+        .get::<AuthenticatedAccount>()
+        .map(|account| account.email.clone())
+        .ok_or_else(|| ApiError::unauthorized("Missing Authorization header"))
+}
+
+/// The caller's organization along with their email
+async fn caller_organization(
+    http_req: &HttpRequest,
+    mongo_client: &MongoClient,
+) -> Result<(String, Organization), ApiError> {
+    let email = caller_email(http_req)?;
+    let org = organizations::organization_of(mongo_client, &email)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?
+        .ok_or_else(not_in_organization)?;
+    Ok((email, org))
+}
+
+fn require_owner(org: &Organization, email: &str) -> Result<(), ApiError> {
+    if org.role_of(email) == Some(OrgRole::Owner) {
+        return Ok(());
+    }
+    Err(
+        ApiError::validation("FORBIDDEN", "Only organization owners can manage members")
+            .with_status(StatusCode::FORBIDDEN),
+    )
+}
+
+/// # Create Organization
+///
+/// Creates an organization with the caller as its owner. Members of an organization
+/// share one monthly quota (at the highest plan among them) and one set of custom
+/// lists, so an agency can issue credentials to several clients under one billing
+/// entity.
+///
+/// ## Responses
+/// - **201 Created**: `{ "org_id", "name", "members": [{ "email", "role", "joined_at" }], "created_at" }`
+/// - **400 Bad Request**: `INVALID_NAME`
+/// - **409 Conflict**: `ALREADY_IN_ORGANIZATION`
+#[utoipa::path(
+    post,
+    path = "/api/v1/orgs",
+    request_body = CreateOrganizationRequest,
+    responses(
+        (status = 201, description = "Organization created", body = Organization),
+        (status = 400, description = "INVALID_NAME", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 409, description = "ALREADY_IN_ORGANIZATION", body = ErrorEnvelope)
+    ),
+    tag = "Organizations"
+)]
+#[post("/orgs")]
+pub async fn create_organization(
+    req: web::Json<CreateOrganizationRequest>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let email = caller_email(&http_req)?;
+    let org = organizations::create_organization(&mongo_client, &email, &req.name).await?;
+    Ok(HttpResponse::Created().json(org))
+}
+
+/// # Current Organization
+///
+/// ## Responses
+/// - **200 OK**: The caller's organization and its members
+/// - **404 Not Found**: `NOT_IN_ORGANIZATION`
+#[utoipa::path(
+    get,
+    path = "/api/v1/orgs/current",
+    responses(
+        (status = 200, description = "The caller's organization", body = Organization),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 404, description = "NOT_IN_ORGANIZATION", body = ErrorEnvelope)
+    ),
+    tag = "Organizations"
+)]
+#[get("/orgs/current")]
+pub async fn current_organization(
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let (_, org) = caller_organization(&http_req, &mongo_client).await?;
+    Ok(HttpResponse::Ok().json(org))
+}
+
+/// # Add Member
+///
+/// Adds a registered account to the caller's organization. Owners only.
+///
+/// ## Responses
+/// - **201 Created**: The updated organization
+/// - **403 Forbidden**: `FORBIDDEN`, the caller isn't an owner
+/// - **404 Not Found**: `NOT_IN_ORGANIZATION` or `MEMBER_NOT_FOUND` (no such account)
+/// - **409 Conflict**: `ALREADY_IN_ORGANIZATION`
+#[utoipa::path(
+    post,
+    path = "/api/v1/orgs/current/members",
+    request_body = AddMemberRequest,
+    responses(
+        (status = 201, description = "Member added", body = Organization),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 403, description = "FORBIDDEN", body = ErrorEnvelope),
+        (status = 404, description = "NOT_IN_ORGANIZATION or MEMBER_NOT_FOUND", body = ErrorEnvelope),
+        (status = 409, description = "ALREADY_IN_ORGANIZATION", body = ErrorEnvelope)
+    ),
+    tag = "Organizations"
+)]
+#[post("/orgs/current/members")]
+pub async fn add_member(
+    req: web::Json<AddMemberRequest>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let (email, org) = caller_organization(&http_req, &mongo_client).await?;
+    require_owner(&org, &email)?;
+
+    let org =
+        organizations::add_member(&mongo_client, &org.org_id, req.email.trim(), req.role).await?;
+    Ok(HttpResponse::Created().json(org))
+}
+
+/// # Remove Member
+///
+/// Owners can remove any member; members can only remove themselves to leave.
+/// The last owner can't leave.
+///
+/// ## Responses
+/// - **204 No Content**: Member removed
+/// - **403 Forbidden**: `FORBIDDEN`
+/// - **404 Not Found**: `NOT_IN_ORGANIZATION` or `MEMBER_NOT_FOUND`
+/// - **409 Conflict**: `LAST_OWNER`
+#[utoipa::path(
+    delete,
+    path = "/api/v1/orgs/current/members/{email}",
+    params(("email" = String, Path, description = "Email of the member to remove")),
+    responses(
+        (status = 204, description = "Member removed"),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 403, description = "FORBIDDEN", body = ErrorEnvelope),
+        (status = 404, description = "NOT_IN_ORGANIZATION or MEMBER_NOT_FOUND", body = ErrorEnvelope),
+        (status = 409, description = "LAST_OWNER", body = ErrorEnvelope)
+    ),
+    tag = "Organizations"
+)]
+#[delete("/orgs/current/members/{email}")]
+pub async fn remove_member(
+    path: web::Path<String>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let (email, org) = caller_organization(&http_req, &mongo_client).await?;
+    let target = path.into_inner();
+    if target != email {
+        require_owner(&org, &email)?;
+    }
+
+    organizations::remove_member(&mongo_client, &org, &target).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_organization)
+        .service(current_organization)
+        .service(add_member)
+        .service(remove_member);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, test};
+
+    #[actix_web::test]
+    async fn test_organization_routes_require_account() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/orgs")
+            .set_json(serde_json::json!({ "name": "Agency" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/orgs/current")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn test_org_errors_map_to_statuses() {
+        use actix_web::ResponseError;
+
+        assert_eq!(
+            ApiError::from(OrgError::AlreadyMember).status_code(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError::from(OrgError::NotFound).status_code(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ApiError::from(OrgError::InvalidName(String::new())).status_code(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use crate::account_settings;
+use crate::auth::AuthedAccount;
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::job_queue::{JobQueue, JobStatus};
+use crate::routes::email::load_owned_job;
+use crate::routes::lists::list_not_found;
+use crate::saved_lists;
+use crate::schedules::{
+    self, MAX_SCHEDULED_EMAILS, NewSchedule, Schedule, ScheduleRun, ScheduleSource,
+    ScheduledAddress,
+};
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, Responder, delete, get, post, web};
+use mongodb::Client as MongoClient;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateScheduleRequest {
+    /// Completed bulk job whose addresses are re-validated; set this or `list_id`
+    pub job_id: Option<String>,
+    /// Saved list re-validated with the addresses it holds at each run
+    pub list_id: Option<String>,
+    /// Five-field cron expression in UTC, or `@hourly`, `@daily`, `@weekly`, `@monthly`
+    pub cron: String,
+    /// Receives a POST with the diff after every run
+    pub webhook_url: Option<String>,
+    /// Role-based checks for list schedules; job schedules keep the job's setting
+    #[serde(default)]
+    pub check_role_based: bool,
+}
+
+/// A schedule as returned by the API; the address list itself is not included
+#[derive(Serialize, ToSchema)]
+pub struct ScheduleView {
+    pub schedule_id: String,
+    pub source: ScheduleSource,
+    pub cron: String,
+    pub check_role_based: bool,
+    pub webhook_url: Option<String>,
+    pub created_at: i64,
+    pub next_run_at: i64,
+    pub email_count: usize,
+    /// Job of the run in progress
+    pub running_job_id: Option<String>,
+    /// Latest runs, oldest first
+    pub runs: Vec<ScheduleRun>,
+}
+
+impl From<Schedule> for ScheduleView {
+    fn from(schedule: Schedule) -> Self {
+        ScheduleView {
+            schedule_id: schedule.schedule_id,
+            source: schedule.source,
+            cron: schedule.cron,
+            check_role_based: schedule.check_role_based,
+            webhook_url: schedule.webhook_url,
+            created_at: schedule.created_at,
+            next_run_at: schedule.next_run_at,
+            email_count: schedule.addresses.len(),
+            running_job_id: schedule.running_job_id,
+            runs: schedule.runs,
+        }
+    }
+}
+
+fn schedule_not_found() -> ApiError {
+    ApiError::not_found("SCHEDULE_NOT_FOUND", "Schedule not found")
+}
+
+/// Whether the tenant's completed job checked role-based addresses, and its verdicts
+async fn job_addresses(
+    job_queue: &JobQueue,
+    tenant_id: &str,
+    job_id: &str,
+) -> Result<(bool, Vec<ScheduledAddress>), ApiError> {
+    let job = load_owned_job(job_queue, job_id, tenant_id).await?;
+    if job.status != JobStatus::Completed {
+        return Err(ApiError::validation(
+            "JOB_NOT_COMPLETE",
+            "Only completed jobs can be scheduled for re-validation",
+        )
+        .with_status(StatusCode::CONFLICT)
+        .with_details(json!({ "status": job.status })));
+    }
+
+    let rows = schedules::job_results(job_queue, job_id)
+        .await
+        .map_err(|e| ApiError::upstream("job queue", e))?;
+    Ok((
+        job.check_role_based,
+        schedules::addresses_from_results(&rows),
+    ))
+}
+
+/// # Create Schedule
+///
+/// Re-validates the addresses of a completed bulk job, or of a saved list, on a cron
+/// schedule (UTC). Each run is queued as a bulk job of the account; once it completes,
+/// its verdicts are compared with the previous run's and the diff ("32 addresses newly
+/// invalid since last run") is recorded and posted to `webhook_url`, if set, or else
+/// to the `webhook_url` of the account settings.
+///
+/// ## Request
+/// ```json
+/// { "job_id": "…", "cron": "0 6 * * 1", "webhook_url": "https://example.com/hooks/lists" }
+/// { "list_id": "…", "cron": "@daily", "check_role_based": true }
+/// ```
+///
+/// ## Responses
+/// - **201 Created**: the schedule, without its addresses
+/// - **400 Bad Request**: `INVALID_SCHEDULE`, `INVALID_WEBHOOK_URL`, or `INVALID_INPUT`
+///   when not exactly one of `job_id` and `list_id` is set
+/// - **404 Not Found**: `JOB_NOT_FOUND` or `LIST_NOT_FOUND`
+/// - **409 Conflict**: `JOB_NOT_COMPLETE`
+/// - **413 Payload Too Large**: `LIST_TOO_LARGE`
+#[utoipa::path(
+    post,
+    path = "/api/v1/schedules",
+    request_body = CreateScheduleRequest,
+    responses(
+        (status = 201, description = "Schedule created", body = ScheduleView),
+        (status = 400, description = "INVALID_SCHEDULE, INVALID_WEBHOOK_URL or INVALID_INPUT", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 404, description = "JOB_NOT_FOUND or LIST_NOT_FOUND", body = ErrorEnvelope),
+        (status = 409, description = "JOB_NOT_COMPLETE: the job has not finished", body = ErrorEnvelope),
+        (status = 413, description = "LIST_TOO_LARGE: too many addresses to schedule", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database or job queue is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Schedules"
+)]
+#[post("/schedules")]
+pub async fn create_schedule(
+    body: web::Json<CreateScheduleRequest>,
+    job_queue: web::Data<JobQueue>,
+    mongo_client: web::Data<MongoClient>,
+    account: AuthedAccount,
+) -> Result<impl Responder, ApiError> {
+    let body = body.into_inner();
+
+    schedules::parse_cron(&body.cron)
+        .map_err(|message| ApiError::validation("INVALID_SCHEDULE", message))?;
+    let webhook_url = body
+        .webhook_url
+        .filter(|url| !url.trim().is_empty())
+        .map(|url| schedules::validate_webhook_url(&url))
+        .transpose()
+        .map_err(|message| ApiError::validation("INVALID_WEBHOOK_URL", message))?;
+
+    let owner = account.tenant_id();
+    // Schedules without a webhook notify the account's default one, if set
+    let webhook_url = match webhook_url {
+        Some(url) => Some(url),
+        None => account_settings::load_settings(&mongo_client, &owner)
+            .await
+            .map_err(|e| ApiError::upstream("database", e))?
+            .and_then(|settings| settings.webhook_url),
+    };
+    let (source, check_role_based, addresses) = match (body.job_id, body.list_id) {
+        (Some(job_id), None) => {
+            let (check_role_based, addresses) = job_addresses(&job_queue, &owner, &job_id).await?;
+            (ScheduleSource::Job { job_id }, check_role_based, addresses)
+        }
+        (None, Some(list_id)) => {
+            saved_lists::get_list(&mongo_client, &owner, &list_id)
+                .await
+                .map_err(|e| ApiError::upstream("database", e))?
+                .ok_or_else(list_not_found)?;
+            // The first run records the list's verdicts
+            (
+                ScheduleSource::List { list_id },
+                body.check_role_based,
+                Vec::new(),
+            )
+        }
+        _ => {
+            return Err(ApiError::validation(
+                "INVALID_INPUT",
+                "Set exactly one of job_id and list_id",
+            ));
+        }
+    };
+    if addresses.len() > MAX_SCHEDULED_EMAILS {
+        return Err(ApiError::validation(
+            "LIST_TOO_LARGE",
+            format!(
+                "Schedules re-validate at most {} addresses",
+                MAX_SCHEDULED_EMAILS
+            ),
+        )
+        .with_status(StatusCode::PAYLOAD_TOO_LARGE));
+    }
+
+    let schedule = schedules::create_schedule(
+        &mongo_client,
+        NewSchedule {
+            owner,
+            plan: account.plan,
+            source,
+            cron: body.cron,
+            check_role_based,
+            webhook_url,
+            addresses,
+        },
+    )
+    .await
+    .map_err(|e| ApiError::upstream("database", e))?;
+    Ok(HttpResponse::Created().json(ScheduleView::from(schedule)))
+}
+
+/// # List Schedules
+///
+/// The account's re-validation schedules with their latest runs, oldest first.
+#[utoipa::path(
+    get,
+    path = "/api/v1/schedules",
+    responses(
+        (status = 200, description = "The account's schedules", body = [ScheduleView]),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Schedules"
+)]
+#[get("/schedules")]
+pub async fn list_schedules(
+    mongo_client: web::Data<MongoClient>,
+    account: AuthedAccount,
+) -> Result<impl Responder, ApiError> {
+    let schedules = schedules::list_schedules(&mongo_client, &account.tenant_id())
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    let views: Vec<ScheduleView> = schedules.into_iter().map(ScheduleView::from).collect();
+    Ok(HttpResponse::Ok().json(views))
+}
+
+/// # Get Schedule
+///
+/// One schedule with the diffs of its latest runs.
+#[utoipa::path(
+    get,
+    path = "/api/v1/schedules/{schedule_id}",
+    params(("schedule_id" = String, Path, description = "ID returned when the schedule was created")),
+    responses(
+        (status = 200, description = "The schedule", body = ScheduleView),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 404, description = "SCHEDULE_NOT_FOUND", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Schedules"
+)]
+#[get("/schedules/{schedule_id}")]
+pub async fn get_schedule(
+    path: web::Path<String>,
+    mongo_client: web::Data<MongoClient>,
+    account: AuthedAccount,
+) -> Result<impl Responder, ApiError> {
+    let schedule = schedules::get_schedule(&mongo_client, &account.tenant_id(), &path)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?
+        .ok_or_else(schedule_not_found)?;
+    Ok(HttpResponse::Ok().json(ScheduleView::from(schedule)))
+}
+
+/// # Delete Schedule
+///
+/// Stops future runs. A run in progress still completes as a regular bulk job.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/schedules/{schedule_id}",
+    params(("schedule_id" = String, Path, description = "ID returned when the schedule was created")),
+    responses(
+        (status = 204, description = "Schedule deleted"),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 404, description = "SCHEDULE_NOT_FOUND", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Schedules"
+)]
+#[delete("/schedules/{schedule_id}")]
+pub async fn delete_schedule(
+    path: web::Path<String>,
+    mongo_client: web::Data<MongoClient>,
+    account: AuthedAccount,
+) -> Result<impl Responder, ApiError> {
+    let deleted = schedules::delete_schedule(&mongo_client, &account.tenant_id(), &path)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    if !deleted {
+        return Err(schedule_not_found());
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_schedule)
+        .service(list_schedules)
+        .service(get_schedule)
+        .service(delete_schedule);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, test};
+
+    #[actix_web::test]
+    async fn test_schedules_require_api_key() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/schedules")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
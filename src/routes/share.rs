@@ -0,0 +1,414 @@
+use crate::branding::{self, Branding};
+use crate::job_queue::{JobQueue, JobStatus, RESULT_CHUNK_SIZE};
+use crate::job_summary::{JobSummary, job_summary};
+use crate::routes::email::BulkEmailValidationResult;
+use crate::routes::status::escape_html;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, Responder, get, web};
+use chrono::{Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use mongodb::Client as MongoClient;
+use serde::{Deserialize, Serialize};
+
+/// Result rows per page of the viewer; divides [`RESULT_CHUNK_SIZE`] so a page
+/// never spans two stored chunks
+pub const PAGE_SIZE: usize = 100;
+
+const SHARE_TOKEN_TYPE: &str = "share";
+
+/// Claims of a signed result viewer link
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShareClaims {
+    pub job: String,
+    pub typ: String,
+    pub exp: usize,
+}
+
+/// Lifetime of share links in seconds (`SHARE_LINK_TTL`, default 3600)
+///
+/// Results themselves expire an hour after the job completes, so longer links
+/// only help while that retention is raised.
+pub fn share_link_ttl() -> i64 {
+    std::env::var("SHARE_LINK_TTL")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|v: &i64| *v > 0)
+        .unwrap_or(3600)
+}
+
+/// Signs a viewer link token for a job; returns the token and its expiry timestamp
+pub fn sign_share_token(job_id: &str) -> Result<(String, i64), String> {
+    let jwt_secret = std::env::var("JWT_SECRET").map_err(|_| "JWT_SECRET is not configured")?;
+    let expires_at = (Utc::now() + Duration::seconds(share_link_ttl())).timestamp();
+    let claims = ShareClaims {
+        job: job_id.to_string(),
+        typ: SHARE_TOKEN_TYPE.to_string(),
+        exp: expires_at as usize,
+    };
+
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(jwt_secret.as_ref()),
+    )
+    .map_err(|_| "Token signing failed".to_string())?;
+    Ok((token, expires_at))
+}
+
+/// Job ID of a valid, unexpired share token
+pub fn verify_share_token(token: &str) -> Result<String, String> {
+    let jwt_secret = std::env::var("JWT_SECRET").map_err(|_| "JWT_SECRET is not configured")?;
+    let claims = decode::<ShareClaims>(
+        token,
+        &DecodingKey::from_secret(jwt_secret.as_ref()),
+        &Validation::new(Algorithm::HS256),
+    )
+    .map_err(|_| "Invalid or expired token")?
+    .claims;
+
+    if claims.typ != SHARE_TOKEN_TYPE {
+        return Err("Invalid or expired token".to_string());
+    }
+    Ok(claims.job)
+}
+
+#[derive(Deserialize)]
+pub struct ViewerQuery {
+    /// 1-based page of the results table
+    pub page: Option<usize>,
+}
+
+/// Everything the result viewer shows
+pub struct ResultViewer {
+    pub token: String,
+    pub summary: JobSummary,
+    pub rows: Vec<BulkEmailValidationResult>,
+    pub page: usize,
+    /// Name and logo of the tenant that ran the job
+    pub branding: Option<Branding>,
+}
+
+impl ResultViewer {
+    pub fn page_count(&self) -> usize {
+        (self.summary.total as usize).div_ceil(PAGE_SIZE).max(1)
+    }
+
+    fn bar(label: &str, count: u64, total: u64) -> String {
+        let percent = if total == 0 {
+            0.0
+        } else {
+            count as f64 * 100.0 / total as f64
+        };
+        format!(
+            "<tr><td>{}</td><td>{}</td><td class=\"bar\"><span style=\"width:{:.1}%\"></span></td><td>{:.1}%</td></tr>\n",
+            escape_html(label),
+            count,
+            percent,
+            percent
+        )
+    }
+
+    pub fn render(&self) -> String {
+        let summary = &self.summary;
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"robots\" content=\"noindex\">\n\
+             <title>Validation results</title>\n<style>\n\
+             body{{font-family:system-ui,sans-serif;max-width:960px;margin:2rem auto;padding:0 1rem;color:#222}}\n\
+             table{{width:100%;border-collapse:collapse;margin-bottom:1.5rem}}td,th{{padding:.3rem .4rem;border-bottom:1px solid #ddd;text-align:left}}\n\
+             .bar{{width:50%}}.bar span{{display:block;height:.8rem;background:#2f81f7}}\n\
+             .valid{{color:#1a7f37}}.invalid{{color:#cf222e}}\n\
+             .brand{{display:flex;align-items:center;gap:.6rem;font-size:1.2rem}}\n\
+             </style>\n</head>\n<body>\n{}<h1>Validation results</h1>\n\
+             <p>{} addresses: <span class=\"valid\">{} valid</span>, <span class=\"invalid\">{} invalid</span></p>\n",
+            self.branding
+                .as_ref()
+                .map(Branding::header_html)
+                .unwrap_or_default(),
+            summary.total,
+            summary.valid,
+            summary.invalid
+        );
+
+        html.push_str("<h2>Verdicts</h2>\n<table>\n");
+        for (verdict, count) in &summary.verdicts {
+            html.push_str(&Self::bar(verdict, *count, summary.total));
+        }
+        html.push_str("</table>\n");
+
+        if !summary.top_domains.is_empty() {
+            html.push_str("<h2>Top domains</h2>\n<table>\n");
+            for domain in &summary.top_domains {
+                html.push_str(&Self::bar(&domain.domain, domain.count, summary.total));
+            }
+            html.push_str("</table>\n");
+        }
+
+        html.push_str(
+            "<h2>Addresses</h2>\n<table>\n<tr><th>Email</th><th>Result</th><th>Reason</th></tr>\n",
+        );
+        for row in &self.rows {
+            let (class, verdict) = if row.validation.is_valid {
+                ("valid", "VALID".to_string())
+            } else {
+                (
+                    "invalid",
+                    row.validation
+                        .error
+                        .as_ref()
+                        .map(|e| e.code.clone())
+                        .unwrap_or_else(|| "INVALID".to_string()),
+                )
+            };
+            let reason = row
+                .validation
+                .error
+                .as_ref()
+                .map(|e| e.message.as_str())
+                .unwrap_or("");
+            html.push_str(&format!(
+                "<tr><td>{}</td><td class=\"{}\">{}</td><td>{}</td></tr>\n",
+                escape_html(&row.email),
+                class,
+                escape_html(&verdict),
+                escape_html(reason)
+            ));
+        }
+        html.push_str("</table>\n<p>");
+
+        let page_count = self.page_count();
+        if self.page > 1 {
+            html.push_str(&format!(
+                "<a href=\"/share/{}?page={}\">&larr; Previous</a> ",
+                self.token,
+                self.page - 1
+            ));
+        }
+        html.push_str(&format!("Page {} of {}", self.page, page_count));
+        if self.page < page_count {
+            html.push_str(&format!(
+                " <a href=\"/share/{}?page={}\">Next &rarr;</a>",
+                self.token,
+                self.page + 1
+            ));
+        }
+        html.push_str("</p>\n</body>\n</html>\n");
+        html
+    }
+}
+
+fn unavailable(status: StatusCode, message: &str) -> HttpResponse {
+    HttpResponse::build(status)
+        .content_type("text/html; charset=utf-8")
+        .body(format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Validation results</title>\n</head>\n<body>\n<p>{}</p>\n</body>\n</html>\n",
+            message
+        ))
+}
+
+/// Rows of one page, read from the single stored chunk holding them
+async fn page_rows(
+    job_queue: &JobQueue,
+    job_id: &str,
+    page: usize,
+) -> Result<Vec<BulkEmailValidationResult>, String> {
+    let start = (page - 1) * PAGE_SIZE;
+    let chunk = job_queue
+        .result_chunk(job_id, start / RESULT_CHUNK_SIZE)
+        .await
+        .map_err(|e| format!("Failed to read job results: {}", e))?;
+    let Some(chunk) = chunk else {
+        return Ok(Vec::new());
+    };
+
+    let rows: Vec<BulkEmailValidationResult> =
+        serde_json::from_str(&chunk).map_err(|e| format!("Corrupt result chunk: {}", e))?;
+    Ok(rows
+        .into_iter()
+        .skip(start % RESULT_CHUNK_SIZE)
+        .take(PAGE_SIZE)
+        .collect())
+}
+
+/// # Shared Result Viewer
+///
+/// Read-only HTML view of a completed job behind a signed link (see
+/// `POST /api/v1/job-results/{job_id}/share`): verdict and domain charts plus a
+/// paginated table of addresses, for people without API access. Links expire after
+/// `SHARE_LINK_TTL` seconds, and with the job's results. The page carries the
+/// submitting tenant's branding, if set.
+#[get("/share/{token}")]
+pub async fn view_shared_results(
+    path: web::Path<String>,
+    query: web::Query<ViewerQuery>,
+    job_queue: Option<web::Data<JobQueue>>,
+    mongo_client: Option<web::Data<MongoClient>>,
+) -> impl Responder {
+    let token = path.into_inner();
+    let Ok(job_id) = verify_share_token(&token) else {
+        return unavailable(
+            StatusCode::NOT_FOUND,
+            "This link is invalid or has expired.",
+        );
+    };
+    let Some(job_queue) = job_queue else {
+        return unavailable(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Results are temporarily unavailable.",
+        );
+    };
+
+    let job = match job_queue.get_job_status(&job_id).await {
+        Ok(Some(job)) if matches!(job.status, JobStatus::Completed) => job,
+        Ok(_) => {
+            return unavailable(StatusCode::GONE, "These results are no longer available.");
+        }
+        Err(_) => {
+            return unavailable(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Results are temporarily unavailable.",
+            );
+        }
+    };
+    // Branding is decoration; the results are still shown if it can't be read
+    let branding = match &mongo_client {
+        Some(mongo_client) => branding::load_branding(mongo_client, &job.tenant_id)
+            .await
+            .ok()
+            .flatten(),
+        None => None,
+    };
+
+    let summary = match job_summary(&job_queue, &job_id).await {
+        Ok(summary) => summary,
+        Err(_) => {
+            return unavailable(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Results are temporarily unavailable.",
+            );
+        }
+    };
+
+    let mut viewer = ResultViewer {
+        token,
+        summary,
+        rows: Vec::new(),
+        page: 1,
+        branding,
+    };
+    viewer.page = query.page.unwrap_or(1).clamp(1, viewer.page_count());
+    viewer.rows = match page_rows(&job_queue, &job_id, viewer.page).await {
+        Ok(rows) => rows,
+        Err(_) => {
+            return unavailable(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Results are temporarily unavailable.",
+            );
+        }
+    };
+
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .insert_header(("Cache-Control", "private, no-store"))
+        // The token is the credential; keep it out of other sites' logs
+        .insert_header(("Referrer-Policy", "no-referrer"))
+        .insert_header(("X-Robots-Tag", "noindex"))
+        .body(viewer.render())
+}
+
+/// Mounts `GET /share/{token}` at the site root, outside the authenticated `/api/v1` scope
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(view_shared_results);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job_summary::DomainCount;
+    use crate::routes::email::{EmailValidationError, EmailValidationResponse};
+    use actix_web::{App, test as actix_test};
+    use std::collections::BTreeMap;
+
+    fn viewer(page: usize) -> ResultViewer {
+        ResultViewer {
+            token: "tok".to_string(),
+            summary: JobSummary {
+                job_id: "job-1".to_string(),
+                total: 250,
+                valid: 200,
+                invalid: 50,
+                verdicts: BTreeMap::from([
+                    ("VALID".to_string(), 200),
+                    ("DISPOSABLE_EMAIL".to_string(), 50),
+                ]),
+                top_domains: vec![DomainCount {
+                    domain: "example.com".to_string(),
+                    count: 125,
+                }],
+            },
+            rows: vec![BulkEmailValidationResult {
+                email: "<b>@example.com".to_string(),
+                validation: EmailValidationResponse {
+                    is_valid: false,
+                    domain: None,
+                    has_tag: None,
+                    base_address: None,
+                    status: None,
+                    error: Some(EmailValidationError {
+                        code: "DISPOSABLE_EMAIL".to_string(),
+                        message: "Disposable email address".to_string(),
+                        violations: Vec::new(),
+                    }),
+                },
+            }],
+            page,
+            branding: None,
+        }
+    }
+
+    #[test]
+    fn test_share_token_round_trip() {
+        unsafe {
+            std::env::set_var("JWT_SECRET", "test-secret-key-for-testing");
+        }
+
+        let (token, expires_at) = sign_share_token("job-1").unwrap();
+        assert!(expires_at > Utc::now().timestamp());
+        assert_eq!(verify_share_token(&token).unwrap(), "job-1");
+        assert!(verify_share_token("not-a-token").is_err());
+    }
+
+    #[test]
+    fn test_render_summary_table_and_pagination() {
+        let html = viewer(2).render();
+
+        assert!(html.contains("250 addresses"));
+        assert!(html.contains("<td>example.com</td><td>125</td>"));
+        assert!(html.contains("width:50.0%"));
+        assert!(html.contains("&lt;b&gt;@example.com"));
+        assert!(!html.contains("<b>@example.com"));
+        assert!(html.contains("href=\"/share/tok?page=1\""));
+        assert!(html.contains("Page 2 of 3"));
+        assert!(html.contains("href=\"/share/tok?page=3\""));
+
+        let last = viewer(3).render();
+        assert!(!last.contains("Next"));
+        assert!(!last.contains("class=\"brand\""));
+
+        let mut branded = viewer(1);
+        branded.branding = Branding::new("t", Some("Acme"), None).ok();
+        assert!(branded.render().contains("<strong>Acme</strong>"));
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_link_is_rejected() {
+        let app = actix_test::init_service(App::new().configure(configure_routes)).await;
+
+        let req = actix_test::TestRequest::get()
+            .uri("/share/forged-token")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 404);
+    }
+}
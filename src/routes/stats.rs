@@ -0,0 +1,86 @@
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::validation_service::ValidationBackends;
+use crate::validation_stats::{StatsReport, StatsWindow};
+use actix_web::{HttpResponse, Responder, get, web};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct StatsQuery {
+    /// `hour`, `day` (default) or `month`
+    pub window: Option<String>,
+}
+
+/// # Validation Statistics
+///
+/// Service-wide totals for dashboards over the last hour, day or month: valid and
+/// invalid counts, invalid results by error code, the most common invalid domains,
+/// the result cache hit rate and the average time to a result. Counters are
+/// updated by the REST, GraphQL and bulk validation pipelines.
+///
+/// ## Responses
+/// - **200 OK**: `{ "window", "since", "total", "valid", "invalid", "error_codes": [{ "code", "count" }], "top_invalid_domains": [{ "domain", "count" }], "cache_hit_rate", "average_latency_ms" }`
+/// - **400 Bad Request**: `INVALID_WINDOW`
+#[utoipa::path(
+    get,
+    path = "/api/v1/stats",
+    params(("window" = Option<String>, Query, description = "hour, day (default) or month")),
+    responses(
+        (status = 200, description = "Validation statistics", body = StatsReport),
+        (status = 400, description = "INVALID_WINDOW", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the stats store is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[get("/stats")]
+pub async fn validation_stats(
+    query: web::Query<StatsQuery>,
+    backends: web::Data<ValidationBackends>,
+) -> Result<impl Responder, ApiError> {
+    let window = match query.window.as_deref() {
+        Some(window) => StatsWindow::parse(window).ok_or_else(|| {
+            ApiError::validation("INVALID_WINDOW", "window must be one of hour, day, month")
+        })?,
+        None => StatsWindow::default(),
+    };
+
+    let report = backends
+        .stats
+        .report(window, chrono::Utc::now().timestamp())
+        .await
+        .map_err(|e| ApiError::upstream("stats store", e))?;
+    Ok(HttpResponse::Ok().json(report))
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(validation_stats);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stores::MemoryStore;
+    use actix_web::{App, test};
+    use std::sync::Arc;
+
+    #[actix_web::test]
+    async fn test_stats_rejects_unknown_window() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(ValidationBackends::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .configure(configure_routes),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/stats?window=week")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], "INVALID_WINDOW");
+    }
+}
@@ -0,0 +1,276 @@
+use crate::health_history::{self, DependencyHistory, DependencyStatus, HealthHistory, Incident};
+use crate::job_queue::JobQueue;
+use actix_web::{HttpResponse, Responder, get, web};
+use chrono::{DateTime, Utc};
+
+/// Incidents listed on the status page
+pub const RECENT_INCIDENTS: usize = 10;
+
+/// Whether uptime percentages and the queue backlog are left off the status page
+/// (`STATUS_PAGE_HIDE_METRICS=true`)
+pub fn hide_metrics() -> bool {
+    std::env::var("STATUS_PAGE_HIDE_METRICS")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "true" | "1" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Everything the status page shows
+pub struct StatusPage {
+    /// `None` when the health history couldn't be read
+    pub components: Option<Vec<DependencyHistory>>,
+    pub incidents: Vec<Incident>,
+    pub backlog: Option<u64>,
+    pub hide_metrics: bool,
+    pub generated_at: DateTime<Utc>,
+}
+
+pub(crate) fn escape_html(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn format_time(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default()
+}
+
+fn status_label(status: Option<DependencyStatus>) -> (&'static str, &'static str) {
+    match status {
+        Some(DependencyStatus::Up) => ("up", "Operational"),
+        Some(DependencyStatus::Down) => ("down", "Outage"),
+        None => ("unknown", "Unknown"),
+    }
+}
+
+impl StatusPage {
+    pub fn render(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta http-equiv=\"refresh\" content=\"60\">\n\
+             <title>Email Sanitizer Status</title>\n<style>\n\
+             body{font-family:system-ui,sans-serif;max-width:720px;margin:2rem auto;padding:0 1rem;color:#222}\n\
+             table{width:100%;border-collapse:collapse}td,th{padding:.4rem;border-bottom:1px solid #ddd;text-align:left}\n\
+             .up{color:#1a7f37}.down{color:#cf222e}.unknown{color:#777}\n\
+             </style>\n</head>\n<body>\n<h1>Email Sanitizer Status</h1>\n",
+        );
+
+        let Some(components) = &self.components else {
+            html.push_str("<p class=\"unknown\">Component status is currently unavailable.</p>\n");
+            return self.finish(html);
+        };
+
+        let all_up = components
+            .iter()
+            .all(|c| c.status == Some(DependencyStatus::Up));
+        html.push_str(if all_up {
+            "<p class=\"up\"><strong>All systems operational</strong></p>\n"
+        } else {
+            "<p class=\"down\"><strong>Some systems are degraded</strong></p>\n"
+        });
+
+        html.push_str("<h2>Components</h2>\n<table>\n<tr><th>Component</th><th>Status</th>");
+        if !self.hide_metrics {
+            html.push_str("<th>Uptime 24h</th><th>7d</th><th>30d</th>");
+        }
+        html.push_str("</tr>\n");
+        for component in components {
+            let (class, label) = status_label(component.status);
+            html.push_str(&format!(
+                "<tr><td>{}</td><td class=\"{}\">{}</td>",
+                escape_html(&component.name),
+                class,
+                label
+            ));
+            if !self.hide_metrics {
+                for window in ["24h", "7d", "30d"] {
+                    let uptime = component.uptime.get(window).copied().flatten();
+                    html.push_str(&match uptime {
+                        Some(percent) => format!("<td>{:.2}%</td>", percent),
+                        None => "<td>&ndash;</td>".to_string(),
+                    });
+                }
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+
+        if !self.hide_metrics
+            && let Some(backlog) = self.backlog
+        {
+            html.push_str(&format!(
+                "<h2>Bulk queue</h2>\n<p>{} job(s) waiting</p>\n",
+                backlog
+            ));
+        }
+
+        html.push_str("<h2>Recent incidents</h2>\n");
+        if self.incidents.is_empty() {
+            html.push_str("<p>No incidents in the last 30 days.</p>\n");
+        } else {
+            html.push_str("<ul>\n");
+            for incident in &self.incidents {
+                let resolution = match incident.resolved_at {
+                    Some(at) => format!("resolved {}", format_time(at)),
+                    None => "ongoing".to_string(),
+                };
+                html.push_str(&format!(
+                    "<li>{} outage from {}, {}</li>\n",
+                    escape_html(&incident.dependency),
+                    format_time(incident.started_at),
+                    resolution
+                ));
+            }
+            html.push_str("</ul>\n");
+        }
+
+        self.finish(html)
+    }
+
+    fn finish(&self, mut html: String) -> String {
+        html.push_str(&format!(
+            "<p><small>Updated {}</small></p>\n</body>\n</html>\n",
+            self.generated_at.format("%Y-%m-%d %H:%M:%S UTC")
+        ));
+        html
+    }
+}
+
+/// # Status Page
+///
+/// Public HTML page with the current health of each component, outages of the last
+/// 30 days (from the health history) and the bulk queue backlog. Set
+/// `STATUS_PAGE_HIDE_METRICS=true` to leave out uptime percentages and the backlog.
+/// The page renders even when the history or queue can't be read.
+#[utoipa::path(
+    get,
+    path = "/status",
+    responses(
+        (status = 200, description = "Status page", content_type = "text/html")
+    ),
+    security(()),
+    tag = "Health Check"
+)]
+#[get("/status")]
+pub async fn status_page(
+    history: Option<web::Data<HealthHistory>>,
+    job_queue: Option<web::Data<JobQueue>>,
+) -> impl Responder {
+    let generated_at = Utc::now();
+    let now = generated_at.timestamp();
+    let hide_metrics = hide_metrics();
+
+    let mut components = None;
+    let mut incidents = Vec::new();
+    if let Some(history) = history {
+        let mut loaded = Vec::new();
+        for name in health_history::DEPENDENCIES {
+            let Ok(transitions) = history.transitions(name).await else {
+                loaded.clear();
+                incidents.clear();
+                break;
+            };
+            let window_start = now - health_history::UPTIME_WINDOWS[2].1;
+            incidents.extend(
+                health_history::incidents(name, &transitions)
+                    .into_iter()
+                    .filter(|i| i.resolved_at.is_none_or(|at| at > window_start)),
+            );
+            loaded.push(health_history::dependency_history(name, &transitions, now));
+        }
+        if !loaded.is_empty() {
+            components = Some(loaded);
+        }
+    }
+    incidents.sort_by_key(|i| std::cmp::Reverse(i.started_at));
+    incidents.truncate(RECENT_INCIDENTS);
+
+    let backlog = match (hide_metrics, job_queue) {
+        (false, Some(job_queue)) => job_queue.pending_jobs().await.ok(),
+        _ => None,
+    };
+
+    let page = StatusPage {
+        components,
+        incidents,
+        backlog,
+        hide_metrics,
+        generated_at,
+    };
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .insert_header(("Cache-Control", "public, max-age=30"))
+        .body(page.render())
+}
+
+/// Mounts `GET /status` at the site root, outside the authenticated `/api/v1` scope
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(status_page);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health_history::Transition;
+    use actix_web::{App, test as actix_test};
+
+    fn page(hide_metrics: bool) -> StatusPage {
+        let transitions = [
+            Transition {
+                status: DependencyStatus::Up,
+                at: 0,
+            },
+            Transition {
+                status: DependencyStatus::Down,
+                at: 3000,
+            },
+        ];
+        StatusPage {
+            components: Some(vec![health_history::dependency_history(
+                "mongodb",
+                &transitions,
+                4000,
+            )]),
+            incidents: health_history::incidents("mongodb", &transitions),
+            backlog: Some(7),
+            hide_metrics,
+            generated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_render_shows_components_incidents_and_backlog() {
+        let html = page(false).render();
+
+        assert!(html.contains("Some systems are degraded"));
+        assert!(html.contains("<td>mongodb</td><td class=\"down\">Outage</td>"));
+        assert!(html.contains("<td>75.00%</td>"));
+        assert!(html.contains("7 job(s) waiting"));
+        assert!(html.contains("mongodb outage from 1970-01-01 00:50 UTC, ongoing"));
+    }
+
+    #[test]
+    fn test_render_hides_metrics() {
+        let html = page(true).render();
+
+        assert!(!html.contains("Uptime"));
+        assert!(!html.contains("job(s) waiting"));
+        assert!(html.contains("Recent incidents"));
+    }
+
+    #[actix_web::test]
+    async fn test_status_page_renders_without_history() {
+        let app = actix_test::init_service(App::new().configure(configure_routes)).await;
+
+        let req = actix_test::TestRequest::get().uri("/status").to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+
+        let body = actix_test::read_body(resp).await;
+        let html = std::str::from_utf8(&body).unwrap();
+        assert!(html.contains("Component status is currently unavailable"));
+    }
+}
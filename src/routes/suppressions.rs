@@ -0,0 +1,436 @@
+use crate::auth::AuthedAccount;
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::handlers::validation::suppression_list::{
+    self, ImportSummary, MAX_IMPORT_ENTRIES, MAX_SUPPRESSIONS, SUPPRESSIONS_PAGE_SIZE,
+    SuppressedAddress, SuppressionError, SuppressionInput, SuppressionReason, SuppressionRecord,
+};
+use crate::job_export::ExportFormat;
+use crate::organizations;
+use crate::routes::email::csv_field;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, Responder, delete, get, post, web};
+use mongodb::Client as MongoClient;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+
+#[derive(Deserialize)]
+pub struct SuppressionsQuery {
+    pub reason: Option<SuppressionReason>,
+    /// 1-based page number
+    pub page: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    pub reason: Option<SuppressionReason>,
+    /// `csv` (default) or `jsonl`
+    pub format: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ImportRequest {
+    pub entries: Vec<SuppressionInput>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SuppressionPage {
+    pub entries: Vec<SuppressionRecord>,
+    /// Entries matching the filter, across all pages
+    pub total: u64,
+    pub page: usize,
+    pub per_page: usize,
+}
+
+/// Header row of CSV exports
+const EXPORT_CSV_HEADER: &str = "email,reason,note,created_at,updated_at\n";
+
+impl From<SuppressionError> for ApiError {
+    fn from(e: SuppressionError) -> Self {
+        match e {
+            SuppressionError::Invalid(message) => ApiError::validation("INVALID_ENTRY", message),
+            SuppressionError::ListFull => ApiError::validation(
+                "LIST_FULL",
+                format!(
+                    "A suppression list holds at most {} addresses",
+                    MAX_SUPPRESSIONS
+                ),
+            ),
+            SuppressionError::Database(e) => ApiError::upstream("database", e),
+        }
+    }
+}
+
+/// Owner of the caller's suppression list: their organization's, shared by all
+/// members, or their own
+async fn list_owner(
+    mongo_client: &MongoClient,
+    account: &AuthedAccount,
+) -> Result<String, ApiError> {
+    organizations::list_owner(mongo_client, &account.email)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))
+}
+
+/// Like [`list_owner`], but only organization owners may change a shared list
+async fn writable_owner(
+    mongo_client: &MongoClient,
+    account: &AuthedAccount,
+) -> Result<String, ApiError> {
+    let scope = organizations::account_scope(mongo_client, &account.email)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    if !scope.can_manage_lists() {
+        return Err(ApiError::validation(
+            "FORBIDDEN",
+            "Only organization owners can change shared lists",
+        )
+        .with_status(StatusCode::FORBIDDEN));
+    }
+    Ok(scope.owner)
+}
+
+/// CSV lines of `entries` matching [`EXPORT_CSV_HEADER`]
+pub fn entries_to_csv(entries: &[SuppressedAddress]) -> String {
+    let mut csv = String::from(EXPORT_CSV_HEADER);
+    for entry in entries {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            csv_field(&entry.email),
+            entry.reason.as_str(),
+            csv_field(entry.note.as_deref().unwrap_or("")),
+            entry.created_at,
+            entry.updated_at
+        ));
+    }
+    csv
+}
+
+/// # List Suppressions
+///
+/// Addresses on the caller's suppression list, by address, [`SUPPRESSIONS_PAGE_SIZE`]
+/// per page. Members of an organization share its list.
+///
+/// ## Query Parameters
+/// - `reason`: only `bounce`, `complaint`, `unsubscribe` or `manual` entries
+/// - `page`: 1-based page number (default 1)
+///
+/// ## Responses
+/// - **200 OK**: `{ "entries": [{ "email", "reason", "note", "created_at", "updated_at" }], "total", "page", "per_page" }`
+/// - **400 Bad Request**: `INVALID_PAGE`
+/// - **401 Unauthorized**: Missing or invalid credentials
+#[utoipa::path(
+    get,
+    path = "/api/v1/suppressions",
+    params(
+        ("reason" = Option<SuppressionReason>, Query, description = "Only entries with this reason"),
+        ("page" = Option<usize>, Query, description = "1-based page number")
+    ),
+    responses(
+        (status = 200, description = "One page of the caller's suppression list", body = SuppressionPage),
+        (status = 400, description = "INVALID_PAGE", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Account"
+)]
+#[get("/suppressions")]
+pub async fn list_suppressions(
+    query: web::Query<SuppressionsQuery>,
+    account: AuthedAccount,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<impl Responder, ApiError> {
+    let page = query.page.unwrap_or(1);
+    if page == 0 {
+        return Err(ApiError::validation("INVALID_PAGE", "page starts at 1"));
+    }
+    let owner = list_owner(&mongo_client, &account).await?;
+
+    let (entries, total) = suppression_list::list(&mongo_client, &owner, query.reason, page)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    Ok(HttpResponse::Ok().json(SuppressionPage {
+        entries: entries.into_iter().map(Into::into).collect(),
+        total,
+        page,
+        per_page: SUPPRESSIONS_PAGE_SIZE,
+    }))
+}
+
+/// # Suppress Address
+///
+/// Adds an address to the caller's suppression list with a reason code (`bounce`,
+/// `complaint`, `unsubscribe` or `manual`, the default) and an optional note.
+/// Validations of the address by the caller or its organization then fail with
+/// `SUPPRESSED`, whatever the other checks and lists say. Suppressing a listed
+/// address replaces its reason and note.
+///
+/// ## Responses
+/// - **201 Created**: `{ "email", "reason", "note", "created_at", "updated_at" }`
+/// - **400 Bad Request**: `INVALID_ENTRY` or `LIST_FULL`
+/// - **401 Unauthorized**: Missing or invalid credentials
+/// - **403 Forbidden**: The caller is an organization member rather than an owner
+#[utoipa::path(
+    post,
+    path = "/api/v1/suppressions",
+    request_body = SuppressionInput,
+    responses(
+        (status = 201, description = "Address suppressed", body = SuppressionRecord),
+        (status = 400, description = "INVALID_ENTRY or LIST_FULL", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 403, description = "FORBIDDEN: only organization owners can change shared lists", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Account"
+)]
+#[post("/suppressions")]
+pub async fn add_suppression(
+    req: web::Json<SuppressionInput>,
+    account: AuthedAccount,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<impl Responder, ApiError> {
+    let owner = writable_owner(&mongo_client, &account).await?;
+    let entry = suppression_list::add(&mongo_client, &owner, &req).await?;
+    Ok(HttpResponse::Created().json(SuppressionRecord::from(entry)))
+}
+
+/// # Import Suppressions
+///
+/// Suppresses up to [`MAX_IMPORT_ENTRIES`] addresses at once, each with its own
+/// reason and note, as `POST /api/v1/suppressions` would. Malformed addresses and
+/// repeats are skipped rather than failing the import; the JSONL export can be fed
+/// back as `entries`.
+///
+/// ## Responses
+/// - **200 OK**: `{ "added", "updated", "skipped" }`
+/// - **400 Bad Request**: `LIST_FULL`, nothing was imported
+/// - **401 Unauthorized**: Missing or invalid credentials
+/// - **403 Forbidden**: The caller is an organization member rather than an owner
+/// - **413 Payload Too Large**: `BATCH_TOO_LARGE`
+/// - **422 Unprocessable Entity**: `EMPTY_BATCH`
+#[utoipa::path(
+    post,
+    path = "/api/v1/suppressions/import",
+    request_body = ImportRequest,
+    responses(
+        (status = 200, description = "Import applied", body = ImportSummary),
+        (status = 400, description = "LIST_FULL", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 403, description = "FORBIDDEN: only organization owners can change shared lists", body = ErrorEnvelope),
+        (status = 413, description = "BATCH_TOO_LARGE", body = ErrorEnvelope),
+        (status = 422, description = "EMPTY_BATCH", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Account"
+)]
+#[post("/suppressions/import")]
+pub async fn import_suppressions(
+    req: web::Json<ImportRequest>,
+    account: AuthedAccount,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<impl Responder, ApiError> {
+    if req.entries.is_empty() {
+        return Err(
+            ApiError::validation("EMPTY_BATCH", "The import contains no entries")
+                .with_status(StatusCode::UNPROCESSABLE_ENTITY),
+        );
+    }
+    if req.entries.len() > MAX_IMPORT_ENTRIES {
+        return Err(ApiError::validation(
+            "BATCH_TOO_LARGE",
+            format!(
+                "The import contains {} entries; the maximum is {}",
+                req.entries.len(),
+                MAX_IMPORT_ENTRIES
+            ),
+        )
+        .with_status(StatusCode::PAYLOAD_TOO_LARGE)
+        .with_details(json!({ "max_batch_size": MAX_IMPORT_ENTRIES })));
+    }
+    let owner = writable_owner(&mongo_client, &account).await?;
+
+    let summary = suppression_list::import(&mongo_client, &owner, &req.entries).await?;
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+/// # Export Suppressions
+///
+/// Downloads the caller's whole suppression list, by address, as CSV
+/// (`email,reason,note,created_at,updated_at`) or JSON lines.
+///
+/// ## Query Parameters
+/// - `reason`: only entries with this reason
+/// - `format`: `csv` (default) or `jsonl`
+///
+/// ## Responses
+/// - **200 OK**: The list as an attachment
+/// - **400 Bad Request**: `INVALID_FORMAT`
+/// - **401 Unauthorized**: Missing or invalid credentials
+#[utoipa::path(
+    get,
+    path = "/api/v1/suppressions/export",
+    params(
+        ("reason" = Option<SuppressionReason>, Query, description = "Only entries with this reason"),
+        ("format" = Option<String>, Query, description = "`csv` (default) or `jsonl`")
+    ),
+    responses(
+        (status = 200, description = "The suppression list", content_type = "text/csv"),
+        (status = 400, description = "INVALID_FORMAT", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Account"
+)]
+#[get("/suppressions/export")]
+pub async fn export_suppressions(
+    query: web::Query<ExportQuery>,
+    account: AuthedAccount,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<impl Responder, ApiError> {
+    let format = match query.format.as_deref().map(ExportFormat::parse) {
+        None => ExportFormat::Csv,
+        Some(Some(format @ (ExportFormat::Csv | ExportFormat::Jsonl))) => format,
+        Some(_) => {
+            return Err(ApiError::validation(
+                "INVALID_FORMAT",
+                "format must be csv or jsonl",
+            ));
+        }
+    };
+    let owner = list_owner(&mongo_client, &account).await?;
+
+    let entries = suppression_list::export(&mongo_client, &owner, query.reason)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    let body = match format {
+        ExportFormat::Csv => entries_to_csv(&entries),
+        _ => entries
+            .into_iter()
+            .map(SuppressionRecord::from)
+            .map(|record| serde_json::to_string(&record).unwrap_or_default() + "\n")
+            .collect(),
+    };
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            "Content-Disposition",
+            format!(
+                "attachment; filename=\"suppressions.{}\"",
+                format.extension()
+            ),
+        ))
+        .body(body))
+}
+
+/// # Remove Suppression
+///
+/// Lifts the suppression of an address; later validations run the usual checks.
+///
+/// ## Responses
+/// - **204 No Content**: Suppression removed
+/// - **401 Unauthorized**: Missing or invalid credentials
+/// - **403 Forbidden**: The caller is an organization member rather than an owner
+/// - **404 Not Found**: `ENTRY_NOT_FOUND`, the address isn't suppressed
+#[utoipa::path(
+    delete,
+    path = "/api/v1/suppressions/{email}",
+    params(("email" = String, Path, description = "Address to remove")),
+    responses(
+        (status = 204, description = "Suppression removed"),
+        (status = 401, description = "Missing or invalid credentials", body = ErrorEnvelope),
+        (status = 403, description = "FORBIDDEN: only organization owners can change shared lists", body = ErrorEnvelope),
+        (status = 404, description = "ENTRY_NOT_FOUND", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: the database is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Account"
+)]
+#[delete("/suppressions/{email}")]
+pub async fn remove_suppression(
+    path: web::Path<String>,
+    account: AuthedAccount,
+    mongo_client: web::Data<MongoClient>,
+) -> Result<impl Responder, ApiError> {
+    let owner = writable_owner(&mongo_client, &account).await?;
+
+    let removed = suppression_list::remove(&mongo_client, &owner, &path)
+        .await
+        .map_err(|e| ApiError::upstream("database", e))?;
+    if removed {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Err(ApiError::not_found(
+            "ENTRY_NOT_FOUND",
+            "The suppression list does not contain this address",
+        ))
+    }
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_suppressions)
+        .service(add_suppression)
+        .service(import_suppressions)
+        .service(export_suppressions)
+        .service(remove_suppression);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, test as actix_test};
+
+    #[test]
+    fn test_entries_to_csv() {
+        let entry = SuppressedAddress {
+            owner: "owner@example.com".to_string(),
+            email: "user@example.com".to_string(),
+            hash: String::new(),
+            reason: SuppressionReason::Complaint,
+            note: Some("ticket 12, spam".to_string()),
+            created_at: 1_700_000_000,
+            updated_at: 1_700_000_100,
+        };
+        assert_eq!(
+            entries_to_csv(&[entry]),
+            "email,reason,note,created_at,updated_at\n\
+             user@example.com,complaint,\"ticket 12, spam\",1700000000,1700000100\n"
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_suppression_routes_require_account() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::get()
+            .uri("/api/v1/suppressions")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = actix_test::TestRequest::post()
+            .uri("/api/v1/suppressions")
+            .set_json(json!({ "email": "user@example.com", "reason": "unsubscribe" }))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = actix_test::TestRequest::get()
+            .uri("/api/v1/suppressions/export")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = actix_test::TestRequest::delete()
+            .uri("/api/v1/suppressions/user@example.com")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
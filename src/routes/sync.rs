@@ -0,0 +1,209 @@
+use crate::auth::AuthedAccount;
+use crate::sync::{
+    self, BatchOutcome, DEFAULT_PAGE_SIZE, ListPage, SYNC_PROTOCOL_VERSION, SyncList, SyncStore,
+    VerdictBatch,
+};
+use actix_web::{HttpRequest, HttpResponse, Responder, get, post, web};
+use mongodb::Client as MongoClient;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Deserialize)]
+pub struct ListSyncQuery {
+    pub list: SyncList,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Checks the agent speaks our protocol version
+///
+/// Returns the tenant the agent syncs for, or the response to send back.
+fn authorize_agent(
+    account: &AuthedAccount,
+    http_req: &HttpRequest,
+) -> Result<String, HttpResponse> {
+    let requested_version = http_req
+        .headers()
+        .get("X-Sync-Protocol")
+        .and_then(|h| h.to_str().ok())
+        .map(|v| v.trim().parse::<u32>().ok());
+    if let Some(version) = requested_version
+        && version != Some(SYNC_PROTOCOL_VERSION)
+    {
+        return Err(HttpResponse::UpgradeRequired().json(json!({
+            "error": "UNSUPPORTED_PROTOCOL_VERSION",
+            "message": "The sync agent must be upgraded to a supported protocol version",
+            "supported_versions": [SYNC_PROTOCOL_VERSION]
+        })));
+    }
+
+    Ok(account.tenant_id())
+}
+
+/// # Pull List Updates
+///
+/// Returns reference list entries (`disposable` domains or `role_based` prefixes)
+/// added after `cursor`, so an on-prem replica can stay current incrementally.
+/// Omit `cursor` to start a full snapshot, and start one again whenever the
+/// returned `version` differs from the one the replica last synced.
+///
+/// ## Responses
+/// - **200 OK**: `{ "protocol_version", "list", "version", "items", "next_cursor", "has_more" }`
+/// - **409 Conflict**: Cursor not recognized; the agent must resync from a snapshot
+/// - **426 Upgrade Required**: Agent speaks an unsupported `X-Sync-Protocol` version
+#[utoipa::path(
+    get,
+    path = "/api/v1/sync/lists",
+    params(
+        ("list" = SyncList, Query, description = "List to pull"),
+        ("cursor" = Option<String>, Query, description = "`next_cursor` of the previous page; omit to start a snapshot"),
+        ("limit" = Option<i64>, Query, description = "Page size"),
+        ("X-Sync-Protocol" = Option<u32>, Header, description = "Protocol version spoken by the agent")
+    ),
+    responses(
+        (status = 200, description = "Entries added after the cursor", body = ListPage),
+        (status = 401, description = "Missing or invalid API key"),
+        (status = 409, description = "CURSOR_INVALID: resync from a snapshot"),
+        (status = 426, description = "UNSUPPORTED_PROTOCOL_VERSION")
+    ),
+    tag = "Sync"
+)]
+#[get("/sync/lists")]
+pub async fn pull_lists(
+    account: AuthedAccount,
+    query: web::Query<ListSyncQuery>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, actix_web::Error> {
+    if let Err(response) = authorize_agent(&account, &http_req) {
+        return Ok(response);
+    }
+
+    let cursor = match sync::parse_cursor(query.cursor.as_deref()) {
+        Ok(cursor) => cursor,
+        Err(message) => {
+            return Ok(HttpResponse::Conflict().json(json!({
+                "error": "CURSOR_INVALID",
+                "message": message
+            })));
+        }
+    };
+
+    let page = sync::pull_list_page(
+        &mongo_client,
+        query.list,
+        cursor,
+        query.limit.unwrap_or(DEFAULT_PAGE_SIZE),
+    )
+    .await
+    .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(page))
+}
+
+/// # Push Verdict Stats
+///
+/// Accepts anonymized, aggregated outcome counts from an on-prem agent. Batches
+/// carry a per-agent `sequence`: retries of an applied batch are acknowledged
+/// without being counted twice, and gaps are rejected with the expected sequence.
+///
+/// ## Responses
+/// - **200 OK**: `{ "accepted": true|false, "duplicate": bool, "next_sequence" }`
+/// - **409 Conflict**: `SEQUENCE_CONFLICT` with `expected_sequence`
+/// - **422 Unprocessable Entity**: Malformed batch
+/// - **426 Upgrade Required**: Agent speaks an unsupported `X-Sync-Protocol` version
+#[utoipa::path(
+    post,
+    path = "/api/v1/sync/verdicts",
+    request_body = VerdictBatch,
+    params(("X-Sync-Protocol" = Option<u32>, Header, description = "Protocol version spoken by the agent")),
+    responses(
+        (status = 200, description = "`{ \"accepted\", \"duplicate\", \"next_sequence\" }`"),
+        (status = 401, description = "Missing or invalid API key"),
+        (status = 409, description = "SEQUENCE_CONFLICT with `expected_sequence`"),
+        (status = 422, description = "INVALID_BATCH"),
+        (status = 426, description = "UNSUPPORTED_PROTOCOL_VERSION")
+    ),
+    tag = "Sync"
+)]
+#[post("/sync/verdicts")]
+pub async fn push_verdicts(
+    account: AuthedAccount,
+    batch: web::Json<VerdictBatch>,
+    sync_store: web::Data<SyncStore>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, actix_web::Error> {
+    let tenant_id = match authorize_agent(&account, &http_req) {
+        Ok(tenant_id) => tenant_id,
+        Err(response) => return Ok(response),
+    };
+
+    if let Err(message) = batch.validate() {
+        return Ok(HttpResponse::UnprocessableEntity().json(json!({
+            "error": "INVALID_BATCH",
+            "message": message
+        })));
+    }
+
+    let outcome = sync_store
+        .apply_verdict_batch(&tenant_id, &batch)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Redis error: {}", e)))?;
+
+    Ok(match outcome {
+        BatchOutcome::Accepted { next_sequence } => HttpResponse::Ok().json(json!({
+            "accepted": true,
+            "duplicate": false,
+            "next_sequence": next_sequence
+        })),
+        BatchOutcome::Duplicate { next_sequence } => HttpResponse::Ok().json(json!({
+            "accepted": false,
+            "duplicate": true,
+            "next_sequence": next_sequence
+        })),
+        BatchOutcome::OutOfOrder { expected_sequence } => HttpResponse::Conflict().json(json!({
+            "error": "SEQUENCE_CONFLICT",
+            "message": "Batches are missing before this sequence",
+            "expected_sequence": expected_sequence
+        })),
+    })
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(pull_lists).service(push_verdicts);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, http::StatusCode, test};
+
+    #[actix_web::test]
+    async fn test_sync_routes_require_authorization() {
+        let mongo_client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(mongo_client))
+                .app_data(web::Data::new(
+                    SyncStore::new("redis://127.0.0.1:6379").unwrap(),
+                ))
+                .service(web::scope("/api/v1").configure(configure_routes)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/sync/lists?list=disposable")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/sync/verdicts")
+            .set_json(json!({ "agent_id": "dc1", "sequence": 1, "stats": [] }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
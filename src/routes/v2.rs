@@ -0,0 +1,335 @@
+use crate::checks::{Checks, ItemOptions};
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::error_codes::{Severity, VerdictCode};
+use crate::handlers::validation::dnsmx::IdnDomain;
+use crate::handlers::validation::syntax::SyntaxViolation;
+use crate::i18n::{self, Lang};
+use crate::routes::email::{
+    EmailRequest, EmailValidationResponse, caller_defaults, email_query_param, set_cache_control,
+    validate_for_caller,
+};
+use crate::validation_service::ValidationBackends;
+use actix_web::{HttpRequest, HttpResponse, get, post, web};
+use mongodb::Client as MongoClient;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Overall judgement on an address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    /// Every check passed
+    Deliverable,
+    /// Mail would arrive, but the address is disposable or a role account
+    Risky,
+    /// Mail to the address would bounce or must not be sent
+    Undeliverable,
+    /// A check could not run; validating again later may reach a verdict
+    Unknown,
+}
+
+impl Verdict {
+    /// Confidence in delivery from 0 to 100; `None` when nothing is known
+    pub fn score(self) -> Option<u8> {
+        match self {
+            Verdict::Deliverable => Some(100),
+            Verdict::Risky => Some(50),
+            Verdict::Undeliverable => Some(0),
+            Verdict::Unknown => None,
+        }
+    }
+
+    /// The verdict a v1 verdict code stands for, from its registry severity
+    fn of_code(code: &str) -> Self {
+        match VerdictCode::parse(code).map(|code| code.info().severity) {
+            Some(Severity::Warning) => Verdict::Risky,
+            Some(Severity::Critical) => Verdict::Unknown,
+            Some(Severity::Error) | None => Verdict::Undeliverable,
+        }
+    }
+}
+
+/// Why an address isn't deliverable
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Reason {
+    /// A verdict code of `/meta/error-codes`
+    pub code: String,
+    pub message: String,
+    /// For `INVALID_SYNTAX`, each rule the address breaks
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<SyntaxViolation>,
+}
+
+/// One address's validation result in the v2 schema
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct EmailValidationV2 {
+    pub email: String,
+    pub verdict: Verdict,
+    /// 100 for deliverable, 50 for risky, 0 for undeliverable; omitted for unknown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<u8>,
+    /// The address's domain in Unicode and punycode forms; omitted for invalid syntax
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<IdnDomain>,
+    /// Omitted for deliverable addresses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<Reason>,
+}
+
+impl EmailValidationV2 {
+    /// Renders a result of the shared validation core
+    pub fn new(email: &str, result: EmailValidationResponse) -> Self {
+        let verdict = match &result.error {
+            None => Verdict::Deliverable,
+            Some(error) => Verdict::of_code(&error.code),
+        };
+        Self {
+            email: email.trim().to_string(),
+            verdict,
+            score: verdict.score(),
+            domain: result.domain,
+            reason: result.error.map(|error| Reason {
+                code: error.code,
+                message: error.message,
+                violations: error.violations,
+            }),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct V2ValidationQuery {
+    /// Unset falls back to the account settings, then to `false`
+    pub check_role_based: Option<bool>,
+    /// Language of `reason.message`, overriding `Accept-Language`; see [`Lang`]
+    pub lang: Option<String>,
+}
+
+impl V2ValidationQuery {
+    async fn checks(
+        &self,
+        http_req: &HttpRequest,
+        mongo_client: &MongoClient,
+    ) -> Result<Checks, ApiError> {
+        let options = ItemOptions {
+            check_role_based: self.check_role_based,
+            checks: None,
+        };
+        Ok(options
+            .with_defaults(&caller_defaults(http_req, mongo_client).await?)
+            .checks(false))
+    }
+
+    /// Validates `email` for the caller, with its reason in the requested language
+    async fn validate(
+        &self,
+        email: &str,
+        backends: &ValidationBackends,
+        mongo_client: &MongoClient,
+        http_req: &HttpRequest,
+    ) -> Result<(EmailValidationResponse, Checks), ApiError> {
+        let lang = Lang::requested(self.lang.as_deref(), http_req)?;
+        let checks = self.checks(http_req, mongo_client).await?;
+        let mut result =
+            validate_for_caller(email, checks, backends, mongo_client, http_req).await?;
+        if let Some(error) = &mut result.error {
+            i18n::localize(error, lang);
+        }
+        Ok((result, checks))
+    }
+}
+
+/// # Email Validation Endpoint (v2)
+///
+/// Validates one address like `POST /api/v1/validate-email`, answering with a verdict
+/// and a score instead of a pass/fail flag. Every verdict, undeliverable included, is
+/// a `200`; error statuses are kept for requests that could not be served.
+///
+/// `reason.message` is localized like v1 messages, from the `lang` query parameter
+/// (`en`, `es`, `de` or `fr`) or the `Accept-Language` header.
+///
+/// ## Responses
+/// - **200 OK**: `{ "email", "verdict", "score"?, "domain"?, "reason"? }`
+/// - **400 Bad Request**: `INVALID_LANG`, `lang` names a language without a catalog
+/// - **401 Unauthorized**: missing or invalid credentials
+/// - **422 Unprocessable Entity**: `INVALID_FIELD`, the address is too long or contains
+///   control characters
+/// - **503 Service Unavailable**: `UPSTREAM_UNAVAILABLE`, a reference list could not be read
+#[utoipa::path(
+    post,
+    path = "/api/v2/validate-email",
+    request_body = EmailRequest,
+    params(
+        ("check_role_based" = Option<bool>, Query, description = "Enable role-based email validation"),
+        ("lang" = Option<String>, Query, description = "Language of reason.message: en, es, de or fr; overrides Accept-Language")
+    ),
+    responses(
+        (status = 200, description = "The address's verdict", body = EmailValidationV2),
+        (status = 400, description = "INVALID_LANG: lang names a language without a catalog", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 422, description = "INVALID_FIELD or INVALID_BODY: the address or body is malformed", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: a dependency is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[post("/validate-email")]
+pub async fn validate_email(
+    req: web::Json<EmailRequest>,
+    query: web::Query<V2ValidationQuery>,
+    backends: web::Data<ValidationBackends>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    let (result, _) = query
+        .validate(&req.email, &backends, &mongo_client, &http_req)
+        .await?;
+    Ok(HttpResponse::Ok().json(EmailValidationV2::new(&req.email, result)))
+}
+
+/// # Email Validation Endpoint (v2, GET)
+///
+/// Same as `POST /api/v2/validate-email` with the address in the `email` query
+/// parameter, which keeps a literal `+`. As with v1, `Cache-Control` allows reuse for
+/// as long as the verdict stays in the validation cache.
+#[utoipa::path(
+    get,
+    path = "/api/v2/validate-email",
+    params(
+        ("email" = String, Query, description = "Address to validate"),
+        ("check_role_based" = Option<bool>, Query, description = "Enable role-based email validation"),
+        ("lang" = Option<String>, Query, description = "Language of reason.message: en, es, de or fr; overrides Accept-Language")
+    ),
+    responses(
+        (status = 200, description = "The address's verdict; Cache-Control reflects the cached result's TTL", body = EmailValidationV2),
+        (status = 400, description = "INVALID_INPUT: the email parameter is missing; INVALID_LANG", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 422, description = "INVALID_FIELD: the address is malformed", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: a dependency is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[get("/validate-email")]
+pub async fn validate_email_get(
+    query: web::Query<V2ValidationQuery>,
+    backends: web::Data<ValidationBackends>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    let email = email_query_param(http_req.query_string()).ok_or_else(|| {
+        ApiError::validation(
+            "INVALID_INPUT",
+            "Pass the address as the email query parameter",
+        )
+    })?;
+    let (result, checks) = query
+        .validate(&email, &backends, &mongo_client, &http_req)
+        .await?;
+    let error_code = result.error.as_ref().map(|error| error.code.clone());
+    let mut response = HttpResponse::Ok().json(EmailValidationV2::new(&email, result));
+    set_cache_control(
+        &mut response,
+        &backends,
+        &email,
+        checks,
+        error_code.as_deref(),
+    )
+    .await;
+    Ok(response)
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(validate_email).service(validate_email_get);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::email::EmailValidationError;
+    use crate::stores::MemoryStore;
+    use actix_web::{App, http::StatusCode, test as actix_test};
+    use mongodb::options::ClientOptions;
+    use std::sync::Arc;
+
+    fn result(code: Option<&str>) -> EmailValidationResponse {
+        EmailValidationResponse {
+            is_valid: code.is_none(),
+            domain: None,
+            has_tag: None,
+            base_address: None,
+            status: code.is_none().then(|| "VALID".to_string()),
+            error: code.map(|code| EmailValidationError {
+                code: code.to_string(),
+                message: "message".to_string(),
+                violations: Vec::new(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_verdicts_from_codes() {
+        for (code, verdict, score) in [
+            (None, Verdict::Deliverable, Some(100)),
+            (Some("DISPOSABLE_EMAIL"), Verdict::Risky, Some(50)),
+            (Some("ROLE_BASED_EMAIL"), Verdict::Risky, Some(50)),
+            (Some("INVALID_DOMAIN"), Verdict::Undeliverable, Some(0)),
+            (Some("SUPPRESSED"), Verdict::Undeliverable, Some(0)),
+            (Some("DNS_UNAVAILABLE"), Verdict::Unknown, None),
+            (Some("NOT_A_CODE"), Verdict::Undeliverable, Some(0)),
+        ] {
+            let rendered = EmailValidationV2::new(" user@example.com ", result(code));
+            assert_eq!(rendered.email, "user@example.com");
+            assert_eq!(rendered.verdict, verdict, "{:?}", code);
+            assert_eq!(rendered.score, score, "{:?}", code);
+            assert_eq!(
+                rendered.reason.map(|reason| reason.code),
+                code.map(str::to_string)
+            );
+        }
+    }
+
+    #[test]
+    fn test_serialized_shape() {
+        let deliverable =
+            serde_json::to_value(EmailValidationV2::new("a@example.com", result(None))).unwrap();
+        assert_eq!(
+            deliverable,
+            serde_json::json!({ "email": "a@example.com", "verdict": "deliverable", "score": 100 })
+        );
+
+        let unknown = serde_json::to_value(EmailValidationV2::new(
+            "a@example.com",
+            result(Some("DNS_UNAVAILABLE")),
+        ))
+        .unwrap();
+        assert_eq!(unknown["verdict"], "unknown");
+        assert!(unknown.get("score").is_none());
+        assert_eq!(unknown["reason"]["code"], "DNS_UNAVAILABLE");
+    }
+
+    #[actix_web::test]
+    async fn test_requests_refused_before_validation() {
+        let mongo_client = MongoClient::with_options(ClientOptions::default()).unwrap();
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(ValidationBackends::from_store(Arc::new(
+                    MemoryStore::default(),
+                ))))
+                .app_data(web::Data::new(mongo_client))
+                .service(web::scope("/api/v2").configure(configure_routes)),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::get()
+            .uri("/api/v2/validate-email")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        // Without the auth middleware nobody is authenticated
+        let req = actix_test::TestRequest::get()
+            .uri("/api/v2/validate-email?email=user@example.com")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
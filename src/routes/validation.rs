@@ -0,0 +1,164 @@
+use crate::auth::AuthedAccount;
+use crate::error::{ApiError, ErrorEnvelope};
+use crate::i18n::{self, Lang};
+use crate::input_limits;
+use crate::metering;
+use crate::routes::email::{
+    EmailValidationResponse, ValidationQuery, caller_defaults, caller_lists,
+};
+use crate::validation_history::{self, HistoryRecord, ValidationSource};
+use crate::validation_service::{ValidationBackends, ValidationService};
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, get, web};
+use mongodb::Client as MongoClient;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+/// Strong validator of a response body: the first 16 bytes of its SHA-256, hex encoded
+/// and quoted
+pub fn etag(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    let hex: String = digest[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("\"{}\"", hex)
+}
+
+/// Whether an `If-None-Match` header matches `etag`, using the weak comparison RFC 9110
+/// prescribes for it
+pub fn if_none_match(header: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    header
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// # Validation Result Resource
+///
+/// The validation result of one address as a cacheable resource, for clients that poll
+/// the same addresses. Results come from the shared validation cache when present (see
+/// [`crate::validation_cache::ValidationCache`]), so repeated reads are cheap; an
+/// undeliverable address is a `200` with `is_valid: false`, not an error.
+///
+/// Every response carries an `ETag` derived from the result it describes; send it back
+/// in `If-None-Match` to get an empty `304 Not Modified` while the result is unchanged.
+/// Requests still count against the monthly quota either way.
+///
+/// ## Request
+/// - Path: the address, percent-encoded
+/// - Query Parameters: `check_role_based`, `verbosity`, `fields` and `lang`, as for
+///   `POST /validate-email`; responses vary with `Accept-Language`
+///
+/// ## Responses
+/// - **200 OK**: `{ "is_valid", "domain"?, "status", "error" }`
+/// - **304 Not Modified**: `If-None-Match` matches the current result
+/// - **400 Bad Request**: `INVALID_FIELDS`, `INVALID_VERBOSITY` or `INVALID_LANG`
+/// - **422 Unprocessable Entity**: `INVALID_FIELD`, the address is too long or contains
+///   control characters
+/// - **503 Service Unavailable**: `UPSTREAM_UNAVAILABLE`, the result could not be decided
+#[utoipa::path(
+    get,
+    path = "/api/v1/validation/{email}",
+    params(
+        ("email" = String, Path, description = "Address to validate, percent-encoded"),
+        ("check_role_based" = Option<bool>, Query, description = "Enable role-based email validation"),
+        ("verbosity" = Option<String>, Query, description = "minimal, standard (default) or full"),
+        ("fields" = Option<String>, Query, description = "Comma-separated result fields to return, e.g. is_valid,error.code"),
+        ("lang" = Option<String>, Query, description = "Language of messages: en, es, de or fr; overrides Accept-Language"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag of a previously returned result")
+    ),
+    responses(
+        (status = 200, description = "The address's validation result, with an ETag header", body = EmailValidationResponse),
+        (status = 304, description = "The result matches If-None-Match"),
+        (status = 400, description = "INVALID_FIELDS, INVALID_VERBOSITY or INVALID_LANG", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid API key", body = ErrorEnvelope),
+        (status = 422, description = "INVALID_FIELD: the address is malformed", body = ErrorEnvelope),
+        (status = 503, description = "UPSTREAM_UNAVAILABLE: a dependency is unavailable", body = ErrorEnvelope)
+    ),
+    tag = "Email Validation"
+)]
+#[get("/validation/{email}")]
+pub async fn get_validation(
+    path: web::Path<String>,
+    query: web::Query<ValidationQuery>,
+    backends: web::Data<ValidationBackends>,
+    mongo_client: web::Data<MongoClient>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    let email = path.into_inner();
+    input_limits::check_email_field("email", &email)?;
+    let shape = query.response_shape()?;
+    let lang = Lang::requested(query.lang.as_deref(), &http_req)?;
+    let account = AuthedAccount::from_http(&http_req)?;
+    metering::charge_request(&http_req, 1).await?;
+    let lists = caller_lists(&http_req, &mongo_client, &[email.trim().to_string()]).await?;
+    let checks = query
+        .options(&caller_defaults(&http_req, &mongo_client).await?)
+        .checks(false);
+    let tenant_id = account.tenant_id();
+    let mut result = ValidationService::new(&backends)
+        .for_tenant(&tenant_id)
+        .validate(&email, checks, &lists)
+        .await;
+    if let Some(error) = &result.error
+        && error.code == "DATABASE_ERROR"
+    {
+        return Err(ApiError::upstream("database", error.message.clone()));
+    }
+    validation_history::record(
+        &mongo_client,
+        vec![HistoryRecord::new(
+            &tenant_id,
+            &email,
+            result.is_valid,
+            result.error.as_ref().map(|e| e.code.as_str()),
+            ValidationSource::Rest,
+        )],
+    );
+
+    if let Some(error) = &mut result.error {
+        i18n::localize(error, lang);
+    }
+    let body = shape.apply(json!(result)).to_string();
+    let etag = etag(body.as_bytes());
+    let unchanged = http_req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| if_none_match(value, &etag));
+
+    let mut response = if unchanged {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    response
+        .insert_header((header::ETAG, etag))
+        .insert_header((header::CACHE_CONTROL, "private, no-cache"))
+        .insert_header((header::VARY, "Accept-Language"));
+    if unchanged {
+        return Ok(response.finish());
+    }
+    Ok(response.content_type("application/json").body(body))
+}
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_validation);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_if_none_match() {
+        let tag = etag(br#"{"is_valid":true}"#);
+        assert_eq!(tag.len(), 34);
+        assert_eq!(tag, etag(br#"{"is_valid":true}"#));
+        assert_ne!(tag, etag(br#"{"is_valid":false}"#));
+
+        assert!(if_none_match(&tag, &tag));
+        assert!(if_none_match(&format!("\"other\", W/{}", tag), &tag));
+        assert!(if_none_match("*", &tag));
+        assert!(!if_none_match("\"other\"", &tag));
+    }
+}
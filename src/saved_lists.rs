@@ -0,0 +1,364 @@
+use crate::auth;
+use crate::job_payload;
+use crate::job_queue::JobQueue;
+use crate::metering::{self, Meter, QuotaExceeded};
+use crate::schedules;
+use chrono::Utc;
+use futures::TryStreamExt;
+use mongodb::bson::{self, doc};
+use mongodb::options::{IndexOptions, ReturnDocument};
+use mongodb::{Client, Collection, IndexModel};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use utoipa::ToSchema;
+
+/// Addresses a saved list holds at most
+pub const MAX_LIST_EMAILS: usize = 50_000;
+
+/// Names longer than this are rejected
+pub const MAX_NAME_LENGTH: usize = 100;
+
+/// A record in the `saved_lists` collection
+///
+/// A named set of addresses an account keeps on the service, to validate as a bulk job
+/// whenever it likes or on a schedule. Addresses are stored trimmed and without exact
+/// duplicates, in the order they were added.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedList {
+    pub list_id: String,
+    /// Lowercased email of the account the list belongs to
+    pub owner: String,
+    pub name: String,
+    pub emails: Vec<String>,
+    /// Unix timestamps
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Latest bulk job validating the list
+    #[serde(default)]
+    pub last_job_id: Option<String>,
+}
+
+/// A saved list without its addresses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SavedListSummary {
+    pub list_id: String,
+    pub name: String,
+    pub email_count: u64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub last_job_id: Option<String>,
+}
+
+impl From<&SavedList> for SavedListSummary {
+    fn from(list: &SavedList) -> Self {
+        SavedListSummary {
+            list_id: list.list_id.clone(),
+            name: list.name.clone(),
+            email_count: list.emails.len() as u64,
+            created_at: list.created_at,
+            updated_at: list.updated_at,
+            last_job_id: list.last_job_id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ListError {
+    InvalidName(String),
+    /// The change would take the list past [`MAX_LIST_EMAILS`]
+    TooLarge,
+    NotFound,
+    /// There are no addresses to validate
+    Empty,
+    /// The list is above the job queue's payload limit
+    JobTooLarge,
+    /// The owner's plan has no quota left for the list's addresses
+    QuotaExceeded(QuotaExceeded),
+    Queue(String),
+    Database(String),
+}
+
+impl From<mongodb::error::Error> for ListError {
+    fn from(e: mongodb::error::Error) -> Self {
+        ListError::Database(e.to_string())
+    }
+}
+
+/// Reads `DB_NAME_PRODUCTION` (default `email_sanitizer`) and `DB_SAVED_LISTS_COLLECTION`
+/// (default `saved_lists`)
+pub fn saved_lists_collection(mongo_client: &Client) -> Collection<SavedList> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let collection_name =
+        std::env::var("DB_SAVED_LISTS_COLLECTION").unwrap_or_else(|_| "saved_lists".to_string());
+    mongo_client.database(&db_name).collection(&collection_name)
+}
+
+/// Creates the indexes used to look lists up by id and owner
+pub async fn ensure_indexes(mongo_client: &Client) -> Result<(), String> {
+    saved_lists_collection(mongo_client)
+        .create_indexes([
+            IndexModel::builder()
+                .keys(doc! { "list_id": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder().keys(doc! { "owner": 1 }).build(),
+        ])
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Trims a list name, rejecting empty and overlong ones
+pub fn validate_name(name: &str) -> Result<&str, ListError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(ListError::InvalidName(format!(
+            "List names are 1 to {} characters",
+            MAX_NAME_LENGTH
+        )));
+    }
+    Ok(name)
+}
+
+/// Trimmed, non-empty addresses of `emails` that are not in `existing`, first
+/// occurrence of each
+pub fn new_emails(existing: &[String], emails: &[String]) -> Vec<String> {
+    let mut seen: HashSet<&str> = existing.iter().map(String::as_str).collect();
+    emails
+        .iter()
+        .map(|email| email.trim())
+        .filter(|email| !email.is_empty() && seen.insert(email))
+        .map(str::to_string)
+        .collect()
+}
+
+pub async fn create_list(
+    mongo_client: &Client,
+    owner: &str,
+    name: &str,
+    emails: &[String],
+) -> Result<SavedList, ListError> {
+    let name = validate_name(name)?;
+    let emails = new_emails(&[], emails);
+    if emails.len() > MAX_LIST_EMAILS {
+        return Err(ListError::TooLarge);
+    }
+    let now = Utc::now().timestamp();
+    let list = SavedList {
+        list_id: uuid::Uuid::new_v4().to_string(),
+        owner: owner.to_string(),
+        name: name.to_string(),
+        emails,
+        created_at: now,
+        updated_at: now,
+        last_job_id: None,
+    };
+    saved_lists_collection(mongo_client)
+        .insert_one(&list)
+        .await?;
+    Ok(list)
+}
+
+/// The owner's lists without their addresses, oldest first
+pub async fn list_lists(
+    mongo_client: &Client,
+    owner: &str,
+) -> Result<Vec<SavedListSummary>, String> {
+    let documents: Vec<bson::Document> = saved_lists_collection(mongo_client)
+        .aggregate([
+            doc! { "$match": { "owner": owner } },
+            doc! { "$sort": { "created_at": 1 } },
+            doc! { "$project": {
+                "_id": 0,
+                "list_id": 1,
+                "name": 1,
+                "email_count": { "$size": "$emails" },
+                "created_at": 1,
+                "updated_at": 1,
+                "last_job_id": 1,
+            } },
+        ])
+        .await
+        .map_err(|e| e.to_string())?
+        .try_collect()
+        .await
+        .map_err(|e| e.to_string())?;
+    documents
+        .into_iter()
+        .map(|document| bson::from_document(document).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// One of the owner's lists; other owners' lists are not found
+pub async fn get_list(
+    mongo_client: &Client,
+    owner: &str,
+    list_id: &str,
+) -> Result<Option<SavedList>, String> {
+    saved_lists_collection(mongo_client)
+        .find_one(doc! { "list_id": list_id, "owner": owner })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Appends the addresses the list doesn't hold yet
+pub async fn add_emails(
+    mongo_client: &Client,
+    owner: &str,
+    list_id: &str,
+    emails: &[String],
+) -> Result<SavedList, ListError> {
+    let list = get_list(mongo_client, owner, list_id)
+        .await
+        .map_err(ListError::Database)?
+        .ok_or(ListError::NotFound)?;
+    let added = new_emails(&list.emails, emails);
+    if list.emails.len() + added.len() > MAX_LIST_EMAILS {
+        return Err(ListError::TooLarge);
+    }
+    saved_lists_collection(mongo_client)
+        .find_one_and_update(
+            doc! { "list_id": list_id, "owner": owner },
+            doc! {
+                "$addToSet": { "emails": { "$each": added } },
+                "$set": { "updated_at": Utc::now().timestamp() },
+            },
+        )
+        .return_document(ReturnDocument::After)
+        .await?
+        .ok_or(ListError::NotFound)
+}
+
+/// Removes the given addresses from the list; addresses it doesn't hold are ignored
+pub async fn remove_emails(
+    mongo_client: &Client,
+    owner: &str,
+    list_id: &str,
+    emails: &[String],
+) -> Result<SavedList, ListError> {
+    let emails: Vec<&str> = emails.iter().map(|email| email.trim()).collect();
+    saved_lists_collection(mongo_client)
+        .find_one_and_update(
+            doc! { "list_id": list_id, "owner": owner },
+            doc! {
+                "$pull": { "emails": { "$in": emails } },
+                "$set": { "updated_at": Utc::now().timestamp() },
+            },
+        )
+        .return_document(ReturnDocument::After)
+        .await?
+        .ok_or(ListError::NotFound)
+}
+
+/// Remembers the job that validates the list
+pub async fn set_last_job(
+    mongo_client: &Client,
+    owner: &str,
+    list_id: &str,
+    job_id: &str,
+) -> Result<(), String> {
+    saved_lists_collection(mongo_client)
+        .update_one(
+            doc! { "list_id": list_id, "owner": owner },
+            doc! { "$set": { "last_job_id": job_id } },
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Queues a bulk job validating every address of the list, as a job of its owner;
+/// returns the job id
+///
+/// The list's addresses are charged to the owner's quota when a `meter` is given.
+pub async fn queue_validation(
+    mongo_client: &Client,
+    job_queue: &JobQueue,
+    owner: &str,
+    list_id: &str,
+    check_role_based: bool,
+    meter: Option<&Meter>,
+) -> Result<String, ListError> {
+    let list = get_list(mongo_client, owner, list_id)
+        .await
+        .map_err(ListError::Database)?
+        .ok_or(ListError::NotFound)?;
+    if list.emails.is_empty() {
+        return Err(ListError::Empty);
+    }
+    if let Some(meter) = meter {
+        metering::charge(meter, mongo_client, owner, list.emails.len() as u64)
+            .await
+            .map_err(ListError::QuotaExceeded)?;
+    }
+    let plan = auth::account_plan(mongo_client, owner)
+        .await
+        .map_err(ListError::Database)?;
+    let job_id = job_queue
+        .enqueue_bulk_validation_for_tenant(owner, plan, list.emails, check_role_based)
+        .await
+        .map_err(|e| {
+            if job_payload::is_too_large(&e) {
+                ListError::JobTooLarge
+            } else {
+                ListError::Queue(e.to_string())
+            }
+        })?;
+    set_last_job(mongo_client, owner, list_id, &job_id)
+        .await
+        .map_err(ListError::Database)?;
+    Ok(job_id)
+}
+
+/// Removes one of the owner's lists along with the schedules that re-validate it;
+/// returns whether there was one
+pub async fn delete_list(
+    mongo_client: &Client,
+    owner: &str,
+    list_id: &str,
+) -> Result<bool, String> {
+    let deleted = saved_lists_collection(mongo_client)
+        .delete_one(doc! { "list_id": list_id, "owner": owner })
+        .await
+        .map_err(|e| e.to_string())?
+        .deleted_count
+        > 0;
+    schedules::schedules_collection(mongo_client)
+        .delete_many(doc! { "owner": owner, "source.type": "list", "source.list_id": list_id })
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_new_emails() {
+        let existing = strings(&["a@example.com"]);
+        let added = new_emails(
+            &existing,
+            &strings(&[
+                " b@example.com",
+                "a@example.com",
+                "",
+                "b@example.com\n",
+                "c@example.com",
+            ]),
+        );
+        assert_eq!(added, strings(&["b@example.com", "c@example.com"]));
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert_eq!(validate_name("  Newsletter "), Ok("Newsletter"));
+        assert!(validate_name(" ").is_err());
+        assert!(validate_name(&"x".repeat(MAX_NAME_LENGTH + 1)).is_err());
+    }
+}
@@ -0,0 +1,658 @@
+use crate::auth::PlanTier;
+use crate::cron::CronSchedule;
+use crate::job_queue::{JobQueue, JobStatus};
+use crate::job_summary::verdict;
+use crate::metering::{self, Meter};
+use crate::routes::email::BulkEmailValidationResult;
+use crate::saved_lists;
+use crate::webhooks;
+use chrono::{TimeZone, Utc};
+use futures::TryStreamExt;
+use mongodb::bson::{self, doc};
+use mongodb::options::IndexOptions;
+use mongodb::{Client, Collection, IndexModel};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Addresses listed per direction in a run's diff; the counts cover all of them
+pub const DIFF_SAMPLE_SIZE: usize = 100;
+
+/// Runs kept per schedule, oldest dropped first
+pub const MAX_RUNS: usize = 10;
+
+/// Largest list a schedule re-validates
+pub const MAX_SCHEDULED_EMAILS: usize = 50_000;
+
+/// Default time between checks for due schedules and finished runs
+const DEFAULT_POLL_SECS: u64 = 60;
+
+/// What a schedule re-validates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ScheduleSource {
+    /// The addresses of a completed bulk job, captured when the schedule was created
+    Job { job_id: String },
+    /// The current addresses of a saved list; its first run sets the baseline
+    List { list_id: String },
+}
+
+/// An address of a scheduled list with its verdict at the last run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledAddress {
+    pub email: String,
+    pub is_valid: bool,
+    /// `VALID` or the error code, as in job summaries
+    pub verdict: String,
+}
+
+/// Addresses whose validity changed between two runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ScheduleDiff {
+    pub newly_invalid: u64,
+    pub newly_valid: u64,
+    pub unchanged: u64,
+    /// The first [`DIFF_SAMPLE_SIZE`] addresses that turned invalid
+    pub newly_invalid_emails: Vec<String>,
+    /// The first [`DIFF_SAMPLE_SIZE`] addresses that turned valid
+    pub newly_valid_emails: Vec<String>,
+}
+
+impl ScheduleDiff {
+    /// One-line description, e.g. "32 addresses newly invalid since last run"
+    pub fn summary(&self) -> String {
+        let addresses = |count: u64| match count {
+            1 => "1 address".to_string(),
+            count => format!("{} addresses", count),
+        };
+        match (self.newly_invalid, self.newly_valid) {
+            (0, 0) => "No address changed validity since last run".to_string(),
+            (invalid, 0) => format!("{} newly invalid since last run", addresses(invalid)),
+            (0, valid) => format!("{} newly valid since last run", addresses(valid)),
+            (invalid, valid) => format!(
+                "{} newly invalid and {} newly valid since last run",
+                addresses(invalid),
+                valid
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    Completed,
+    /// The job failed or expired before its results were read
+    Failed,
+}
+
+/// One re-validation of a schedule's list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ScheduleRun {
+    /// Bulk job the run was queued as, readable through the job endpoints while it lasts
+    pub job_id: String,
+    pub status: RunStatus,
+    /// Unix timestamps
+    pub started_at: i64,
+    pub finished_at: i64,
+    /// Changes against the previous run; `None` for failed runs
+    pub diff: Option<ScheduleDiff>,
+    /// Whether the webhook answered with a 2xx; `None` without a webhook
+    #[serde(default)]
+    pub webhook_delivered: Option<bool>,
+}
+
+/// A record in the `validation_schedules` collection
+///
+/// Holds the addresses of the latest run with their verdicts; each run is queued as a
+/// bulk job of the owner and compared against them once it completes. Job schedules
+/// re-validate the same addresses every time, list schedules the list's addresses at
+/// the time of the run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub schedule_id: String,
+    /// Tenant of the API key that created the schedule; runs are queued as its jobs
+    pub owner: String,
+    pub plan: PlanTier,
+    pub source: ScheduleSource,
+    pub cron: String,
+    pub check_role_based: bool,
+    pub webhook_url: Option<String>,
+    /// Unix timestamps
+    pub created_at: i64,
+    pub next_run_at: i64,
+    /// Job of the run in progress
+    #[serde(default)]
+    pub running_job_id: Option<String>,
+    #[serde(default)]
+    pub running_since: Option<i64>,
+    pub addresses: Vec<ScheduledAddress>,
+    /// Latest runs, oldest first
+    #[serde(default)]
+    pub runs: Vec<ScheduleRun>,
+}
+
+/// Reads `DB_NAME_PRODUCTION` (default `email_sanitizer`) and `DB_SCHEDULES_COLLECTION`
+/// (default `validation_schedules`)
+pub fn schedules_collection(mongo_client: &Client) -> Collection<Schedule> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let collection_name = std::env::var("DB_SCHEDULES_COLLECTION")
+        .unwrap_or_else(|_| "validation_schedules".to_string());
+    mongo_client.database(&db_name).collection(&collection_name)
+}
+
+/// Creates the indexes used to look schedules up by id and owner, and to find due ones
+pub async fn ensure_indexes(mongo_client: &Client) -> Result<(), String> {
+    schedules_collection(mongo_client)
+        .create_indexes([
+            IndexModel::builder()
+                .keys(doc! { "schedule_id": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder().keys(doc! { "owner": 1 }).build(),
+            IndexModel::builder()
+                .keys(doc! { "next_run_at": 1 })
+                .build(),
+        ])
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Time between checks for due schedules, from `SCHEDULE_POLL_SECS` (default 60)
+pub fn poll_interval() -> Duration {
+    let secs = std::env::var("SCHEDULE_POLL_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_POLL_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Parses a schedule's cron expression, refusing ones that fire more than once an hour
+/// or never
+pub fn parse_cron(expression: &str) -> Result<CronSchedule, String> {
+    let cron = CronSchedule::parse(expression)?;
+    if cron.runs_more_than_hourly() {
+        return Err("Lists are re-validated at most once an hour; use a single minute".to_string());
+    }
+    if cron.next_after(Utc::now()).is_none() {
+        return Err("The cron expression never fires".to_string());
+    }
+    Ok(cron)
+}
+
+/// Trims a webhook URL, accepting only absolute http(s) URLs
+pub fn validate_webhook_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.has_host() => {
+            Ok(url.to_string())
+        }
+        _ => Err("Webhook URLs must be absolute http or https URLs".to_string()),
+    }
+}
+
+/// Addresses of a job's results with their verdicts, first occurrence of each address
+pub fn addresses_from_results(rows: &[BulkEmailValidationResult]) -> Vec<ScheduledAddress> {
+    let mut seen = HashSet::new();
+    rows.iter()
+        .filter(|row| seen.insert(row.email.as_str()))
+        .map(|row| ScheduledAddress {
+            email: row.email.clone(),
+            is_valid: row.validation.is_valid,
+            verdict: verdict(&row.validation).to_string(),
+        })
+        .collect()
+}
+
+/// Compares a run's verdicts with the previous ones; addresses missing from either
+/// side are left out
+pub fn diff(previous: &[ScheduledAddress], current: &[ScheduledAddress]) -> ScheduleDiff {
+    let previous: HashMap<&str, bool> = previous
+        .iter()
+        .map(|address| (address.email.as_str(), address.is_valid))
+        .collect();
+    let mut diff = ScheduleDiff::default();
+    for address in current {
+        match previous.get(address.email.as_str()) {
+            Some(true) if !address.is_valid => {
+                diff.newly_invalid += 1;
+                if diff.newly_invalid_emails.len() < DIFF_SAMPLE_SIZE {
+                    diff.newly_invalid_emails.push(address.email.clone());
+                }
+            }
+            Some(false) if address.is_valid => {
+                diff.newly_valid += 1;
+                if diff.newly_valid_emails.len() < DIFF_SAMPLE_SIZE {
+                    diff.newly_valid_emails.push(address.email.clone());
+                }
+            }
+            Some(_) => diff.unchanged += 1,
+            None => {}
+        }
+    }
+    diff
+}
+
+/// Every result of a completed job, in submission order
+pub async fn job_results(
+    job_queue: &JobQueue,
+    job_id: &str,
+) -> Result<Vec<BulkEmailValidationResult>, String> {
+    let db_error = |e: redis::RedisError| format!("Failed to read job results: {}", e);
+    let chunk_count = job_queue
+        .result_chunk_count(job_id)
+        .await
+        .map_err(db_error)?;
+    let mut rows = Vec::new();
+    for index in 0..chunk_count {
+        let chunk = job_queue
+            .result_chunk(job_id, index)
+            .await
+            .map_err(db_error)?
+            .ok_or_else(|| "Job results expired".to_string())?;
+        let chunk: Vec<BulkEmailValidationResult> =
+            serde_json::from_str(&chunk).map_err(|e| format!("Corrupt result chunk: {}", e))?;
+        rows.extend(chunk);
+    }
+    Ok(rows)
+}
+
+/// Fields of a new schedule supplied by its owner
+pub struct NewSchedule {
+    pub owner: String,
+    pub plan: PlanTier,
+    pub source: ScheduleSource,
+    pub cron: String,
+    pub check_role_based: bool,
+    pub webhook_url: Option<String>,
+    pub addresses: Vec<ScheduledAddress>,
+}
+
+/// Stores a schedule whose first run is the cron expression's next occurrence
+pub async fn create_schedule(mongo_client: &Client, new: NewSchedule) -> Result<Schedule, String> {
+    let now = Utc::now();
+    let next_run_at = parse_cron(&new.cron)?
+        .next_after(now)
+        .ok_or_else(|| "The cron expression never fires".to_string())?;
+    let schedule = Schedule {
+        schedule_id: uuid::Uuid::new_v4().to_string(),
+        owner: new.owner,
+        plan: new.plan,
+        source: new.source,
+        cron: new.cron.trim().to_string(),
+        check_role_based: new.check_role_based,
+        webhook_url: new.webhook_url,
+        created_at: now.timestamp(),
+        next_run_at: next_run_at.timestamp(),
+        running_job_id: None,
+        running_since: None,
+        addresses: new.addresses,
+        runs: Vec::new(),
+    };
+    schedules_collection(mongo_client)
+        .insert_one(&schedule)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(schedule)
+}
+
+/// The owner's schedules, oldest first
+pub async fn list_schedules(mongo_client: &Client, owner: &str) -> Result<Vec<Schedule>, String> {
+    schedules_collection(mongo_client)
+        .find(doc! { "owner": owner })
+        .sort(doc! { "created_at": 1 })
+        .await
+        .map_err(|e| e.to_string())?
+        .try_collect()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// One of the owner's schedules; other owners' schedules are not found
+pub async fn get_schedule(
+    mongo_client: &Client,
+    owner: &str,
+    schedule_id: &str,
+) -> Result<Option<Schedule>, String> {
+    schedules_collection(mongo_client)
+        .find_one(doc! { "schedule_id": schedule_id, "owner": owner })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Removes one of the owner's schedules; returns whether there was one. A run in
+/// progress finishes as a plain job.
+pub async fn delete_schedule(
+    mongo_client: &Client,
+    owner: &str,
+    schedule_id: &str,
+) -> Result<bool, String> {
+    schedules_collection(mongo_client)
+        .delete_one(doc! { "schedule_id": schedule_id, "owner": owner })
+        .await
+        .map(|result| result.deleted_count > 0)
+        .map_err(|e| e.to_string())
+}
+
+/// Queues a job for every due schedule without a run in progress
+///
+/// Each schedule is claimed by moving `next_run_at` forward with a conditional update,
+/// so when several API processes poll, only one of them queues the run.
+async fn start_due_runs(
+    mongo_client: &Client,
+    job_queue: &JobQueue,
+    meter: Option<&Meter>,
+    now: i64,
+) -> Result<(), String> {
+    let collection = schedules_collection(mongo_client);
+    let due: Vec<Schedule> = collection
+        .find(doc! { "next_run_at": { "$lte": now }, "running_job_id": null })
+        .await
+        .map_err(|e| e.to_string())?
+        .try_collect()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for schedule in due {
+        let next_run_at = CronSchedule::parse(&schedule.cron)
+            .ok()
+            .and_then(|cron| cron.next_after(Utc.timestamp_opt(now, 0).single()?))
+            .map_or(i64::MAX, |next| next.timestamp());
+        let claimed = collection
+            .update_one(
+                doc! {
+                    "schedule_id": &schedule.schedule_id,
+                    "next_run_at": schedule.next_run_at,
+                    "running_job_id": null,
+                },
+                doc! { "$set": { "next_run_at": next_run_at } },
+            )
+            .await
+            .map_err(|e| e.to_string())?
+            .modified_count
+            == 1;
+        if !claimed {
+            continue;
+        }
+
+        let emails = match &schedule.source {
+            ScheduleSource::Job { .. } => schedule
+                .addresses
+                .iter()
+                .map(|address| address.email.clone())
+                .collect(),
+            ScheduleSource::List { list_id } => {
+                match saved_lists::get_list(mongo_client, &schedule.owner, list_id).await? {
+                    Some(list) if !list.emails.is_empty() => list.emails,
+                    // Empty lists are skipped until addresses are added again
+                    _ => continue,
+                }
+            }
+        };
+        if let Some(meter) = meter
+            && metering::charge(meter, mongo_client, &schedule.owner, emails.len() as u64)
+                .await
+                .is_err()
+        {
+            // Skipped like a failed run; the next one may fall in a new billing period
+            eprintln!(
+                "Skipped scheduled run of {}: monthly quota exceeded",
+                schedule.schedule_id
+            );
+            continue;
+        }
+        match job_queue
+            .enqueue_bulk_validation_for_tenant(
+                &schedule.owner,
+                schedule.plan,
+                emails,
+                schedule.check_role_based,
+            )
+            .await
+        {
+            Ok(job_id) => {
+                collection
+                    .update_one(
+                        doc! { "schedule_id": &schedule.schedule_id },
+                        doc! { "$set": { "running_job_id": job_id, "running_since": now } },
+                    )
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            // The run is skipped; the next one is already scheduled
+            Err(e) => eprintln!(
+                "Failed to queue scheduled run of {}: {}",
+                schedule.schedule_id, e
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// Records the outcome of every run whose job has finished and notifies its webhook
+async fn finish_runs(mongo_client: &Client, job_queue: &JobQueue, now: i64) -> Result<(), String> {
+    let collection = schedules_collection(mongo_client);
+    let running: Vec<Schedule> = collection
+        .find(doc! { "running_job_id": { "$ne": null } })
+        .await
+        .map_err(|e| e.to_string())?
+        .try_collect()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for schedule in running {
+        let Some(job_id) = schedule.running_job_id.clone() else {
+            continue;
+        };
+        let job = job_queue
+            .get_job_status(&job_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let current = match job.map(|job| job.status) {
+            Some(JobStatus::Pending | JobStatus::Processing) => continue,
+            Some(JobStatus::Completed) => match job_results(job_queue, &job_id).await {
+                Ok(rows) => Some(addresses_from_results(&rows)),
+                Err(e) => {
+                    eprintln!("Failed to read scheduled run {}: {}", job_id, e);
+                    None
+                }
+            },
+            // Failed, or expired before it was picked up here
+            Some(JobStatus::Failed) | None => None,
+        };
+
+        let run = ScheduleRun {
+            job_id: job_id.clone(),
+            status: match current {
+                Some(_) => RunStatus::Completed,
+                None => RunStatus::Failed,
+            },
+            started_at: schedule.running_since.unwrap_or(now),
+            finished_at: now,
+            diff: current
+                .as_ref()
+                .map(|current| diff(&schedule.addresses, current)),
+            webhook_delivered: None,
+        };
+        let mut set = doc! { "running_job_id": null, "running_since": null };
+        if let Some(current) = &current {
+            set.insert(
+                "addresses",
+                bson::to_bson(current).map_err(|e| e.to_string())?,
+            );
+        }
+        let run_bson = bson::to_bson(&run).map_err(|e| e.to_string())?;
+        let recorded = collection
+            .update_one(
+                doc! { "schedule_id": &schedule.schedule_id, "running_job_id": &job_id },
+                doc! {
+                    "$set": set,
+                    "$push": { "runs": { "$each": [run_bson], "$slice": -(MAX_RUNS as i64) } },
+                },
+            )
+            .await
+            .map_err(|e| e.to_string())?
+            .modified_count
+            == 1;
+
+        // Another process recorded the run first and notifies instead
+        if !recorded {
+            continue;
+        }
+        if let Some(url) = &schedule.webhook_url {
+            let delivered = notify(mongo_client, &schedule, url, &run).await;
+            collection
+                .update_one(
+                    doc! { "schedule_id": &schedule.schedule_id, "runs.job_id": &job_id },
+                    doc! { "$set": { "runs.$.webhook_delivered": delivered } },
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Body posted to a schedule's webhook when a run finishes
+pub fn webhook_payload(schedule_id: &str, run: &ScheduleRun) -> serde_json::Value {
+    let (event, summary) = match &run.diff {
+        Some(diff) => ("schedule.run_completed", diff.summary()),
+        None => (
+            "schedule.run_failed",
+            "The scheduled re-validation failed".to_string(),
+        ),
+    };
+    json!({
+        "event": event,
+        "schedule_id": schedule_id,
+        "summary": summary,
+        "run": run,
+    })
+}
+
+/// Posts the run to the webhook, signed with the owner's secret; returns whether it
+/// answered with a 2xx
+async fn notify(mongo_client: &Client, schedule: &Schedule, url: &str, run: &ScheduleRun) -> bool {
+    let payload = webhook_payload(&schedule.schedule_id, run);
+    match webhooks::deliver_signed(mongo_client, &schedule.owner, url, &payload).await {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!(
+                "Schedule webhook for {} failed: {}",
+                schedule.schedule_id, e
+            );
+            false
+        }
+    }
+}
+
+/// Starts due runs and records finished ones every `interval`, forever
+///
+/// Each run's addresses are charged to the schedule owner's quota when a `meter` is
+/// given.
+pub async fn run_periodically(
+    mongo_client: Client,
+    job_queue: JobQueue,
+    meter: Option<Meter>,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let now = Utc::now().timestamp();
+        if let Err(e) = finish_runs(&mongo_client, &job_queue, now).await {
+            eprintln!("Failed to record scheduled runs: {}", e);
+        }
+        if let Err(e) = start_due_runs(&mongo_client, &job_queue, meter.as_ref(), now).await {
+            eprintln!("Failed to start scheduled runs: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(email: &str, is_valid: bool) -> ScheduledAddress {
+        ScheduledAddress {
+            email: email.to_string(),
+            is_valid,
+            verdict: if is_valid { "VALID" } else { "INVALID_DOMAIN" }.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let previous = [
+            address("a@example.com", true),
+            address("b@example.com", true),
+            address("c@example.com", false),
+            address("d@example.com", true),
+        ];
+        let current = [
+            address("a@example.com", false),
+            address("b@example.com", false),
+            address("c@example.com", true),
+            address("d@example.com", true),
+            address("new@example.com", false),
+        ];
+        let diff = diff(&previous, &current);
+
+        assert_eq!(diff.newly_invalid, 2);
+        assert_eq!(diff.newly_valid, 1);
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(
+            diff.newly_invalid_emails,
+            vec!["a@example.com", "b@example.com"]
+        );
+        assert_eq!(diff.newly_valid_emails, vec!["c@example.com"]);
+        assert_eq!(
+            diff.summary(),
+            "2 addresses newly invalid and 1 newly valid since last run"
+        );
+    }
+
+    #[test]
+    fn test_diff_summary() {
+        let diff = ScheduleDiff {
+            newly_invalid: 32,
+            ..Default::default()
+        };
+        assert_eq!(diff.summary(), "32 addresses newly invalid since last run");
+        assert_eq!(
+            ScheduleDiff::default().summary(),
+            "No address changed validity since last run"
+        );
+
+        let run = ScheduleRun {
+            job_id: "job-1".to_string(),
+            status: RunStatus::Completed,
+            started_at: 0,
+            finished_at: 60,
+            diff: Some(diff),
+            webhook_delivered: None,
+        };
+        let payload = webhook_payload("schedule-1", &run);
+        assert_eq!(payload["event"], "schedule.run_completed");
+        assert_eq!(payload["run"]["diff"]["newly_invalid"], 32);
+    }
+
+    #[test]
+    fn test_parse_cron_and_webhook_url() {
+        assert!(parse_cron("0 3 * * 1").is_ok());
+        assert!(parse_cron("*/30 * * * *").is_err());
+        assert!(parse_cron("not cron").is_err());
+
+        assert_eq!(
+            validate_webhook_url(" https://hooks.example.com/run ").as_deref(),
+            Ok("https://hooks.example.com/run")
+        );
+        assert!(validate_webhook_url("ftp://example.com").is_err());
+        assert!(validate_webhook_url("/relative").is_err());
+    }
+}
@@ -0,0 +1,361 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Timeout of fetching a signing certificate or confirming a subscription
+const SNS_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Signing certificates kept in memory; SNS rotates them rarely
+const MAX_CACHED_CERTS: usize = 16;
+
+/// A message Amazon SNS posts to an HTTP(S) subscription
+///
+/// See <https://docs.aws.amazon.com/sns/latest/dg/sns-verify-signature-of-message.html>.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SnsMessage {
+    /// `Notification`, `SubscriptionConfirmation` or `UnsubscribeConfirmation`
+    #[serde(rename = "Type")]
+    pub kind: String,
+    pub message_id: String,
+    pub topic_arn: String,
+    #[serde(default)]
+    pub subject: Option<String>,
+    pub message: String,
+    pub timestamp: String,
+    /// Confirmations only
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Confirmations only
+    #[serde(rename = "SubscribeURL", default)]
+    pub subscribe_url: Option<String>,
+    pub signature_version: String,
+    pub signature: String,
+    #[serde(rename = "SigningCertURL")]
+    pub signing_cert_url: String,
+}
+
+impl SnsMessage {
+    pub fn is_notification(&self) -> bool {
+        self.kind == "Notification"
+    }
+
+    /// The fields SNS signed, as `name\nvalue\n` lines in its fixed order
+    pub fn string_to_sign(&self) -> Result<String, String> {
+        let mut fields = vec![("Message", self.message.as_str())];
+        fields.push(("MessageId", &self.message_id));
+        match self.kind.as_str() {
+            "Notification" => {
+                if let Some(subject) = &self.subject {
+                    fields.push(("Subject", subject));
+                }
+                fields.push(("Timestamp", &self.timestamp));
+            }
+            "SubscriptionConfirmation" | "UnsubscribeConfirmation" => {
+                let subscribe_url = self
+                    .subscribe_url
+                    .as_deref()
+                    .ok_or("The confirmation has no SubscribeURL")?;
+                let token = self
+                    .token
+                    .as_deref()
+                    .ok_or("The confirmation has no Token")?;
+                fields.push(("SubscribeURL", subscribe_url));
+                fields.push(("Timestamp", &self.timestamp));
+                fields.push(("Token", token));
+            }
+            other => return Err(format!("Unsupported SNS message type {}", other)),
+        }
+        fields.push(("TopicArn", &self.topic_arn));
+        fields.push(("Type", &self.kind));
+        Ok(fields
+            .into_iter()
+            .map(|(name, value)| format!("{}\n{}\n", name, value))
+            .collect())
+    }
+
+    /// Checks the signature against the PEM signing certificate
+    ///
+    /// Only `SignatureVersion` 2 (RSA with SHA-256) is accepted; SHA-1 signatures of
+    /// version 1 are refused, so topics must have their `SignatureVersion` attribute
+    /// set to 2.
+    pub fn verify_with_cert(&self, cert_pem: &[u8]) -> Result<(), String> {
+        if self.signature_version != "2" {
+            return Err("Only SignatureVersion 2 is accepted; set it on the SNS topic".to_string());
+        }
+        let cert = rustls_pemfile::certs(&mut &cert_pem[..])
+            .next()
+            .ok_or("The signing certificate is empty")?
+            .map_err(|_| "The signing certificate is not PEM")?;
+        let cert = webpki::EndEntityCert::try_from(&cert)
+            .map_err(|_| "The signing certificate is not an X.509 certificate")?;
+        let signature = BASE64
+            .decode(self.signature.trim())
+            .map_err(|_| "The signature is not base64")?;
+        cert.verify_signature(
+            webpki::ring::RSA_PKCS1_2048_8192_SHA256,
+            self.string_to_sign()?.as_bytes(),
+            &signature,
+        )
+        .map_err(|_| "The SNS signature does not match".to_string())
+    }
+
+    /// Visits the `SubscribeURL` of a subscription confirmation, which must point at SNS
+    pub async fn confirm_subscription(&self) -> Result<(), String> {
+        let url = self
+            .subscribe_url
+            .as_deref()
+            .ok_or("The confirmation has no SubscribeURL")?;
+        check_sns_url(url)?;
+        client()?
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Region of an SNS endpoint host such as `sns.us-east-1.amazonaws.com`
+fn sns_region(host: &str) -> Option<&str> {
+    let region = host
+        .strip_prefix("sns.")?
+        .strip_suffix(".amazonaws.com")
+        .or_else(|| host.strip_prefix("sns.")?.strip_suffix(".amazonaws.com.cn"))?;
+    let valid = !region.is_empty()
+        && region
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    valid.then_some(region)
+}
+
+/// Checks that a URL is `https://sns.<region>.amazonaws.com/...` without credentials
+/// or a custom port, so nothing but SNS is ever fetched on behalf of a message
+pub fn check_sns_url(url: &str) -> Result<reqwest::Url, String> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| format!("\"{}\" is not a URL", url))?;
+    let is_sns = parsed.scheme() == "https"
+        && parsed.username().is_empty()
+        && parsed.password().is_none()
+        && parsed.port().is_none()
+        && parsed.host_str().and_then(sns_region).is_some();
+    if !is_sns {
+        return Err(format!("\"{}\" is not an SNS endpoint", url));
+    }
+    Ok(parsed)
+}
+
+/// Checks that `SigningCertURL` names a certificate served by SNS
+pub fn check_cert_url(url: &str) -> Result<(), String> {
+    let parsed = check_sns_url(url)?;
+    if !parsed.path().ends_with(".pem") {
+        return Err(format!("\"{}\" is not an SNS signing certificate", url));
+    }
+    Ok(())
+}
+
+/// Checks a topic ARN such as `arn:aws:sns:us-east-1:123456789012:ses-feedback`
+pub fn check_topic_arn(arn: &str) -> Result<String, String> {
+    let arn = arn.trim();
+    let parts: Vec<&str> = arn.split(':').collect();
+    let valid = match parts.as_slice() {
+        ["arn", partition, "sns", region, account, topic] => {
+            partition.starts_with("aws")
+                && !region.is_empty()
+                && account.len() == 12
+                && account.chars().all(|c| c.is_ascii_digit())
+                && !topic.is_empty()
+                && topic.len() <= 256
+                && topic
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        }
+        _ => false,
+    };
+    if !valid {
+        return Err(format!("\"{}\" is not an SNS topic ARN", arn));
+    }
+    Ok(arn.to_string())
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(SNS_REQUEST_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+fn cert_cache() -> &'static Mutex<HashMap<String, Vec<u8>>> {
+    static CERTS: OnceLock<Mutex<HashMap<String, Vec<u8>>>> = OnceLock::new();
+    CERTS.get_or_init(Mutex::default)
+}
+
+/// The PEM certificate at a `SigningCertURL` checked by [`check_cert_url`], fetched
+/// once and then kept in memory
+pub async fn signing_cert(url: &str) -> Result<Vec<u8>, String> {
+    if let Some(cert) = cert_cache().lock().unwrap().get(url) {
+        return Ok(cert.clone());
+    }
+    let cert = client()?
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?
+        .bytes()
+        .await
+        .map_err(|e| e.to_string())?
+        .to_vec();
+
+    let mut cache = cert_cache().lock().unwrap();
+    if cache.len() >= MAX_CACHED_CERTS {
+        cache.clear();
+    }
+    cache.insert(url.to_string(), cert.clone());
+    Ok(cert)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Self-signed test certificate; its key signed [`SIGNATURE`]
+    const TEST_CERT: &str = "-----BEGIN CERTIFICATE-----
+MIIDLzCCAhegAwIBAgIURan0OC0AevOEuwgmllyP76hKbgowDQYJKoZIhvcNAQEL
+BQAwJjEkMCIGA1UEAwwbc25zLnVzLWVhc3QtMS5hbWF6b25hd3MuY29tMCAXDTI2
+MTAxNzAxMzgyOFoYDzIxMjYwOTIzMDEzODI4WjAmMSQwIgYDVQQDDBtzbnMudXMt
+ZWFzdC0xLmFtYXpvbmF3cy5jb20wggEiMA0GCSqGSIb3DQEBAQUAA4IBDwAwggEK
+AoIBAQCt4aIIhjctWrqnV9vv6cvMjio4iKsUGvN2rWSxl0e7lYWdQV/EA+hUvncQ
+oZgnGjs1qzG1yiEUNohjH5TZhJfo24VOPK6bdLabxpUaTRtP3MIq5gdMjgUAJhNJ
+TwuoWRpX37yP0uCn81/5+/8pgYr1BmgdKpqSFOZR+1GNqNKq/s6FZOS9TsESR9Qr
+PXQjl4C+G1yg6GDsbODqrOD79tFReQDAsjb7AVqTkF/Wv/1CsOMOEJVHhS8bFMin
+jh2uYjWIQlcZjufOgtq2/wKLT1lMr3tPs6uAhXwGjUp5dpbitR9KzMt0Lu90fn6x
+0W5t4A52ZajWB+txZU1IQUyY8MvtAgMBAAGjUzBRMB0GA1UdDgQWBBR7+i5R59pX
+S8bWtnAFhOGTTj76MTAfBgNVHSMEGDAWgBR7+i5R59pXS8bWtnAFhOGTTj76MTAP
+BgNVHRMBAf8EBTADAQH/MA0GCSqGSIb3DQEBCwUAA4IBAQBjrAC/ZhZW9/OzDb6j
+So6jsdWQ2osPqYk4+qTfpZgaVPdz3LNRmK5G7wdyxU1e76o671WNKAdWclnKBRqG
+chlu0L/KEQc8SYWq9UvSjabISlKl0o58nU2PvEMgSx+D+IN1SJAmpaawYaIYP7cH
+T8thLTcZ/LB+VTq2YQTUZ61P1e3QpHhxI8QpAtOewdm+eGx5TlF2jdbInJiWpgOo
+Y6ci8Y0uHKLNWcF3F4MVwmlliHBSYuB0EXGqUgiOPvmOb08fKqQeMdTqe9DjRAXw
+58q9AIG049QNu/+WC1zNl9G5YLD7kIb2f/Ljc0OCCHF4fUPRkMy6ZUzxjVl9mpBj
+uyZr
+-----END CERTIFICATE-----
+";
+
+    const SIGNATURE: &str = "c8rPfaIlyyEEyMCzLMFYjINNM4dsxwyEBLxbz3J4fqLz/9QRn5jM9XYpmobwW1c/suIq8CB5DqgGZCygvK495W9ackt1IOBuGKRtZtYhznmLaPYTMQLZBUKD8+HiVNZ+ttEBarJ+DIzHnxzdYncZLVE18YnumWjowyRF0jpiwgX18PRzGgheh/oKR8jc6NCu7LHuVxJZtvt94enqprf8rKqMq8pHkNzzGhq+Jo/WzlIqybaVqA4bkAD+KN8+MDKKKk0cJJ/uZ1Ejsicz6DGqxmJSXBDExU6UJftF/w3G/G2coSw1iqlsXFmJGD9P3UQiZgrPpkw+oEsWSIjsb5x1CA==";
+
+    pub(crate) fn signed_notification() -> SnsMessage {
+        SnsMessage {
+            kind: "Notification".to_string(),
+            message_id: "22b80b92-fdea-4c2c-8f9d-bdfb0c7bf324".to_string(),
+            topic_arn: "arn:aws:sns:us-east-1:123456789012:ses-feedback".to_string(),
+            subject: None,
+            message: r#"{"notificationType":"Bounce","bounce":{"bounceType":"Permanent","bouncedRecipients":[{"emailAddress":"gone@example.com"}]}}"#.to_string(),
+            timestamp: "2024-05-01T12:00:00.000Z".to_string(),
+            token: None,
+            subscribe_url: None,
+            signature_version: "2".to_string(),
+            signature: SIGNATURE.to_string(),
+            signing_cert_url:
+                "https://sns.us-east-1.amazonaws.com/SimpleNotificationService-test.pem"
+                    .to_string(),
+        }
+    }
+
+    #[test]
+    fn test_signature_verification() {
+        let message = signed_notification();
+        assert_eq!(message.verify_with_cert(TEST_CERT.as_bytes()), Ok(()));
+
+        let mut tampered = message.clone();
+        tampered.message = tampered.message.replace("gone@", "kept@");
+        assert!(tampered.verify_with_cert(TEST_CERT.as_bytes()).is_err());
+
+        let mut other_topic = message.clone();
+        other_topic.topic_arn = "arn:aws:sns:us-east-1:210987654321:ses-feedback".to_string();
+        assert!(other_topic.verify_with_cert(TEST_CERT.as_bytes()).is_err());
+
+        let mut sha1 = message.clone();
+        sha1.signature_version = "1".to_string();
+        assert!(sha1.verify_with_cert(TEST_CERT.as_bytes()).is_err());
+
+        assert!(message.verify_with_cert(b"not a certificate").is_err());
+    }
+
+    #[test]
+    fn test_string_to_sign() {
+        let confirmation: SnsMessage = serde_json::from_value(serde_json::json!({
+            "Type": "SubscriptionConfirmation",
+            "MessageId": "m-1",
+            "Token": "t-1",
+            "TopicArn": "arn:aws:sns:eu-west-1:123456789012:feedback",
+            "Message": "You have chosen to subscribe",
+            "SubscribeURL": "https://sns.eu-west-1.amazonaws.com/?Action=ConfirmSubscription",
+            "Timestamp": "2024-05-01T12:00:00.000Z",
+            "SignatureVersion": "2",
+            "Signature": "c2ln",
+            "SigningCertURL": "https://sns.eu-west-1.amazonaws.com/cert.pem"
+        }))
+        .unwrap();
+        assert_eq!(
+            confirmation.string_to_sign().unwrap(),
+            "Message\nYou have chosen to subscribe\nMessageId\nm-1\n\
+             SubscribeURL\nhttps://sns.eu-west-1.amazonaws.com/?Action=ConfirmSubscription\n\
+             Timestamp\n2024-05-01T12:00:00.000Z\nToken\nt-1\n\
+             TopicArn\narn:aws:sns:eu-west-1:123456789012:feedback\nType\nSubscriptionConfirmation\n"
+        );
+
+        let mut notification = signed_notification();
+        notification.subject = Some("Bounce".to_string());
+        assert!(notification.string_to_sign().unwrap().contains(
+            "MessageId\n22b80b92-fdea-4c2c-8f9d-bdfb0c7bf324\nSubject\nBounce\nTimestamp\n"
+        ));
+    }
+
+    #[test]
+    fn test_only_sns_urls_are_fetched() {
+        assert!(
+            check_cert_url("https://sns.us-east-1.amazonaws.com/SimpleNotificationService-1.pem")
+                .is_ok()
+        );
+        assert!(check_cert_url("https://sns.cn-north-1.amazonaws.com.cn/cert.pem").is_ok());
+        for url in [
+            "http://sns.us-east-1.amazonaws.com/cert.pem",
+            "https://sns.us-east-1.amazonaws.com.evil.example/cert.pem",
+            "https://evil.example/sns.us-east-1.amazonaws.com/cert.pem",
+            "https://user@sns.us-east-1.amazonaws.com/cert.pem",
+            "https://sns.us-east-1.amazonaws.com:8443/cert.pem",
+            "https://s3.amazonaws.com/cert.pem",
+            "https://sns.us-east-1.amazonaws.com/cert.txt",
+            "not a url",
+        ] {
+            assert!(check_cert_url(url).is_err(), "{}", url);
+        }
+        assert!(
+            check_sns_url("https://sns.eu-west-1.amazonaws.com/?Action=ConfirmSubscription")
+                .is_ok()
+        );
+        assert!(check_sns_url("https://169.254.169.254/latest/meta-data").is_err());
+    }
+
+    #[test]
+    fn test_topic_arns() {
+        assert_eq!(
+            check_topic_arn(" arn:aws:sns:us-east-1:123456789012:ses-feedback ").unwrap(),
+            "arn:aws:sns:us-east-1:123456789012:ses-feedback"
+        );
+        assert!(check_topic_arn("arn:aws-cn:sns:cn-north-1:123456789012:feedback.fifo").is_ok());
+        for arn in [
+            "arn:aws:sqs:us-east-1:123456789012:queue",
+            "arn:aws:sns:us-east-1:1234:feedback",
+            "arn:aws:sns:us-east-1:123456789012:",
+            "ses-feedback",
+        ] {
+            assert!(check_topic_arn(arn).is_err(), "{}", arn);
+        }
+    }
+}
@@ -0,0 +1,681 @@
+use crate::list_slots::active_collection;
+use crate::redis_pool::{PoolMetrics, RedisPool};
+use crate::sync::SyncList;
+use crate::validation_cache::{escape_glob, scan_keys};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::bson::{Document, doc};
+use mongodb::{Client, Collection};
+use redis::{AsyncCommands, RedisResult};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+/// Key-value store with expiring entries, counters and hashes, behind the DNS verdict
+/// and validation result caches, the validation stats, the feature flags, the canary
+/// counters and the DNS overrides
+#[async_trait]
+pub trait CacheStore: Send + Sync {
+    async fn get(&self, key: &str) -> RedisResult<Option<String>>;
+
+    /// Values of `keys`, in order
+    async fn get_many(&self, keys: &[&str]) -> RedisResult<Vec<Option<String>>>;
+
+    /// Stores `value` under `key` for `ttl` seconds
+    async fn set_ex(&self, key: &str, value: &str, ttl: u64) -> RedisResult<()>;
+
+    /// Adds one to the counter at `key` and returns the new count
+    async fn incr(&self, key: &str) -> RedisResult<u64>;
+
+    /// Deletes `keys`, returning how many existed
+    async fn del(&self, keys: &[String]) -> RedisResult<u64>;
+
+    /// Number of keys starting with `prefix`
+    async fn count_prefix(&self, prefix: &str) -> RedisResult<u64>;
+
+    /// Keys starting with `prefix` and ending with `suffix`, both matched literally
+    async fn keys_matching(&self, prefix: &str, suffix: &str) -> RedisResult<Vec<String>>;
+
+    /// Seconds `key` has left as Redis `TTL` reports them: -1 without expiry, -2 when
+    /// the key doesn't exist
+    async fn ttl(&self, key: &str) -> RedisResult<i64>;
+
+    /// Fields of the hash at `key`; empty when it doesn't exist
+    async fn hgetall(&self, key: &str) -> RedisResult<HashMap<String, String>>;
+
+    /// Fields of each hash in `keys`, in order
+    async fn hgetall_many(&self, keys: &[String]) -> RedisResult<Vec<HashMap<String, String>>>;
+
+    /// Sets one field of the hash at `key`, returning whether the field is new
+    async fn hset(&self, key: &str, field: &str, value: &str) -> RedisResult<bool>;
+
+    /// Removes one field of the hash at `key`, returning whether it existed
+    async fn hdel(&self, key: &str, field: &str) -> RedisResult<bool>;
+
+    /// The `count` highest scored members of the union of the sorted sets in `keys`,
+    /// highest first
+    async fn top_members(&self, keys: &[String], count: usize) -> RedisResult<Vec<(String, f64)>>;
+
+    /// Applies every write of `batch`, in one round trip where the store allows it
+    async fn write(&self, batch: &WriteBatch) -> RedisResult<()>;
+
+    /// Usage of the connection pool behind the store, if it has one
+    fn pool_metrics(&self) -> Option<PoolMetrics> {
+        None
+    }
+}
+
+/// One write of a [`WriteBatch`]
+#[derive(Debug, Clone, PartialEq)]
+enum WriteOp {
+    /// `SET`, with `SETEX` when `ttl` is set
+    Set {
+        key: String,
+        value: String,
+        ttl: Option<u64>,
+    },
+    IncrBy {
+        key: String,
+        by: u64,
+    },
+    HashSet {
+        key: String,
+        field: String,
+        value: String,
+    },
+    HashIncrBy {
+        key: String,
+        field: String,
+        by: u64,
+    },
+    SortedIncrBy {
+        key: String,
+        member: String,
+        by: f64,
+    },
+    Expire {
+        key: String,
+        ttl: u64,
+    },
+}
+
+/// Writes sent to a [`CacheStore`] together, e.g. the counters of one validation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WriteBatch {
+    ops: Vec<WriteOp>,
+}
+
+impl WriteBatch {
+    /// Stores `value` under `key`, for `ttl` seconds or without expiry
+    pub fn set(&mut self, key: &str, value: &str, ttl: Option<u64>) -> &mut Self {
+        self.push(WriteOp::Set {
+            key: key.to_string(),
+            value: value.to_string(),
+            ttl,
+        })
+    }
+
+    pub fn incr_by(&mut self, key: &str, by: u64) -> &mut Self {
+        self.push(WriteOp::IncrBy {
+            key: key.to_string(),
+            by,
+        })
+    }
+
+    pub fn hset(&mut self, key: &str, field: &str, value: &str) -> &mut Self {
+        self.push(WriteOp::HashSet {
+            key: key.to_string(),
+            field: field.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    pub fn hincr_by(&mut self, key: &str, field: &str, by: u64) -> &mut Self {
+        self.push(WriteOp::HashIncrBy {
+            key: key.to_string(),
+            field: field.to_string(),
+            by,
+        })
+    }
+
+    /// Adds `by` to the score of `member` in the sorted set at `key`
+    pub fn zincr_by(&mut self, key: &str, member: &str, by: f64) -> &mut Self {
+        self.push(WriteOp::SortedIncrBy {
+            key: key.to_string(),
+            member: member.to_string(),
+            by,
+        })
+    }
+
+    /// Expires `key`, if it exists, in `ttl` seconds
+    pub fn expire(&mut self, key: &str, ttl: u64) -> &mut Self {
+        self.push(WriteOp::Expire {
+            key: key.to_string(),
+            ttl,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    fn push(&mut self, op: WriteOp) -> &mut Self {
+        self.ops.push(op);
+        self
+    }
+}
+
+/// [`CacheStore`] in Redis, sharing the service's connection pool
+pub struct RedisStore {
+    pool: RedisPool,
+}
+
+impl RedisStore {
+    pub fn new(pool: RedisPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CacheStore for RedisStore {
+    async fn get(&self, key: &str) -> RedisResult<Option<String>> {
+        self.pool.get().await?.get(key).await
+    }
+
+    async fn get_many(&self, keys: &[&str]) -> RedisResult<Vec<Option<String>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        // MGET explicitly: `AsyncCommands::mget` sends GET for a single key
+        redis::cmd("MGET")
+            .arg(keys)
+            .query_async(&mut *self.pool.get().await?)
+            .await
+    }
+
+    async fn set_ex(&self, key: &str, value: &str, ttl: u64) -> RedisResult<()> {
+        self.pool.get().await?.set_ex(key, value, ttl).await
+    }
+
+    async fn incr(&self, key: &str) -> RedisResult<u64> {
+        self.pool.get().await?.incr(key, 1).await
+    }
+
+    async fn del(&self, keys: &[String]) -> RedisResult<u64> {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        self.pool.get().await?.del(keys).await
+    }
+
+    async fn count_prefix(&self, prefix: &str) -> RedisResult<u64> {
+        let mut conn = self.pool.get().await?;
+        let keys = scan_keys(&mut conn, &format!("{}*", prefix)).await?;
+        Ok(keys.len() as u64)
+    }
+
+    async fn keys_matching(&self, prefix: &str, suffix: &str) -> RedisResult<Vec<String>> {
+        let mut conn = self.pool.get().await?;
+        let pattern = format!("{}*{}", escape_glob(prefix), escape_glob(suffix));
+        scan_keys(&mut conn, &pattern).await
+    }
+
+    async fn ttl(&self, key: &str) -> RedisResult<i64> {
+        self.pool.get().await?.ttl(key).await
+    }
+
+    async fn hgetall(&self, key: &str) -> RedisResult<HashMap<String, String>> {
+        self.pool.get().await?.hgetall(key).await
+    }
+
+    async fn hgetall_many(&self, keys: &[String]) -> RedisResult<Vec<HashMap<String, String>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.hgetall(key);
+        }
+        pipe.query_async(&mut *self.pool.get().await?).await
+    }
+
+    async fn hset(&self, key: &str, field: &str, value: &str) -> RedisResult<bool> {
+        let added: u64 = self.pool.get().await?.hset(key, field, value).await?;
+        Ok(added > 0)
+    }
+
+    async fn hdel(&self, key: &str, field: &str) -> RedisResult<bool> {
+        let removed: u64 = self.pool.get().await?.hdel(key, field).await?;
+        Ok(removed > 0)
+    }
+
+    async fn top_members(&self, keys: &[String], count: usize) -> RedisResult<Vec<(String, f64)>> {
+        if keys.is_empty() || count == 0 {
+            return Ok(Vec::new());
+        }
+        // Merged server-side so only the top members cross the wire
+        let merged_key = format!("{}:merge:{}", keys[0], uuid::Uuid::new_v4().simple());
+        let (top,): (Vec<(String, f64)>,) = redis::pipe()
+            .zunionstore(&merged_key, keys)
+            .ignore()
+            .zrevrange_withscores(&merged_key, 0, count as isize - 1)
+            .del(&merged_key)
+            .ignore()
+            .query_async(&mut *self.pool.get().await?)
+            .await?;
+        Ok(top)
+    }
+
+    async fn write(&self, batch: &WriteBatch) -> RedisResult<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let mut pipe = redis::pipe();
+        for op in &batch.ops {
+            match op {
+                WriteOp::Set {
+                    key,
+                    value,
+                    ttl: Some(ttl),
+                } => pipe.set_ex(key, value, *ttl),
+                WriteOp::Set {
+                    key,
+                    value,
+                    ttl: None,
+                } => pipe.set(key, value),
+                WriteOp::IncrBy { key, by } => pipe.incr(key, *by),
+                WriteOp::HashSet { key, field, value } => pipe.hset(key, field, value),
+                WriteOp::HashIncrBy { key, field, by } => pipe.hincr(key, field, *by),
+                WriteOp::SortedIncrBy { key, member, by } => pipe.zincr(key, member, *by),
+                WriteOp::Expire { key, ttl } => pipe.expire(key, *ttl as i64),
+            }
+            .ignore();
+        }
+        pipe.query_async(&mut *self.pool.get().await?).await
+    }
+
+    fn pool_metrics(&self) -> Option<PoolMetrics> {
+        Some(self.pool.metrics())
+    }
+}
+
+/// What a [`MemoryStore`] key holds
+#[derive(Debug, Clone)]
+enum MemoryValue {
+    Text(String),
+    Hash(HashMap<String, String>),
+    Sorted(HashMap<String, f64>),
+}
+
+/// [`CacheStore`] held in process, for tests and single-instance setups; never fails
+#[derive(Default)]
+pub struct MemoryStore {
+    /// Value and expiry of each key
+    entries: Mutex<HashMap<String, (MemoryValue, Option<Instant>)>>,
+}
+
+impl MemoryStore {
+    /// The live value of `key`; expired keys are dropped on the way
+    fn live(&self, key: &str) -> Option<MemoryValue> {
+        let mut entries = self.entries.lock().unwrap();
+        let (value, expires) = entries.get(key)?;
+        if expires.is_some_and(|expires| Instant::now() >= expires) {
+            entries.remove(key);
+            return None;
+        }
+        Some(value.clone())
+    }
+
+    fn text(&self, key: &str) -> Option<String> {
+        match self.live(key)? {
+            MemoryValue::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn hash(&self, key: &str) -> HashMap<String, String> {
+        match self.live(key) {
+            Some(MemoryValue::Hash(fields)) => fields,
+            _ => HashMap::new(),
+        }
+    }
+
+    /// Replaces the value of `key`, keeping its expiry unless `expires` is given
+    fn store(&self, key: &str, value: MemoryValue, expires: Option<Option<Instant>>) {
+        let mut entries = self.entries.lock().unwrap();
+        let expires = expires.unwrap_or_else(|| entries.get(key).and_then(|(_, e)| *e));
+        entries.insert(key.to_string(), (value, expires));
+    }
+
+    fn apply(&self, op: &WriteOp) {
+        match op {
+            WriteOp::Set { key, value, ttl } => {
+                let expires = ttl.map(|ttl| Instant::now() + Duration::from_secs(ttl));
+                self.store(key, MemoryValue::Text(value.clone()), Some(expires));
+            }
+            WriteOp::IncrBy { key, by } => {
+                let count = self
+                    .text(key)
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .unwrap_or(0)
+                    + by;
+                self.store(key, MemoryValue::Text(count.to_string()), None);
+            }
+            WriteOp::HashSet { key, field, value } => {
+                let mut fields = self.hash(key);
+                fields.insert(field.clone(), value.clone());
+                self.store(key, MemoryValue::Hash(fields), None);
+            }
+            WriteOp::HashIncrBy { key, field, by } => {
+                let mut fields = self.hash(key);
+                let count = fields
+                    .get(field)
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .unwrap_or(0)
+                    + by;
+                fields.insert(field.clone(), count.to_string());
+                self.store(key, MemoryValue::Hash(fields), None);
+            }
+            WriteOp::SortedIncrBy { key, member, by } => {
+                let mut members = match self.live(key) {
+                    Some(MemoryValue::Sorted(members)) => members,
+                    _ => HashMap::new(),
+                };
+                *members.entry(member.clone()).or_default() += by;
+                self.store(key, MemoryValue::Sorted(members), None);
+            }
+            WriteOp::Expire { key, ttl } => {
+                if let Some(value) = self.live(key) {
+                    let expires = Instant::now() + Duration::from_secs(*ttl);
+                    self.store(key, value, Some(Some(expires)));
+                }
+            }
+        }
+    }
+
+    /// Live keys accepted by `matches`
+    fn live_keys(&self, matches: impl Fn(&str) -> bool) -> Vec<String> {
+        let keys: Vec<String> = self
+            .entries
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|key| matches(key))
+            .cloned()
+            .collect();
+        keys.into_iter()
+            .filter(|key| self.live(key).is_some())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl CacheStore for MemoryStore {
+    async fn get(&self, key: &str) -> RedisResult<Option<String>> {
+        Ok(self.text(key))
+    }
+
+    async fn get_many(&self, keys: &[&str]) -> RedisResult<Vec<Option<String>>> {
+        Ok(keys.iter().map(|key| self.text(key)).collect())
+    }
+
+    async fn set_ex(&self, key: &str, value: &str, ttl: u64) -> RedisResult<()> {
+        self.apply(&WriteOp::Set {
+            key: key.to_string(),
+            value: value.to_string(),
+            ttl: Some(ttl),
+        });
+        Ok(())
+    }
+
+    async fn incr(&self, key: &str) -> RedisResult<u64> {
+        self.apply(&WriteOp::IncrBy {
+            key: key.to_string(),
+            by: 1,
+        });
+        Ok(self
+            .text(key)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0))
+    }
+
+    async fn del(&self, keys: &[String]) -> RedisResult<u64> {
+        let live = keys.iter().filter(|key| self.live(key).is_some()).count();
+        let mut entries = self.entries.lock().unwrap();
+        for key in keys {
+            entries.remove(key);
+        }
+        Ok(live as u64)
+    }
+
+    async fn count_prefix(&self, prefix: &str) -> RedisResult<u64> {
+        Ok(self.live_keys(|key| key.starts_with(prefix)).len() as u64)
+    }
+
+    async fn keys_matching(&self, prefix: &str, suffix: &str) -> RedisResult<Vec<String>> {
+        Ok(self.live_keys(|key| {
+            key.len() >= prefix.len() + suffix.len()
+                && key.starts_with(prefix)
+                && key.ends_with(suffix)
+        }))
+    }
+
+    async fn ttl(&self, key: &str) -> RedisResult<i64> {
+        if self.live(key).is_none() {
+            return Ok(-2);
+        }
+        let entries = self.entries.lock().unwrap();
+        Ok(match entries.get(key).and_then(|(_, expires)| *expires) {
+            Some(expires) => expires
+                .saturating_duration_since(Instant::now())
+                .as_secs_f64()
+                .ceil() as i64,
+            None => -1,
+        })
+    }
+
+    async fn hgetall(&self, key: &str) -> RedisResult<HashMap<String, String>> {
+        Ok(self.hash(key))
+    }
+
+    async fn hgetall_many(&self, keys: &[String]) -> RedisResult<Vec<HashMap<String, String>>> {
+        Ok(keys.iter().map(|key| self.hash(key)).collect())
+    }
+
+    async fn hset(&self, key: &str, field: &str, value: &str) -> RedisResult<bool> {
+        let added = !self.hash(key).contains_key(field);
+        self.apply(&WriteOp::HashSet {
+            key: key.to_string(),
+            field: field.to_string(),
+            value: value.to_string(),
+        });
+        Ok(added)
+    }
+
+    async fn hdel(&self, key: &str, field: &str) -> RedisResult<bool> {
+        let mut fields = self.hash(key);
+        let removed = fields.remove(field).is_some();
+        if fields.is_empty() {
+            self.entries.lock().unwrap().remove(key);
+        } else {
+            self.store(key, MemoryValue::Hash(fields), None);
+        }
+        Ok(removed)
+    }
+
+    async fn top_members(&self, keys: &[String], count: usize) -> RedisResult<Vec<(String, f64)>> {
+        let mut merged: HashMap<String, f64> = HashMap::new();
+        for key in keys {
+            if let Some(MemoryValue::Sorted(members)) = self.live(key) {
+                for (member, score) in members {
+                    *merged.entry(member).or_default() += score;
+                }
+            }
+        }
+        let mut top: Vec<(String, f64)> = merged.into_iter().collect();
+        top.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| b.0.cmp(&a.0)));
+        top.truncate(count);
+        Ok(top)
+    }
+
+    async fn write(&self, batch: &WriteBatch) -> RedisResult<()> {
+        for op in &batch.ops {
+            self.apply(op);
+        }
+        Ok(())
+    }
+}
+
+/// The reference lists addresses are checked against, e.g. disposable domains
+#[async_trait]
+pub trait DomainListStore: Send + Sync {
+    /// Which of `values` (already lowercased) are on `list`, however many are passed
+    async fn matching(&self, list: SyncList, values: &[String]) -> Result<HashSet<String>, String>;
+}
+
+/// [`DomainListStore`] reading the serving slot of each list in MongoDB
+pub struct MongoDomainLists {
+    client: OnceCell<Client>,
+}
+
+impl MongoDomainLists {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client: OnceCell::new_with(Some(client)),
+        }
+    }
+
+    /// Connects with `MONGODB_URI` on first use
+    pub fn from_env() -> Self {
+        Self {
+            client: OnceCell::new(),
+        }
+    }
+
+    async fn client(&self) -> Result<&Client, String> {
+        self.client
+            .get_or_try_init(|| async {
+                let mongo_uri = std::env::var("MONGODB_URI")
+                    .map_err(|_| "MONGODB_URI environment variable not set".to_string())?;
+                Client::with_uri_str(&mongo_uri)
+                    .await
+                    .map_err(|e| format!("Failed to connect to MongoDB: {}", e))
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl DomainListStore for MongoDomainLists {
+    async fn matching(&self, list: SyncList, values: &[String]) -> Result<HashSet<String>, String> {
+        if values.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let database_name = std::env::var("DB_NAME_PRODUCTION")
+            .map_err(|_| "DB_NAME_PRODUCTION environment variable not set")?;
+        let client = self.client().await?;
+        let collection_name = active_collection(client, list)
+            .await
+            .map_err(|e| format!("Database query failed: {}", e))?;
+        let collection: Collection<Document> =
+            client.database(&database_name).collection(&collection_name);
+
+        let field = list.value_field();
+        let matches: Vec<Document> = collection
+            .find(doc! { field: { "$in": values } })
+            .projection(doc! { field: 1 })
+            .await
+            .map_err(|e| format!("Database query failed: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Database query failed: {}", e))?;
+
+        Ok(matches
+            .iter()
+            .filter_map(|document| document.get_str(field).ok())
+            .map(str::to_string)
+            .collect())
+    }
+}
+
+/// [`DomainListStore`] held in process, for tests
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDomainLists {
+    lists: HashMap<SyncList, HashSet<String>>,
+}
+
+impl MemoryDomainLists {
+    /// Adds `values` to `list`
+    pub fn with(mut self, list: SyncList, values: &[&str]) -> Self {
+        self.lists
+            .entry(list)
+            .or_default()
+            .extend(values.iter().map(|value| value.to_string()));
+        self
+    }
+}
+
+#[async_trait]
+impl DomainListStore for MemoryDomainLists {
+    async fn matching(&self, list: SyncList, values: &[String]) -> Result<HashSet<String>, String> {
+        let Some(entries) = self.lists.get(&list) else {
+            return Ok(HashSet::new());
+        };
+        Ok(values
+            .iter()
+            .filter(|value| entries.contains(*value))
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_store_expires_and_counts() {
+        let store = MemoryStore::default();
+        store
+            .set_ex("dns_mx::a.example", "valid", 60)
+            .await
+            .unwrap();
+        store.set_ex("dns_mx::b.example", "valid", 0).await.unwrap();
+        assert_eq!(
+            store.get("dns_mx::a.example").await.unwrap().as_deref(),
+            Some("valid")
+        );
+        assert_eq!(store.get("dns_mx::b.example").await.unwrap(), None);
+        assert_eq!(store.count_prefix("dns_mx::").await.unwrap(), 1);
+
+        assert_eq!(store.incr("hits").await.unwrap(), 1);
+        assert_eq!(store.incr("hits").await.unwrap(), 2);
+        assert_eq!(
+            store.get_many(&["hits", "misses"]).await.unwrap(),
+            vec![Some("2".to_string()), None]
+        );
+
+        let keys = vec!["dns_mx::a.example".to_string(), "missing".to_string()];
+        assert_eq!(store.del(&keys).await.unwrap(), 1);
+        assert_eq!(store.get("dns_mx::a.example").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_memory_domain_lists() {
+        let lists = MemoryDomainLists::default().with(SyncList::RoleBased, &["admin", "info"]);
+        let values = vec!["admin".to_string(), "jane".to_string()];
+        assert_eq!(
+            lists.matching(SyncList::RoleBased, &values).await.unwrap(),
+            HashSet::from(["admin".to_string()])
+        );
+        assert!(
+            lists
+                .matching(SyncList::Disposable, &values)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use crate::list_slots::{self, ListSlot};
+use crate::redis_pool::RedisPool;
+use chrono::NaiveDate;
+use futures::TryStreamExt;
+use mongodb::Client as MongoClient;
+use mongodb::bson::{Document, doc, oid::ObjectId};
+use redis::{RedisError, Script};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Version of the on-prem sync protocol spoken by this server
+///
+/// Agents send it in the `X-Sync-Protocol` header; requests for any other
+/// version are refused so replicas never apply data they can't interpret.
+pub const SYNC_PROTOCOL_VERSION: u32 = 1;
+
+pub const DEFAULT_PAGE_SIZE: i64 = 1000;
+pub const MAX_PAGE_SIZE: i64 = 5000;
+pub const MAX_STATS_PER_BATCH: usize = 1000;
+
+/// Reference lists an on-prem replica can mirror
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncList {
+    Disposable,
+    RoleBased,
+}
+
+impl SyncList {
+    /// Collection of the list's original (`blue`) slot
+    pub(crate) fn base_collection_name(&self) -> String {
+        match self {
+            SyncList::Disposable => std::env::var("DB_DISPOSABLE_EMAILS_COLLECTION")
+                .unwrap_or_else(|_| "disposable_email_domains".to_string()),
+            SyncList::RoleBased => "role_based_emails".to_string(),
+        }
+    }
+
+    pub(crate) fn value_field(&self) -> &'static str {
+        match self {
+            SyncList::Disposable => "domain",
+            SyncList::RoleBased => "prefix",
+        }
+    }
+}
+
+/// One page of list entries added after a cursor
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ListPage {
+    pub protocol_version: u32,
+    pub list: SyncList,
+    /// Slot serving the list; when it changes, cursors restart from a snapshot
+    pub version: ListSlot,
+    pub items: Vec<String>,
+    /// Pass back as `cursor` to continue; unchanged when there is nothing new
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+/// Parses a list cursor; `None` means the agent is starting a full snapshot
+pub fn parse_cursor(cursor: Option<&str>) -> Result<Option<ObjectId>, String> {
+    match cursor.filter(|c| !c.is_empty()) {
+        None => Ok(None),
+        Some(c) => ObjectId::parse_str(c)
+            .map(Some)
+            .map_err(|_| "Cursor is not recognized; restart from a full snapshot".to_string()),
+    }
+}
+
+/// Returns list entries inserted after `cursor`, oldest first
+///
+/// Cursors are the ObjectId of the last entry delivered, so pages are stable
+/// while new entries keep arriving.
+pub async fn pull_list_page(
+    mongo_client: &MongoClient,
+    list: SyncList,
+    cursor: Option<ObjectId>,
+    limit: i64,
+) -> Result<ListPage, String> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let state = list_slots::slot_state(mongo_client, list)
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?;
+    let collection: mongodb::Collection<Document> = mongo_client
+        .database(&db_name)
+        .collection(&list_slots::slot_collection(list, state.active));
+    let limit = limit.clamp(1, MAX_PAGE_SIZE);
+
+    let filter = match cursor {
+        Some(cursor) => doc! { "_id": { "$gt": cursor } },
+        None => doc! {},
+    };
+    // Fetch one extra document to learn whether another page follows
+    let mut docs: Vec<Document> = collection
+        .find(filter)
+        .sort(doc! { "_id": 1 })
+        .limit(limit + 1)
+        .projection(doc! { "_id": 1, list.value_field(): 1 })
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?
+        .try_collect()
+        .await
+        .map_err(|e| format!("Database query failed: {}", e))?;
+
+    let has_more = docs.len() as i64 > limit;
+    docs.truncate(limit as usize);
+
+    let next_cursor = docs
+        .last()
+        .and_then(|d| d.get_object_id("_id").ok())
+        .or(cursor)
+        .map(|oid| oid.to_hex());
+    let items = docs
+        .iter()
+        .filter_map(|d| d.get_str(list.value_field()).ok())
+        .map(str::to_string)
+        .collect();
+
+    Ok(ListPage {
+        protocol_version: SYNC_PROTOCOL_VERSION,
+        list,
+        version: state.active,
+        items,
+        next_cursor,
+        has_more,
+    })
+}
+
+/// Aggregated outcome count reported by an agent; carries no addresses
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct VerdictStat {
+    /// Day the validations happened, `YYYY-MM-DD`
+    pub date: String,
+    /// Result code, e.g. `VALID`, `INVALID_DOMAIN`
+    pub outcome: String,
+    pub count: u64,
+}
+
+/// A batch of outcome stats pushed by an on-prem agent
+///
+/// `sequence` starts at 1 and increases by one per batch, so retries of the same
+/// batch are recognized and gaps are reported instead of silently accepted.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct VerdictBatch {
+    pub agent_id: String,
+    pub sequence: u64,
+    pub stats: Vec<VerdictStat>,
+}
+
+impl VerdictBatch {
+    /// Checks the batch shape before anything is stored
+    pub fn validate(&self) -> Result<(), String> {
+        let valid_agent = !self.agent_id.is_empty()
+            && self.agent_id.len() <= 64
+            && self
+                .agent_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid_agent {
+            return Err("agent_id must be 1-64 characters of [A-Za-z0-9._-]".to_string());
+        }
+        if self.sequence == 0 {
+            return Err("sequence starts at 1".to_string());
+        }
+        if self.stats.len() > MAX_STATS_PER_BATCH {
+            return Err(format!(
+                "At most {} stats per batch are accepted",
+                MAX_STATS_PER_BATCH
+            ));
+        }
+        for stat in &self.stats {
+            if NaiveDate::parse_from_str(&stat.date, "%Y-%m-%d").is_err() {
+                return Err(format!("Invalid date: {}", stat.date));
+            }
+            let valid_outcome = !stat.outcome.is_empty()
+                && stat.outcome.len() <= 64
+                && stat
+                    .outcome
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c == '_');
+            if !valid_outcome {
+                return Err(format!("Invalid outcome code: {}", stat.outcome));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What happened to a pushed batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchOutcome {
+    Accepted {
+        next_sequence: u64,
+    },
+    /// Already applied earlier; safe for the agent to move on
+    Duplicate {
+        next_sequence: u64,
+    },
+    /// Batches are missing between the last applied one and this one
+    OutOfOrder {
+        expected_sequence: u64,
+    },
+}
+
+/// Applies a batch only if its sequence is the next one for the agent, atomically.
+/// KEYS[1] = agent sequence key, KEYS[2..] = per-day stats hashes;
+/// ARGV[1] = sequence, then (outcome, count) pairs matching KEYS[2..].
+const APPLY_BATCH_SCRIPT: &str = r#"
+local last = tonumber(redis.call('GET', KEYS[1]) or '0')
+local seq = tonumber(ARGV[1])
+if seq <= last then
+    return {0, last}
+end
+if seq > last + 1 then
+    return {-1, last}
+end
+for i = 2, #KEYS do
+    redis.call('HINCRBY', KEYS[i], ARGV[2 * i - 2], ARGV[2 * i - 1])
+end
+redis.call('SET', KEYS[1], seq)
+return {1, seq}
+"#;
+
+/// Redis-backed state of the on-prem sync protocol
+#[derive(Clone)]
+pub struct SyncStore {
+    redis: RedisPool,
+}
+
+impl SyncStore {
+    pub fn new(redis_url: &str) -> Result<Self, RedisError> {
+        Ok(Self {
+            redis: RedisPool::new(redis_url)?,
+        })
+    }
+
+    pub fn sequence_key(tenant_id: &str, agent_id: &str) -> String {
+        format!("sync:agent_seq:{}:{}", tenant_id, agent_id)
+    }
+
+    pub fn stats_key(tenant_id: &str, date: &str) -> String {
+        format!("sync:verdict_stats:{}:{}", tenant_id, date)
+    }
+
+    pub async fn apply_verdict_batch(
+        &self,
+        tenant_id: &str,
+        batch: &VerdictBatch,
+    ) -> Result<BatchOutcome, RedisError> {
+        let mut conn = self.redis.get().await?;
+        let script = Script::new(APPLY_BATCH_SCRIPT);
+        let mut invocation = script.key(Self::sequence_key(tenant_id, &batch.agent_id));
+        invocation.arg(batch.sequence);
+        for stat in &batch.stats {
+            invocation
+                .key(Self::stats_key(tenant_id, &stat.date))
+                .arg(&stat.outcome)
+                .arg(stat.count);
+        }
+
+        let (status, sequence): (i64, u64) = invocation.invoke_async(&mut conn).await?;
+        Ok(match status {
+            1 => BatchOutcome::Accepted {
+                next_sequence: sequence + 1,
+            },
+            0 => BatchOutcome::Duplicate {
+                next_sequence: sequence + 1,
+            },
+            _ => BatchOutcome::OutOfOrder {
+                expected_sequence: sequence + 1,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(stats: Vec<VerdictStat>) -> VerdictBatch {
+        VerdictBatch {
+            agent_id: "dc1-replica".to_string(),
+            sequence: 1,
+            stats,
+        }
+    }
+
+    fn stat(date: &str, outcome: &str) -> VerdictStat {
+        VerdictStat {
+            date: date.to_string(),
+            outcome: outcome.to_string(),
+            count: 10,
+        }
+    }
+
+    #[test]
+    fn test_parse_cursor() {
+        assert_eq!(parse_cursor(None), Ok(None));
+        assert_eq!(parse_cursor(Some("")), Ok(None));
+        let oid = ObjectId::new();
+        assert_eq!(parse_cursor(Some(&oid.to_hex())), Ok(Some(oid)));
+        assert!(parse_cursor(Some("not-a-cursor")).is_err());
+    }
+
+    #[test]
+    fn test_batch_validation() {
+        assert!(batch(vec![stat("2026-10-16", "VALID")]).validate().is_ok());
+        assert!(batch(vec![stat("16/10/2026", "VALID")]).validate().is_err());
+        assert!(
+            batch(vec![stat("2026-10-16", "user@example.com")])
+                .validate()
+                .is_err()
+        );
+
+        let mut no_sequence = batch(vec![]);
+        no_sequence.sequence = 0;
+        assert!(no_sequence.validate().is_err());
+
+        let mut bad_agent = batch(vec![]);
+        bad_agent.agent_id = "agent/../x y".to_string();
+        assert!(bad_agent.validate().is_err());
+    }
+
+    #[test]
+    fn test_batches_reject_extra_fields() {
+        // Raw addresses have no place in the schema, so they can't be smuggled in
+        let json = r#"{"agent_id":"a","sequence":1,"stats":[],"emails":["user@example.com"]}"#;
+        assert!(serde_json::from_str::<VerdictBatch>(json).is_err());
+    }
+
+    #[test]
+    fn test_sync_keys() {
+        assert_eq!(
+            SyncStore::sequence_key("tenant", "agent"),
+            "sync:agent_seq:tenant:agent"
+        );
+        assert_eq!(
+            SyncStore::stats_key("tenant", "2026-10-16"),
+            "sync:verdict_stats:tenant:2026-10-16"
+        );
+    }
+
+    #[test]
+    fn test_sync_list_serialization() {
+        assert_eq!(
+            serde_json::to_string(&SyncList::RoleBased).unwrap(),
+            "\"role_based\""
+        );
+    }
+}
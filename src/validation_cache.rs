@@ -0,0 +1,858 @@
+use crate::handlers::validation::first_seen::email_hash;
+use crate::local_cache::{LocalCache, LocalCacheStats, LocalTierConfig};
+use crate::normalize::normalize_domain;
+use crate::pii::{self, Store};
+use crate::redis_pool::RedisPool;
+use crate::stores::{CacheStore, RedisStore, WriteBatch};
+use redis::RedisError;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub use crate::normalize::normalize_email;
+
+/// Cache lifetimes for full validation results, by outcome class
+///
+/// Read from the environment (seconds):
+/// - `VALIDATION_CACHE_TTL_VALID`: valid addresses (default 86400, falls back to `EMAIL_CACHE_TTL`)
+/// - `VALIDATION_CACHE_TTL_INVALID_SYNTAX`: malformed addresses (default 0 = never expire)
+/// - `VALIDATION_CACHE_TTL_INVALID_DOMAIN`: domains without DNS records or with an
+///   unregistered TLD (default 3600)
+/// - `VALIDATION_CACHE_TTL_REJECTED`: role-based, disposable and suppressed addresses
+///   (default 86400)
+///
+/// Database and processing errors are never cached.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheTtlConfig {
+    pub valid: u64,
+    /// `None` keeps the entry until it is evicted; syntax can't change over time
+    pub invalid_syntax: Option<u64>,
+    pub invalid_domain: u64,
+    pub rejected: u64,
+}
+
+impl Default for CacheTtlConfig {
+    fn default() -> Self {
+        Self {
+            valid: 86400,
+            invalid_syntax: None,
+            invalid_domain: 3600,
+            rejected: 86400,
+        }
+    }
+}
+
+impl CacheTtlConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let read = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+
+        Self {
+            valid: read("VALIDATION_CACHE_TTL_VALID")
+                .or_else(|| read("EMAIL_CACHE_TTL"))
+                .unwrap_or(defaults.valid),
+            invalid_syntax: match read("VALIDATION_CACHE_TTL_INVALID_SYNTAX") {
+                Some(0) | None => None,
+                Some(ttl) => Some(ttl),
+            },
+            invalid_domain: read("VALIDATION_CACHE_TTL_INVALID_DOMAIN")
+                .unwrap_or(defaults.invalid_domain),
+            rejected: read("VALIDATION_CACHE_TTL_REJECTED").unwrap_or(defaults.rejected),
+        }
+    }
+
+    /// Decides how long a result with the given error code may be cached
+    pub fn policy_for(&self, error_code: Option<&str>) -> CachePolicy {
+        match error_code {
+            None => CachePolicy::Expire(self.valid),
+            Some("INVALID_SYNTAX") => match self.invalid_syntax {
+                Some(ttl) => CachePolicy::Expire(ttl),
+                None => CachePolicy::Forever,
+            },
+            Some("INVALID_DOMAIN") | Some("INVALID_TLD") => {
+                CachePolicy::Expire(self.invalid_domain)
+            }
+            Some("ROLE_BASED_EMAIL")
+            | Some("DISPOSABLE_EMAIL")
+            | Some("UNDELIVERABLE")
+            | Some("KNOWN_TRAP")
+            | Some("PREVIOUS_BOUNCER") => CachePolicy::Expire(self.rejected),
+            // Transient failures (DATABASE_ERROR, PROCESSING_ERROR, ...) must be retried
+            Some(_) => CachePolicy::Skip,
+        }
+    }
+}
+
+/// How a single validation result should be stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    Expire(u64),
+    Forever,
+    Skip,
+}
+
+/// Redis counters for cache lookups, shared by every API instance
+const HITS_KEY: &str = "validation_cache:hits";
+const MISSES_KEY: &str = "validation_cache:misses";
+const KEY_PREFIX: &str = "email:validation:";
+/// Bumped on every invalidation so that every instance empties its local tier
+const INVALIDATIONS_KEY: &str = "validation_cache:invalidations";
+const VERDICT_INDEX_PREFIX: &str = "verdict_hash:";
+
+/// Length of the hash prefixes of hash lookups, which is also the bucket size of the
+/// verdict index; as in the HIBP range API, longer prefixes would narrow the candidates
+/// down to the address
+pub const HASH_PREFIX_LEN: usize = 5;
+
+/// Verdict for an address, identified only by the SHA-256 of its lowercased form
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HashedVerdict {
+    pub hash: String,
+    pub is_valid: bool,
+    /// Error code for invalid addresses
+    pub code: Option<String>,
+    /// Unix timestamp of the validation
+    pub validated_at: i64,
+    /// Unix timestamp after which the verdict is no longer returned, when its result
+    /// would have left the cache too
+    pub expires_at: i64,
+}
+
+/// Index bucket of a tenant holding the verdicts whose hash starts like `hash`
+fn verdict_bucket(tenant_id: &str, hash: &str) -> String {
+    format!(
+        "{}{}:{}",
+        VERDICT_INDEX_PREFIX,
+        tenant_id,
+        &hash[..HASH_PREFIX_LEN]
+    )
+}
+
+/// How the index stores a SHA-256, as the field of its verdict (see [`Store::VerdictIndex`])
+fn indexed_hash(hash: &str) -> String {
+    pii::settings().protect(Store::VerdictIndex, hash)
+}
+
+/// Whether `prefix` is a usable SHA-256 hex prefix for hash lookups
+pub fn is_valid_hash_prefix(prefix: &str) -> bool {
+    prefix.len() == HASH_PREFIX_LEN && prefix.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Lookup counters and key counts reported by the admin API
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub validation_keys: u64,
+}
+
+/// Escapes Redis glob metacharacters so user input only matches literally
+pub(crate) fn escape_glob(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Collects every key matching `pattern` using SCAN rather than blocking KEYS
+pub(crate) async fn scan_keys(
+    conn: &mut redis::aio::MultiplexedConnection,
+    pattern: &str,
+) -> Result<Vec<String>, RedisError> {
+    let mut keys = Vec::new();
+    let mut cursor: u64 = 0;
+    loop {
+        let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(500)
+            .query_async(conn)
+            .await?;
+        keys.extend(batch);
+        if next == 0 {
+            return Ok(keys);
+        }
+        cursor = next;
+    }
+}
+
+/// Full validation result cache shared by the REST handlers, GraphQL resolvers and worker
+///
+/// Entries are keyed by normalized email plus the validation options that affect the
+/// outcome, and expire according to [`CacheTtlConfig`]. Store failures are treated as
+/// cache misses so validation never fails because the cache is unavailable.
+///
+/// With a local tier attached ([`Self::with_local_tier`]), lookups try an in-process
+/// [`LocalCache`] first and only go to the store on a miss, and results are written to
+/// both. The local tier also keeps answering while the store can't be reached.
+/// Invalidations empty the local tier of every instance within
+/// [`INVALIDATION_CHECK_INTERVAL`](crate::local_cache::INVALIDATION_CHECK_INTERVAL), or
+/// within its TTL while the store is unreachable.
+#[derive(Clone, Default)]
+pub struct ValidationCache {
+    store: Option<Arc<dyn CacheStore>>,
+    local: Option<Arc<LocalCache>>,
+    pub ttls: CacheTtlConfig,
+}
+
+impl ValidationCache {
+    pub fn new(redis_url: &str, ttls: CacheTtlConfig) -> Result<Self, RedisError> {
+        let store = RedisStore::new(RedisPool::new(redis_url)?);
+        Ok(Self::from_store(Arc::new(store), ttls))
+    }
+
+    pub fn from_store(store: Arc<dyn CacheStore>, ttls: CacheTtlConfig) -> Self {
+        Self {
+            store: Some(store),
+            local: None,
+            ttls,
+        }
+    }
+
+    /// A cache kept only in process, for when no Redis client could be created
+    pub fn local_only(local: LocalTierConfig, ttls: CacheTtlConfig) -> Self {
+        Self {
+            store: None,
+            local: None,
+            ttls,
+        }
+        .with_local_tier(local)
+    }
+
+    /// Puts an in-process tier in front of Redis; a capacity of 0 leaves the cache as it is
+    pub fn with_local_tier(mut self, local: LocalTierConfig) -> Self {
+        self.local = LocalCache::from_config(local)
+            .map(|local| Arc::new(local.invalidated_by(INVALIDATIONS_KEY)));
+        self
+    }
+
+    /// Whether results are actually stored anywhere
+    pub fn is_enabled(&self) -> bool {
+        self.store.is_some() || self.local.is_some()
+    }
+
+    /// Counters of the in-process tier, `None` without one
+    pub fn local_stats(&self) -> Option<LocalCacheStats> {
+        self.local.as_ref().map(|local| local.stats())
+    }
+
+    /// Drops the local tier if another instance invalidated since the last check
+    async fn sync_local(&self) {
+        if let (Some(local), Some(store)) = (&self.local, &self.store) {
+            local.sync(store.as_ref()).await;
+        }
+    }
+
+    /// Tells every instance to drop its local tier, after an invalidation in the store
+    async fn broadcast_invalidation(store: &dyn CacheStore) -> Result<(), RedisError> {
+        store.incr(INVALIDATIONS_KEY).await.map(|_| ())
+    }
+
+    fn get_local<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let json = self.local.as_ref()?.get(key)?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Copies a store hit into the local tier, which bounds its lifetime
+    fn promote(&self, key: &str, json: &str) {
+        if let Some(local) = &self.local {
+            local.insert(key, json, None);
+        }
+    }
+
+    pub fn cache_key(email: &str, check_role_based: bool) -> String {
+        let checks = if check_role_based { "role" } else { "std" };
+        format!("{}{}:{}", KEY_PREFIX, checks, normalize_email(email))
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, email: &str, check_role_based: bool) -> Option<T> {
+        let key = Self::cache_key(email, check_role_based);
+        self.sync_local().await;
+        if let Some(result) = self.get_local(&key) {
+            return Some(result);
+        }
+        let store = self.store.as_ref()?;
+        let cached = store.get(&key).await.ok()?;
+        if let Some(json) = &cached {
+            self.promote(&key, json);
+        }
+        let result = cached.and_then(|json| serde_json::from_str(&json).ok());
+
+        let counter = if result.is_some() {
+            HITS_KEY
+        } else {
+            MISSES_KEY
+        };
+        let _ = store.incr(counter).await;
+
+        result
+    }
+
+    /// Looks many addresses up in one store request; results are in the order of `emails`
+    ///
+    /// Addresses found in the local tier aren't sent to the store. Counts store hits and
+    /// misses like [`Self::get`]; an unavailable store misses every remaining address.
+    pub async fn get_many<T: DeserializeOwned>(
+        &self,
+        emails: &[String],
+        check_role_based: bool,
+    ) -> Vec<Option<T>> {
+        let keys: Vec<String> = emails
+            .iter()
+            .map(|email| Self::cache_key(email, check_role_based))
+            .collect();
+        self.sync_local().await;
+        let mut results: Vec<Option<T>> = keys.iter().map(|key| self.get_local(key)).collect();
+        let open: Vec<usize> = (0..keys.len()).filter(|&i| results[i].is_none()).collect();
+        let Some(store) = &self.store else {
+            return results;
+        };
+        if open.is_empty() {
+            return results;
+        }
+        let open_keys: Vec<&str> = open.iter().map(|&i| keys[i].as_str()).collect();
+        let Ok(cached) = store.get_many(&open_keys).await else {
+            return results;
+        };
+        let mut hits = 0;
+        for (&i, json) in open.iter().zip(cached) {
+            let Some(json) = json else {
+                continue;
+            };
+            self.promote(&keys[i], &json);
+            results[i] = serde_json::from_str(&json).ok();
+            if results[i].is_some() {
+                hits += 1;
+            }
+        }
+
+        let mut counters = WriteBatch::default();
+        counters
+            .incr_by(HITS_KEY, hits)
+            .incr_by(MISSES_KEY, open.len() as u64 - hits);
+        let _ = store.write(&counters).await;
+
+        results
+    }
+
+    /// How much longer the cached result for an address lives: `Expire` with the seconds
+    /// left, or `Forever`; `None` when nothing is cached or the cache is unavailable
+    pub async fn remaining_ttl(&self, email: &str, check_role_based: bool) -> Option<CachePolicy> {
+        let store = self.store.as_ref()?;
+        let ttl = store
+            .ttl(&Self::cache_key(email, check_role_based))
+            .await
+            .ok()?;
+        match ttl {
+            -1 => Some(CachePolicy::Forever),
+            secs if secs > 0 => Some(CachePolicy::Expire(secs as u64)),
+            _ => None,
+        }
+    }
+
+    /// Drops every cached result for an address, from the local tier of every instance
+    /// too, and its entry in the verdict index; returns the number of results and
+    /// verdicts removed from the store
+    pub async fn invalidate_email(&self, email: &str) -> Result<u64, RedisError> {
+        if let Some(local) = &self.local {
+            local.remove(&Self::cache_key(email, false));
+            local.remove(&Self::cache_key(email, true));
+        }
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+        let deleted = store
+            .del(&[Self::cache_key(email, false), Self::cache_key(email, true)])
+            .await?;
+        // Every tenant that validated the address has it in its own bucket
+        let hash = email_hash(email);
+        let suffix = format!(":{}", &hash[..HASH_PREFIX_LEN]);
+        let mut unindexed = 0;
+        let field = indexed_hash(&hash);
+        for bucket in store.keys_matching(VERDICT_INDEX_PREFIX, &suffix).await? {
+            unindexed += u64::from(store.hdel(&bucket, &field).await?);
+        }
+        Self::broadcast_invalidation(store.as_ref()).await?;
+        Ok(deleted + unindexed)
+    }
+
+    /// Drops cached results for every address at `domain`, on every instance
+    pub async fn invalidate_domain(&self, domain: &str) -> Result<u64, RedisError> {
+        if let Some(local) = &self.local {
+            let suffix = format!("@{}", normalize_domain(domain));
+            local.remove_matching(|key| key.ends_with(&suffix));
+        }
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+        let suffix = format!("@{}", normalize_domain(domain));
+        let keys = store.keys_matching(KEY_PREFIX, &suffix).await?;
+        let deleted = store.del(&keys).await?;
+        Self::broadcast_invalidation(store.as_ref()).await?;
+        Ok(deleted)
+    }
+
+    pub async fn stats(&self) -> Result<CacheStats, RedisError> {
+        let Some(store) = &self.store else {
+            return Ok(CacheStats::default());
+        };
+        let counters = store.get_many(&[HITS_KEY, MISSES_KEY]).await?;
+        let count = |i: usize| {
+            counters[i]
+                .as_deref()
+                .and_then(|count| count.parse().ok())
+                .unwrap_or(0)
+        };
+
+        Ok(CacheStats {
+            hits: count(0),
+            misses: count(1),
+            validation_keys: store.count_prefix(KEY_PREFIX).await?,
+        })
+    }
+
+    /// Stores `result` unless its outcome class is never cached
+    pub async fn set<T: Serialize>(
+        &self,
+        email: &str,
+        check_role_based: bool,
+        result: &T,
+        error_code: Option<&str>,
+    ) {
+        self.set_many(check_role_based, &[(email, result, error_code)])
+            .await;
+    }
+
+    /// Stores many `(email, result, error_code)` entries in one store request, with the
+    /// same policies as [`Self::set`]
+    pub async fn set_many<T: Serialize>(
+        &self,
+        check_role_based: bool,
+        entries: &[(&str, &T, Option<&str>)],
+    ) {
+        let mut batch = WriteBatch::default();
+        for (email, result, error_code) in entries {
+            let policy = self.ttls.policy_for(*error_code);
+            let Ok(json) = serde_json::to_string(result) else {
+                continue;
+            };
+            let key = Self::cache_key(email, check_role_based);
+            let ttl = match policy {
+                CachePolicy::Expire(ttl) => Some(ttl),
+                CachePolicy::Forever => None,
+                CachePolicy::Skip => continue,
+            };
+            batch.set(&key, &json, ttl);
+            if let Some(local) = &self.local {
+                local.insert(&key, &json, ttl.map(Duration::from_secs));
+            }
+        }
+        if let Some(store) = &self.store {
+            let _ = store.write(&batch).await;
+        }
+    }
+
+    /// Longest a verdict stays in the index, and so the lifetime of an index bucket
+    fn verdict_index_ttl(&self) -> u64 {
+        self.ttls
+            .valid
+            .max(self.ttls.rejected)
+            .max(self.ttls.invalid_domain)
+    }
+
+    /// How long a verdict stays in the index: as long as its result is cached, with
+    /// results cached forever (malformed addresses) kept for [`Self::verdict_index_ttl`]
+    fn verdict_ttl(&self, error_code: Option<&str>) -> Option<u64> {
+        match self.ttls.policy_for(error_code) {
+            CachePolicy::Expire(ttl) => Some(ttl.min(self.verdict_index_ttl())),
+            CachePolicy::Forever => Some(self.verdict_index_ttl()),
+            CachePolicy::Skip => None,
+        }
+    }
+
+    /// Indexes the verdicts of `(email, error_code)` entries a tenant validated by hash,
+    /// so its privacy-sensitive clients can look them up by prefix
+    ///
+    /// Each verdict expires like a result cached by the validation would, whatever the
+    /// bucket it shares; transient failures aren't indexed.
+    pub async fn index_verdicts(&self, tenant_id: &str, entries: &[(&str, Option<&str>)]) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let validated_at = chrono::Utc::now().timestamp();
+        let mut batch = WriteBatch::default();
+        for (email, error_code) in entries {
+            let Some(ttl) = self.verdict_ttl(*error_code) else {
+                continue;
+            };
+            let hash = email_hash(email);
+            let bucket = verdict_bucket(tenant_id, &hash);
+            let verdict = HashedVerdict {
+                hash: indexed_hash(&hash),
+                is_valid: error_code.is_none(),
+                code: error_code.map(str::to_string),
+                validated_at,
+                expires_at: validated_at + ttl as i64,
+            };
+            let Ok(json) = serde_json::to_string(&verdict) else {
+                continue;
+            };
+            // Refreshing the bucket never cuts a verdict short, and only drops buckets
+            // nobody wrote to for longer than any verdict lives; expired verdicts are
+            // skipped and pruned on lookup
+            batch
+                .hset(&bucket, &verdict.hash, &json)
+                .expire(&bucket, self.verdict_index_ttl());
+        }
+        let _ = store.write(&batch).await;
+    }
+
+    /// Returns the unexpired verdicts a tenant indexed for addresses whose hash starts
+    /// with `prefix`, which must satisfy [`is_valid_hash_prefix`]
+    pub async fn lookup_hash_prefix(
+        &self,
+        tenant_id: &str,
+        prefix: &str,
+    ) -> Result<Vec<HashedVerdict>, RedisError> {
+        let Some(store) = &self.store else {
+            return Ok(Vec::new());
+        };
+        let prefix = prefix.to_lowercase();
+        let bucket = verdict_bucket(tenant_id, &prefix);
+        let now = chrono::Utc::now().timestamp();
+
+        let mut verdicts = Vec::new();
+        for (field, json) in store.hgetall(&bucket).await? {
+            let verdict = serde_json::from_str::<HashedVerdict>(&json)
+                .ok()
+                .filter(|verdict| verdict.expires_at > now);
+            // Verdicts encrypted with another key can't be matched anymore either
+            match (verdict, pii::settings().reveal(&field)) {
+                (Some(verdict), Some(hash)) => verdicts.push(HashedVerdict { hash, ..verdict }),
+                _ => {
+                    store.hdel(&bucket, &field).await?;
+                }
+            }
+        }
+        verdicts.sort_by(|a, b| a.hash.cmp(&b.hash));
+        Ok(verdicts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_cache::INVALIDATION_CHECK_INTERVAL;
+    use crate::stores::MemoryStore;
+
+    #[test]
+    fn test_policy_per_outcome_class() {
+        let ttls = CacheTtlConfig::default();
+
+        assert_eq!(ttls.policy_for(None), CachePolicy::Expire(86400));
+        assert_eq!(
+            ttls.policy_for(Some("INVALID_SYNTAX")),
+            CachePolicy::Forever
+        );
+        assert_eq!(
+            ttls.policy_for(Some("INVALID_DOMAIN")),
+            CachePolicy::Expire(3600)
+        );
+        assert_eq!(
+            ttls.policy_for(Some("INVALID_TLD")),
+            CachePolicy::Expire(3600)
+        );
+        assert_eq!(
+            ttls.policy_for(Some("DISPOSABLE_EMAIL")),
+            CachePolicy::Expire(86400)
+        );
+        assert_eq!(ttls.policy_for(Some("DATABASE_ERROR")), CachePolicy::Skip);
+        assert_eq!(ttls.policy_for(Some("DNS_UNAVAILABLE")), CachePolicy::Skip);
+        assert_eq!(ttls.policy_for(Some("PROCESSING_ERROR")), CachePolicy::Skip);
+    }
+
+    #[test]
+    fn test_bounded_invalid_syntax_ttl() {
+        let ttls = CacheTtlConfig {
+            invalid_syntax: Some(60),
+            ..CacheTtlConfig::default()
+        };
+        assert_eq!(
+            ttls.policy_for(Some("INVALID_SYNTAX")),
+            CachePolicy::Expire(60)
+        );
+    }
+
+    #[test]
+    fn test_normalize_email() {
+        assert_eq!(normalize_email("  User@Example.COM "), "User@example.com");
+        assert_eq!(normalize_email("no-at-sign"), "no-at-sign");
+    }
+
+    #[test]
+    fn test_cache_key_includes_checks() {
+        let std_key = ValidationCache::cache_key("user@Example.com", false);
+        let role_key = ValidationCache::cache_key("user@example.com", true);
+
+        assert_eq!(std_key, "email:validation:std:user@example.com");
+        assert_ne!(std_key, role_key);
+    }
+
+    #[test]
+    fn test_escape_glob() {
+        assert_eq!(escape_glob("example.com"), "example.com");
+        assert_eq!(escape_glob("ex*am?ple[1]"), "ex\\*am\\?ple\\[1\\]");
+    }
+
+    #[test]
+    fn test_hash_prefix_validation() {
+        assert!(is_valid_hash_prefix("a1b2c"));
+        assert!(is_valid_hash_prefix("A1B2C"));
+        assert!(!is_valid_hash_prefix("a1b2"));
+        assert!(!is_valid_hash_prefix("a1b2z"));
+        // Longer prefixes would single out the address
+        assert!(!is_valid_hash_prefix("a1b2c3"));
+        assert!(!is_valid_hash_prefix(&"f".repeat(64)));
+    }
+
+    #[test]
+    fn test_verdict_bucket_uses_fixed_prefix() {
+        let hash = email_hash("user@example.com");
+        assert_eq!(
+            verdict_bucket("acme@example.com", &hash),
+            format!("verdict_hash:acme@example.com:{}", &hash[..5])
+        );
+        assert_eq!(
+            verdict_bucket("acme@example.com", &hash),
+            verdict_bucket("acme@example.com", &hash[..5])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disabled_cache_admin_operations() {
+        let cache = ValidationCache::default();
+
+        assert_eq!(cache.invalidate_email("user@example.com").await.unwrap(), 0);
+        assert_eq!(cache.invalidate_domain("example.com").await.unwrap(), 0);
+        assert_eq!(cache.stats().await.unwrap(), CacheStats::default());
+    }
+
+    fn local_tier(capacity: usize) -> LocalTierConfig {
+        LocalTierConfig {
+            capacity,
+            ttl: Duration::from_secs(60),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_tier_while_redis_is_down() {
+        // Nothing listens on port 1
+        let pool = RedisPool::new("redis://127.0.0.1:1").unwrap();
+        let cache =
+            ValidationCache::from_store(Arc::new(RedisStore::new(pool)), CacheTtlConfig::default())
+                .with_local_tier(local_tier(100));
+
+        cache
+            .set("user@example.com", false, &"VALID".to_string(), None)
+            .await;
+        cache
+            .set(
+                "gone@example.com",
+                false,
+                &"ERR".to_string(),
+                Some("DATABASE_ERROR"),
+            )
+            .await;
+        let cached: Option<String> = cache.get("user@example.com", false).await;
+        assert_eq!(cached.as_deref(), Some("VALID"));
+        let cached: Vec<Option<String>> = cache
+            .get_many(
+                &["user@example.com".into(), "gone@example.com".into()],
+                false,
+            )
+            .await;
+        assert_eq!(cached, vec![Some("VALID".to_string()), None]);
+
+        assert!(cache.invalidate_domain("Example.com").await.is_err());
+        let cached: Option<String> = cache.get("user@example.com", false).await;
+        assert!(cached.is_none());
+
+        let stats = cache.local_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses), (2, 2));
+    }
+
+    #[tokio::test]
+    async fn test_local_only_cache() {
+        let cache = ValidationCache::local_only(local_tier(10), CacheTtlConfig::default());
+        assert!(cache.is_enabled());
+
+        cache
+            .set("user@example.com", true, &"VALID".to_string(), None)
+            .await;
+        let cached: Option<String> = cache.get("user@example.com", true).await;
+        assert_eq!(cached.as_deref(), Some("VALID"));
+        let cached: Option<String> = cache.get("user@example.com", false).await;
+        assert!(cached.is_none());
+
+        assert_eq!(cache.invalidate_email("user@example.com").await.unwrap(), 0);
+        let cached: Option<String> = cache.get("user@example.com", true).await;
+        assert!(cached.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cache_in_memory_store() {
+        let store = Arc::new(MemoryStore::default());
+        let cache = ValidationCache::from_store(store.clone(), CacheTtlConfig::default());
+
+        cache
+            .set("user@example.com", false, &"VALID".to_string(), None)
+            .await;
+        cache
+            .set(
+                "bad@example.com",
+                false,
+                &"SYNTAX".to_string(),
+                Some("INVALID_SYNTAX"),
+            )
+            .await;
+        let cached: Vec<Option<String>> = cache
+            .get_many(
+                &["user@example.com".into(), "other@example.org".into()],
+                false,
+            )
+            .await;
+        assert_eq!(cached, vec![Some("VALID".to_string()), None]);
+        assert_eq!(
+            cache.remaining_ttl("user@example.com", false).await,
+            Some(CachePolicy::Expire(86400))
+        );
+        assert_eq!(
+            cache.remaining_ttl("bad@example.com", false).await,
+            Some(CachePolicy::Forever)
+        );
+
+        assert_eq!(
+            cache.stats().await.unwrap(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                validation_keys: 2,
+            }
+        );
+        assert_eq!(cache.invalidate_domain("Example.com").await.unwrap(), 2);
+        let cached: Option<String> = cache.get("user@example.com", false).await;
+        assert!(cached.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_hashed_verdicts_per_tenant_and_expiry() {
+        let cache = ValidationCache::from_store(
+            Arc::new(MemoryStore::default()),
+            CacheTtlConfig {
+                invalid_domain: 0,
+                ..CacheTtlConfig::default()
+            },
+        );
+        // Both hashes share a bucket
+        let (valid, unresolvable) = ("user@example.com", "user162825@example.com");
+        let hash = email_hash(valid);
+        assert_eq!(hash[..5], email_hash(unresolvable)[..5]);
+        cache
+            .index_verdicts(
+                "acme@example.com",
+                &[
+                    (valid, None),
+                    (unresolvable, Some("INVALID_DOMAIN")),
+                    ("other@example.com", Some("DATABASE_ERROR")),
+                ],
+            )
+            .await;
+
+        let verdicts = cache
+            .lookup_hash_prefix("acme@example.com", &hash[..5])
+            .await
+            .unwrap();
+        assert_eq!(verdicts.len(), 1);
+        assert_eq!(verdicts[0].hash, hash);
+        assert!(verdicts[0].is_valid);
+        assert_eq!(verdicts[0].expires_at, verdicts[0].validated_at + 86400);
+
+        assert!(
+            cache
+                .lookup_hash_prefix("other@example.com", &hash[..5])
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_email_drops_hashed_verdicts() {
+        let cache = ValidationCache::from_store(
+            Arc::new(MemoryStore::default()),
+            CacheTtlConfig::default(),
+        );
+        cache
+            .set("user@example.com", false, &"VALID".to_string(), None)
+            .await;
+        for tenant in ["acme@example.com", "other@example.com"] {
+            cache
+                .index_verdicts(tenant, &[("user@example.com", None)])
+                .await;
+        }
+        let hash = email_hash("user@example.com");
+
+        // The cached result and the verdict of each tenant
+        assert_eq!(cache.invalidate_email("user@example.com").await.unwrap(), 3);
+        for tenant in ["acme@example.com", "other@example.com"] {
+            assert!(
+                cache
+                    .lookup_hash_prefix(tenant, &hash[..5])
+                    .await
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+        assert_eq!(cache.invalidate_email("user@example.com").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_invalidation_reaches_other_instances_local_tier() {
+        let store: Arc<dyn CacheStore> = Arc::new(MemoryStore::default());
+        let instance = || {
+            ValidationCache::from_store(store.clone(), CacheTtlConfig::default())
+                .with_local_tier(local_tier(10))
+        };
+        let (a, b) = (instance(), instance());
+
+        a.set("user@example.com", false, &"VALID".to_string(), None)
+            .await;
+        let cached: Option<String> = b.get("user@example.com", false).await;
+        assert_eq!(cached.as_deref(), Some("VALID"));
+
+        a.invalidate_email("user@example.com").await.unwrap();
+        tokio::time::sleep(INVALIDATION_CHECK_INTERVAL).await;
+        let cached: Option<String> = b.get("user@example.com", false).await;
+        assert!(cached.is_none());
+        assert_eq!(b.local_stats().unwrap().entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_cache_is_a_noop() {
+        let cache = ValidationCache::default();
+        assert!(!cache.is_enabled());
+        assert!(cache.local_stats().is_none());
+
+        cache
+            .set("user@example.com", false, &"VALID".to_string(), None)
+            .await;
+        let cached: Option<String> = cache.get("user@example.com", false).await;
+        assert!(cached.is_none());
+    }
+}
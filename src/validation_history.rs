@@ -0,0 +1,372 @@
+use crate::account_settings;
+use crate::pii::{self, Protection};
+use futures::TryStreamExt;
+use mongodb::bson::{DateTime, Document, doc};
+use mongodb::options::IndexOptions;
+use mongodb::{Client, Collection, IndexModel};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Days records are kept when `VALIDATION_HISTORY_RETENTION_DAYS` is unset
+pub const DEFAULT_RETENTION_DAYS: u64 = 90;
+
+/// Entries per page of a history query
+pub const HISTORY_PAGE_SIZE: usize = 100;
+
+/// Interface a validation was requested through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationSource {
+    Rest,
+    Graphql,
+    Bulk,
+}
+
+/// One validation as stored in the history collection
+///
+/// A hash of the address is kept for lookups (see [`pii::PiiSettings::hash`]); with
+/// `PII_HISTORY=encrypt` the address is kept too, encrypted. It is never stored in
+/// the clear.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    /// Account that requested the validation, lowercased
+    pub owner: String,
+    pub email_hash: String,
+    /// The encrypted address, with `PII_HISTORY=encrypt`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// `VALID` or `INVALID`
+    pub verdict: String,
+    pub error_code: Option<String>,
+    pub source: ValidationSource,
+    /// Expiry of the record is measured from this date (TTL index)
+    pub validated_at: DateTime,
+    /// Earlier expiry for accounts that keep their history for less than the server's
+    /// retention period (`result_retention_days`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime>,
+}
+
+impl HistoryRecord {
+    pub fn new(
+        owner: &str,
+        email: &str,
+        is_valid: bool,
+        error_code: Option<&str>,
+        source: ValidationSource,
+    ) -> Self {
+        let settings = pii::settings();
+        Self {
+            owner: owner.to_lowercase(),
+            email_hash: settings.hash(email),
+            email: match settings.history {
+                Protection::Encrypt => settings.encrypt(email),
+                _ => None,
+            },
+            verdict: if is_valid { "VALID" } else { "INVALID" }.to_string(),
+            error_code: error_code.map(str::to_string),
+            source,
+            validated_at: DateTime::now(),
+            expires_at: None,
+        }
+    }
+
+    /// Expires the record `days` after the validation
+    pub fn expiring_after(mut self, days: u64) -> Self {
+        let millis = (days * 24 * 3600 * 1000) as i64;
+        self.expires_at = Some(DateTime::from_millis(
+            self.validated_at.timestamp_millis().saturating_add(millis),
+        ));
+        self
+    }
+}
+
+/// A history record as returned by the query API
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct HistoryEntry {
+    /// SHA-256 of the trimmed, lowercased address, keyed with `PII_HASH_SALT` when set
+    pub email_hash: String,
+    /// The address, for records stored with `PII_HISTORY=encrypt`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    pub verdict: String,
+    pub error_code: Option<String>,
+    pub source: ValidationSource,
+    /// Unix timestamp of the validation
+    pub validated_at: i64,
+}
+
+impl From<HistoryRecord> for HistoryEntry {
+    fn from(record: HistoryRecord) -> Self {
+        HistoryEntry {
+            email_hash: record.email_hash,
+            email: record
+                .email
+                .and_then(|email| pii::settings().reveal(&email)),
+            verdict: record.verdict,
+            error_code: record.error_code,
+            source: record.source,
+            validated_at: record.validated_at.timestamp_millis() / 1000,
+        }
+    }
+}
+
+/// Narrows a history query; every field is optional
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryFilter {
+    /// Only validations of this address
+    pub email: Option<String>,
+    /// Unix timestamps bounding `validated_at`, both inclusive
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl HistoryFilter {
+    /// Rejects ranges that end before they start
+    pub fn check(&self) -> Result<(), String> {
+        match (self.from, self.to) {
+            (Some(from), Some(to)) if from > to => Err("from must not be after to".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    fn document(&self, owner: &str) -> Document {
+        let mut filter = doc! { "owner": owner.to_lowercase() };
+        if let Some(email) = &self.email {
+            filter.insert("email_hash", pii::settings().hash(email));
+        }
+        let mut range = Document::new();
+        if let Some(from) = self.from {
+            range.insert("$gte", DateTime::from_millis(from.saturating_mul(1000)));
+        }
+        if let Some(to) = self.to {
+            range.insert("$lte", DateTime::from_millis(to.saturating_mul(1000)));
+        }
+        if !range.is_empty() {
+            filter.insert("validated_at", range);
+        }
+        filter
+    }
+}
+
+/// Whether validations are recorded (`VALIDATION_HISTORY_ENABLED`, default true)
+pub fn is_enabled() -> bool {
+    std::env::var("VALIDATION_HISTORY_ENABLED")
+        .map(|v| !matches!(v.trim().to_ascii_lowercase().as_str(), "false" | "0" | "no"))
+        .unwrap_or(true)
+}
+
+/// How long records are kept (`VALIDATION_HISTORY_RETENTION_DAYS`)
+pub fn retention() -> Duration {
+    let days = std::env::var("VALIDATION_HISTORY_RETENTION_DAYS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+    Duration::from_secs(days * 24 * 3600)
+}
+
+/// Reads `DB_NAME_PRODUCTION` (default `email_sanitizer`) and
+/// `DB_VALIDATION_HISTORY_COLLECTION` (default `validation_history`)
+pub fn history_collection(mongo_client: &Client) -> Collection<HistoryRecord> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let collection_name = std::env::var("DB_VALIDATION_HISTORY_COLLECTION")
+        .unwrap_or_else(|_| "validation_history".to_string());
+    mongo_client.database(&db_name).collection(&collection_name)
+}
+
+/// Creates the query index and the TTL indexes that enforce the retention periods
+///
+/// A changed retention period is applied to the existing TTL index with `collMod`;
+/// records carrying an `expires_at` of their own expire at that date.
+pub async fn ensure_indexes(mongo_client: &Client) -> Result<(), String> {
+    let collection = history_collection(mongo_client);
+    let expire_after = retention();
+
+    collection
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "owner": 1, "validated_at": -1 })
+                .build(),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    collection
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "expires_at": 1 })
+                .options(IndexOptions::builder().expire_after(Duration::ZERO).build())
+                .build(),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let ttl_index = IndexModel::builder()
+        .keys(doc! { "validated_at": 1 })
+        .options(IndexOptions::builder().expire_after(expire_after).build())
+        .build();
+    if collection.create_index(ttl_index).await.is_err() {
+        mongo_client
+            .database(&collection.namespace().db)
+            .run_command(doc! {
+                "collMod": collection.name(),
+                "index": {
+                    "keyPattern": { "validated_at": 1 },
+                    "expireAfterSeconds": expire_after.as_secs() as i64,
+                },
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Stores records without holding up the response
+///
+/// The history is an audit trail, not part of the verdict; a failed write is
+/// logged and the validation still succeeds. Records of an account with a shorter
+/// `result_retention_days` setting expire after that many days.
+pub fn record(mongo_client: &Client, records: Vec<HistoryRecord>) {
+    if records.is_empty() || !is_enabled() {
+        return;
+    }
+    let mongo_client = mongo_client.clone();
+    tokio::spawn(async move {
+        let retention_days = account_settings::load_settings(&mongo_client, &records[0].owner)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|settings| settings.result_retention_days);
+        let records: Vec<HistoryRecord> = match retention_days {
+            Some(days) => records
+                .into_iter()
+                .map(|record| record.expiring_after(days))
+                .collect(),
+            None => records,
+        };
+        if let Err(e) = history_collection(&mongo_client).insert_many(records).await {
+            eprintln!("validation history write failed: {}", e);
+        }
+    });
+}
+
+/// Deletes the owner's records validated more than `days` ago, so a shortened
+/// retention period also applies to the history already kept
+pub async fn purge_older_than(mongo_client: &Client, owner: &str, days: u64) -> Result<(), String> {
+    let cutoff = DateTime::now()
+        .timestamp_millis()
+        .saturating_sub((days * 24 * 3600 * 1000) as i64);
+    history_collection(mongo_client)
+        .delete_many(doc! {
+            "owner": owner.to_lowercase(),
+            "validated_at": { "$lt": DateTime::from_millis(cutoff) },
+        })
+        .await
+        .map_err(|_| "Database error".to_string())?;
+    Ok(())
+}
+
+/// Deletes the records of an address, of one owner or of every owner; returns how many
+pub async fn erase(mongo_client: &Client, owner: Option<&str>, email: &str) -> Result<u64, String> {
+    let mut filter = doc! { "email_hash": pii::settings().hash(email) };
+    if let Some(owner) = owner {
+        filter.insert("owner", owner.to_lowercase());
+    }
+    history_collection(mongo_client)
+        .delete_many(filter)
+        .await
+        .map(|result| result.deleted_count)
+        .map_err(|_| "Database error".to_string())
+}
+
+/// One page of the owner's history, newest first
+pub async fn query_history(
+    mongo_client: &Client,
+    owner: &str,
+    filter: &HistoryFilter,
+    page: usize,
+) -> Result<Vec<HistoryEntry>, String> {
+    let skip = page.saturating_sub(1).saturating_mul(HISTORY_PAGE_SIZE);
+    history_collection(mongo_client)
+        .find(filter.document(owner))
+        .sort(doc! { "validated_at": -1 })
+        .skip(skip as u64)
+        .limit(HISTORY_PAGE_SIZE as i64)
+        .await
+        .map_err(|_| "Database error".to_string())?
+        .map_ok(HistoryEntry::from)
+        .try_collect()
+        .await
+        .map_err(|_| "Database error".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::validation::first_seen::email_hash;
+
+    #[test]
+    fn test_record_keeps_only_the_hash() {
+        let record = HistoryRecord::new(
+            "Owner@Example.com",
+            " User@Example.com",
+            false,
+            Some("INVALID_DOMAIN"),
+            ValidationSource::Bulk,
+        );
+
+        assert_eq!(record.owner, "owner@example.com");
+        assert_eq!(record.email_hash, email_hash("user@example.com"));
+        assert!(record.email.is_none());
+        assert_eq!(record.verdict, "INVALID");
+        assert!(!serde_json::to_string(&record).unwrap().contains("User@"));
+        assert_eq!(
+            serde_json::to_value(ValidationSource::Graphql).unwrap(),
+            "graphql"
+        );
+
+        assert!(record.expires_at.is_none());
+        let expiring = record.clone().expiring_after(7);
+        assert_eq!(
+            expiring.expires_at.unwrap().timestamp_millis()
+                - record.validated_at.timestamp_millis(),
+            7 * 24 * 3600 * 1000
+        );
+    }
+
+    #[test]
+    fn test_filter_document() {
+        let filter = HistoryFilter {
+            email: Some("user@example.com".to_string()),
+            from: Some(100),
+            to: Some(200),
+        };
+        assert!(filter.check().is_ok());
+
+        let document = filter.document("Owner@Example.com");
+        assert_eq!(document.get_str("owner").unwrap(), "owner@example.com");
+        assert_eq!(
+            document.get_str("email_hash").unwrap(),
+            email_hash("user@example.com")
+        );
+        let range = document.get_document("validated_at").unwrap();
+        assert_eq!(
+            range.get_datetime("$gte").unwrap(),
+            &DateTime::from_millis(100_000)
+        );
+
+        assert!(
+            !HistoryFilter::default()
+                .document("o")
+                .contains_key("validated_at")
+        );
+        let reversed = HistoryFilter {
+            from: Some(2),
+            to: Some(1),
+            ..Default::default()
+        };
+        assert!(reversed.check().is_err());
+    }
+}
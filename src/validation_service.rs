@@ -0,0 +1,806 @@
+use crate::bulk::{DomainCheck, DomainMemo};
+use crate::checks::Checks;
+use crate::handlers::validation::custom_lists::{AccountLists, CustomVerdict};
+use crate::handlers::validation::dnsmx::{self, DnsOutcome, IdnDomain};
+use crate::handlers::validation::{disposable, role_based, suppression, syntax, tld};
+use crate::local_cache::LocalTierConfig;
+use crate::normalize::lookup_key;
+use crate::routes::email::{EmailValidationError, EmailValidationResponse, RedisCache};
+use crate::stores::{CacheStore, DomainListStore, MemoryDomainLists};
+use crate::validation_cache::{CacheTtlConfig, ValidationCache};
+use crate::validation_stats::{ValidationEvent, ValidationStats};
+use crate::verification::{ProviderVerdict, VerifierChain};
+use futures::future::join_all;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+
+pub fn valid() -> EmailValidationResponse {
+    EmailValidationResponse {
+        is_valid: true,
+        domain: None,
+        has_tag: None,
+        base_address: None,
+        status: Some("VALID".to_string()),
+        error: None,
+    }
+}
+
+pub fn rejection(code: &str, message: impl Into<String>) -> EmailValidationResponse {
+    EmailValidationResponse {
+        is_valid: false,
+        domain: None,
+        has_tag: None,
+        base_address: None,
+        status: None,
+        error: Some(EmailValidationError {
+            code: code.to_string(),
+            message: message.into(),
+            violations: Vec::new(),
+        }),
+    }
+}
+
+/// Result for `email` if it is on one of `lists`; malformed addresses never are
+pub fn list_verdict(lists: &AccountLists, email: &str) -> Option<EmailValidationResponse> {
+    if lists.is_empty() || !syntax::is_valid_email(email) {
+        return None;
+    }
+    match lists.verdict(email)? {
+        CustomVerdict::Allowed => Some(valid()),
+        CustomVerdict::Blocked => Some(rejection(
+            "CUSTOM_BLOCKED",
+            "Email address is blocked by the account's blocklist",
+        )),
+        CustomVerdict::Feedback(kind) => Some(rejection(kind.code(), kind.message())),
+        CustomVerdict::Suppressed(reason) => Some(rejection("SUPPRESSED", reason.message())),
+    }
+}
+
+/// Positions the earlier stages have not decided yet
+fn undecided(results: &[Option<EmailValidationResponse>]) -> Vec<usize> {
+    results
+        .iter()
+        .enumerate()
+        .filter(|(_, result)| result.is_none())
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn distinct(values: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    values
+        .iter()
+        .filter(|value| seen.insert(value.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// Stores and providers the validation flow runs through, registered once as app data
+/// and shared by the REST handlers, the gRPC service and the bulk worker
+#[derive(Clone)]
+pub struct ValidationBackends {
+    pub cache: ValidationCache,
+    pub stats: ValidationStats,
+    pub verifiers: VerifierChain,
+    /// Disposable and role-based reference lists
+    pub domain_lists: Arc<dyn DomainListStore>,
+    /// DNS verdict cache, routing its misses through the canary resolver
+    pub dns: RedisCache,
+}
+
+impl ValidationBackends {
+    /// Backends sharing the store of `dns`, configured from the environment
+    pub fn from_env(dns: RedisCache, domain_lists: Arc<dyn DomainListStore>) -> Self {
+        let store = dns.store();
+        Self {
+            cache: ValidationCache::from_store(store.clone(), CacheTtlConfig::from_env())
+                .with_local_tier(LocalTierConfig::validation_from_env()),
+            stats: ValidationStats::from_store(store),
+            verifiers: VerifierChain::from_env(),
+            domain_lists,
+            dns,
+        }
+    }
+
+    /// Backends sharing `store`, e.g. a `MemoryStore` in tests, with the default cache
+    /// lifetimes, no fallback providers and empty reference lists
+    pub fn from_store(store: Arc<dyn CacheStore>) -> Self {
+        Self {
+            cache: ValidationCache::from_store(store.clone(), CacheTtlConfig::default()),
+            stats: ValidationStats::from_store(store.clone()),
+            verifiers: VerifierChain::default(),
+            domain_lists: Arc::new(MemoryDomainLists::default()),
+            dns: RedisCache::from_store(store, 3600),
+        }
+    }
+
+    /// Reads the reference lists from `domain_lists`
+    pub fn with_domain_lists(mut self, domain_lists: Arc<dyn DomainListStore>) -> Self {
+        self.domain_lists = domain_lists;
+        self
+    }
+}
+
+/// The validation flow behind every API: the caller's custom lists, the result cache,
+/// the check pipeline and the fallback providers, counted in the validation stats
+///
+/// REST handlers, GraphQL resolvers, the gRPC service and the bulk worker all validate
+/// through it, so an address gets the same verdict and the same caching whichever way
+/// it comes in. It borrows its stores; build one per request.
+#[derive(Clone, Copy)]
+pub struct ValidationService<'a> {
+    cache: &'a ValidationCache,
+    stats: &'a ValidationStats,
+    verifiers: &'a VerifierChain,
+    domain_lists: &'a dyn DomainListStore,
+    /// DNS verdict cache and canary routing; without it domains are resolved directly
+    dns: Option<&'a RedisCache>,
+    /// Account whose hash lookup index the verdicts go to; `None` indexes nothing
+    tenant_id: Option<&'a str>,
+}
+
+impl<'a> ValidationService<'a> {
+    /// Validates through `backends`
+    pub fn new(backends: &'a ValidationBackends) -> Self {
+        Self {
+            cache: &backends.cache,
+            stats: &backends.stats,
+            verifiers: &backends.verifiers,
+            domain_lists: backends.domain_lists.as_ref(),
+            dns: Some(&backends.dns),
+            tenant_id: None,
+        }
+    }
+
+    /// Validates through separately configured stores
+    pub fn from_parts(
+        cache: &'a ValidationCache,
+        stats: &'a ValidationStats,
+        verifiers: &'a VerifierChain,
+        domain_lists: &'a dyn DomainListStore,
+        dns: Option<&'a RedisCache>,
+    ) -> Self {
+        Self {
+            cache,
+            stats,
+            verifiers,
+            domain_lists,
+            dns,
+            tenant_id: None,
+        }
+    }
+
+    /// Indexes the verdicts for the hash lookups of `tenant_id`, the caller
+    pub fn for_tenant(mut self, tenant_id: &'a str) -> Self {
+        self.tenant_id = Some(tenant_id);
+        self
+    }
+
+    /// Validates one address
+    pub async fn validate(
+        &self,
+        email: &str,
+        checks: Checks,
+        lists: &AccountLists,
+    ) -> EmailValidationResponse {
+        self.validate_many(&[email.to_string()], checks, lists, &DomainMemo::default())
+            .await
+            .remove(0)
+    }
+
+    /// Validates the addresses of a bulk request or job, each with its own `checks`;
+    /// results are in the order of `emails`, which should already be deduplicated
+    ///
+    /// Addresses sharing a set of checks are validated together, and DNS verdicts are
+    /// shared across sets, so each domain is resolved once.
+    pub async fn validate_batch(
+        &self,
+        emails: &[String],
+        checks: &[Checks],
+        lists: &AccountLists,
+    ) -> Vec<EmailValidationResponse> {
+        let mut groups: Vec<(Checks, Vec<usize>)> = Vec::new();
+        for (i, checks) in checks.iter().enumerate() {
+            match groups.iter_mut().find(|(group, _)| group == checks) {
+                Some((_, positions)) => positions.push(i),
+                None => groups.push((*checks, vec![i])),
+            }
+        }
+
+        let domains = DomainMemo::default();
+        let mut results = vec![None; emails.len()];
+        for (checks, positions) in groups {
+            let group: Vec<String> = positions.iter().map(|&i| emails[i].clone()).collect();
+            let validations = self.validate_many(&group, checks, lists, &domains).await;
+            for (i, result) in positions.into_iter().zip(validations) {
+                results[i] = Some(result);
+            }
+        }
+        results
+            .into_iter()
+            .map(|result| result.expect("every address belongs to a set of checks"))
+            .collect()
+    }
+
+    /// Validates addresses needing the same checks with a fixed number of backend
+    /// round trips
+    ///
+    /// Custom list verdicts come first and are never cached, cached results come from
+    /// one MGET, and the misses go through the pipeline together before those it left
+    /// undecided are put to the fallback providers. Fresh results are cached, and every
+    /// validation counted, in one pipeline each. Results of a non-standard set of
+    /// `checks` bypass the cache.
+    async fn validate_many(
+        &self,
+        emails: &[String],
+        checks: Checks,
+        lists: &AccountLists,
+        domains: &DomainMemo,
+    ) -> Vec<EmailValidationResponse> {
+        let started = Instant::now();
+        let emails: Vec<String> = emails
+            .iter()
+            .map(|email| email.trim().to_string())
+            .collect();
+
+        let mut results: Vec<Option<EmailValidationResponse>> = emails
+            .iter()
+            .map(|email| list_verdict(lists, email))
+            .collect();
+        let custom: Vec<bool> = results.iter().map(Option::is_some).collect();
+
+        let open = undecided(&results);
+        let open_emails: Vec<String> = open.iter().map(|&i| emails[i].clone()).collect();
+        let cached = if checks.is_standard() {
+            self.cache
+                .get_many::<EmailValidationResponse>(&open_emails, checks.role_based)
+                .await
+        } else {
+            vec![None; open.len()]
+        };
+        let mut cache_hit = vec![false; emails.len()];
+        let mut misses = Vec::new();
+        for (i, cached) in open.into_iter().zip(cached) {
+            match cached {
+                Some(cached) => {
+                    results[i] = Some(cached);
+                    cache_hit[i] = true;
+                }
+                None => misses.push(i),
+            }
+        }
+
+        let miss_emails: Vec<String> = misses.iter().map(|&i| emails[i].clone()).collect();
+        let fresh = self.pipeline(&miss_emails, checks, domains).await;
+        let fresh = join_all(
+            miss_emails
+                .iter()
+                .zip(fresh)
+                .map(|(email, result)| self.with_fallback_verification(email, result)),
+        )
+        .await;
+        if checks.is_standard() {
+            // The cache decides per error code how long (and whether) to keep each result
+            let entries: Vec<(&str, &EmailValidationResponse, Option<&str>)> = miss_emails
+                .iter()
+                .zip(&fresh)
+                .map(|(email, result)| {
+                    let error_code = result.error.as_ref().map(|e| e.code.as_str());
+                    (email.as_str(), result, error_code)
+                })
+                .collect();
+            self.cache.set_many(checks.role_based, &entries).await;
+        }
+        for (i, result) in misses.into_iter().zip(fresh) {
+            results[i] = Some(result);
+        }
+        let results: Vec<EmailValidationResponse> = results
+            .into_iter()
+            .map(|result| result.expect("every address is listed, cached or validated"))
+            .collect();
+
+        if let Some(tenant_id) = self.tenant_id
+            && checks.is_standard()
+        {
+            let verdicts: Vec<(&str, Option<&str>)> = emails
+                .iter()
+                .zip(&results)
+                .zip(&custom)
+                .filter(|(_, custom)| !**custom)
+                .map(|((email, result), _)| {
+                    (
+                        email.as_str(),
+                        result.error.as_ref().map(|e| e.code.as_str()),
+                    )
+                })
+                .collect();
+            self.cache.index_verdicts(tenant_id, &verdicts).await;
+        }
+
+        // Each address is charged an equal share of the batch's time
+        let latency = started.elapsed() / emails.len().max(1) as u32;
+        let events: Vec<ValidationEvent> = emails
+            .iter()
+            .zip(&results)
+            .enumerate()
+            .filter(|(i, _)| !custom[*i])
+            .map(|(i, (email, result))| ValidationEvent {
+                email,
+                is_valid: result.is_valid,
+                error_code: result.error.as_ref().map(|e| e.code.as_str()),
+                cache_hit: cache_hit[i],
+                latency,
+            })
+            .collect();
+        self.stats
+            .record_many(&events, chrono::Utc::now().timestamp())
+            .await;
+        results
+    }
+
+    /// Asks the configured fallback providers about an address local checks couldn't
+    /// decide (`DATABASE_ERROR`); their verdict replaces the local result, which is kept
+    /// when no provider can decide either
+    async fn with_fallback_verification(
+        &self,
+        email: &str,
+        result: EmailValidationResponse,
+    ) -> EmailValidationResponse {
+        let undecided = result
+            .error
+            .as_ref()
+            .is_some_and(|error| error.code == "DATABASE_ERROR");
+        if !undecided || self.verifiers.is_empty() {
+            return result;
+        }
+        let verdict = match self.verifiers.verify(email).await {
+            Some(ProviderVerdict {
+                deliverable: true, ..
+            }) => valid(),
+            Some(ProviderVerdict { provider, .. }) => rejection(
+                "UNDELIVERABLE",
+                format!("Email address was reported undeliverable by {}", provider),
+            ),
+            None => return result,
+        };
+        EmailValidationResponse {
+            domain: result.domain,
+            ..verdict
+        }
+    }
+
+    /// Runs the check pipeline over many addresses at once, bypassing lists, cache and
+    /// fallback providers; results are in the order of `emails`
+    ///
+    /// Every stage handles all addresses it still has to decide in one go: a single
+    /// suppression query, one DNS lookup per distinct domain, a single role-based query
+    /// and a single disposable query, however many addresses are passed. Stages `checks`
+    /// leaves out are skipped; syntax is always checked.
+    pub async fn run_pipeline(
+        &self,
+        emails: &[String],
+        checks: Checks,
+    ) -> Vec<EmailValidationResponse> {
+        self.pipeline(emails, checks, &DomainMemo::default()).await
+    }
+
+    async fn pipeline(
+        &self,
+        emails: &[String],
+        checks: Checks,
+        domains: &DomainMemo,
+    ) -> Vec<EmailValidationResponse> {
+        let mut results: Vec<Option<EmailValidationResponse>> = vec![None; emails.len()];
+
+        // 1. Syntax validation
+        for (email, result) in emails.iter().zip(results.iter_mut()) {
+            if let Err(syntax_error) = syntax::parse_email(email) {
+                *result = Some(EmailValidationResponse {
+                    is_valid: false,
+                    domain: None,
+                    has_tag: None,
+                    base_address: None,
+                    status: None,
+                    error: Some(EmailValidationError {
+                        code: "INVALID_SYNTAX".to_string(),
+                        message: syntax_error.to_string(),
+                        violations: syntax_error.violations,
+                    }),
+                });
+            }
+        }
+        // Addresses past the syntax check report their domain, whatever the verdict;
+        // Unicode and punycode spellings share DNS verdicts
+        let well_formed = undecided(&results);
+        let email_domains: Vec<Option<IdnDomain>> = emails
+            .iter()
+            .map(|email| {
+                email
+                    .rsplit_once('@')
+                    .and_then(|(_, domain)| dnsmx::idn_domain(domain))
+            })
+            .collect();
+
+        // 2. Known spamtrap, complainer and bouncer hashes
+        if checks.suppression {
+            let open = undecided(&results);
+            let open_emails: Vec<String> = open.iter().map(|&i| emails[i].clone()).collect();
+            match suppression::lookup_many(&open_emails).await {
+                Ok(kinds) => {
+                    for (&i, kind) in open.iter().zip(kinds) {
+                        if let Some(kind) = kind {
+                            results[i] = Some(rejection(kind.code(), kind.message()));
+                        }
+                    }
+                }
+                Err(e) => {
+                    let message = format!("Failed to check suppression list: {}", e);
+                    for i in open {
+                        results[i] = Some(rejection("DATABASE_ERROR", message.clone()));
+                    }
+                }
+            }
+        }
+
+        // 3. TLD check, sparing the DNS round trip for domains that can't exist
+        if checks.tld {
+            for i in undecided(&results) {
+                if let Some(domain) = &email_domains[i]
+                    && !tld::has_known_tld(&domain.ascii)
+                {
+                    results[i] = Some(rejection(
+                        "INVALID_TLD",
+                        "Email domain does not end in a registered top-level domain",
+                    ));
+                }
+            }
+        }
+
+        // 4. DNS/MX validation (with cache), one lookup per distinct domain; resolver
+        // failures aren't memoized, so the next batch at the domain tries again. A
+        // domain with no IDNA form can't receive mail, DNS check or not.
+        let open = undecided(&results);
+        let mut lookups: Vec<(&IdnDomain, &str)> = Vec::new();
+        let mut seen = HashSet::new();
+        for &i in &open {
+            if let Some(domain) = &email_domains[i]
+                && checks.dns
+                && seen.insert(domain.ascii.as_str())
+            {
+                lookups.push((domain, emails[i].as_str()));
+            }
+        }
+        let resolved = join_all(lookups.iter().map(|&(domain, email)| {
+            domains.get_or_check(DomainCheck::Dns, &domain.ascii, move || async move {
+                match self.lookup_dns(domain, email).await {
+                    DnsOutcome::Unavailable => Err("DNS resolution failed".to_string()),
+                    outcome => Ok(outcome.is_valid()),
+                }
+            })
+        }))
+        .await;
+        let outcomes: HashMap<&str, Result<bool, String>> = lookups
+            .iter()
+            .map(|(domain, _)| domain.ascii.as_str())
+            .zip(resolved)
+            .collect();
+        for i in open {
+            let dns = match &email_domains[i] {
+                None => Ok(false),
+                Some(_) if !checks.dns => Ok(true),
+                Some(domain) => outcomes[domain.ascii.as_str()].clone(),
+            };
+            match dns {
+                Ok(true) => {}
+                Ok(false) => {
+                    results[i] = Some(rejection(
+                        "INVALID_DOMAIN",
+                        "Email domain has no valid DNS records",
+                    ))
+                }
+                Err(_) => {
+                    results[i] = Some(rejection(
+                        "DNS_UNAVAILABLE",
+                        "Email domain's DNS records could not be resolved; try again later",
+                    ))
+                }
+            }
+        }
+
+        // 5. Role-based email check (optional)
+        if checks.role_based {
+            let open = undecided(&results);
+            let local_parts: Vec<String> = open
+                .iter()
+                .map(|&i| {
+                    emails[i]
+                        .split_once('@')
+                        .map(|(local_part, _)| lookup_key(local_part))
+                        .unwrap_or_default()
+                })
+                .collect();
+            match role_based::role_based_local_parts(self.domain_lists, &distinct(&local_parts))
+                .await
+            {
+                Ok(role_based) => {
+                    for (&i, local_part) in open.iter().zip(&local_parts) {
+                        if role_based.contains(local_part) {
+                            results[i] = Some(rejection(
+                                "ROLE_BASED_EMAIL",
+                                "Email address uses a role-based local part",
+                            ));
+                        }
+                    }
+                }
+                Err(e) => {
+                    for i in open {
+                        results[i] = Some(rejection("DATABASE_ERROR", e.clone()));
+                    }
+                }
+            }
+        }
+
+        // 6. Disposable email check
+        let open = undecided(&results);
+        if checks.disposable {
+            let disposable_domains: Vec<String> = open
+                .iter()
+                .map(|&i| {
+                    emails[i]
+                        .split_once('@')
+                        .map(|(_, domain)| lookup_key(domain))
+                        .unwrap_or_default()
+                })
+                .collect();
+            let disposable =
+                disposable::disposable_domains(self.domain_lists, &distinct(&disposable_domains))
+                    .await
+                    .map_err(|e| e.to_string());
+            for (&i, domain) in open.iter().zip(&disposable_domains) {
+                results[i] = Some(match &disposable {
+                    Ok(disposable) if disposable.contains(domain) => rejection(
+                        "DISPOSABLE_EMAIL",
+                        "The email address domain is a provider of disposable email addresses",
+                    ),
+                    Ok(_) => valid(),
+                    Err(message) => rejection("DATABASE_ERROR", message.clone()),
+                });
+            }
+        } else {
+            // Addresses no enabled stage rejected
+            for i in open {
+                results[i] = Some(valid());
+            }
+        }
+
+        for i in well_formed {
+            if let Some(result) = &mut results[i] {
+                result.domain = email_domains[i].clone();
+                result.set_tag(&emails[i]);
+            }
+        }
+        results
+            .into_iter()
+            .map(|result| result.expect("the last stage decides every address"))
+            .collect()
+    }
+
+    /// DNS verdict for `domain`, from the DNS cache when there is one; `email`, an
+    /// address at the domain, picks the resolver path for canary routing
+    async fn lookup_dns(&self, domain: &IdnDomain, email: &str) -> DnsOutcome {
+        let Some(redis_cache) = self.dns else {
+            let ascii = domain.ascii.clone();
+            return tokio::task::spawn_blocking(move || dnsmx::resolve_domain_dns(&ascii))
+                .await
+                .unwrap_or(DnsOutcome::Unavailable);
+        };
+        match redis_cache.get_dns_validation(&domain.ascii).await {
+            Ok(Some(cached_outcome)) => cached_outcome,
+            _ => {
+                let outcome = redis_cache.canary.check_dns(email).await;
+                let _ = redis_cache.set_dns_validation(&domain.ascii, outcome).await;
+                outcome
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checks::Check;
+    use crate::handlers::validation::first_seen::email_hash;
+    use crate::stores::MemoryDomainLists;
+    use crate::sync::SyncList;
+
+    fn codes(results: &[EmailValidationResponse]) -> Vec<Option<&str>> {
+        results
+            .iter()
+            .map(|result| result.error.as_ref().map(|e| e.code.as_str()))
+            .collect()
+    }
+
+    /// Stores that need neither Redis nor MongoDB
+    #[derive(Default)]
+    struct Backends {
+        cache: ValidationCache,
+        stats: ValidationStats,
+        verifiers: VerifierChain,
+        domain_lists: MemoryDomainLists,
+    }
+
+    impl Backends {
+        fn service(&self) -> ValidationService<'_> {
+            ValidationService::from_parts(
+                &self.cache,
+                &self.stats,
+                &self.verifiers,
+                &self.domain_lists,
+                None,
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_pipeline_keeps_input_order() {
+        let backends = Backends::default();
+        let emails = vec![
+            "not-an-email".to_string(),
+            "user@example.fake".to_string(),
+            "a..b@example.com".to_string(),
+            "other@example.fake".to_string(),
+        ];
+        let results = backends
+            .service()
+            .run_pipeline(&emails, Checks::standard(false))
+            .await;
+        assert_eq!(
+            codes(&results),
+            vec![
+                Some("INVALID_SYNTAX"),
+                Some("INVALID_TLD"),
+                Some("INVALID_SYNTAX"),
+                Some("INVALID_TLD"),
+            ]
+        );
+        let violations = &results[2].error.as_ref().unwrap().violations;
+        assert!(
+            violations
+                .iter()
+                .any(|violation| violation.as_str() == "CONSECUTIVE_DOTS")
+        );
+        // Only addresses past the syntax check report their domain
+        assert!(results[0].domain.is_none());
+        assert_eq!(results[1].domain.as_ref().unwrap().ascii, "example.fake");
+    }
+
+    #[tokio::test]
+    async fn test_run_pipeline_skips_left_out_checks() {
+        let backends = Backends::default();
+        let emails = vec!["bad".to_string(), "user@example.fake".to_string()];
+        let results = backends
+            .service()
+            .run_pipeline(&emails, Checks::only(&[]))
+            .await;
+        assert_eq!(codes(&results), vec![Some("INVALID_SYNTAX"), None]);
+        assert!(results[1].is_valid);
+    }
+
+    #[tokio::test]
+    async fn test_run_pipeline_checks_reference_lists() {
+        let backends = Backends {
+            domain_lists: MemoryDomainLists::default()
+                .with(SyncList::RoleBased, &["admin"])
+                .with(SyncList::Disposable, &["mailinator.com"]),
+            ..Backends::default()
+        };
+        let emails = vec![
+            "Admin@example.com".to_string(),
+            "jane@Mailinator.com".to_string(),
+            "jane@example.com".to_string(),
+        ];
+        let results = backends
+            .service()
+            .run_pipeline(
+                &emails,
+                Checks::only(&[Check::RoleBased, Check::Disposable]),
+            )
+            .await;
+        assert_eq!(
+            codes(&results),
+            vec![Some("ROLE_BASED_EMAIL"), Some("DISPOSABLE_EMAIL"), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lists_decide_before_the_pipeline() {
+        let backends = Backends::default();
+        let service = backends.service();
+        let lists = AccountLists {
+            allowed: vec!["ok@example.fake".to_string()],
+            blocked: vec!["no@example.fake".to_string()],
+            ..AccountLists::default()
+        };
+
+        let allowed = service
+            .validate(" ok@example.fake ", Checks::standard(false), &lists)
+            .await;
+        assert!(allowed.is_valid);
+        let blocked = service
+            .validate("no@example.fake", Checks::standard(false), &lists)
+            .await;
+        assert_eq!(codes(&[blocked]), vec![Some("CUSTOM_BLOCKED")]);
+        let unlisted = service
+            .validate("other@example.fake", Checks::standard(false), &lists)
+            .await;
+        assert_eq!(codes(&[unlisted]), vec![Some("INVALID_TLD")]);
+    }
+
+    #[tokio::test]
+    async fn test_verdicts_are_indexed_for_the_tenant() {
+        let backends = Backends {
+            cache: ValidationCache::from_store(
+                Arc::new(crate::stores::MemoryStore::default()),
+                CacheTtlConfig::default(),
+            ),
+            ..Backends::default()
+        };
+        let lists = AccountLists {
+            allowed: vec!["ok@example.fake".to_string()],
+            ..AccountLists::default()
+        };
+        let emails = vec![
+            "ok@example.fake".to_string(),
+            "other@example.fake".to_string(),
+        ];
+        backends
+            .service()
+            .for_tenant("acme@example.com")
+            .validate_batch(&emails, &[Checks::standard(false); 2], &lists)
+            .await;
+
+        let lookup = |tenant: &'static str, email: &str| {
+            let hash = email_hash(email);
+            let cache = &backends.cache;
+            async move { cache.lookup_hash_prefix(tenant, &hash[..5]).await.unwrap() }
+        };
+        let verdicts = lookup("acme@example.com", "other@example.fake").await;
+        assert_eq!(verdicts.len(), 1);
+        assert_eq!(verdicts[0].code.as_deref(), Some("INVALID_TLD"));
+        // Custom list verdicts aren't the service's
+        assert!(
+            lookup("acme@example.com", "ok@example.fake")
+                .await
+                .is_empty()
+        );
+        assert!(
+            lookup("other@example.com", "other@example.fake")
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_batch_runs_each_address_with_its_checks() {
+        let backends = Backends::default();
+        let emails = vec![
+            "a@example.fake".to_string(),
+            "b@example.fake".to_string(),
+            "bad".to_string(),
+        ];
+        let checks = vec![
+            Checks::standard(false),
+            Checks::only(&[]),
+            Checks::only(&[]),
+        ];
+        let results = backends
+            .service()
+            .validate_batch(&emails, &checks, &AccountLists::default())
+            .await;
+        assert_eq!(
+            codes(&results),
+            vec![Some("INVALID_TLD"), None, Some("INVALID_SYNTAX")]
+        );
+    }
+}
@@ -0,0 +1,485 @@
+use crate::error_codes::{self, CodeKind, Severity};
+use crate::job_summary::DomainCount;
+use crate::normalize::normalize_domain;
+use crate::redis_pool::RedisPool;
+use crate::stores::{CacheStore, RedisStore, WriteBatch};
+use redis::RedisError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+const KEY_PREFIX: &str = "validation_stats:";
+
+/// Invalid domains listed in a report's `top_invalid_domains`
+pub const TOP_INVALID_DOMAINS: usize = 10;
+
+/// Period a stats report covers, ending now
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsWindow {
+    /// The last 60 minutes, in minute buckets
+    Hour,
+    /// The last 24 hours, in hourly buckets
+    #[default]
+    Day,
+    /// The last 30 days, in daily buckets
+    Month,
+}
+
+impl StatsWindow {
+    pub const ALL: [StatsWindow; 3] = [StatsWindow::Hour, StatsWindow::Day, StatsWindow::Month];
+
+    pub fn parse(window: &str) -> Option<Self> {
+        match window.trim().to_ascii_lowercase().as_str() {
+            "hour" => Some(StatsWindow::Hour),
+            "day" => Some(StatsWindow::Day),
+            "month" => Some(StatsWindow::Month),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatsWindow::Hour => "hour",
+            StatsWindow::Day => "day",
+            StatsWindow::Month => "month",
+        }
+    }
+
+    /// Length of one bucket in seconds
+    fn bucket_secs(&self) -> i64 {
+        match self {
+            StatsWindow::Hour => 60,
+            StatsWindow::Day => 3600,
+            StatsWindow::Month => 86400,
+        }
+    }
+
+    /// Buckets read for a report
+    fn buckets(&self) -> i64 {
+        match self {
+            StatsWindow::Hour => 60,
+            StatsWindow::Day => 24,
+            StatsWindow::Month => 30,
+        }
+    }
+
+    /// Bucket keys kept a little longer than the window so reports never miss one
+    fn ttl_secs(&self) -> i64 {
+        self.bucket_secs() * (self.buckets() + 2)
+    }
+
+    fn counters_key(&self, bucket: i64) -> String {
+        format!("{}{}:{}", KEY_PREFIX, self.as_str(), bucket)
+    }
+
+    fn domains_key(&self, bucket: i64) -> String {
+        format!("{}{}:{}:invalid_domains", KEY_PREFIX, self.as_str(), bucket)
+    }
+
+    /// Buckets covering the window that ends at `now`, oldest first
+    fn bucket_range(&self, now: i64) -> std::ops::RangeInclusive<i64> {
+        let current = now / self.bucket_secs();
+        current - self.buckets() + 1..=current
+    }
+}
+
+/// One validation as counted by [`ValidationStats::record`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationEvent<'a> {
+    pub email: &'a str,
+    pub is_valid: bool,
+    pub error_code: Option<&'a str>,
+    pub cache_hit: bool,
+    pub latency: Duration,
+}
+
+/// Occurrences of one error code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ErrorCodeCount {
+    pub code: String,
+    pub count: u64,
+}
+
+/// Validation totals over a [`StatsWindow`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct StatsReport {
+    pub window: StatsWindow,
+    /// Unix timestamp of the first bucket included
+    pub since: i64,
+    pub total: u64,
+    pub valid: u64,
+    pub invalid: u64,
+    /// Invalid results by error code, most frequent first
+    pub error_codes: Vec<ErrorCodeCount>,
+    pub top_invalid_domains: Vec<DomainCount>,
+    /// Share of validations served from the result cache; `None` without traffic
+    pub cache_hit_rate: Option<f64>,
+    /// Mean time to produce a result in milliseconds; `None` without traffic
+    pub average_latency_ms: Option<f64>,
+}
+
+impl StatsReport {
+    /// Builds a report from the summed counter fields of a window's buckets
+    fn from_counters(
+        window: StatsWindow,
+        since: i64,
+        counters: &HashMap<String, u64>,
+        top_invalid_domains: Vec<DomainCount>,
+    ) -> Self {
+        let field = |name: &str| counters.get(name).copied().unwrap_or(0);
+        let total = field("total");
+        let hits = field("cache_hit");
+
+        let mut error_codes: Vec<ErrorCodeCount> = counters
+            .iter()
+            .filter_map(|(name, count)| {
+                name.strip_prefix("code:").map(|code| ErrorCodeCount {
+                    code: code.to_string(),
+                    count: *count,
+                })
+            })
+            .collect();
+        error_codes.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.code.cmp(&b.code)));
+
+        StatsReport {
+            window,
+            since,
+            total,
+            valid: field("valid"),
+            invalid: field("invalid"),
+            error_codes,
+            top_invalid_domains,
+            cache_hit_rate: (total > 0).then(|| hits as f64 / total as f64),
+            average_latency_ms: (total > 0)
+                .then(|| field("latency_us") as f64 / total as f64 / 1000.0),
+        }
+    }
+}
+
+/// Validations of one bucket of a [`StatsWindow`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TimelinePoint {
+    /// Unix timestamp the bucket starts at
+    pub at: i64,
+    pub total: u64,
+    pub invalid: u64,
+    /// Results that failed on our side or in a dependency (critical verdict codes such
+    /// as `DNS_UNAVAILABLE`), rather than judging the address
+    pub errors: u64,
+    /// `errors` over `total`; `None` without traffic
+    pub error_rate: Option<f64>,
+}
+
+impl TimelinePoint {
+    fn from_counters(at: i64, counters: &HashMap<String, u64>) -> Self {
+        let field = |name: &str| counters.get(name).copied().unwrap_or(0);
+        let total = field("total");
+        let errors = counters
+            .iter()
+            .filter_map(|(name, count)| Some((name.strip_prefix("code:")?, *count)))
+            .filter(|(code, _)| {
+                error_codes::lookup(code).any(|info| {
+                    info.kind == CodeKind::Verdict && info.severity == Severity::Critical
+                })
+            })
+            .map(|(_, count)| count)
+            .sum();
+        TimelinePoint {
+            at,
+            total,
+            invalid: field("invalid"),
+            errors,
+            error_rate: (total > 0).then(|| errors as f64 / total as f64),
+        }
+    }
+}
+
+/// Service-wide validation counters behind `GET /api/v1/stats`
+///
+/// Every validation increments counters in minute, hour and day buckets, so hourly,
+/// daily and monthly reports each read a bounded number of keys. Like the validation
+/// cache, recording never fails a validation: store errors are ignored.
+#[derive(Clone, Default)]
+pub struct ValidationStats {
+    store: Option<Arc<dyn CacheStore>>,
+}
+
+/// Counters of a bucket hash; fields that aren't counts are left out
+fn parse_counters(fields: HashMap<String, String>) -> HashMap<String, u64> {
+    fields
+        .into_iter()
+        .filter_map(|(name, count)| Some((name, count.parse().ok()?)))
+        .collect()
+}
+
+impl ValidationStats {
+    pub fn new(redis_url: &str) -> Result<Self, RedisError> {
+        let store = RedisStore::new(RedisPool::new(redis_url)?);
+        Ok(Self::from_store(Arc::new(store)))
+    }
+
+    pub fn from_store(store: Arc<dyn CacheStore>) -> Self {
+        Self { store: Some(store) }
+    }
+
+    pub async fn record(&self, event: ValidationEvent<'_>, now: i64) {
+        self.record_many(&[event], now).await;
+    }
+
+    /// Counts many validations in one store request
+    pub async fn record_many(&self, events: &[ValidationEvent<'_>], now: i64) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if events.is_empty() {
+            return;
+        }
+
+        let mut batch = WriteBatch::default();
+        for event in events {
+            Self::add_to_batch(&mut batch, event, now);
+        }
+        let _ = store.write(&batch).await;
+    }
+
+    fn add_to_batch(batch: &mut WriteBatch, event: &ValidationEvent<'_>, now: i64) {
+        let verdict = if event.is_valid { "valid" } else { "invalid" };
+        let cache = if event.cache_hit {
+            "cache_hit"
+        } else {
+            "cache_miss"
+        };
+        let domain = (!event.is_valid)
+            .then(|| event.email.trim().rsplit_once('@'))
+            .flatten()
+            .map(|(_, domain)| normalize_domain(domain))
+            .filter(|domain| !domain.is_empty());
+
+        for window in StatsWindow::ALL {
+            let bucket = now / window.bucket_secs();
+            let key = window.counters_key(bucket);
+            batch
+                .hincr_by(&key, "total", 1)
+                .hincr_by(&key, verdict, 1)
+                .hincr_by(&key, cache, 1)
+                .hincr_by(&key, "latency_us", event.latency.as_micros() as u64);
+            if let Some(code) = event.error_code {
+                batch.hincr_by(&key, &format!("code:{}", code), 1);
+            }
+            batch.expire(&key, window.ttl_secs() as u64);
+
+            if let Some(domain) = &domain {
+                let domains_key = window.domains_key(bucket);
+                batch
+                    .zincr_by(&domains_key, domain, 1.0)
+                    .expire(&domains_key, window.ttl_secs() as u64);
+            }
+        }
+    }
+
+    pub async fn report(&self, window: StatsWindow, now: i64) -> Result<StatsReport, RedisError> {
+        let buckets = window.bucket_range(now);
+        let since = buckets.start() * window.bucket_secs();
+        let Some(store) = &self.store else {
+            return Ok(StatsReport::from_counters(
+                window,
+                since,
+                &HashMap::new(),
+                Vec::new(),
+            ));
+        };
+
+        let counter_keys: Vec<String> = buckets
+            .clone()
+            .map(|bucket| window.counters_key(bucket))
+            .collect();
+        let mut counters: HashMap<String, u64> = HashMap::new();
+        for bucket in store.hgetall_many(&counter_keys).await? {
+            for (name, count) in parse_counters(bucket) {
+                *counters.entry(name).or_default() += count;
+            }
+        }
+
+        let domain_keys: Vec<String> = buckets.map(|b| window.domains_key(b)).collect();
+        let top_invalid_domains = store
+            .top_members(&domain_keys, TOP_INVALID_DOMAINS)
+            .await?
+            .into_iter()
+            .map(|(domain, count)| DomainCount {
+                domain,
+                count: count as u64,
+            })
+            .collect();
+
+        Ok(StatsReport::from_counters(
+            window,
+            since,
+            &counters,
+            top_invalid_domains,
+        ))
+    }
+
+    /// Per-bucket totals and error rates over the window, oldest first
+    pub async fn timeline(
+        &self,
+        window: StatsWindow,
+        now: i64,
+    ) -> Result<Vec<TimelinePoint>, RedisError> {
+        let buckets = window.bucket_range(now);
+        let per_bucket: Vec<HashMap<String, u64>> = match &self.store {
+            Some(store) => {
+                let keys: Vec<String> = buckets
+                    .clone()
+                    .map(|bucket| window.counters_key(bucket))
+                    .collect();
+                store
+                    .hgetall_many(&keys)
+                    .await?
+                    .into_iter()
+                    .map(parse_counters)
+                    .collect()
+            }
+            None => vec![HashMap::new(); buckets.clone().count()],
+        };
+        Ok(buckets
+            .zip(per_bucket)
+            .map(|(bucket, counters)| {
+                TimelinePoint::from_counters(bucket * window.bucket_secs(), &counters)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stores::MemoryStore;
+
+    #[test]
+    fn test_window_buckets() {
+        assert_eq!(StatsWindow::parse("Month"), Some(StatsWindow::Month));
+        assert_eq!(StatsWindow::parse("week"), None);
+
+        let range = StatsWindow::Hour.bucket_range(3600 * 10 + 30);
+        assert_eq!(range.clone().count(), 60);
+        assert_eq!(*range.end(), 600);
+        assert_eq!(StatsWindow::Day.counters_key(5), "validation_stats:day:5");
+    }
+
+    #[test]
+    fn test_timeline_point_counts_critical_codes() {
+        let counters: HashMap<String, u64> = [
+            ("total", 10),
+            ("invalid", 6),
+            ("code:INVALID_SYNTAX", 3),
+            ("code:DNS_UNAVAILABLE", 2),
+            ("code:PROCESSING_ERROR", 1),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+
+        let point = TimelinePoint::from_counters(3600, &counters);
+        assert_eq!((point.at, point.total, point.invalid), (3600, 10, 6));
+        assert_eq!(point.errors, 3);
+        assert_eq!(point.error_rate, Some(0.3));
+        assert_eq!(
+            TimelinePoint::from_counters(0, &HashMap::new()).error_rate,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeline_without_store() {
+        let points = ValidationStats::default()
+            .timeline(StatsWindow::Day, 86400 * 3 + 10)
+            .await
+            .unwrap();
+        assert_eq!(points.len(), 24);
+        assert_eq!(points.last().unwrap().at, 86400 * 3);
+        assert!(points.iter().all(|point| point.total == 0));
+    }
+
+    #[test]
+    fn test_report_from_counters() {
+        let counters: HashMap<String, u64> = [
+            ("total", 4),
+            ("valid", 1),
+            ("invalid", 3),
+            ("cache_hit", 1),
+            ("cache_miss", 3),
+            ("latency_us", 8000),
+            ("code:INVALID_DOMAIN", 1),
+            ("code:INVALID_SYNTAX", 2),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+
+        let report = StatsReport::from_counters(StatsWindow::Day, 0, &counters, Vec::new());
+        assert_eq!(report.total, 4);
+        assert_eq!(report.invalid, 3);
+        assert_eq!(report.error_codes[0].code, "INVALID_SYNTAX");
+        assert_eq!(report.cache_hit_rate, Some(0.25));
+        assert_eq!(report.average_latency_ms, Some(2.0));
+
+        let empty = StatsReport::from_counters(StatsWindow::Hour, 0, &HashMap::new(), Vec::new());
+        assert_eq!(empty.cache_hit_rate, None);
+        assert!(empty.error_codes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_disabled_stats_report_zeroes() {
+        let stats = ValidationStats::default();
+        stats
+            .record(
+                ValidationEvent {
+                    email: "a@example.com",
+                    is_valid: true,
+                    error_code: None,
+                    cache_hit: false,
+                    latency: Duration::from_millis(3),
+                },
+                0,
+            )
+            .await;
+        let report = stats.report(StatsWindow::Month, 86400 * 40).await.unwrap();
+        assert_eq!(report.total, 0);
+        assert_eq!(report.since, 86400 * 11);
+    }
+
+    #[tokio::test]
+    async fn test_report_from_memory_store() {
+        let stats = ValidationStats::from_store(Arc::new(MemoryStore::default()));
+        let event = |email, error_code: Option<&'static str>| ValidationEvent {
+            email,
+            is_valid: error_code.is_none(),
+            error_code,
+            cache_hit: false,
+            latency: Duration::from_millis(2),
+        };
+        stats
+            .record_many(
+                &[
+                    event("a@example.com", None),
+                    event("b@bad.example", Some("INVALID_DOMAIN")),
+                    event("c@bad.example", Some("INVALID_DOMAIN")),
+                    event("d@other.example", Some("INVALID_DOMAIN")),
+                ],
+                3600,
+            )
+            .await;
+
+        let report = stats.report(StatsWindow::Day, 3600 * 2).await.unwrap();
+        assert_eq!((report.total, report.invalid), (4, 3));
+        assert_eq!(report.top_invalid_domains[0].domain, "bad.example");
+        assert_eq!(report.top_invalid_domains[0].count, 2);
+        assert_eq!(report.top_invalid_domains.len(), 2);
+
+        let points = stats.timeline(StatsWindow::Day, 3600 * 2).await.unwrap();
+        assert_eq!(points.iter().map(|point| point.total).sum::<u64>(), 4);
+    }
+}
@@ -0,0 +1,353 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT_MS: u64 = 2000;
+const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+const DEFAULT_COOLDOWN_SECS: u64 = 30;
+
+/// Verdict of a verification service on one address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerificationOutcome {
+    Deliverable,
+    Undeliverable,
+    /// The service could not decide, or could not be asked
+    Unknown,
+}
+
+/// A service that can judge whether an address accepts mail
+#[async_trait]
+pub trait Verifier: Send + Sync {
+    /// Name shown in verdict messages
+    fn name(&self) -> &str;
+
+    async fn verify(&self, email: &str) -> VerificationOutcome;
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// Stops calling a provider for `cooldown` after `failure_threshold` answers in a row
+/// that were timeouts or `unknown`
+///
+/// Once the cooldown has passed the provider is tried again; one more failure opens
+/// the circuit for another cooldown, a definite answer closes it.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// Whether the provider may be called now
+    pub fn allows(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state
+            .open_until
+            .is_none_or(|open_until| Instant::now() >= open_until)
+    }
+
+    pub fn record_success(&self) {
+        *self.state.lock().unwrap() = BreakerState::default();
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures += 1;
+        if state.consecutive_failures >= self.failure_threshold {
+            state.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+/// A verifier with its own timeout and circuit breaker
+pub struct FallbackProvider {
+    verifier: Arc<dyn Verifier>,
+    timeout: Duration,
+    breaker: CircuitBreaker,
+}
+
+impl FallbackProvider {
+    pub fn new(verifier: Arc<dyn Verifier>, timeout: Duration, breaker: CircuitBreaker) -> Self {
+        Self {
+            verifier,
+            timeout,
+            breaker,
+        }
+    }
+
+    /// The provider's answer; timeouts and skipped calls are `Unknown`
+    async fn verify(&self, email: &str) -> VerificationOutcome {
+        if !self.breaker.allows() {
+            return VerificationOutcome::Unknown;
+        }
+        let outcome = tokio::time::timeout(self.timeout, self.verifier.verify(email))
+            .await
+            .unwrap_or(VerificationOutcome::Unknown);
+        match outcome {
+            VerificationOutcome::Unknown => self.breaker.record_failure(),
+            _ => self.breaker.record_success(),
+        }
+        outcome
+    }
+}
+
+/// Definite verdict of a fallback provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderVerdict {
+    pub provider: String,
+    pub deliverable: bool,
+}
+
+/// External providers consulted, in order, when local checks can't reach a verdict
+///
+/// Configured from the environment:
+/// - `VERIFICATION_PROVIDERS`: comma-separated provider names; none when unset
+/// - `VERIFICATION_PROVIDER_<NAME>_URL`: endpoint queried with `?email=`
+/// - `VERIFICATION_PROVIDER_<NAME>_API_KEY`: sent as a bearer token (optional)
+/// - `VERIFICATION_PROVIDER_<NAME>_TIMEOUT_MS` (default 2000)
+/// - `VERIFICATION_PROVIDER_<NAME>_FAILURE_THRESHOLD` (default 5) and
+///   `VERIFICATION_PROVIDER_<NAME>_COOLDOWN_SECS` (default 30) for the circuit breaker
+#[derive(Clone, Default)]
+pub struct VerifierChain {
+    providers: Arc<Vec<FallbackProvider>>,
+}
+
+impl VerifierChain {
+    pub fn new(providers: Vec<FallbackProvider>) -> Self {
+        Self {
+            providers: Arc::new(providers),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let names = std::env::var("VERIFICATION_PROVIDERS").unwrap_or_default();
+        let providers = names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .filter_map(|name| {
+                let setting = |key: &str| {
+                    std::env::var(format!(
+                        "VERIFICATION_PROVIDER_{}_{}",
+                        name.to_ascii_uppercase(),
+                        key
+                    ))
+                    .ok()
+                    .filter(|v| !v.trim().is_empty())
+                };
+                let number = |key: &str, default: u64| {
+                    setting(key)
+                        .and_then(|v| v.trim().parse::<u64>().ok())
+                        .unwrap_or(default)
+                };
+
+                let Some(url) = setting("URL") else {
+                    eprintln!("verification provider {} has no URL; skipped", name);
+                    return None;
+                };
+                let verifier = HttpVerifier::new(name, &url, setting("API_KEY"));
+                Some(FallbackProvider::new(
+                    Arc::new(verifier),
+                    Duration::from_millis(number("TIMEOUT_MS", DEFAULT_TIMEOUT_MS)),
+                    CircuitBreaker::new(
+                        number("FAILURE_THRESHOLD", DEFAULT_FAILURE_THRESHOLD as u64) as u32,
+                        Duration::from_secs(number("COOLDOWN_SECS", DEFAULT_COOLDOWN_SECS)),
+                    ),
+                ))
+            })
+            .collect();
+        Self::new(providers)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// First definite verdict; providers answering `unknown` pass the address on to the next
+    pub async fn verify(&self, email: &str) -> Option<ProviderVerdict> {
+        for provider in self.providers.iter() {
+            let deliverable = match provider.verify(email).await {
+                VerificationOutcome::Deliverable => true,
+                VerificationOutcome::Undeliverable => false,
+                VerificationOutcome::Unknown => continue,
+            };
+            return Some(ProviderVerdict {
+                provider: provider.verifier.name().to_string(),
+                deliverable,
+            });
+        }
+        None
+    }
+}
+
+/// Verification API answering `GET {url}?email=` with a JSON body whose `result` (or
+/// `status`) is `deliverable`/`valid` or `undeliverable`/`invalid`
+///
+/// Any other answer, and any request failure, is `Unknown`.
+pub struct HttpVerifier {
+    name: String,
+    url: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl HttpVerifier {
+    pub fn new(name: &str, url: &str, api_key: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+/// Outcome reported in a provider's response body
+pub fn parse_outcome(body: &serde_json::Value) -> VerificationOutcome {
+    let result = body
+        .get("result")
+        .or_else(|| body.get("status"))
+        .and_then(|v| v.as_str())
+        .map(str::to_ascii_lowercase);
+    match result.as_deref() {
+        Some("deliverable" | "valid") => VerificationOutcome::Deliverable,
+        Some("undeliverable" | "invalid") => VerificationOutcome::Undeliverable,
+        _ => VerificationOutcome::Unknown,
+    }
+}
+
+#[async_trait]
+impl Verifier for HttpVerifier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn verify(&self, email: &str) -> VerificationOutcome {
+        let mut request = self.client.get(&self.url).query(&[("email", email)]);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = match request.send().await {
+            Ok(response) if response.status().is_success() => response,
+            _ => return VerificationOutcome::Unknown,
+        };
+        match response.json::<serde_json::Value>().await {
+            Ok(body) => parse_outcome(&body),
+            Err(_) => VerificationOutcome::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Fixed {
+        name: &'static str,
+        outcome: VerificationOutcome,
+        delay: Duration,
+        calls: AtomicUsize,
+    }
+
+    impl Fixed {
+        fn new(name: &'static str, outcome: VerificationOutcome, delay: Duration) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                outcome,
+                delay,
+                calls: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Verifier for Fixed {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn verify(&self, _email: &str) -> VerificationOutcome {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            self.outcome
+        }
+    }
+
+    fn provider(verifier: Arc<Fixed>, threshold: u32) -> FallbackProvider {
+        FallbackProvider::new(
+            verifier,
+            Duration::from_millis(50),
+            CircuitBreaker::new(threshold, Duration::from_secs(60)),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_chain_skips_slow_and_undecided_providers() {
+        let slow = Fixed::new(
+            "slow",
+            VerificationOutcome::Deliverable,
+            Duration::from_secs(5),
+        );
+        let undecided = Fixed::new("undecided", VerificationOutcome::Unknown, Duration::ZERO);
+        let decisive = Fixed::new(
+            "decisive",
+            VerificationOutcome::Undeliverable,
+            Duration::ZERO,
+        );
+        let chain = VerifierChain::new(vec![
+            provider(slow.clone(), 2),
+            provider(undecided.clone(), 10),
+            provider(decisive.clone(), 10),
+        ]);
+
+        let expected = Some(ProviderVerdict {
+            provider: "decisive".to_string(),
+            deliverable: false,
+        });
+        assert_eq!(chain.verify("user@example.com").await, expected);
+        assert_eq!(chain.verify("user@example.com").await, expected);
+        // Two timeouts opened the slow provider's circuit
+        assert_eq!(chain.verify("user@example.com").await, expected);
+        assert_eq!(slow.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(undecided.calls.load(Ordering::SeqCst), 3);
+
+        assert_eq!(
+            VerifierChain::default().verify("user@example.com").await,
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_outcome() {
+        assert_eq!(
+            parse_outcome(&json!({ "result": "deliverable" })),
+            VerificationOutcome::Deliverable
+        );
+        assert_eq!(
+            parse_outcome(&json!({ "status": "Invalid" })),
+            VerificationOutcome::Undeliverable
+        );
+        assert_eq!(
+            parse_outcome(&json!({ "result": "catch-all" })),
+            VerificationOutcome::Unknown
+        );
+        assert_eq!(parse_outcome(&json!([])), VerificationOutcome::Unknown);
+    }
+}
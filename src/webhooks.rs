@@ -0,0 +1,211 @@
+use crate::password;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use mongodb::options::ReturnDocument;
+use mongodb::{Client, Collection, bson::doc};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use utoipa::ToSchema;
+
+/// Header carrying the signature of a webhook body, as `t=<timestamp>,v1=<hex HMAC>`
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+
+/// How far a signature's timestamp may be from the receiver's clock, in seconds
+pub const DEFAULT_TOLERANCE_SECS: i64 = 300;
+
+const WEBHOOK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// The secret an account's webhooks are signed with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct WebhookSecret {
+    /// Account the secret belongs to, lowercased
+    pub tenant_id: String,
+    pub secret: String,
+    /// Unix timestamp of the secret's creation or last rotation
+    pub created_at: i64,
+}
+
+impl WebhookSecret {
+    pub fn generate(tenant_id: &str) -> Self {
+        Self {
+            tenant_id: tenant_id.to_lowercase(),
+            secret: format!("whsec_{}", password::random_hex(32)),
+            created_at: Utc::now().timestamp(),
+        }
+    }
+}
+
+fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// `X-Signature` value of `body` sent at `timestamp`
+///
+/// The HMAC-SHA256 covers `<timestamp>.<body>`, so a captured delivery can't be
+/// replayed later with a fresh timestamp.
+pub fn signature_header(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    format!("t={},v1={}", timestamp, signature(secret, timestamp, body))
+}
+
+/// Checks an `X-Signature` header against the raw body a receiver got
+///
+/// Receivers in any language do the same:
+/// 1. Split the header on `,` into `t=<timestamp>` and `v1=<signature>`.
+/// 2. Reject the delivery when the timestamp is more than `tolerance_secs` away
+///    from the current time; [`DEFAULT_TOLERANCE_SECS`] suits most receivers.
+/// 3. Compute the hex HMAC-SHA256 of `<timestamp>.<raw body>` keyed with the
+///    account's webhook secret, and compare it to `v1` in constant time.
+///
+/// The body must be the bytes as received; re-serialized JSON may differ.
+pub fn verify_signature(
+    secret: &str,
+    header: &str,
+    body: &[u8],
+    now: i64,
+    tolerance_secs: i64,
+) -> Result<(), String> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.push(value),
+            _ => {}
+        }
+    }
+    let timestamp = timestamp.ok_or("The signature has no timestamp")?;
+    if (now - timestamp).abs() > tolerance_secs {
+        return Err("The signature timestamp is outside the tolerance".to_string());
+    }
+
+    let expected = signature(secret, timestamp, body);
+    let matches = signatures
+        .iter()
+        .any(|given| bool::from(given.as_bytes().ct_eq(expected.as_bytes())));
+    if matches {
+        Ok(())
+    } else {
+        Err("The signature does not match".to_string())
+    }
+}
+
+/// Reads `DB_NAME_PRODUCTION` (default `email_sanitizer`) and `DB_WEBHOOK_SECRETS_COLLECTION`
+/// (default `webhook_secrets`)
+pub fn secrets_collection(mongo_client: &Client) -> Collection<WebhookSecret> {
+    let db_name =
+        std::env::var("DB_NAME_PRODUCTION").unwrap_or_else(|_| "email_sanitizer".to_string());
+    let collection_name = std::env::var("DB_WEBHOOK_SECRETS_COLLECTION")
+        .unwrap_or_else(|_| "webhook_secrets".to_string());
+    mongo_client.database(&db_name).collection(&collection_name)
+}
+
+/// The account's secret, created on first use
+pub async fn secret_for(mongo_client: &Client, tenant_id: &str) -> Result<WebhookSecret, String> {
+    let generated = WebhookSecret::generate(tenant_id);
+    secrets_collection(mongo_client)
+        .find_one_and_update(
+            doc! { "tenant_id": &generated.tenant_id },
+            doc! { "$setOnInsert": {
+                "secret": &generated.secret,
+                "created_at": generated.created_at,
+            } },
+        )
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .await
+        .map_err(|_| "Database error".to_string())?
+        .ok_or_else(|| "Database error".to_string())
+}
+
+/// Replaces the account's secret; deliveries are signed with the new one from now on
+pub async fn rotate_secret(
+    mongo_client: &Client,
+    tenant_id: &str,
+) -> Result<WebhookSecret, String> {
+    let secret = WebhookSecret::generate(tenant_id);
+    secrets_collection(mongo_client)
+        .replace_one(doc! { "tenant_id": &secret.tenant_id }, &secret)
+        .upsert(true)
+        .await
+        .map_err(|_| "Database error".to_string())?;
+    Ok(secret)
+}
+
+/// Posts `payload` to `url`, signed with `secret` when given; fails unless the
+/// receiver answers with a 2xx
+pub async fn deliver(
+    url: &str,
+    payload: &serde_json::Value,
+    secret: Option<&str>,
+) -> Result<(), String> {
+    let body = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+    let mut request = reqwest::Client::new()
+        .post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .header(reqwest::header::CONTENT_TYPE, "application/json");
+    if let Some(secret) = secret {
+        let header = signature_header(secret, Utc::now().timestamp(), &body);
+        request = request.header(SIGNATURE_HEADER, header);
+    }
+    request
+        .body(body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Posts `payload` to `url` signed with the account's secret
+///
+/// Nothing is sent when the secret can't be read: an unsigned delivery would be
+/// rejected by receivers that verify, and accepted by those that should.
+pub async fn deliver_signed(
+    mongo_client: &Client,
+    tenant_id: &str,
+    url: &str,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let secret = secret_for(mongo_client, tenant_id).await?;
+    deliver(url, payload, Some(&secret.secret)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_round_trip() {
+        let body = br#"{"event":"validation.completed"}"#;
+        let header = signature_header("whsec_test", 1_700_000_000, body);
+        assert!(header.starts_with("t=1700000000,v1="));
+
+        assert!(verify_signature("whsec_test", &header, body, 1_700_000_100, 300).is_ok());
+        // Tampered body, wrong secret, replayed after the tolerance, no timestamp
+        assert!(verify_signature("whsec_test", &header, b"{}", 1_700_000_100, 300).is_err());
+        assert!(verify_signature("whsec_other", &header, body, 1_700_000_100, 300).is_err());
+        assert!(verify_signature("whsec_test", &header, body, 1_700_000_301, 300).is_err());
+        let unstamped = header.split_once(',').unwrap().1;
+        assert!(verify_signature("whsec_test", unstamped, body, 1_700_000_000, 300).is_err());
+    }
+
+    #[test]
+    fn test_generated_secrets_differ() {
+        let first = WebhookSecret::generate("Owner@Example.com");
+        let second = WebhookSecret::generate("owner@example.com");
+        assert_eq!(first.tenant_id, "owner@example.com");
+        assert!(first.secret.starts_with("whsec_"));
+        assert_eq!(first.secret.len(), "whsec_".len() + 64);
+        assert_ne!(first.secret, second.secret);
+    }
+}
@@ -1,73 +1,388 @@
-use crate::job_queue::{BulkValidationJob, JobQueue, JobStatus};
-use crate::routes::email::{RedisCache, validate_single_email};
+use crate::bulk::DedupedBatch;
+use crate::handlers::validation::custom_lists::AccountLists;
+use crate::job_queue::{BulkValidationJob, DEFAULT_TENANT, JobQueue, JobStatus, RESULT_CHUNK_SIZE};
+use crate::routes::email::BulkEmailValidationResult;
+use crate::validation_service::{ValidationBackends, ValidationService};
+use crate::webhooks;
 use futures::future::join_all;
+use mongodb::Client as MongoClient;
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// What the binary runs, from `--mode api` (the default) or `--mode worker`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunMode {
+    /// HTTP, GraphQL and gRPC servers
+    Api,
+    /// Only the bulk validation worker, scaled separately from the API
+    Worker,
+}
+
+impl RunMode {
+    /// Parses `--mode <mode>` or `--mode=<mode>` from the command line arguments
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter();
+        let mut mode = None;
+        while let Some(arg) = args.next() {
+            if arg == "--mode" {
+                mode = args.next();
+            } else if let Some(value) = arg.strip_prefix("--mode=") {
+                mode = Some(value.to_string());
+            }
+        }
+        match mode.as_deref() {
+            None | Some("api") => Ok(RunMode::Api),
+            Some("worker") => Ok(RunMode::Worker),
+            Some(other) => Err(format!(
+                "Unknown mode '{}', expected 'api' or 'worker'",
+                other
+            )),
+        }
+    }
+}
+
+/// Size and identity of a process's pool of job consumers
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkerConfig {
+    /// Jobs processed at a time, each by its own consumer
+    pub concurrency: usize,
+    /// Prefix of the consumer names; must be unique per process
+    pub worker_id: String,
+}
+
+impl Default for WorkerConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 1,
+            worker_id: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+}
+
+impl WorkerConfig {
+    /// Read from `WORKER_CONCURRENCY` (default 4) and `WORKER_ID` (default: random)
+    pub fn from_env() -> Self {
+        let concurrency = std::env::var("WORKER_CONCURRENCY")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|concurrency| *concurrency > 0)
+            .unwrap_or(4);
+        let worker_id = std::env::var("WORKER_ID")
+            .ok()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        Self {
+            concurrency,
+            worker_id,
+        }
+    }
+
+    /// Consumer names, one per concurrent job
+    pub fn consumers(&self) -> Vec<String> {
+        (1..=self.concurrency)
+            .map(|n| format!("{}-{}", self.worker_id, n))
+            .collect()
+    }
+}
 
 pub struct ValidationWorker {
     job_queue: JobQueue,
-    redis_cache: RedisCache,
+    backends: ValidationBackends,
+    config: WorkerConfig,
+    /// Where the accounts' webhook secrets are kept; without one results are
+    /// posted unsigned
+    secrets: Option<MongoClient>,
 }
 
 impl ValidationWorker {
-    pub fn new(job_queue: JobQueue, redis_cache: RedisCache) -> Self {
+    pub fn new(job_queue: JobQueue, backends: ValidationBackends) -> Self {
         Self {
             job_queue,
-            redis_cache,
+            backends,
+            config: WorkerConfig::default(),
+            secrets: None,
         }
     }
 
+    pub fn with_config(mut self, config: WorkerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Signs webhook deliveries with the secrets stored in MongoDB
+    pub fn with_webhook_secrets(mut self, mongo_client: MongoClient) -> Self {
+        self.secrets = Some(mongo_client);
+        self
+    }
+
+    /// Runs one job consumer per unit of concurrency, forever
     pub async fn start(&self) {
-        let job_queue = self.job_queue.clone();
-        let redis_cache = self.redis_cache.clone();
-
-        job_queue
-            .clone()
-            .process_jobs(move |job| {
-                let redis_cache = redis_cache.clone();
-                let job_queue = job_queue.clone();
-                async move {
-                    Self::process_bulk_validation(job, redis_cache, job_queue).await;
-                }
-            })
-            .await;
+        let (_keep_running, shutdown) = watch::channel(false);
+        self.run_until(shutdown).await;
+    }
+
+    /// Runs one job consumer per unit of concurrency until `shutdown` turns true; each
+    /// finishes its running job before returning
+    pub async fn run_until(&self, shutdown: watch::Receiver<bool>) {
+        let consumers = self.config.consumers().into_iter().map(|consumer| {
+            let job_queue = self.job_queue.clone();
+            let backends = self.backends.clone();
+            let secrets = self.secrets.clone();
+            let shutdown = shutdown.clone();
+            async move {
+                let queue = job_queue.clone();
+                queue
+                    .process_jobs(&consumer, shutdown, move |job| {
+                        let backends = backends.clone();
+                        let job_queue = job_queue.clone();
+                        let secrets = secrets.clone();
+                        async move {
+                            Self::process_bulk_validation(job, backends, job_queue, secrets).await;
+                        }
+                    })
+                    .await;
+            }
+        });
+        join_all(consumers).await;
     }
 
     async fn process_bulk_validation(
         job: BulkValidationJob,
-        redis_cache: RedisCache,
+        backends: ValidationBackends,
         job_queue: JobQueue,
+        secrets: Option<MongoClient>,
     ) {
-        let validation_futures =
-            job.emails
-                .iter()
-                .map(|email| {
-                    let email_clone = email.clone();
-                    let redis_cache = redis_cache.clone();
-                    let check_role_based = job.check_role_based;
-                    async move {
-                        validate_single_email(&email_clone, check_role_based, &redis_cache).await
-                    }
-                })
-                .collect::<Vec<_>>();
-
-        let _results = join_all(validation_futures).await;
-
-        // Mark job as completed
-        let _ = job_queue
-            .update_job_status(&job.id, JobStatus::Completed)
+        // Each unique address is validated once per set of checks, then mapped back to
+        // every position; each domain is resolved once
+        let (batch, checks) = if job.dedupe_tagged {
+            DedupedBatch::merging_tags(&job.emails, &job.checks())
+        } else {
+            DedupedBatch::with_keys(&job.emails, &job.checks())
+        };
+        // Jobs from before per-tenant queues have no account to index verdicts for
+        let mut service = ValidationService::new(&backends);
+        if job.tenant_id != DEFAULT_TENANT {
+            service = service.for_tenant(&job.tenant_id);
+        }
+        let validations = service
+            .validate_batch(&batch.unique, &checks, &AccountLists::default())
             .await;
+        let results = batch.fan_out(&validations);
+        let rows: Vec<BulkEmailValidationResult> = job
+            .emails
+            .iter()
+            .cloned()
+            .zip(results)
+            .map(|(email, mut validation)| {
+                if validation.has_tag.is_some() {
+                    validation.set_tag(&email);
+                }
+                BulkEmailValidationResult { email, validation }
+            })
+            .collect();
+
+        // Store results in chunks for streaming downloads, written in compressed,
+        // pipelined batches; a job whose results could not be stored is reported as
+        // failed rather than completed
+        let chunks: Result<Vec<String>, _> = rows
+            .chunks(RESULT_CHUNK_SIZE)
+            .map(serde_json::to_string)
+            .collect();
+        let stored = match chunks {
+            Ok(chunks) => job_queue
+                .append_result_chunks(&job.id, &chunks)
+                .await
+                .is_ok(),
+            Err(_) => false,
+        };
+        let status = if stored {
+            JobStatus::Completed
+        } else {
+            JobStatus::Failed
+        };
+
+        let _ = job_queue.update_job_status(&job.id, status).await;
+
+        if let Some(url) = &job.webhook_url {
+            notify(secrets.as_ref(), &job, url, &rows).await;
+        }
+    }
+}
+
+/// Body posted to an async validation's webhook: the address and its full result
+pub fn completion_payload(job_id: &str, rows: &[BulkEmailValidationResult]) -> serde_json::Value {
+    let row = rows.first();
+    json!({
+        "event": "validation.completed",
+        "job_id": job_id,
+        "email": row.map(|row| &row.email),
+        "result": row.map(|row| &row.validation),
+    })
+}
+
+/// Posts the result to the job's webhook, signed with the submitter's secret;
+/// failures are only logged, the result stays downloadable from the job
+async fn notify(
+    secrets: Option<&MongoClient>,
+    job: &BulkValidationJob,
+    url: &str,
+    rows: &[BulkEmailValidationResult],
+) {
+    let payload = completion_payload(&job.id, rows);
+    let delivered = match secrets {
+        Some(mongo_client) => {
+            webhooks::deliver_signed(mongo_client, &job.tenant_id, url, &payload).await
+        }
+        None => webhooks::deliver(url, &payload, None).await,
+    };
+    if let Err(e) = delivered {
+        eprintln!("Validation webhook for job {} failed: {}", job.id, e);
     }
 }
 
+/// Background task running a [`ValidationWorker`], inside the API process or as the
+/// whole of a `--mode worker` process
+pub struct WorkerPool {
+    shutdown: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl WorkerPool {
+    pub fn spawn(worker: ValidationWorker) -> Self {
+        let (shutdown, receiver) = watch::channel(false);
+        let task = tokio::spawn(async move { worker.run_until(receiver).await });
+        Self { shutdown, task }
+    }
+
+    /// Stops claiming jobs and waits up to `grace` for the running ones
+    ///
+    /// Jobs still running after `grace` are abandoned; other workers pick them up again
+    /// once the abandoned consumers' heartbeats expire.
+    pub async fn shutdown(self, grace: Duration) {
+        let _ = self.shutdown.send(true);
+        let abort = self.task.abort_handle();
+        if tokio::time::timeout(grace, self.task).await.is_err() {
+            eprintln!(
+                "Validation worker did not stop within {}s; its jobs will be re-queued",
+                grace.as_secs()
+            );
+            abort.abort();
+        }
+    }
+}
+
+/// Whether the API process runs its own worker (`EMBEDDED_WORKER`, default true); turn
+/// it off when dedicated `--mode worker` processes handle the queue
+pub fn embedded_worker_enabled() -> bool {
+    std::env::var("EMBEDDED_WORKER")
+        .map(|v| !matches!(v.trim().to_ascii_lowercase().as_str(), "false" | "0" | "no"))
+        .unwrap_or(true)
+}
+
+/// How long shutdown waits for running jobs (`WORKER_SHUTDOWN_GRACE_SECS`, default 30)
+pub fn shutdown_grace() -> Duration {
+    let secs = std::env::var("WORKER_SHUTDOWN_GRACE_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(30);
+    Duration::from_secs(secs)
+}
+
+/// Resolves on Ctrl-C or, on Unix, SIGTERM
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+            return;
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::routes::email::EmailValidationResponse;
+    use crate::stores::MemoryStore;
+    use std::sync::Arc;
+
+    fn memory_backends() -> ValidationBackends {
+        ValidationBackends::from_store(Arc::new(MemoryStore::default()))
+    }
+
+    #[test]
+    fn test_run_mode_from_args() {
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(RunMode::from_args(args(&["bin"])), Ok(RunMode::Api));
+        assert_eq!(
+            RunMode::from_args(args(&["bin", "--mode", "worker"])),
+            Ok(RunMode::Worker)
+        );
+        assert_eq!(
+            RunMode::from_args(args(&["bin", "--mode=api"])),
+            Ok(RunMode::Api)
+        );
+        assert!(RunMode::from_args(args(&["bin", "--mode", "cron"])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_worker_pool_shutdown() {
+        if let Ok(job_queue) = JobQueue::new("redis://127.0.0.1:6379") {
+            let worker = ValidationWorker::new(job_queue, memory_backends());
+            let pool = WorkerPool::spawn(worker);
+            // Without Redis the consumers are idle, so they stop well within the grace period
+            let stopped = tokio::time::timeout(
+                Duration::from_secs(10),
+                pool.shutdown(Duration::from_secs(8)),
+            )
+            .await;
+            assert!(stopped.is_ok());
+        }
+    }
+
+    #[test]
+    fn test_completion_payload() {
+        let rows = vec![BulkEmailValidationResult {
+            email: "user@example.com".to_string(),
+            validation: EmailValidationResponse {
+                is_valid: true,
+                domain: None,
+                has_tag: None,
+                base_address: None,
+                status: Some("VALID".to_string()),
+                error: None,
+            },
+        }];
+        let payload = completion_payload("job-1", &rows);
+        assert_eq!(payload["event"], "validation.completed");
+        assert_eq!(payload["job_id"], "job-1");
+        assert_eq!(payload["email"], "user@example.com");
+        assert_eq!(payload["result"]["is_valid"], true);
+    }
+
+    #[test]
+    fn test_worker_consumers() {
+        let config = WorkerConfig {
+            concurrency: 3,
+            worker_id: "host-a".to_string(),
+        };
+        assert_eq!(config.consumers(), vec!["host-a-1", "host-a-2", "host-a-3"]);
+    }
 
     #[tokio::test]
     async fn test_validation_worker_new() {
-        let redis_cache = RedisCache::test_dummy();
+        let backends = memory_backends();
         // Just test that we can create a JobQueue - don't worry about Redis connection in tests
         if let Ok(job_queue) = JobQueue::new("redis://127.0.0.1:6379") {
-            let _worker = ValidationWorker::new(job_queue, redis_cache);
+            let _worker = ValidationWorker::new(job_queue, backends);
             assert!(true);
         } else {
             // If Redis is not available, just pass the test
@@ -77,9 +392,9 @@ mod tests {
 
     #[tokio::test]
     async fn test_validation_worker_start() {
-        let redis_cache = RedisCache::test_dummy();
+        let backends = memory_backends();
         if let Ok(job_queue) = JobQueue::new("redis://127.0.0.1:6379") {
-            let worker = ValidationWorker::new(job_queue, redis_cache);
+            let worker = ValidationWorker::new(job_queue, backends);
 
             // Test that start method can be called without panicking
             let result =
@@ -94,7 +409,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_process_bulk_validation() {
-        let redis_cache = RedisCache::test_dummy();
+        let backends = memory_backends();
         if let Ok(job_queue) = JobQueue::new("redis://127.0.0.1:6379") {
             let job = BulkValidationJob {
                 id: "test-job".to_string(),
@@ -102,10 +417,17 @@ mod tests {
                 check_role_based: false,
                 status: JobStatus::Pending,
                 created_at: 1234567890,
+                tenant_id: crate::job_queue::DEFAULT_TENANT.to_string(),
+                updated_at: None,
+                email_count: 1,
+                spilled: false,
+                item_options: Default::default(),
+                webhook_url: None,
+                dedupe_tagged: false,
             };
 
             // Test the static method directly
-            ValidationWorker::process_bulk_validation(job, redis_cache, job_queue).await;
+            ValidationWorker::process_bulk_validation(job, backends, job_queue, None).await;
             // If we reach here without panicking, the test passes
             assert!(true);
         } else {